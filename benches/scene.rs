@@ -0,0 +1,152 @@
+//! Scene update and draw submission benchmarks at 1k, 10k and 100k nodes
+//!
+//! ```bash
+//! cargo bench --bench scene
+//! cargo bench --bench scene -- update_transforms/10000
+//! ```
+//!
+//! Draw submission needs a GPU adapter (a software one is fine) and is
+//! skipped when none is available.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use diomanim::animation::effects;
+use diomanim::animation::property::AnimationInstance;
+use diomanim::core::{Color, TimeValue};
+use diomanim::pipeline::FRAME_TEXTURE;
+use diomanim::render::graph::{DrawLayer, ScenePass};
+use diomanim::render::{RenderGraph, RendererDescriptor, ShapeRenderer};
+use diomanim::scene::SceneGraph;
+use std::time::{Duration, Instant};
+
+const NODE_COUNTS: [usize; 3] = [1_000, 10_000, 100_000];
+
+/// Children per group node, so transform updates walk a real hierarchy
+const GROUP_SIZE: usize = 10;
+
+/// Circles and rectangles on a grid, in groups of [`GROUP_SIZE`] under a
+/// rectangle parent; every node spins when `animated`
+fn build_scene(nodes: usize, animated: bool) -> SceneGraph {
+    let mut scene = SceneGraph::new();
+    let columns = (nodes as f32).sqrt().ceil() as usize;
+    let spacing = 2.0 / columns as f32;
+    let mut parent = None;
+
+    for index in 0..nodes {
+        let x = (index % columns) as f32 * spacing - 1.0;
+        let y = (index / columns) as f32 * spacing - 1.0;
+        let builder = if index % GROUP_SIZE == 0 {
+            scene.add_rectangle(format!("group{index}"), spacing, spacing, Color::BLUE)
+        } else {
+            scene.add_circle(format!("node{index}"), spacing * 0.4, Color::RED)
+        };
+        let builder = match parent {
+            Some(parent_id) => builder.parent_to(parent_id),
+            None => builder,
+        };
+        let id = builder.at(x, y, 0.0).build();
+        if index % GROUP_SIZE == 0 {
+            parent = Some(id);
+        }
+
+        if animated {
+            let mut clip = effects::spin(1.0, 2.0);
+            clip.loop_animation = true;
+            if let Some(node) = scene.get_node_mut(id) {
+                node.add_animation(AnimationInstance::new(clip, TimeValue::new(0.0)));
+            }
+        }
+    }
+
+    scene.update_transforms();
+    scene
+}
+
+fn bench_update_transforms(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update_transforms");
+    for nodes in NODE_COUNTS {
+        let mut scene = build_scene(nodes, false);
+        group.throughput(Throughput::Elements(nodes as u64));
+        group.bench_function(BenchmarkId::from_parameter(nodes), |b| {
+            b.iter(|| scene.update_transforms());
+        });
+    }
+    group.finish();
+}
+
+fn bench_update_animations(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update_animations");
+    let delta = TimeValue::new(1.0 / 60.0);
+    for nodes in NODE_COUNTS {
+        let mut scene = build_scene(nodes, true);
+        group.throughput(Throughput::Elements(nodes as u64));
+        group.bench_function(BenchmarkId::from_parameter(nodes), |b| {
+            b.iter(|| scene.update_animations(delta));
+        });
+    }
+    group.finish();
+}
+
+fn bench_visible_renderables(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_visible_renderables");
+    for nodes in NODE_COUNTS {
+        let scene = build_scene(nodes, false);
+        group.throughput(Throughput::Elements(nodes as u64));
+        group.bench_function(BenchmarkId::from_parameter(nodes), |b| {
+            b.iter(|| scene.get_visible_renderables());
+        });
+    }
+    group.finish();
+}
+
+/// Recording and submitting one frame's draw calls, excluding the wait for
+/// the GPU to finish
+fn bench_draw_submission(c: &mut Criterion) {
+    let renderer = pollster::block_on(ShapeRenderer::with_descriptor(&RendererDescriptor::new(
+        256, 256,
+    )));
+    let mut renderer = match renderer {
+        Ok(renderer) => renderer,
+        Err(err) => {
+            eprintln!("skipping draw_submission: {err}");
+            return;
+        }
+    };
+
+    let mut group = c.benchmark_group("draw_submission");
+    group.sample_size(10);
+    for nodes in NODE_COUNTS {
+        let scene = build_scene(nodes, false);
+        let mut graph = RenderGraph::new(256, 256);
+        graph.add_texture(FRAME_TEXTURE, wgpu::TextureFormat::Rgba8Unorm);
+        graph.add_pass(ScenePass::new("scene", FRAME_TEXTURE, DrawLayer::All));
+
+        group.throughput(Throughput::Elements(nodes as u64));
+        group.bench_function(BenchmarkId::from_parameter(nodes), |b| {
+            b.iter_custom(|iterations| {
+                let mut total = Duration::ZERO;
+                for _ in 0..iterations {
+                    let start = Instant::now();
+                    graph
+                        .execute(&mut renderer, &scene, None)
+                        .expect("frame submission failed");
+                    total += start.elapsed();
+                    renderer
+                        .get_device()
+                        .poll(wgpu::PollType::wait_indefinitely())
+                        .expect("GPU wait failed");
+                }
+                total
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_update_transforms,
+    bench_update_animations,
+    bench_visible_renderables,
+    bench_draw_submission
+);
+criterion_main!(benches);
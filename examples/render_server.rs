@@ -0,0 +1,22 @@
+//! Render Server
+//!
+//! Serves scene-script renders over a JSON-RPC WebSocket until killed.
+//!
+//! ```text
+//! cargo run --example render_server --features server -- 127.0.0.1:9460 output/server
+//! ```
+
+use diomanim::server::RenderServer;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let addr = args.next().unwrap_or_else(|| "127.0.0.1:9460".to_string());
+    let output_dir = args.next().unwrap_or_else(|| "output/server".to_string());
+
+    let server = RenderServer::bind(addr.as_str(), &output_dir)?;
+    println!(
+        "Render server listening on ws://{} (output in {output_dir})",
+        server.local_addr()?
+    );
+    server.run()
+}
@@ -0,0 +1,360 @@
+//! Animations passed to `Scene.play`, named like Manim's
+//!
+//! Each animation starts from the mobject's state when it is played and
+//! leaves the mobject in its end state, so consecutive `play` calls chain.
+//! Like Manim's default `smooth` rate function, they ease in and out.
+
+use crate::mobjects::Mobject;
+use crate::values::point;
+use diomanim::animation::property::{AnimationClip, AnimationTrack, InterpolationType, Keyframe};
+use diomanim::core::{TimeValue, Vector3 as CoreVector3};
+use diomanim::scene::SceneNode;
+use pyo3::prelude::*;
+
+#[derive(Clone, Copy)]
+pub(crate) enum Kind {
+    FadeIn,
+    FadeOut,
+    Create,
+    Uncreate,
+    GrowFromCenter,
+    ShrinkToCenter,
+    MoveTo(CoreVector3),
+    Shift(CoreVector3),
+    Rotate(f32),
+    ScaleInPlace(f32),
+}
+
+/// Base class of animations
+#[pyclass(subclass, module = "diomanim")]
+pub struct Animation {
+    pub(crate) mobject: Py<Mobject>,
+    pub(crate) kind: Kind,
+    /// Seconds, overriding the `run_time` given to `play`
+    pub(crate) run_time: Option<f32>,
+}
+
+#[pymethods]
+impl Animation {
+    #[getter]
+    fn mobject(&self, py: Python<'_>) -> Py<Mobject> {
+        self.mobject.clone_ref(py)
+    }
+
+    #[getter]
+    fn run_time(&self) -> Option<f32> {
+        self.run_time
+    }
+}
+
+impl Animation {
+    fn new(mobject: Bound<'_, Mobject>, kind: Kind, run_time: Option<f32>) -> Self {
+        Self {
+            mobject: mobject.unbind(),
+            kind,
+            run_time,
+        }
+    }
+
+    /// Whether the animation brings its mobject on screen, so it should be
+    /// hidden until the animation starts
+    pub(crate) fn is_entrance(&self) -> bool {
+        matches!(
+            self.kind,
+            Kind::FadeIn | Kind::Create | Kind::GrowFromCenter
+        )
+    }
+
+    /// Put `node` in the state the animation starts from, for frames before
+    /// it starts
+    pub(crate) fn hide(&self, node: &mut SceneNode) {
+        if matches!(self.kind, Kind::FadeIn | Kind::Create) {
+            node.opacity = 0.0;
+        }
+        if matches!(self.kind, Kind::Create | Kind::GrowFromCenter) {
+            node._local_transform.scale = CoreVector3::zero();
+        }
+    }
+
+    /// The clip playing this animation over `duration` seconds, moving
+    /// `mobject` to its end state
+    pub(crate) fn clip(&self, mobject: &mut Mobject, duration: f32) -> AnimationClip {
+        let scale = |s: f32| CoreVector3::new(s, s, s);
+        let opacity = |o: f32| CoreVector3::new(o, 0.0, 0.0);
+        let mut clip = AnimationClip::new(self.name().to_string());
+        // Bringing back a mobject that was faded or shrunk away
+        let mut unshrunk = false;
+        if self.is_entrance() {
+            if mobject.opacity <= 0.0 {
+                mobject.opacity = 1.0;
+            }
+            if mobject.scale <= 0.0 {
+                mobject.scale = 1.0;
+                unshrunk = true;
+            }
+        }
+        match self.kind {
+            Kind::FadeIn => {
+                clip.add_track(tween(
+                    "opacity",
+                    opacity(0.0),
+                    opacity(mobject.opacity),
+                    duration,
+                ));
+                if unshrunk {
+                    let full = scale(mobject.scale);
+                    clip.add_track(tween("scale", full, full, duration));
+                }
+            }
+            Kind::FadeOut => {
+                clip.add_track(tween(
+                    "opacity",
+                    opacity(mobject.opacity),
+                    opacity(0.0),
+                    duration,
+                ));
+                mobject.opacity = 0.0;
+            }
+            Kind::Create => {
+                clip.add_track(tween(
+                    "opacity",
+                    opacity(0.0),
+                    opacity(mobject.opacity),
+                    duration,
+                ));
+                clip.add_track(tween("scale", scale(0.0), scale(mobject.scale), duration));
+            }
+            Kind::Uncreate => {
+                clip.add_track(tween(
+                    "opacity",
+                    opacity(mobject.opacity),
+                    opacity(0.0),
+                    duration,
+                ));
+                clip.add_track(tween("scale", scale(mobject.scale), scale(0.0), duration));
+                mobject.opacity = 0.0;
+                mobject.scale = 0.0;
+            }
+            Kind::GrowFromCenter => {
+                clip.add_track(tween("scale", scale(0.0), scale(mobject.scale), duration));
+            }
+            Kind::ShrinkToCenter => {
+                clip.add_track(tween("scale", scale(mobject.scale), scale(0.0), duration));
+                mobject.scale = 0.0;
+            }
+            Kind::MoveTo(target) => {
+                clip.add_track(tween("position", mobject.position, target, duration));
+                mobject.position = target;
+            }
+            Kind::Shift(offset) => {
+                let target = mobject.position + offset;
+                clip.add_track(tween("position", mobject.position, target, duration));
+                mobject.position = target;
+            }
+            Kind::Rotate(angle) => {
+                let from = CoreVector3::new(0.0, 0.0, mobject.angle);
+                mobject.angle += angle;
+                let to = CoreVector3::new(0.0, 0.0, mobject.angle);
+                clip.add_track(tween("rotation", from, to, duration));
+            }
+            Kind::ScaleInPlace(factor) => {
+                let from = mobject.scale;
+                mobject.scale *= factor;
+                clip.add_track(tween("scale", scale(from), scale(mobject.scale), duration));
+            }
+        }
+        clip
+    }
+
+    fn name(&self) -> &'static str {
+        match self.kind {
+            Kind::FadeIn => "FadeIn",
+            Kind::FadeOut => "FadeOut",
+            Kind::Create => "Create",
+            Kind::Uncreate => "Uncreate",
+            Kind::GrowFromCenter => "GrowFromCenter",
+            Kind::ShrinkToCenter => "ShrinkToCenter",
+            Kind::MoveTo(_) => "MoveTo",
+            Kind::Shift(_) => "Shift",
+            Kind::Rotate(_) => "Rotate",
+            Kind::ScaleInPlace(_) => "ScaleInPlace",
+        }
+    }
+}
+
+/// Track easing from `from` to `to` over `duration` seconds
+fn tween(
+    name: &str,
+    from: CoreVector3,
+    to: CoreVector3,
+    duration: f32,
+) -> AnimationTrack<CoreVector3> {
+    let mut track = AnimationTrack::new(name.to_string());
+    track.add_keyframe(
+        Keyframe::new(TimeValue::new(0.0), from).with_interpolation(InterpolationType::EaseInOut),
+    );
+    track.add_keyframe(Keyframe::new(TimeValue::new(duration), to));
+    track
+}
+
+#[pyclass(extends = Animation, module = "diomanim")]
+pub struct FadeIn;
+
+#[pymethods]
+impl FadeIn {
+    #[new]
+    #[pyo3(signature = (mobject, run_time=None))]
+    fn new(mobject: Bound<'_, Mobject>, run_time: Option<f32>) -> (Self, Animation) {
+        (Self, Animation::new(mobject, Kind::FadeIn, run_time))
+    }
+}
+
+#[pyclass(extends = Animation, module = "diomanim")]
+pub struct FadeOut;
+
+#[pymethods]
+impl FadeOut {
+    #[new]
+    #[pyo3(signature = (mobject, run_time=None))]
+    fn new(mobject: Bound<'_, Mobject>, run_time: Option<f32>) -> (Self, Animation) {
+        (Self, Animation::new(mobject, Kind::FadeOut, run_time))
+    }
+}
+
+/// Fades in while growing from the center (there are no stroke-drawing
+/// animations yet)
+#[pyclass(extends = Animation, module = "diomanim")]
+pub struct Create;
+
+#[pymethods]
+impl Create {
+    #[new]
+    #[pyo3(signature = (mobject, run_time=None))]
+    fn new(mobject: Bound<'_, Mobject>, run_time: Option<f32>) -> (Self, Animation) {
+        (Self, Animation::new(mobject, Kind::Create, run_time))
+    }
+}
+
+/// [`Create`] for text
+#[pyclass(extends = Animation, module = "diomanim")]
+pub struct Write;
+
+#[pymethods]
+impl Write {
+    #[new]
+    #[pyo3(signature = (mobject, run_time=None))]
+    fn new(mobject: Bound<'_, Mobject>, run_time: Option<f32>) -> (Self, Animation) {
+        (Self, Animation::new(mobject, Kind::Create, run_time))
+    }
+}
+
+#[pyclass(extends = Animation, module = "diomanim")]
+pub struct Uncreate;
+
+#[pymethods]
+impl Uncreate {
+    #[new]
+    #[pyo3(signature = (mobject, run_time=None))]
+    fn new(mobject: Bound<'_, Mobject>, run_time: Option<f32>) -> (Self, Animation) {
+        (Self, Animation::new(mobject, Kind::Uncreate, run_time))
+    }
+}
+
+#[pyclass(extends = Animation, module = "diomanim")]
+pub struct GrowFromCenter;
+
+#[pymethods]
+impl GrowFromCenter {
+    #[new]
+    #[pyo3(signature = (mobject, run_time=None))]
+    fn new(mobject: Bound<'_, Mobject>, run_time: Option<f32>) -> (Self, Animation) {
+        (
+            Self,
+            Animation::new(mobject, Kind::GrowFromCenter, run_time),
+        )
+    }
+}
+
+#[pyclass(extends = Animation, module = "diomanim")]
+pub struct ShrinkToCenter;
+
+#[pymethods]
+impl ShrinkToCenter {
+    #[new]
+    #[pyo3(signature = (mobject, run_time=None))]
+    fn new(mobject: Bound<'_, Mobject>, run_time: Option<f32>) -> (Self, Animation) {
+        (
+            Self,
+            Animation::new(mobject, Kind::ShrinkToCenter, run_time),
+        )
+    }
+}
+
+#[pyclass(extends = Animation, module = "diomanim")]
+pub struct MoveTo;
+
+#[pymethods]
+impl MoveTo {
+    #[new]
+    #[pyo3(signature = (mobject, point, run_time=None))]
+    fn new(
+        mobject: Bound<'_, Mobject>,
+        point: &Bound<'_, PyAny>,
+        run_time: Option<f32>,
+    ) -> PyResult<(Self, Animation)> {
+        let target = self::point(point)?;
+        Ok((
+            Self,
+            Animation::new(mobject, Kind::MoveTo(target), run_time),
+        ))
+    }
+}
+
+#[pyclass(extends = Animation, module = "diomanim")]
+pub struct Shift;
+
+#[pymethods]
+impl Shift {
+    #[new]
+    #[pyo3(signature = (mobject, vector, run_time=None))]
+    fn new(
+        mobject: Bound<'_, Mobject>,
+        vector: &Bound<'_, PyAny>,
+        run_time: Option<f32>,
+    ) -> PyResult<(Self, Animation)> {
+        let offset = point(vector)?;
+        Ok((Self, Animation::new(mobject, Kind::Shift(offset), run_time)))
+    }
+}
+
+/// Rotate counterclockwise by `angle` radians about the mobject's center
+#[pyclass(extends = Animation, module = "diomanim")]
+pub struct Rotate;
+
+#[pymethods]
+impl Rotate {
+    #[new]
+    #[pyo3(signature = (mobject, angle=std::f32::consts::PI, run_time=None))]
+    fn new(mobject: Bound<'_, Mobject>, angle: f32, run_time: Option<f32>) -> (Self, Animation) {
+        (Self, Animation::new(mobject, Kind::Rotate(angle), run_time))
+    }
+}
+
+#[pyclass(extends = Animation, module = "diomanim")]
+pub struct ScaleInPlace;
+
+#[pymethods]
+impl ScaleInPlace {
+    #[new]
+    #[pyo3(signature = (mobject, scale_factor, run_time=None))]
+    fn new(
+        mobject: Bound<'_, Mobject>,
+        scale_factor: f32,
+        run_time: Option<f32>,
+    ) -> (Self, Animation) {
+        (
+            Self,
+            Animation::new(mobject, Kind::ScaleInPlace(scale_factor), run_time),
+        )
+    }
+}
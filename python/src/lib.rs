@@ -0,0 +1,101 @@
+//! # Python bindings
+//!
+//! A Manim-style Python API over diomanim's scene graph and GPU renderer,
+//! built with [maturin](https://www.maturin.rs):
+//!
+//! ```text
+//! cd python && maturin develop --release
+//! ```
+//!
+//! Porting a Manim scene mostly means changing the import:
+//!
+//! ```python
+//! from diomanim import *
+//!
+//! class SquareToCircle(Scene):
+//!     def construct(self):
+//!         square = Square(color=BLUE).shift(2 * LEFT)
+//!         circle = Circle(color=RED)
+//!         self.play(Create(square))
+//!         self.play(Rotate(square, PI / 4), FadeIn(circle), run_time=2)
+//!         self.play(Shift(circle, UP))
+//!         self.wait()
+//!
+//! SquareToCircle().render("output/square_to_circle.mp4")
+//! ```
+//!
+//! Points are `Vector3` values or any sequence of two or three numbers
+//! (numpy arrays included), and colors are `Color` values, `"#RRGGBB"`
+//! strings or Manim color names.
+
+mod animations;
+mod mobjects;
+mod scene;
+mod values;
+
+use diomanim::core::NAMED_COLORS;
+use pyo3::prelude::*;
+use std::f32::consts::{PI, TAU};
+
+#[pymodule]
+#[pyo3(name = "diomanim")]
+fn diomanim_python(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<values::Vector3>()?;
+    m.add_class::<values::Color>()?;
+
+    m.add_class::<scene::Scene>()?;
+    m.add_function(wrap_pyfunction!(scene::render_to_video, m)?)?;
+
+    m.add_class::<mobjects::Mobject>()?;
+    m.add_class::<mobjects::Circle>()?;
+    m.add_class::<mobjects::Dot>()?;
+    m.add_class::<mobjects::Rectangle>()?;
+    m.add_class::<mobjects::Square>()?;
+    m.add_class::<mobjects::Line>()?;
+    m.add_class::<mobjects::Arrow>()?;
+    m.add_class::<mobjects::Polygon>()?;
+    m.add_class::<mobjects::RegularPolygon>()?;
+    m.add_class::<mobjects::Triangle>()?;
+    m.add_class::<mobjects::Star>()?;
+    m.add_class::<mobjects::Text>()?;
+    m.add_class::<mobjects::MathTex>()?;
+
+    m.add_class::<animations::Animation>()?;
+    m.add_class::<animations::FadeIn>()?;
+    m.add_class::<animations::FadeOut>()?;
+    m.add_class::<animations::Create>()?;
+    m.add_class::<animations::Write>()?;
+    m.add_class::<animations::Uncreate>()?;
+    m.add_class::<animations::GrowFromCenter>()?;
+    m.add_class::<animations::ShrinkToCenter>()?;
+    m.add_class::<animations::MoveTo>()?;
+    m.add_class::<animations::Shift>()?;
+    m.add_class::<animations::Rotate>()?;
+    m.add_class::<animations::ScaleInPlace>()?;
+
+    for (name, color) in NAMED_COLORS {
+        m.add(name.to_uppercase(), values::Color(*color))?;
+    }
+
+    let directions = [
+        ("ORIGIN", (0.0, 0.0)),
+        ("UP", (0.0, 1.0)),
+        ("DOWN", (0.0, -1.0)),
+        ("LEFT", (-1.0, 0.0)),
+        ("RIGHT", (1.0, 0.0)),
+        ("UL", (-1.0, 1.0)),
+        ("UR", (1.0, 1.0)),
+        ("DL", (-1.0, -1.0)),
+        ("DR", (1.0, -1.0)),
+    ];
+    for (name, (x, y)) in directions {
+        m.add(
+            name,
+            values::Vector3(diomanim::core::Vector3::new(x, y, 0.0)),
+        )?;
+    }
+    m.add("PI", PI)?;
+    m.add("TAU", TAU)?;
+    m.add("DEGREES", PI / 180.0)?;
+    Ok(())
+}
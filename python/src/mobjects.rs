@@ -0,0 +1,462 @@
+//! Shapes and text, named and parameterized like Manim's mobjects
+//!
+//! A mobject keeps its own position, scale, rotation and opacity. Once it is
+//! added to a scene it also owns a scene node, and changes made outside
+//! `play` take effect at the scene's current time.
+
+use crate::scene::{renderer_font_size, SharedScene};
+use crate::values::{color, optional_color, point, Vector3};
+use diomanim::core::{Color as CoreColor, Vector3 as CoreVector3};
+use diomanim::mobjects::Polygon as CorePolygon;
+use diomanim::scene::{NodeEffect, NodeId, Renderable, Theme};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyTuple;
+
+/// Manim's default circle radius and square side, in scene units
+const DEFAULT_RADIUS: f32 = 1.0;
+const DEFAULT_SIDE: f32 = 2.0;
+/// Radius of a `Dot`
+const DOT_RADIUS: f32 = 0.08;
+
+/// Base class of everything that can be added to a scene
+#[pyclass(subclass, module = "diomanim")]
+pub struct Mobject {
+    pub(crate) renderable: Renderable,
+    /// Color given by the script; unset colors come from the scene's theme
+    pub(crate) color: Option<CoreColor>,
+    /// Whether an unset color is the theme's fill (shapes) or foreground (lines, text)
+    pub(crate) filled: bool,
+    /// Font size (text) or stroke width (lines) given by the script
+    pub(crate) size: Option<f32>,
+    pub(crate) position: CoreVector3,
+    pub(crate) scale: f32,
+    /// Rotation about the z axis, in radians
+    pub(crate) angle: f32,
+    pub(crate) opacity: f32,
+    pub(crate) effects: Vec<NodeEffect>,
+    /// Scene and node the mobject was added to
+    pub(crate) node: Option<(SharedScene, NodeId)>,
+}
+
+impl Mobject {
+    fn new(renderable: Renderable, color: Option<CoreColor>, filled: bool) -> Self {
+        Self {
+            renderable,
+            color,
+            filled,
+            size: None,
+            position: CoreVector3::zero(),
+            scale: 1.0,
+            angle: 0.0,
+            opacity: 1.0,
+            effects: Vec::new(),
+            node: None,
+        }
+    }
+
+    fn shape(renderable: Renderable, color: Option<CoreColor>) -> Self {
+        Self::new(renderable, color, true)
+    }
+
+    fn stroke(renderable: Renderable, color: Option<CoreColor>, size: Option<f32>) -> Self {
+        Self {
+            size,
+            ..Self::new(renderable, color, false)
+        }
+    }
+
+    /// The renderable with unset colors and sizes taken from `theme`
+    pub(crate) fn resolved_renderable(&self, theme: &Theme) -> Renderable {
+        let mut renderable = self.renderable.clone();
+        *renderable.color_mut() = self.color.unwrap_or(if self.filled {
+            theme.fill
+        } else {
+            theme.foreground
+        });
+        match &mut renderable {
+            Renderable::Text { font_size, .. } | Renderable::Math { font_size, .. } => {
+                *font_size = renderer_font_size(self.size.unwrap_or(theme.font_size));
+            }
+            Renderable::Line { thickness, .. } | Renderable::Arrow { thickness, .. } => {
+                *thickness = self.size.unwrap_or(theme.stroke_width);
+            }
+            _ => {}
+        }
+        renderable
+    }
+
+    /// Whether the mobject has a node in `scene`
+    pub(crate) fn is_in(&self, scene: &SharedScene) -> bool {
+        self.node
+            .as_ref()
+            .is_some_and(|(bound, _)| std::sync::Arc::ptr_eq(bound, scene))
+    }
+
+    /// Set a transform track of the node (if any) from the scene's current time
+    fn set_track(&self, track: &str, value: CoreVector3) {
+        if let Some((scene, id)) = &self.node {
+            scene.lock().unwrap().set_track(*id, track, value);
+        }
+    }
+}
+
+#[pymethods]
+impl Mobject {
+    /// Move the center to `point`
+    fn move_to<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        point: &Bound<'py, PyAny>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        slf.position = self::point(point)?;
+        slf.set_track("position", slf.position);
+        Ok(slf)
+    }
+
+    /// Move by the sum of `vectors`
+    #[pyo3(signature = (*vectors))]
+    fn shift<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        vectors: &Bound<'py, PyTuple>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        for vector in vectors.iter() {
+            slf.position = slf.position + point(&vector)?;
+        }
+        slf.set_track("position", slf.position);
+        Ok(slf)
+    }
+
+    /// Multiply the size by `factor`
+    fn scale(mut slf: PyRefMut<'_, Self>, factor: f32) -> PyRefMut<'_, Self> {
+        slf.scale *= factor;
+        let scale = slf.scale;
+        slf.set_track("scale", CoreVector3::new(scale, scale, scale));
+        slf
+    }
+
+    /// Rotate counterclockwise by `angle` radians
+    fn rotate(mut slf: PyRefMut<'_, Self>, angle: f32) -> PyRefMut<'_, Self> {
+        slf.angle += angle;
+        slf.set_track("rotation", CoreVector3::new(0.0, 0.0, slf.angle));
+        slf
+    }
+
+    fn set_opacity(mut slf: PyRefMut<'_, Self>, opacity: f32) -> PyRefMut<'_, Self> {
+        slf.opacity = opacity.clamp(0.0, 1.0);
+        slf.set_track("opacity", CoreVector3::new(slf.opacity, 0.0, 0.0));
+        slf
+    }
+
+    /// Change the color; unlike moves, this applies to the whole scene
+    /// rather than from the current time
+    fn set_color<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        color: &Bound<'py, PyAny>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        let color = self::color(color)?;
+        slf.color = Some(color);
+        *slf.renderable.color_mut() = color;
+        if let Some((scene, id)) = &slf.node {
+            if let Some(node) = scene.lock().unwrap().graph.get_node_mut(*id) {
+                if let Some(renderable) = &mut node.renderable {
+                    *renderable.color_mut() = color;
+                }
+            }
+        }
+        Ok(slf)
+    }
+
+    /// Draw a glow of `color` (the mobject's own by default) blurred by
+    /// `radius` pixels
+    #[pyo3(signature = (color=None, radius=8.0))]
+    fn add_glow<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        color: Option<&Bound<'py, PyAny>>,
+        radius: f32,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        let color = optional_color(color)?
+            .or(slf.color)
+            .unwrap_or(CoreColor::WHITE);
+        slf.effects.push(NodeEffect::glow(color, radius));
+        Ok(slf)
+    }
+
+    /// Draw a drop shadow offset by `offset` (in the renderer's -1..1 frame
+    /// units), blurred by `softness` pixels
+    #[pyo3(signature = (offset=(0.02, -0.02), softness=6.0))]
+    fn add_shadow(
+        mut slf: PyRefMut<'_, Self>,
+        offset: (f32, f32),
+        softness: f32,
+    ) -> PyRefMut<'_, Self> {
+        slf.effects
+            .push(NodeEffect::shadow(offset.0, offset.1, softness));
+        slf
+    }
+
+    fn get_center(&self) -> Vector3 {
+        Vector3(self.position)
+    }
+
+    #[getter]
+    fn opacity(&self) -> f32 {
+        self.opacity
+    }
+}
+
+#[pyclass(extends = Mobject, module = "diomanim")]
+pub struct Circle;
+
+#[pymethods]
+impl Circle {
+    #[new]
+    #[pyo3(signature = (radius=DEFAULT_RADIUS, color=None))]
+    fn new(radius: f32, color: Option<&Bound<'_, PyAny>>) -> PyResult<(Self, Mobject)> {
+        let renderable = Renderable::Circle {
+            radius,
+            color: CoreColor::WHITE,
+        };
+        Ok((Self, Mobject::shape(renderable, optional_color(color)?)))
+    }
+}
+
+#[pyclass(extends = Mobject, module = "diomanim")]
+pub struct Dot;
+
+#[pymethods]
+impl Dot {
+    #[new]
+    #[pyo3(signature = (point=None, radius=DOT_RADIUS, color=None))]
+    fn new(
+        point: Option<&Bound<'_, PyAny>>,
+        radius: f32,
+        color: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<(Self, Mobject)> {
+        let renderable = Renderable::Circle {
+            radius,
+            color: CoreColor::WHITE,
+        };
+        let mut mobject = Mobject::stroke(renderable, optional_color(color)?, None);
+        if let Some(point) = point {
+            mobject.position = self::point(point)?;
+        }
+        Ok((Self, mobject))
+    }
+}
+
+#[pyclass(extends = Mobject, module = "diomanim")]
+pub struct Rectangle;
+
+#[pymethods]
+impl Rectangle {
+    #[new]
+    #[pyo3(signature = (width=4.0, height=2.0, color=None))]
+    fn new(width: f32, height: f32, color: Option<&Bound<'_, PyAny>>) -> PyResult<(Self, Mobject)> {
+        let renderable = Renderable::Rectangle {
+            width,
+            height,
+            color: CoreColor::WHITE,
+        };
+        Ok((Self, Mobject::shape(renderable, optional_color(color)?)))
+    }
+}
+
+#[pyclass(extends = Mobject, module = "diomanim")]
+pub struct Square;
+
+#[pymethods]
+impl Square {
+    #[new]
+    #[pyo3(signature = (side_length=DEFAULT_SIDE, color=None))]
+    fn new(side_length: f32, color: Option<&Bound<'_, PyAny>>) -> PyResult<(Self, Mobject)> {
+        let renderable = Renderable::Rectangle {
+            width: side_length,
+            height: side_length,
+            color: CoreColor::WHITE,
+        };
+        Ok((Self, Mobject::shape(renderable, optional_color(color)?)))
+    }
+}
+
+#[pyclass(extends = Mobject, module = "diomanim")]
+pub struct Line;
+
+#[pymethods]
+impl Line {
+    #[new]
+    #[pyo3(signature = (start=None, end=None, color=None, stroke_width=None))]
+    fn new(
+        start: Option<&Bound<'_, PyAny>>,
+        end: Option<&Bound<'_, PyAny>>,
+        color: Option<&Bound<'_, PyAny>>,
+        stroke_width: Option<f32>,
+    ) -> PyResult<(Self, Mobject)> {
+        let renderable = Renderable::Line {
+            start: start.map_or(Ok(CoreVector3::new(-1.0, 0.0, 0.0)), point)?,
+            end: end.map_or(Ok(CoreVector3::new(1.0, 0.0, 0.0)), point)?,
+            color: CoreColor::WHITE,
+            thickness: 0.0,
+        };
+        Ok((
+            Self,
+            Mobject::stroke(renderable, optional_color(color)?, stroke_width),
+        ))
+    }
+}
+
+#[pyclass(extends = Mobject, module = "diomanim")]
+pub struct Arrow;
+
+#[pymethods]
+impl Arrow {
+    #[new]
+    #[pyo3(signature = (start=None, end=None, color=None, stroke_width=None))]
+    fn new(
+        start: Option<&Bound<'_, PyAny>>,
+        end: Option<&Bound<'_, PyAny>>,
+        color: Option<&Bound<'_, PyAny>>,
+        stroke_width: Option<f32>,
+    ) -> PyResult<(Self, Mobject)> {
+        let renderable = Renderable::Arrow {
+            start: start.map_or(Ok(CoreVector3::new(-1.0, 0.0, 0.0)), point)?,
+            end: end.map_or(Ok(CoreVector3::new(1.0, 0.0, 0.0)), point)?,
+            color: CoreColor::WHITE,
+            thickness: 0.0,
+        };
+        Ok((
+            Self,
+            Mobject::stroke(renderable, optional_color(color)?, stroke_width),
+        ))
+    }
+}
+
+fn polygon(vertices: Vec<CoreVector3>, color: Option<CoreColor>) -> Mobject {
+    Mobject::shape(
+        Renderable::Polygon {
+            vertices,
+            color: CoreColor::WHITE,
+        },
+        color,
+    )
+}
+
+#[pyclass(extends = Mobject, module = "diomanim")]
+pub struct Polygon;
+
+#[pymethods]
+impl Polygon {
+    #[new]
+    #[pyo3(signature = (*vertices, color=None))]
+    fn new(
+        vertices: &Bound<'_, PyTuple>,
+        color: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<(Self, Mobject)> {
+        if vertices.len() < 3 {
+            return Err(PyValueError::new_err("a polygon needs at least 3 vertices"));
+        }
+        let vertices = vertices
+            .iter()
+            .map(|v| point(&v))
+            .collect::<PyResult<_>>()?;
+        Ok((Self, polygon(vertices, optional_color(color)?)))
+    }
+}
+
+#[pyclass(extends = Mobject, module = "diomanim")]
+pub struct RegularPolygon;
+
+#[pymethods]
+impl RegularPolygon {
+    #[new]
+    #[pyo3(signature = (n=6, radius=DEFAULT_RADIUS, color=None))]
+    fn new(n: usize, radius: f32, color: Option<&Bound<'_, PyAny>>) -> PyResult<(Self, Mobject)> {
+        if n < 3 {
+            return Err(PyValueError::new_err("a polygon needs at least 3 sides"));
+        }
+        let vertices = CorePolygon::regular(n, radius, CoreColor::WHITE).vertices;
+        Ok((Self, polygon(vertices, optional_color(color)?)))
+    }
+}
+
+#[pyclass(extends = Mobject, module = "diomanim")]
+pub struct Triangle;
+
+#[pymethods]
+impl Triangle {
+    #[new]
+    #[pyo3(signature = (radius=DEFAULT_RADIUS, color=None))]
+    fn new(radius: f32, color: Option<&Bound<'_, PyAny>>) -> PyResult<(Self, Mobject)> {
+        let vertices = CorePolygon::triangle(radius, CoreColor::WHITE).vertices;
+        Ok((Self, polygon(vertices, optional_color(color)?)))
+    }
+}
+
+#[pyclass(extends = Mobject, module = "diomanim")]
+pub struct Star;
+
+#[pymethods]
+impl Star {
+    #[new]
+    #[pyo3(signature = (n=5, outer_radius=DEFAULT_RADIUS, inner_radius=None, color=None))]
+    fn new(
+        n: usize,
+        outer_radius: f32,
+        inner_radius: Option<f32>,
+        color: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<(Self, Mobject)> {
+        if n < 2 {
+            return Err(PyValueError::new_err("a star needs at least 2 points"));
+        }
+        let inner_radius = inner_radius.unwrap_or(outer_radius * 0.382);
+        let vertices = CorePolygon::star(n, outer_radius, inner_radius, CoreColor::WHITE).vertices;
+        Ok((Self, polygon(vertices, optional_color(color)?)))
+    }
+}
+
+#[pyclass(extends = Mobject, module = "diomanim")]
+pub struct Text;
+
+#[pymethods]
+impl Text {
+    #[new]
+    #[pyo3(signature = (text, font_size=None, color=None))]
+    fn new(
+        text: String,
+        font_size: Option<f32>,
+        color: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<(Self, Mobject)> {
+        let renderable = Renderable::Text {
+            content: text,
+            font_size: 0.0,
+            color: CoreColor::WHITE,
+        };
+        Ok((
+            Self,
+            Mobject::stroke(renderable, optional_color(color)?, font_size),
+        ))
+    }
+}
+
+#[pyclass(extends = Mobject, module = "diomanim")]
+pub struct MathTex;
+
+#[pymethods]
+impl MathTex {
+    #[new]
+    #[pyo3(signature = (latex, font_size=None, color=None))]
+    fn new(
+        latex: String,
+        font_size: Option<f32>,
+        color: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<(Self, Mobject)> {
+        let renderable = Renderable::Math {
+            latex,
+            font_size: 0.0,
+            color: CoreColor::WHITE,
+        };
+        Ok((
+            Self,
+            Mobject::stroke(renderable, optional_color(color)?, font_size),
+        ))
+    }
+}
@@ -0,0 +1,341 @@
+//! `Scene`: a timeline that `add`, `play` and `wait` append to, rendered
+//! to video by `render_to_video`
+
+use crate::animations::Animation;
+use crate::mobjects::Mobject;
+use crate::values::color;
+use diomanim::animation::property::{AnimationClip, AnimationInstance, AnimationTrack, Keyframe};
+use diomanim::core::Matrix4;
+use diomanim::core::{transform::Quaternion, TimeValue, Vector3 as CoreVector3};
+use diomanim::export::{export_video_ffmpeg, VideoExportSettings};
+use diomanim::pipeline::{
+    render_frames_with_graph, scene_frame_graph, RenderConfig, FRAME_TEXTURE,
+};
+use diomanim::render::ShapeRenderer;
+use diomanim::scene::{NodeId, PostEffect, SceneGraph, Theme};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyTuple};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Height of the frame in scene units, as in Manim (the renderer's frame
+/// spans -1..1 on both axes)
+const FRAME_HEIGHT: f32 = 8.0;
+/// Pixel size glyphs are rasterized at for rendering
+const TEXT_ATLAS_SIZE: f32 = 48.0;
+/// Scene units per em for each point of `font_size`: 0.75 at Manim's
+/// default size of 48
+const EM_PER_POINT: f32 = 0.75 / 48.0;
+
+/// The renderer's font size for text of `font_size` points
+pub(crate) fn renderer_font_size(font_size: f32) -> f32 {
+    // Glyphs are drawn at `font_size / 1000` units per atlas pixel
+    font_size * EM_PER_POINT * 1000.0 / TEXT_ATLAS_SIZE
+}
+
+/// Scene graph shared by a `Scene` and the mobjects added to it
+pub type SharedScene = Arc<Mutex<SceneState>>;
+
+pub struct SceneState {
+    pub(crate) graph: SceneGraph,
+    /// Seconds of animation scheduled so far
+    pub(crate) time: f32,
+}
+
+impl SceneState {
+    /// Set a transform track (`position`, `scale`, `rotation` as z angle, or
+    /// `opacity`) of node `id` from the current time on
+    pub(crate) fn set_track(&mut self, id: NodeId, track: &str, value: CoreVector3) {
+        let time = self.time;
+        let Some(node) = self.graph.get_node_mut(id) else {
+            return;
+        };
+        if time > 0.0 {
+            let mut clip = AnimationClip::new("Set".to_string());
+            let mut hold = AnimationTrack::new(track.to_string());
+            hold.add_keyframe(Keyframe::new(TimeValue::new(0.0), value));
+            clip.add_track(hold);
+            node.add_animation(AnimationInstance::new(clip, TimeValue::new(time)));
+            return;
+        }
+        match track {
+            "position" => node._local_transform.position = value,
+            "scale" => node._local_transform.scale = value,
+            "rotation" => node._local_transform.rotation = Quaternion::from_rotation_z(value.z),
+            "opacity" => node.opacity = value.x,
+            _ => {}
+        }
+    }
+}
+
+/// View-projection showing `FRAME_HEIGHT` scene units across the output's
+/// height, with square pixels
+fn frame_camera(width: u32, height: u32) -> Matrix4 {
+    let y = 2.0 / FRAME_HEIGHT;
+    let aspect_ratio = width as f32 / height.max(1) as f32;
+    Matrix4::from_scale(CoreVector3::new(y / aspect_ratio, y, 1.0))
+}
+
+/// A Manim-style scene: subclass it and override `construct`, or call
+/// `add`/`play`/`wait` on an instance directly
+#[pyclass(subclass, module = "diomanim")]
+pub struct Scene {
+    state: SharedScene,
+    constructed: bool,
+    rendered: bool,
+}
+
+impl Scene {
+    /// Give `mobject` a node in this scene, shown from the current time.
+    ///
+    /// For an `entrance` animation the node is left as is: the animation
+    /// holds its first frame, which hides the node, until it starts.
+    fn add_node(&self, mobject: &mut Mobject, entrance: bool) {
+        let mut state = self.state.lock().unwrap();
+        let renderable = mobject.resolved_renderable(state.graph.theme());
+        let name = format!("mobject_{}", state.graph.node_count());
+        let id = state.graph.create_node(name);
+        let shown_later = !entrance && state.time > 0.0;
+        let node = state.graph.get_node_mut(id).unwrap();
+        node.set_renderable(renderable);
+        node.effects.clone_from(&mobject.effects);
+        node._local_transform.position = mobject.position;
+        node._local_transform.scale = CoreVector3::new(mobject.scale, mobject.scale, mobject.scale);
+        node._local_transform.rotation = Quaternion::from_rotation_z(mobject.angle);
+        node.opacity = if shown_later { 0.0 } else { mobject.opacity };
+        if shown_later {
+            state.set_track(id, "opacity", CoreVector3::new(mobject.opacity, 0.0, 0.0));
+        }
+        mobject.node = Some((self.state.clone(), id));
+    }
+
+    fn node(mobject: &Mobject) -> NodeId {
+        mobject.node.as_ref().expect("mobject was added").1
+    }
+}
+
+#[pymethods]
+impl Scene {
+    #[new]
+    #[pyo3(signature = (*_args, **_kwargs))]
+    fn new(_args: &Bound<'_, PyTuple>, _kwargs: Option<&Bound<'_, PyDict>>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(SceneState {
+                graph: SceneGraph::new(),
+                time: 0.0,
+            })),
+            constructed: false,
+            rendered: false,
+        }
+    }
+
+    /// Build the scene; called by `render` (override in a subclass)
+    fn construct(&self) {}
+
+    /// Show `mobjects` from the current time
+    #[pyo3(signature = (*mobjects))]
+    fn add(&self, mobjects: &Bound<'_, PyTuple>) -> PyResult<()> {
+        for mobject in mobjects.iter() {
+            let mut mobject = mobject.extract::<PyRefMut<'_, Mobject>>()?;
+            if !mobject.is_in(&self.state) {
+                self.add_node(&mut mobject, false);
+            } else if mobject.opacity <= 0.0 {
+                mobject.opacity = 1.0;
+                let id = Self::node(&mobject);
+                let opacity = CoreVector3::new(1.0, 0.0, 0.0);
+                self.state.lock().unwrap().set_track(id, "opacity", opacity);
+            }
+        }
+        Ok(())
+    }
+
+    /// Hide `mobjects` from the current time
+    #[pyo3(signature = (*mobjects))]
+    fn remove(&self, mobjects: &Bound<'_, PyTuple>) -> PyResult<()> {
+        for mobject in mobjects.iter() {
+            let mut mobject = mobject.extract::<PyRefMut<'_, Mobject>>()?;
+            if mobject.is_in(&self.state) {
+                mobject.opacity = 0.0;
+                let id = Self::node(&mobject);
+                let hidden = CoreVector3::new(0.0, 0.0, 0.0);
+                self.state.lock().unwrap().set_track(id, "opacity", hidden);
+            }
+        }
+        Ok(())
+    }
+
+    /// Play `animations` together, then advance the scene's time by the
+    /// longest of them (`run_time` seconds unless an animation sets its own)
+    #[pyo3(signature = (*animations, run_time=1.0))]
+    fn play(&self, animations: &Bound<'_, PyTuple>, run_time: f32) -> PyResult<()> {
+        let py = animations.py();
+        let mut longest: f32 = 0.0;
+        for animation in animations.iter() {
+            let animation = animation.extract::<PyRef<'_, Animation>>()?;
+            let duration = animation.run_time.unwrap_or(run_time).max(0.0);
+            let mut mobject = animation.mobject.bind(py).borrow_mut();
+
+            // A mobject first seen here is added before its animation plays
+            let first = !mobject.is_in(&self.state);
+            let entrance = first && animation.is_entrance();
+            if first {
+                self.add_node(&mut mobject, entrance);
+            }
+
+            let clip = animation.clip(&mut mobject, duration);
+            let mut state = self.state.lock().unwrap();
+            let start = TimeValue::new(state.time);
+            if let Some(node) = state.graph.get_node_mut(Self::node(&mobject)) {
+                if entrance {
+                    // The first frame is drawn before any animation is applied
+                    animation.hide(node);
+                }
+                node.add_animation(AnimationInstance::new(clip, start).with_fill_before(entrance));
+            }
+            longest = longest.max(duration);
+        }
+        self.state.lock().unwrap().time += longest;
+        Ok(())
+    }
+
+    /// Hold the current frame for `duration` seconds
+    #[pyo3(signature = (duration=1.0))]
+    fn wait(&self, duration: f32) {
+        self.state.lock().unwrap().time += duration.max(0.0);
+    }
+
+    /// Seconds of animation scheduled so far
+    #[getter]
+    fn time(&self) -> f32 {
+        self.state.lock().unwrap().time
+    }
+
+    /// Use the `"dark"` (default) or `"light"` theme for colors, stroke
+    /// widths and font sizes mobjects don't set
+    fn set_theme(&self, name: &str) -> PyResult<()> {
+        let theme = match name {
+            "dark" => Theme::dark(),
+            "light" => Theme::light(),
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown theme '{name}' (expected \"dark\" or \"light\")"
+                )))
+            }
+        };
+        self.state.lock().unwrap().graph.set_theme(theme);
+        Ok(())
+    }
+
+    fn set_background(&self, background: &Bound<'_, PyAny>) -> PyResult<()> {
+        let background = color(background)?;
+        let mut state = self.state.lock().unwrap();
+        let theme = state.graph.theme().clone().with_background(background);
+        state.graph.set_theme(theme);
+        Ok(())
+    }
+
+    /// Make bright shapes glow into their surroundings
+    #[pyo3(signature = (threshold=0.7, intensity=1.0))]
+    fn add_bloom(&self, threshold: f32, intensity: f32) {
+        let bloom = PostEffect::bloom(threshold, intensity);
+        self.state.lock().unwrap().graph.add_post_effect(bloom);
+    }
+
+    /// Darken the frame towards its edges
+    #[pyo3(signature = (intensity=0.4))]
+    fn add_vignette(&self, intensity: f32) {
+        let vignette = PostEffect::vignette(intensity);
+        self.state.lock().unwrap().graph.add_post_effect(vignette);
+    }
+
+    /// Blur the whole frame by `radius` pixels
+    fn add_blur(&self, radius: f32) {
+        let blur = PostEffect::blur(radius);
+        self.state.lock().unwrap().graph.add_post_effect(blur);
+    }
+
+    /// See [`render_to_video`]
+    #[pyo3(signature = (output="output/scene.mp4", width=1920, height=1080, fps=30, frames_dir=None))]
+    fn render(
+        slf: &Bound<'_, Self>,
+        output: &str,
+        width: u32,
+        height: u32,
+        fps: u32,
+        frames_dir: Option<PathBuf>,
+    ) -> PyResult<()> {
+        render_to_video(slf, output, width, height, fps, frames_dir)
+    }
+}
+
+/// Run the scene's `construct` (once) and render it to an MP4 at `output`
+/// with ffmpeg.
+///
+/// Frames are written to `frames_dir` (a temporary directory by default).
+/// A scene can only be rendered once, since rendering plays its animations.
+#[pyfunction]
+#[pyo3(signature = (scene, output="output/scene.mp4", width=1920, height=1080, fps=30, frames_dir=None))]
+pub fn render_to_video(
+    scene: &Bound<'_, Scene>,
+    output: &str,
+    width: u32,
+    height: u32,
+    fps: u32,
+    frames_dir: Option<PathBuf>,
+) -> PyResult<()> {
+    if !scene.borrow().constructed {
+        scene.borrow_mut().constructed = true;
+        scene.call_method0("construct")?;
+    }
+    let state = {
+        let mut scene = scene.borrow_mut();
+        if scene.rendered {
+            return Err(PyRuntimeError::new_err("the scene was already rendered"));
+        }
+        scene.rendered = true;
+        scene.state.clone()
+    };
+    let (mut graph, duration) = {
+        let mut state = state.lock().unwrap();
+        (std::mem::take(&mut state.graph), state.time)
+    };
+
+    let frames_dir = frames_dir.unwrap_or_else(|| {
+        std::env::temp_dir().join(format!("diomanim_frames_{}", std::process::id()))
+    });
+    let output = output.to_string();
+    scene
+        .py()
+        .detach(move || -> Result<(), String> {
+            let theme = graph.theme().clone();
+            let mut renderer =
+                pollster::block_on(ShapeRenderer::new(width, height)).map_err(|e| e.to_string())?;
+            if let Err(e) =
+                renderer.init_text_rendering_with_font(theme.font_path(), TEXT_ATLAS_SIZE)
+            {
+                eprintln!("Text rendering unavailable: {e}");
+            }
+
+            // At least one frame, for scenes that only add mobjects
+            let duration = duration.max(1.0 / fps.max(1) as f32);
+            let config = RenderConfig::new(width, height, fps, duration)
+                .with_theme(&theme)
+                .with_frames_dir(&frames_dir);
+            let mut frame_graph = scene_frame_graph(&graph, &config);
+            frame_graph.set_camera(frame_camera(width, height), CoreVector3::new(0.0, 0.0, 5.0));
+            render_frames_with_graph(
+                &mut renderer,
+                &mut graph,
+                &config,
+                &mut frame_graph,
+                FRAME_TEXTURE,
+            )
+            .map_err(|e| e.to_string())?;
+
+            let settings =
+                VideoExportSettings::new(width, height, fps, output, config.frame_pattern());
+            export_video_ffmpeg(&settings).map_err(|e| e.to_string())
+        })
+        .map_err(PyRuntimeError::new_err)
+}
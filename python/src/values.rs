@@ -0,0 +1,181 @@
+//! Points and colors, and the Python values accepted in their place
+
+use diomanim::core::{Color as CoreColor, Vector3 as CoreVector3};
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::prelude::*;
+
+/// A 3D point or direction, like the numpy arrays Manim passes around
+#[pyclass(name = "Vector3", module = "diomanim", frozen, eq)]
+#[derive(Clone, Copy, PartialEq)]
+pub struct Vector3(pub CoreVector3);
+
+#[pymethods]
+impl Vector3 {
+    #[new]
+    #[pyo3(signature = (x=0.0, y=0.0, z=0.0))]
+    fn new(x: f32, y: f32, z: f32) -> Self {
+        Self(CoreVector3::new(x, y, z))
+    }
+
+    #[getter]
+    fn x(&self) -> f32 {
+        self.0.x
+    }
+
+    #[getter]
+    fn y(&self) -> f32 {
+        self.0.y
+    }
+
+    #[getter]
+    fn z(&self) -> f32 {
+        self.0.z
+    }
+
+    fn __add__(&self, other: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Ok(Self(self.0 + point(other)?))
+    }
+
+    fn __radd__(&self, other: &Bound<'_, PyAny>) -> PyResult<Self> {
+        self.__add__(other)
+    }
+
+    fn __sub__(&self, other: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Ok(Self(self.0 - point(other)?))
+    }
+
+    fn __rsub__(&self, other: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Ok(Self(point(other)? - self.0))
+    }
+
+    fn __mul__(&self, scalar: f32) -> Self {
+        Self(self.0 * scalar)
+    }
+
+    fn __rmul__(&self, scalar: f32) -> Self {
+        self.__mul__(scalar)
+    }
+
+    fn __truediv__(&self, scalar: f32) -> Self {
+        Self(self.0 / scalar)
+    }
+
+    fn __neg__(&self) -> Self {
+        Self(-self.0)
+    }
+
+    fn __len__(&self) -> usize {
+        3
+    }
+
+    fn __getitem__(&self, index: isize) -> PyResult<f32> {
+        match index {
+            0 | -3 => Ok(self.0.x),
+            1 | -2 => Ok(self.0.y),
+            2 | -1 => Ok(self.0.z),
+            _ => Err(PyIndexError::new_err("Vector3 index out of range")),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Vector3({}, {}, {})", self.0.x, self.0.y, self.0.z)
+    }
+}
+
+/// An RGBA color with components in `0..=1`
+#[pyclass(name = "Color", module = "diomanim", frozen, eq)]
+#[derive(Clone, Copy, PartialEq)]
+pub struct Color(pub CoreColor);
+
+#[pymethods]
+impl Color {
+    #[new]
+    #[pyo3(signature = (r, g, b, a=1.0))]
+    fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self(CoreColor::rgba(r, g, b, a))
+    }
+
+    /// Parse `"#RRGGBB"` or `"RRGGBB"`
+    #[staticmethod]
+    fn from_hex(hex: &str) -> Self {
+        Self(CoreColor::from_hex(hex))
+    }
+
+    #[getter]
+    fn r(&self) -> f32 {
+        self.0.r
+    }
+
+    #[getter]
+    fn g(&self) -> f32 {
+        self.0.g
+    }
+
+    #[getter]
+    fn b(&self) -> f32 {
+        self.0.b
+    }
+
+    #[getter]
+    fn a(&self) -> f32 {
+        self.0.a
+    }
+
+    /// Blend towards `other` by `t` (0 = this color, 1 = `other`)
+    fn interpolate(&self, other: &Bound<'_, PyAny>, t: f32) -> PyResult<Self> {
+        Ok(Self(self.0.lerp(&color(other)?, t)))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Color({}, {}, {}, {})",
+            self.0.r, self.0.g, self.0.b, self.0.a
+        )
+    }
+}
+
+/// A point from a `Vector3` or any sequence of two or three numbers
+/// (a missing z is 0)
+pub fn point(value: &Bound<'_, PyAny>) -> PyResult<CoreVector3> {
+    if let Ok(vector) = value.cast::<Vector3>() {
+        return Ok(vector.get().0);
+    }
+    let coords: Vec<f32> = value.extract().map_err(|_| {
+        PyValueError::new_err(format!(
+            "expected a point, got {}",
+            value
+                .get_type()
+                .name()
+                .map_or_else(|_| "?".into(), |n| n.to_string())
+        ))
+    })?;
+    match coords[..] {
+        [x, y] => Ok(CoreVector3::new(x, y, 0.0)),
+        [x, y, z] => Ok(CoreVector3::new(x, y, z)),
+        _ => Err(PyValueError::new_err(format!(
+            "a point needs 2 or 3 coordinates, got {}",
+            coords.len()
+        ))),
+    }
+}
+
+/// A color from a `Color`, a `"#RRGGBB"` string or a Manim color name
+pub fn color(value: &Bound<'_, PyAny>) -> PyResult<CoreColor> {
+    if let Ok(color) = value.cast::<Color>() {
+        return Ok(color.get().0);
+    }
+    let name: String = value
+        .extract()
+        .map_err(|_| PyValueError::new_err("expected a Color, hex string or color name"))?;
+    if name.starts_with('#') {
+        Ok(CoreColor::from_hex(&name))
+    } else {
+        CoreColor::from_name(&name)
+            .ok_or_else(|| PyValueError::new_err(format!("unknown color '{name}'")))
+    }
+}
+
+/// [`color`] for an optional argument
+pub fn optional_color(value: Option<&Bound<'_, PyAny>>) -> PyResult<Option<CoreColor>> {
+    value.map(color).transpose()
+}
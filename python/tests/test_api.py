@@ -0,0 +1,63 @@
+"""Tests for the Python API that don't need a GPU (run with pytest after
+`maturin develop`)."""
+
+import math
+
+import pytest
+
+from diomanim import *
+
+
+def test_vectors_and_constants():
+    assert UP + RIGHT == UR
+    assert 2 * LEFT == Vector3(-2, 0, 0)
+    assert list(DOWN) == [0.0, -1.0, 0.0]
+    assert DEGREES * 180 == pytest.approx(math.pi)
+
+
+def test_colors_accept_names_and_hex():
+    assert Color.from_hex("#FF0000") == Color(1, 0, 0)
+    assert Circle(color="RED").opacity == 1.0
+    Square(color="#58C4DD")
+    with pytest.raises(ValueError):
+        Circle(color="not a color")
+
+
+def test_mobjects_chain_and_track_their_center():
+    square = Square(side_length=1).move_to((1, 2)).shift(UP, RIGHT)
+    assert square.get_center() == Vector3(2, 3, 0)
+    assert square.scale(2) is square
+    with pytest.raises(ValueError):
+        Dot((1, 2, 3, 4))
+
+
+def test_play_and_wait_advance_time():
+    scene = Scene()
+    circle = Circle()
+    scene.play(Create(circle))
+    assert scene.time == pytest.approx(1.0)
+    scene.play(Shift(circle, UP), Rotate(circle, PI, run_time=3), run_time=2)
+    assert scene.time == pytest.approx(4.0)
+    scene.wait(0.5)
+    assert scene.time == pytest.approx(4.5)
+    assert circle.get_center() == UP
+
+
+def test_animations_leave_mobjects_in_their_end_state():
+    scene = Scene()
+    dot = Dot()
+    scene.add(dot)
+    scene.play(FadeOut(dot))
+    assert dot.opacity == 0.0
+    scene.play(FadeIn(dot))
+    assert dot.opacity == 1.0
+
+
+def test_unknown_theme_is_an_error():
+    with pytest.raises(ValueError):
+        Scene().set_theme("sepia")
+
+
+def test_play_rejects_non_animations():
+    with pytest.raises(TypeError):
+        Scene().play(Circle())
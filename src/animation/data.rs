@@ -0,0 +1,516 @@
+//! # Data-Driven Animation
+//!
+//! A [`TimeSeries`] is a table of numbers over time loaded from CSV or JSON,
+//! such as stock prices or sensor readings. Its columns become keyframed
+//! [`AnimationTrack`]s for node properties or [`ValueTracker`]s for
+//! counters, so real datasets animate without hand-written keyframes.
+//!
+//! The time column holds numbers (in any unit) or dates like `2024-03-01`
+//! or `2024-03-01T09:30:00` (counted in days). Without one, rows are
+//! evenly spaced. [`TimeSeries::fit_to`] stretches the data over a stretch
+//! of the scene; [`TimeSeries::resample`] thins dense data or fills uneven
+//! gaps. Empty cells are skipped, the animation interpolating across them.
+//!
+//! ```rust
+//! use diomanim::animation::{property::AnimationClip, property::AnimationInstance, TimeSeries};
+//! use diomanim::core::*;
+//! use diomanim::mobjects::{DecimalNumber, NumberFormat};
+//! use diomanim::scene::SceneGraph;
+//!
+//! # fn example() -> Result<(), String> {
+//! let prices = TimeSeries::from_csv(
+//!     "date,close\n2024-01-01,101.5\n2024-01-02,99.0\n2024-01-05,104.25\n",
+//!     Some("date"),
+//! )?
+//! .fit_to(0.0, 6.0);
+//!
+//! let mut scene = SceneGraph::new();
+//! let dot = scene.add_circle("dot", 0.05, Color::TEAL).build();
+//! let mut clip = AnimationClip::new("prices".to_string());
+//! clip.add_track(prices.track("close", "position", |close| {
+//!     Vector3::new(0.0, (close - 100.0) / 10.0, 0.0)
+//! })?);
+//! scene
+//!     .get_node_mut(dot)
+//!     .unwrap()
+//!     .add_animation(AnimationInstance::new(clip, TimeValue::new(0.0)));
+//!
+//! let number = DecimalNumber::new(prices.tracker("close")?, NumberFormat::new(2));
+//! scene.add_decimal_number("price", number, None, None);
+//! # Ok(())
+//! # }
+//! ```
+
+use super::property::{Animatable, AnimationTrack, InterpolationType, Keyframe, TangentMode};
+use super::tracker::ValueTracker;
+use crate::core::TimeValue;
+use serde_json::Value;
+use std::path::Path;
+
+/// Columns of numbers sampled over time
+#[derive(Debug, Clone)]
+pub struct TimeSeries {
+    /// Time of each row in the data's own unit (days for dates, row
+    /// numbers without a time column), ascending
+    pub times: Vec<f64>,
+    /// Names of the value columns
+    pub columns: Vec<String>,
+    /// `values[column][row]`, NaN where the cell is empty or not a number
+    values: Vec<Vec<f32>>,
+    /// Scene time of the first row
+    pub start: f32,
+    /// Scene seconds per unit of data time
+    pub seconds_per_unit: f32,
+    /// How tracks move between rows (`Bezier` draws a smooth curve through
+    /// them)
+    pub interpolation: InterpolationType,
+}
+
+impl TimeSeries {
+    /// Load a `.csv` or `.json` file (see [`Self::from_csv`] and
+    /// [`Self::from_json`])
+    pub fn load(path: impl AsRef<Path>, time_column: Option<&str>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("json") => Self::from_json(&text, time_column),
+            _ => Self::from_csv(&text, time_column),
+        }
+    }
+
+    /// Parse comma-separated values with a header row, timed by
+    /// `time_column` (rows are evenly spaced without one)
+    pub fn from_csv(text: &str, time_column: Option<&str>) -> Result<Self, String> {
+        let mut lines = text
+            .lines()
+            .map(|line| line.trim_end_matches('\r'))
+            .filter(|line| !line.trim().is_empty());
+        let header = lines.next().ok_or("CSV has no header row")?;
+        let header = split_csv_line(header);
+        let rows = lines.map(split_csv_line).collect();
+        Self::from_table(header, rows, time_column)
+    }
+
+    /// Parse a JSON array of row objects (`[{"t": 0, "price": 9.5}, ...]`)
+    /// or an object of columns (`{"t": [0, 1], "price": [9.5, 9.8]}`)
+    pub fn from_json(text: &str, time_column: Option<&str>) -> Result<Self, String> {
+        let root: Value = serde_json::from_str(text).map_err(|e| format!("invalid JSON: {e}"))?;
+        let (header, rows) = match root {
+            Value::Array(objects) => {
+                let mut header: Vec<String> = Vec::new();
+                for object in &objects {
+                    let object = object.as_object().ok_or("JSON rows must be objects")?;
+                    for key in object.keys() {
+                        if !header.contains(key) {
+                            header.push(key.clone());
+                        }
+                    }
+                }
+                let rows = objects
+                    .iter()
+                    .map(|object| {
+                        header
+                            .iter()
+                            .map(|key| json_cell(object.get(key)))
+                            .collect()
+                    })
+                    .collect();
+                (header, rows)
+            }
+            Value::Object(columns) => {
+                let mut header = Vec::new();
+                let mut cells = Vec::new();
+                for (key, column) in columns {
+                    let column = column
+                        .as_array()
+                        .ok_or_else(|| format!("JSON column '{key}' must be an array"))?;
+                    header.push(key);
+                    cells.push(column.clone());
+                }
+                let row_count = cells.iter().map(Vec::len).max().unwrap_or(0);
+                let rows = (0..row_count)
+                    .map(|row| {
+                        cells
+                            .iter()
+                            .map(|column| json_cell(column.get(row)))
+                            .collect()
+                    })
+                    .collect();
+                (header, rows)
+            }
+            _ => return Err("JSON time series must be an array or an object".to_string()),
+        };
+        Self::from_table(header, rows, time_column)
+    }
+
+    /// Build a series from header names and rows of cells
+    fn from_table(
+        header: Vec<String>,
+        rows: Vec<Vec<String>>,
+        time_column: Option<&str>,
+    ) -> Result<Self, String> {
+        let time_index = time_column
+            .map(|name| {
+                header
+                    .iter()
+                    .position(|column| column == name)
+                    .ok_or_else(|| format!("No time column '{name}'"))
+            })
+            .transpose()?;
+
+        let mut timed_rows = Vec::with_capacity(rows.len());
+        for (number, row) in rows.iter().enumerate() {
+            let time = match time_index {
+                Some(index) => {
+                    let cell = row.get(index).map_or("", |cell| cell.trim());
+                    parse_time(cell)
+                        .ok_or_else(|| format!("Row {}: can't read time '{cell}'", number + 1))?
+                }
+                None => number as f64,
+            };
+            timed_rows.push((time, row));
+        }
+        // Stable, so rows at the same time keep their order
+        timed_rows.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let columns: Vec<(usize, String)> = header
+            .into_iter()
+            .enumerate()
+            .filter(|&(index, _)| Some(index) != time_index)
+            .collect();
+        let values = columns
+            .iter()
+            .map(|&(index, _)| {
+                timed_rows
+                    .iter()
+                    .map(|(_, row)| {
+                        row.get(index)
+                            .and_then(|cell| cell.trim().parse().ok())
+                            .unwrap_or(f32::NAN)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(Self {
+            times: timed_rows.iter().map(|&(time, _)| time).collect(),
+            columns: columns.into_iter().map(|(_, name)| name).collect(),
+            values,
+            start: 0.0,
+            seconds_per_unit: 1.0,
+            interpolation: InterpolationType::Linear,
+        })
+    }
+
+    /// Number of rows
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+
+    /// The values of `column`, NaN where a cell is empty
+    pub fn column(&self, name: &str) -> Option<&[f32]> {
+        let index = self.columns.iter().position(|column| column == name)?;
+        Some(&self.values[index])
+    }
+
+    /// Place the first row at scene time `start`, with `seconds_per_unit`
+    /// scene seconds per unit of data time
+    pub fn with_timing(mut self, start: f32, seconds_per_unit: f32) -> Self {
+        self.start = start;
+        self.seconds_per_unit = seconds_per_unit;
+        self
+    }
+
+    /// Stretch the rows over `duration` scene seconds from `start`
+    pub fn fit_to(self, start: f32, duration: f32) -> Self {
+        let span = self.data_span();
+        let seconds_per_unit = if span > 0.0 {
+            (f64::from(duration) / span) as f32
+        } else {
+            1.0
+        };
+        self.with_timing(start, seconds_per_unit)
+    }
+
+    pub fn with_interpolation(mut self, interpolation: InterpolationType) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Data time from the first row to the last
+    pub fn data_span(&self) -> f64 {
+        match (self.times.first(), self.times.last()) {
+            (Some(first), Some(last)) => last - first,
+            _ => 0.0,
+        }
+    }
+
+    /// Scene time of data time `time`
+    pub fn scene_time(&self, time: f64) -> f32 {
+        let first = self.times.first().copied().unwrap_or(0.0);
+        self.start + ((time - first) * f64::from(self.seconds_per_unit)) as f32
+    }
+
+    /// Value of `column` at data time `time`, linearly interpolated between
+    /// the nearest rows that have one (`None` if the column has no values)
+    pub fn value_at(&self, column: &str, time: f64) -> Option<f32> {
+        let index = self.columns.iter().position(|name| name == column)?;
+        let value = self.interpolate(index, time);
+        (!value.is_nan()).then_some(value)
+    }
+
+    fn interpolate(&self, column: usize, time: f64) -> f32 {
+        let values = &self.values[column];
+        let after = self.times.partition_point(|&t| t <= time);
+        let previous = (0..after).rev().find(|&row| !values[row].is_nan());
+        let next = (after..values.len()).find(|&row| !values[row].is_nan());
+        match (previous, next) {
+            (Some(previous), Some(next)) => {
+                let span = self.times[next] - self.times[previous];
+                let t = ((time - self.times[previous]) / span) as f32;
+                values[previous] + (values[next] - values[previous]) * t
+            }
+            (Some(row), None) | (None, Some(row)) => values[row],
+            (None, None) => f32::NAN,
+        }
+    }
+
+    /// Rows every `interval` units of data time from the first row to the
+    /// last, interpolated linearly (the series itself if `interval` isn't
+    /// positive)
+    pub fn resample(&self, interval: f64) -> Self {
+        let span = self.data_span();
+        if interval <= 0.0 || self.is_empty() {
+            return self.clone();
+        }
+        let first = self.times[0];
+        let steps = (span / interval + 1e-9).floor() as usize;
+        let mut times: Vec<f64> = (0..=steps)
+            .map(|step| first + step as f64 * interval)
+            .collect();
+        if span - steps as f64 * interval > interval * 1e-6 {
+            times.push(first + span);
+        }
+        let values = (0..self.columns.len())
+            .map(|column| {
+                times
+                    .iter()
+                    .map(|&time| self.interpolate(column, time))
+                    .collect()
+            })
+            .collect();
+        Self {
+            times,
+            values,
+            ..self.clone()
+        }
+    }
+
+    /// A track named `name` (a node property such as `"position"` or
+    /// `"opacity"`) with a keyframe at each row of `column`, its value
+    /// mapped by `map`
+    pub fn track<T: Animatable + std::fmt::Debug>(
+        &self,
+        column: &str,
+        name: &str,
+        map: impl Fn(f32) -> T,
+    ) -> Result<AnimationTrack<T>, String> {
+        self.track_columns(&[column], name, |values| map(values[0]))
+    }
+
+    /// A track with a keyframe at each row where every one of `columns`
+    /// has a value, mapped by `map` (for example x and y columns into a
+    /// position)
+    pub fn track_columns<T: Animatable + std::fmt::Debug>(
+        &self,
+        columns: &[&str],
+        name: &str,
+        map: impl Fn(&[f32]) -> T,
+    ) -> Result<AnimationTrack<T>, String> {
+        let indices = columns
+            .iter()
+            .map(|&column| {
+                self.columns
+                    .iter()
+                    .position(|name| name == column)
+                    .ok_or_else(|| format!("No column '{column}'"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut track = AnimationTrack::new(name.to_string());
+        let mut row_values = vec![0.0; indices.len()];
+        for (row, &time) in self.times.iter().enumerate() {
+            for (value, &column) in row_values.iter_mut().zip(&indices) {
+                *value = self.values[column][row];
+            }
+            if row_values.iter().any(|value| value.is_nan()) {
+                continue;
+            }
+            let keyframe = Keyframe::new(TimeValue::new(self.scene_time(time)), map(&row_values));
+            // Rows are already in time order
+            track.keyframes.push(match self.interpolation {
+                InterpolationType::Bezier => keyframe.with_tangent_mode(TangentMode::Auto),
+                interpolation => keyframe.with_interpolation(interpolation),
+            });
+        }
+        if track.is_empty() {
+            return Err(format!("No rows have values for {}", columns.join(", ")));
+        }
+        track.update_handles();
+        Ok(track)
+    }
+
+    /// A tracker following `column`, for counters and other derived values
+    pub fn tracker(&self, column: &str) -> Result<ValueTracker, String> {
+        self.track(column, "value", |value| value)
+            .map(ValueTracker::from_track)
+    }
+}
+
+/// Split a CSV line at commas outside double quotes, unquoting fields
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// A JSON value as a table cell (empty for missing values and null)
+fn json_cell(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Null) | None => String::new(),
+        Some(value) => value.to_string(),
+    }
+}
+
+/// A number, or a date as days since 1970-01-01
+fn parse_time(cell: &str) -> Option<f64> {
+    cell.parse().ok().or_else(|| parse_date(cell))
+}
+
+/// `YYYY-MM-DD`, optionally followed by `T` or a space and `HH:MM[:SS]`
+fn parse_date(text: &str) -> Option<f64> {
+    let text = text.trim_end_matches('Z');
+    let (date, clock) = match text.split_once(['T', ' ']) {
+        Some((date, clock)) => (date, Some(clock)),
+        None => (text, None),
+    };
+    let mut parts = date.split('-').map(|part| part.parse::<i64>().ok());
+    let (Some(Some(year)), Some(Some(month)), Some(Some(day)), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let seconds = match clock {
+        Some(clock) => {
+            let mut parts = clock.split(':').map(|part| part.parse::<f64>().ok());
+            let hours = parts.next()??;
+            let minutes = parts.next()??;
+            let seconds = parts.next().unwrap_or(Some(0.0))?;
+            hours * 3600.0 + minutes * 60.0 + seconds
+        }
+        None => 0.0,
+    };
+    Some(days_from_civil(year, month, day) as f64 + seconds / 86_400.0)
+}
+
+/// Days from 1970-01-01 to a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Vector3;
+
+    #[test]
+    fn test_csv_series_drives_tracks() {
+        let series = TimeSeries::from_csv(
+            "date,close,note\n\
+             2024-01-03,104,\"late, high\"\n\
+             2024-01-01,100,start\n\
+             2024-01-02,,gap\n\
+             2024-01-05,96,\n",
+            Some("date"),
+        )
+        .unwrap()
+        .fit_to(1.0, 2.0);
+
+        assert_eq!(series.columns, ["close", "note"]);
+        assert_eq!(series.times[0], 19_723.0);
+        assert_eq!(series.data_span(), 4.0);
+        assert_eq!(series.value_at("close", series.times[1]), Some(102.0));
+        assert_eq!(series.value_at("note", 19_723.0), None);
+
+        let track = series
+            .track("close", "position", |close| Vector3::new(0.0, close, 0.0))
+            .unwrap();
+        assert_eq!(track.keyframes.len(), 3);
+        assert_eq!(track.sample(TimeValue::new(1.5)).y, 102.0);
+        assert_eq!(track.sample(TimeValue::new(3.0)).y, 96.0);
+
+        let mut tracker = series.tracker("close").unwrap();
+        assert_eq!(tracker.advance(TimeValue::new(2.0)), 104.0);
+        assert!(series.tracker("volume").is_err());
+
+        let daily = series.resample(0.5);
+        assert_eq!(daily.len(), 9);
+        assert_eq!(daily.column("close").unwrap()[2], 102.0);
+    }
+
+    #[test]
+    fn test_json_rows_and_columns() {
+        let rows = TimeSeries::from_json(
+            r#"[{"t": 0, "x": 1, "y": 2}, {"t": 2, "x": 3, "y": null}, {"t": 4, "x": 5, "y": 6}]"#,
+            Some("t"),
+        )
+        .unwrap();
+        let columns =
+            TimeSeries::from_json(r#"{"x": [1, 3, 5], "y": [2, null, 6]}"#, None).unwrap();
+        assert_eq!(columns.times, [0.0, 1.0, 2.0]);
+
+        let track = rows
+            .with_interpolation(InterpolationType::Bezier)
+            .track_columns(&["x", "y"], "position", |xy| {
+                Vector3::new(xy[0], xy[1], 0.0)
+            })
+            .unwrap();
+        assert_eq!(track.keyframes.len(), 2);
+        assert_eq!(track.keyframes[1].time, TimeValue::new(4.0));
+        let middle = track.sample(TimeValue::new(2.0));
+        assert!((middle - Vector3::new(3.0, 4.0, 0.0)).length() < 1e-3);
+        assert!(columns.column("y").unwrap()[1].is_nan());
+        assert_eq!(columns.value_at("y", 1.0), Some(4.0));
+    }
+}
@@ -0,0 +1,274 @@
+//! # Vertex Deformation
+//!
+//! Effects that bend a node's geometry rather than its transform. The
+//! renderer passes every generated vertex (in the shape's local space)
+//! through the node's deformers just before uploading it:
+//!
+//! - **Wave**: a travelling sine wave across the shape
+//! - **ApplyWave**: a single pulse crossing the shape once, then rest
+//! - **SquashStretch**: stretching along the direction of motion, squashing
+//!   across it, so fast moves read as elastic
+//!
+//! Implement [`VertexDeformer`] for custom effects. Lines and rectangles
+//! are subdivided while deformed so waves can bend them, and text moves the
+//! corners of its glyph quads; other shapes keep their vertices.
+//!
+//! ```rust
+//! use diomanim::animation::deform::{SquashStretch, Wave};
+//! use diomanim::core::*;
+//! use diomanim::scene::SceneGraph;
+//!
+//! let mut scene = SceneGraph::new();
+//! scene
+//!     .add_line("string", Vector3::new(-3.0, 0.0, 0.0), Vector3::new(3.0, 0.0, 0.0), Color::WHITE, 2.0)
+//!     .deform(Wave::new(0.2, 1.5, 0.5));
+//! scene
+//!     .add_circle("ball", 0.5, Color::RED)
+//!     .deform(SquashStretch::new(0.15))
+//!     .move_to(0.0, Vector3::new(4.0, 0.0, 0.0), 1.0);
+//! ```
+
+use crate::core::Vector3;
+use std::f32::consts::TAU;
+use std::sync::Arc;
+
+/// What a deformer knows about the node being drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeformContext {
+    /// Seconds since the node's deformers started
+    pub time: f32,
+    /// How fast the node is moving, in scene units per second
+    pub velocity: Vector3,
+}
+
+/// Moves vertices of a shape before it is drawn
+pub trait VertexDeformer: std::fmt::Debug + Send + Sync {
+    /// New position of the vertex at `position` (shape-local coordinates)
+    fn deform(&self, position: Vector3, context: &DeformContext) -> Vector3;
+}
+
+/// A node's deformers with the context to apply them in, as handed to the
+/// renderer for one draw
+#[derive(Debug, Clone)]
+pub struct Deformation {
+    pub deformers: Vec<Arc<dyn VertexDeformer>>,
+    pub context: DeformContext,
+}
+
+impl Deformation {
+    /// Run `position` through every deformer in order
+    pub fn apply(&self, position: Vector3) -> Vector3 {
+        self.deformers.iter().fold(position, |position, deformer| {
+            deformer.deform(position, &self.context)
+        })
+    }
+}
+
+/// A sine wave travelling along `direction`, displacing vertices sideways
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Wave {
+    /// Largest sideways displacement, in scene units
+    pub amplitude: f32,
+    /// Distance between crests, in scene units
+    pub wavelength: f32,
+    /// Crests passing a point per second (negative travels backwards)
+    pub speed: f32,
+    /// Unit direction the wave travels along
+    pub direction: Vector3,
+}
+
+impl Wave {
+    /// A wave travelling along the x axis
+    pub fn new(amplitude: f32, wavelength: f32, speed: f32) -> Self {
+        Self {
+            amplitude,
+            wavelength,
+            speed,
+            direction: Vector3::right(),
+        }
+    }
+
+    pub fn with_direction(mut self, direction: Vector3) -> Self {
+        self.direction = direction.normalized();
+        self
+    }
+}
+
+impl VertexDeformer for Wave {
+    fn deform(&self, position: Vector3, context: &DeformContext) -> Vector3 {
+        if self.wavelength <= 0.0 {
+            return position;
+        }
+        let along = position.dot(&self.direction);
+        let side = Vector3::new(-self.direction.y, self.direction.x, 0.0);
+        let phase = TAU * (along / self.wavelength - self.speed * context.time);
+        position + side * (self.amplitude * phase.sin())
+    }
+}
+
+/// A single wave pulse passing once across a shape, leaving it at rest
+/// before and after (Manim's ApplyWave)
+///
+/// The pulse is as long as the shape's `span` along `direction` and holds
+/// `ripples` half-waves: one lifts the shape in a single hump, two send an
+/// S-curve across it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ApplyWave {
+    /// Largest sideways displacement, in scene units
+    pub amplitude: f32,
+    /// Half-waves in the pulse
+    pub ripples: f32,
+    /// Unit direction the pulse travels along
+    pub direction: Vector3,
+    /// Where the shape starts and ends along `direction`, in its local
+    /// coordinates
+    pub span: (f32, f32),
+    /// Seconds after the node's deformers started when the pulse enters
+    pub start_time: f32,
+    /// Seconds the pulse takes to cross
+    pub duration: f32,
+}
+
+impl ApplyWave {
+    /// A one-hump pulse along the x axis across `span`
+    pub fn new(amplitude: f32, span: (f32, f32), start_time: f32, duration: f32) -> Self {
+        Self {
+            amplitude,
+            ripples: 1.0,
+            direction: Vector3::right(),
+            span,
+            start_time,
+            duration,
+        }
+    }
+
+    pub fn with_ripples(mut self, ripples: f32) -> Self {
+        self.ripples = ripples;
+        self
+    }
+
+    pub fn with_direction(mut self, direction: Vector3) -> Self {
+        self.direction = direction.normalized();
+        self
+    }
+}
+
+impl VertexDeformer for ApplyWave {
+    fn deform(&self, position: Vector3, context: &DeformContext) -> Vector3 {
+        let length = self.span.1 - self.span.0;
+        let progress = (context.time - self.start_time) / self.duration.max(f32::EPSILON);
+        if length <= 0.0 || !(0.0..=1.0).contains(&progress) {
+            return position;
+        }
+        // Where the vertex sits inside the pulse (0..1), which slides from
+        // just before the span to just past it
+        let along = (position.dot(&self.direction) - self.span.0) / length;
+        let inside = along + 1.0 - 2.0 * progress;
+        if !(0.0..=1.0).contains(&inside) {
+            return position;
+        }
+        let side = Vector3::new(-self.direction.y, self.direction.x, 0.0);
+        let lift = (std::f32::consts::PI * self.ripples * inside).sin();
+        position + side * (self.amplitude * lift)
+    }
+}
+
+/// Stretch along the direction of motion and squash across it, keeping the
+/// area the same
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SquashStretch {
+    /// Extra stretch per unit of speed
+    pub amount: f32,
+    /// Upper bound on the stretch factor
+    pub max_stretch: f32,
+}
+
+impl SquashStretch {
+    pub fn new(amount: f32) -> Self {
+        Self {
+            amount,
+            max_stretch: 1.6,
+        }
+    }
+
+    pub fn with_max_stretch(mut self, max_stretch: f32) -> Self {
+        self.max_stretch = max_stretch.max(1.0);
+        self
+    }
+
+    /// Stretch factor at `speed` scene units per second
+    pub fn stretch(&self, speed: f32) -> f32 {
+        (1.0 + self.amount * speed).clamp(1.0, self.max_stretch)
+    }
+}
+
+impl VertexDeformer for SquashStretch {
+    fn deform(&self, position: Vector3, context: &DeformContext) -> Vector3 {
+        let speed = context.velocity.length();
+        let stretch = self.stretch(speed);
+        if stretch <= 1.0 {
+            return position;
+        }
+        let direction = context.velocity / speed;
+        let along = direction * position.dot(&direction);
+        let across = position - along;
+        along * stretch + across / stretch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wave_and_squash_stretch() {
+        let still = DeformContext {
+            time: 0.0,
+            velocity: Vector3::zero(),
+        };
+        let wave = Wave::new(0.5, 2.0, 1.0);
+        // A quarter wavelength in, the wave is at its crest
+        let crest = wave.deform(Vector3::new(0.5, 0.0, 0.0), &still);
+        assert!((crest.y - 0.5).abs() < 1e-5);
+        // Half a period later it is in the trough
+        let later = DeformContext { time: 0.5, ..still };
+        assert!((wave.deform(Vector3::new(0.5, 0.0, 0.0), &later).y + 0.5).abs() < 1e-5);
+
+        let squash = SquashStretch::new(0.5);
+        let point = Vector3::new(1.0, 1.0, 0.0);
+        assert_eq!(squash.deform(point, &still), point);
+        let moving = DeformContext {
+            velocity: Vector3::new(2.0, 0.0, 0.0),
+            ..still
+        };
+        let deformation = Deformation {
+            deformers: vec![Arc::new(squash)],
+            context: moving,
+        };
+        let deformed = deformation.apply(point);
+        assert!((deformed.x - 1.6).abs() < 1e-5);
+        assert!((deformed.x * deformed.y - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_apply_wave_crosses_once() {
+        let wave = ApplyWave::new(0.2, (-1.0, 1.0), 1.0, 2.0);
+        let at = |time: f32| DeformContext {
+            time,
+            velocity: Vector3::zero(),
+        };
+        let point = Vector3::new(0.0, 0.5, 0.0);
+        // At rest before and after the pulse
+        assert_eq!(wave.deform(point, &at(0.5)), point);
+        assert_eq!(wave.deform(point, &at(3.5)), point);
+        // Halfway the hump's crest is over the middle of the span
+        assert!((wave.deform(point, &at(2.0)).y - 0.7).abs() < 1e-5);
+        // ...and hasn't reached the far end yet a quarter of the way in
+        let end = Vector3::new(1.0, 0.0, 0.0);
+        assert_eq!(wave.deform(end, &at(1.4)), end);
+
+        let s_curve = wave.with_ripples(2.0);
+        let quarter = Vector3::new(-0.5, 0.0, 0.0);
+        assert!((s_curve.deform(quarter, &at(2.0)).y - 0.2).abs() < 1e-5);
+    }
+}
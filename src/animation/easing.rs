@@ -15,13 +15,14 @@
 //! let value = ease_in_out_cubic(t); // Smooth acceleration and deceleration
 //! ```
 
+use serde::{Deserialize, Serialize};
 use std::f32::consts::PI;
 
 /// Easing function type
 pub type EasingFn = fn(f32) -> f32;
 
 /// Enum of all available easing functions
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EasingType {
     Linear,
 
@@ -75,7 +76,9 @@ pub enum EasingType {
     EaseOutBounce,
     EaseInOutBounce,
 
-    // Custom
+    // Custom; functions can't be serialized, so serializing a `Custom`
+    // easing fails
+    #[serde(skip)]
     Custom(EasingFn),
 }
 
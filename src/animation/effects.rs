@@ -6,14 +6,26 @@
 //! ## Phase 1 Effects
 //! - Opacity animations (FadeIn, FadeOut)
 //! - Scale animations (GrowFromCenter, ShrinkToCenter)
-//! - Combined effects (Create, Uncreate)
+//! - Geometry reveal (Create, Uncreate)
+//! - Emphasis (Indicate, Wiggle)
+//! - Color changes (FadeToColor, highlighting whole nodes or their glyphs)
+//! - Clip mask animations (IrisIn, IrisOut)
+//! - Render target camera pans (TargetPan)
 //!
 //! ## Phase 2 Effects
-//! - Transform animations (MoveTo, Shift, Rotate)
-//! - Path animations (Write)
+//! - Transform animations (MoveTo, Shift, Rotate, MoveToTarget, Restore)
+//! - Path animations (Write, MoveAlongPath)
+//!
+//! ## Grouped Effects
+//! - Staggered starts across many targets (LaggedStart)
 
-use crate::animation::property::{AnimationClip, AnimationTrack, Keyframe};
-use crate::core::{TimeValue, Vector3};
+use crate::animation::property::{
+    AnimationClip, AnimationInstance, AnimationTrack, InterpolationType, Keyframe,
+};
+use crate::core::transform::Quaternion;
+use crate::core::{unwrapped_angles, AnimationRateType, Color, Path, TimeValue, Vector3};
+use std::f32::consts::TAU;
+use std::ops::Range;
 
 /// Create a FadeIn animation that animates opacity from 0 to 1
 pub fn fade_in(duration: f32) -> AnimationClip {
@@ -126,70 +138,290 @@ pub fn shrink_to_center(duration: f32) -> AnimationClip {
     clip
 }
 
-/// Create a combined FadeIn + GrowFromCenter effect
-pub fn create(duration: f32) -> AnimationClip {
-    let mut clip = AnimationClip::new("Create".to_string());
+/// Create an IrisIn animation that opens a node's clip mask from 0 to full size
+pub fn iris_in(duration: f32) -> AnimationClip {
+    let mut clip = AnimationClip::new("IrisIn".to_string());
+    let mut track = AnimationTrack::new("clip_scale".to_string());
 
-    // Opacity track
-    let mut opacity_track = AnimationTrack::new("opacity".to_string());
-    opacity_track.add_keyframe(Keyframe::new(
+    track.add_keyframe(Keyframe::new(
         TimeValue::new(0.0),
         Vector3::new(0.0, 0.0, 0.0),
     ));
-    opacity_track.add_keyframe(Keyframe::new(
+    track.add_keyframe(Keyframe::new(
         TimeValue::new(duration),
         Vector3::new(1.0, 0.0, 0.0),
     ));
 
-    // Scale track
-    let mut scale_track = AnimationTrack::new("scale".to_string());
-    scale_track.add_keyframe(Keyframe::new(
+    clip.add_track(track);
+    clip.loop_animation = false;
+    clip
+}
+
+/// Create an IrisOut animation that closes a node's clip mask to nothing
+pub fn iris_out(duration: f32) -> AnimationClip {
+    let mut clip = AnimationClip::new("IrisOut".to_string());
+    let mut track = AnimationTrack::new("clip_scale".to_string());
+
+    track.add_keyframe(Keyframe::new(
         TimeValue::new(0.0),
-        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(1.0, 0.0, 0.0),
     ));
-    scale_track.add_keyframe(Keyframe::new(
+    track.add_keyframe(Keyframe::new(
         TimeValue::new(duration),
-        Vector3::new(1.0, 1.0, 1.0),
+        Vector3::new(0.0, 0.0, 0.0),
     ));
 
-    clip.add_track(opacity_track);
-    clip.add_track(scale_track);
+    clip.add_track(track);
     clip.loop_animation = false;
     clip
 }
 
-/// Create an Uncreate animation (reverse of Create)
+/// Create a TargetPan animation that moves a render target's camera center
+/// from `from` to `to`
+pub fn target_pan(from: Vector3, to: Vector3, duration: f32) -> AnimationClip {
+    let mut clip = AnimationClip::new("TargetPan".to_string());
+    let mut track = AnimationTrack::new("target_center".to_string());
+
+    track.add_keyframe(Keyframe::new(TimeValue::new(0.0), from));
+    track.add_keyframe(Keyframe::new(TimeValue::new(duration), to));
+
+    clip.add_track(track);
+    clip.loop_animation = false;
+    clip
+}
+
+/// Draw the node's geometry progressively: circles sweep an arc, polygons
+/// fill in edge by edge and lines grow from their start (see
+/// [`crate::scene::reveal`])
+pub fn create(duration: f32) -> AnimationClip {
+    progress("Create", 0.0, 1.0, duration)
+}
+
+/// Draw `length` scene units of outline (see
+/// [`Renderable::outline`](crate::scene::Renderable::outline)) at no more
+/// than `speed` units per second, so long shapes take longer than short ones
+///
+/// ```rust
+/// use diomanim::animation::effects::create_at_speed;
+///
+/// let clip = create_at_speed(3.0, 2.0);
+/// assert_eq!(clip.duration().value, 1.5);
+/// ```
+pub fn create_at_speed(length: f32, speed: f32) -> AnimationClip {
+    create(length.max(0.0) / speed.max(f32::EPSILON))
+}
+
+/// Erase the node's geometry back along its outline (reverse of Create)
 pub fn uncreate(duration: f32) -> AnimationClip {
-    let mut clip = AnimationClip::new("Uncreate".to_string());
+    progress("Uncreate", 1.0, 0.0, duration)
+}
 
-    // Opacity track
-    let mut opacity_track = AnimationTrack::new("opacity".to_string());
-    opacity_track.add_keyframe(Keyframe::new(
+/// Animate the drawn share of a node's geometry from `from` to `to`
+fn progress(name: &str, from: f32, to: f32, duration: f32) -> AnimationClip {
+    let mut clip = AnimationClip::new(name.to_string());
+    let mut track = AnimationTrack::new("progress".to_string());
+    track.add_keyframe(Keyframe::new(
         TimeValue::new(0.0),
-        Vector3::new(1.0, 0.0, 0.0),
+        Vector3::new(from, 0.0, 0.0),
     ));
-    opacity_track.add_keyframe(Keyframe::new(
+    track.add_keyframe(Keyframe::new(
         TimeValue::new(duration),
-        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(to, 0.0, 0.0),
     ));
+    clip.add_track(track);
+    clip.loop_animation = false;
+    clip
+}
 
-    // Scale track
+/// Briefly scale a node up by `scale_factor` and tint it `highlight`, then
+/// return to `scale` and `color` (Manim's Indicate)
+pub fn indicate(
+    scale: Vector3,
+    color: Color,
+    highlight: Color,
+    scale_factor: f32,
+    duration: f32,
+) -> AnimationClip {
+    let mut clip = AnimationClip::new("Indicate".to_string());
     let mut scale_track = AnimationTrack::new("scale".to_string());
-    scale_track.add_keyframe(Keyframe::new(
-        TimeValue::new(0.0),
-        Vector3::new(1.0, 1.0, 1.0),
-    ));
-    scale_track.add_keyframe(Keyframe::new(
-        TimeValue::new(duration),
-        Vector3::new(0.0, 0.0, 0.0),
-    ));
+    let mut color_track = AnimationTrack::new("color".to_string());
+    for (time, scale, color) in [
+        (0.0, scale, color),
+        (duration / 2.0, scale * scale_factor, highlight),
+        (duration, scale, color),
+    ] {
+        let time = TimeValue::new(time);
+        scale_track.add_keyframe(
+            Keyframe::new(time, scale).with_interpolation(InterpolationType::EaseInOut),
+        );
+        color_track.add_keyframe(
+            Keyframe::new(time, color).with_interpolation(InterpolationType::EaseInOut),
+        );
+    }
+    clip.add_track(scale_track);
+    clip.add_track(color_track);
+    clip.loop_animation = false;
+    clip
+}
+
+/// Keyframes per second of a wiggle
+const WIGGLE_KEYFRAMES_PER_SECOND: f32 = 60.0;
 
-    clip.add_track(opacity_track);
+/// Rock a node `wiggles` times by up to `angle` radians while it swells to
+/// `scale_factor` and back, settling on its rest `rotation` and `scale`
+/// (Manim's Wiggle)
+///
+/// ```rust
+/// use diomanim::animation::effects::wiggle;
+/// use diomanim::core::{transform::Quaternion, Vector3};
+///
+/// let clip = wiggle(Quaternion::identity(), Vector3::one(), 1.1, 0.1, 6.0, 2.0);
+/// assert_eq!(clip.tracks.len(), 2); // rotation + scale
+/// ```
+pub fn wiggle(
+    rotation: Quaternion,
+    scale: Vector3,
+    scale_factor: f32,
+    angle: f32,
+    wiggles: f32,
+    duration: f32,
+) -> AnimationClip {
+    let mut clip = AnimationClip::new("Wiggle".to_string());
+    let count = ((duration * WIGGLE_KEYFRAMES_PER_SECOND).ceil() as usize).max(2);
+    let mut rotation_track = AnimationTrack::new("rotation".to_string());
+    let mut scale_track = AnimationTrack::new("scale".to_string());
+    for i in 0..count {
+        let t = i as f32 / (count - 1) as f32;
+        // Swells out and back, smoothly, with the rocking inside it
+        let envelope = AnimationRateType::smooth(AnimationRateType::there_and_back(t));
+        let rock = angle * envelope * (TAU * wiggles * t).sin();
+        let time = TimeValue::new(duration * t);
+        rotation_track.add_keyframe(Keyframe::new(
+            time,
+            rotation * Quaternion::from_rotation_z(rock),
+        ));
+        scale_track.add_keyframe(Keyframe::new(
+            time,
+            scale * (1.0 + (scale_factor - 1.0) * envelope),
+        ));
+    }
+    clip.add_track(rotation_track);
     clip.add_track(scale_track);
     clip.loop_animation = false;
     clip
 }
 
+/// Change a node's color from `from` to `to` (Manim's FadeToColor)
+pub fn fade_to_color(from: Color, to: Color, duration: f32) -> AnimationClip {
+    color_change("FadeToColor", "color".to_string(), from, to, duration)
+}
+
+/// Tint a node `highlight` and back to `color`, easing both ways
+pub fn highlight(color: Color, highlight: Color, duration: f32) -> AnimationClip {
+    color_there_and_back("Highlight", "color".to_string(), color, highlight, duration)
+}
+
+/// Change the color of some of a text or math node's glyphs from `from` to
+/// `to`, leaving the rest of the text as it is
+///
+/// `glyphs` indexes the characters of
+/// [`Renderable::glyph_text`](crate::scene::Renderable::glyph_text), e.g.
+/// from [`Renderable::find_glyphs`](crate::scene::Renderable::find_glyphs).
+pub fn fade_glyphs_to_color(
+    glyphs: Range<usize>,
+    from: Color,
+    to: Color,
+    duration: f32,
+) -> AnimationClip {
+    color_change(
+        "FadeToColor",
+        glyph_color_track(&glyphs),
+        from,
+        to,
+        duration,
+    )
+}
+
+/// Tint some of a text or math node's glyphs `highlight` and back to
+/// `color`, to pick out one term of an equation at a time
+///
+/// ```rust
+/// use diomanim::animation::effects::highlight_glyphs;
+/// use diomanim::core::Color;
+/// use diomanim::scene::Renderable;
+///
+/// let label = Renderable::Text {
+///     content: "a + b = c".to_string(),
+///     font_size: 48.0,
+///     color: Color::WHITE,
+/// };
+/// let term = label.find_glyphs("b").unwrap();
+/// let clip = highlight_glyphs(term, Color::WHITE, Color::YELLOW, 1.0);
+/// assert_eq!(clip.tracks.len(), 1);
+/// ```
+pub fn highlight_glyphs(
+    glyphs: Range<usize>,
+    color: Color,
+    highlight: Color,
+    duration: f32,
+) -> AnimationClip {
+    color_there_and_back(
+        "Highlight",
+        glyph_color_track(&glyphs),
+        color,
+        highlight,
+        duration,
+    )
+}
+
+/// Name of the color track recoloring `glyphs` of a node's text
+pub fn glyph_color_track(glyphs: &Range<usize>) -> String {
+    format!("glyph_color[{}..{}]", glyphs.start, glyphs.end)
+}
+
+/// The glyphs a track named by [`glyph_color_track`] recolors
+pub fn parse_glyph_color_track(name: &str) -> Option<Range<usize>> {
+    let range = name.strip_prefix("glyph_color[")?.strip_suffix(']')?;
+    let (start, end) = range.split_once("..")?;
+    Some(start.parse().ok()?..end.parse().ok()?)
+}
+
+/// A color track named `track` easing from `from` to `to`
+fn color_change(name: &str, track: String, from: Color, to: Color, duration: f32) -> AnimationClip {
+    let mut clip = AnimationClip::new(name.to_string());
+    let mut color_track = AnimationTrack::new(track);
+    for (time, color) in [(0.0, from), (duration, to)] {
+        color_track.add_keyframe(
+            Keyframe::new(TimeValue::new(time), color)
+                .with_interpolation(InterpolationType::EaseInOut),
+        );
+    }
+    clip.add_track(color_track);
+    clip.loop_animation = false;
+    clip
+}
+
+/// A color track named `track` easing to `highlight` halfway and back
+fn color_there_and_back(
+    name: &str,
+    track: String,
+    color: Color,
+    highlight: Color,
+    duration: f32,
+) -> AnimationClip {
+    let mut clip = AnimationClip::new(name.to_string());
+    let mut color_track = AnimationTrack::new(track);
+    for (time, color) in [(0.0, color), (duration / 2.0, highlight), (duration, color)] {
+        color_track.add_keyframe(
+            Keyframe::new(TimeValue::new(time), color)
+                .with_interpolation(InterpolationType::EaseInOut),
+        );
+    }
+    clip.add_track(color_track);
+    clip.loop_animation = false;
+    clip
+}
+
 // ============================================================================
 // PHASE 2 EFFECTS - Transform Animations
 // ============================================================================
@@ -312,6 +544,82 @@ pub fn transform(
     clip
 }
 
+/// Animate a node into its target state, set up with
+/// [`SceneNode::generate_target`](crate::scene::SceneNode::generate_target)
+/// (Manim's MoveToTarget)
+///
+/// Transform, opacity and shape all move at once, starting from wherever
+/// the node is when the animation starts; see [`crate::scene::state`].
+pub fn move_to_target(duration: f32) -> AnimationClip {
+    transition("MoveToTarget", "move_to_target", duration)
+}
+
+/// Animate a node back to the state it had when
+/// [`SceneNode::save_state`](crate::scene::SceneNode::save_state) was
+/// called, e.g. to undo a run of emphasis effects (Manim's Restore)
+pub fn restore(duration: f32) -> AnimationClip {
+    transition("Restore", "restore", duration)
+}
+
+/// A clip easing the transition track `track` from 0 to 1
+fn transition(name: &str, track: &str, duration: f32) -> AnimationClip {
+    let mut clip = AnimationClip::new(name.to_string());
+    let mut track = AnimationTrack::new(track.to_string());
+    track.add_keyframe(
+        Keyframe::new(TimeValue::new(0.0), Vector3::zero())
+            .with_interpolation(InterpolationType::EaseInOut),
+    );
+    track.add_keyframe(Keyframe::new(
+        TimeValue::new(duration),
+        Vector3::new(1.0, 0.0, 0.0),
+    ));
+    clip.add_track(track);
+    clip.loop_animation = false;
+    clip
+}
+
+/// Keyframes per second of a path-following animation
+const PATH_KEYFRAMES_PER_SECOND: f32 = 60.0;
+
+/// Move along `path` at constant speed, optionally turning to face the
+/// direction of travel
+///
+/// The path is sampled by arc length, so speed doesn't change on tight
+/// curves. With `rotate_with_tangent` a rotation track keeps the object's
+/// x axis along the path's tangent.
+///
+/// ```rust
+/// use diomanim::animation::effects::move_along_path;
+/// use diomanim::core::{Path, Vector3};
+///
+/// let orbit = move_along_path(&Path::circle(Vector3::zero(), 3.0), 4.0, true);
+/// assert_eq!(orbit.tracks.len(), 2); // position + rotation
+/// ```
+pub fn move_along_path(path: &Path, duration: f32, rotate_with_tangent: bool) -> AnimationClip {
+    let mut clip = AnimationClip::new("MoveAlongPath".to_string());
+    let count = ((duration * PATH_KEYFRAMES_PER_SECOND).ceil() as usize).max(2);
+    let samples = path.sampler().even_samples(count);
+    let time = |i: usize| TimeValue::new(duration * i as f32 / (count - 1) as f32);
+
+    let mut position = AnimationTrack::new("position".to_string());
+    for (i, &(point, _)) in samples.iter().enumerate() {
+        position.add_keyframe(Keyframe::new(time(i), point));
+    }
+    clip.add_track(position);
+
+    if rotate_with_tangent {
+        let mut rotation = AnimationTrack::new("rotation".to_string());
+        let angles = unwrapped_angles(samples.iter().map(|&(_, tangent)| tangent));
+        for (i, angle) in angles.into_iter().enumerate() {
+            rotation.add_keyframe(Keyframe::new(time(i), Vector3::new(0.0, 0.0, angle)));
+        }
+        clip.add_track(rotation);
+    }
+
+    clip.loop_animation = false;
+    clip
+}
+
 /// Spin animation - continuous rotation
 ///
 /// # Arguments
@@ -322,6 +630,86 @@ pub fn spin(rotations: f32, duration: f32) -> AnimationClip {
     rotate(0.0, end_angle, duration)
 }
 
+/// One effect per target, each starting a fixed lag after the previous one
+///
+/// Built by [`staggered`]; the scene's `add_staggered` and `play_staggered`
+/// attach it to nodes.
+#[derive(Debug)]
+pub struct Stagger<T> {
+    /// Targets with their clip and start offset, in start order
+    pub entries: Vec<(T, AnimationClip, f32)>,
+}
+
+impl<T: Copy> Stagger<T> {
+    /// Time from the first start until the last clip ends
+    pub fn duration(&self) -> f32 {
+        self.entries
+            .iter()
+            .map(|(_, clip, offset)| offset + clip.duration().seconds())
+            .fold(0.0, f32::max)
+    }
+
+    /// Instances for every target when the group starts at `start_time`
+    ///
+    /// Each instance holds its first frame until its turn, so targets of a
+    /// staggered fade-in stay hidden instead of popping in.
+    pub fn instances(self, start_time: f32) -> impl Iterator<Item = (T, AnimationInstance)> {
+        self.entries.into_iter().map(move |(target, clip, offset)| {
+            let start = TimeValue::new(start_time + offset);
+            (
+                target,
+                AnimationInstance::new(clip, start).with_fill_before(true),
+            )
+        })
+    }
+}
+
+/// Apply an effect to each target with start times `lag` seconds apart,
+/// like Manim's LaggedStart
+///
+/// `effect` builds the clip for each target from its index and the target.
+///
+/// ```rust
+/// use diomanim::animation::effects;
+///
+/// let stagger = effects::staggered(&["a", "b", "c"], |_, _| effects::fade_in(1.0), 0.25);
+/// assert_eq!(stagger.entries[2].2, 0.5);
+/// assert_eq!(stagger.duration(), 1.5);
+/// ```
+pub fn staggered<T: Copy>(
+    targets: &[T],
+    mut effect: impl FnMut(usize, T) -> AnimationClip,
+    lag: f32,
+) -> Stagger<T> {
+    let entries = targets
+        .iter()
+        .enumerate()
+        .map(|(index, &target)| (target, effect(index, target), index as f32 * lag))
+        .collect();
+    Stagger { entries }
+}
+
+/// [`staggered`] with the lag given as a fraction of each clip's duration
+/// (Manim's `lag_ratio`): 0 plays all at once, 1 plays one after another
+pub fn lagged<T: Copy>(
+    targets: &[T],
+    mut effect: impl FnMut(usize, T) -> AnimationClip,
+    lag_ratio: f32,
+) -> Stagger<T> {
+    let mut offset = 0.0;
+    let entries = targets
+        .iter()
+        .enumerate()
+        .map(|(index, &target)| {
+            let clip = effect(index, target);
+            let start = offset;
+            offset += clip.duration().seconds() * lag_ratio;
+            (target, clip, start)
+        })
+        .collect();
+    Stagger { entries }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -345,7 +733,7 @@ mod tests {
     fn test_create() {
         let anim = create(1.5);
         assert_eq!(anim.name, "Create");
-        assert_eq!(anim.tracks.len(), 2); // opacity + scale
+        assert_eq!(anim.tracks.len(), 1); // progress
     }
 
     #[test]
@@ -371,6 +759,74 @@ mod tests {
         assert_eq!(anim.tracks.len(), 1);
     }
 
+    #[test]
+    fn test_move_along_path() {
+        let path = Path::polyline(&[
+            Vector3::zero(),
+            Vector3::new(3.0, 0.0, 0.0),
+            Vector3::new(3.0, 1.0, 0.0),
+        ]);
+        let anim = move_along_path(&path, 2.0, true);
+        assert_eq!(anim.name, "MoveAlongPath");
+        assert_eq!(anim.duration(), TimeValue::new(2.0));
+
+        // Constant speed: three quarters of the time covers the first leg
+        let position = anim.track::<Vector3>("position").unwrap();
+        let at = position.sample(TimeValue::new(1.5));
+        assert!(at.distance(&Vector3::new(3.0, 0.0, 0.0)) < 0.01);
+
+        let rotation = anim.track::<Vector3>("rotation").unwrap();
+        assert!(rotation.sample(TimeValue::new(0.5)).z.abs() < 1e-5);
+        let turned = rotation.sample(TimeValue::new(2.0)).z;
+        assert!((turned - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_lagged() {
+        let stagger = lagged(&[1, 2, 3, 4], |index, _| fade_in(1.0 + index as f32), 0.5);
+        let offsets: Vec<f32> = stagger.entries.iter().map(|entry| entry.2).collect();
+        assert_eq!(offsets, vec![0.0, 0.5, 1.5, 3.0]);
+        assert_eq!(stagger.duration(), 7.0);
+
+        let instances: Vec<_> = stagger.instances(2.0).collect();
+        assert_eq!(instances[3].0, 4);
+        assert_eq!(instances[3].1.start_time, TimeValue::new(5.0));
+        assert!(instances[3].1.fill_before);
+    }
+
+    #[test]
+    fn test_wiggle_settles_at_rest() {
+        let rest = Quaternion::from_rotation_z(0.5);
+        let anim = wiggle(rest, Vector3::one(), 1.2, 0.1, 4.0, 1.0);
+        assert_eq!(anim.name, "Wiggle");
+
+        let rotation = anim.track::<Quaternion>("rotation").unwrap();
+        let scale = anim.track::<Vector3>("scale").unwrap();
+        for time in [0.0, 1.0] {
+            let at = TimeValue::new(time);
+            assert!((rotation.sample(at).dot(&rest).abs() - 1.0).abs() < 1e-5);
+            assert!(scale.sample(at).distance(&Vector3::one()) < 1e-5);
+        }
+        // Fully swollen halfway through
+        assert!((scale.sample(TimeValue::new(0.5)).x - 1.2).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_glyph_highlight() {
+        let clip = highlight_glyphs(2..5, Color::WHITE, Color::YELLOW, 2.0);
+        assert_eq!(clip.name, "Highlight");
+        let track = clip.track::<Color>("glyph_color[2..5]").unwrap();
+        assert_eq!(track.sample(TimeValue::new(1.0)), Color::YELLOW);
+        assert_eq!(track.sample(TimeValue::new(2.0)), Color::WHITE);
+
+        assert_eq!(
+            parse_glyph_color_track(&glyph_color_track(&(2..5))),
+            Some(2..5)
+        );
+        assert_eq!(parse_glyph_color_track("color"), None);
+        assert_eq!(parse_glyph_color_track("glyph_color[a..5]"), None);
+    }
+
     #[test]
     fn test_transform() {
         let anim = transform(
@@ -9,6 +9,12 @@
 //! - **AnimationTrack**: A single animated property (e.g., position, rotation, scale)
 //! - **Keyframe**: A specific value at a specific time point
 //! - **InterpolationType**: How values are interpolated between keyframes (Linear, Ease, etc.)
+//! - **Handle**: A Bezier control point shaping a curve between keyframes, placed
+//!   by hand or by the keyframe's **TangentMode**
+//! - **ProceduralModifier**: Noise and pulse motion layered on a node's transform
+//! - **VertexDeformer**: Per-vertex bending of a node's geometry (waves, squash and stretch)
+//! - **ValueTracker**: A single animated number for counters and other derived values
+//! - **TimeSeries**: CSV/JSON data whose columns become tracks and trackers
 //! - **AnimationController**: Manages multiple concurrent animations
 //! - **Timer**: Utility for timing and progress tracking
 //!
@@ -35,23 +41,32 @@
 //! clip.add_track(track);
 //! ```
 
+pub mod data;
+pub mod deform;
 pub mod easing;
 pub mod effects;
+pub mod procedural;
 pub mod property;
+pub mod tracker;
 
 use crate::core::TimeValue;
 use property::{AnimationClip, AnimationInstance};
 
 // Re-export key types
+pub use data::TimeSeries;
 pub use effects::*;
-pub use property::{AnimationSample, AnimationTrack, InterpolationType, Keyframe};
+pub use procedural::{ModifierKind, ModifierOffset, ProceduralModifier};
+pub use property::{
+    AnimationSample, AnimationTrack, Handle, InterpolationType, Keyframe, PlayMode, TangentMode,
+};
+pub use tracker::ValueTracker;
 
 // Timer for animation control
 pub struct Timer {
     duration: TimeValue,
     elapsed: TimeValue,
     is_running: bool,
-    start_time: Option<std::time::Instant>,
+    start_time: Option<instant::Instant>,
 }
 
 impl Timer {
@@ -66,7 +81,7 @@ impl Timer {
 
     pub fn start(&mut self) {
         self.is_running = true;
-        self.start_time = Some(std::time::Instant::now());
+        self.start_time = Some(instant::Instant::now());
     }
 
     pub fn stop(&mut self) {
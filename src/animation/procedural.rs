@@ -0,0 +1,209 @@
+//! # Procedural Modifiers
+//!
+//! Motion computed from time instead of keyframes, layered on top of a
+//! node's animated transform:
+//!
+//! - **Jitter**: Perlin-noise drift of the position
+//! - **Wiggle**: Perlin-noise rocking of the rotation
+//! - **Breathe**: sinusoidal pulsing of the scale
+//!
+//! Noise is seeded, so a render is the same every time; give nodes different
+//! seeds to keep them from moving in lockstep.
+//!
+//! ```rust
+//! use diomanim::animation::procedural::ProceduralModifier;
+//! use diomanim::core::*;
+//! use diomanim::scene::SceneGraph;
+//!
+//! let mut scene = SceneGraph::new();
+//! scene
+//!     .add_circle("firefly", 0.1, Color::YELLOW)
+//!     .modifier(ProceduralModifier::jitter(0.3, 0.5).with_seed(7))
+//!     .modifier(ProceduralModifier::breathe(0.1, 1.0));
+//! ```
+
+use crate::core::Vector3;
+use std::f32::consts::TAU;
+
+/// What a modifier animates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModifierKind {
+    /// Position offset in x and y, up to `amplitude` scene units
+    Jitter,
+    /// Z rotation offset, up to `amplitude` radians
+    Wiggle,
+    /// Scale factor swinging `amplitude` around 1
+    Breathe,
+}
+
+/// A time-driven offset applied to a node's transform
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProceduralModifier {
+    pub kind: ModifierKind,
+    /// Largest offset the modifier produces
+    pub amplitude: f32,
+    /// Rough number of swings per second
+    pub frequency: f32,
+    /// Picks the noise pattern (jitter, wiggle) or phase (breathe)
+    pub seed: u32,
+}
+
+/// The combined transform offset of a node's modifiers at one moment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModifierOffset {
+    /// Added to the position
+    pub position: Vector3,
+    /// Added to the z rotation, in radians
+    pub rotation: f32,
+    /// Multiplies the scale
+    pub scale: f32,
+}
+
+impl Default for ModifierOffset {
+    fn default() -> Self {
+        Self {
+            position: Vector3::zero(),
+            rotation: 0.0,
+            scale: 1.0,
+        }
+    }
+}
+
+impl ModifierOffset {
+    /// Whether the offset leaves a transform unchanged
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Apply `other` on top of this offset
+    pub fn combine(self, other: Self) -> Self {
+        Self {
+            position: self.position + other.position,
+            rotation: self.rotation + other.rotation,
+            scale: self.scale * other.scale,
+        }
+    }
+}
+
+impl ProceduralModifier {
+    pub fn new(kind: ModifierKind, amplitude: f32, frequency: f32) -> Self {
+        Self {
+            kind,
+            amplitude,
+            frequency,
+            seed: 0,
+        }
+    }
+
+    /// Noisy drift of up to `amplitude` scene units
+    pub fn jitter(amplitude: f32, frequency: f32) -> Self {
+        Self::new(ModifierKind::Jitter, amplitude, frequency)
+    }
+
+    /// Noisy rocking of up to `amplitude` radians
+    pub fn wiggle(amplitude: f32, frequency: f32) -> Self {
+        Self::new(ModifierKind::Wiggle, amplitude, frequency)
+    }
+
+    /// Scale pulsing between `1 - amplitude` and `1 + amplitude`
+    pub fn breathe(amplitude: f32, frequency: f32) -> Self {
+        Self::new(ModifierKind::Breathe, amplitude, frequency)
+    }
+
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The offset at `time` seconds after the modifier was attached
+    pub fn sample(&self, time: f32) -> ModifierOffset {
+        let x = time * self.frequency;
+        match self.kind {
+            ModifierKind::Jitter => ModifierOffset {
+                position: Vector3::new(
+                    perlin(self.seed, x) * self.amplitude,
+                    perlin(self.seed.wrapping_add(1), x) * self.amplitude,
+                    0.0,
+                ),
+                ..ModifierOffset::default()
+            },
+            ModifierKind::Wiggle => ModifierOffset {
+                rotation: perlin(self.seed, x) * self.amplitude,
+                ..ModifierOffset::default()
+            },
+            ModifierKind::Breathe => {
+                let phase = unit_hash(self.seed, 0) * TAU;
+                ModifierOffset {
+                    scale: 1.0 + self.amplitude * (TAU * x + phase).sin(),
+                    ..ModifierOffset::default()
+                }
+            }
+        }
+    }
+}
+
+/// Combined offset of `modifiers` at `time`
+pub fn sample_all(modifiers: &[ProceduralModifier], time: f32) -> ModifierOffset {
+    modifiers
+        .iter()
+        .fold(ModifierOffset::default(), |offset, modifier| {
+            offset.combine(modifier.sample(time))
+        })
+}
+
+/// Deterministic value in `[0, 1)` for lattice point `i` of noise `seed`
+pub(crate) fn unit_hash(seed: u32, i: i32) -> f32 {
+    // Integer mixing from the murmur3 finalizer
+    let mut h = seed.wrapping_mul(0x9E37_79B9) ^ (i as u32).wrapping_mul(0x85EB_CA6B);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7FEB_352D);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846C_A68B);
+    h ^= h >> 16;
+    (h >> 8) as f32 / (1u32 << 24) as f32
+}
+
+/// One-dimensional Perlin gradient noise, roughly in `[-1, 1]` and zero at
+/// whole numbers
+pub fn perlin(seed: u32, x: f32) -> f32 {
+    let cell = x.floor();
+    let t = x - cell;
+    let i = cell as i32;
+    let gradient = |i: i32| unit_hash(seed, i) * 2.0 - 1.0;
+
+    let left = gradient(i) * t;
+    let right = gradient(i + 1) * (t - 1.0);
+    // Quintic fade keeps the slope continuous across cells
+    let fade = t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    // Gradients of +-1 peak at 0.5, so double to fill [-1, 1]
+    2.0 * (left + (right - left) * fade)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modifiers_are_deterministic_and_bounded() {
+        let jitter = ProceduralModifier::jitter(0.5, 2.0).with_seed(3);
+        assert_eq!(jitter.sample(1.37), jitter.sample(1.37));
+        assert_ne!(jitter.sample(1.37), jitter.with_seed(4).sample(1.37));
+
+        for step in 0..500 {
+            let time = step as f32 * 0.013;
+            let offset = jitter.sample(time);
+            assert!(offset.position.x.abs() <= 0.5 && offset.position.y.abs() <= 0.5);
+            assert!(perlin(9, time * 7.0).abs() <= 1.0);
+        }
+        // Noise is continuous
+        let (a, b) = (perlin(1, 2.4999), perlin(1, 2.5001));
+        assert!((a - b).abs() < 1e-3);
+
+        let breathe = ProceduralModifier::breathe(0.2, 1.0);
+        let offset = sample_all(&[breathe, ProceduralModifier::wiggle(0.1, 1.0)], 0.0);
+        assert!((0.8..=1.2).contains(&offset.scale));
+        assert!(offset.position == Vector3::zero());
+        // Wiggle noise is zero on the lattice
+        assert!(offset.rotation.abs() < 1e-6);
+    }
+}
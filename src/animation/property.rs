@@ -1,12 +1,27 @@
 // Property animation system for animating object properties over time
-use crate::core::TimeValue;
+use crate::animation::easing::EasingType;
+use crate::core::{ColorInterpolation, TimeValue};
+use serde::{Deserialize, Serialize};
 use std::any::Any;
 
+/// Keyframes less than this many seconds apart are at the same time
+pub const KEYFRAME_TIME_EPSILON: f32 = 1e-4;
+
+/// Bisection steps finding where on a Bezier segment a time falls
+const BEZIER_SOLVE_STEPS: usize = 32;
+
 /// Trait for types that can be animated/interpolated
 pub trait Animatable: Clone + Send + Sync + 'static {
     /// Linear interpolation between self and other at time t (0.0 to 1.0)
     fn lerp(&self, other: &Self, t: f32) -> Self;
 
+    /// Interpolate through the color space `space`; only colors have more
+    /// than one way to blend
+    fn lerp_in(&self, other: &Self, t: f32, space: ColorInterpolation) -> Self {
+        let _ = space;
+        self.lerp(other, t)
+    }
+
     /// Return a default/zero value for this type
     fn default_value() -> Self;
 }
@@ -26,6 +41,28 @@ impl Animatable for crate::core::Vector3 {
     }
 }
 
+// Implement Animatable for plain numbers (see ValueTracker)
+impl Animatable for f32 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+
+    fn default_value() -> Self {
+        0.0
+    }
+}
+
+// Implement Animatable for rotations, taking the shortest arc
+impl Animatable for crate::core::transform::Quaternion {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self.slerp(other, t)
+    }
+
+    fn default_value() -> Self {
+        Self::identity()
+    }
+}
+
 // Implement Animatable for Color
 impl Animatable for crate::core::Color {
     fn lerp(&self, other: &Self, t: f32) -> Self {
@@ -37,18 +74,58 @@ impl Animatable for crate::core::Color {
         }
     }
 
+    fn lerp_in(&self, other: &Self, t: f32, space: ColorInterpolation) -> Self {
+        self.interpolate(other, t, space)
+    }
+
     fn default_value() -> Self {
         Self::BLACK
     }
 }
 
+/// A Bezier control point of a keyframe, `time` seconds before it (in
+/// handle) or after it (out handle)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Handle<T> {
+    pub time: f32,
+    pub value: T,
+}
+
+impl<T> Handle<T> {
+    pub fn new(time: f32, value: T) -> Self {
+        Self { time, value }
+    }
+}
+
+/// How [`AnimationTrack::update_handles`] treats a keyframe's handles, as
+/// a curve editor would
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TangentMode {
+    /// Handles are left as they were set
+    #[default]
+    Free,
+    /// Handles follow the neighbouring keyframes for a smooth curve
+    Auto,
+    /// Level handles, easing in and out of the keyframe's value
+    Flat,
+}
+
 /// A keyframe stores a value at a specific time point
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Keyframe<T: Animatable> {
     pub time: TimeValue,
     pub value: T,
     /// Interpolation type for this keyframe segment
     pub interpolation: InterpolationType,
+    /// Color space this segment blends through (color tracks only)
+    pub color_space: ColorInterpolation,
+    /// Handle shaping the [`InterpolationType::Bezier`] segment before the
+    /// keyframe (a third of the way back, level, when missing)
+    pub in_handle: Option<Handle<T>>,
+    /// Handle shaping the [`InterpolationType::Bezier`] segment after the
+    /// keyframe (a third of the way on, level, when missing)
+    pub out_handle: Option<Handle<T>>,
+    pub tangent_mode: TangentMode,
 }
 
 impl<T: Animatable + std::fmt::Debug> Keyframe<T> {
@@ -57,23 +134,52 @@ impl<T: Animatable + std::fmt::Debug> Keyframe<T> {
             time,
             value,
             interpolation: InterpolationType::Linear,
+            color_space: ColorInterpolation::Rgb,
+            in_handle: None,
+            out_handle: None,
+            tangent_mode: TangentMode::Free,
         }
     }
 
+    /// Shape the curve around the keyframe with Bezier handles, making the
+    /// segment after it a Bezier segment
+    pub fn with_handles(mut self, in_handle: Handle<T>, out_handle: Handle<T>) -> Self {
+        self.in_handle = Some(in_handle);
+        self.out_handle = Some(out_handle);
+        self.interpolation = InterpolationType::Bezier;
+        self
+    }
+
+    /// Let [`AnimationTrack::update_handles`] place the handles, making
+    /// the segment after the keyframe a Bezier segment
+    pub fn with_tangent_mode(mut self, tangent_mode: TangentMode) -> Self {
+        self.tangent_mode = tangent_mode;
+        self.interpolation = InterpolationType::Bezier;
+        self
+    }
+
     pub fn with_interpolation(mut self, interpolation: InterpolationType) -> Self {
         self.interpolation = interpolation;
         self
     }
+
+    pub fn with_color_space(mut self, color_space: ColorInterpolation) -> Self {
+        self.color_space = color_space;
+        self
+    }
 }
 
 /// Types of interpolation between keyframes
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InterpolationType {
     Linear,
     Step,
     EaseIn,
     EaseOut,
     EaseInOut,
+    /// Cubic Bezier through the out handle of the segment's first keyframe
+    /// and the in handle of its last
+    Bezier,
 }
 
 impl InterpolationType {
@@ -90,6 +196,8 @@ impl InterpolationType {
             }
             InterpolationType::EaseIn => t * t,
             InterpolationType::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            // The handles shape Bezier segments, see AnimationTrack::sample
+            InterpolationType::Bezier => t,
             InterpolationType::EaseInOut => {
                 if t < 0.5 {
                     2.0 * t * t
@@ -99,15 +207,40 @@ impl InterpolationType {
             }
         }
     }
+
+    /// The same curve traversed backwards (`EaseIn` becomes `EaseOut`)
+    ///
+    /// `Step` stays `Step`, so reversed step segments switch at the other end.
+    pub fn reversed(self) -> Self {
+        match self {
+            InterpolationType::EaseIn => InterpolationType::EaseOut,
+            InterpolationType::EaseOut => InterpolationType::EaseIn,
+            other => other,
+        }
+    }
 }
 
 /// A track animates a single property over time using keyframes
-#[derive(Debug, Clone)]
+///
+/// Tracks of serializable values (de)serialize with their keyframes and
+/// handles, for saving curves from an editor or exchanging them with
+/// other tools. A track eased with [`EasingType::Custom`] can't be
+/// serialized.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnimationTrack<T: Animatable + std::fmt::Debug> {
     pub name: String,
     pub keyframes: Vec<Keyframe<T>>,
     /// Default value when no keyframes exist
     pub default_value: T,
+    /// Clip time the track's time zero falls on, so tracks of one clip
+    /// can start one after another
+    pub offset: TimeValue,
+    /// Easing of the whole track, warping time across its keyframes on top
+    /// of each segment's own interpolation; serializing fails if it's
+    /// [`EasingType::Custom`]
+    pub easing: Option<EasingType>,
+    /// Disabled tracks are skipped when their clip plays
+    pub enabled: bool,
 }
 
 impl<T: Animatable + std::fmt::Debug> AnimationTrack<T> {
@@ -117,6 +250,9 @@ impl<T: Animatable + std::fmt::Debug> AnimationTrack<T> {
             name,
             keyframes: Vec::new(),
             default_value: default_value.clone(),
+            offset: TimeValue::new(0.0),
+            easing: None,
+            enabled: true,
         }
     }
 
@@ -125,15 +261,177 @@ impl<T: Animatable + std::fmt::Debug> AnimationTrack<T> {
             name,
             keyframes: Vec::new(),
             default_value,
+            offset: TimeValue::new(0.0),
+            easing: None,
+            enabled: true,
         }
     }
 
+    /// Start the track `offset` seconds into its clip
+    pub fn with_offset(mut self, offset: TimeValue) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Ease the whole track with `easing`
+    pub fn with_easing(mut self, easing: EasingType) -> Self {
+        self.easing = Some(easing);
+        self
+    }
+
     /// Add a keyframe to this track
     pub fn add_keyframe(&mut self, keyframe: Keyframe<T>) {
         self.keyframes.push(keyframe);
-        // Keep keyframes sorted by time
+        self.sort_keyframes();
+    }
+
+    /// Keep keyframes sorted by time (stable, so equal times keep their order)
+    fn sort_keyframes(&mut self) {
+        self.keyframes
+            .sort_by(|a, b| a.time.value.total_cmp(&b.time.value));
+    }
+
+    /// Index of the keyframe at `time` (within [`KEYFRAME_TIME_EPSILON`])
+    pub fn keyframe_at(&self, time: TimeValue) -> Option<usize> {
         self.keyframes
-            .sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+            .iter()
+            .position(|keyframe| (keyframe.time.value - time.value).abs() < KEYFRAME_TIME_EPSILON)
+    }
+
+    /// Remove and return the keyframe at `index`
+    pub fn remove_keyframe(&mut self, index: usize) -> Option<Keyframe<T>> {
+        (index < self.keyframes.len()).then(|| self.keyframes.remove(index))
+    }
+
+    /// Move the keyframe at `index` to `time` and return its new index
+    ///
+    /// A different keyframe already at `time` is replaced.
+    pub fn move_keyframe(&mut self, index: usize, time: TimeValue) -> Option<usize> {
+        let mut keyframe = self.remove_keyframe(index)?;
+        if let Some(existing) = self.keyframe_at(time) {
+            self.keyframes.remove(existing);
+        }
+        keyframe.time = time;
+        self.keyframes.push(keyframe);
+        self.sort_keyframes();
+        self.keyframe_at(time)
+    }
+
+    /// Set the value at `time`, adding a keyframe there if there is none
+    ///
+    /// A new keyframe takes the interpolation of the segment it splits.
+    /// Returns the keyframe's index.
+    pub fn set_value_at(&mut self, time: TimeValue, value: T) -> usize {
+        if let Some(index) = self.keyframe_at(time) {
+            self.keyframes[index].value = value;
+            return index;
+        }
+        let interpolation = self.interpolation_at(time);
+        self.add_keyframe(Keyframe::new(time, value).with_interpolation(interpolation));
+        self.keyframe_at(time).unwrap_or(0)
+    }
+
+    /// Interpolation of the segment containing `time`
+    fn interpolation_at(&self, time: TimeValue) -> InterpolationType {
+        self.keyframes
+            .iter()
+            .rev()
+            .find(|keyframe| keyframe.time <= time)
+            .map_or(InterpolationType::Linear, |keyframe| keyframe.interpolation)
+    }
+
+    /// Blend every segment through `color_space` (color tracks only)
+    pub fn set_color_space(&mut self, color_space: ColorInterpolation) {
+        for keyframe in &mut self.keyframes {
+            keyframe.color_space = color_space;
+        }
+    }
+
+    /// Time of the last keyframe
+    pub fn end_time(&self) -> TimeValue {
+        self.keyframes
+            .last()
+            .map_or(TimeValue::new(0.0), |keyframe| keyframe.time)
+    }
+
+    /// Keep only `start..end`, shifted to begin at zero
+    ///
+    /// Keyframes are added at both cuts with the values sampled there.
+    /// Cut segments keep their interpolation type, so an eased segment cut
+    /// part way only approximates the original curve.
+    pub fn trim(&mut self, start: TimeValue, end: TimeValue) {
+        if self.keyframes.is_empty() {
+            return;
+        }
+        let end = end.max(start);
+        let first = Keyframe::new(TimeValue::new(0.0), self.sample(start))
+            .with_interpolation(self.interpolation_at(start));
+        let last = Keyframe::new(end - start, self.sample(end))
+            .with_interpolation(self.interpolation_at(end));
+
+        let mut keyframes = vec![first];
+        keyframes.extend(
+            self.keyframes
+                .iter()
+                .filter(|keyframe| {
+                    keyframe.time.value > start.value + KEYFRAME_TIME_EPSILON
+                        && keyframe.time.value < end.value - KEYFRAME_TIME_EPSILON
+                })
+                .map(|keyframe| Keyframe {
+                    time: keyframe.time - start,
+                    ..keyframe.clone()
+                }),
+        );
+        if end.value - start.value >= KEYFRAME_TIME_EPSILON {
+            keyframes.push(last);
+        }
+        self.keyframes = keyframes;
+    }
+
+    /// Split at `time`: this track keeps what comes before, and the rest is
+    /// returned as a track starting at zero
+    pub fn split_off(&mut self, time: TimeValue) -> Self {
+        let mut rest = self.clone();
+        rest.trim(time, self.end_time().max(time));
+        self.trim(TimeValue::new(0.0), time);
+        rest
+    }
+
+    /// Play the track backwards over `0..end`: a keyframe at `t` moves to `end - t`
+    pub fn reverse(&mut self, end: TimeValue) {
+        // Segment i..i+1 becomes segment i+1..i, whose interpolation is
+        // stored on its new first keyframe (the old i+1)
+        let interpolations: Vec<InterpolationType> = self
+            .keyframes
+            .iter()
+            .map(|keyframe| keyframe.interpolation.reversed())
+            .collect();
+        self.keyframes.reverse();
+        let count = self.keyframes.len();
+        for (index, keyframe) in self.keyframes.iter_mut().enumerate() {
+            keyframe.time = end - keyframe.time;
+            std::mem::swap(&mut keyframe.in_handle, &mut keyframe.out_handle);
+            keyframe.interpolation = if index + 1 < count {
+                interpolations[count - index - 2]
+            } else {
+                InterpolationType::Linear
+            };
+        }
+    }
+
+    /// Stretch (`factor > 1`) or compress (`factor < 1`) the track in time
+    pub fn scale_time(&mut self, factor: f32) {
+        let factor = factor.max(0.0);
+        for keyframe in &mut self.keyframes {
+            keyframe.time = TimeValue::new(keyframe.time.value * factor);
+            for handle in [&mut keyframe.in_handle, &mut keyframe.out_handle]
+                .into_iter()
+                .flatten()
+            {
+                handle.time *= factor;
+            }
+        }
+        self.offset = TimeValue::new(self.offset.value * factor);
     }
 
     /// Sample the value at a given time
@@ -166,10 +464,76 @@ impl<T: Animatable + std::fmt::Debug> AnimationTrack<T> {
         }
 
         let t_raw = (time - kf0.time).seconds() / duration;
+        if kf0.interpolation == InterpolationType::Bezier {
+            return bezier_segment(kf0, kf1, duration, t_raw);
+        }
         let t = kf0.interpolation.apply(t_raw);
 
         // Interpolate
-        kf0.value.lerp(&kf1.value, t)
+        kf0.value.lerp_in(&kf1.value, t, kf0.color_space)
+    }
+
+    /// Place the handles of keyframes whose [`TangentMode`] is `Auto` or
+    /// `Flat`, as a curve editor does after keyframes change
+    ///
+    /// Auto handles point along the line between the neighbouring keyframes
+    /// (flat at the ends of the track); handles reach a third of the way
+    /// to the neighbouring keyframe.
+    pub fn update_handles(&mut self) {
+        for index in 0..self.keyframes.len() {
+            let keyframe = &self.keyframes[index];
+            if keyframe.tangent_mode == TangentMode::Free {
+                continue;
+            }
+            let previous = index.checked_sub(1).map(|i| &self.keyframes[i]);
+            let next = self.keyframes.get(index + 1);
+            let reach_in = previous.map_or(0.0, |p| (keyframe.time - p.time).seconds() / 3.0);
+            let reach_out = next.map_or(0.0, |n| (n.time - keyframe.time).seconds() / 3.0);
+            let (in_value, out_value) = match (keyframe.tangent_mode, previous, next) {
+                (TangentMode::Auto, Some(previous), Some(next)) => {
+                    let span = (next.time - previous.time).seconds();
+                    if span <= 0.0 {
+                        (keyframe.value.clone(), keyframe.value.clone())
+                    } else {
+                        (
+                            offset_along(
+                                &keyframe.value,
+                                &previous.value,
+                                &next.value,
+                                reach_in / span,
+                            ),
+                            offset_along(
+                                &keyframe.value,
+                                &next.value,
+                                &previous.value,
+                                reach_out / span,
+                            ),
+                        )
+                    }
+                }
+                _ => (keyframe.value.clone(), keyframe.value.clone()),
+            };
+            let keyframe = &mut self.keyframes[index];
+            keyframe.in_handle = Some(Handle::new(reach_in, in_value));
+            keyframe.out_handle = Some(Handle::new(reach_out, out_value));
+        }
+    }
+
+    /// Sample the value at `time` in the clip, after the track's offset
+    /// and easing (see [`Self::sample`] for the track's own time)
+    pub fn sample_clip(&self, time: TimeValue) -> T {
+        let local = time - self.offset;
+        let (Some(easing), Some(first), Some(last)) =
+            (self.easing, self.keyframes.first(), self.keyframes.last())
+        else {
+            return self.sample(local);
+        };
+        let span = (last.time - first.time).seconds();
+        if span <= 0.0 {
+            return self.sample(local);
+        }
+        let progress = ((local - first.time).seconds() / span).clamp(0.0, 1.0);
+        self.sample(first.time + TimeValue::new(easing.apply(progress) * span))
     }
 
     /// Get the duration of this track
@@ -234,25 +598,251 @@ impl AnimationClip {
 
         self.tracks
             .iter()
-            .map(|track| track.duration())
+            .map(|track| track.offset() + track.duration())
+            .max()
+            .unwrap_or(TimeValue::new(0.0))
+    }
+
+    /// The track named `name` with values of type `T`
+    pub fn track<T: Animatable + std::fmt::Debug + 'static>(
+        &self,
+        name: &str,
+    ) -> Option<&AnimationTrack<T>> {
+        self.tracks
+            .iter()
+            .filter_map(|track| track.as_any().downcast_ref::<AnimationTrack<T>>())
+            .find(|track| track.name == name)
+    }
+
+    /// The track named `name` with values of type `T`, for editing its keyframes
+    pub fn track_mut<T: Animatable + std::fmt::Debug + 'static>(
+        &mut self,
+        name: &str,
+    ) -> Option<&mut AnimationTrack<T>> {
+        self.tracks
+            .iter_mut()
+            .filter_map(|track| track.as_any_mut().downcast_mut::<AnimationTrack<T>>())
+            .find(|track| track.name == name)
+    }
+
+    /// Run `edit` on the tracks named `name`, whatever their value type,
+    /// and return whether there were any
+    fn edit_tracks(&mut self, name: &str, mut edit: impl FnMut(&mut dyn AnyTrack)) -> bool {
+        let mut found = false;
+        for track in self.tracks.iter_mut().filter(|track| track.name() == name) {
+            edit(track.as_mut());
+            found = true;
+        }
+        found
+    }
+
+    /// Start the tracks named `name` `offset` seconds into the clip;
+    /// returns whether there were any
+    pub fn set_track_offset(&mut self, name: &str, offset: TimeValue) -> bool {
+        self.edit_tracks(name, |track| track.set_offset(offset))
+    }
+
+    /// Ease the tracks named `name` as a whole (`None` leaves only their
+    /// keyframes' interpolation); returns whether there were any
+    pub fn set_track_easing(&mut self, name: &str, easing: Option<EasingType>) -> bool {
+        self.edit_tracks(name, |track| track.set_easing(easing))
+    }
+
+    /// Turn the tracks named `name` on or off; returns whether there were any
+    pub fn set_track_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        self.edit_tracks(name, |track| track.set_enabled(enabled))
+    }
+
+    /// Clip time of the last keyframe of any track
+    pub fn end_time(&self) -> TimeValue {
+        self.tracks
+            .iter()
+            .map(|track| track.offset() + track.end_time())
             .max()
             .unwrap_or(TimeValue::new(0.0))
     }
+
+    /// Keep only `start..end` of every track, shifted to begin at zero
+    ///
+    /// Tracks already playing at `start` are cut there and start at zero;
+    /// tracks starting after it keep their keyframes and start that much
+    /// after zero.
+    pub fn trim(&mut self, start: TimeValue, end: TimeValue) {
+        for track in &mut self.tracks {
+            // In seconds, since `TimeValue` can't go below zero
+            let offset = track.offset().value;
+            track.trim(
+                TimeValue::new(start.value - offset),
+                TimeValue::new(end.value - offset),
+            );
+            track.set_offset(TimeValue::new(offset - start.value));
+        }
+    }
+
+    /// Split at `time`: this clip keeps what comes before, and the rest is
+    /// returned as a clip (with the same name and settings) starting at zero
+    pub fn split_off(&mut self, time: TimeValue) -> AnimationClip {
+        AnimationClip {
+            name: self.name.clone(),
+            tracks: self
+                .tracks
+                .iter_mut()
+                .map(|track| {
+                    // Tracks starting after `time` go whole into the rest,
+                    // starting as long after its zero
+                    let offset = track.offset().value;
+                    let mut rest = track.split_off(TimeValue::new(time.value - offset));
+                    rest.set_offset(TimeValue::new(offset - time.value));
+                    rest
+                })
+                .collect(),
+            loop_animation: self.loop_animation,
+            speed: self.speed,
+        }
+    }
+
+    /// Play the clip backwards, keeping its tracks aligned
+    pub fn reverse(&mut self) {
+        let end = self.end_time();
+        for track in &mut self.tracks {
+            track.reverse(end - track.offset());
+            track.set_offset(TimeValue::new(0.0));
+        }
+    }
+
+    /// Stretch (`factor > 1`) or compress (`factor < 1`) every track in time
+    pub fn scale_duration(&mut self, factor: f32) {
+        for track in &mut self.tracks {
+            track.scale_time(factor);
+        }
+    }
+
+    /// Scale the clip in time so it lasts `duration`
+    pub fn set_duration(&mut self, duration: TimeValue) {
+        let current = self.duration();
+        if current.value > 0.0 {
+            self.scale_duration(duration.value / current.value);
+        }
+    }
+}
+
+/// Value of the Bezier segment `kf0..kf1` (lasting `duration` seconds)
+/// `t` of the way through its time
+fn bezier_segment<T: Animatable>(kf0: &Keyframe<T>, kf1: &Keyframe<T>, duration: f32, t: f32) -> T {
+    let out_handle = kf0
+        .out_handle
+        .clone()
+        .unwrap_or_else(|| Handle::new(duration / 3.0, kf0.value.clone()));
+    let in_handle = kf1
+        .in_handle
+        .clone()
+        .unwrap_or_else(|| Handle::new(duration / 3.0, kf1.value.clone()));
+
+    // Handle times kept inside the segment make time increase along the
+    // curve, so the curve parameter at `t` can be found by bisection
+    let x1 = out_handle.time.clamp(0.0, duration) / duration;
+    let x2 = 1.0 - in_handle.time.clamp(0.0, duration) / duration;
+    let time_at = |u: f32| {
+        let v = 1.0 - u;
+        3.0 * v * v * u * x1 + 3.0 * v * u * u * x2 + u * u * u
+    };
+    let (mut low, mut high) = (0.0f32, 1.0f32);
+    for _ in 0..BEZIER_SOLVE_STEPS {
+        let middle = (low + high) / 2.0;
+        if time_at(middle) < t {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    let u = (low + high) / 2.0;
+
+    // De Casteljau, so any animatable value can follow the curve
+    let space = kf0.color_space;
+    let a = kf0.value.lerp_in(&out_handle.value, u, space);
+    let b = out_handle.value.lerp_in(&in_handle.value, u, space);
+    let c = in_handle.value.lerp_in(&kf1.value, u, space);
+    let ab = a.lerp_in(&b, u, space);
+    let bc = b.lerp_in(&c, u, space);
+    ab.lerp_in(&bc, u, space)
+}
+
+/// `from + k * (toward - away)`, built from lerps as an affine combination
+/// so it works for any animatable value (`k` below 1)
+fn offset_along<T: Animatable>(from: &T, toward: &T, away: &T, k: f32) -> T {
+    from.lerp(away, -k / (1.0 - k)).lerp(toward, k)
 }
 
 /// Trait for type-erased tracks
 pub trait AnyTrack: Send + Sync + std::fmt::Debug {
+    fn name(&self) -> &str;
     fn duration(&self) -> TimeValue;
     fn sample_to_sample(&self, time: TimeValue, sample: &mut AnimationSample);
     /// Get a reference to self as Any for downcasting
     fn as_any(&self) -> &dyn Any;
+    /// Get a mutable reference to self as Any for downcasting
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn end_time(&self) -> TimeValue;
+    fn trim(&mut self, start: TimeValue, end: TimeValue);
+    fn split_off(&mut self, time: TimeValue) -> Box<dyn AnyTrack>;
+    fn reverse(&mut self, end: TimeValue);
+    fn scale_time(&mut self, factor: f32);
+    fn offset(&self) -> TimeValue;
+    fn set_offset(&mut self, offset: TimeValue);
+    fn set_easing(&mut self, easing: Option<EasingType>);
+    fn is_enabled(&self) -> bool;
+    fn set_enabled(&mut self, enabled: bool);
 }
 
 impl<T: Animatable + std::fmt::Debug + 'static> AnyTrack for AnimationTrack<T> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
     fn duration(&self) -> TimeValue {
         self.duration()
     }
 
+    fn end_time(&self) -> TimeValue {
+        self.end_time()
+    }
+
+    fn trim(&mut self, start: TimeValue, end: TimeValue) {
+        self.trim(start, end);
+    }
+
+    fn split_off(&mut self, time: TimeValue) -> Box<dyn AnyTrack> {
+        Box::new(self.split_off(time))
+    }
+
+    fn reverse(&mut self, end: TimeValue) {
+        self.reverse(end);
+    }
+
+    fn scale_time(&mut self, factor: f32) {
+        self.scale_time(factor);
+    }
+
+    fn offset(&self) -> TimeValue {
+        self.offset
+    }
+
+    fn set_offset(&mut self, offset: TimeValue) {
+        self.offset = offset;
+    }
+
+    fn set_easing(&mut self, easing: Option<EasingType>) {
+        self.easing = easing;
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
     fn sample_to_sample(&self, _time: TimeValue, _sample: &mut AnimationSample) {
         // This would need a more sophisticated system for storing different types
         // For now, we'll skip type-erased sampling
@@ -261,6 +851,10 @@ impl<T: Animatable + std::fmt::Debug + 'static> AnyTrack for AnimationTrack<T> {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
 }
 
 /// Result of sampling an animation at a time point
@@ -283,48 +877,170 @@ impl AnimationSample {
     }
 }
 
+/// What an animation does when its playhead reaches the end of the clip
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PlayMode {
+    /// Stop at the end (the start, when playing backwards) and finish
+    #[default]
+    Once,
+    /// Wrap around to the other end
+    Loop,
+    /// Bounce between the ends, alternating direction
+    PingPong,
+    /// Stay on the last frame without finishing
+    Hold,
+}
+
 /// An animation instance is a running animation with state
 pub struct AnimationInstance {
     pub clip: AnimationClip,
     pub start_time: TimeValue,
     pub is_playing: bool,
+    /// Position in the clip after applying the play mode
     pub current_time: TimeValue,
+    pub play_mode: PlayMode,
+    /// Playback rate (negative plays backwards from the end), on top of the clip's speed
+    pub rate: f32,
+    /// Show the clip's first frame while waiting for `start_time`
+    pub fill_before: bool,
+    /// Playhead in clip seconds before wrapping, bouncing or clamping
+    playhead: f32,
+    /// Scene time advanced so far, counted from when the instance was added
+    elapsed: f32,
 }
 
 impl AnimationInstance {
+    /// Play `clip` forwards, looping if the clip is set to loop
     pub fn new(clip: AnimationClip, start_time: TimeValue) -> Self {
+        let play_mode = if clip.loop_animation {
+            PlayMode::Loop
+        } else {
+            PlayMode::Once
+        };
         Self {
             clip,
             start_time,
             is_playing: true,
             current_time: TimeValue::new(0.0),
+            play_mode,
+            rate: 1.0,
+            fill_before: false,
+            playhead: 0.0,
+            elapsed: 0.0,
         }
     }
 
+    pub fn with_play_mode(mut self, play_mode: PlayMode) -> Self {
+        self.play_mode = play_mode;
+        self
+    }
+
+    /// Set the playback rate; a negative rate starts from the end of the clip
+    pub fn with_rate(mut self, rate: f32) -> Self {
+        self.rate = rate;
+        if rate < 0.0 && self.playhead == 0.0 {
+            self.playhead = self.clip.duration().value;
+            self.current_time = self.clip.duration();
+        }
+        self
+    }
+
+    /// Hold the first frame before the animation starts, so a delayed
+    /// fade-in keeps its target hidden until its turn
+    pub fn with_fill_before(mut self, fill_before: bool) -> Self {
+        self.fill_before = fill_before;
+        self
+    }
+
+    /// Whether `start_time` has been reached (see [`Self::advance`])
+    pub fn has_started(&self) -> bool {
+        self.elapsed >= self.start_time.value
+    }
+
+    /// Clip seconds played per second of scene time
+    fn effective_rate(&self) -> f32 {
+        self.rate * self.clip.speed
+    }
+
     /// Update the animation to the current time
     pub fn update(&mut self, current_time: TimeValue) -> Option<AnimationSample> {
         if !self.is_playing {
             return None;
         }
 
-        // Calculate local time in the animation
-        let mut local_time = current_time - self.start_time;
-        let duration = self.clip.duration();
+        let elapsed = (current_time - self.start_time).value;
+        let rate = self.effective_rate();
+        let origin = if rate < 0.0 {
+            self.clip.duration().value
+        } else {
+            0.0
+        };
+        self.seek_playhead(origin + elapsed * rate);
+        Some(self.clip.sample(self.current_time))
+    }
 
-        if duration > TimeValue::new(0.0) {
-            if self.clip.loop_animation {
-                // Loop the animation
-                let loop_time = (local_time % duration).seconds();
-                local_time = TimeValue::new(loop_time);
-            } else if local_time >= duration {
-                // Animation finished
-                self.is_playing = false;
-                local_time = duration;
+    /// Move the playhead by `delta_time` of scene time and return the clip time to sample
+    ///
+    /// Scene time before `start_time` only counts down to the start.
+    pub fn advance(&mut self, delta_time: TimeValue) -> TimeValue {
+        if self.is_playing {
+            let start = self.start_time.value;
+            let before = (self.elapsed - start).max(0.0);
+            self.elapsed += delta_time.value;
+            let played = (self.elapsed - start).max(0.0) - before;
+            if played > 0.0 {
+                let playhead = self.playhead + played * self.effective_rate();
+                self.seek_playhead(playhead);
             }
         }
+        self.current_time
+    }
+
+    /// Place the playhead at `playhead` clip seconds and apply the play mode
+    fn seek_playhead(&mut self, playhead: f32) {
+        self.playhead = playhead;
+        let duration = self.clip.duration().value;
+        if duration <= 0.0 {
+            self.current_time = TimeValue::new(playhead);
+            return;
+        }
+
+        let time = match self.play_mode {
+            PlayMode::Once | PlayMode::Hold => {
+                let backwards = self.effective_rate() < 0.0;
+                let ended = if backwards {
+                    playhead <= 0.0
+                } else {
+                    playhead >= duration
+                };
+                if ended && self.play_mode == PlayMode::Once {
+                    self.is_playing = false;
+                }
+                playhead.clamp(0.0, duration)
+            }
+            PlayMode::Loop => playhead.rem_euclid(duration),
+            PlayMode::PingPong => {
+                let phase = playhead.rem_euclid(2.0 * duration);
+                if phase > duration {
+                    2.0 * duration - phase
+                } else {
+                    phase
+                }
+            }
+        };
+        self.current_time = TimeValue::new(time);
+    }
 
-        self.current_time = local_time;
-        Some(self.clip.sample(local_time))
+    /// Whether the animation ran to its end and stopped (only in [`PlayMode::Once`])
+    pub fn is_finished(&self) -> bool {
+        !self.is_playing && self.play_mode == PlayMode::Once && {
+            let duration = self.clip.duration().value;
+            if self.effective_rate() < 0.0 {
+                self.playhead <= 0.0
+            } else {
+                self.playhead >= duration
+            }
+        }
     }
 
     pub fn play(&mut self) {
@@ -337,6 +1053,256 @@ impl AnimationInstance {
 
     pub fn stop(&mut self) {
         self.is_playing = false;
+        self.playhead = 0.0;
+        self.elapsed = 0.0;
         self.current_time = TimeValue::new(0.0);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Vector3;
+
+    fn t(seconds: f32) -> TimeValue {
+        TimeValue::new(seconds)
+    }
+
+    fn ramp() -> AnimationTrack<Vector3> {
+        let mut track = AnimationTrack::new("position".to_string());
+        track.add_keyframe(Keyframe::new(t(0.0), Vector3::new(0.0, 0.0, 0.0)));
+        track.add_keyframe(
+            Keyframe::new(t(1.0), Vector3::new(1.0, 0.0, 0.0))
+                .with_interpolation(InterpolationType::EaseIn),
+        );
+        track.add_keyframe(Keyframe::new(t(2.0), Vector3::new(3.0, 0.0, 0.0)));
+        track
+    }
+
+    fn instance(mode: PlayMode, rate: f32) -> AnimationInstance {
+        let mut clip = AnimationClip::new("move".to_string());
+        clip.add_track(ramp());
+        AnimationInstance::new(clip, t(0.0))
+            .with_play_mode(mode)
+            .with_rate(rate)
+    }
+
+    #[test]
+    fn test_play_modes() {
+        let mut once = instance(PlayMode::Once, 1.0);
+        assert_eq!(once.advance(t(1.5)), t(1.5));
+        assert_eq!(once.advance(t(1.0)), t(2.0));
+        assert!(once.is_finished());
+
+        let mut hold = instance(PlayMode::Hold, 1.0);
+        assert_eq!(hold.advance(t(3.0)), t(2.0));
+        assert!(hold.is_playing && !hold.is_finished());
+
+        let mut looping = instance(PlayMode::Loop, 2.0);
+        assert_eq!(looping.advance(t(1.25)), t(0.5));
+
+        let mut ping_pong = instance(PlayMode::PingPong, 1.0);
+        assert_eq!(ping_pong.advance(t(2.5)), t(1.5));
+        assert_eq!(ping_pong.advance(t(2.0)), t(0.5));
+        assert!(ping_pong.is_playing);
+
+        // Backwards playback starts at the end and finishes at the start
+        let mut backwards = instance(PlayMode::Once, -1.0);
+        assert_eq!(backwards.current_time, t(2.0));
+        assert_eq!(backwards.advance(t(0.5)), t(1.5));
+        assert_eq!(backwards.advance(t(2.0)), t(0.0));
+        assert!(backwards.is_finished());
+
+        // Nothing plays before the start time
+        let mut delayed = instance(PlayMode::Once, 1.0);
+        delayed.start_time = t(1.0);
+        assert_eq!(delayed.advance(t(0.5)), t(0.0));
+        assert!(!delayed.has_started());
+        assert_eq!(delayed.advance(t(1.0)), t(0.5));
+        assert!(delayed.has_started());
+
+        let mut absolute = instance(PlayMode::PingPong, -0.5);
+        let sample = absolute.update(t(6.0));
+        assert!(sample.is_some());
+        assert_eq!(absolute.current_time, t(1.0));
+    }
+
+    #[test]
+    fn test_keyframe_editing() {
+        let mut track = ramp();
+        assert_eq!(track.keyframe_at(t(1.0)), Some(1));
+
+        // Moving onto another keyframe replaces it
+        assert_eq!(track.move_keyframe(0, t(2.0)), Some(1));
+        assert_eq!(track.keyframes.len(), 2);
+        assert_eq!(track.sample(t(2.0)).x, 0.0);
+
+        let mut track = ramp();
+        let index = track.set_value_at(t(1.5), Vector3::new(5.0, 0.0, 0.0));
+        assert_eq!(index, 2);
+        assert_eq!(track.keyframes[2].interpolation, InterpolationType::EaseIn);
+        track.set_value_at(t(1.0), Vector3::new(2.0, 0.0, 0.0));
+        assert_eq!(track.keyframes.len(), 4);
+        assert_eq!(track.sample(t(1.0)).x, 2.0);
+
+        assert!(track.remove_keyframe(2).is_some());
+        assert!(track.remove_keyframe(7).is_none());
+        assert_eq!(track.keyframes.len(), 3);
+    }
+
+    #[test]
+    fn test_trim_split_reverse_scale() {
+        let mut track = ramp();
+        track.trim(t(0.5), t(2.0));
+        assert_eq!(track.keyframes.len(), 3);
+        assert_eq!(track.end_time(), t(1.5));
+        assert_eq!(track.sample(t(0.0)).x, 0.5);
+        assert_eq!(track.sample(t(1.5)).x, 3.0);
+
+        let mut first = ramp();
+        let rest = first.split_off(t(1.0));
+        assert_eq!(first.end_time(), t(1.0));
+        assert_eq!(first.sample(t(1.0)).x, 1.0);
+        assert_eq!(rest.end_time(), t(1.0));
+        assert_eq!(rest.sample(t(0.0)).x, 1.0);
+        assert_eq!(rest.sample(t(1.0)).x, 3.0);
+
+        let mut reversed = ramp();
+        reversed.reverse(t(2.0));
+        assert_eq!(reversed.sample(t(0.0)).x, 3.0);
+        assert_eq!(reversed.sample(t(2.0)).x, 0.0);
+        // The eased segment 1..2 now runs 0..1 and eases out
+        assert_eq!(
+            reversed.keyframes[0].interpolation,
+            InterpolationType::EaseOut
+        );
+        let forward = ramp();
+        for time in [0.25, 0.5, 1.25, 1.75] {
+            let x = reversed.sample(t(2.0 - time)).x;
+            assert!((x - forward.sample(t(time)).x).abs() < 1e-5);
+        }
+
+        let mut clip = AnimationClip::new("move".to_string());
+        clip.add_track(ramp());
+        clip.set_duration(t(4.0));
+        assert_eq!(clip.duration(), t(4.0));
+        let track = clip.track::<Vector3>("position").unwrap();
+        assert_eq!(track.sample(t(2.0)).x, 1.0);
+
+        let tail = clip.split_off(t(2.0));
+        assert_eq!(clip.duration(), t(2.0));
+        assert_eq!(tail.duration(), t(2.0));
+        clip.track_mut::<Vector3>("position")
+            .unwrap()
+            .set_value_at(t(2.0), Vector3::new(9.0, 0.0, 0.0));
+        let track = clip.track::<Vector3>("position").unwrap();
+        assert_eq!(track.sample(t(2.0)).x, 9.0);
+    }
+
+    #[test]
+    fn test_track_offsets_and_easing() {
+        let mut fade = AnimationTrack::new("opacity".to_string());
+        fade.add_keyframe(Keyframe::new(t(0.0), 0.0f32));
+        fade.add_keyframe(Keyframe::new(t(1.0), 1.0f32));
+        let enter = || {
+            let mut clip = AnimationClip::new("enter".to_string());
+            clip.add_track(ramp());
+            clip.add_track(fade.clone().with_offset(t(1.5)));
+            clip
+        };
+        let mut clip = enter();
+        assert_eq!(clip.duration(), t(2.5));
+        assert_eq!(clip.end_time(), t(2.5));
+
+        // The fade waits for its offset, then plays on clip time
+        let fade = clip.track::<f32>("opacity").unwrap();
+        assert_eq!(fade.sample_clip(t(1.0)), 0.0);
+        assert_eq!(fade.sample_clip(t(2.0)), 0.5);
+        assert_eq!(fade.sample_clip(t(3.0)), 1.0);
+
+        assert!(clip.set_track_easing("opacity", Some(EasingType::EaseInQuad)));
+        let fade = clip.track::<f32>("opacity").unwrap();
+        assert_eq!(fade.sample_clip(t(2.0)), 0.25);
+        assert!(!clip.set_track_enabled("scale", false));
+        assert!(clip.set_track_enabled("opacity", false));
+        assert!(!clip.tracks[1].is_enabled());
+
+        // Cutting the clip keeps each track where it was in clip time
+        clip.set_track_easing("opacity", None);
+        clip.trim(t(1.0), t(2.5));
+        let fade = clip.track::<f32>("opacity").unwrap();
+        assert_eq!(fade.offset, t(0.5));
+        assert_eq!(fade.sample_clip(t(0.5)), 0.0);
+        assert_eq!(fade.sample_clip(t(1.0)), 0.5);
+        let position = clip.track::<Vector3>("position").unwrap();
+        assert_eq!(position.offset, t(0.0));
+        assert_eq!(position.sample_clip(t(0.0)).x, 1.0);
+
+        // Splitting before the fade starts leaves all of it to the rest
+        let mut early = enter();
+        let late = early.split_off(t(1.0));
+        let fade = late.track::<f32>("opacity").unwrap();
+        assert_eq!(fade.offset, t(0.5));
+        assert_eq!(fade.sample_clip(t(1.0)), 0.5);
+        assert_eq!(
+            early.track::<f32>("opacity").unwrap().sample_clip(t(1.0)),
+            0.0
+        );
+        let tail = enter().split_off(t(2.0));
+        let fade = tail.track::<f32>("opacity").unwrap();
+        assert_eq!(fade.offset, t(0.0));
+        assert_eq!(fade.sample_clip(t(0.5)), 1.0);
+    }
+
+    #[test]
+    fn test_bezier_handles() {
+        // Level handles ease out of and into the keyframes
+        let mut curve = AnimationTrack::new("opacity".to_string());
+        curve.add_keyframe(Keyframe::new(t(0.0), 0.0f32).with_tangent_mode(TangentMode::Flat));
+        curve.add_keyframe(Keyframe::new(t(1.0), 1.0f32).with_tangent_mode(TangentMode::Flat));
+        curve.update_handles();
+        assert!((curve.sample(t(0.5)) - 0.5).abs() < 1e-4);
+        assert!(curve.sample(t(0.25)) < 0.25);
+        assert!(curve.sample(t(0.75)) > 0.75);
+
+        // Auto handles carry the slope through a middle keyframe
+        curve.add_keyframe(Keyframe::new(t(2.0), 2.0f32));
+        curve.keyframes[1].tangent_mode = TangentMode::Auto;
+        curve.update_handles();
+        let middle = curve.keyframes[1].clone();
+        let (in_handle, out_handle) = (middle.in_handle.unwrap(), middle.out_handle.unwrap());
+        assert!((in_handle.value - 2.0 / 3.0).abs() < 1e-5);
+        assert!((out_handle.value - 4.0 / 3.0).abs() < 1e-5);
+
+        // Handles swap sides when the track is reversed
+        let mut reversed = curve.clone();
+        reversed.reverse(t(2.0));
+        assert!((reversed.sample(t(1.75)) - curve.sample(t(0.25))).abs() < 1e-4);
+
+        // Curves round-trip through serialization
+        let json = serde_json::to_string(&curve).unwrap();
+        let loaded: AnimationTrack<f32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.keyframes[1].out_handle, Some(out_handle));
+        assert_eq!(loaded.sample(t(0.3)), curve.sample(t(0.3)));
+    }
+
+    #[test]
+    fn test_track_easing_serialization() {
+        let mut track = AnimationTrack::new("opacity".to_string())
+            .with_easing(EasingType::EaseInOutQuad)
+            .with_offset(TimeValue::new(0.5));
+        track.add_keyframe(Keyframe::new(TimeValue::new(0.0), 0.0_f32));
+        track.add_keyframe(Keyframe::new(TimeValue::new(1.0), 1.0_f32));
+
+        let json = serde_json::to_string(&track).unwrap();
+        let loaded: AnimationTrack<f32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.easing, Some(EasingType::EaseInOutQuad));
+        assert_eq!(loaded.offset, track.offset);
+        let t = TimeValue::new(0.75);
+        assert_eq!(loaded.sample_clip(t), track.sample_clip(t));
+
+        // Custom easings are functions and can't be written out
+        let custom = track.with_easing(EasingType::Custom(|t| t * t));
+        assert!(serde_json::to_string(&custom).is_err());
+    }
+}
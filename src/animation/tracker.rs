@@ -0,0 +1,134 @@
+//! # Value Trackers
+//!
+//! A [`ValueTracker`] is a single number animated over time, like Manim's
+//! ValueTracker. It doesn't draw anything itself; mobjects such as
+//! [`crate::mobjects::DecimalNumber`] read it every frame.
+//!
+//! ```rust
+//! use diomanim::animation::{InterpolationType, ValueTracker};
+//! use diomanim::core::TimeValue;
+//!
+//! let mut visitors = ValueTracker::new(0.0);
+//! visitors.animate_to(1200.0, 0.5, 2.0, InterpolationType::EaseOut);
+//!
+//! assert_eq!(visitors.value_at(TimeValue::new(0.25)), 0.0);
+//! assert_eq!(visitors.value_at(TimeValue::new(3.0)), 1200.0);
+//! ```
+
+use super::property::{AnimationTrack, InterpolationType, Keyframe, KEYFRAME_TIME_EPSILON};
+use crate::core::TimeValue;
+
+/// A number that changes over time
+#[derive(Debug, Clone)]
+pub struct ValueTracker {
+    track: AnimationTrack<f32>,
+    /// Seconds since the tracker started, advanced by [`Self::advance`]
+    time: TimeValue,
+}
+
+impl ValueTracker {
+    /// A tracker holding `value` until animated
+    pub fn new(value: f32) -> Self {
+        let mut track = AnimationTrack::with_default_value("value".to_string(), value);
+        track.add_keyframe(Keyframe::new(TimeValue::new(0.0), value));
+        Self {
+            track,
+            time: TimeValue::new(0.0),
+        }
+    }
+
+    /// A tracker following the keyframes of `track`
+    pub fn from_track(track: AnimationTrack<f32>) -> Self {
+        Self {
+            track,
+            time: TimeValue::new(0.0),
+        }
+    }
+
+    /// The value at the tracker's current time
+    pub fn value(&self) -> f32 {
+        self.value_at(self.time)
+    }
+
+    /// The value at `time` seconds
+    pub fn value_at(&self, time: TimeValue) -> f32 {
+        self.track.sample(time)
+    }
+
+    /// Jump to `value` at `time` seconds, holding it until the next change
+    pub fn set_value_at(&mut self, time: f32, value: f32) {
+        let time = TimeValue::new(time);
+        // Keep the old value up to the jump, then switch in zero time
+        let before = self.value_at(time);
+        self.track.set_value_at(time, before);
+        self.track
+            .add_keyframe(Keyframe::new(time, value).with_interpolation(InterpolationType::Step));
+    }
+
+    /// Move from the value at `start` to `target` over `duration` seconds
+    pub fn animate_to(
+        &mut self,
+        target: f32,
+        start: f32,
+        duration: f32,
+        interpolation: InterpolationType,
+    ) {
+        let start = TimeValue::new(start);
+        let from = self.value_at(start);
+        // After a jump there are two keyframes at `start`; the change follows the later one
+        let index =
+            match self.track.keyframes.iter().rposition(|keyframe| {
+                (keyframe.time.value - start.value).abs() < KEYFRAME_TIME_EPSILON
+            }) {
+                Some(index) => index,
+                None => self.track.set_value_at(start, from),
+            };
+        self.track.keyframes[index].interpolation = interpolation;
+        self.track.add_keyframe(Keyframe::new(
+            TimeValue::new(start.value + duration),
+            target,
+        ));
+    }
+
+    /// Move by `delta` from the value at `start` over `duration` seconds
+    pub fn increment(&mut self, delta: f32, start: f32, duration: f32) {
+        let target = self.value_at(TimeValue::new(start)) + delta;
+        self.animate_to(target, start, duration, InterpolationType::EaseInOut);
+    }
+
+    /// Advance the tracker's clock and return the new value
+    pub fn advance(&mut self, delta_time: TimeValue) -> f32 {
+        self.time += delta_time;
+        self.value()
+    }
+
+    /// The tracker's current time
+    pub fn time(&self) -> TimeValue {
+        self.time
+    }
+
+    /// When the last change finishes
+    pub fn end_time(&self) -> TimeValue {
+        self.track.end_time()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_animation() {
+        let mut tracker = ValueTracker::new(10.0);
+        tracker.animate_to(20.0, 1.0, 2.0, InterpolationType::Linear);
+        tracker.set_value_at(4.0, 0.0);
+        tracker.increment(5.0, 5.0, 1.0);
+
+        assert_eq!(tracker.value(), 10.0);
+        assert_eq!(tracker.advance(TimeValue::new(2.0)), 15.0);
+        assert_eq!(tracker.value_at(TimeValue::new(3.5)), 20.0);
+        assert_eq!(tracker.value_at(TimeValue::new(4.5)), 0.0);
+        assert_eq!(tracker.value_at(TimeValue::new(6.0)), 5.0);
+        assert_eq!(tracker.end_time(), TimeValue::new(6.0));
+    }
+}
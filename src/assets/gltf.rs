@@ -0,0 +1,700 @@
+//! glTF node hierarchies and animations
+//!
+//! Reads `.gltf` files (JSON with `data:` URI or neighbouring `.bin`
+//! buffers) and binary `.glb` files, as exported by Blender, into a
+//! [`GltfDocument`]: every node's name, parent and rest transform, and the
+//! translation, rotation and scale channels of every animation.
+//! [`SceneGraph::import_gltf`] turns it into empty transform nodes played
+//! by [`AnimationClip`]s, so shapes parented to them (or found by name)
+//! follow motion authored in Blender.
+//!
+//! Meshes, materials, cameras, skins and morph target weights are ignored,
+//! as are sparse accessors. glTF's axes (y up, meters) are kept as they
+//! are; scale the imported roots to fit the scene. Cubic spline channels
+//! become Bezier segments with handles along the exported tangents.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use diomanim::assets::gltf::load_gltf;
+//! use diomanim::core::Color;
+//! use diomanim::scene::SceneGraph;
+//!
+//! # fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let rig = load_gltf("exports/arm.glb")?;
+//! let mut scene = SceneGraph::new();
+//! scene.import_gltf(&rig, Some(0), 0.0);
+//!
+//! // The dot rides along with the hand bone's animation
+//! let hand = scene.find_by_name("Hand").ok_or("no hand")?;
+//! let dot = scene.add_circle("dot", 0.1, Color::RED).id();
+//! scene.parent(dot, hand)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::animation::property::{
+    Animatable, AnimationClip, AnimationInstance, AnimationTrack, AnyTrack, Handle,
+    InterpolationType, Keyframe,
+};
+use crate::core::{Quaternion, TimeValue, Transform, Vector3};
+use crate::scene::{NodeId, SceneGraph};
+use glam::Mat4;
+use serde_json::Value;
+use std::path::Path;
+
+/// First four bytes of a binary glTF file ("glTF")
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+/// Chunk types of a binary glTF file ("JSON" and "BIN\0")
+const GLB_JSON_CHUNK: u32 = 0x4E4F_534A;
+const GLB_BIN_CHUNK: u32 = 0x004E_4942;
+/// Most floats an accessor without a buffer view may expand to
+const MAX_ZEROED_ACCESSOR_LEN: usize = 1 << 24;
+
+/// A glTF file's nodes and animations
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfDocument {
+    /// Every node, in file order (channels refer to nodes by index)
+    pub nodes: Vec<GltfNode>,
+    pub animations: Vec<GltfAnimation>,
+}
+
+/// A glTF node
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfNode {
+    /// Name from the file, or `node_<index>` for unnamed nodes
+    pub name: String,
+    /// Index of the parent node
+    pub parent: Option<usize>,
+    /// Rest transform relative to the parent
+    pub transform: Transform,
+}
+
+/// A named animation (a Blender action)
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfAnimation {
+    pub name: String,
+    pub channels: Vec<GltfChannel>,
+}
+
+/// Node property a channel animates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GltfProperty {
+    Translation,
+    /// Quaternion `x, y, z, w`
+    Rotation,
+    Scale,
+}
+
+/// How a channel moves between its keyframes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GltfInterpolation {
+    Linear,
+    Step,
+    /// Hermite spline; each keyframe stores an in tangent, its value and an
+    /// out tangent
+    CubicSpline,
+}
+
+/// Keyframes of one property of one node
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfChannel {
+    /// Index of the animated node
+    pub node: usize,
+    pub property: GltfProperty,
+    pub interpolation: GltfInterpolation,
+    /// Keyframe times in seconds
+    pub times: Vec<f32>,
+    /// Keyframe values, their components one after another
+    pub values: Vec<f32>,
+}
+
+impl GltfProperty {
+    /// Number of components in one value
+    pub fn components(self) -> usize {
+        match self {
+            Self::Rotation => 4,
+            Self::Translation | Self::Scale => 3,
+        }
+    }
+}
+
+impl GltfChannel {
+    /// The channel as a track of the node property it drives (`position`,
+    /// `rotation` or `scale`)
+    pub fn track(&self) -> Box<dyn AnyTrack> {
+        let vector = |v: &[f32]| Vector3::new(v[0], v[1], v[2]);
+        match self.property {
+            GltfProperty::Translation => Box::new(self.keyframes("position", vector)),
+            GltfProperty::Scale => Box::new(self.keyframes("scale", vector)),
+            GltfProperty::Rotation => {
+                Box::new(self.keyframes("rotation", |v| Quaternion::new(v[0], v[1], v[2], v[3])))
+            }
+        }
+    }
+
+    /// Time of the last keyframe
+    pub fn end_time(&self) -> f32 {
+        self.times.last().copied().unwrap_or(0.0)
+    }
+
+    fn keyframes<T: Animatable + std::fmt::Debug>(
+        &self,
+        name: &str,
+        value: impl Fn(&[f32]) -> T,
+    ) -> AnimationTrack<T> {
+        let width = self.property.components();
+        let element = |index: usize| &self.values[index * width..(index + 1) * width];
+        let mut track = AnimationTrack::new(name.to_string());
+        for (index, &time) in self.times.iter().enumerate() {
+            let keyframe = match self.interpolation {
+                GltfInterpolation::Linear => {
+                    Keyframe::new(TimeValue::new(time), value(element(index)))
+                }
+                GltfInterpolation::Step => {
+                    Keyframe::new(TimeValue::new(time), value(element(index)))
+                        .with_interpolation(InterpolationType::Step)
+                }
+                GltfInterpolation::CubicSpline => {
+                    let [in_tangent, point, out_tangent] =
+                        [0, 1, 2].map(|part| element(index * 3 + part));
+                    // Tangents are per second; Bezier handles sit a third
+                    // of the way along the segment
+                    let before = index.checked_sub(1).map_or(0.0, |p| time - self.times[p]) / 3.0;
+                    let after = self.times.get(index + 1).map_or(0.0, |next| next - time) / 3.0;
+                    let along = |tangent: &[f32], seconds: f32| {
+                        let moved: Vec<f32> = point
+                            .iter()
+                            .zip(tangent)
+                            .map(|(p, t)| p + t * seconds)
+                            .collect();
+                        value(&moved)
+                    };
+                    Keyframe::new(TimeValue::new(time), value(point)).with_handles(
+                        Handle::new(before, along(in_tangent, -before)),
+                        Handle::new(after, along(out_tangent, after)),
+                    )
+                }
+            };
+            track.add_keyframe(keyframe);
+        }
+        track
+    }
+}
+
+impl GltfAnimation {
+    /// Length of the animation in seconds
+    pub fn duration(&self) -> f32 {
+        self.channels
+            .iter()
+            .map(GltfChannel::end_time)
+            .fold(0.0, f32::max)
+    }
+
+    /// The tracks animating node `node` as a clip, or `None` if the
+    /// animation leaves the node alone
+    pub fn clip_for(&self, node: usize) -> Option<AnimationClip> {
+        let mut clip = AnimationClip::new(format!("{}:{node}", self.name));
+        clip.tracks.extend(
+            self.channels
+                .iter()
+                .filter(|channel| channel.node == node)
+                .map(GltfChannel::track),
+        );
+        (!clip.tracks.is_empty()).then_some(clip)
+    }
+}
+
+impl SceneGraph {
+    /// Add `document`'s nodes as empty nodes parented as in the file, and
+    /// play its animation at `animation` (if any) from `start_time`
+    ///
+    /// Returns the scene node of every glTF node, by glTF index.
+    pub fn import_gltf(
+        &mut self,
+        document: &GltfDocument,
+        animation: Option<usize>,
+        start_time: f32,
+    ) -> Vec<NodeId> {
+        let ids: Vec<NodeId> = document
+            .nodes
+            .iter()
+            .map(|node| self.create_node_with_transform(node.name.clone(), node.transform))
+            .collect();
+        for (node, &id) in document.nodes.iter().zip(&ids) {
+            if let Some(parent) = node.parent {
+                // Cycles are invalid glTF; their last link stays unparented
+                self.parent(id, ids[parent]).ok();
+            }
+        }
+
+        if let Some(animation) = animation.and_then(|index| document.animations.get(index)) {
+            for (index, &id) in ids.iter().enumerate() {
+                if let (Some(clip), Some(node)) = (animation.clip_for(index), self.get_node_mut(id))
+                {
+                    node.add_animation(AnimationInstance::new(clip, TimeValue::new(start_time)));
+                }
+            }
+        }
+        ids
+    }
+}
+
+/// Read a `.gltf` or `.glb` file; `.bin` buffers are looked up next to it
+pub fn load_gltf(path: impl AsRef<Path>) -> Result<GltfDocument, String> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    parse_gltf(&bytes, path.parent())
+}
+
+/// Parse glTF JSON or a binary `.glb`, reading external buffers from
+/// `base_dir` (they are an error without one)
+pub fn parse_gltf(bytes: &[u8], base_dir: Option<&Path>) -> Result<GltfDocument, String> {
+    let (json, binary) = if bytes.starts_with(GLB_MAGIC) {
+        split_glb(bytes)?
+    } else {
+        (bytes, None)
+    };
+    let root: Value =
+        serde_json::from_slice(json).map_err(|e| format!("invalid glTF JSON: {e}"))?;
+    let buffers = root["buffers"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|buffer| load_buffer(buffer, binary, base_dir))
+        .collect::<Result<Vec<_>, _>>()?;
+    let reader = Reader {
+        root: &root,
+        buffers,
+    };
+    let nodes = reader.nodes()?;
+    let animations = reader.animations(nodes.len())?;
+    Ok(GltfDocument { nodes, animations })
+}
+
+/// The JSON chunk and (if any) binary chunk of a `.glb` file
+fn split_glb(bytes: &[u8]) -> Result<(&[u8], Option<&[u8]>), String> {
+    let word = |offset: usize| {
+        bytes
+            .get(offset..offset + 4)
+            .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+            .ok_or("truncated GLB file")
+    };
+    let length = (word(8)? as usize).min(bytes.len());
+    let (mut json, mut binary) = (None, None);
+    let mut offset = 12;
+    while offset + 8 <= length {
+        let chunk_length = word(offset)? as usize;
+        let chunk_type = word(offset + 4)?;
+        let data = bytes
+            .get(offset + 8..offset + 8 + chunk_length)
+            .ok_or("truncated GLB chunk")?;
+        match chunk_type {
+            GLB_JSON_CHUNK if json.is_none() => json = Some(data),
+            GLB_BIN_CHUNK if binary.is_none() => binary = Some(data),
+            _ => {}
+        }
+        offset += 8 + chunk_length;
+    }
+    Ok((json.ok_or("GLB file has no JSON chunk")?, binary))
+}
+
+/// The bytes of a buffer: a `data:` URI, a file next to the glTF, or the
+/// GLB binary chunk when it has no URI
+fn load_buffer(
+    buffer: &Value,
+    binary: Option<&[u8]>,
+    base_dir: Option<&Path>,
+) -> Result<Vec<u8>, String> {
+    match buffer["uri"].as_str() {
+        Some(uri) if uri.starts_with("data:") => {
+            let (_, data) = uri
+                .split_once(";base64,")
+                .ok_or("buffer data URI is not base64")?;
+            decode_base64(data)
+        }
+        Some(uri) => {
+            let path = base_dir
+                .ok_or_else(|| format!("buffer {uri} needs a base directory"))?
+                .join(uri);
+            std::fs::read(&path).map_err(|e| format!("{}: {e}", path.display()))
+        }
+        None => binary
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| "buffer has no URI and there is no GLB binary chunk".to_string()),
+    }
+}
+
+/// Decode standard base64, ignoring padding and whitespace
+fn decode_base64(text: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut bits, mut count) = (0u32, 0);
+    for c in text.bytes() {
+        let digit = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' | b' ' | b'\n' | b'\r' => continue,
+            _ => return Err(format!("invalid base64 character {:?}", c as char)),
+        };
+        bits = bits << 6 | u32::from(digit);
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            bytes.push((bits >> count) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+/// Array of `N` numbers, if `value` is one
+fn numbers<const N: usize>(value: &Value) -> Option<[f32; N]> {
+    let array = value.as_array().filter(|array| array.len() == N)?;
+    let mut numbers = [0.0; N];
+    for (number, value) in numbers.iter_mut().zip(array) {
+        *number = value.as_f64()? as f32;
+    }
+    Some(numbers)
+}
+
+/// The parsed JSON and loaded buffers of a glTF file
+struct Reader<'a> {
+    root: &'a Value,
+    buffers: Vec<Vec<u8>>,
+}
+
+impl Reader<'_> {
+    fn nodes(&self) -> Result<Vec<GltfNode>, String> {
+        let json = self.root["nodes"].as_array().map_or(&[][..], Vec::as_slice);
+        let mut nodes: Vec<GltfNode> = json
+            .iter()
+            .enumerate()
+            .map(|(index, node)| GltfNode {
+                name: node["name"]
+                    .as_str()
+                    .map_or_else(|| format!("node_{index}"), str::to_string),
+                parent: None,
+                transform: node_transform(node),
+            })
+            .collect();
+        for (index, node) in json.iter().enumerate() {
+            for child in node["children"].as_array().into_iter().flatten() {
+                let child = child
+                    .as_u64()
+                    .and_then(|child| nodes.get_mut(child as usize))
+                    .ok_or_else(|| format!("node {index} has an invalid child"))?;
+                if child.parent.replace(index).is_some() {
+                    return Err(format!("node {} has two parents", child.name));
+                }
+            }
+        }
+        Ok(nodes)
+    }
+
+    fn animations(&self, node_count: usize) -> Result<Vec<GltfAnimation>, String> {
+        let mut animations = Vec::new();
+        for (index, animation) in self.root["animations"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            let mut channels = Vec::new();
+            for channel in animation["channels"].as_array().into_iter().flatten() {
+                let target = &channel["target"];
+                let property = match target["path"].as_str() {
+                    Some("translation") => GltfProperty::Translation,
+                    Some("rotation") => GltfProperty::Rotation,
+                    Some("scale") => GltfProperty::Scale,
+                    // Morph target weights have no counterpart
+                    _ => continue,
+                };
+                let Some(node) = target["node"]
+                    .as_u64()
+                    .map(|node| node as usize)
+                    .filter(|&node| node < node_count)
+                else {
+                    continue;
+                };
+                let sampler = channel["sampler"].as_u64().map_or(&Value::Null, |sampler| {
+                    &animation["samplers"][sampler as usize]
+                });
+                let interpolation = match sampler["interpolation"].as_str() {
+                    Some("STEP") => GltfInterpolation::Step,
+                    Some("CUBICSPLINE") => GltfInterpolation::CubicSpline,
+                    _ => GltfInterpolation::Linear,
+                };
+                let (times, _) = self.accessor(&sampler["input"])?;
+                let (values, components) = self.accessor(&sampler["output"])?;
+                let per_keyframe = if interpolation == GltfInterpolation::CubicSpline {
+                    3
+                } else {
+                    1
+                };
+                if components != property.components()
+                    || values.len() != times.len() * components * per_keyframe
+                {
+                    return Err(format!(
+                        "animation {index}: {property:?} channel of node {node} has mismatched keyframes"
+                    ));
+                }
+                channels.push(GltfChannel {
+                    node,
+                    property,
+                    interpolation,
+                    times,
+                    values,
+                });
+            }
+            animations.push(GltfAnimation {
+                name: animation["name"]
+                    .as_str()
+                    .map_or_else(|| format!("animation_{index}"), str::to_string),
+                channels,
+            });
+        }
+        Ok(animations)
+    }
+
+    /// The elements of the accessor at `index` as floats, one element's
+    /// components after another, and the number of components per element
+    fn accessor(&self, index: &Value) -> Result<(Vec<f32>, usize), String> {
+        let index = index.as_u64().ok_or("sampler without an accessor")? as usize;
+        let accessor = &self.root["accessors"][index];
+        if accessor.get("sparse").is_some() {
+            return Err(format!(
+                "accessor {index}: sparse accessors are not supported"
+            ));
+        }
+        let count = accessor["count"].as_u64().unwrap_or(0) as usize;
+        let components = match accessor["type"].as_str() {
+            Some("SCALAR") => 1,
+            Some("VEC2") => 2,
+            Some("VEC3") => 3,
+            Some("VEC4") => 4,
+            other => return Err(format!("accessor {index}: unsupported type {other:?}")),
+        };
+        let component_type = accessor["componentType"].as_u64().unwrap_or(0);
+        let size = match component_type {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            other => return Err(format!("accessor {index}: unknown component type {other}")),
+        };
+        let normalized = accessor["normalized"].as_bool().unwrap_or(false);
+        let too_large = || format!("accessor {index} is too large");
+        let len = count.checked_mul(components).ok_or_else(too_large)?;
+        let Some(view) = accessor["bufferView"].as_u64() else {
+            // Accessors without a buffer view are all zeros
+            if len > MAX_ZEROED_ACCESSOR_LEN {
+                return Err(too_large());
+            }
+            return Ok((vec![0.0; len], components));
+        };
+        let view = &self.root["bufferViews"][view as usize];
+        let buffer = view["buffer"]
+            .as_u64()
+            .and_then(|buffer| self.buffers.get(buffer as usize))
+            .ok_or_else(|| format!("accessor {index}: missing buffer"))?;
+        let start = (view["byteOffset"].as_u64().unwrap_or(0) as usize)
+            .checked_add(accessor["byteOffset"].as_u64().unwrap_or(0) as usize)
+            .ok_or_else(too_large)?;
+        let stride = view["byteStride"]
+            .as_u64()
+            .map_or(size * components, |stride| stride as usize);
+        if count == 0 {
+            return Ok((Vec::new(), components));
+        }
+        // Checked up front so a bogus count can't allocate or index past the buffer
+        let end = (count - 1)
+            .checked_mul(stride)
+            .and_then(|last| last.checked_add(start))
+            .and_then(|last| last.checked_add(components * size));
+        if end.is_none_or(|end| end > buffer.len()) {
+            return Err(format!("accessor {index} runs past its buffer"));
+        }
+
+        let mut values = Vec::with_capacity(len);
+        for element in 0..count {
+            for component in 0..components {
+                let at = start + element * stride + component * size;
+                values.push(decode_component(
+                    component_type,
+                    normalized,
+                    &buffer[at..at + size],
+                ));
+            }
+        }
+        Ok((values, components))
+    }
+}
+
+/// A little-endian accessor component as a float, mapping normalized
+/// integers to `0..1` (unsigned) or `-1..1` (signed)
+fn decode_component(component_type: u64, normalized: bool, bytes: &[u8]) -> f32 {
+    let (value, max) = match component_type {
+        5120 => (f32::from(bytes[0] as i8), 127.0),
+        5121 => (f32::from(bytes[0]), 255.0),
+        5122 => (f32::from(i16::from_le_bytes([bytes[0], bytes[1]])), 32767.0),
+        5123 => (f32::from(u16::from_le_bytes([bytes[0], bytes[1]])), 65535.0),
+        5125 => (
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32,
+            1.0,
+        ),
+        _ => return f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+    };
+    if normalized {
+        (value / max).max(-1.0)
+    } else {
+        value
+    }
+}
+
+/// A node's local transform, from its `matrix` or its translation, rotation
+/// and scale
+fn node_transform(node: &Value) -> Transform {
+    if let Some(matrix) = numbers::<16>(&node["matrix"]) {
+        let (scale, rotation, translation) =
+            Mat4::from_cols_array(&matrix).to_scale_rotation_translation();
+        return Transform {
+            position: translation.into(),
+            rotation: rotation.into(),
+            scale: scale.into(),
+        };
+    }
+    let mut transform = Transform::new();
+    if let Some([x, y, z]) = numbers(&node["translation"]) {
+        transform.position = Vector3::new(x, y, z);
+    }
+    if let Some([x, y, z, w]) = numbers(&node["rotation"]) {
+        transform.rotation = Quaternion::new(x, y, z, w);
+    }
+    if let Some([x, y, z]) = numbers(&node["scale"]) {
+        transform.scale = Vector3::new(x, y, z);
+    }
+    transform
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A binary glTF file holding `json` and `binary`
+    fn glb(json: &Value, binary: &[u8]) -> Vec<u8> {
+        let mut json = json.to_string().into_bytes();
+        json.resize(json.len().next_multiple_of(4), b' ');
+        let mut bytes = GLB_MAGIC.to_vec();
+        let length = 12 + 8 + json.len() + 8 + binary.len();
+        for word in [2, length as u32, json.len() as u32, GLB_JSON_CHUNK] {
+            bytes.extend(word.to_le_bytes());
+        }
+        bytes.extend(json);
+        bytes.extend((binary.len() as u32).to_le_bytes());
+        bytes.extend(GLB_BIN_CHUNK.to_le_bytes());
+        bytes.extend(binary);
+        bytes
+    }
+
+    #[test]
+    fn test_import_animated_hierarchy() {
+        let binary: Vec<u8> = [0.0f32, 2.0, 1.0, 0.0, 0.0, 3.0, 0.0, 0.0]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        let file = glb(
+            &json!({
+                "asset": {"version": "2.0"},
+                "nodes": [
+                    {"name": "Arm", "children": [1], "translation": [1.0, 0.0, 0.0]},
+                    {"name": "Hand", "scale": [2.0, 2.0, 2.0]}
+                ],
+                "buffers": [{"byteLength": 32}],
+                "bufferViews": [
+                    {"buffer": 0, "byteLength": 8},
+                    {"buffer": 0, "byteOffset": 8, "byteLength": 24}
+                ],
+                "accessors": [
+                    {"bufferView": 0, "componentType": 5126, "count": 2, "type": "SCALAR"},
+                    {"bufferView": 1, "componentType": 5126, "count": 2, "type": "VEC3"}
+                ],
+                "animations": [{
+                    "name": "reach",
+                    "samplers": [{"input": 0, "output": 1}],
+                    "channels": [{"sampler": 0, "target": {"node": 0, "path": "translation"}}]
+                }]
+            }),
+            &binary,
+        );
+        let document = parse_gltf(&file, None).unwrap();
+        assert_eq!(document.nodes[1].parent, Some(0));
+        assert_eq!(
+            document.nodes[1].transform.scale,
+            Vector3::new(2.0, 2.0, 2.0)
+        );
+        assert_eq!(document.animations[0].duration(), 2.0);
+        assert!(document.animations[0].clip_for(1).is_none());
+
+        let mut scene = SceneGraph::new();
+        let ids = scene.import_gltf(&document, Some(0), 0.0);
+        assert_eq!(scene.find_by_name("Hand"), Some(ids[1]));
+        assert_eq!(scene.get_node(ids[1]).unwrap().parent, Some(ids[0]));
+        scene.update_animations(TimeValue::new(1.0));
+        let arm = scene.get_node(ids[0]).unwrap();
+        assert_eq!(arm._local_transform.position, Vector3::new(2.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_oversized_accessors_are_rejected() {
+        let with_input = |input: Value| {
+            glb(
+                &json!({
+                    "nodes": [{"name": "Arm"}],
+                    "buffers": [{"byteLength": 8}],
+                    "bufferViews": [{"buffer": 0, "byteLength": 8}],
+                    "accessors": [
+                        input,
+                        {"bufferView": 0, "componentType": 5126, "count": 2, "type": "SCALAR"}
+                    ],
+                    "animations": [{
+                        "samplers": [{"input": 0, "output": 1}],
+                        "channels": [{"sampler": 0, "target": {"node": 0, "path": "scale"}}]
+                    }]
+                }),
+                &[0; 8],
+            )
+        };
+        for input in [
+            json!({"bufferView": 0, "componentType": 5126, "count": 3, "type": "SCALAR"}),
+            json!({"bufferView": 0, "componentType": 5126, "count": u64::MAX / 2, "type": "VEC4"}),
+            json!({"componentType": 5126, "count": u64::MAX / 2, "type": "SCALAR"}),
+        ] {
+            let error = parse_gltf(&with_input(input), None).unwrap_err();
+            assert!(error.starts_with("accessor 0"), "{error}");
+        }
+    }
+
+    #[test]
+    fn test_cubic_spline_and_data_uri() {
+        assert_eq!(decode_base64("AACAPw==").unwrap(), 1.0f32.to_le_bytes());
+        assert!(parse_gltf(b"{\"buffers\": [{\"uri\": \"data:,raw\"}]}", None).is_err());
+
+        // Leaving 0 and arriving at 1 with zero tangents eases in and out
+        let channel = GltfChannel {
+            node: 0,
+            property: GltfProperty::Scale,
+            interpolation: GltfInterpolation::CubicSpline,
+            times: vec![0.0, 1.0],
+            values: [[0.0; 9], [0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0]].concat(),
+        };
+        let track = channel.track();
+        let track = track
+            .as_any()
+            .downcast_ref::<AnimationTrack<Vector3>>()
+            .unwrap();
+        assert!((track.sample(TimeValue::new(0.5)).x - 0.5).abs() < 1e-4);
+        assert!(track.sample(TimeValue::new(0.25)).x < 0.25);
+    }
+}
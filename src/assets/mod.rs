@@ -0,0 +1,343 @@
+//! # Asset Management
+//!
+//! An [`AssetServer`] loads fonts, PNG images and SVG documents once and
+//! hands out [`AssetHandle`]s that renderables refer to, so a picture used by
+//! a hundred nodes is decoded once and uploaded to the GPU once (the renderer
+//! keeps one texture per handle, see
+//! [`ShapeRenderer::set_asset_server`](crate::render::ShapeRenderer::set_asset_server)).
+//!
+//! - **load**: read and decode a file now; loading the same path again returns the same handle
+//! - **load_bytes**: decode embedded data (e.g. `include_bytes!`) under a name
+//! - **load_async**: decode on a background thread so the preview keeps running; the
+//!   renderer skips assets until they have loaded
+//!
+//! Handles are hashes of the path or name, so they are the same on every run
+//! and frame caches stay valid across renders.
+//!
+//! Node hierarchies and their animations exported from Blender are read
+//! with [`gltf`] and added to a scene with
+//! [`SceneGraph::import_gltf`](crate::scene::SceneGraph::import_gltf).
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use diomanim::assets::AssetServer;
+//! use diomanim::scene::SceneGraph;
+//!
+//! # fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let assets = AssetServer::new();
+//! let logo = assets.load("assets/logo.png")?;
+//! let icon = assets.load_bytes(
+//!     "icon.svg",
+//!     br##"<svg viewBox="0 0 2 2"><circle cx="1" cy="1" r="1" fill="#fc6255"/></svg>"##.to_vec(),
+//! )?;
+//!
+//! let mut scene = SceneGraph::new();
+//! for i in 0..10 {
+//!     // One decode and one GPU texture, however many nodes use it
+//!     scene.add_image(format!("logo_{i}"), logo, 0.3, 0.3).at(i as f32 * 0.2 - 0.9, 0.0, 0.0);
+//! }
+//! scene.add_svg("icon", icon, 0.5, 0.5);
+//! // renderer.set_asset_server(assets.clone());
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod gltf;
+pub mod vector;
+
+pub use gltf::{load_gltf, parse_gltf, GltfDocument};
+pub use vector::{parse_svg, VectorImage, VectorShape};
+
+use crate::pipeline::FrameHasher;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Condvar, Mutex};
+
+/// Reference to an asset loaded by an [`AssetServer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AssetHandle(pub u64);
+
+impl AssetHandle {
+    /// The handle of the asset loaded from `source` (a path or embedded name)
+    pub fn for_source(source: &str) -> Self {
+        let mut hasher = FrameHasher::new();
+        hasher.write_str(source);
+        Self(hasher.finish())
+    }
+}
+
+/// Decoded RGBA8 image with tightly packed rows
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageData {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl ImageData {
+    /// Decode an 8- or 16-bit PNG of any color type
+    pub fn decode_png(bytes: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut decoder = png::Decoder::new(std::io::Cursor::new(bytes));
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size().ok_or("PNG too large")?];
+        let info = reader.next_frame(&mut buffer)?;
+        buffer.truncate(info.buffer_size());
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => buffer,
+            png::ColorType::Rgb => buffer
+                .chunks_exact(3)
+                .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buffer
+                .chunks_exact(2)
+                .flat_map(|ga| [ga[0], ga[0], ga[0], ga[1]])
+                .collect(),
+            png::ColorType::Grayscale => buffer.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+            png::ColorType::Indexed => return Err("unexpanded indexed PNG".into()),
+        };
+        Ok(Self {
+            width: info.width,
+            height: info.height,
+            pixels,
+        })
+    }
+}
+
+/// A decoded asset
+#[derive(Debug, Clone)]
+pub enum Asset {
+    /// TrueType/OpenType font data
+    Font(Arc<Vec<u8>>),
+    Image(Arc<ImageData>),
+    /// SVG document as filled paths
+    Vector(Arc<VectorImage>),
+}
+
+impl Asset {
+    /// Decode `bytes`, picking the kind from the extension of `source`
+    pub fn decode(source: &str, bytes: Vec<u8>) -> Result<Self, Box<dyn std::error::Error>> {
+        let extension = Path::new(source)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        Ok(match extension.as_str() {
+            "ttf" | "otf" | "ttc" => {
+                ttf_parser::Face::parse(&bytes, 0)?;
+                Self::Font(Arc::new(bytes))
+            }
+            "png" => Self::Image(Arc::new(ImageData::decode_png(&bytes)?)),
+            "svg" => Self::Vector(Arc::new(parse_svg(std::str::from_utf8(&bytes)?)?)),
+            _ => return Err(format!("unsupported asset type: {source}").into()),
+        })
+    }
+}
+
+/// Where an asset is in loading
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadState {
+    /// Decoding on a background thread
+    Loading,
+    Loaded,
+    Failed(String),
+    /// Never requested
+    Unknown,
+}
+
+#[derive(Default)]
+struct AssetStore {
+    assets: HashMap<AssetHandle, Result<Asset, String>>,
+    /// Handles requested with [`AssetServer::load_async`] that are still decoding
+    loading: Vec<AssetHandle>,
+}
+
+/// Shared, thread-safe cache of decoded assets
+///
+/// Clones share the same cache, so the scene code and the renderer can each
+/// hold one.
+#[derive(Clone, Default)]
+pub struct AssetServer {
+    store: Arc<(Mutex<AssetStore>, Condvar)>,
+}
+
+impl AssetServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load and decode the file at `path`, or return the handle of an earlier load
+    pub fn load(&self, path: impl AsRef<Path>) -> Result<AssetHandle, Box<dyn std::error::Error>> {
+        let source = path.as_ref().to_string_lossy().into_owned();
+        let handle = AssetHandle::for_source(&source);
+        if self.state(handle) == LoadState::Loaded {
+            return Ok(handle);
+        }
+        self.wait_for(handle);
+        let asset = Asset::decode(&source, std::fs::read(path.as_ref())?)?;
+        self.insert(handle, Ok(asset));
+        Ok(handle)
+    }
+
+    /// Decode embedded `bytes` (kind chosen by the extension of `name`),
+    /// or return the handle of an earlier load under `name`
+    pub fn load_bytes(
+        &self,
+        name: &str,
+        bytes: Vec<u8>,
+    ) -> Result<AssetHandle, Box<dyn std::error::Error>> {
+        let handle = AssetHandle::for_source(name);
+        if self.state(handle) != LoadState::Loaded {
+            self.insert(handle, Ok(Asset::decode(name, bytes)?));
+        }
+        Ok(handle)
+    }
+
+    /// Start loading the file at `path` on a background thread and return
+    /// its handle right away
+    ///
+    /// Until it finishes, [`AssetServer::get`] returns `None` and renderers
+    /// draw nothing for it. Errors are reported by [`AssetServer::state`].
+    pub fn load_async(&self, path: impl AsRef<Path>) -> AssetHandle {
+        let source = path.as_ref().to_string_lossy().into_owned();
+        let handle = AssetHandle::for_source(&source);
+        {
+            let mut store = self.store.0.lock().unwrap();
+            if store.assets.contains_key(&handle) || store.loading.contains(&handle) {
+                return handle;
+            }
+            store.loading.push(handle);
+        }
+
+        let server = self.clone();
+        let decode = move || {
+            let asset = std::fs::read(&source)
+                .map_err(|e| e.to_string())
+                .and_then(|bytes| Asset::decode(&source, bytes).map_err(|e| e.to_string()));
+            if let Err(e) = &asset {
+                eprintln!("Failed to load asset {source}: {e}");
+            }
+            server.insert(handle, asset);
+        };
+        // Browsers have no threads to spare; decode in place
+        #[cfg(target_arch = "wasm32")]
+        decode();
+        #[cfg(not(target_arch = "wasm32"))]
+        std::thread::spawn(decode);
+        handle
+    }
+
+    /// The asset behind `handle`, once it has loaded
+    pub fn get(&self, handle: AssetHandle) -> Option<Asset> {
+        let store = self.store.0.lock().unwrap();
+        store.assets.get(&handle)?.as_ref().ok().cloned()
+    }
+
+    pub fn image(&self, handle: AssetHandle) -> Option<Arc<ImageData>> {
+        match self.get(handle)? {
+            Asset::Image(image) => Some(image),
+            _ => None,
+        }
+    }
+
+    pub fn vector(&self, handle: AssetHandle) -> Option<Arc<VectorImage>> {
+        match self.get(handle)? {
+            Asset::Vector(vector) => Some(vector),
+            _ => None,
+        }
+    }
+
+    pub fn font(&self, handle: AssetHandle) -> Option<Arc<Vec<u8>>> {
+        match self.get(handle)? {
+            Asset::Font(font) => Some(font),
+            _ => None,
+        }
+    }
+
+    pub fn state(&self, handle: AssetHandle) -> LoadState {
+        let store = self.store.0.lock().unwrap();
+        match store.assets.get(&handle) {
+            Some(Ok(_)) => LoadState::Loaded,
+            Some(Err(e)) => LoadState::Failed(e.clone()),
+            None if store.loading.contains(&handle) => LoadState::Loading,
+            None => LoadState::Unknown,
+        }
+    }
+
+    /// Number of background loads still running
+    pub fn pending(&self) -> usize {
+        self.store.0.lock().unwrap().loading.len()
+    }
+
+    /// Block until every background load has finished, so offline renders
+    /// never miss an asset
+    pub fn wait_until_loaded(&self) {
+        let (store, loaded) = &*self.store;
+        let mut store = store.lock().unwrap();
+        while !store.loading.is_empty() {
+            store = loaded.wait(store).unwrap();
+        }
+    }
+
+    /// Block until a background load of `handle`, if any, has finished
+    fn wait_for(&self, handle: AssetHandle) {
+        let (store, loaded) = &*self.store;
+        let mut store = store.lock().unwrap();
+        while store.loading.contains(&handle) {
+            store = loaded.wait(store).unwrap();
+        }
+    }
+
+    fn insert(&self, handle: AssetHandle, asset: Result<Asset, String>) {
+        let (store, loaded) = &*self.store;
+        let mut store = store.lock().unwrap();
+        store.loading.retain(|&loading| loading != handle);
+        store.assets.insert(handle, asset);
+        loaded.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::save_png;
+
+    #[test]
+    fn test_load_deduplicates() {
+        let dir = std::env::temp_dir().join(format!("diomanim_assets_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("dot.png");
+        save_png(&path, 2, 1, &[255, 0, 0, 255, 0, 0, 255, 128]).unwrap();
+
+        let assets = AssetServer::new();
+        let handle = assets.load(&path).unwrap();
+        assert_eq!(assets.load(&path).unwrap(), handle);
+        assert_eq!(assets.load_async(&path), handle);
+        let image = assets.image(handle).unwrap();
+        assert_eq!((image.width, image.height), (2, 1));
+        assert_eq!(image.pixels[4..], [0, 0, 255, 128]);
+        assert!(assets.vector(handle).is_none());
+
+        let missing = assets.load_async(dir.join("missing.png"));
+        assets.wait_until_loaded();
+        assert!(matches!(assets.state(missing), LoadState::Failed(_)));
+        assert_eq!(assets.pending(), 0);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_load_bytes() {
+        let assets = AssetServer::new();
+        let svg = br#"<svg viewBox="0 0 10 10"><rect width="10" height="10"/></svg>"#;
+        let handle = assets.load_bytes("square.svg", svg.to_vec()).unwrap();
+        assert_eq!(handle, AssetHandle::for_source("square.svg"));
+        assert_eq!(assets.vector(handle).unwrap().shapes.len(), 1);
+        assert!(assets.load_bytes("notes.txt", Vec::new()).is_err());
+        assert_eq!(
+            assets.state(AssetHandle::for_source("notes.txt")),
+            LoadState::Unknown
+        );
+    }
+}
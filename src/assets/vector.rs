@@ -0,0 +1,621 @@
+//! SVG documents as filled paths
+//!
+//! Only what diagrams, icons and TeX output commonly use is read: `<path>`,
+//! `<polygon>`, `<polyline>`, `<rect>`, `<circle>` and `<ellipse>` with a
+//! `fill` attribute or `fill:` style, inherited from enclosing `<g>`
+//! elements, along with `transform` attributes and `<use>` of shapes by id
+//! (glyph outlines are defined once in `<defs>` and placed with `<use>`).
+//! Strokes, gradients and text are ignored, and arcs in path data become
+//! straight lines.
+
+use crate::core::path::{Path, PathSegment};
+use crate::core::{Color, Vector3};
+use std::collections::HashMap;
+
+/// An SVG document's filled shapes
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorImage {
+    /// `viewBox` as `[x, y, width, height]`; shape coordinates are in this
+    /// box, with y growing down
+    pub view_box: [f32; 4],
+    pub shapes: Vec<VectorShape>,
+}
+
+/// One closed subpath and its fill
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorShape {
+    pub path: Path,
+    pub fill: Color,
+}
+
+impl VectorImage {
+    /// Width over height of the view box
+    pub fn aspect_ratio(&self) -> f32 {
+        self.view_box[2] / self.view_box[3].max(f32::EPSILON)
+    }
+
+    /// Outline points of every shape, mapped into a `width` x `height` box
+    /// centered on the origin with y up
+    pub fn fitted_outlines(&self, width: f32, height: f32) -> Vec<(Vec<Vector3>, Color)> {
+        let [x, y, view_width, view_height] = self.view_box;
+        let scale_x = width / view_width.max(f32::EPSILON);
+        let scale_y = height / view_height.max(f32::EPSILON);
+        self.shapes
+            .iter()
+            .map(|shape| {
+                let points = shape
+                    .path
+                    .flatten()
+                    .into_iter()
+                    .map(|p| {
+                        Vector3::new(
+                            (p.x - x) * scale_x - width / 2.0,
+                            height / 2.0 - (p.y - y) * scale_y,
+                            0.0,
+                        )
+                    })
+                    .collect();
+                (points, shape.fill)
+            })
+            .collect()
+    }
+}
+
+/// Parse the filled shapes of an SVG document
+pub fn parse_svg(source: &str) -> Result<VectorImage, String> {
+    let mut view_box = None;
+    let mut shapes = Vec::new();
+    // Fills (`None` where none is set) and transforms of enclosing groups
+    let mut groups: Vec<(Option<Option<Color>>, Affine)> = Vec::new();
+    // Depth inside `<defs>` and other elements that are only referenced
+    let mut hidden = 0usize;
+    // Shapes with an id, in their own coordinates, for `<use>`
+    let mut defined: HashMap<String, (Vec<Path>, Option<Option<Color>>)> = HashMap::new();
+
+    let mut rest = source;
+    while let Some(open) = rest.find('<') {
+        rest = &rest[open + 1..];
+        let close = rest.find('>').ok_or("unterminated tag")?;
+        let tag = &rest[..close];
+        rest = &rest[close + 1..];
+
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            match name.trim() {
+                "g" => {
+                    groups.pop();
+                }
+                "defs" | "symbol" | "clipPath" | "mask" => hidden = hidden.saturating_sub(1),
+                _ => {}
+            }
+            continue;
+        }
+
+        let self_closing = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let name_end = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+        let name = &tag[..name_end];
+        let attributes = Attributes(&tag[name_end..]);
+        let (inherited, group_transform) =
+            groups.last().copied().unwrap_or((None, Affine::IDENTITY));
+        let own_transform = attributes.transform()?;
+
+        match name {
+            "svg" if view_box.is_none() => view_box = Some(attributes.view_box()?),
+            "g" if !self_closing => groups.push((
+                attributes.fill().or(inherited),
+                group_transform.then(own_transform),
+            )),
+            "defs" | "symbol" | "clipPath" | "mask" if !self_closing => hidden += 1,
+            "use" => {
+                let Some((paths, fill)) = attributes
+                    .get("href")
+                    .or_else(|| attributes.get("xlink:href"))
+                    .and_then(|href| defined.get(href.trim_start_matches('#')))
+                else {
+                    continue;
+                };
+                let placed = Affine::translate(attributes.number("x"), attributes.number("y"));
+                let transform = group_transform.then(own_transform).then(placed);
+                let fill = (*fill)
+                    .or(attributes.fill())
+                    .unwrap_or(inherited.unwrap_or(Some(Color::BLACK)));
+                if let (0, Some(fill)) = (hidden, fill) {
+                    for path in paths {
+                        shapes.push(VectorShape {
+                            path: transform.apply_path(path),
+                            fill,
+                        });
+                    }
+                }
+            }
+            _ => {
+                let paths: Vec<Path> = attributes
+                    .shape(name)?
+                    .iter()
+                    .map(|path| own_transform.apply_path(path))
+                    .collect();
+                if let Some(id) = attributes.get("id") {
+                    defined.insert(id.to_string(), (paths.clone(), attributes.fill()));
+                }
+                let fill = attributes
+                    .fill()
+                    .unwrap_or(inherited.unwrap_or(Some(Color::BLACK)));
+                let (0, Some(fill)) = (hidden, fill) else {
+                    continue;
+                };
+                for path in &paths {
+                    shapes.push(VectorShape {
+                        path: group_transform.apply_path(path),
+                        fill,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(VectorImage {
+        view_box: view_box.ok_or("no <svg> element")?,
+        shapes,
+    })
+}
+
+/// 2D affine map `[a, b, c, d, e, f]` taking `(x, y)` to
+/// `(a x + c y + e, b x + d y + f)`, as in SVG's `matrix()`
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Affine([f32; 6]);
+
+impl Affine {
+    const IDENTITY: Self = Self([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    fn translate(x: f32, y: f32) -> Self {
+        Self([1.0, 0.0, 0.0, 1.0, x, y])
+    }
+
+    /// The map applying `inner` first, then `self`
+    fn then(self, inner: Self) -> Self {
+        let [a, b, c, d, e, f] = self.0;
+        let [a2, b2, c2, d2, e2, f2] = inner.0;
+        Self([
+            a * a2 + c * b2,
+            b * a2 + d * b2,
+            a * c2 + c * d2,
+            b * c2 + d * d2,
+            a * e2 + c * f2 + e,
+            b * e2 + d * f2 + f,
+        ])
+    }
+
+    fn apply(&self, point: Vector3) -> Vector3 {
+        let [a, b, c, d, e, f] = self.0;
+        Vector3::new(
+            a * point.x + c * point.y + e,
+            b * point.x + d * point.y + f,
+            point.z,
+        )
+    }
+
+    /// Bezier control points map with their curve, so paths map exactly
+    fn apply_path(&self, path: &Path) -> Path {
+        if *self == Self::IDENTITY {
+            return path.clone();
+        }
+        let segments = path
+            .segments
+            .iter()
+            .map(|segment| match *segment {
+                PathSegment::Line(end) => PathSegment::Line(self.apply(end)),
+                PathSegment::Quadratic(control, end) => {
+                    PathSegment::Quadratic(self.apply(control), self.apply(end))
+                }
+                PathSegment::Cubic(c1, c2, end) => {
+                    PathSegment::Cubic(self.apply(c1), self.apply(c2), self.apply(end))
+                }
+            })
+            .collect();
+        Path {
+            start: self.apply(path.start),
+            segments,
+            closed: path.closed,
+        }
+    }
+
+    /// A `transform` attribute's list of `matrix`, `translate`, `scale`,
+    /// `rotate`, `skewX` and `skewY`, applied right to left
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut transform = Self::IDENTITY;
+        let mut rest = text.trim();
+        while !rest.is_empty() {
+            let open = rest
+                .find('(')
+                .ok_or_else(|| format!("invalid transform `{text}`"))?;
+            let close = rest
+                .find(')')
+                .ok_or_else(|| format!("invalid transform `{text}`"))?;
+            let name = rest[..open].trim_matches(|c: char| c.is_whitespace() || c == ',');
+            let args = numbers(&rest[open + 1..close]);
+            let step = match (name, args.as_slice()) {
+                ("matrix", &[a, b, c, d, e, f]) => Self([a, b, c, d, e, f]),
+                ("translate", &[x]) => Self::translate(x, 0.0),
+                ("translate", &[x, y]) => Self::translate(x, y),
+                ("scale", &[s]) => Self([s, 0.0, 0.0, s, 0.0, 0.0]),
+                ("scale", &[x, y]) => Self([x, 0.0, 0.0, y, 0.0, 0.0]),
+                ("rotate", &[angle, ..]) => {
+                    let (sin, cos) = angle.to_radians().sin_cos();
+                    let rotation = Self([cos, sin, -sin, cos, 0.0, 0.0]);
+                    match args[..] {
+                        [_, cx, cy] => Self::translate(cx, cy)
+                            .then(rotation)
+                            .then(Self::translate(-cx, -cy)),
+                        _ => rotation,
+                    }
+                }
+                ("skewX", &[angle]) => Self([1.0, 0.0, angle.to_radians().tan(), 1.0, 0.0, 0.0]),
+                ("skewY", &[angle]) => Self([1.0, angle.to_radians().tan(), 0.0, 1.0, 0.0, 0.0]),
+                _ => return Err(format!("invalid transform `{text}`")),
+            };
+            transform = transform.then(step);
+            rest = rest[close + 1..].trim_start();
+        }
+        Ok(transform)
+    }
+}
+
+/// The attribute text of a tag
+struct Attributes<'a>(&'a str);
+
+impl Attributes<'_> {
+    fn get(&self, name: &str) -> Option<&str> {
+        let mut rest = self.0;
+        loop {
+            let equals = rest.find('=')?;
+            let key = rest[..equals].trim();
+            let value = rest[equals + 1..].trim_start();
+            let quote = value.chars().next()?;
+            let end = value[1..].find(quote)?;
+            if key == name {
+                return Some(&value[1..=end]);
+            }
+            rest = &value[end + 2..];
+        }
+    }
+
+    fn number(&self, name: &str) -> f32 {
+        self.get(name)
+            .and_then(|value| numbers(value).first().copied())
+            .unwrap_or(0.0)
+    }
+
+    /// `Some(None)` for `fill="none"`, `None` when no fill is set
+    fn fill(&self) -> Option<Option<Color>> {
+        let style_fill = self.get("style").and_then(|style| {
+            style.split(';').find_map(|declaration| {
+                let (key, value) = declaration.split_once(':')?;
+                (key.trim() == "fill").then(|| value.trim())
+            })
+        });
+        let fill = style_fill.or_else(|| self.get("fill"))?;
+        Some(parse_color(fill))
+    }
+
+    fn transform(&self) -> Result<Affine, String> {
+        self.get("transform")
+            .map_or(Ok(Affine::IDENTITY), Affine::parse)
+    }
+
+    fn view_box(&self) -> Result<[f32; 4], String> {
+        if let Some(view_box) = self.get("viewBox") {
+            if let [x, y, width, height] = numbers(view_box)[..] {
+                return Ok([x, y, width, height]);
+            }
+            return Err(format!("invalid viewBox `{view_box}`"));
+        }
+        match (self.get("width"), self.get("height")) {
+            (Some(_), Some(_)) => Ok([0.0, 0.0, self.number("width"), self.number("height")]),
+            _ => Err("<svg> has neither a viewBox nor a size".to_string()),
+        }
+    }
+
+    /// Closed paths of a shape element (none for other elements)
+    fn shape(&self, name: &str) -> Result<Vec<Path>, String> {
+        let point = |x: f32, y: f32| Vector3::new(x, y, 0.0);
+        Ok(match name {
+            "path" => parse_path_data(self.get("d").unwrap_or_default())?,
+            "polygon" | "polyline" => {
+                let points: Vec<Vector3> = numbers(self.get("points").unwrap_or_default())
+                    .chunks_exact(2)
+                    .map(|xy| point(xy[0], xy[1]))
+                    .collect();
+                vec![Path::polygon(&points)]
+            }
+            "rect" => {
+                let (x, y) = (self.number("x"), self.number("y"));
+                let (width, height) = (self.number("width"), self.number("height"));
+                vec![Path::polygon(&[
+                    point(x, y),
+                    point(x + width, y),
+                    point(x + width, y + height),
+                    point(x, y + height),
+                ])]
+            }
+            "circle" => {
+                let r = self.number("r");
+                vec![Path::circle(point(self.number("cx"), self.number("cy")), r)]
+            }
+            "ellipse" => vec![Path::ellipse(
+                point(self.number("cx"), self.number("cy")),
+                self.number("rx"),
+                self.number("ry"),
+            )],
+            _ => Vec::new(),
+        })
+    }
+}
+
+/// `#rgb`, `#rrggbb`, a named color or `none` (black if unrecognized)
+fn parse_color(value: &str) -> Option<Color> {
+    let value = value.trim();
+    if value == "none" || value == "transparent" {
+        return None;
+    }
+    Some(match value.strip_prefix('#') {
+        Some(hex) if hex.len() == 3 => {
+            let doubled: String = hex.chars().flat_map(|c| [c, c]).collect();
+            Color::from_hex(&doubled)
+        }
+        Some(hex) if hex.len() == 6 => Color::from_hex(hex),
+        _ => Color::from_name(value).unwrap_or(Color::BLACK),
+    })
+}
+
+/// Every number in `text`, however they are separated
+fn numbers(text: &str) -> Vec<f32> {
+    let mut tokens = PathTokens::new(text);
+    std::iter::from_fn(|| tokens.number()).collect()
+}
+
+/// Commands and numbers of SVG path data
+struct PathTokens<'a> {
+    rest: &'a str,
+}
+
+impl<'a> PathTokens<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { rest: text }
+    }
+
+    fn skip_separators(&mut self) {
+        self.rest = self
+            .rest
+            .trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+
+    fn command(&mut self) -> Option<char> {
+        self.skip_separators();
+        let c = self.rest.chars().next()?;
+        c.is_ascii_alphabetic().then(|| {
+            self.rest = &self.rest[1..];
+            c
+        })
+    }
+
+    /// The next number; `1.5.5` is two numbers and `1-2` is `1` then `-2`
+    fn number(&mut self) -> Option<f32> {
+        self.skip_separators();
+        let bytes = self.rest.as_bytes();
+        let mut end = 0;
+        if matches!(bytes.first(), Some(b'+' | b'-')) {
+            end += 1;
+        }
+        let mut seen_dot = false;
+        while let Some(&b) = bytes.get(end) {
+            match b {
+                b'0'..=b'9' => end += 1,
+                b'.' if !seen_dot => {
+                    seen_dot = true;
+                    end += 1;
+                }
+                b'e' | b'E' if matches!(bytes.get(end + 1), Some(b'0'..=b'9' | b'+' | b'-')) => {
+                    end += 2;
+                    while bytes.get(end).is_some_and(u8::is_ascii_digit) {
+                        end += 1;
+                    }
+                    break;
+                }
+                _ => break,
+            }
+        }
+        let value = self.rest[..end].parse().ok()?;
+        self.rest = &self.rest[end..];
+        Some(value)
+    }
+
+    fn point(&mut self) -> Option<Vector3> {
+        Some(Vector3::new(self.number()?, self.number()?, 0.0))
+    }
+}
+
+/// Subpaths of SVG path data, each closed so it can be filled
+fn parse_path_data(data: &str) -> Result<Vec<Path>, String> {
+    let mut tokens = PathTokens::new(data);
+    let mut paths = Vec::new();
+    let mut path: Option<Path> = None;
+    let mut current = Vector3::zero();
+    // Reflected control point for `S` and `T`
+    let mut last_control: Option<Vector3> = None;
+    let mut command = None;
+
+    loop {
+        if let Some(next) = tokens.command() {
+            command = Some(next);
+        }
+        let Some(c) = command else {
+            break;
+        };
+        if tokens.rest.trim().is_empty() && !matches!(c, 'Z' | 'z') {
+            break;
+        }
+        let relative = c.is_ascii_lowercase();
+        let origin = if relative { current } else { Vector3::zero() };
+        let invalid = || format!("invalid path data near `{}`", tokens_preview(data));
+
+        match c.to_ascii_uppercase() {
+            'M' => {
+                let start = tokens.point().ok_or_else(invalid)? + origin;
+                paths.extend(path.take().map(Path::close));
+                path = Some(Path::new(start));
+                current = start;
+                last_control = None;
+                // Further pairs are implicit line-tos
+                command = Some(if relative { 'l' } else { 'L' });
+                continue;
+            }
+            'Z' => {
+                if let Some(open) = path.take() {
+                    current = open.start;
+                    paths.push(open.close());
+                }
+                last_control = None;
+                command = None;
+                continue;
+            }
+            _ => {}
+        }
+
+        let start = current;
+        let segment = match c.to_ascii_uppercase() {
+            'L' => PathSegment::Line(tokens.point().ok_or_else(invalid)? + origin),
+            'H' => {
+                let x = tokens.number().ok_or_else(invalid)? + origin.x;
+                PathSegment::Line(Vector3::new(x, current.y, 0.0))
+            }
+            'V' => {
+                let y = tokens.number().ok_or_else(invalid)? + origin.y;
+                PathSegment::Line(Vector3::new(current.x, y, 0.0))
+            }
+            'C' => {
+                let c1 = tokens.point().ok_or_else(invalid)? + origin;
+                let c2 = tokens.point().ok_or_else(invalid)? + origin;
+                PathSegment::Cubic(c1, c2, tokens.point().ok_or_else(invalid)? + origin)
+            }
+            'S' => {
+                let c1 = last_control.map_or(current, |c| current * 2.0 - c);
+                let c2 = tokens.point().ok_or_else(invalid)? + origin;
+                PathSegment::Cubic(c1, c2, tokens.point().ok_or_else(invalid)? + origin)
+            }
+            'Q' => {
+                let control = tokens.point().ok_or_else(invalid)? + origin;
+                PathSegment::Quadratic(control, tokens.point().ok_or_else(invalid)? + origin)
+            }
+            'T' => {
+                let control = last_control.map_or(current, |c| current * 2.0 - c);
+                PathSegment::Quadratic(control, tokens.point().ok_or_else(invalid)? + origin)
+            }
+            'A' => {
+                // Radii, rotation and flags are read but the arc is drawn straight
+                for _ in 0..5 {
+                    tokens.number().ok_or_else(invalid)?;
+                }
+                PathSegment::Line(tokens.point().ok_or_else(invalid)? + origin)
+            }
+            _ => return Err(format!("unknown path command `{c}`")),
+        };
+
+        last_control = match segment {
+            PathSegment::Cubic(_, c2, _) if matches!(c, 'C' | 'c' | 'S' | 's') => Some(c2),
+            PathSegment::Quadratic(control, _) if matches!(c, 'Q' | 'q' | 'T' | 't') => {
+                Some(control)
+            }
+            _ => None,
+        };
+        current = segment.end();
+        path.get_or_insert_with(|| Path::new(start))
+            .segments
+            .push(segment);
+    }
+    paths.extend(path.map(Path::close));
+    Ok(paths)
+}
+
+/// The start of path data, for error messages
+fn tokens_preview(data: &str) -> &str {
+    &data[..data.len().min(24)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_svg() {
+        let svg = r##"<?xml version="1.0"?>
+            <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 50">
+              <g fill="#f00">
+                <rect x="10" y="10" width="20" height="20"/>
+                <path d="M50,10 l20 0 0 20z m10-5 h5 v5" fill="none"/>
+              </g>
+              <path d="M0 0 Q10 10 20 0 T40 0" style="stroke: red; fill: #00ff00"/>
+              <circle cx="80" cy="25" r="10" stroke="blue"/>
+            </svg>"##;
+        let image = parse_svg(svg).unwrap();
+        assert_eq!(image.view_box, [0.0, 0.0, 100.0, 50.0]);
+        assert_eq!(image.aspect_ratio(), 2.0);
+        assert_eq!(image.shapes.len(), 3);
+        assert_eq!(image.shapes[0].fill, Color::from_hex("ff0000"));
+        assert_eq!(image.shapes[1].fill, Color::from_hex("00ff00"));
+        assert_eq!(
+            image.shapes[1].path.segments[1],
+            PathSegment::Quadratic(Vector3::new(30.0, -10.0, 0.0), Vector3::new(40.0, 0.0, 0.0))
+        );
+        assert_eq!(image.shapes[2].fill, Color::BLACK);
+
+        // The rectangle maps into a centered box with y up
+        let outlines = image.fitted_outlines(2.0, 1.0);
+        let corner = outlines[0].0[0];
+        assert!((corner.x + 0.8).abs() < 1e-6 && (corner.y - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_parse_svg_defs_use_and_transforms() {
+        // Shaped like dvisvgm output: glyphs defined once, placed with <use>
+        let svg = r##"<svg viewBox='0 0 40 20' xmlns:xlink='http://www.w3.org/1999/xlink'>
+              <defs><path id='g0-1' d='M0 0h2v-2h-2z'/></defs>
+              <g id='page1' transform='matrix(2 0 0 2 -10 0)'>
+                <use x='10' y='5' xlink:href='#g0-1'/>
+                <use href='#g0-1' transform='translate(1)' x='12' y='5' fill='red'/>
+                <rect x='10' y='6' width='4' height='1' transform='scale(1 2)'/>
+              </g>
+            </svg>"##;
+        let image = parse_svg(svg).unwrap();
+        // Only the placed copies and the rule are drawn, not the definition
+        assert_eq!(image.shapes.len(), 3);
+        assert_eq!(image.shapes[0].path.start, Vector3::new(10.0, 10.0, 0.0));
+        assert_eq!(
+            image.shapes[0].path.segments[0],
+            PathSegment::Line(Vector3::new(14.0, 10.0, 0.0))
+        );
+        assert_eq!(image.shapes[1].path.start, Vector3::new(16.0, 10.0, 0.0));
+        assert_eq!(image.shapes[1].fill, Color::from_name("red").unwrap());
+        assert_eq!(image.shapes[2].path.start, Vector3::new(10.0, 24.0, 0.0));
+        assert!(parse_svg("<svg viewBox='0 0 1 1'><g transform='shear(2)'></g></svg>").is_err());
+    }
+
+    #[test]
+    fn test_parse_path_data() {
+        let paths = parse_path_data("M50,10 l20 0 0 20z m10-5 h5 v5").unwrap();
+        assert_eq!(paths.len(), 2);
+        assert_eq!(
+            paths[0].segments[1],
+            PathSegment::Line(Vector3::new(70.0, 30.0, 0.0))
+        );
+        assert_eq!(paths[1].start, Vector3::new(60.0, 5.0, 0.0));
+        assert_eq!(
+            paths[1].segments[1],
+            PathSegment::Line(Vector3::new(65.0, 10.0, 0.0))
+        );
+        assert_eq!(numbers("1.5.5-2e1,3"), vec![1.5, 0.5, -20.0, 3.0]);
+        assert!(parse_path_data("M 0 0 X 1").is_err());
+    }
+}
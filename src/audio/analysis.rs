@@ -0,0 +1,310 @@
+//! Audio analysis
+//!
+//! [`AudioSamples`] holds a sound decoded to mono and measures it at any
+//! time: its loudness, the waveform around that time, or its spectrum in
+//! log-spaced frequency bands, for visualizations that move with the music
+//! (see [`crate::mobjects::AudioVisualizer`]).
+//!
+//! WAV files (8, 16, 24 or 32-bit PCM, or 32-bit float) are decoded here;
+//! with the `audio` feature, other formats such as MP3 decode through rodio.
+
+use std::path::Path;
+
+/// Samples in each spectrum window (a power of two, for the FFT)
+pub const SPECTRUM_WINDOW: usize = 2048;
+
+/// Frequency range spanned by spectrum bands, in Hz
+const MIN_FREQUENCY: f32 = 20.0;
+const MAX_FREQUENCY: f32 = 16_000.0;
+
+/// Level in decibels (relative to full scale) shown as an empty band
+const SPECTRUM_FLOOR_DB: f32 = -60.0;
+
+/// A sound as mono samples in `-1..1`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioSamples {
+    /// Samples per second
+    pub sample_rate: u32,
+    pub samples: Vec<f32>,
+}
+
+impl AudioSamples {
+    pub fn new(sample_rate: u32, samples: Vec<f32>) -> Self {
+        Self {
+            sample_rate: sample_rate.max(1),
+            samples,
+        }
+    }
+
+    /// Decode the audio file at `path`, mixing its channels down to mono
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let is_wav = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("wav"));
+        if is_wav {
+            let bytes = std::fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+            return Self::from_wav(&bytes);
+        }
+        Self::decode_other(path)
+    }
+
+    #[cfg(feature = "audio")]
+    fn decode_other(path: &Path) -> Result<Self, String> {
+        use rodio::Source;
+
+        let file = std::fs::File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let decoder = rodio::Decoder::new(std::io::BufReader::new(file))
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        let channels = usize::from(decoder.channels().max(1));
+        let sample_rate = decoder.sample_rate();
+        let interleaved: Vec<f32> = decoder.map(|sample| f32::from(sample) / 32768.0).collect();
+        Ok(Self::new(sample_rate, mix_down(&interleaved, channels)))
+    }
+
+    #[cfg(not(feature = "audio"))]
+    fn decode_other(path: &Path) -> Result<Self, String> {
+        Err(format!(
+            "{}: only WAV files can be decoded without the audio feature",
+            path.display()
+        ))
+    }
+
+    /// Decode a RIFF WAV file
+    pub fn from_wav(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            return Err("not a WAV file".to_string());
+        }
+        let u16_at = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+        let u32_at = |at: usize| {
+            u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+        };
+
+        // (format, channels, sample rate, bits per sample)
+        let mut format = None;
+        let mut data = None;
+        let mut offset = 12;
+        while offset + 8 <= bytes.len() {
+            let id = &bytes[offset..offset + 4];
+            let size = u32_at(offset + 4) as usize;
+            let body = offset + 8;
+            let end = (body + size).min(bytes.len());
+            match id {
+                b"fmt " if size >= 16 && end - body >= 16 => {
+                    let mut tag = u16_at(body);
+                    // WAVE_FORMAT_EXTENSIBLE keeps the real format in its subformat GUID
+                    if tag == 0xFFFE && end - body >= 26 {
+                        tag = u16_at(body + 24);
+                    }
+                    format = Some((tag, u16_at(body + 2), u32_at(body + 4), u16_at(body + 14)));
+                }
+                b"data" => data = Some(&bytes[body..end]),
+                _ => {}
+            }
+            // Chunks are padded to an even length
+            offset = body + size + size % 2;
+        }
+        let (tag, channels, sample_rate, bits) = format.ok_or("WAV file has no fmt chunk")?;
+        let data = data.ok_or("WAV file has no data chunk")?;
+
+        let decode: fn(&[u8]) -> f32 = match (tag, bits) {
+            (1, 8) => |s| (f32::from(s[0]) - 128.0) / 128.0,
+            (1, 16) => |s| f32::from(i16::from_le_bytes([s[0], s[1]])) / 32768.0,
+            (1, 24) => |s| (i32::from_le_bytes([0, s[0], s[1], s[2]]) >> 8) as f32 / 8_388_608.0,
+            (1, 32) => |s| i32::from_le_bytes([s[0], s[1], s[2], s[3]]) as f32 / 2_147_483_648.0,
+            (3, 32) => |s| f32::from_le_bytes([s[0], s[1], s[2], s[3]]),
+            _ => {
+                return Err(format!(
+                    "unsupported WAV format {tag} with {bits}-bit samples"
+                ))
+            }
+        };
+        let interleaved: Vec<f32> = data
+            .chunks_exact(usize::from(bits / 8))
+            .map(decode)
+            .collect();
+        Ok(Self::new(
+            sample_rate,
+            mix_down(&interleaved, usize::from(channels.max(1))),
+        ))
+    }
+
+    /// Length in seconds
+    pub fn duration(&self) -> f32 {
+        self.samples.len() as f32 / self.sample_rate as f32
+    }
+
+    /// The sample at index `index`, silent outside the sound
+    fn sample(&self, index: i64) -> f32 {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| self.samples.get(index))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Index of the first of `length` samples centered on `time`
+    fn window_start(&self, time: f32, length: usize) -> i64 {
+        (time * self.sample_rate as f32).round() as i64 - length as i64 / 2
+    }
+
+    /// Root mean square level of the `window` seconds around `time` (0..1)
+    pub fn rms(&self, time: f32, window: f32) -> f32 {
+        let length = ((window * self.sample_rate as f32) as usize).max(1);
+        let start = self.window_start(time, length);
+        let sum: f32 = (0..length as i64)
+            .map(|i| self.sample(start + i).powi(2))
+            .sum();
+        (sum / length as f32).sqrt()
+    }
+
+    /// `points` samples evenly spread over the `window` seconds around
+    /// `time`, in `-1..1`
+    pub fn waveform(&self, time: f32, window: f32, points: usize) -> Vec<f32> {
+        let start = time - window / 2.0;
+        let step = window / points.max(1) as f32;
+        (0..points)
+            .map(|i| {
+                let at = start + (i as f32 + 0.5) * step;
+                self.sample((at * self.sample_rate as f32).round() as i64)
+            })
+            .collect()
+    }
+
+    /// Levels of `bands` log-spaced frequency bands (20 Hz up to 16 kHz or
+    /// the Nyquist frequency) around `time`, each in `0..1` on a decibel scale
+    pub fn spectrum(&self, time: f32, bands: usize) -> Vec<f32> {
+        let start = self.window_start(time, SPECTRUM_WINDOW);
+        let mut real: Vec<f32> = (0..SPECTRUM_WINDOW)
+            .map(|i| {
+                // Hann window against spectral leakage
+                let hann = 0.5
+                    - 0.5 * (std::f32::consts::TAU * i as f32 / (SPECTRUM_WINDOW - 1) as f32).cos();
+                self.sample(start + i as i64) * hann
+            })
+            .collect();
+        let mut imaginary = vec![0.0; SPECTRUM_WINDOW];
+        fft(&mut real, &mut imaginary);
+
+        // A full-scale sine peaks at 0 dB; the Hann window halves amplitudes
+        let scale = 4.0 / SPECTRUM_WINDOW as f32;
+        let magnitude = |bin: usize| (real[bin].powi(2) + imaginary[bin].powi(2)).sqrt() * scale;
+        let bin_width = self.sample_rate as f32 / SPECTRUM_WINDOW as f32;
+        let top = MAX_FREQUENCY.min(self.sample_rate as f32 / 2.0);
+        let frequency = |band: f32| MIN_FREQUENCY * (top / MIN_FREQUENCY).powf(band / bands as f32);
+
+        (0..bands)
+            .map(|band| {
+                let low = (frequency(band as f32) / bin_width).floor() as usize;
+                let high = ((frequency(band as f32 + 1.0) / bin_width).ceil() as usize)
+                    .max(low + 1)
+                    .min(SPECTRUM_WINDOW / 2 + 1);
+                let peak = (low.min(high - 1)..high).map(magnitude).fold(0.0, f32::max);
+                let decibels = 20.0 * peak.max(1e-9).log10();
+                ((decibels - SPECTRUM_FLOOR_DB) / -SPECTRUM_FLOOR_DB).clamp(0.0, 1.0)
+            })
+            .collect()
+    }
+}
+
+/// Average each frame of `channels` interleaved samples into one
+fn mix_down(interleaved: &[f32], channels: usize) -> Vec<f32> {
+    interleaved
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+/// In-place radix-2 FFT; the length must be a power of two
+fn fft(real: &mut [f32], imaginary: &mut [f32]) {
+    let n = real.len();
+    // Bit-reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            real.swap(i, j);
+            imaginary.swap(i, j);
+        }
+    }
+
+    let mut length = 2;
+    while length <= n {
+        let angle = -std::f32::consts::TAU / length as f32;
+        for start in (0..n).step_by(length) {
+            for k in 0..length / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (a, b) = (start + k, start + k + length / 2);
+                let re = real[b] * cos - imaginary[b] * sin;
+                let im = real[b] * sin + imaginary[b] * cos;
+                real[b] = real[a] - re;
+                imaginary[b] = imaginary[a] - im;
+                real[a] += re;
+                imaginary[a] += im;
+            }
+        }
+        length <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 16-bit stereo WAV file of `frames`
+    fn wav(sample_rate: u32, frames: &[[i16; 2]]) -> Vec<u8> {
+        let data: Vec<u8> = frames
+            .iter()
+            .flatten()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+        let mut bytes = b"RIFF".to_vec();
+        bytes.extend((36 + data.len() as u32).to_le_bytes());
+        bytes.extend(b"WAVEfmt ");
+        bytes.extend(16u32.to_le_bytes());
+        for word in [1u16, 2] {
+            bytes.extend(word.to_le_bytes());
+        }
+        bytes.extend(sample_rate.to_le_bytes());
+        bytes.extend((sample_rate * 4).to_le_bytes());
+        for word in [4u16, 16] {
+            bytes.extend(word.to_le_bytes());
+        }
+        bytes.extend(b"data");
+        bytes.extend((data.len() as u32).to_le_bytes());
+        bytes.extend(data);
+        bytes
+    }
+
+    #[test]
+    fn test_wav_levels_and_spectrum() {
+        let audio =
+            AudioSamples::from_wav(&wav(4, &[[16384, 0], [-16384, -16384], [0, 0], [0, 0]]))
+                .unwrap();
+        assert_eq!(audio.samples, [0.25, -0.5, 0.0, 0.0]);
+        assert_eq!(audio.duration(), 1.0);
+        assert!(AudioSamples::from_wav(b"RIFF....AVI ").is_err());
+
+        // A 1 kHz tone lights up the band holding 1 kHz and no other
+        let rate = 44_100;
+        let tone: Vec<f32> = (0..rate)
+            .map(|i| (std::f32::consts::TAU * 1000.0 * i as f32 / rate as f32).sin())
+            .collect();
+        let audio = AudioSamples::new(rate, tone);
+        assert!((audio.rms(0.5, 0.1) - std::f32::consts::FRAC_1_SQRT_2).abs() < 0.01);
+        let spectrum = audio.spectrum(0.5, 10);
+        let loudest = (0..10)
+            .max_by(|&a, &b| spectrum[a].total_cmp(&spectrum[b]))
+            .unwrap();
+        // Bands are 20 Hz * 800^(band / 10); 1 kHz falls in band 5
+        assert_eq!(loudest, 5);
+        assert!(spectrum[5] > 0.9);
+        assert!(spectrum[0] < 0.1);
+        assert_eq!(audio.waveform(2.0, 0.1, 4), [0.0; 4]);
+    }
+}
@@ -0,0 +1,177 @@
+//! # Audio Playback
+//!
+//! Plays [`SoundCue`]s scheduled on a [`Timeline`] in sync with preview
+//! playback. Actual output requires the `audio` feature (backed by `rodio`);
+//! without it the player still tracks which cues are due but stays silent.
+//!
+//! Exported videos mix the same cues into their audio track through ffmpeg,
+//! see [`crate::export::VideoExportSettings::with_sound_cues`].
+//!
+//! [`analysis`] decodes sounds to samples and measures their loudness and
+//! spectrum over time, for visualizations synchronized with the cues.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::audio::CuePlayer;
+//! use diomanim::core::*;
+//!
+//! let mut timeline = Timeline::new();
+//! timeline.add_sound("assets/click.wav", 0.5);
+//!
+//! let mut player = CuePlayer::new();
+//! // Called every frame with the previous and current playback time
+//! let fired = player.update(&timeline, 0.4, 0.6, 5.0);
+//! assert_eq!(fired, 1);
+//! ```
+
+pub mod analysis;
+
+pub use analysis::AudioSamples;
+
+use crate::core::{SoundCue, TimeValue, Timeline};
+
+#[cfg(feature = "audio")]
+use std::{fs::File, io::BufReader};
+
+/// Triggers timeline sound cues as playback time advances
+pub struct CuePlayer {
+    #[cfg(feature = "audio")]
+    output: Option<(rodio::OutputStream, rodio::OutputStreamHandle)>,
+    #[cfg(feature = "audio")]
+    sinks: Vec<rodio::Sink>,
+}
+
+impl CuePlayer {
+    /// Open the default audio device (silently disabled if unavailable)
+    pub fn new() -> Self {
+        Self {
+            #[cfg(feature = "audio")]
+            output: match rodio::OutputStream::try_default() {
+                Ok(output) => Some(output),
+                Err(e) => {
+                    eprintln!("Audio output unavailable: {}", e);
+                    None
+                }
+            },
+            #[cfg(feature = "audio")]
+            sinks: Vec::new(),
+        }
+    }
+
+    /// Cues due when playback moves from `previous` to `current`.
+    ///
+    /// A `current` earlier than `previous` is treated as a loop wrap, so cues
+    /// at the end of the clip and at its start both fire.
+    pub fn due_cues(
+        timeline: &Timeline,
+        previous: f32,
+        current: f32,
+        duration: f32,
+    ) -> Vec<&SoundCue> {
+        if current >= previous {
+            timeline.sound_cues_in_range(TimeValue::new(previous), TimeValue::new(current))
+        } else {
+            let mut cues = timeline
+                .sound_cues_in_range(TimeValue::new(previous), TimeValue::new(duration + 1e-3));
+            cues.extend(timeline.sound_cues_in_range(TimeValue::new(0.0), TimeValue::new(current)));
+            cues
+        }
+    }
+
+    /// Start every cue due between `previous` and `current`; returns how many fired
+    pub fn update(
+        &mut self,
+        timeline: &Timeline,
+        previous: f32,
+        current: f32,
+        duration: f32,
+    ) -> usize {
+        let due = Self::due_cues(timeline, previous, current, duration);
+        for cue in &due {
+            self.play(cue);
+        }
+        due.len()
+    }
+
+    #[cfg(feature = "audio")]
+    fn play(&mut self, cue: &SoundCue) {
+        use rodio::Source;
+
+        let Some((_, handle)) = &self.output else {
+            return;
+        };
+
+        let source = match File::open(&cue.path)
+            .map_err(|e| e.to_string())
+            .and_then(|file| rodio::Decoder::new(BufReader::new(file)).map_err(|e| e.to_string()))
+        {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("Failed to load sound {}: {}", cue.path.display(), e);
+                return;
+            }
+        };
+
+        match rodio::Sink::try_new(handle) {
+            Ok(sink) => {
+                sink.append(source.amplify(cue.volume));
+                self.sinks.retain(|sink| !sink.empty());
+                self.sinks.push(sink);
+            }
+            Err(e) => eprintln!("Failed to play sound {}: {}", cue.path.display(), e),
+        }
+    }
+
+    #[cfg(not(feature = "audio"))]
+    #[allow(clippy::unused_self)]
+    fn play(&mut self, _cue: &SoundCue) {}
+
+    /// Pause all playing sounds
+    pub fn pause(&self) {
+        #[cfg(feature = "audio")]
+        for sink in &self.sinks {
+            sink.pause();
+        }
+    }
+
+    /// Resume sounds paused with [`CuePlayer::pause`]
+    pub fn resume(&self) {
+        #[cfg(feature = "audio")]
+        for sink in &self.sinks {
+            sink.play();
+        }
+    }
+
+    /// Stop and discard all playing sounds (e.g. after a seek)
+    pub fn stop(&mut self) {
+        #[cfg(feature = "audio")]
+        for sink in self.sinks.drain(..) {
+            sink.stop();
+        }
+    }
+}
+
+impl Default for CuePlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_due_cues_wraps_on_loop() {
+        let mut timeline = Timeline::new();
+        timeline.add_sound("start.wav", 0.0);
+        timeline.add_sound("end.wav", 4.9);
+
+        assert_eq!(CuePlayer::due_cues(&timeline, 0.0, 0.1, 5.0).len(), 1);
+        assert!(CuePlayer::due_cues(&timeline, 1.0, 2.0, 5.0).is_empty());
+
+        let wrapped = CuePlayer::due_cues(&timeline, 4.8, 0.05, 5.0);
+        assert_eq!(wrapped.len(), 2);
+    }
+}
@@ -75,6 +75,13 @@ impl Camera {
         }
     }
 
+    /// Combined projection * view matrix (row-major, wgpu clip space)
+    pub fn view_projection_matrix(&self) -> Matrix4 {
+        self.projection_matrix() * self.view_matrix()
+    }
+
+    // Projection matrices are right-handed (camera looks down -Z in view space)
+    // and map depth to wgpu's [0, 1] clip range.
     fn perspective_projection_matrix(&self) -> Matrix4 {
         let f = 1.0 / (self.fov * 0.5).tan();
         let nf = 1.0 / (self.near_clip - self.far_clip);
@@ -83,8 +90,13 @@ impl Camera {
             data: [
                 [f / self.aspect_ratio, 0.0, 0.0, 0.0],
                 [0.0, f, 0.0, 0.0],
-                [0.0, 0.0, (self.far_clip + self.near_clip) * nf, -1.0],
-                [0.0, 0.0, 2.0 * self.far_clip * self.near_clip * nf, 0.0],
+                [
+                    0.0,
+                    0.0,
+                    self.far_clip * nf,
+                    self.far_clip * self.near_clip * nf,
+                ],
+                [0.0, 0.0, -1.0, 0.0],
             ],
         }
     }
@@ -98,16 +110,17 @@ impl Camera {
             data: [
                 [2.0 / width, 0.0, 0.0, 0.0],
                 [0.0, 2.0 / height, 0.0, 0.0],
-                [0.0, 0.0, 2.0 * nf, 0.0],
-                [0.0, 0.0, (self.far_clip + self.near_clip) * nf, 1.0],
+                [0.0, 0.0, nf, self.near_clip * nf],
+                [0.0, 0.0, 0.0, 1.0],
             ],
         }
     }
 
-    fn look_at_matrix(&self, target: Vector3, up: Vector3) -> Matrix4 {
+    /// View matrix for a camera at the current position looking at `target`
+    pub fn look_at_matrix(&self, target: Vector3, up: Vector3) -> Matrix4 {
         let forward = (target - self.transform.position).normalized();
-        let right = up.cross(&forward).normalized();
-        let up = forward.cross(&right);
+        let right = forward.cross(&up).normalized();
+        let up = right.cross(&forward);
 
         let pos = self.transform.position;
         Matrix4 {
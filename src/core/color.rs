@@ -35,6 +35,31 @@ impl Color {
         Self::new(r, g, b)
     }
 
+    /// Color from a `0xRRGGBB` literal, usable in constants
+    pub const fn from_rgb_hex(rgb: u32) -> Self {
+        Self {
+            r: ((rgb >> 16) & 0xff) as f32 / 255.0,
+            g: ((rgb >> 8) & 0xff) as f32 / 255.0,
+            b: (rgb & 0xff) as f32 / 255.0,
+            a: 1.0,
+        }
+    }
+
+    /// Look up a color by name, ignoring case, spaces and `grey`/`gray`.
+    /// Names follow Manim, so `"blue"` is Manim's `BLUE_C` rather than
+    /// [`Color::BLUE`], and `"teal_e"` is [`Color::TEAL_E`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        let key = name
+            .trim()
+            .to_ascii_lowercase()
+            .replace([' ', '-'], "_")
+            .replace("grey", "gray");
+        NAMED_COLORS
+            .iter()
+            .find(|(known, _)| *known == key)
+            .map(|(_, color)| *color)
+    }
+
     pub fn from_rgb8(r: u8, g: u8, b: u8) -> Self {
         Self::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0)
     }
@@ -47,6 +72,12 @@ impl Color {
         )
     }
 
+    /// `#rrggbb` notation (alpha is dropped), as used by SVG and CSS
+    pub fn to_hex(&self) -> String {
+        let (r, g, b) = self.to_rgb8();
+        format!("#{r:02x}{g:02x}{b:02x}")
+    }
+
     pub fn lerp(&self, other: &Color, t: f32) -> Color {
         Color {
             r: self.r + (other.r - self.r) * t,
@@ -105,6 +136,12 @@ impl Color {
     pub fn to_f32_array(&self) -> [f32; 4] {
         [self.r, self.g, self.b, self.a]
     }
+
+    /// Linear-light RGBA (alpha unchanged), as 3D formats such as glTF expect
+    pub fn to_linear_array(&self) -> [f32; 4] {
+        let [r, g, b] = [self.r, self.g, self.b].map(srgb_to_linear);
+        [r, g, b, self.a]
+    }
 }
 
 impl Default for Color {
@@ -239,16 +276,338 @@ impl Color {
     };
 }
 
+/// Color space to interpolate through, see [`Color::interpolate`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorInterpolation {
+    /// Straight blend of the sRGB channels
+    #[default]
+    Rgb,
+    /// Around the hue wheel (the short way), blending saturation and value
+    Hsv,
+    /// Around the hue wheel (the short way), blending saturation and lightness
+    Hsl,
+    /// Through OkLab, which keeps perceived lightness even and avoids the
+    /// muddy middles of RGB blends
+    Oklab,
+}
+
+// Color spaces
+impl Color {
+    /// Hue in degrees (`0..360`), saturation and value (`0..=1`)
+    pub fn to_hsv(&self) -> (f32, f32, f32) {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let saturation = if max > 0.0 { (max - min) / max } else { 0.0 };
+        (self.hue(max, min), saturation, max)
+    }
+
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let saturation = saturation.clamp(0.0, 1.0);
+        let value = value.clamp(0.0, 1.0);
+        let chroma = value * saturation;
+        Self::from_hue_chroma(hue, chroma, value - chroma)
+    }
+
+    /// Hue in degrees (`0..360`), saturation and lightness (`0..=1`)
+    pub fn to_hsl(&self) -> (f32, f32, f32) {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let lightness = f32::midpoint(max, min);
+        let saturation = if max - min <= f32::EPSILON {
+            0.0
+        } else {
+            (max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+        (self.hue(max, min), saturation, lightness)
+    }
+
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Self {
+        let saturation = saturation.clamp(0.0, 1.0);
+        let lightness = lightness.clamp(0.0, 1.0);
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        Self::from_hue_chroma(hue, chroma, lightness - chroma / 2.0)
+    }
+
+    /// OkLab lightness (`0..=1`) and the `a` (green-red) and `b`
+    /// (blue-yellow) axes
+    pub fn to_oklab(&self) -> (f32, f32, f32) {
+        let [red, green, blue] = [self.r, self.g, self.b].map(srgb_to_linear);
+        let long = (0.412_221_46 * red + 0.536_332_55 * green + 0.051_445_995 * blue).cbrt();
+        let medium = (0.211_903_5 * red + 0.680_699_5 * green + 0.107_396_96 * blue).cbrt();
+        let short = (0.088_302_46 * red + 0.281_718_85 * green + 0.629_978_7 * blue).cbrt();
+        (
+            0.210_454_26 * long + 0.793_617_8 * medium - 0.004_072_047 * short,
+            1.977_998_5 * long - 2.428_592_2 * medium + 0.450_593_7 * short,
+            0.025_904_037 * long + 0.782_771_77 * medium - 0.808_675_77 * short,
+        )
+    }
+
+    /// Inverse of [`Color::to_oklab`]; colors outside sRGB are clamped
+    pub fn from_oklab(lightness: f32, a: f32, b: f32) -> Self {
+        let long = (lightness + 0.396_337_78 * a + 0.215_803_76 * b).powi(3);
+        let medium = (lightness - 0.105_561_346 * a - 0.063_854_17 * b).powi(3);
+        let short = (lightness - 0.089_484_18 * a - 1.291_485_5 * b).powi(3);
+        Self::new(
+            linear_to_srgb(4.076_741_7 * long - 3.307_711_6 * medium + 0.230_969_94 * short),
+            linear_to_srgb(-1.268_438 * long + 2.609_757_4 * medium - 0.341_319_38 * short),
+            linear_to_srgb(-0.004_196_086_3 * long - 0.703_418_6 * medium + 1.707_614_7 * short),
+        )
+    }
+
+    /// Blend towards `other` by `t` through `space` (alpha blends linearly)
+    pub fn interpolate(&self, other: &Color, t: f32, space: ColorInterpolation) -> Color {
+        let blend = |from: f32, to: f32| from + (to - from) * t;
+        let color = match space {
+            ColorInterpolation::Rgb => return self.lerp(other, t),
+            ColorInterpolation::Hsv => {
+                let ((h0, s0, v0), (h1, s1, v1)) = (self.to_hsv(), other.to_hsv());
+                let (h0, h1) = hue_endpoints(h0, s0, h1, s1);
+                Color::from_hsv(blend(h0, h1), blend(s0, s1), blend(v0, v1))
+            }
+            ColorInterpolation::Hsl => {
+                let ((h0, s0, l0), (h1, s1, l1)) = (self.to_hsl(), other.to_hsl());
+                let (h0, h1) = hue_endpoints(h0, s0, h1, s1);
+                Color::from_hsl(blend(h0, h1), blend(s0, s1), blend(l0, l1))
+            }
+            ColorInterpolation::Oklab => {
+                let ((l0, a0, b0), (l1, a1, b1)) = (self.to_oklab(), other.to_oklab());
+                Color::from_oklab(blend(l0, l1), blend(a0, a1), blend(b0, b1))
+            }
+        };
+        color.with_opacity(blend(self.a, other.a))
+    }
+
+    fn hue(&self, max: f32, min: f32) -> f32 {
+        let delta = max - min;
+        if delta <= f32::EPSILON {
+            return 0.0;
+        }
+        let sector = if max == self.r {
+            ((self.g - self.b) / delta).rem_euclid(6.0)
+        } else if max == self.g {
+            (self.b - self.r) / delta + 2.0
+        } else {
+            (self.r - self.g) / delta + 4.0
+        };
+        sector * 60.0
+    }
+
+    /// RGB from a hue, the chroma and the amount added to every channel
+    fn from_hue_chroma(hue: f32, chroma: f32, base: f32) -> Self {
+        let sector = hue.rem_euclid(360.0) / 60.0;
+        let second = chroma * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
+        let (r, g, b) = match sector as u32 {
+            0 => (chroma, second, 0.0),
+            1 => (second, chroma, 0.0),
+            2 => (0.0, chroma, second),
+            3 => (0.0, second, chroma),
+            4 => (second, 0.0, chroma),
+            _ => (chroma, 0.0, second),
+        };
+        Self::new(r + base, g + base, b + base)
+    }
+}
+
+/// Hues to blend between, going the short way round. A gray has no hue of
+/// its own and takes the other end's, so blends with gray don't swing
+/// through unrelated colors.
+fn hue_endpoints(h0: f32, s0: f32, h1: f32, s1: f32) -> (f32, f32) {
+    let (h0, h1) = match (s0 <= f32::EPSILON, s1 <= f32::EPSILON) {
+        (true, false) => (h1, h1),
+        (false, true) => (h0, h0),
+        _ => (h0, h1),
+    };
+    let mut delta = (h1 - h0).rem_euclid(360.0);
+    if delta > 180.0 {
+        delta -= 360.0;
+    }
+    (h0, h0 + delta)
+}
+
+fn srgb_to_linear(channel: f32) -> f32 {
+    if channel <= 0.040_45 {
+        channel / 12.92
+    } else {
+        ((channel + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(channel: f32) -> f32 {
+    if channel <= 0.003_130_8 {
+        channel * 12.92
+    } else {
+        1.055 * channel.max(0.0).powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Manim's palette: each hue in five shades from light (`_A`) to dark
+/// (`_E`), with `_C` the hue Manim means by the bare name
+impl Color {
+    pub const BLUE_A: Color = Color::from_rgb_hex(0xC7_E9_F1);
+    pub const BLUE_B: Color = Color::from_rgb_hex(0x9C_DC_EB);
+    pub const BLUE_C: Color = Color::from_rgb_hex(0x58_C4_DD);
+    pub const BLUE_D: Color = Color::from_rgb_hex(0x29_AB_CA);
+    pub const BLUE_E: Color = Color::from_rgb_hex(0x1C_75_8A);
+
+    pub const TEAL_A: Color = Color::from_rgb_hex(0xAC_EA_D7);
+    pub const TEAL_B: Color = Color::from_rgb_hex(0x76_DD_C0);
+    pub const TEAL_C: Color = Color::from_rgb_hex(0x5C_D0_B3);
+    pub const TEAL_D: Color = Color::from_rgb_hex(0x55_C1_A7);
+    pub const TEAL_E: Color = Color::from_rgb_hex(0x49_A8_8F);
+
+    pub const GREEN_A: Color = Color::from_rgb_hex(0xC9_E2_AE);
+    pub const GREEN_B: Color = Color::from_rgb_hex(0xA6_CF_8C);
+    pub const GREEN_C: Color = Color::from_rgb_hex(0x83_C1_67);
+    pub const GREEN_D: Color = Color::from_rgb_hex(0x77_B0_5D);
+    pub const GREEN_E: Color = Color::from_rgb_hex(0x69_9C_52);
+
+    pub const YELLOW_A: Color = Color::from_rgb_hex(0xFF_F1_B6);
+    pub const YELLOW_B: Color = Color::from_rgb_hex(0xFF_EA_94);
+    pub const YELLOW_C: Color = Color::from_rgb_hex(0xFF_FF_00);
+    pub const YELLOW_D: Color = Color::from_rgb_hex(0xF4_D3_45);
+    pub const YELLOW_E: Color = Color::from_rgb_hex(0xE8_C1_1C);
+
+    pub const GOLD_A: Color = Color::from_rgb_hex(0xF7_C7_97);
+    pub const GOLD_B: Color = Color::from_rgb_hex(0xF9_B7_75);
+    pub const GOLD_C: Color = Color::from_rgb_hex(0xF0_AC_5F);
+    pub const GOLD_D: Color = Color::from_rgb_hex(0xE1_A1_58);
+    pub const GOLD_E: Color = Color::from_rgb_hex(0xC7_8D_46);
+
+    pub const RED_A: Color = Color::from_rgb_hex(0xF7_A1_A3);
+    pub const RED_B: Color = Color::from_rgb_hex(0xFF_80_80);
+    pub const RED_C: Color = Color::from_rgb_hex(0xFC_62_55);
+    pub const RED_D: Color = Color::from_rgb_hex(0xE6_5A_4C);
+    pub const RED_E: Color = Color::from_rgb_hex(0xCF_50_44);
+
+    pub const MAROON_A: Color = Color::from_rgb_hex(0xEC_AB_C1);
+    pub const MAROON_B: Color = Color::from_rgb_hex(0xEC_92_AB);
+    pub const MAROON_C: Color = Color::from_rgb_hex(0xC5_5F_73);
+    pub const MAROON_D: Color = Color::from_rgb_hex(0xA2_4D_61);
+    pub const MAROON_E: Color = Color::from_rgb_hex(0x94_42_4F);
+
+    pub const PURPLE_A: Color = Color::from_rgb_hex(0xCA_A3_E8);
+    pub const PURPLE_B: Color = Color::from_rgb_hex(0xB1_89_C6);
+    pub const PURPLE_C: Color = Color::from_rgb_hex(0x9A_72_AC);
+    pub const PURPLE_D: Color = Color::from_rgb_hex(0x71_55_82);
+    pub const PURPLE_E: Color = Color::from_rgb_hex(0x64_41_72);
+
+    pub const GRAY_A: Color = Color::from_rgb_hex(0xDD_DD_DD);
+    pub const GRAY_B: Color = Color::from_rgb_hex(0xBB_BB_BB);
+    pub const GRAY_C: Color = Color::from_rgb_hex(0x88_88_88);
+    pub const GRAY_D: Color = Color::from_rgb_hex(0x44_44_44);
+    pub const GRAY_E: Color = Color::from_rgb_hex(0x22_22_22);
+
+    pub const PURE_RED: Color = Color::from_rgb_hex(0xFF_00_00);
+    pub const PURE_GREEN: Color = Color::from_rgb_hex(0x00_FF_00);
+    pub const PURE_BLUE: Color = Color::from_rgb_hex(0x00_00_FF);
+    pub const MANIM_PINK: Color = Color::from_rgb_hex(0xD1_47_BD);
+    pub const LIGHT_PINK: Color = Color::from_rgb_hex(0xDC_75_CD);
+    pub const MANIM_ORANGE: Color = Color::from_rgb_hex(0xFF_86_2F);
+    pub const LIGHT_BROWN: Color = Color::from_rgb_hex(0xCD_85_3F);
+    pub const DARK_BROWN: Color = Color::from_rgb_hex(0x8B_45_13);
+    pub const GRAY_BROWN: Color = Color::from_rgb_hex(0x73_63_57);
+}
+
+/// Names [`Color::from_name`] knows, in Manim's spelling
+pub const NAMED_COLORS: &[(&str, Color)] = &[
+    ("blue", Color::BLUE_C),
+    ("blue_a", Color::BLUE_A),
+    ("blue_b", Color::BLUE_B),
+    ("blue_c", Color::BLUE_C),
+    ("blue_d", Color::BLUE_D),
+    ("blue_e", Color::BLUE_E),
+    ("teal", Color::TEAL_C),
+    ("teal_a", Color::TEAL_A),
+    ("teal_b", Color::TEAL_B),
+    ("teal_c", Color::TEAL_C),
+    ("teal_d", Color::TEAL_D),
+    ("teal_e", Color::TEAL_E),
+    ("green", Color::GREEN_C),
+    ("green_a", Color::GREEN_A),
+    ("green_b", Color::GREEN_B),
+    ("green_c", Color::GREEN_C),
+    ("green_d", Color::GREEN_D),
+    ("green_e", Color::GREEN_E),
+    ("yellow", Color::YELLOW_C),
+    ("yellow_a", Color::YELLOW_A),
+    ("yellow_b", Color::YELLOW_B),
+    ("yellow_c", Color::YELLOW_C),
+    ("yellow_d", Color::YELLOW_D),
+    ("yellow_e", Color::YELLOW_E),
+    ("gold", Color::GOLD_C),
+    ("gold_a", Color::GOLD_A),
+    ("gold_b", Color::GOLD_B),
+    ("gold_c", Color::GOLD_C),
+    ("gold_d", Color::GOLD_D),
+    ("gold_e", Color::GOLD_E),
+    ("red", Color::RED_C),
+    ("red_a", Color::RED_A),
+    ("red_b", Color::RED_B),
+    ("red_c", Color::RED_C),
+    ("red_d", Color::RED_D),
+    ("red_e", Color::RED_E),
+    ("maroon", Color::MAROON_C),
+    ("maroon_a", Color::MAROON_A),
+    ("maroon_b", Color::MAROON_B),
+    ("maroon_c", Color::MAROON_C),
+    ("maroon_d", Color::MAROON_D),
+    ("maroon_e", Color::MAROON_E),
+    ("purple", Color::PURPLE_C),
+    ("purple_a", Color::PURPLE_A),
+    ("purple_b", Color::PURPLE_B),
+    ("purple_c", Color::PURPLE_C),
+    ("purple_d", Color::PURPLE_D),
+    ("purple_e", Color::PURPLE_E),
+    ("gray", Color::GRAY_C),
+    ("gray_a", Color::GRAY_A),
+    ("gray_b", Color::GRAY_B),
+    ("gray_c", Color::GRAY_C),
+    ("gray_d", Color::GRAY_D),
+    ("gray_e", Color::GRAY_E),
+    ("white", Color::WHITE),
+    ("black", Color::BLACK),
+    ("lighter_gray", Color::from_rgb_hex(0xEE_EE_EE)),
+    ("light_gray", Color::GRAY_B),
+    ("dark_gray", Color::GRAY_D),
+    ("darker_gray", Color::GRAY_E),
+    ("dark_blue", Color::BLUE_E),
+    ("pure_red", Color::PURE_RED),
+    ("pure_green", Color::PURE_GREEN),
+    ("pure_blue", Color::PURE_BLUE),
+    ("pink", Color::MANIM_PINK),
+    ("light_pink", Color::LIGHT_PINK),
+    ("orange", Color::MANIM_ORANGE),
+    ("light_brown", Color::LIGHT_BROWN),
+    ("dark_brown", Color::DARK_BROWN),
+    ("gray_brown", Color::GRAY_BROWN),
+    ("cyan", Color::CYAN),
+    ("magenta", Color::MAGENTA),
+    ("lime", Color::LIME),
+    ("brown", Color::BROWN),
+    ("transparent", Color::TRANSPARENT),
+];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ColorGradient {
     stops: Vec<(f32, Color)>,
+    #[serde(default)]
+    interpolation: ColorInterpolation,
 }
 
 impl ColorGradient {
     pub fn new(stops: Vec<(f32, Color)>) -> Self {
         let mut stops = stops;
         stops.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
-        Self { stops }
+        Self {
+            stops,
+            interpolation: ColorInterpolation::Rgb,
+        }
+    }
+
+    /// Blend between stops through `interpolation` instead of RGB
+    pub fn with_interpolation(mut self, interpolation: ColorInterpolation) -> Self {
+        self.interpolation = interpolation;
+        self
     }
 
     pub fn from_colors(colors: &[Color]) -> Self {
@@ -282,7 +641,7 @@ impl ColorGradient {
 
             if t >= t0 && t <= t1 {
                 let local_t = (t - t0) / (t1 - t0);
-                return color0.lerp(&color1, local_t);
+                return color0.interpolate(&color1, local_t, self.interpolation);
             }
         }
 
@@ -293,6 +652,27 @@ impl ColorGradient {
         self.stops.push((position.clamp(0.0, 1.0), color));
         self.stops.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
     }
+
+    /// `n` colors evenly spaced along the gradient, ends included
+    pub fn samples(&self, n: usize) -> Vec<Color> {
+        match n {
+            0 => Vec::new(),
+            1 => vec![self.evaluate(0.0)],
+            _ => (0..n)
+                .map(|i| self.evaluate(i as f32 / (n - 1) as f32))
+                .collect(),
+        }
+    }
+}
+
+/// `n` colors running smoothly through `colors` in order, like Manim's
+/// `color_gradient`: for coloring the parts of a group consistently
+pub fn color_gradient(colors: &[Color], n: usize) -> Vec<Color> {
+    match colors {
+        [] => Vec::new(),
+        [only] => vec![*only; n],
+        _ => ColorGradient::from_colors(colors).samples(n),
+    }
 }
 
 #[cfg(test)]
@@ -328,6 +708,58 @@ mod tests {
         assert_eq!(c.to_rgb8(), (255, 128, 0));
     }
 
+    #[test]
+    fn test_color_spaces() {
+        let close = |x: f32, y: f32| (x - y).abs() < 1e-3;
+        let orange = Color::from_rgb8(255, 128, 0);
+        let (hue, saturation, value) = orange.to_hsv();
+        assert!((hue - 30.1).abs() < 0.1 && close(saturation, 1.0) && close(value, 1.0));
+        let (hue, saturation, lightness) = orange.to_hsl();
+        assert!((hue - 30.1).abs() < 0.1 && close(saturation, 1.0) && close(lightness, 0.5));
+
+        for color in [orange, Color::TEAL_C, Color::GRAY_D, Color::WHITE] {
+            let hsv = color.to_hsv();
+            let hsl = color.to_hsl();
+            let lab = color.to_oklab();
+            for round_trip in [
+                Color::from_hsv(hsv.0, hsv.1, hsv.2),
+                Color::from_hsl(hsl.0, hsl.1, hsl.2),
+                Color::from_oklab(lab.0, lab.1, lab.2),
+            ] {
+                assert!(close(round_trip.r, color.r));
+                assert!(close(round_trip.g, color.g));
+                assert!(close(round_trip.b, color.b));
+            }
+        }
+        // OkLab white is lightness 1 with no chroma
+        let (lightness, a, b) = Color::WHITE.to_oklab();
+        assert!(close(lightness, 1.0) && close(a, 0.0) && close(b, 0.0));
+
+        // Red to blue through HSV passes magenta, not dark purple
+        let middle = Color::RED.interpolate(&Color::BLUE, 0.5, ColorInterpolation::Hsv);
+        assert_eq!(middle.to_rgb8(), (255, 0, 255));
+        // The middle of an OkLab black-white blend is a neutral gray
+        // halfway in perceived lightness
+        let gray = Color::BLACK.interpolate(&Color::WHITE, 0.5, ColorInterpolation::Oklab);
+        assert!((gray.to_oklab().0 - 0.5).abs() < 1e-3 && (gray.r - gray.b).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_manim_palette() {
+        assert_eq!(Color::BLUE_C.to_rgb8(), (0x58, 0xc4, 0xdd));
+        assert_eq!(Color::from_name("Teal"), Some(Color::TEAL_C));
+        assert_eq!(Color::from_name("grey_a"), Some(Color::GRAY_A));
+        assert_eq!(Color::from_name("pure red"), Some(Color::PURE_RED));
+        assert_eq!(Color::from_name("chartreuse"), None);
+
+        let shades = color_gradient(&[Color::BLACK, Color::WHITE], 5);
+        assert_eq!(shades.len(), 5);
+        assert_eq!(shades[0], Color::BLACK);
+        assert!((shades[2].g - 0.5).abs() < 1e-6);
+        assert_eq!(shades[4], Color::WHITE);
+        assert_eq!(color_gradient(&[Color::RED], 3), [Color::RED; 3]);
+    }
+
     #[test]
     fn test_color_from_rgb8() {
         let c = Color::from_rgb8(255, 128, 0);
@@ -340,6 +772,7 @@ mod tests {
     fn test_color_to_rgb8() {
         let c = Color::new(1.0, 0.5, 0.0);
         assert_eq!(c.to_rgb8(), (255, 127, 0));
+        assert_eq!(c.to_hex(), "#ff7f00");
     }
 
     #[test]
@@ -5,9 +5,10 @@
 //!
 //! ## Key Components
 //!
-//! - **Vectors**: 2D and 3D vector operations with SIMD optimization
+//! - **Vectors**: 2D and 3D vector operations backed by glam's SIMD types
 //! - **Colors**: RGBA color representation with conversion utilities
-//! - **Transforms**: Position, rotation, and scale transformations
+//! - **Transforms**: Position, rotation, and scale transformations, with glam-backed quaternions and matrices
+//! - **Paths**: Line and Bezier paths sampled by arc length
 //! - **Time**: High-precision timing with nanosecond accuracy
 //! - **Camera**: View and projection matrix calculations
 //!
@@ -30,12 +31,14 @@
 
 pub mod camera;
 pub mod color;
+pub mod path;
 pub mod time;
 pub mod transform;
 pub mod vector;
 
 pub use camera::*;
 pub use color::*;
+pub use path::*;
 pub use time::*;
 pub use transform::*;
 pub use vector::*;
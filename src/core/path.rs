@@ -0,0 +1,389 @@
+//! Paths made of line and Bezier segments
+//!
+//! A [`Path`] is a chain of segments starting at a point, used for motion
+//! paths and outlines. [`PathSampler`] measures it by arc length, so points
+//! can be placed at even distances whatever the curvature:
+//!
+//! ```rust
+//! use diomanim::core::*;
+//!
+//! let path = Path::new(Vector3::zero())
+//!     .line_to(Vector3::new(2.0, 0.0, 0.0))
+//!     .cubic_to(
+//!         Vector3::new(3.0, 0.0, 0.0),
+//!         Vector3::new(3.0, 2.0, 0.0),
+//!         Vector3::new(2.0, 2.0, 0.0),
+//!     );
+//!
+//! let sampler = path.sampler();
+//! let halfway = sampler.point_at(sampler.length() / 2.0);
+//! ```
+
+use super::Vector3;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::{PI, TAU};
+
+/// Line pieces each curved segment is flattened into for measuring
+const CURVE_STEPS: usize = 32;
+
+/// Control point distance for approximating a quarter circle with a cubic
+const QUARTER_ARC_HANDLE: f32 = 0.552_284_8;
+
+/// One piece of a path, continuing from where the previous one ended
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PathSegment {
+    /// Straight line to the end point
+    Line(Vector3),
+    /// Quadratic Bezier curve with one control point
+    Quadratic(Vector3, Vector3),
+    /// Cubic Bezier curve with two control points
+    Cubic(Vector3, Vector3, Vector3),
+}
+
+impl PathSegment {
+    /// Where the segment ends
+    pub fn end(&self) -> Vector3 {
+        match *self {
+            Self::Line(end) | Self::Quadratic(_, end) | Self::Cubic(_, _, end) => end,
+        }
+    }
+
+    /// Point at parameter `t` in `[0, 1]` along the segment starting at `from`
+    pub fn point(&self, from: Vector3, t: f32) -> Vector3 {
+        let u = 1.0 - t;
+        match *self {
+            Self::Line(end) => from.lerp(&end, t),
+            Self::Quadratic(control, end) => {
+                from * (u * u) + control * (2.0 * u * t) + end * (t * t)
+            }
+            Self::Cubic(c1, c2, end) => {
+                from * (u * u * u)
+                    + c1 * (3.0 * u * u * t)
+                    + c2 * (3.0 * u * t * t)
+                    + end * (t * t * t)
+            }
+        }
+    }
+
+    /// Direction of travel (not normalized) at parameter `t`
+    pub fn derivative(&self, from: Vector3, t: f32) -> Vector3 {
+        let u = 1.0 - t;
+        match *self {
+            Self::Line(end) => end - from,
+            Self::Quadratic(control, end) => {
+                (control - from) * (2.0 * u) + (end - control) * (2.0 * t)
+            }
+            Self::Cubic(c1, c2, end) => {
+                (c1 - from) * (3.0 * u * u) + (c2 - c1) * (6.0 * u * t) + (end - c2) * (3.0 * t * t)
+            }
+        }
+    }
+
+    fn is_curve(&self) -> bool {
+        !matches!(self, Self::Line(_))
+    }
+}
+
+/// A chain of line and Bezier segments
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Path {
+    pub start: Vector3,
+    pub segments: Vec<PathSegment>,
+    /// Whether the last segment returns to `start`
+    pub closed: bool,
+}
+
+impl Path {
+    /// An empty path at `start`
+    pub fn new(start: Vector3) -> Self {
+        Self {
+            start,
+            segments: Vec::new(),
+            closed: false,
+        }
+    }
+
+    /// Straight lines through `points` (empty paths for no points)
+    pub fn polyline(points: &[Vector3]) -> Self {
+        let mut path = Self::new(points.first().copied().unwrap_or_else(Vector3::zero));
+        for &point in points.iter().skip(1) {
+            path = path.line_to(point);
+        }
+        path
+    }
+
+    /// Closed polygon through `points`
+    pub fn polygon(points: &[Vector3]) -> Self {
+        Self::polyline(points).close()
+    }
+
+    /// Counter-clockwise circle starting on the positive x axis
+    pub fn circle(center: Vector3, radius: f32) -> Self {
+        Self::ellipse(center, radius, radius)
+    }
+
+    /// Counter-clockwise ellipse with radii `rx` and `ry`, starting on the
+    /// positive x axis
+    pub fn ellipse(center: Vector3, rx: f32, ry: f32) -> Self {
+        let point = |angle: f32| center + Vector3::new(rx * angle.cos(), ry * angle.sin(), 0.0);
+        let tangent = |angle: f32| Vector3::new(-rx * angle.sin(), ry * angle.cos(), 0.0);
+
+        let mut path = Self::new(point(0.0));
+        for quarter in 0..4 {
+            let (a0, a1) = (quarter as f32 * PI / 2.0, (quarter + 1) as f32 * PI / 2.0);
+            path = path.cubic_to(
+                point(a0) + tangent(a0) * QUARTER_ARC_HANDLE,
+                point(a1) - tangent(a1) * QUARTER_ARC_HANDLE,
+                point(a1),
+            );
+        }
+        path.closed = true;
+        path
+    }
+
+    /// Circular arc around `center` from `start_angle` sweeping `sweep`
+    /// radians (counter-clockwise when positive)
+    pub fn arc(center: Vector3, radius: f32, start_angle: f32, sweep: f32) -> Self {
+        let point = |angle: f32| center + Vector3::new(angle.cos(), angle.sin(), 0.0) * radius;
+        let tangent = |angle: f32| Vector3::new(-angle.sin(), angle.cos(), 0.0) * radius;
+
+        // Split into pieces of at most a quarter turn so cubics stay accurate
+        let pieces = (sweep.abs() / (PI / 2.0)).ceil().max(1.0) as usize;
+        let step = sweep / pieces as f32;
+        let handle = 4.0 / 3.0 * (step / 4.0).tan();
+
+        let mut path = Self::new(point(start_angle));
+        for piece in 0..pieces {
+            let a0 = start_angle + piece as f32 * step;
+            let a1 = a0 + step;
+            path = path.cubic_to(
+                point(a0) + tangent(a0) * handle,
+                point(a1) - tangent(a1) * handle,
+                point(a1),
+            );
+        }
+        path
+    }
+
+    pub fn line_to(mut self, end: Vector3) -> Self {
+        self.segments.push(PathSegment::Line(end));
+        self
+    }
+
+    pub fn quadratic_to(mut self, control: Vector3, end: Vector3) -> Self {
+        self.segments.push(PathSegment::Quadratic(control, end));
+        self
+    }
+
+    pub fn cubic_to(mut self, c1: Vector3, c2: Vector3, end: Vector3) -> Self {
+        self.segments.push(PathSegment::Cubic(c1, c2, end));
+        self
+    }
+
+    /// Return to the start with a straight line (if not already there)
+    pub fn close(mut self) -> Self {
+        if self.end().distance(&self.start) > f32::EPSILON {
+            self.segments.push(PathSegment::Line(self.start));
+        }
+        self.closed = true;
+        self
+    }
+
+    /// Where the last segment ends
+    pub fn end(&self) -> Vector3 {
+        self.segments.last().map_or(self.start, PathSegment::end)
+    }
+
+    /// Segments paired with the point each one starts from
+    pub fn segments_with_start(&self) -> impl Iterator<Item = (Vector3, &PathSegment)> {
+        let starts = std::iter::once(self.start).chain(self.segments.iter().map(PathSegment::end));
+        starts.zip(&self.segments)
+    }
+
+    /// Points along the path, with curves split into `CURVE_STEPS` lines
+    pub fn flatten(&self) -> Vec<Vector3> {
+        let mut points = vec![self.start];
+        for (from, segment) in self.segments_with_start() {
+            points.extend(segment_steps(segment).map(|(_, t)| segment.point(from, t)));
+        }
+        points
+    }
+
+    /// Total length of the path
+    pub fn length(&self) -> f32 {
+        self.sampler().length()
+    }
+
+    /// Measure the path for sampling by distance
+    pub fn sampler(&self) -> PathSampler {
+        PathSampler::new(self)
+    }
+}
+
+/// Parameters at the start and end of each line a segment flattens into
+fn segment_steps(segment: &PathSegment) -> impl Iterator<Item = (f32, f32)> {
+    let steps = if segment.is_curve() { CURVE_STEPS } else { 1 };
+    (1..=steps).map(move |step| ((step - 1) as f32 / steps as f32, step as f32 / steps as f32))
+}
+
+/// A flattened path with cumulative lengths, for sampling by arc length
+#[derive(Debug, Clone)]
+pub struct PathSampler {
+    points: Vec<Vector3>,
+    /// Distance from the start to each point
+    distances: Vec<f32>,
+    /// Curve directions at the start and end of each piece between points
+    tangents: Vec<(Vector3, Vector3)>,
+}
+
+impl PathSampler {
+    fn new(path: &Path) -> Self {
+        let points = path.flatten();
+        let mut total = 0.0;
+        let distances = std::iter::once(0.0)
+            .chain(points.windows(2).map(|pair| {
+                total += pair[0].distance(&pair[1]);
+                total
+            }))
+            .collect();
+        let tangents = path
+            .segments_with_start()
+            .flat_map(|(from, segment)| {
+                segment_steps(segment).map(move |(t0, t1)| {
+                    (
+                        segment.derivative(from, t0).normalized(),
+                        segment.derivative(from, t1).normalized(),
+                    )
+                })
+            })
+            .collect();
+        Self {
+            points,
+            distances,
+            tangents,
+        }
+    }
+
+    pub fn length(&self) -> f32 {
+        self.distances.last().copied().unwrap_or(0.0)
+    }
+
+    /// Index of the flattened piece containing `distance`, and how far into it
+    fn locate(&self, distance: f32) -> (usize, f32) {
+        if self.points.len() < 2 {
+            return (0, 0.0);
+        }
+        let distance = distance.clamp(0.0, self.length());
+        let index = self
+            .distances
+            .partition_point(|&d| d <= distance)
+            .clamp(1, self.points.len() - 1)
+            - 1;
+        let piece = self.distances[index + 1] - self.distances[index];
+        let t = if piece > 0.0 {
+            (distance - self.distances[index]) / piece
+        } else {
+            0.0
+        };
+        (index, t)
+    }
+
+    /// Point `distance` along the path (clamped to its ends)
+    pub fn point_at(&self, distance: f32) -> Vector3 {
+        match self.points.len() {
+            0 => Vector3::zero(),
+            1 => self.points[0],
+            _ => {
+                let (index, t) = self.locate(distance);
+                self.points[index].lerp(&self.points[index + 1], t)
+            }
+        }
+    }
+
+    /// Unit direction of travel `distance` along the path (zero for paths
+    /// without length)
+    pub fn tangent_at(&self, distance: f32) -> Vector3 {
+        if self.points.len() < 2 {
+            return Vector3::zero();
+        }
+        let (index, t) = self.locate(distance);
+        let (start, end) = self.tangents[index];
+        let tangent = start.lerp(&end, t);
+        if tangent.length() > f32::EPSILON {
+            return tangent.normalized();
+        }
+        // Coincident control points leave curve ends without a direction;
+        // use the nearest piece that has length instead
+        self.points[index..]
+            .windows(2)
+            .chain(self.points[..=index].windows(2).rev())
+            .map(|pair| pair[1] - pair[0])
+            .find(|direction| direction.length() > f32::EPSILON)
+            .map_or_else(Vector3::zero, |direction| direction.normalized())
+    }
+
+    /// Point at `fraction` of the path's length, in `[0, 1]`
+    pub fn point_at_fraction(&self, fraction: f32) -> Vector3 {
+        self.point_at(fraction * self.length())
+    }
+
+    /// `count` points evenly spaced by distance, with their tangents,
+    /// including both ends
+    pub fn even_samples(&self, count: usize) -> Vec<(Vector3, Vector3)> {
+        let last = count.saturating_sub(1).max(1) as f32;
+        (0..count)
+            .map(|i| {
+                let distance = i as f32 / last * self.length();
+                (self.point_at(distance), self.tangent_at(distance))
+            })
+            .collect()
+    }
+}
+
+/// Angle in the xy-plane of each direction, unwrapped so consecutive angles
+/// never jump by more than half a turn
+pub fn unwrapped_angles(directions: impl IntoIterator<Item = Vector3>) -> Vec<f32> {
+    let mut angles: Vec<f32> = Vec::new();
+    for direction in directions {
+        let angle = direction.y.atan2(direction.x);
+        let angle = match angles.last() {
+            Some(&previous) => previous + (angle - previous + PI).rem_euclid(TAU) - PI,
+            None => angle,
+        };
+        angles.push(angle);
+    }
+    angles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arc_length_sampling() {
+        let square = Path::polygon(&[
+            Vector3::zero(),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+        ]);
+        assert!(square.closed);
+        let sampler = square.sampler();
+        assert!((sampler.length() - 4.0).abs() < 1e-5);
+        assert_eq!(sampler.point_at(1.5), Vector3::new(1.0, 0.5, 0.0));
+        assert_eq!(sampler.tangent_at(2.5), Vector3::new(-1.0, 0.0, 0.0));
+
+        let circle = Path::circle(Vector3::zero(), 2.0);
+        assert!((circle.length() - 2.0 * TAU).abs() < 0.01);
+        let quarter = circle.sampler().point_at_fraction(0.25);
+        assert!(quarter.distance(&Vector3::new(0.0, 2.0, 0.0)) < 0.01);
+
+        let arc = Path::arc(Vector3::zero(), 1.0, 0.0, PI);
+        assert!((arc.length() - PI).abs() < 0.01);
+        assert!(arc.end().distance(&Vector3::new(-1.0, 0.0, 0.0)) < 1e-5);
+
+        // A full turn keeps climbing rather than wrapping back to -PI
+        let angles = unwrapped_angles(circle.sampler().even_samples(9).into_iter().map(|s| s.1));
+        assert!((angles[8] - angles[0] - TAU).abs() < 0.05);
+    }
+}
@@ -1,6 +1,7 @@
 use instant::Instant;
 use serde::{Deserialize, Serialize};
 use std::f32::consts::PI;
+use std::path::PathBuf;
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct TimeValue {
@@ -286,8 +287,81 @@ impl AnimationRateType {
     }
 }
 
+/// A sound effect scheduled on the timeline (e.g. a click at t=2.5s)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SoundCue {
+    /// Audio file to play (any format supported by the player / ffmpeg)
+    pub path: PathBuf,
+    /// When the sound starts
+    pub time: TimeValue,
+    /// Linear gain (1.0 = unchanged)
+    pub volume: f32,
+}
+
+impl SoundCue {
+    pub fn new(path: impl Into<PathBuf>, seconds: f32) -> Self {
+        Self {
+            path: path.into(),
+            time: TimeValue::new(seconds),
+            volume: 1.0,
+        }
+    }
+
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.volume = volume.max(0.0);
+        self
+    }
+}
+
+/// A caption shown between `start` and `end` (exported as .srt/.vtt)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaptionCue {
+    pub text: String,
+    pub start: TimeValue,
+    pub end: TimeValue,
+}
+
+impl CaptionCue {
+    pub fn new(text: impl Into<String>, start: f32, end: f32) -> Self {
+        Self {
+            text: text.into(),
+            start: TimeValue::new(start),
+            end: TimeValue::new(end.max(start)),
+        }
+    }
+
+    pub fn duration(&self) -> TimeValue {
+        TimeValue::new(self.end.value - self.start.value)
+    }
+
+    /// Whether the caption is on screen at `time` (end exclusive)
+    pub fn is_active(&self, time: TimeValue) -> bool {
+        time.value >= self.start.value && time.value < self.end.value
+    }
+}
+
+/// A named span of the timeline that can be rendered on its own
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Section {
+    pub name: String,
+    pub start: TimeValue,
+    /// `None` while the section runs to the end of the scene
+    pub end: Option<TimeValue>,
+}
+
+impl Section {
+    /// Whether `time` falls inside the section (end exclusive)
+    pub fn contains(&self, time: TimeValue) -> bool {
+        time.value >= self.start.value && self.end.is_none_or(|end| time.value < end.value)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Timeline {
     markers: Vec<(String, TimeValue)>,
+    sections: Vec<Section>,
+    sound_cues: Vec<SoundCue>,
+    captions: Vec<CaptionCue>,
     current_time: TimeValue,
 }
 
@@ -295,6 +369,9 @@ impl Timeline {
     pub fn new() -> Self {
         Self {
             markers: Vec::new(),
+            sections: Vec::new(),
+            sound_cues: Vec::new(),
+            captions: Vec::new(),
             current_time: TimeValue::new(0.0),
         }
     }
@@ -346,6 +423,160 @@ impl Timeline {
     pub fn current_time(&self) -> TimeValue {
         self.current_time
     }
+
+    /// Start a section named `name` at the current time, ending the previous one
+    ///
+    /// ```rust
+    /// use diomanim::core::*;
+    ///
+    /// let mut timeline = Timeline::new();
+    /// timeline.section("intro");
+    /// timeline.jump_by(TimeValue::new(4.0));
+    /// timeline.section("proof");
+    ///
+    /// assert_eq!(timeline.get_section("intro").unwrap().end, Some(TimeValue::new(4.0)));
+    /// assert_eq!(timeline.get_section("proof").unwrap().end, None);
+    /// ```
+    pub fn section(&mut self, name: impl Into<String>) {
+        let start = self.current_time;
+        if let Some(last) = self.sections.last_mut() {
+            if last.end.is_none_or(|end| end.value > start.value) {
+                last.end = Some(start);
+            }
+        }
+        self.sections.push(Section {
+            name: name.into(),
+            start,
+            end: None,
+        });
+    }
+
+    /// Add a section with explicit bounds, keeping sections sorted by start
+    pub fn add_section(&mut self, name: impl Into<String>, start: f32, end: f32) {
+        let section = Section {
+            name: name.into(),
+            start: TimeValue::new(start),
+            end: Some(TimeValue::new(end.max(start))),
+        };
+        let index = self
+            .sections
+            .partition_point(|existing| existing.start.value <= section.start.value);
+        self.sections.insert(index, section);
+    }
+
+    pub fn sections(&self) -> &[Section] {
+        &self.sections
+    }
+
+    pub fn get_section(&self, name: &str) -> Option<&Section> {
+        self.sections.iter().find(|section| section.name == name)
+    }
+
+    /// The first `duration` seconds split at every section start and end,
+    /// for presenting the scene one step at a time
+    ///
+    /// Each cue is named after the section it lies in, or `cue_<n>` in gaps
+    /// between sections.
+    ///
+    /// ```rust
+    /// use diomanim::core::*;
+    ///
+    /// let mut timeline = Timeline::new();
+    /// timeline.add_section("intro", 1.0, 3.0);
+    /// timeline.add_section("proof", 3.0, 6.0);
+    ///
+    /// let cues = timeline.cues(8.0);
+    /// let names: Vec<&str> = cues.iter().map(|cue| cue.name.as_str()).collect();
+    /// assert_eq!(names, ["cue_0", "intro", "proof", "cue_3"]);
+    /// assert_eq!(cues[3].end, Some(TimeValue::new(8.0)));
+    /// ```
+    pub fn cues(&self, duration: f32) -> Vec<Section> {
+        let mut bounds: Vec<f32> = self
+            .sections
+            .iter()
+            .flat_map(|section| {
+                [
+                    section.start.value,
+                    section.end.map_or(duration, |end| end.value),
+                ]
+            })
+            .filter(|&time| time > 0.0 && time < duration)
+            .collect();
+        bounds.push(duration);
+        bounds.sort_by(f32::total_cmp);
+        bounds.dedup();
+
+        let mut start = 0.0;
+        bounds
+            .into_iter()
+            .enumerate()
+            .map(|(i, end)| {
+                let name = self
+                    .sections
+                    .iter()
+                    .rev()
+                    .find(|section| section.contains(TimeValue::new(start)))
+                    .map_or_else(|| format!("cue_{i}"), |section| section.name.clone());
+                let cue = Section {
+                    name,
+                    start: TimeValue::new(start),
+                    end: Some(TimeValue::new(end)),
+                };
+                start = end;
+                cue
+            })
+            .collect()
+    }
+
+    /// Schedule a sound cue, keeping cues sorted by time
+    pub fn add_sound_cue(&mut self, cue: SoundCue) {
+        let index = self
+            .sound_cues
+            .partition_point(|existing| existing.time.value <= cue.time.value);
+        self.sound_cues.insert(index, cue);
+    }
+
+    /// Schedule `path` to play at `seconds`
+    pub fn add_sound(&mut self, path: impl Into<PathBuf>, seconds: f32) {
+        self.add_sound_cue(SoundCue::new(path, seconds));
+    }
+
+    pub fn sound_cues(&self) -> &[SoundCue] {
+        &self.sound_cues
+    }
+
+    /// Cues starting in the half-open interval `[start, end)`
+    pub fn sound_cues_in_range(&self, start: TimeValue, end: TimeValue) -> Vec<&SoundCue> {
+        self.sound_cues
+            .iter()
+            .filter(|cue| cue.time.value >= start.value && cue.time.value < end.value)
+            .collect()
+    }
+
+    /// Add a caption, keeping captions sorted by start time
+    pub fn add_caption_cue(&mut self, cue: CaptionCue) {
+        let index = self
+            .captions
+            .partition_point(|existing| existing.start.value <= cue.start.value);
+        self.captions.insert(index, cue);
+    }
+
+    /// Show `text` as a caption from `start` to `end` seconds
+    pub fn add_caption(&mut self, text: impl Into<String>, start: f32, end: f32) {
+        self.add_caption_cue(CaptionCue::new(text, start, end));
+    }
+
+    pub fn captions(&self) -> &[CaptionCue] {
+        &self.captions
+    }
+
+    /// Captions on screen at `time`
+    pub fn active_captions(&self, time: TimeValue) -> Vec<&CaptionCue> {
+        self.captions
+            .iter()
+            .filter(|cue| cue.is_active(time))
+            .collect()
+    }
 }
 
 impl Default for Timeline {
@@ -415,3 +646,67 @@ impl std::ops::Rem for TimeValue {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sections_close_previous() {
+        let mut timeline = Timeline::new();
+        timeline.section("intro");
+        timeline.jump_to(TimeValue::new(2.0));
+        timeline.section("proof");
+        timeline.add_section("outro", 8.0, 10.0);
+
+        let names: Vec<&str> = timeline
+            .sections()
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, ["intro", "proof", "outro"]);
+
+        let proof = timeline.get_section("proof").unwrap();
+        assert!(proof.contains(TimeValue::new(9.0)));
+        assert!(!timeline
+            .get_section("intro")
+            .unwrap()
+            .contains(TimeValue::new(2.0)));
+    }
+
+    #[test]
+    fn test_captions_sorted_and_active() {
+        let mut timeline = Timeline::new();
+        timeline.add_caption("Second", 2.0, 4.0);
+        timeline.add_caption("First", 0.0, 2.5);
+
+        let texts: Vec<&str> = timeline
+            .captions()
+            .iter()
+            .map(|c| c.text.as_str())
+            .collect();
+        assert_eq!(texts, ["First", "Second"]);
+
+        assert_eq!(timeline.active_captions(TimeValue::new(2.2)).len(), 2);
+        assert_eq!(timeline.active_captions(TimeValue::new(4.0)).len(), 0);
+        assert_eq!(CaptionCue::new("x", 3.0, 1.0).duration().value, 0.0);
+    }
+
+    #[test]
+    fn test_sound_cues_sorted_and_ranged() {
+        let mut timeline = Timeline::new();
+        timeline.add_sound("whoosh.wav", 2.5);
+        timeline.add_sound_cue(SoundCue::new("click.wav", 0.0).with_volume(0.5));
+        timeline.add_sound("pop.wav", 1.0);
+
+        let times: Vec<f32> = timeline.sound_cues().iter().map(|c| c.time.value).collect();
+        assert_eq!(times, vec![0.0, 1.0, 2.5]);
+
+        let first = timeline.sound_cues_in_range(TimeValue::new(0.0), TimeValue::new(1.0));
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].volume, 0.5);
+
+        let later = timeline.sound_cues_in_range(TimeValue::new(1.0), TimeValue::new(3.0));
+        assert_eq!(later.len(), 2);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::core::Vector3;
+use glam::{Mat4, Quat};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -52,23 +53,9 @@ impl Transform {
         self.position = self.position + offset;
     }
 
-    pub fn rotate(&mut self, mut rotation: Quaternion) {
+    pub fn rotate(&mut self, rotation: Quaternion) {
         // Combine rotations by multiplication
-        let q2 = self.rotation;
-
-        // Quaternion multiplication: q1 * q2
-        let w = rotation.w * q2.w - rotation.x * q2.x - rotation.y * q2.y - rotation.z * q2.z;
-        let x = rotation.w * q2.x + rotation.x * q2.w + rotation.y * q2.z - rotation.z * q2.y;
-        let y = rotation.w * q2.y - rotation.x * q2.z + rotation.y * q2.w + rotation.z * q2.x;
-        let z = rotation.w * q2.z + rotation.x * q2.y - rotation.y * q2.x + rotation.z * q2.w;
-
-        rotation.x = x;
-        rotation.y = y;
-        rotation.z = z;
-        rotation.w = w;
-        rotation.normalize();
-
-        self.rotation = rotation;
+        self.rotation = (rotation * self.rotation).normalized();
     }
 
     pub fn look_at(&mut self, target: Vector3, up: Vector3) {
@@ -80,11 +67,13 @@ impl Transform {
     }
 
     pub fn matrix(&self) -> Matrix4 {
-        let translation = Matrix4::from_translation(self.position);
-        let rotation = Matrix4::from_quaternion(self.rotation);
-        let scale = Matrix4::from_scale(self.scale);
-
-        translation * rotation * scale
+        // translation * rotation * scale
+        Mat4::from_scale_rotation_translation(
+            self.scale.into(),
+            self.rotation.into(),
+            self.position.into(),
+        )
+        .into()
     }
 
     pub fn inverse(&self) -> Self {
@@ -282,7 +271,7 @@ impl Quaternion {
         let sin_theta = theta.sin();
         let sin_theta_0 = theta_0.sin();
 
-        let s0 = ((1.0 - t) * theta).cos() - dot * sin_theta / sin_theta_0;
+        let s0 = (theta_0 - theta).sin() / sin_theta_0;
         let s1 = sin_theta / sin_theta_0;
 
         Self::new(
@@ -299,12 +288,39 @@ impl Quaternion {
         q
     }
 
+    /// Rotation by `angle` radians about the z axis, the only rotation
+    /// flat scenes need
+    pub fn from_rotation_z(angle: f32) -> Self {
+        Self::from_axis_angle(Vector3::new(0.0, 0.0, 1.0), angle)
+    }
+
     pub fn rotate_vector(&self, vector: Vector3) -> Vector3 {
-        let q_vec = Vector3::new(self.x, self.y, self.z);
-        let t = q_vec.cross(&vector) * 2.0;
-        let u = q_vec.cross(&t) * 2.0;
+        Quat::from(*self).mul_vec3a(vector.into()).into()
+    }
+}
+
+impl From<Quaternion> for Quat {
+    fn from(q: Quaternion) -> Self {
+        Quat::from_xyzw(q.x, q.y, q.z, q.w)
+    }
+}
+
+impl From<Quat> for Quaternion {
+    fn from(q: Quat) -> Self {
+        Self {
+            x: q.x,
+            y: q.y,
+            z: q.z,
+            w: q.w,
+        }
+    }
+}
 
-        vector + t * self.w + u
+// Hamilton product: `a * b` rotates by `b`, then by `a`
+impl std::ops::Mul for Quaternion {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        (Quat::from(self) * Quat::from(other)).into()
     }
 }
 
@@ -355,43 +371,48 @@ impl Matrix4 {
         let wy2 = q.w * y2;
         let wz2 = q.w * z2;
 
+        // Rows, matching `data` (translation lives in the last column)
         Self {
             data: [
-                [1.0 - (yy2 + zz2), xy2 + wz2, xz2 - wy2, 0.0],
-                [xy2 - wz2, 1.0 - (xx2 + zz2), yz2 + wx2, 0.0],
-                [xz2 + wy2, yz2 - wx2, 1.0 - (xx2 + yy2), 0.0],
+                [1.0 - (yy2 + zz2), xy2 - wz2, xz2 + wy2, 0.0],
+                [xy2 + wz2, 1.0 - (xx2 + zz2), yz2 - wx2, 0.0],
+                [xz2 - wy2, yz2 + wx2, 1.0 - (xx2 + yy2), 0.0],
                 [0.0, 0.0, 0.0, 1.0],
             ],
         }
     }
 
     pub fn mul(&self, other: &Self) -> Self {
-        let mut result = Self::identity();
-        for i in 0..4 {
-            for j in 0..4 {
-                result.data[i][j] = self.data[i][0] * other.data[0][j]
-                    + self.data[i][1] * other.data[1][j]
-                    + self.data[i][2] * other.data[2][j]
-                    + self.data[i][3] * other.data[3][j];
-            }
-        }
-        result
+        self.mul_ref(other)
     }
 
     pub fn transform_point(&self, point: Vector3) -> Vector3 {
-        let x = self.data[0][0] * point.x
-            + self.data[0][1] * point.y
-            + self.data[0][2] * point.z
-            + self.data[0][3];
-        let y = self.data[1][0] * point.x
-            + self.data[1][1] * point.y
-            + self.data[1][2] * point.z
-            + self.data[1][3];
-        let z = self.data[2][0] * point.x
-            + self.data[2][1] * point.y
-            + self.data[2][2] * point.z
-            + self.data[2][3];
-        Vector3::new(x, y, z)
+        Mat4::from(*self).transform_point3a(point.into()).into()
+    }
+
+    /// Column-major copy of the matrix, as expected by WGSL `mat4x4<f32>`
+    pub fn to_cols_array_2d(&self) -> [[f32; 4]; 4] {
+        Mat4::from(*self).to_cols_array_2d()
+    }
+
+    /// Build a matrix from column-major data
+    pub fn from_cols_array_2d(cols: &[[f32; 4]; 4]) -> Self {
+        Mat4::from_cols_array_2d(cols).into()
+    }
+}
+
+// `data` is row-major, so reading it as glam's columns gives the transpose
+impl From<Matrix4> for Mat4 {
+    fn from(m: Matrix4) -> Self {
+        Mat4::from_cols_array_2d(&m.data).transpose()
+    }
+}
+
+impl From<Mat4> for Matrix4 {
+    fn from(m: Mat4) -> Self {
+        Self {
+            data: m.transpose().to_cols_array_2d(),
+        }
     }
 }
 
@@ -411,15 +432,10 @@ impl std::ops::Mul for Matrix4 {
 
 impl Matrix4 {
     pub fn mul_ref(&self, other: &Self) -> Self {
-        let mut result = Self::identity();
-        for i in 0..4 {
-            for j in 0..4 {
-                result.data[i][j] = self.data[i][0] * other.data[0][j]
-                    + self.data[i][1] * other.data[1][j]
-                    + self.data[i][2] * other.data[2][j]
-                    + self.data[i][3] * other.data[3][j];
-            }
+        // (A * B)^T = B^T * A^T, which skips both transposes
+        let product = Mat4::from_cols_array_2d(&other.data) * Mat4::from_cols_array_2d(&self.data);
+        Self {
+            data: product.to_cols_array_2d(),
         }
-        result
     }
 }
@@ -1,3 +1,6 @@
+// Arithmetic goes through glam's `Vec3A`, which is 16-byte aligned so it
+// fits an SSE/NEON register; the structs themselves stay plain x/y/z.
+use glam::{Vec2, Vec3, Vec3A};
 use serde::{Deserialize, Serialize};
 use std::ops::{Add, Div, Mul, Neg, Sub};
 
@@ -36,36 +39,29 @@ impl Vector3 {
     }
 
     pub fn length(&self) -> f32 {
-        (self.x * self.x + self.y * self.y + self.z * self.z).sqrt()
+        Vec3A::from(*self).length()
     }
 
     pub fn normalized(&self) -> Self {
-        let len = self.length();
+        let v = Vec3A::from(*self);
+        let len = v.length();
         if len > 0.0001 {
-            Self::new(self.x / len, self.y / len, self.z / len)
+            (v / len).into()
         } else {
             Self::zero()
         }
     }
 
     pub fn dot(&self, other: &Self) -> f32 {
-        self.x * other.x + self.y * other.y + self.z * other.z
+        Vec3A::from(*self).dot(Vec3A::from(*other))
     }
 
     pub fn cross(&self, other: &Self) -> Self {
-        Self::new(
-            self.y * other.z - self.z * other.y,
-            self.z * other.x - self.x * other.z,
-            self.x * other.y - self.y * other.x,
-        )
+        Vec3A::from(*self).cross(Vec3A::from(*other)).into()
     }
 
     pub fn lerp(&self, other: &Self, t: f32) -> Self {
-        Self::new(
-            self.x + (other.x - self.x) * t,
-            self.y + (other.y - self.y) * t,
-            self.z + (other.z - self.z) * t,
-        )
+        Vec3A::from(*self).lerp(Vec3A::from(*other), t).into()
     }
 
     pub fn distance(&self, other: &Self) -> f32 {
@@ -130,39 +126,76 @@ impl Vector2 {
     }
 }
 
+// Conversions to and from glam
+impl From<Vector3> for Vec3A {
+    fn from(v: Vector3) -> Self {
+        Vec3A::new(v.x, v.y, v.z)
+    }
+}
+
+impl From<Vec3A> for Vector3 {
+    fn from(v: Vec3A) -> Self {
+        Self::new(v.x, v.y, v.z)
+    }
+}
+
+impl From<Vector3> for Vec3 {
+    fn from(v: Vector3) -> Self {
+        Vec3::new(v.x, v.y, v.z)
+    }
+}
+
+impl From<Vec3> for Vector3 {
+    fn from(v: Vec3) -> Self {
+        Self::new(v.x, v.y, v.z)
+    }
+}
+
+impl From<Vector2> for Vec2 {
+    fn from(v: Vector2) -> Self {
+        Vec2::new(v.x, v.y)
+    }
+}
+
+impl From<Vec2> for Vector2 {
+    fn from(v: Vec2) -> Self {
+        Self::new(v.x, v.y)
+    }
+}
+
 // Operator overloads for Vector3
 impl Add for Vector3 {
     type Output = Self;
     fn add(self, other: Self) -> Self {
-        Self::new(self.x + other.x, self.y + other.y, self.z + other.z)
+        (Vec3A::from(self) + Vec3A::from(other)).into()
     }
 }
 
 impl Sub for Vector3 {
     type Output = Self;
     fn sub(self, other: Self) -> Self {
-        Self::new(self.x - other.x, self.y - other.y, self.z - other.z)
+        (Vec3A::from(self) - Vec3A::from(other)).into()
     }
 }
 
 impl Mul<f32> for Vector3 {
     type Output = Self;
     fn mul(self, scalar: f32) -> Self {
-        Self::new(self.x * scalar, self.y * scalar, self.z * scalar)
+        (Vec3A::from(self) * scalar).into()
     }
 }
 
 impl Div<f32> for Vector3 {
     type Output = Self;
     fn div(self, scalar: f32) -> Self {
-        Self::new(self.x / scalar, self.y / scalar, self.z / scalar)
+        (Vec3A::from(self) / scalar).into()
     }
 }
 
 impl Neg for Vector3 {
     type Output = Self;
     fn neg(self) -> Self {
-        Self::new(-self.x, -self.y, -self.z)
+        (-Vec3A::from(self)).into()
     }
 }
 
@@ -0,0 +1,224 @@
+//! # Caption Export
+//!
+//! Writes caption tracks as SRT (`.srt`) or VTT (`.vtt`) files that sit
+//! alongside an exported video. Captions come either from explicit
+//! [`CaptionCue`]s on a [`crate::core::Timeline`] or from the Text nodes that
+//! are visible while frames are rendered, collected by a [`CaptionRecorder`].
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::core::CaptionCue;
+//! use diomanim::export::captions::{format_captions, CaptionFormat};
+//!
+//! let cues = [CaptionCue::new("Hello, world!", 0.0, 1.5)];
+//! let srt = format_captions(&cues, CaptionFormat::Srt);
+//! assert!(srt.contains("00:00:00,000 --> 00:00:01,500"));
+//! ```
+
+use crate::core::{CaptionCue, TimeValue};
+use crate::scene::{Renderable, SceneGraph};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Caption file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptionFormat {
+    /// SRT subtitles (`.srt`)
+    Srt,
+    /// Web Video Text Tracks (`.vtt`)
+    WebVtt,
+}
+
+impl CaptionFormat {
+    /// Pick the format from a file extension (`srt` or `vtt`)
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "srt" => Some(Self::Srt),
+            "vtt" => Some(Self::WebVtt),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Srt => "srt",
+            Self::WebVtt => "vtt",
+        }
+    }
+}
+
+/// Format a timestamp as `HH:MM:SS,mmm` (SRT) or `HH:MM:SS.mmm` (VTT)
+fn format_timestamp(time: TimeValue, format: CaptionFormat) -> String {
+    let total_ms = (time.value.max(0.0) * 1000.0).round() as u64;
+    let separator = match format {
+        CaptionFormat::Srt => ',',
+        CaptionFormat::WebVtt => '.',
+    };
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        total_ms / 3_600_000,
+        (total_ms / 60_000) % 60,
+        (total_ms / 1000) % 60,
+        separator,
+        total_ms % 1000
+    )
+}
+
+/// Render captions to the contents of a caption file
+pub fn format_captions(cues: &[CaptionCue], format: CaptionFormat) -> String {
+    let mut output = String::new();
+    if format == CaptionFormat::WebVtt {
+        output.push_str("WEBVTT\n\n");
+    }
+
+    for (i, cue) in cues.iter().enumerate() {
+        if format == CaptionFormat::Srt {
+            let _ = writeln!(output, "{}", i + 1);
+        }
+        let _ = writeln!(
+            output,
+            "{} --> {}",
+            format_timestamp(cue.start, format),
+            format_timestamp(cue.end, format)
+        );
+        // Blank lines would terminate the cue early
+        for line in cue.text.lines().filter(|line| !line.trim().is_empty()) {
+            let _ = writeln!(output, "{line}");
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Write captions to `path`, choosing SRT or VTT from its extension
+pub fn write_captions(
+    cues: &[CaptionCue],
+    path: impl AsRef<Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    let format = CaptionFormat::from_path(path)
+        .ok_or_else(|| format!("unsupported caption file: {}", path.display()))?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, format_captions(cues, format))?;
+    Ok(())
+}
+
+/// Builds caption cues from the Text nodes visible in each rendered frame
+///
+/// Call [`CaptionRecorder::record`] once per frame during export; a cue
+/// starts when a text first becomes visible and ends when it disappears.
+#[derive(Debug, Default)]
+pub struct CaptionRecorder {
+    open: Vec<(String, TimeValue)>,
+    cues: Vec<CaptionCue>,
+}
+
+impl CaptionRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record which texts are visible in `scene` at `time`
+    pub fn record(&mut self, scene: &SceneGraph, time: TimeValue) {
+        let mut visible: Vec<String> = Vec::new();
+        for (_, renderable, _) in scene.get_visible_renderables() {
+            let content = match renderable {
+                Renderable::Text { content, .. } | Renderable::TextOnPath { content, .. } => {
+                    content
+                }
+                Renderable::RichText { text, .. } => text.plain_text(),
+                _ => continue,
+            };
+            if !content.trim().is_empty() && !visible.contains(&content) {
+                visible.push(content);
+            }
+        }
+
+        let (still_open, closed): (Vec<_>, Vec<_>) = std::mem::take(&mut self.open)
+            .into_iter()
+            .partition(|(text, _)| visible.contains(text));
+        for (text, start) in closed {
+            self.push_cue(text, start, time);
+        }
+        self.open = still_open;
+
+        for text in visible {
+            if !self.open.iter().any(|(open, _)| *open == text) {
+                self.open.push((text, time));
+            }
+        }
+    }
+
+    /// Close any captions still on screen at `end` and return all cues by start time
+    pub fn finish(mut self, end: TimeValue) -> Vec<CaptionCue> {
+        for (text, start) in std::mem::take(&mut self.open) {
+            self.push_cue(text, start, end);
+        }
+        self.cues
+            .sort_by(|a, b| a.start.value.total_cmp(&b.start.value));
+        self.cues
+    }
+
+    fn push_cue(&mut self, text: String, start: TimeValue, end: TimeValue) {
+        if end.value > start.value {
+            self.cues
+                .push(CaptionCue::new(text, start.value, end.value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Color;
+
+    #[test]
+    fn test_srt_and_vtt_formatting() {
+        let cues = [
+            CaptionCue::new("Intro", 0.0, 1.25),
+            CaptionCue::new("Line one\n\nLine two", 61.5, 3725.0),
+        ];
+
+        let srt = format_captions(&cues, CaptionFormat::Srt);
+        assert_eq!(
+            srt,
+            "1\n00:00:00,000 --> 00:00:01,250\nIntro\n\n\
+             2\n00:01:01,500 --> 01:02:05,000\nLine one\nLine two\n\n"
+        );
+
+        let vtt = format_captions(&cues, CaptionFormat::WebVtt);
+        assert!(vtt.starts_with("WEBVTT\n\n00:00:00.000 --> 00:00:01.250\nIntro\n"));
+        assert_eq!(
+            CaptionFormat::from_path(Path::new("out/video.VTT")),
+            Some(CaptionFormat::WebVtt)
+        );
+    }
+
+    #[test]
+    fn test_recorder_tracks_text_visibility() {
+        let mut scene = SceneGraph::new();
+        let title = scene.create_node("title".to_string());
+        scene
+            .get_node_mut(title)
+            .unwrap()
+            .set_renderable(Renderable::Text {
+                content: "Title".to_string(),
+                font_size: 48.0,
+                color: Color::WHITE,
+            });
+
+        let mut recorder = CaptionRecorder::new();
+        recorder.record(&scene, TimeValue::new(0.0));
+        recorder.record(&scene, TimeValue::new(0.5));
+        scene.get_node_mut(title).unwrap().visible = false;
+        recorder.record(&scene, TimeValue::new(1.0));
+
+        let cues = recorder.finish(TimeValue::new(2.0));
+        assert_eq!(cues, vec![CaptionCue::new("Title", 0.0, 1.0)]);
+    }
+}
@@ -0,0 +1,690 @@
+//! # Figure Export
+//!
+//! Writes a single frame of a scene as a vector PDF or EPS figure, so
+//! diagrams composed in diomanim can go straight into LaTeX documents
+//! (`\includegraphics{figure.pdf}`) at print quality. Like
+//! [`export_svg`](crate::render::export_svg), every visible node is drawn from
+//! its own geometry under its world transform.
+//!
+//! Text is set in the standard PDF/PostScript fonts (Helvetica, with Times
+//! for math), so no fonts are embedded; characters those fonts can't encode
+//! (outside Latin-1, or ASCII for EPS) print as `?`. EPS has no transparency,
+//! so translucent nodes are drawn opaque. Text on paths, images, SVG assets,
+//! clip masks, effects, lighting and deformers are not exported.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use diomanim::core::Color;
+//! use diomanim::export::figure::export_figure;
+//! use diomanim::scene::SceneGraph;
+//!
+//! let mut scene = SceneGraph::new();
+//! scene.add_circle("dot", 0.2, Color::RED);
+//! scene.add_text("label", "unit circle", 24.0, Color::WHITE).at(0.0, -0.4, 0.0);
+//! export_figure(&mut scene, 0.0, "figures/circle.pdf")?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::core::path::{Path as ScenePath, PathSegment};
+use crate::core::{Color, Vector3};
+use crate::render::arrow_geometry;
+use crate::render::svg::{advance_scene, TEXT_UNIT};
+use crate::scene::{Renderable, SceneGraph, SceneNode};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Page size of [`export_figure`] files in points (6.67 x 3.75 inches, 16:9)
+pub const FIGURE_SIZE: (f32, f32) = (480.0, 270.0);
+
+/// Figure file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FigureFormat {
+    /// Portable Document Format (`.pdf`)
+    Pdf,
+    /// Encapsulated PostScript (`.eps`)
+    Eps,
+}
+
+impl FigureFormat {
+    /// Pick the format from a file extension (`pdf` or `eps`)
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "pdf" => Some(Self::Pdf),
+            "eps" => Some(Self::Eps),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Pdf => "pdf",
+            Self::Eps => "eps",
+        }
+    }
+}
+
+/// Standard font a text run is set in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Font {
+    Sans,
+    SansBold,
+    Math,
+}
+
+impl Font {
+    const ALL: [Font; 3] = [Font::Sans, Font::SansBold, Font::Math];
+
+    fn base_font(self) -> &'static str {
+        match self {
+            Font::Sans => "Helvetica",
+            Font::SansBold => "Helvetica-Bold",
+            Font::Math => "Times-Italic",
+        }
+    }
+
+    /// Name of the font in the PDF page resources
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Sans => "F1",
+            Font::SansBold => "F2",
+            Font::Math => "F3",
+        }
+    }
+}
+
+/// Text in one style, continuing from where the previous run ended
+#[derive(Debug, Clone)]
+struct TextRun {
+    text: String,
+    font: Font,
+    size: f32,
+    color: Color,
+}
+
+/// Something drawn in a node's space
+#[derive(Debug, Clone)]
+enum Mark {
+    Fill(ScenePath, Color),
+    /// Path stroked `width` scene units wide
+    Stroke(ScenePath, Color, f32),
+    /// Runs starting at a baseline point
+    Text(Vector3, Vec<TextRun>),
+}
+
+/// A visible node's marks and the transform they are drawn with
+struct NodeMarks {
+    /// Affine world transform `[a b c d e f]`
+    transform: [f32; 6],
+    opacity: f32,
+    marks: Vec<Mark>,
+}
+
+/// Advance `scene` by `time` seconds and write what is visible as a PDF or
+/// EPS figure of [`FIGURE_SIZE`], picking the format from the extension
+///
+/// Animations are advanced from the scene's current state, so pass a freshly
+/// built scene to get the frame at `time`.
+pub fn export_figure(
+    scene: &mut SceneGraph,
+    time: f32,
+    path: impl AsRef<Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    let format = FigureFormat::from_path(path)
+        .ok_or_else(|| format!("unsupported figure file: {}", path.display()))?;
+
+    advance_scene(scene, time);
+    let (width, height) = FIGURE_SIZE;
+    let contents = match format {
+        FigureFormat::Pdf => scene_to_pdf(scene, width, height),
+        FigureFormat::Eps => scene_to_eps(scene, width, height).into_bytes(),
+    };
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, contents)?;
+    Ok(())
+}
+
+/// The visible renderables of `scene`, as last updated, as a single-page PDF
+/// of `width` x `height` points on the theme's background
+pub fn scene_to_pdf(scene: &SceneGraph, width: f32, height: f32) -> Vec<u8> {
+    let nodes = collect_marks(scene);
+
+    // One graphics state per opacity in use
+    let mut alphas: Vec<String> = Vec::new();
+    let mut alpha_state = |alpha: f32| -> Option<usize> {
+        (alpha < 1.0).then(|| {
+            let alpha = num(alpha.max(0.0));
+            alphas.iter().position(|a| *a == alpha).unwrap_or_else(|| {
+                alphas.push(alpha);
+                alphas.len() - 1
+            })
+        })
+    };
+
+    let mut content = Vec::new();
+    let background = scene.theme().background;
+    push_str(
+        &mut content,
+        &format!(
+            "{} rg 0 0 {} {} re f\n{} 0 0 {} {} {} cm\n",
+            rgb(background),
+            num(width),
+            num(height),
+            num(width / 2.0),
+            num(height / 2.0),
+            num(width / 2.0),
+            num(height / 2.0)
+        ),
+    );
+    for node in &nodes {
+        push_str(&mut content, &format!("q {} cm\n", matrix(&node.transform)));
+        for mark in &node.marks {
+            let alpha = alpha_state(mark_alpha(mark) * node.opacity);
+            let state = alpha.map_or(String::new(), |index| format!("/GS{index} gs "));
+            match mark {
+                Mark::Fill(path, color) => push_str(
+                    &mut content,
+                    &format!("q {state}{} rg\n{}f Q\n", rgb(*color), pdf_path(path)),
+                ),
+                Mark::Stroke(path, color, width) => push_str(
+                    &mut content,
+                    &format!(
+                        "q {state}{} RG {} w\n{}S Q\n",
+                        rgb(*color),
+                        num(*width),
+                        pdf_path(path)
+                    ),
+                ),
+                Mark::Text(origin, runs) => {
+                    push_str(
+                        &mut content,
+                        &format!("q {state}BT {} {} Td\n", num(origin.x), num(origin.y)),
+                    );
+                    for run in runs {
+                        push_str(
+                            &mut content,
+                            &format!(
+                                "/{} {} Tf {} rg (",
+                                run.font.resource(),
+                                num(run.size),
+                                rgb(run.color)
+                            ),
+                        );
+                        content.extend(escape_string(&run.text, 0xFF));
+                        push_str(&mut content, ") Tj\n");
+                    }
+                    push_str(&mut content, "ET Q\n");
+                }
+            }
+        }
+        push_str(&mut content, "Q\n");
+    }
+
+    // Objects: catalog, pages, page, content, fonts, graphics states
+    let font_ids: Vec<usize> = (0..Font::ALL.len()).map(|i| 5 + i).collect();
+    let state_ids: Vec<usize> = (0..alphas.len()).map(|i| 5 + Font::ALL.len() + i).collect();
+    let mut resources = String::from("/Font <<");
+    for (font, id) in Font::ALL.iter().zip(&font_ids) {
+        let _ = write!(resources, " /{} {id} 0 R", font.resource());
+    }
+    resources.push_str(" >>");
+    if !alphas.is_empty() {
+        resources.push_str(" /ExtGState <<");
+        for (index, id) in state_ids.iter().enumerate() {
+            let _ = write!(resources, " /GS{index} {id} 0 R");
+        }
+        resources.push_str(" >>");
+    }
+
+    let mut objects: Vec<Vec<u8>> = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << {resources} >> /Contents 4 0 R >>",
+            num(width),
+            num(height)
+        )
+        .into_bytes(),
+    ];
+    let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+    stream.extend(&content);
+    stream.extend(b"\nendstream");
+    objects.push(stream);
+    for font in Font::ALL {
+        objects.push(
+            format!(
+                "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+                font.base_font()
+            )
+            .into_bytes(),
+        );
+    }
+    for alpha in &alphas {
+        objects.push(format!("<< /Type /ExtGState /ca {alpha} /CA {alpha} >>").into_bytes());
+    }
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        push_str(&mut pdf, &format!("{} 0 obj\n", index + 1));
+        pdf.extend(object);
+        push_str(&mut pdf, "\nendobj\n");
+    }
+    let xref = pdf.len();
+    push_str(
+        &mut pdf,
+        &format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1),
+    );
+    for offset in offsets {
+        push_str(&mut pdf, &format!("{offset:010} 00000 n \n"));
+    }
+    push_str(
+        &mut pdf,
+        &format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objects.len() + 1
+        ),
+    );
+    pdf
+}
+
+/// The visible renderables of `scene`, as last updated, as an EPS figure of
+/// `width` x `height` points on the theme's background
+pub fn scene_to_eps(scene: &SceneGraph, width: f32, height: f32) -> String {
+    let mut eps = String::new();
+    let _ = write!(
+        eps,
+        "%!PS-Adobe-3.0 EPSF-3.0\n\
+         %%BoundingBox: 0 0 {} {}\n\
+         %%HiResBoundingBox: 0 0 {} {}\n\
+         %%Creator: diomanim\n\
+         %%EndComments\n",
+        width.ceil() as u32,
+        height.ceil() as u32,
+        num(width),
+        num(height)
+    );
+    let _ = writeln!(
+        eps,
+        "{} setrgbcolor 0 0 {} {} rectfill",
+        rgb(scene.theme().background),
+        num(width),
+        num(height)
+    );
+    let _ = writeln!(
+        eps,
+        "[{} 0 0 {} {} {}] concat",
+        num(width / 2.0),
+        num(height / 2.0),
+        num(width / 2.0),
+        num(height / 2.0)
+    );
+
+    for node in collect_marks(scene) {
+        let _ = writeln!(eps, "gsave [{}] concat", matrix(&node.transform));
+        for mark in &node.marks {
+            match mark {
+                Mark::Fill(path, color) => {
+                    let _ = writeln!(
+                        eps,
+                        "gsave {} setrgbcolor newpath\n{}fill grestore",
+                        rgb(*color),
+                        ps_path(path)
+                    );
+                }
+                Mark::Stroke(path, color, width) => {
+                    let _ = writeln!(
+                        eps,
+                        "gsave {} setrgbcolor {} setlinewidth newpath\n{}stroke grestore",
+                        rgb(*color),
+                        num(*width),
+                        ps_path(path)
+                    );
+                }
+                Mark::Text(origin, runs) => {
+                    let _ = writeln!(eps, "gsave {} {} moveto", num(origin.x), num(origin.y));
+                    for run in runs {
+                        let text =
+                            String::from_utf8(escape_string(&run.text, 0x7F)).unwrap_or_default();
+                        let _ = writeln!(
+                            eps,
+                            "/{} findfont {} scalefont setfont {} setrgbcolor ({text}) show",
+                            run.font.base_font(),
+                            num(run.size),
+                            rgb(run.color)
+                        );
+                    }
+                    eps.push_str("grestore\n");
+                }
+            }
+        }
+        eps.push_str("grestore\n");
+    }
+    eps.push_str("showpage\n%%EOF\n");
+    eps
+}
+
+/// What each visible node draws, in draw order
+fn collect_marks(scene: &SceneGraph) -> Vec<NodeMarks> {
+    scene
+        .visible_renderable_nodes()
+        .into_iter()
+        .filter_map(|node| {
+            let marks = node_marks(node.renderable.as_ref()?);
+            (!marks.is_empty()).then(|| NodeMarks {
+                transform: affine(node),
+                opacity: node.opacity,
+                marks,
+            })
+        })
+        .collect()
+}
+
+fn affine(node: &SceneNode) -> [f32; 6] {
+    let [x_axis, y_axis, _, translation] = node.world_transform.matrix().to_cols_array_2d();
+    [
+        x_axis[0],
+        x_axis[1],
+        y_axis[0],
+        y_axis[1],
+        translation[0],
+        translation[1],
+    ]
+}
+
+fn node_marks(renderable: &Renderable) -> Vec<Mark> {
+    let sans = |text: &str, size: f32, color: Color| TextRun {
+        text: text.to_string(),
+        font: Font::Sans,
+        size: size * TEXT_UNIT,
+        color,
+    };
+
+    match renderable {
+        Renderable::Circle { radius, color } => {
+            vec![Mark::Fill(
+                ScenePath::circle(Vector3::zero(), *radius),
+                *color,
+            )]
+        }
+        Renderable::Rectangle {
+            width,
+            height,
+            color,
+        } => {
+            let (x, y) = (width / 2.0, height / 2.0);
+            let corners = [
+                Vector3::new(-x, -y, 0.0),
+                Vector3::new(x, -y, 0.0),
+                Vector3::new(x, y, 0.0),
+                Vector3::new(-x, y, 0.0),
+            ];
+            vec![Mark::Fill(ScenePath::polygon(&corners), *color)]
+        }
+        Renderable::Polygon { vertices, color } => {
+            vec![Mark::Fill(ScenePath::polygon(vertices), *color)]
+        }
+        Renderable::Line {
+            start,
+            end,
+            color,
+            thickness,
+        } => vec![Mark::Stroke(
+            ScenePath::polyline(&[*start, *end]),
+            *color,
+            thickness / 100.0,
+        )],
+        Renderable::Polyline {
+            points,
+            color,
+            thickness,
+            ..
+        } => vec![Mark::Stroke(
+            ScenePath::polyline(points),
+            *color,
+            thickness / 100.0,
+        )],
+        Renderable::Arrow {
+            start,
+            end,
+            color,
+            thickness,
+        } => arrow_geometry(*start, *end)
+            .map(|(shaft_end, tip)| {
+                vec![
+                    Mark::Stroke(
+                        ScenePath::polyline(&[*start, shaft_end]),
+                        *color,
+                        thickness / 100.0,
+                    ),
+                    Mark::Fill(ScenePath::polygon(&tip), *color),
+                ]
+            })
+            .unwrap_or_default(),
+        Renderable::Text {
+            content,
+            font_size,
+            color,
+        } => vec![Mark::Text(
+            Vector3::zero(),
+            vec![sans(content, *font_size, *color)],
+        )],
+        Renderable::Math {
+            latex,
+            font_size,
+            color,
+        } => {
+            use crate::math::{expression::parse_latex, layout::MathLayout};
+
+            // Layout positions grow down
+            MathLayout::layout_node(&parse_latex(latex), *font_size)
+                .flatten()
+                .into_iter()
+                .map(|(position, text, size)| {
+                    Mark::Text(
+                        Vector3::new(position.x * TEXT_UNIT, -position.y * TEXT_UNIT, 0.0),
+                        vec![TextRun {
+                            text,
+                            font: Font::Math,
+                            size: size * TEXT_UNIT,
+                            color: *color,
+                        }],
+                    )
+                })
+                .collect()
+        }
+        Renderable::RichText {
+            text,
+            font_size,
+            color,
+        } => {
+            let runs = text
+                .spans
+                .iter()
+                .map(|span| TextRun {
+                    text: span.text.clone(),
+                    font: if span.math {
+                        Font::Math
+                    } else if span.style.bold {
+                        Font::SansBold
+                    } else {
+                        Font::Sans
+                    },
+                    size: font_size * span.style.scale * TEXT_UNIT,
+                    color: span
+                        .style
+                        .color
+                        .map_or(*color, |c| c.with_opacity(c.a * color.a)),
+                })
+                .collect();
+            vec![Mark::Text(Vector3::zero(), runs)]
+        }
+        Renderable::TextOnPath { .. } | Renderable::Image { .. } | Renderable::Svg { .. } => {
+            Vec::new()
+        }
+    }
+}
+
+/// Opacity of a mark's color; text uses its first run's
+fn mark_alpha(mark: &Mark) -> f32 {
+    match mark {
+        Mark::Fill(_, color) | Mark::Stroke(_, color, _) => color.a,
+        Mark::Text(_, runs) => runs.first().map_or(1.0, |run| run.color.a),
+    }
+}
+
+/// Path construction operators, one segment per line
+fn pdf_path(path: &ScenePath) -> String {
+    write_path(path, ["m", "l", "c", "h"])
+}
+
+fn ps_path(path: &ScenePath) -> String {
+    write_path(path, ["moveto", "lineto", "curveto", "closepath"])
+}
+
+/// `path` with the given move, line, curve and close operators; quadratic
+/// segments become cubics, which both formats need
+fn write_path(path: &ScenePath, [move_to, line_to, curve_to, close]: [&str; 4]) -> String {
+    let point = |p: Vector3| format!("{} {}", num(p.x), num(p.y));
+    let mut ops = format!("{} {move_to}\n", point(path.start));
+    let mut current = path.start;
+    for segment in &path.segments {
+        let (c1, c2, end) = match *segment {
+            PathSegment::Line(end) => {
+                let _ = writeln!(ops, "{} {line_to}", point(end));
+                current = end;
+                continue;
+            }
+            PathSegment::Quadratic(control, end) => (
+                current + (control - current) * (2.0 / 3.0),
+                end + (control - end) * (2.0 / 3.0),
+                end,
+            ),
+            PathSegment::Cubic(c1, c2, end) => (c1, c2, end),
+        };
+        let _ = writeln!(ops, "{} {} {} {curve_to}", point(c1), point(c2), point(end));
+        current = end;
+    }
+    if path.closed {
+        let _ = writeln!(ops, "{close}");
+    }
+    ops
+}
+
+fn matrix(m: &[f32; 6]) -> String {
+    m.map(num).join(" ")
+}
+
+fn rgb(color: Color) -> String {
+    format!(
+        "{} {} {}",
+        num(color.r.clamp(0.0, 1.0)),
+        num(color.g.clamp(0.0, 1.0)),
+        num(color.b.clamp(0.0, 1.0))
+    )
+}
+
+/// A number without exponent or trailing zeros (neither format accepts
+/// exponents)
+fn num(value: f32) -> String {
+    let text = format!("{value:.5}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" | "" => "0".to_string(),
+        _ => text.to_string(),
+    }
+}
+
+/// String literal contents with `\`, `(` and `)` escaped and characters
+/// above `max` replaced by `?`
+fn escape_string(text: &str, max: u32) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '(' | ')' => bytes.extend([b'\\', c as u8]),
+            c if (c as u32) <= max => bytes.push(c as u8),
+            _ => bytes.push(b'?'),
+        }
+    }
+    bytes
+}
+
+fn push_str(bytes: &mut Vec<u8>, text: &str) {
+    bytes.extend_from_slice(text.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn figure_scene() -> SceneGraph {
+        let mut scene = SceneGraph::new();
+        scene.add_circle("dot", 0.25, Color::RED).at(0.5, 0.0, 0.0);
+        scene.add_text("label", "f(x) = é→", 24.0, Color::WHITE.with_opacity(0.5));
+        scene.update_transforms();
+        scene
+    }
+
+    #[test]
+    fn test_scene_to_pdf() {
+        let pdf = scene_to_pdf(&figure_scene(), 200.0, 100.0);
+        // One char per byte, so string offsets are byte offsets
+        let text: String = pdf
+            .iter()
+            .map(|&b| if b.is_ascii() { b as char } else { '?' })
+            .collect();
+        assert!(text.starts_with("%PDF-1.4\n"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("/MediaBox [0 0 200 100]"));
+        assert!(text.contains("100 0 0 50 100 50 cm"));
+        assert!(text.contains("q 1 0 0 1 0.5 0 cm"));
+        assert!(text.contains("/BaseFont /Helvetica "));
+        assert!(text.contains("/GS0 gs"));
+        assert!(text.contains("<< /Type /ExtGState /ca 0.5 /CA 0.5 >>"));
+        // Parentheses are escaped, Latin-1 kept as one byte, the rest replaced
+        assert!(pdf.windows(13).any(|w| w == b"(f\\(x\\) = \xE9?)"));
+
+        // Every xref offset points at its object
+        let xref = text.find("\nxref\n").unwrap() + 1;
+        let startxref: usize = text[text.rfind("startxref\n").unwrap() + 10..]
+            .lines()
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(startxref, xref);
+        for (index, line) in text[xref..].lines().skip(3).take(4).enumerate() {
+            let offset: usize = line[..10].parse().unwrap();
+            assert!(text[offset..].starts_with(&format!("{} 0 obj", index + 1)));
+        }
+    }
+
+    #[test]
+    fn test_scene_to_eps() {
+        let eps = scene_to_eps(&figure_scene(), 200.5, 100.0);
+        assert!(eps.starts_with("%!PS-Adobe-3.0 EPSF-3.0\n%%BoundingBox: 0 0 201 100\n"));
+        assert!(eps.contains("[100.25 0 0 50 100.25 50] concat"));
+        assert!(eps.contains("1 0 0 setrgbcolor newpath\n0.25 0 moveto\n"));
+        assert!(eps.contains(
+            "/Helvetica findfont 1.152 scalefont setfont 1 1 1 setrgbcolor (f\\(x\\) = ??) show"
+        ));
+        assert!(eps.ends_with("showpage\n%%EOF\n"));
+    }
+
+    #[test]
+    fn test_figure_format_and_numbers() {
+        assert_eq!(
+            FigureFormat::from_path(Path::new("fig.PDF")),
+            Some(FigureFormat::Pdf)
+        );
+        assert_eq!(FigureFormat::from_path(Path::new("fig.png")), None);
+        assert_eq!(num(1.0), "1");
+        assert_eq!(num(-0.000001), "0");
+        assert_eq!(num(0.125), "0.125");
+    }
+}
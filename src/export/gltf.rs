@@ -0,0 +1,625 @@
+//! # glTF Export
+//!
+//! Writes an animated [`SceneGraph`] as glTF 2.0, binary (`.glb`) or JSON
+//! with the buffer embedded (`.gltf`), so scenes can be taken into Blender,
+//! three.js or game engines.
+//!
+//! Every scene node becomes a glTF node under the same parent. Shapes are
+//! tessellated the way the renderer draws them, into flat unlit meshes:
+//! filled outlines as triangle fans, lines, polylines and arrow shafts as
+//! strips `thickness` wide. Text, math, images and SVG assets become empty
+//! nodes, and opacity, color changes, effects and deformers are not
+//! exported.
+//!
+//! As for [`lottie`](super::lottie), the scene is played through like an
+//! offline render, sampling each node's local transform once per frame,
+//! and runs of frames where a value changes linearly collapse into single
+//! keyframe segments. glTF can't animate visibility, so hidden and fully
+//! transparent nodes are scaled to zero. Animated nodes can be read back
+//! with [`crate::assets::gltf`].
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::core::{Color, Vector3};
+//! use diomanim::export::gltf::{scene_to_gltf, GltfSettings};
+//! use diomanim::scene::SceneGraph;
+//!
+//! let mut scene = SceneGraph::new();
+//! scene
+//!     .add_circle("dot", 0.2, Color::RED)
+//!     .move_to(0.0, Vector3::new(1.0, 0.0, 0.0), 1.0);
+//!
+//! let gltf = scene_to_gltf(&mut scene, &GltfSettings::new(30, 1.0));
+//! assert_eq!(gltf["nodes"][0]["name"], "dot");
+//! ```
+
+use super::lottie::keyframe_indices;
+use crate::core::{Color, TimeValue, Vector3};
+use crate::render::arrow_geometry;
+use crate::scene::{NodeId, Renderable, SceneGraph, SceneNode};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Chunk types of a binary glTF file ("JSON" and "BIN\0")
+const GLB_JSON_CHUNK: u32 = 0x4E4F_534A;
+const GLB_BIN_CHUNK: u32 = 0x004E_4942;
+
+/// Buffer view targets for vertex attributes and indices
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// Outline points closer than this to the first are where the outline
+/// closes, and are dropped
+const CLOSING_TOLERANCE: f32 = 1e-5;
+
+/// glTF export settings
+#[derive(Debug, Clone)]
+pub struct GltfSettings {
+    /// Transform samples per second
+    pub fps: u32,
+    /// Length of the animation in seconds
+    pub duration: f32,
+}
+
+impl GltfSettings {
+    pub fn new(fps: u32, duration: f32) -> Self {
+        Self {
+            fps: fps.max(1),
+            duration: duration.max(0.0),
+        }
+    }
+
+    /// Number of frames sampled
+    pub fn frame_count(&self) -> u32 {
+        (self.duration * self.fps as f32).round() as u32
+    }
+}
+
+/// A node's local transform at every frame
+struct NodeSamples {
+    id: NodeId,
+    name: String,
+    parent: Option<NodeId>,
+    renderable: Option<Renderable>,
+    translation: Vec<[f32; 3]>,
+    /// Quaternions `x, y, z, w`, kept in one hemisphere so consecutive
+    /// frames interpolate the short way round
+    rotation: Vec<[f32; 4]>,
+    scale: Vec<[f32; 3]>,
+}
+
+impl NodeSamples {
+    fn new(node: &SceneNode) -> Self {
+        Self {
+            id: node.id,
+            name: node.name.clone(),
+            parent: node.parent,
+            renderable: node.renderable.clone(),
+            translation: Vec::new(),
+            rotation: Vec::new(),
+            scale: Vec::new(),
+        }
+    }
+
+    /// Record the node's state at the next frame
+    fn push(&mut self, node: &SceneNode) {
+        let transform = &node._local_transform;
+        let q = transform.rotation;
+        let mut rotation = [q.x, q.y, q.z, q.w];
+        if let Some(previous) = self.rotation.last() {
+            let dot: f32 = previous.iter().zip(&rotation).map(|(a, b)| a * b).sum();
+            if dot < 0.0 {
+                rotation = rotation.map(|c| -c);
+            }
+        }
+        let scale = if node.visible && node.opacity > 0.0 {
+            [transform.scale.x, transform.scale.y, transform.scale.z]
+        } else {
+            [0.0; 3]
+        };
+        self.translation.push([
+            transform.position.x,
+            transform.position.y,
+            transform.position.z,
+        ]);
+        self.rotation.push(rotation);
+        self.scale.push(scale);
+    }
+
+    /// Repeat the first sample (scaled to zero) until there are `frames`
+    /// samples, for nodes added after the first frame
+    fn backfill(&mut self, frames: usize) {
+        let missing = frames.saturating_sub(self.translation.len());
+        if missing == 0 {
+            return;
+        }
+        let translation = self.translation[0];
+        let rotation = self.rotation[0];
+        self.translation.splice(0..0, vec![translation; missing]);
+        self.rotation.splice(0..0, vec![rotation; missing]);
+        self.scale.splice(0..0, vec![[0.0; 3]; missing]);
+    }
+
+    /// Keep the last transform, scaled to zero, for a frame the node wasn't
+    /// in the scene
+    fn hold(&mut self) {
+        let (Some(&translation), Some(&rotation)) = (self.translation.last(), self.rotation.last())
+        else {
+            return;
+        };
+        self.translation.push(translation);
+        self.rotation.push(rotation);
+        self.scale.push([0.0; 3]);
+    }
+}
+
+/// Triangles of a tessellated shape
+#[derive(Default)]
+struct Mesh {
+    positions: Vec<[f32; 3]>,
+    indices: Vec<u32>,
+}
+
+impl Mesh {
+    /// Fill the outline `points` with a fan from the first, like the renderer
+    fn fan(&mut self, points: &[Vector3]) {
+        let first = self.positions.len() as u32;
+        self.positions
+            .extend(points.iter().map(|point| [point.x, point.y, point.z]));
+        for i in 1..points.len().saturating_sub(1) as u32 {
+            self.indices.extend([first, first + i, first + i + 1]);
+        }
+    }
+
+    /// A band `thickness` wide (in line units, 1/100 scene unit) along
+    /// `points`, offset along the average of neighbouring segment normals
+    fn strip(&mut self, points: &[Vector3], thickness: f32) {
+        if points.len() < 2 {
+            return;
+        }
+        let half_thickness = thickness / 200.0;
+        let direction = |from: Vector3, to: Vector3| {
+            let (x, y) = (to.x - from.x, to.y - from.y);
+            let length = (x * x + y * y).sqrt();
+            (length >= 1e-6).then(|| (x / length, y / length))
+        };
+
+        let first = self.positions.len() as u32;
+        let mut previous = (1.0, 0.0);
+        for (i, &point) in points.iter().enumerate() {
+            let incoming = (i > 0).then(|| direction(points[i - 1], point)).flatten();
+            let outgoing = points.get(i + 1).and_then(|&next| direction(point, next));
+            let (dx, dy) = match (incoming, outgoing) {
+                (Some(a), Some(b)) => {
+                    direction(Vector3::zero(), Vector3::new(a.0 + b.0, a.1 + b.1, 0.0)).unwrap_or(b)
+                }
+                (Some(d), None) | (None, Some(d)) => d,
+                (None, None) => previous,
+            };
+            previous = (dx, dy);
+            for side in [-half_thickness, half_thickness] {
+                self.positions
+                    .push([point.x - dy * side, point.y + dx * side, point.z]);
+            }
+        }
+        for i in 0..points.len() as u32 - 1 {
+            let (bottom, top) = (first + 2 * i, first + 2 * i + 1);
+            self.indices
+                .extend([bottom, bottom + 2, bottom + 3, bottom, bottom + 3, top]);
+        }
+    }
+}
+
+/// `renderable` as triangles in node space and their color, or `None` for
+/// renderables that aren't exported
+fn tessellate(renderable: &Renderable) -> Option<(Mesh, Color)> {
+    let mut mesh = Mesh::default();
+    match renderable {
+        Renderable::Circle { .. } | Renderable::Rectangle { .. } | Renderable::Polygon { .. } => {
+            let mut points = renderable.outline()?.flatten();
+            if points.len() > 1 && points[points.len() - 1].distance(&points[0]) < CLOSING_TOLERANCE
+            {
+                points.pop();
+            }
+            mesh.fan(&points);
+        }
+        Renderable::Line {
+            start,
+            end,
+            thickness,
+            ..
+        } => mesh.strip(&[*start, *end], *thickness),
+        Renderable::Polyline {
+            points, thickness, ..
+        } => mesh.strip(points, *thickness),
+        Renderable::Arrow {
+            start,
+            end,
+            thickness,
+            ..
+        } => {
+            let (shaft_end, tip) = arrow_geometry(*start, *end)?;
+            mesh.strip(&[*start, shaft_end], *thickness);
+            mesh.fan(&tip);
+        }
+        Renderable::Text { .. }
+        | Renderable::Math { .. }
+        | Renderable::RichText { .. }
+        | Renderable::TextOnPath { .. }
+        | Renderable::Image { .. }
+        | Renderable::Svg { .. } => return None,
+    }
+    (!mesh.indices.is_empty()).then(|| (mesh, renderable.color()))
+}
+
+/// Buffer views and accessors packed into one binary buffer
+#[derive(Default)]
+struct Buffers {
+    binary: Vec<u8>,
+    views: Vec<Value>,
+    accessors: Vec<Value>,
+}
+
+impl Buffers {
+    /// Add a buffer view holding `bytes`, 4-byte aligned, and return its index
+    fn view(&mut self, bytes: impl IntoIterator<Item = u8>, target: Option<u32>) -> usize {
+        self.binary.resize(self.binary.len().next_multiple_of(4), 0);
+        let offset = self.binary.len();
+        self.binary.extend(bytes);
+        let mut view = json!({
+            "buffer": 0,
+            "byteOffset": offset,
+            "byteLength": self.binary.len() - offset,
+        });
+        if let Some(target) = target {
+            view["target"] = json!(target);
+        }
+        self.views.push(view);
+        self.views.len() - 1
+    }
+
+    /// Add a float accessor of scalars or vectors (with their bounds) and
+    /// return its index
+    fn floats<const N: usize>(&mut self, values: &[[f32; N]], target: Option<u32>) -> usize {
+        let kind = match N {
+            1 => "SCALAR",
+            2 => "VEC2",
+            3 => "VEC3",
+            _ => "VEC4",
+        };
+        let mut min = [f32::INFINITY; N];
+        let mut max = [f32::NEG_INFINITY; N];
+        for value in values {
+            for c in 0..N {
+                min[c] = min[c].min(value[c]);
+                max[c] = max[c].max(value[c]);
+            }
+        }
+        let view = self.view(
+            values
+                .iter()
+                .flatten()
+                .flat_map(|value| value.to_le_bytes()),
+            target,
+        );
+        self.accessors.push(json!({
+            "bufferView": view,
+            "componentType": 5126,
+            "count": values.len(),
+            "type": kind,
+            "min": min.as_slice(),
+            "max": max.as_slice(),
+        }));
+        self.accessors.len() - 1
+    }
+
+    /// Add an accessor of triangle indices and return its index
+    fn indices(&mut self, indices: &[u32]) -> usize {
+        let view = self.view(
+            indices.iter().flat_map(|index| index.to_le_bytes()),
+            Some(ELEMENT_ARRAY_BUFFER),
+        );
+        self.accessors.push(json!({
+            "bufferView": view,
+            "componentType": 5125,
+            "count": indices.len(),
+            "type": "SCALAR",
+        }));
+        self.accessors.len() - 1
+    }
+}
+
+/// Play `scene` for `settings.duration` seconds and return it as glTF JSON
+/// with the buffer embedded as a data URI
+///
+/// Animations are advanced as in an offline render, so the scene is left at
+/// its final frame.
+pub fn scene_to_gltf(scene: &mut SceneGraph, settings: &GltfSettings) -> Value {
+    let (mut gltf, binary) = build(scene, settings);
+    if !binary.is_empty() {
+        gltf["buffers"][0]["uri"] = json!(format!(
+            "data:application/octet-stream;base64,{}",
+            encode_base64(&binary)
+        ));
+    }
+    gltf
+}
+
+/// Play `scene` for `settings.duration` seconds and return it as a binary
+/// glTF (`.glb`) file
+pub fn scene_to_glb(scene: &mut SceneGraph, settings: &GltfSettings) -> Vec<u8> {
+    let (gltf, mut binary) = build(scene, settings);
+    let mut json = gltf.to_string().into_bytes();
+    json.resize(json.len().next_multiple_of(4), b' ');
+    binary.resize(binary.len().next_multiple_of(4), 0);
+
+    let binary_chunk = if binary.is_empty() {
+        0
+    } else {
+        8 + binary.len()
+    };
+    let length = 12 + 8 + json.len() + binary_chunk;
+    let mut glb = b"glTF".to_vec();
+    for word in [2, length as u32, json.len() as u32, GLB_JSON_CHUNK] {
+        glb.extend(word.to_le_bytes());
+    }
+    glb.extend(json);
+    if !binary.is_empty() {
+        glb.extend((binary.len() as u32).to_le_bytes());
+        glb.extend(GLB_BIN_CHUNK.to_le_bytes());
+        glb.extend(binary);
+    }
+    glb
+}
+
+/// Write `scene` as glTF: JSON with an embedded buffer for `.gltf` paths,
+/// binary otherwise (see [`scene_to_gltf`] and [`scene_to_glb`])
+pub fn export_gltf(
+    scene: &mut SceneGraph,
+    settings: &GltfSettings,
+    path: impl AsRef<Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let is_json = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("gltf"));
+    if is_json {
+        std::fs::write(path, scene_to_gltf(scene, settings).to_string())?;
+    } else {
+        std::fs::write(path, scene_to_glb(scene, settings))?;
+    }
+    Ok(())
+}
+
+/// The glTF JSON of `scene`, with `buffers[0]` lacking its URI, and the
+/// binary buffer it refers to
+fn build(scene: &mut SceneGraph, settings: &GltfSettings) -> (Value, Vec<u8>) {
+    let frame_count = settings.frame_count().max(1) as usize;
+    let frame_time = TimeValue::new(1.0 / settings.fps as f32);
+    let mut samples: Vec<NodeSamples> = Vec::new();
+
+    scene.update_transforms();
+    for frame in 0..frame_count {
+        if frame > 0 {
+            scene.update_animations(frame_time);
+            scene.update_transforms();
+        }
+        let mut sampled = vec![false; samples.len()];
+        for node in scene.nodes_in_draw_order() {
+            let index = samples
+                .iter()
+                .position(|sampled| sampled.id == node.id)
+                .unwrap_or_else(|| {
+                    samples.push(NodeSamples::new(node));
+                    sampled.push(false);
+                    samples.len() - 1
+                });
+            samples[index].push(node);
+            samples[index].backfill(frame + 1);
+            sampled[index] = true;
+        }
+        for (node, sampled) in samples.iter_mut().zip(sampled) {
+            if !sampled {
+                node.hold();
+            }
+        }
+    }
+
+    let indices: HashMap<NodeId, usize> = samples
+        .iter()
+        .enumerate()
+        .map(|(index, node)| (node.id, index))
+        .collect();
+    let mut buffers = Buffers::default();
+    let mut nodes = Vec::new();
+    let mut meshes = Vec::new();
+    let mut materials = Vec::new();
+    let mut roots = Vec::new();
+    let mut children = vec![Vec::new(); samples.len()];
+    let mut animation_samplers = Vec::new();
+    let mut channels = Vec::new();
+
+    for (index, node) in samples.iter().enumerate() {
+        match node.parent.and_then(|parent| indices.get(&parent)) {
+            Some(&parent) => children[parent].push(index),
+            None => roots.push(index),
+        }
+
+        let mut json_node = json!({
+            "name": node.name,
+            "translation": node.translation[0].as_slice(),
+            "rotation": node.rotation[0].as_slice(),
+            "scale": node.scale[0].as_slice(),
+        });
+        if let Some((mesh, color)) = node.renderable.as_ref().and_then(tessellate) {
+            let position = buffers.floats(&mesh.positions, Some(ARRAY_BUFFER));
+            let triangles = buffers.indices(&mesh.indices);
+            json_node["mesh"] = json!(meshes.len());
+            meshes.push(json!({
+                "name": node.name,
+                "primitives": [{
+                    "attributes": {"POSITION": position},
+                    "indices": triangles,
+                    "material": materials.len(),
+                }],
+            }));
+            materials.push(material(color));
+        }
+        nodes.push(json_node);
+
+        let mut animate = |buffers: &mut Buffers, path: &str, keys: &[usize], output: usize| {
+            let times: Vec<[f32; 1]> = keys
+                .iter()
+                .map(|&frame| [frame as f32 / settings.fps as f32])
+                .collect();
+            let input = buffers.floats(&times, None);
+            channels.push(json!({
+                "sampler": animation_samplers.len(),
+                "target": {"node": index, "path": path},
+            }));
+            animation_samplers.push(json!({
+                "input": input,
+                "output": output,
+                "interpolation": "LINEAR",
+            }));
+        };
+        let keys = keyframe_indices(&node.translation);
+        if keys.len() > 1 {
+            let values: Vec<[f32; 3]> = keys.iter().map(|&frame| node.translation[frame]).collect();
+            let output = buffers.floats(&values, None);
+            animate(&mut buffers, "translation", &keys, output);
+        }
+        let keys = keyframe_indices(&node.rotation);
+        if keys.len() > 1 {
+            let values: Vec<[f32; 4]> = keys.iter().map(|&frame| node.rotation[frame]).collect();
+            let output = buffers.floats(&values, None);
+            animate(&mut buffers, "rotation", &keys, output);
+        }
+        let keys = keyframe_indices(&node.scale);
+        if keys.len() > 1 {
+            let values: Vec<[f32; 3]> = keys.iter().map(|&frame| node.scale[frame]).collect();
+            let output = buffers.floats(&values, None);
+            animate(&mut buffers, "scale", &keys, output);
+        }
+    }
+    for (node, children) in nodes.iter_mut().zip(children) {
+        if !children.is_empty() {
+            node["children"] = json!(children);
+        }
+    }
+
+    let mut gltf = json!({
+        "asset": {"version": "2.0", "generator": "diomanim"},
+        "scene": 0,
+        "scenes": [{"nodes": roots}],
+        "nodes": nodes,
+    });
+    if !meshes.is_empty() {
+        gltf["meshes"] = json!(meshes);
+        gltf["materials"] = json!(materials);
+        gltf["extensionsUsed"] = json!(["KHR_materials_unlit"]);
+    }
+    if !channels.is_empty() {
+        gltf["animations"] = json!([{
+            "name": "scene",
+            "samplers": animation_samplers,
+            "channels": channels,
+        }]);
+    }
+    if !buffers.binary.is_empty() {
+        gltf["buffers"] = json!([{"byteLength": buffers.binary.len()}]);
+        gltf["bufferViews"] = json!(buffers.views);
+        gltf["accessors"] = json!(buffers.accessors);
+    }
+    (gltf, buffers.binary)
+}
+
+/// Flat, unlit, double-sided material of one color
+fn material(color: Color) -> Value {
+    let mut material = json!({
+        "pbrMetallicRoughness": {
+            "baseColorFactor": color.to_linear_array().as_slice(),
+            "metallicFactor": 0.0,
+            "roughnessFactor": 1.0,
+        },
+        "doubleSided": true,
+        "extensions": {"KHR_materials_unlit": {}},
+    });
+    if color.a < 1.0 {
+        material["alphaMode"] = json!("BLEND");
+    }
+    material
+}
+
+/// Standard base64 with padding
+fn encode_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| {
+            bits | (u32::from(byte) << (16 - 8 * i))
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(ALPHABET[((bits >> (18 - 6 * i)) & 63) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::gltf::{parse_gltf, GltfProperty};
+
+    #[test]
+    fn test_scene_round_trips_through_gltf() {
+        let mut scene = SceneGraph::new();
+        let dot = scene
+            .add_circle("dot", 0.25, Color::RED)
+            .move_to(0.0, Vector3::new(1.0, 0.0, 0.0), 1.0)
+            .build();
+        scene
+            .add_line(
+                "spoke",
+                Vector3::zero(),
+                Vector3::new(0.5, 0.0, 0.0),
+                Color::WHITE,
+                2.0,
+            )
+            .parent_to(dot);
+        scene.add_text("label", "Hi", 24.0, Color::WHITE);
+
+        let settings = GltfSettings::new(10, 2.0);
+        let gltf = scene_to_gltf(&mut scene, &settings);
+        // The text has no mesh; the line is a two-triangle strip
+        assert_eq!(gltf["meshes"].as_array().unwrap().len(), 2);
+        assert!(gltf["nodes"][2].get("mesh").is_none());
+        let spoke = gltf["nodes"][1]["mesh"].as_u64().unwrap() as usize;
+        let triangles = gltf["meshes"][spoke]["primitives"][0]["indices"]
+            .as_u64()
+            .unwrap() as usize;
+        assert_eq!(gltf["accessors"][triangles]["count"], 6);
+        assert_eq!(encode_base64(b"diomanim"), "ZGlvbWFuaW0=");
+
+        let mut scene = SceneGraph::new();
+        scene
+            .add_circle("dot", 0.25, Color::RED)
+            .move_to(0.0, Vector3::new(1.0, 0.0, 0.0), 1.0);
+        let document = parse_gltf(&scene_to_glb(&mut scene, &settings), None).unwrap();
+        assert_eq!(document.nodes[0].name, "dot");
+        // The move collapses into one segment, then holds
+        let channel = &document.animations[0].channels[0];
+        assert_eq!(channel.property, GltfProperty::Translation);
+        assert_eq!(channel.times, [0.0, 1.0, 1.9]);
+        assert_eq!(channel.values[3..6], [1.0, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,571 @@
+//! # Lottie Export
+//!
+//! Converts an animated [`SceneGraph`] into Lottie JSON (the Bodymovin format
+//! After Effects exports), so scenes can be embedded in web and mobile apps
+//! with lottie-web, lottie-android or lottie-ios as vectors instead of video.
+//!
+//! The scene is played through like an offline render, sampling every shape
+//! node's world transform and opacity once per frame. Runs of frames where a
+//! value changes linearly collapse into a single keyframe segment, so a node
+//! sliding at constant speed gets two position keyframes rather than one per
+//! frame, and nodes that never move get static properties.
+//!
+//! Circles, rectangles, lines, arrows and polygons become shape layers. Text
+//! and math are skipped (Lottie players would need their fonts), as are
+//! images, SVG assets, clip masks, node effects, deformers and post effects.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::core::Color;
+//! use diomanim::export::lottie::{scene_to_lottie, LottieSettings};
+//! use diomanim::scene::SceneGraph;
+//!
+//! let mut scene = SceneGraph::new();
+//! scene.add_circle("dot", 0.2, Color::RED).fade_out(0.0, 1.0);
+//!
+//! let lottie = scene_to_lottie(&mut scene, &LottieSettings::new(1280, 720, 30, 2.0));
+//! assert_eq!(lottie["op"], 60);
+//! ```
+
+use crate::core::{Color, TimeValue, Vector3};
+use crate::render::arrow_geometry;
+use crate::scene::{NodeId, Renderable, SceneGraph, SceneNode};
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// Lottie format version written to the `v` field
+const LOTTIE_VERSION: &str = "5.7.4";
+
+/// Samples within this distance of the straight line between two keyframes
+/// are left to interpolation
+const KEYFRAME_TOLERANCE: f32 = 1e-3;
+
+/// Index of the null layer that maps scene units to pixels
+const SCENE_LAYER: u32 = 1;
+
+/// Lottie export settings
+#[derive(Debug, Clone)]
+pub struct LottieSettings {
+    pub width: u32,
+    pub height: u32,
+    pub fps: u32,
+    /// Length of the animation in seconds
+    pub duration: f32,
+    /// Color of a full-frame background layer (transparent if `None`)
+    pub background: Option<Color>,
+}
+
+impl LottieSettings {
+    pub fn new(width: u32, height: u32, fps: u32, duration: f32) -> Self {
+        Self {
+            width,
+            height,
+            fps: fps.max(1),
+            duration: duration.max(0.0),
+            background: None,
+        }
+    }
+
+    /// Draw a solid background behind the scene
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Number of frames in the animation
+    pub fn frame_count(&self) -> u32 {
+        (self.duration * self.fps as f32).round() as u32
+    }
+}
+
+/// A shape node's transform and opacity at every frame
+struct LayerSamples {
+    id: NodeId,
+    name: String,
+    renderable: Renderable,
+    position: Vec<[f32; 2]>,
+    /// Degrees, unwrapped so consecutive frames never jump by a full turn
+    rotation: Vec<[f32; 1]>,
+    scale: Vec<[f32; 2]>,
+    /// Percent
+    opacity: Vec<[f32; 1]>,
+}
+
+impl LayerSamples {
+    fn new(node: &SceneNode, renderable: Renderable) -> Self {
+        Self {
+            id: node.id,
+            name: node.name.clone(),
+            renderable,
+            position: Vec::new(),
+            rotation: Vec::new(),
+            scale: Vec::new(),
+            opacity: Vec::new(),
+        }
+    }
+
+    /// Record the node's state at the next frame
+    fn push(&mut self, node: &SceneNode, visible: bool) {
+        let transform = &node.world_transform;
+        let x_axis = transform
+            .rotation
+            .rotate_vector(Vector3::new(1.0, 0.0, 0.0));
+        let mut rotation = x_axis.y.atan2(x_axis.x).to_degrees();
+        if let Some([previous]) = self.rotation.last() {
+            rotation += ((previous - rotation) / 360.0).round() * 360.0;
+        }
+        self.position
+            .push([transform.position.x, transform.position.y]);
+        self.rotation.push([rotation]);
+        self.scale
+            .push([transform.scale.x * 100.0, transform.scale.y * 100.0]);
+        self.opacity
+            .push([if visible { node.opacity * 100.0 } else { 0.0 }]);
+    }
+
+    /// Repeat the first sample (hidden) until there are `frames` samples, for
+    /// nodes added after the first frame
+    fn backfill(&mut self, frames: usize) {
+        let missing = frames.saturating_sub(self.position.len());
+        if missing == 0 {
+            return;
+        }
+        let position = self.position[0];
+        let rotation = self.rotation[0];
+        let scale = self.scale[0];
+        self.position.splice(0..0, vec![position; missing]);
+        self.rotation.splice(0..0, vec![rotation; missing]);
+        self.scale.splice(0..0, vec![scale; missing]);
+        self.opacity.splice(0..0, vec![[0.0]; missing]);
+    }
+
+    /// Keep the last transform, hidden, for a frame the node wasn't in the scene
+    fn hold(&mut self) {
+        let (Some(&position), Some(&rotation), Some(&scale)) = (
+            self.position.last(),
+            self.rotation.last(),
+            self.scale.last(),
+        ) else {
+            return;
+        };
+        self.position.push(position);
+        self.rotation.push(rotation);
+        self.scale.push(scale);
+        self.opacity.push([0.0]);
+    }
+}
+
+/// Play `scene` for `settings.duration` seconds and return it as a Lottie
+/// animation
+///
+/// Animations are advanced as in an offline render, so the scene is left at
+/// its final frame.
+pub fn scene_to_lottie(scene: &mut SceneGraph, settings: &LottieSettings) -> Value {
+    let frame_count = settings.frame_count().max(1) as usize;
+    let frame_time = TimeValue::new(1.0 / settings.fps as f32);
+    let mut layers: Vec<LayerSamples> = Vec::new();
+
+    scene.update_transforms();
+    for frame in 0..frame_count {
+        if frame > 0 {
+            scene.update_animations(frame_time);
+            scene.update_transforms();
+        }
+
+        let visible: Vec<NodeId> = scene
+            .visible_renderable_nodes()
+            .iter()
+            .map(|node| node.id)
+            .collect();
+        let mut sampled = vec![false; layers.len()];
+        for node in scene.nodes_in_draw_order() {
+            // Glyphs are the renderables `shape_items` skips
+            let Some(renderable) = node.renderable.as_ref().filter(|r| !r.is_glyphs()) else {
+                continue;
+            };
+            let index = layers
+                .iter()
+                .position(|layer| layer.id == node.id)
+                .unwrap_or_else(|| {
+                    layers.push(LayerSamples::new(node, renderable.clone()));
+                    sampled.push(false);
+                    layers.len() - 1
+                });
+            layers[index].push(node, visible.contains(&node.id));
+            layers[index].backfill(frame + 1);
+            sampled[index] = true;
+        }
+        for (layer, sampled) in layers.iter_mut().zip(sampled) {
+            if !sampled {
+                layer.hold();
+            }
+        }
+    }
+
+    let out_point = settings.frame_count().max(1);
+    let mut json_layers = vec![scene_layer(settings, out_point)];
+    // Lottie draws its first layer on top, the scene draws its first node first
+    for (index, layer) in layers.iter().enumerate().rev() {
+        json_layers.push(shape_layer(
+            layer,
+            SCENE_LAYER + 1 + index as u32,
+            out_point,
+        ));
+    }
+    if let Some(background) = settings.background {
+        json_layers.push(background_layer(
+            settings,
+            background,
+            SCENE_LAYER + 1 + layers.len() as u32,
+            out_point,
+        ));
+    }
+
+    json!({
+        "v": LOTTIE_VERSION,
+        "fr": settings.fps,
+        "ip": 0,
+        "op": out_point,
+        "w": settings.width,
+        "h": settings.height,
+        "nm": "diomanim",
+        "ddd": 0,
+        "assets": [],
+        "layers": json_layers,
+    })
+}
+
+/// Write `scene` as a Lottie `.json` file (see [`scene_to_lottie`])
+pub fn export_lottie(
+    scene: &mut SceneGraph,
+    settings: &LottieSettings,
+    path: impl AsRef<Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, scene_to_lottie(scene, settings).to_string())?;
+    Ok(())
+}
+
+/// Invisible parent layer mapping scene units (-1..1, y up) onto the frame
+fn scene_layer(settings: &LottieSettings, out_point: u32) -> Value {
+    let half_width = settings.width as f32 / 2.0;
+    let half_height = settings.height as f32 / 2.0;
+    json!({
+        "ddd": 0,
+        "ind": SCENE_LAYER,
+        "ty": 3,
+        "nm": "scene",
+        "ks": {
+            "o": {"a": 0, "k": 100},
+            "r": {"a": 0, "k": 0},
+            "p": {"a": 0, "k": [half_width, half_height, 0]},
+            "a": {"a": 0, "k": [0, 0, 0]},
+            "s": {"a": 0, "k": [half_width * 100.0, -half_height * 100.0, 100]},
+        },
+        "ao": 0,
+        "ip": 0,
+        "op": out_point,
+        "st": 0,
+        "sr": 1,
+    })
+}
+
+fn background_layer(settings: &LottieSettings, color: Color, index: u32, out_point: u32) -> Value {
+    json!({
+        "ddd": 0,
+        "ind": index,
+        "ty": 1,
+        "nm": "background",
+        "ks": {
+            "o": {"a": 0, "k": color.a * 100.0},
+            "r": {"a": 0, "k": 0},
+            "p": {"a": 0, "k": [0, 0, 0]},
+            "a": {"a": 0, "k": [0, 0, 0]},
+            "s": {"a": 0, "k": [100, 100, 100]},
+        },
+        "sc": color.to_hex(),
+        "sw": settings.width,
+        "sh": settings.height,
+        "ao": 0,
+        "ip": 0,
+        "op": out_point,
+        "st": 0,
+        "sr": 1,
+    })
+}
+
+fn shape_layer(layer: &LayerSamples, index: u32, out_point: u32) -> Value {
+    let mut items = shape_items(&layer.renderable).unwrap_or_default();
+    items.push(json!({
+        "ty": "tr",
+        "p": {"a": 0, "k": [0, 0]},
+        "a": {"a": 0, "k": [0, 0]},
+        "s": {"a": 0, "k": [100, 100]},
+        "r": {"a": 0, "k": 0},
+        "o": {"a": 0, "k": 100},
+    }));
+    json!({
+        "ddd": 0,
+        "ind": index,
+        "ty": 4,
+        "nm": layer.name,
+        "parent": SCENE_LAYER,
+        "ks": {
+            "o": property(&layer.opacity),
+            "r": property(&layer.rotation),
+            "p": property(&layer.position.iter().map(|&[x, y]| [x, y, 0.0]).collect::<Vec<_>>()),
+            "a": {"a": 0, "k": [0, 0, 0]},
+            "s": property(&layer.scale.iter().map(|&[x, y]| [x, y, 100.0]).collect::<Vec<_>>()),
+        },
+        "ao": 0,
+        "shapes": [{"ty": "gr", "nm": layer.name, "it": items}],
+        "ip": 0,
+        "op": out_point,
+        "st": 0,
+        "sr": 1,
+        "bm": 0,
+    })
+}
+
+/// Lottie shape items drawing `renderable` in node space, or `None` for
+/// renderables that aren't exported
+fn shape_items(renderable: &Renderable) -> Option<Vec<Value>> {
+    let items = match renderable {
+        Renderable::Circle { radius, color } => vec![
+            json!({
+                "ty": "el",
+                "p": {"a": 0, "k": [0, 0]},
+                "s": {"a": 0, "k": [radius * 2.0, radius * 2.0]},
+            }),
+            fill(*color),
+        ],
+        Renderable::Rectangle {
+            width,
+            height,
+            color,
+        } => vec![
+            json!({
+                "ty": "rc",
+                "p": {"a": 0, "k": [0, 0]},
+                "s": {"a": 0, "k": [width, height]},
+                "r": {"a": 0, "k": 0},
+            }),
+            fill(*color),
+        ],
+        Renderable::Polygon { vertices, color } => vec![path(vertices, true), fill(*color)],
+        Renderable::Line {
+            start,
+            end,
+            color,
+            thickness,
+        } => vec![path(&[*start, *end], false), stroke(*color, *thickness)],
+        Renderable::Polyline {
+            points,
+            color,
+            thickness,
+            ..
+        } => vec![path(points, false), stroke(*color, *thickness)],
+        Renderable::Arrow {
+            start,
+            end,
+            color,
+            thickness,
+        } => {
+            let Some((shaft_end, tip)) = arrow_geometry(*start, *end) else {
+                return Some(Vec::new());
+            };
+            vec![
+                json!({
+                    "ty": "gr",
+                    "nm": "shaft",
+                    "it": [path(&[*start, shaft_end], false), stroke(*color, *thickness), group_transform()],
+                }),
+                json!({
+                    "ty": "gr",
+                    "nm": "tip",
+                    "it": [path(&tip, true), fill(*color), group_transform()],
+                }),
+            ]
+        }
+        Renderable::Text { .. }
+        | Renderable::Math { .. }
+        | Renderable::RichText { .. }
+        | Renderable::TextOnPath { .. }
+        | Renderable::Image { .. }
+        | Renderable::Svg { .. } => return None,
+    };
+    Some(items)
+}
+
+fn group_transform() -> Value {
+    json!({
+        "ty": "tr",
+        "p": {"a": 0, "k": [0, 0]},
+        "a": {"a": 0, "k": [0, 0]},
+        "s": {"a": 0, "k": [100, 100]},
+        "r": {"a": 0, "k": 0},
+        "o": {"a": 0, "k": 100},
+    })
+}
+
+/// Straight-edged path through `points`
+fn path(points: &[Vector3], closed: bool) -> Value {
+    let vertices: Vec<[f32; 2]> = points.iter().map(|point| [point.x, point.y]).collect();
+    let tangents = vec![[0.0_f32, 0.0]; points.len()];
+    json!({
+        "ty": "sh",
+        "ks": {
+            "a": 0,
+            "k": {"i": tangents, "o": tangents, "v": vertices, "c": closed},
+        },
+    })
+}
+
+fn fill(color: Color) -> Value {
+    json!({
+        "ty": "fl",
+        "c": {"a": 0, "k": [color.r, color.g, color.b, 1]},
+        "o": {"a": 0, "k": color.a * 100.0},
+        "r": 1,
+    })
+}
+
+/// Stroke `thickness` wide, in the renderer's line units (1/100 scene unit)
+fn stroke(color: Color, thickness: f32) -> Value {
+    json!({
+        "ty": "st",
+        "c": {"a": 0, "k": [color.r, color.g, color.b, 1]},
+        "o": {"a": 0, "k": color.a * 100.0},
+        "w": {"a": 0, "k": thickness / 100.0},
+        "lc": 1,
+        "lj": 1,
+    })
+}
+
+/// Static or keyframed Lottie property from one sample per frame
+fn property<const N: usize>(samples: &[[f32; N]]) -> Value {
+    let value = |sample: &[f32; N]| {
+        if N == 1 {
+            json!(sample[0])
+        } else {
+            json!(sample.as_slice())
+        }
+    };
+    let keys = keyframe_indices(samples);
+    if keys.len() < 2 {
+        return json!({"a": 0, "k": value(&samples[0])});
+    }
+
+    let last = keys.len() - 1;
+    let keyframes: Vec<Value> = keys
+        .iter()
+        .enumerate()
+        .map(|(i, &frame)| {
+            if i == last {
+                json!({"t": frame, "s": samples[frame].as_slice()})
+            } else {
+                // Linear in/out handles
+                json!({
+                    "t": frame,
+                    "s": samples[frame].as_slice(),
+                    "o": {"x": [0], "y": [0]},
+                    "i": {"x": [1], "y": [1]},
+                })
+            }
+        })
+        .collect();
+    json!({"a": 1, "k": keyframes})
+}
+
+/// Frames to keyframe so that linear interpolation between them reproduces
+/// every sample; a single index means the value never changes
+pub(super) fn keyframe_indices<const N: usize>(samples: &[[f32; N]]) -> Vec<usize> {
+    let on_line = |from: usize, to: usize, frame: usize| {
+        let t = (frame - from) as f32 / (to - from) as f32;
+        (0..N).all(|c| {
+            let expected = samples[from][c] + (samples[to][c] - samples[from][c]) * t;
+            (samples[frame][c] - expected).abs() <= KEYFRAME_TOLERANCE
+        })
+    };
+
+    let mut keys = vec![0];
+    let mut anchor = 0;
+    for end in 2..samples.len() {
+        if !(anchor + 1..end).all(|frame| on_line(anchor, end, frame)) {
+            anchor = end - 1;
+            keys.push(anchor);
+        }
+    }
+    let last = samples.len().saturating_sub(1);
+    let changes = samples
+        .iter()
+        .any(|sample| (0..N).any(|c| (sample[c] - samples[0][c]).abs() > KEYFRAME_TOLERANCE));
+    if last > 0 && changes {
+        keys.push(last);
+    }
+    if !changes {
+        keys.truncate(1);
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyframes_collapse_linear_runs() {
+        let constant = [[2.0_f32]; 10];
+        assert_eq!(keyframe_indices(&constant), vec![0]);
+
+        // Ramp up for 5 frames, then hold
+        let ramp: Vec<[f32; 1]> = (0..10).map(|i| [i.min(4) as f32 * 25.0]).collect();
+        assert_eq!(keyframe_indices(&ramp), vec![0, 4, 9]);
+
+        let animated = property(&ramp);
+        assert_eq!(animated["a"], 1);
+        assert_eq!(animated["k"][1]["t"], 4);
+        assert_eq!(animated["k"][1]["s"], json!([100.0]));
+        assert_eq!(property(&constant), json!({"a": 0, "k": 2.0}));
+    }
+
+    #[test]
+    fn test_scene_to_lottie() {
+        let mut scene = SceneGraph::new();
+        scene
+            .add_circle("dot", 0.25, Color::RED)
+            .at(0.5, 0.0, 0.0)
+            .fade_out(0.0, 1.0);
+        scene.add_rectangle("box", 0.5, 0.2, Color::BLUE);
+        scene.add_text("label", "Hi", 24.0, Color::WHITE);
+
+        let settings = LottieSettings::new(200, 100, 10, 2.0).with_background(Color::BLACK);
+        let lottie = scene_to_lottie(&mut scene, &settings);
+        assert_eq!(lottie["fr"], 10);
+        assert_eq!(lottie["op"], 20);
+
+        // Scene null, both shapes (last drawn on top), background; no text
+        let layers = lottie["layers"].as_array().unwrap();
+        let names: Vec<&str> = layers.iter().map(|l| l["nm"].as_str().unwrap()).collect();
+        assert_eq!(names, ["scene", "box", "dot", "background"]);
+        assert_eq!(layers[0]["ks"]["s"]["k"], json!([10000.0, -5000.0, 100]));
+
+        let dot = &layers[2];
+        assert_eq!(dot["parent"], SCENE_LAYER);
+        assert_eq!(dot["ks"]["p"], json!({"a": 0, "k": [0.5, 0.0, 0.0]}));
+        assert_eq!(dot["shapes"][0]["it"][0]["s"]["k"], json!([0.5, 0.5]));
+        // The fade out becomes one linear segment, then holds
+        let opacity = dot["ks"]["o"]["k"].as_array().unwrap();
+        assert_eq!(opacity.len(), 3);
+        assert_eq!(opacity[0]["s"], json!([100.0]));
+        assert_eq!(opacity[1]["t"], 10);
+        assert_eq!(opacity[2]["s"], json!([0.0]));
+
+        assert_eq!(layers[1]["ks"]["o"], json!({"a": 0, "k": 100.0}));
+        assert_eq!(layers[3]["sc"], "#000000");
+    }
+}
@@ -0,0 +1,190 @@
+//! # Video Metadata and Chapters
+//!
+//! A [`VideoMetadata`] set with [`VideoExportSettings::with_metadata`] is
+//! written into the MP4 (or WebM, MOV) container: a title, author and
+//! description that players and video sites show, and chapter markers,
+//! usually one per timeline [`Section`], that make a long lecture navigable.
+//! ffmpeg reads them from an `FFMETADATA1` file written next to the video
+//! while it encodes.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::core::Timeline;
+//! use diomanim::export::metadata::VideoMetadata;
+//!
+//! let mut timeline = Timeline::new();
+//! timeline.add_section("Definition", 0.0, 12.0);
+//! timeline.add_section("Proof", 12.0, 40.0);
+//!
+//! let metadata = VideoMetadata::new()
+//!     .with_title("Fourier Series")
+//!     .with_author("Ada")
+//!     .with_timeline_chapters(&timeline, 45.0);
+//! assert!(metadata.ffmetadata().contains("title=Proof"));
+//! ```
+//!
+//! [`VideoExportSettings::with_metadata`]: super::VideoExportSettings::with_metadata
+
+use crate::core::{Section, Timeline};
+use std::fmt::Write;
+use std::path::Path;
+
+/// A named span of the video players can jump to
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapter {
+    pub title: String,
+    /// Start in seconds
+    pub start: f32,
+    /// End in seconds
+    pub end: f32,
+}
+
+impl Chapter {
+    pub fn new(title: impl Into<String>, start: f32, end: f32) -> Self {
+        let start = start.max(0.0);
+        Self {
+            title: title.into(),
+            start,
+            end: end.max(start),
+        }
+    }
+
+    /// Chapter covering `section`; open sections run to `duration`
+    pub fn from_section(section: &Section, duration: f32) -> Self {
+        let end = section.end.map_or(duration, |end| end.value);
+        Self::new(&section.name, section.start.value, end)
+    }
+}
+
+/// Descriptive tags and chapters of an exported video
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VideoMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    /// In start order
+    pub chapters: Vec<Chapter>,
+}
+
+impl VideoMetadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn with_author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_chapter(mut self, chapter: Chapter) -> Self {
+        let index = self
+            .chapters
+            .partition_point(|existing| existing.start <= chapter.start);
+        self.chapters.insert(index, chapter);
+        self
+    }
+
+    /// Add a chapter per section of `timeline` that starts within
+    /// `duration` seconds, cut off at `duration`
+    pub fn with_timeline_chapters(self, timeline: &Timeline, duration: f32) -> Self {
+        timeline
+            .sections()
+            .iter()
+            .filter(|section| section.start.value < duration)
+            .map(|section| {
+                let chapter = Chapter::from_section(section, duration);
+                Chapter::new(chapter.title, chapter.start, chapter.end.min(duration))
+            })
+            .fold(self, Self::with_chapter)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.author.is_none()
+            && self.description.is_none()
+            && self.chapters.is_empty()
+    }
+
+    /// The metadata in ffmpeg's `FFMETADATA1` format, with chapter times in
+    /// milliseconds
+    ///
+    /// The author is written as both `artist` and `author`, since players
+    /// differ in which one they show.
+    pub fn ffmetadata(&self) -> String {
+        let mut text = String::from(";FFMETADATA1\n");
+        let tags = [
+            ("title", &self.title),
+            ("artist", &self.author),
+            ("author", &self.author),
+            ("description", &self.description),
+        ];
+        for (key, value) in tags {
+            if let Some(value) = value {
+                let _ = writeln!(text, "{key}={}", escape(value));
+            }
+        }
+        for chapter in &self.chapters {
+            let millis = |seconds: f32| (seconds * 1000.0).round() as u64;
+            let _ = write!(
+                text,
+                "\n[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+                millis(chapter.start),
+                millis(chapter.end),
+                escape(&chapter.title)
+            );
+        }
+        text
+    }
+
+    /// Write [`Self::ffmetadata`] to `path`
+    pub fn write_ffmetadata(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.ffmetadata())
+    }
+}
+
+/// Backslash-escape the characters `FFMETADATA1` values can't hold as is
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffmetadata_chapters_from_sections() {
+        let mut timeline = Timeline::new();
+        timeline.add_section("Proof", 10.0, 30.0);
+        timeline.add_section("Intro", 0.0, 10.0);
+        timeline.add_section("Credits", 90.0, 95.0);
+        let metadata = VideoMetadata::new()
+            .with_title("Limits; a #1 = hit")
+            .with_timeline_chapters(&timeline, 25.0);
+
+        let text = metadata.ffmetadata();
+        assert!(text.starts_with(";FFMETADATA1\ntitle=Limits\\; a \\#1 \\= hit\n"));
+        assert!(!text.contains("artist"));
+        // Sections past the end are dropped and the last one is cut short
+        assert_eq!(metadata.chapters.len(), 2);
+        assert!(text.ends_with("[CHAPTER]\nTIMEBASE=1/1000\nSTART=10000\nEND=25000\ntitle=Proof\n"));
+        assert!(VideoMetadata::new().is_empty());
+    }
+}
@@ -1,10 +1,160 @@
 //! # Video Export Module
 //!
-//! Provides functionality to export rendered PNG frames to video files (MP4/H.264)
-//! using ffmpeg subprocess
+//! Provides functionality to export rendered PNG frames to video files (MP4/H.264,
+//! WebM/VP9, looping GIFs, or ProRes 4444 and TIFF/EXR frame sequences, see [`VideoCodec`]) using
+//! ffmpeg subprocess, plus caption tracks (see [`captions`]), titles and
+//! chapter markers (see [`metadata`]), Lottie
+//! vector animations (see [`lottie`]), glTF scenes for 3D tools (see [`gltf`]) and HTML slide decks (see [`slides`]). In the browser, where there is no
+//! ffmpeg, [`web`] captures canvas frames as PNG blobs instead.
 
-use std::path::Path;
-use std::process::Command;
+pub mod captions;
+pub mod figure;
+pub mod gltf;
+pub mod lottie;
+pub mod metadata;
+pub mod slides;
+#[cfg(target_arch = "wasm32")]
+pub mod web;
+
+use crate::core::{CaptionCue, SoundCue};
+use crate::pipeline::CancellationToken;
+use captions::CaptionFormat;
+use metadata::VideoMetadata;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    crate::pipeline::{NoProgress, ProgressSink, RenderCancelled, RenderStage},
+    std::io::{BufRead, BufReader, Read},
+    std::process::{Command, Stdio},
+};
+
+/// Codec and container the frames are encoded to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VideoCodec {
+    /// H.264 in an MP4 file, for playback and sharing
+    #[default]
+    H264,
+    /// VP9 in a WebM file, for playback in browsers
+    Vp9,
+    /// ProRes 4444 with alpha in a QuickTime `.mov`, for video editors
+    ProRes4444,
+    /// Animated GIF that loops forever, with a palette generated from the
+    /// frames, for social media clips (see [`crate::pipeline::looping`])
+    Gif,
+    /// 16-bit RGBA TIFF frames; the output path is a pattern such as
+    /// `comp/frame_%04d.tiff`
+    TiffSequence,
+    /// Half-float RGBA OpenEXR frames; the output path is a pattern such as
+    /// `comp/frame_%04d.exr`
+    ExrSequence,
+}
+
+impl VideoCodec {
+    /// File extension of the output (of each frame for sequences)
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::H264 => "mp4",
+            Self::Vp9 => "webm",
+            Self::ProRes4444 => "mov",
+            Self::Gif => "gif",
+            Self::TiffSequence => "tiff",
+            Self::ExrSequence => "exr",
+        }
+    }
+
+    /// Codec writing files with `extension` (in any case), if one does
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        [
+            Self::H264,
+            Self::Vp9,
+            Self::ProRes4444,
+            Self::Gif,
+            Self::TiffSequence,
+            Self::ExrSequence,
+        ]
+        .into_iter()
+        .find(|codec| codec.extension().eq_ignore_ascii_case(extension))
+    }
+
+    /// Whether each frame is written to its own file
+    pub fn is_image_sequence(self) -> bool {
+        matches!(self, Self::TiffSequence | Self::ExrSequence)
+    }
+
+    /// Whether the container holds tags and chapters (see [`metadata`])
+    pub fn supports_metadata(self) -> bool {
+        !self.is_image_sequence() && self != Self::Gif
+    }
+
+    /// ffmpeg output arguments selecting the encoder and pixel format
+    pub fn ffmpeg_args(self) -> &'static [&'static str] {
+        match self {
+            // crf 18 is visually lossless; slow trades speed for compression
+            Self::H264 => &[
+                "-c:v", "libx264", "-pix_fmt", "yuv420p", "-crf", "18", "-preset", "slow",
+            ],
+            // Constant quality: crf with a zero bitrate cap
+            Self::Vp9 => &[
+                "-c:v",
+                "libvpx-vp9",
+                "-pix_fmt",
+                "yuv420p",
+                "-crf",
+                "30",
+                "-b:v",
+                "0",
+            ],
+            Self::ProRes4444 => &[
+                "-c:v",
+                "prores_ks",
+                "-profile:v",
+                "4444",
+                "-pix_fmt",
+                "yuva444p10le",
+                "-vendor",
+                "apl0",
+            ],
+            // Loop count 0 repeats forever
+            Self::Gif => &["-loop", "0"],
+            Self::TiffSequence => &["-c:v", "tiff", "-pix_fmt", "rgba64le"],
+            Self::ExrSequence => &[
+                "-c:v",
+                "exr",
+                "-pix_fmt",
+                "gbrapf32le",
+                "-format",
+                "half",
+                "-compression",
+                "zip1",
+            ],
+        }
+    }
+
+    /// ffmpeg video filter the frames need before encoding, if any
+    #[cfg(not(target_arch = "wasm32"))]
+    fn video_filter(self) -> Option<&'static str> {
+        match self {
+            // One 256-color palette fitted to all frames instead of a fixed one
+            Self::Gif => {
+                Some("split[frames][copy];[copy]palettegen[palette];[frames][palette]paletteuse")
+            }
+            _ => None,
+        }
+    }
+
+    /// ffmpeg arguments encoding the sound cues (`None` for image sequences
+    /// and GIFs)
+    #[cfg(not(target_arch = "wasm32"))]
+    fn audio_args(self) -> Option<&'static [&'static str]> {
+        match self {
+            Self::H264 => Some(&["-c:a", "aac", "-b:a", "192k"]),
+            Self::Vp9 => Some(&["-c:a", "libopus", "-b:a", "128k"]),
+            Self::ProRes4444 => Some(&["-c:a", "pcm_s16le"]),
+            Self::Gif | Self::TiffSequence | Self::ExrSequence => None,
+        }
+    }
+}
 
 /// Video export settings
 pub struct VideoExportSettings {
@@ -13,6 +163,20 @@ pub struct VideoExportSettings {
     pub fps: u32,
     pub output_path: String,
     pub input_pattern: String,
+    /// Encoder and container of the output
+    pub codec: VideoCodec,
+    /// Sounds mixed into the audio track (no audio track if empty)
+    pub sound_cues: Vec<SoundCue>,
+    /// Captions written next to the video (none if empty)
+    pub captions: Vec<CaptionCue>,
+    /// Format of the caption file written next to the video
+    pub caption_format: CaptionFormat,
+    /// Also render the captions into the video frames
+    pub burn_in_captions: bool,
+    /// Title, author, description and chapters written into the container
+    pub metadata: VideoMetadata,
+    /// Stops ffmpeg (or keeps it from starting) when cancelled
+    pub cancel: CancellationToken,
 }
 
 impl VideoExportSettings {
@@ -29,11 +193,104 @@ impl VideoExportSettings {
             fps,
             output_path,
             input_pattern,
+            codec: VideoCodec::default(),
+            sound_cues: Vec::new(),
+            captions: Vec::new(),
+            caption_format: CaptionFormat::Srt,
+            burn_in_captions: false,
+            metadata: VideoMetadata::new(),
+            cancel: CancellationToken::new(),
         }
     }
+
+    /// Encode with `codec` instead of H.264
+    ///
+    /// Image sequences and GIFs have no audio track, so sound cues are skipped.
+    pub fn with_codec(mut self, codec: VideoCodec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// Mix the given sound cues (e.g. `timeline.sound_cues()`) into the video
+    pub fn with_sound_cues(mut self, cues: &[SoundCue]) -> Self {
+        self.sound_cues = cues.to_vec();
+        self
+    }
+
+    /// Write `cues` to a caption file next to the video
+    pub fn with_captions(mut self, cues: &[CaptionCue], format: CaptionFormat) -> Self {
+        self.captions = cues.to_vec();
+        self.caption_format = format;
+        self
+    }
+
+    /// Burn the captions into the frames as well (requires ffmpeg with libass)
+    pub fn with_burned_in_captions(mut self) -> Self {
+        self.burn_in_captions = true;
+        self
+    }
+
+    /// Write `metadata` into the video's container (not supported by image
+    /// sequences and GIFs)
+    pub fn with_metadata(mut self, metadata: VideoMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Stop encoding when `token` is cancelled, removing the partial output
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    /// Path of the caption file, e.g. `output/video.srt` for `output/video.mp4`
+    pub fn caption_path(&self) -> PathBuf {
+        Path::new(&self.output_path).with_extension(self.caption_format.extension())
+    }
+
+    /// Path of the `FFMETADATA1` file ffmpeg reads the metadata from while
+    /// encoding, e.g. `output/video.ffmeta` for `output/video.mp4`
+    pub fn metadata_path(&self) -> PathBuf {
+        Path::new(&self.output_path).with_extension("ffmeta")
+    }
+}
+
+/// Escape a path for use inside a single-quoted ffmpeg filter argument
+#[cfg(not(target_arch = "wasm32"))]
+fn escape_filter_path(path: &Path) -> String {
+    path.to_string_lossy()
+        .replace('\\', "/")
+        .replace('\'', "'\\''")
+}
+
+/// Build the ffmpeg `-filter_complex` graph that delays each cue to its
+/// start time and mixes them into a single `[aout]` stream.
+///
+/// Cue `i` is expected as ffmpeg input `i + 1` (input 0 is the frame sequence).
+/// The mix is padded with silence so `-shortest` trims it to the video length.
+pub fn sound_cue_filter(cues: &[SoundCue]) -> String {
+    let mut filter = String::new();
+    for (i, cue) in cues.iter().enumerate() {
+        let delay_ms = (cue.time.seconds() * 1000.0).max(0.0).round() as u64;
+        let _ = write!(
+            filter,
+            "[{}:a]adelay={delay_ms}:all=1,volume={}[a{i}];",
+            i + 1,
+            cue.volume
+        );
+    }
+    for i in 0..cues.len() {
+        let _ = write!(filter, "[a{i}]");
+    }
+    let _ = write!(
+        filter,
+        "amix=inputs={}:normalize=0:dropout_transition=0,apad[aout]",
+        cues.len()
+    );
+    filter
 }
 
-/// Export PNG frames to MP4 video using ffmpeg
+/// Export PNG frames to video (MP4 by default, see [`VideoCodec`]) using ffmpeg
 ///
 /// # Arguments
 /// * `settings` - Video export settings
@@ -53,6 +310,7 @@ impl VideoExportSettings {
 /// );
 /// export_video_ffmpeg(&settings).unwrap();
 /// ```
+#[cfg(not(target_arch = "wasm32"))]
 pub fn export_video_ffmpeg(
     settings: &VideoExportSettings,
 ) -> Result<(), Box<dyn std::error::Error>> {
@@ -63,7 +321,70 @@ pub fn export_video_ffmpeg(
     println!("  Output: {}", settings.output_path);
     println!("  Resolution: {}x{}", settings.width, settings.height);
     println!("  FPS: {}", settings.fps);
+    println!("  Codec: {:?}", settings.codec);
+    if !settings.sound_cues.is_empty() {
+        println!("  Sound cues: {}", settings.sound_cues.len());
+    }
+    if !settings.captions.is_empty() {
+        println!("  Captions: {}", settings.caption_path().display());
+    }
+    if !settings.metadata.chapters.is_empty() {
+        println!("  Chapters: {}", settings.metadata.chapters.len());
+    }
     println!();
+    if settings.codec.audio_args().is_none() && !settings.sound_cues.is_empty() {
+        println!(
+            "⚠️  {:?} output has no audio track; skipping sound cues\n",
+            settings.codec
+        );
+    }
+    if !settings.codec.supports_metadata() && !settings.metadata.is_empty() {
+        println!(
+            "⚠️  {:?} output has no container metadata; skipping title and chapters\n",
+            settings.codec
+        );
+    }
+
+    encode(settings, &mut NoProgress)?;
+
+    if settings.codec.is_image_sequence() {
+        println!("✅ Frame sequence export complete!");
+        println!("   Output: {}", settings.output_path);
+        return Ok(());
+    }
+
+    // Get output file size
+    let metadata = std::fs::metadata(&settings.output_path)?;
+    let file_size_mb = metadata.len() as f64 / (1024.0 * 1024.0);
+
+    println!("✅ Video export complete!");
+    println!("   Output: {}", settings.output_path);
+    println!("   Size: {:.2} MB", file_size_mb);
+
+    Ok(())
+}
+
+/// Like [`export_video_ffmpeg`], but reporting [`RenderStage::Encoding`]
+/// and each encoded frame to `progress` instead of printing
+#[cfg(not(target_arch = "wasm32"))]
+pub fn export_video_ffmpeg_with_progress(
+    settings: &VideoExportSettings,
+    progress: &mut dyn ProgressSink,
+) -> Result<(), Box<dyn std::error::Error>> {
+    progress.on_stage(RenderStage::Encoding);
+    encode(settings, progress)
+}
+
+/// Run ffmpeg for `settings`, reporting the frames it has encoded
+#[cfg(not(target_arch = "wasm32"))]
+fn encode(
+    settings: &VideoExportSettings,
+    progress: &mut dyn ProgressSink,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let total = input_frame_count(&settings.input_pattern);
+    if settings.cancel.is_cancelled() {
+        return Err(cancelled(settings, 0, total).into());
+    }
 
     // Check if ffmpeg is available
     let ffmpeg_check = Command::new("ffmpeg").arg("-version").output();
@@ -77,44 +398,224 @@ pub fn export_video_ffmpeg(
         std::fs::create_dir_all(parent)?;
     }
 
+    // Captions go next to the video (and are read back by ffmpeg for burn-in)
+    if !settings.captions.is_empty() {
+        captions::write_captions(&settings.captions, settings.caption_path())?;
+    }
+
     // Build ffmpeg command
     // ffmpeg -framerate 30 -i frames/frame_%04d.png -c:v libx264 -pix_fmt yuv420p -crf 18 output.mp4
-    let output = Command::new("ffmpeg")
+    let mut command = Command::new("ffmpeg");
+    command
         .arg("-y") // Overwrite output file without asking
+        .args(["-progress", "pipe:1", "-nostats"])
         .arg("-framerate")
         .arg(settings.fps.to_string())
         .arg("-i")
-        .arg(&settings.input_pattern)
-        .arg("-c:v")
-        .arg("libx264") // H.264 codec
-        .arg("-pix_fmt")
-        .arg("yuv420p") // Standard pixel format for compatibility
-        .arg("-crf")
-        .arg("18") // Quality (0-51, lower is better, 18 is visually lossless)
-        .arg("-preset")
-        .arg("slow") // Encoding speed vs compression (slow = better compression)
+        .arg(&settings.input_pattern);
+
+    // Sound cues: one input per cue, delayed and mixed into an audio track
+    let audio_args = settings.codec.audio_args();
+    let mut inputs = 1;
+    if let (Some(audio_args), false) = (audio_args, settings.sound_cues.is_empty()) {
+        for cue in &settings.sound_cues {
+            command.arg("-i").arg(&cue.path);
+        }
+        inputs += settings.sound_cues.len();
+        command
+            .arg("-filter_complex")
+            .arg(sound_cue_filter(&settings.sound_cues))
+            .arg("-map")
+            .arg("0:v")
+            .arg("-map")
+            .arg("[aout]")
+            .args(audio_args)
+            .arg("-shortest");
+    }
+
+    // Metadata: one more input, with tags and chapters but no streams
+    let metadata_path = (!settings.metadata.is_empty() && settings.codec.supports_metadata())
+        .then(|| settings.metadata_path());
+    if let Some(path) = &metadata_path {
+        settings.metadata.write_ffmetadata(path)?;
+        command
+            .arg("-i")
+            .arg(path)
+            .arg("-map_metadata")
+            .arg(inputs.to_string())
+            .arg("-map_chapters")
+            .arg(inputs.to_string());
+    }
+
+    let mut filters = Vec::new();
+    if settings.burn_in_captions && !settings.captions.is_empty() {
+        filters.push(format!(
+            "subtitles=filename='{}'",
+            escape_filter_path(&settings.caption_path())
+        ));
+    }
+    filters.extend(settings.codec.video_filter().map(str::to_string));
+    if !filters.is_empty() {
+        command.arg("-vf").arg(filters.join(","));
+    }
+
+    let child = command
+        .args(settings.codec.ffmpeg_args())
         .arg(&settings.output_path)
-        .output()?;
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let result = child
+        .map_err(Into::into)
+        .and_then(|child| wait_for_encode(child, settings, progress, total));
+    if let Some(path) = metadata_path {
+        let _ = std::fs::remove_file(path);
+    }
+    result
+}
+
+/// Follow ffmpeg's progress lines until it exits or is cancelled
+#[cfg(not(target_arch = "wasm32"))]
+fn wait_for_encode(
+    mut child: std::process::Child,
+    settings: &VideoExportSettings,
+    progress: &mut dyn ProgressSink,
+    total: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Drain stderr alongside the progress lines so ffmpeg never blocks on it
+    let stderr = child.stderr.take();
+    let errors = std::thread::spawn(move || {
+        let mut text = String::new();
+        if let Some(mut stderr) = stderr {
+            let _ = stderr.read_to_string(&mut text);
+        }
+        text
+    });
+    let mut encoded = 0;
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if settings.cancel.is_cancelled() {
+                let _ = child.kill();
+                let _ = child.wait();
+                if !settings.codec.is_image_sequence() {
+                    let _ = std::fs::remove_file(&settings.output_path);
+                }
+                return Err(cancelled(settings, encoded, total).into());
+            }
+            if let Some(frame) = line
+                .strip_prefix("frame=")
+                .and_then(|frame| frame.trim().parse::<u32>().ok())
+            {
+                encoded = frame;
+                progress.on_frame(frame, total.max(frame));
+            }
+        }
+    }
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
+    let status = child.wait()?;
+    let stderr = errors.join().unwrap_or_default();
+    if !status.success() {
         return Err(format!("ffmpeg failed: {}", stderr).into());
     }
+    Ok(())
+}
 
-    // Get output file size
-    let metadata = std::fs::metadata(&settings.output_path)?;
-    let file_size_mb = metadata.len() as f64 / (1024.0 * 1024.0);
+#[cfg(not(target_arch = "wasm32"))]
+fn cancelled(settings: &VideoExportSettings, encoded: u32, total: u32) -> RenderCancelled {
+    let output = settings.output_path.clone();
+    RenderCancelled::new(RenderStage::Encoding, encoded, total.max(encoded), output)
+}
 
-    println!("✅ Video export complete!");
-    println!("   Output: {}", settings.output_path);
-    println!("   Size: {:.2} MB", file_size_mb);
+/// Number of existing files matching an ffmpeg input pattern such as
+/// `frames/frame_%05d.png`, or 0 if the pattern has no `%d` field
+#[cfg(not(target_arch = "wasm32"))]
+fn input_frame_count(pattern: &str) -> u32 {
+    let path = Path::new(pattern);
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let Some((prefix, field)) = name.split_once('%') else {
+        return 0;
+    };
+    let Some((_, suffix)) = field.split_once('d') else {
+        return 0;
+    };
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let file = entry.file_name();
+            let file = file.to_string_lossy();
+            file.strip_prefix(prefix)
+                .and_then(|rest| rest.strip_suffix(suffix))
+                .is_some_and(|digits| {
+                    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+                })
+        })
+        .count() as u32
+}
+
+/// Join videos with identical encoding settings into one file (no re-encode)
+///
+/// Uses ffmpeg's concat demuxer; the input list is written next to `output`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn concat_videos(
+    inputs: &[PathBuf],
+    output: impl AsRef<Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output = output.as_ref();
+    if inputs.is_empty() {
+        return Err("no videos to concatenate".into());
+    }
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
 
+    let list_path = output.with_extension("concat.txt");
+    let mut list = String::new();
+    for input in inputs {
+        let absolute = std::fs::canonicalize(input)?;
+        let _ = writeln!(list, "file '{}'", escape_filter_path(&absolute));
+    }
+    std::fs::write(&list_path, list)?;
+
+    let result = Command::new("ffmpeg")
+        .arg("-y")
+        .arg("-f")
+        .arg("concat")
+        .arg("-safe")
+        .arg("0")
+        .arg("-i")
+        .arg(&list_path)
+        .arg("-c")
+        .arg("copy")
+        .arg(output)
+        .output()
+        .map_err(|_| "ffmpeg not found. Please install ffmpeg to export videos.");
+    std::fs::remove_file(&list_path).ok();
+
+    let result = result?;
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        return Err(format!("ffmpeg concat failed: {stderr}").into());
+    }
+
+    println!(
+        "✅ Joined {} videos into {}",
+        inputs.len(),
+        output.display()
+    );
     Ok(())
 }
 
 /// Simple helper to export frames with default pattern
 ///
 /// Assumes frames are named: `frame_0000.png`, `frame_0001.png`, etc.
+#[cfg(not(target_arch = "wasm32"))]
 pub fn export_video(
     frames_dir: &str,
     output_path: &str,
@@ -146,5 +647,106 @@ mod tests {
         assert_eq!(settings.fps, 30);
         assert_eq!(settings.output_path, "test.mp4");
         assert_eq!(settings.input_pattern, "frames/frame_%04d.png");
+        assert!(settings.sound_cues.is_empty());
+        assert!(settings.captions.is_empty());
+        assert_eq!(settings.codec, VideoCodec::H264);
+    }
+
+    #[test]
+    fn test_video_codecs() {
+        let settings = VideoExportSettings::new(
+            1920,
+            1080,
+            30,
+            "comp/frame_%04d.exr".to_string(),
+            "frames/frame_%04d.png".to_string(),
+        )
+        .with_codec(VideoCodec::ExrSequence);
+        assert!(settings.codec.is_image_sequence());
+        assert_eq!(settings.codec.audio_args(), None);
+
+        let prores = VideoCodec::ProRes4444;
+        assert_eq!(prores.extension(), "mov");
+        assert!(!prores.is_image_sequence());
+        assert!(prores.ffmpeg_args().contains(&"yuva444p10le"));
+        assert!(VideoCodec::TiffSequence.ffmpeg_args().contains(&"rgba64le"));
+        assert_eq!(VideoCodec::Vp9.extension(), "webm");
+        assert!(VideoCodec::Vp9.audio_args().is_some());
+        assert_eq!(VideoCodec::Gif.extension(), "gif");
+        assert!(VideoCodec::Gif.audio_args().is_none());
+        assert!(!VideoCodec::Gif.supports_metadata());
+        assert!(VideoCodec::Vp9.supports_metadata());
+        assert_eq!(VideoCodec::from_extension("WebM"), Some(VideoCodec::Vp9));
+        assert_eq!(VideoCodec::from_extension("avi"), None);
+        assert!(VideoCodec::Gif
+            .video_filter()
+            .unwrap()
+            .contains("palettegen"));
+    }
+
+    #[test]
+    fn test_caption_path_follows_output() {
+        let settings = VideoExportSettings::new(
+            1280,
+            720,
+            30,
+            "output/lesson.mp4".to_string(),
+            "frames/frame_%04d.png".to_string(),
+        )
+        .with_captions(&[CaptionCue::new("Hi", 0.0, 1.0)], CaptionFormat::WebVtt);
+        assert_eq!(settings.caption_path(), Path::new("output/lesson.vtt"));
+        assert_eq!(settings.metadata_path(), Path::new("output/lesson.ffmeta"));
+    }
+
+    #[test]
+    fn test_input_frame_count() {
+        let dir = std::env::temp_dir().join(format!("diomanim_export_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "frame_00000.png",
+            "frame_00001.png",
+            "frame_x.png",
+            "other.png",
+        ] {
+            std::fs::write(dir.join(name), []).unwrap();
+        }
+        let pattern = dir.join("frame_%05d.png");
+        assert_eq!(input_frame_count(&pattern.to_string_lossy()), 2);
+        assert_eq!(input_frame_count("no_field.png"), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_cancelled_export_never_starts() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let settings = VideoExportSettings::new(
+            64,
+            64,
+            30,
+            "never/written.mp4".to_string(),
+            "never/frame_%05d.png".to_string(),
+        )
+        .with_cancellation(token);
+        let error = export_video_ffmpeg_with_progress(&settings, &mut NoProgress).unwrap_err();
+        let cancelled = error.downcast_ref::<RenderCancelled>().unwrap();
+        assert_eq!(cancelled.stage, RenderStage::Encoding);
+        assert_eq!(cancelled.frames_done, 0);
+        assert!(!Path::new("never").exists());
+    }
+
+    #[test]
+    fn test_sound_cue_filter() {
+        let cues = [
+            SoundCue::new("click.wav", 2.5),
+            SoundCue::new("whoosh.wav", 0.0).with_volume(0.5),
+        ];
+        let filter = sound_cue_filter(&cues);
+        assert_eq!(
+            filter,
+            "[1:a]adelay=2500:all=1,volume=1[a0];\
+             [2:a]adelay=0:all=1,volume=0.5[a1];\
+             [a0][a1]amix=inputs=2:normalize=0:dropout_transition=0,apad[aout]"
+        );
     }
 }
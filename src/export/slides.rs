@@ -0,0 +1,161 @@
+//! # HTML Slide Decks
+//!
+//! Turns a presentation (a scene split into cues, see
+//! [`Timeline::cues`](crate::core::Timeline::cues)) into a folder a browser
+//! can present from: one video per cue plus an `index.html` that plays
+//! them in turn. The deck behaves like the preview's presentation mode:
+//! Space, → or Page Down finish the playing cue or start the next one,
+//! ← or Page Up replay the previous cue, and F toggles full screen.
+//! The page is self-contained, with no scripts or styles loaded from
+//! elsewhere. [`crate::pipeline::render_slides`] renders the videos and
+//! writes the page.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::export::slides::{slide_deck_html, Slide};
+//!
+//! let html = slide_deck_html(
+//!     "Pythagoras",
+//!     &[Slide::new("intro", "00_intro.webm"), Slide::new("proof", "01_proof.webm")],
+//! );
+//! assert!(html.contains(r#"<video src="01_proof.webm""#));
+//! ```
+
+use std::fmt::Write;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+/// One cue of a slide deck
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Slide {
+    /// Title shown in the browser tab and slide counter
+    pub name: String,
+    /// Video of the cue, relative to the deck's `index.html`
+    pub video: String,
+}
+
+impl Slide {
+    pub fn new(name: impl Into<String>, video: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            video: video.into(),
+        }
+    }
+}
+
+/// Keyboard handling shared by every deck
+const DECK_SCRIPT: &str = r#"
+const slides = [...document.querySelectorAll("video")];
+const counter = document.getElementById("counter");
+let current = 0;
+
+function show(index) {
+  slides.forEach((video, i) => {
+    video.hidden = i !== index;
+    if (i !== index) video.pause();
+  });
+  current = index;
+  const video = slides[index];
+  counter.textContent = `${index + 1}/${slides.length} ${video.dataset.name}`;
+  video.currentTime = 0;
+  video.play();
+}
+
+function next() {
+  const video = slides[current];
+  if (!video.ended) {
+    video.currentTime = video.duration;
+    video.pause();
+  } else if (current + 1 < slides.length) {
+    show(current + 1);
+  }
+}
+
+document.addEventListener("keydown", (event) => {
+  if (event.key === " " || event.key === "ArrowRight" || event.key === "PageDown") {
+    event.preventDefault();
+    next();
+  } else if (event.key === "ArrowLeft" || event.key === "PageUp") {
+    event.preventDefault();
+    show(Math.max(current - 1, 0));
+  } else if (event.key === "f") {
+    if (document.fullscreenElement) document.exitFullscreen();
+    else document.documentElement.requestFullscreen();
+  }
+});
+
+show(0);
+"#;
+
+/// An HTML page presenting `slides` in order, one video at a time
+pub fn slide_deck_html(title: &str, slides: &[Slide]) -> String {
+    let mut html = String::new();
+    let _ = writeln!(html, "<!DOCTYPE html>");
+    let _ = writeln!(html, "<html>\n<head>\n<meta charset=\"utf-8\">");
+    let _ = writeln!(html, "<title>{}</title>", escape_html(title));
+    let _ = writeln!(
+        html,
+        "<style>\nhtml, body {{ margin: 0; height: 100%; background: #000; overflow: hidden; }}\n\
+         video {{ width: 100%; height: 100%; object-fit: contain; }}\n\
+         #counter {{ position: fixed; right: 1em; bottom: 0.5em; color: #888; font: 14px sans-serif; }}\n\
+         </style>"
+    );
+    let _ = writeln!(html, "</head>\n<body>");
+    for slide in slides {
+        let _ = writeln!(
+            html,
+            "<video src=\"{}\" data-name=\"{}\" preload=\"auto\" muted playsinline hidden></video>",
+            escape_html(&slide.video),
+            escape_html(&slide.name)
+        );
+    }
+    let _ = writeln!(html, "<div id=\"counter\"></div>");
+    let _ = writeln!(html, "<script>{DECK_SCRIPT}</script>");
+    let _ = writeln!(html, "</body>\n</html>");
+    html
+}
+
+/// Write [`slide_deck_html`] to `path`
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_slide_deck(
+    path: impl AsRef<Path>,
+    title: &str,
+    slides: &[Slide],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, slide_deck_html(title, slides))?;
+    Ok(())
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slide_deck_lists_videos_in_order() {
+        let html = slide_deck_html(
+            "A <b> & c",
+            &[
+                Slide::new("intro", "00_intro.webm"),
+                Slide::new("\"proof\"", "01_proof.webm"),
+            ],
+        );
+        assert!(html.contains("<title>A &lt;b&gt; &amp; c</title>"));
+        let first = html.find("00_intro.webm").unwrap();
+        let second = html.find("01_proof.webm").unwrap();
+        assert!(first < second);
+        assert!(html.contains("data-name=\"&quot;proof&quot;\""));
+        assert_eq!(html.matches("<video ").count(), 2);
+    }
+}
@@ -0,0 +1,76 @@
+//! # Browser Frame Export
+//!
+//! Renders a scene frame by frame onto a canvas and captures each frame as a
+//! PNG [`Blob`], ready to download, upload or hand to an encoder such as
+//! `WebCodecs`. Frames step with the same fixed timestep as
+//! [`crate::pipeline::render_frames`], so they match a desktop render.
+
+use crate::core::{Matrix4, Vector3};
+use crate::pipeline::{FrameClock, OfflineClock, RenderConfig};
+use crate::preview::web::CanvasSurface;
+use crate::scene::SceneGraph;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, HtmlCanvasElement};
+
+/// Render `config`'s frames of `scene` on `surface`, returning one PNG blob
+/// per frame
+///
+/// The canvas is resized to the configured resolution. Scenes are drawn
+/// through the same identity camera as offline renders.
+pub async fn render_png_blobs(
+    surface: &mut CanvasSurface,
+    scene: &mut SceneGraph,
+    config: &RenderConfig,
+) -> Result<Vec<Blob>, Box<dyn std::error::Error>> {
+    surface.resize(config.width, config.height);
+    scene.update_transforms();
+
+    let range = config.frame_range();
+    let mut clock = OfflineClock::new(config.fps, range.end);
+    let mut blobs = Vec::with_capacity(range.len());
+    while let Some(tick) = clock.tick() {
+        if tick.delta.value > 0.0 {
+            scene.update_animations(tick.delta);
+            scene.update_transforms();
+        }
+        if tick.index < range.start {
+            continue;
+        }
+        surface.draw(
+            scene,
+            &Matrix4::identity(),
+            Vector3::new(0.0, 0.0, 5.0),
+            config.background,
+        )?;
+        blobs.push(canvas_png(surface.canvas()).await?);
+    }
+    Ok(blobs)
+}
+
+/// Encode what `canvas` currently shows as a PNG
+pub async fn canvas_png(canvas: &HtmlCanvasElement) -> Result<Blob, Box<dyn std::error::Error>> {
+    let mut request = Ok(());
+    let encoded = js_sys::Promise::new(&mut |resolve, _reject| {
+        let callback = Closure::once_into_js(move |blob: JsValue| {
+            let _ = resolve.call1(&JsValue::NULL, &blob);
+        });
+        request = canvas.to_blob_with_type(callback.unchecked_ref(), "image/png");
+    });
+    request.map_err(js_error)?;
+
+    // The callback gets `null` when the canvas is empty
+    JsFuture::from(encoded)
+        .await
+        .map_err(js_error)?
+        .dyn_into::<Blob>()
+        .map_err(|_| "canvas could not be encoded as PNG".into())
+}
+
+#[allow(clippy::needless_pass_by_value)] // Called from `map_err`
+fn js_error(error: JsValue) -> Box<dyn std::error::Error> {
+    error
+        .as_string()
+        .unwrap_or_else(|| format!("{error:?}"))
+        .into()
+}
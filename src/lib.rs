@@ -42,19 +42,44 @@
 //! - [`scene`] - Scene graph hierarchy for organizing objects
 //! - [`mobjects`] - Scene objects (shapes, geometry, etc.)
 //! - [`render`] - GPU rendering pipeline using WebGPU
+//! - [`assets`] - Fonts, images and SVGs loaded once and shared by handle
+//! - [`project`] - Multiple named scenes sharing a theme and assets, rendered from the command line
+//! - `physics` - Rigid bodies simulated with rapier2d in fixed steps (`physics` feature)
+//! - `scripting` - Scenes authored as hot-reloadable Rhai scripts (`scripting` feature)
+//! - `server` - Headless render server taking scene scripts over a JSON-RPC WebSocket (`server` feature)
+//!
+//! ## WebAssembly
+//!
+//! The library builds for `wasm32-unknown-unknown` on the browser's WebGPU
+//! backend (`cargo build --lib --target wasm32-unknown-unknown`). The
+//! preview window, ffmpeg export and golden-image testing are desktop only;
+//! in the browser, `preview::web` draws scenes to a canvas and
+//! `export::web` captures its frames as PNG blobs.
 
 #![warn(clippy::all, clippy::pedantic)]
 #![allow(clippy::module_name_repetitions)]
 #![allow(clippy::must_use_candidate)]
 
 pub mod animation;
+pub mod assets;
+pub mod audio;
 pub mod core;
 pub mod export;
 pub mod math;
 pub mod mobjects;
+#[cfg(feature = "physics")]
+pub mod physics;
+pub mod pipeline;
 pub mod preview;
+pub mod project;
 pub mod render;
 pub mod scene;
+#[cfg(feature = "scripting")]
+pub mod scripting;
+#[cfg(all(feature = "server", not(target_arch = "wasm32")))]
+pub mod server;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod testing;
 pub mod text;
 
 pub mod prelude {
@@ -4,6 +4,53 @@
 
 use super::MathNode;
 
+/// LaTeX commands for symbols, and the characters they draw
+pub const SYMBOLS: &[(&str, &str)] = &[
+    // Greek letters
+    ("alpha", "α"),
+    ("beta", "β"),
+    ("gamma", "γ"),
+    ("delta", "δ"),
+    ("epsilon", "ε"),
+    ("theta", "θ"),
+    ("lambda", "λ"),
+    ("mu", "μ"),
+    ("pi", "π"),
+    ("sigma", "σ"),
+    ("phi", "φ"),
+    ("omega", "ω"),
+    // Special symbols
+    ("infty", "∞"),
+    ("sum", "Σ"),
+    ("prod", "Π"),
+    ("int", "∫"),
+    ("partial", "∂"),
+    ("nabla", "∇"),
+    ("times", "×"),
+    ("cdot", "·"),
+    ("pm", "±"),
+    ("leq", "≤"),
+    ("geq", "≥"),
+    ("neq", "≠"),
+    ("approx", "≈"),
+];
+
+/// The character drawn for `\command`
+pub fn symbol_for_command(command: &str) -> Option<&'static str> {
+    SYMBOLS
+        .iter()
+        .find(|(name, _)| *name == command)
+        .map(|(_, symbol)| *symbol)
+}
+
+/// The command drawing `symbol`, without its backslash
+pub fn command_for_symbol(symbol: &str) -> Option<&'static str> {
+    SYMBOLS
+        .iter()
+        .find(|(_, drawn)| *drawn == symbol)
+        .map(|(name, _)| *name)
+}
+
 /// Parse LaTeX math notation into a MathNode tree
 pub struct MathParser {
     input: Vec<char>,
@@ -31,9 +78,7 @@ impl MathParser {
         let mut children = Vec::new();
 
         while !self.is_eof() {
-            if let Some(node) = self.parse_node() {
-                children.push(node);
-            }
+            self.parse_into(&mut children);
         }
 
         if children.is_empty() {
@@ -45,6 +90,58 @@ impl MathParser {
         }
     }
 
+    /// Parse the next node into `children`, attaching a `^` or `_` script
+    /// to the node before it
+    fn parse_into(&mut self, children: &mut Vec<MathNode>) {
+        self.skip_whitespace();
+        if self.is_eof() {
+            return;
+        }
+
+        let ch = self.current();
+        if ch != '^' && ch != '_' {
+            children.extend(self.parse_node());
+            return;
+        }
+        self.advance();
+        let base = Box::new(
+            children
+                .pop()
+                .unwrap_or_else(|| MathNode::Text(String::new())),
+        );
+        let script = Box::new(
+            self.parse_script()
+                .unwrap_or_else(|| MathNode::Text(String::new())),
+        );
+        children.push(if ch == '^' {
+            MathNode::Superscript {
+                base,
+                exponent: script,
+            }
+        } else {
+            MathNode::Subscript {
+                base,
+                index: script,
+            }
+        });
+    }
+
+    /// Parse a script: a braced group, a command or a single character
+    fn parse_script(&mut self) -> Option<MathNode> {
+        self.skip_whitespace();
+        if self.is_eof() {
+            return None;
+        }
+        match self.current() {
+            '{' => self.parse_braced_group(),
+            '\\' => self.parse_command(),
+            ch => {
+                self.advance();
+                Some(MathNode::Text(ch.to_string()))
+            }
+        }
+    }
+
     /// Parse a single node
     fn parse_node(&mut self) -> Option<MathNode> {
         self.skip_whitespace();
@@ -57,8 +154,6 @@ impl MathParser {
 
         match ch {
             '\\' => self.parse_command(),
-            '^' => self.parse_superscript(),
-            '_' => self.parse_subscript(),
             '{' => self.parse_group(),
             '+' | '-' | '=' | '<' | '>' | '*' | '/' => {
                 let op = ch.to_string();
@@ -73,39 +168,26 @@ impl MathParser {
     fn parse_command(&mut self) -> Option<MathNode> {
         self.advance(); // skip '\'
 
+        // Escaped special characters draw as themselves
+        if !self.is_eof() && "{}%#&_^$".contains(self.current()) {
+            let escaped = self.current();
+            self.advance();
+            if escaped == '^' && self.input[self.pos..].starts_with(&['{', '}']) {
+                self.pos += 2;
+            }
+            return Some(MathNode::Text(escaped.to_string()));
+        }
+
         let cmd = self.read_identifier();
 
         match cmd.as_str() {
             "frac" => self.parse_fraction(),
             "sqrt" => self.parse_sqrt(),
-            // Greek letters
-            "alpha" => Some(MathNode::Symbol("α".to_string())),
-            "beta" => Some(MathNode::Symbol("β".to_string())),
-            "gamma" => Some(MathNode::Symbol("γ".to_string())),
-            "delta" => Some(MathNode::Symbol("δ".to_string())),
-            "epsilon" => Some(MathNode::Symbol("ε".to_string())),
-            "theta" => Some(MathNode::Symbol("θ".to_string())),
-            "lambda" => Some(MathNode::Symbol("λ".to_string())),
-            "mu" => Some(MathNode::Symbol("μ".to_string())),
-            "pi" => Some(MathNode::Symbol("π".to_string())),
-            "sigma" => Some(MathNode::Symbol("σ".to_string())),
-            "phi" => Some(MathNode::Symbol("φ".to_string())),
-            "omega" => Some(MathNode::Symbol("ω".to_string())),
-            // Special symbols
-            "infty" => Some(MathNode::Symbol("∞".to_string())),
-            "sum" => Some(MathNode::Symbol("Σ".to_string())),
-            "prod" => Some(MathNode::Symbol("Π".to_string())),
-            "int" => Some(MathNode::Symbol("∫".to_string())),
-            "partial" => Some(MathNode::Symbol("∂".to_string())),
-            "nabla" => Some(MathNode::Symbol("∇".to_string())),
-            "times" => Some(MathNode::Symbol("×".to_string())),
-            "cdot" => Some(MathNode::Symbol("·".to_string())),
-            "pm" => Some(MathNode::Symbol("±".to_string())),
-            "leq" => Some(MathNode::Symbol("≤".to_string())),
-            "geq" => Some(MathNode::Symbol("≥".to_string())),
-            "neq" => Some(MathNode::Symbol("≠".to_string())),
-            "approx" => Some(MathNode::Symbol("≈".to_string())),
-            _ => Some(MathNode::Text(format!("\\{}", cmd))),
+            "backslash" => Some(MathNode::Text("\\".to_string())),
+            _ => Some(match symbol_for_command(&cmd) {
+                Some(symbol) => MathNode::Symbol(symbol.to_string()),
+                None => MathNode::Text(format!("\\{}", cmd)),
+            }),
         }
     }
 
@@ -129,23 +211,6 @@ impl MathParser {
         })
     }
 
-    /// Parse ^{exponent} or ^x
-    fn parse_superscript(&mut self) -> Option<MathNode> {
-        self.advance(); // skip '^'
-
-        // Get the base from the previous node (not implemented yet)
-        // For now, we'll skip this and handle it in a second pass
-        None
-    }
-
-    /// Parse _{index} or _x
-    fn parse_subscript(&mut self) -> Option<MathNode> {
-        self.advance(); // skip '_'
-
-        // Get the base from the previous node (not implemented yet)
-        None
-    }
-
     /// Parse a group enclosed in braces: {content}
     fn parse_group(&mut self) -> Option<MathNode> {
         self.parse_braced_group()
@@ -161,6 +226,10 @@ impl MathParser {
         let mut depth = 1;
 
         while !self.is_eof() && depth > 0 {
+            self.skip_whitespace();
+            if self.is_eof() {
+                break;
+            }
             let ch = self.current();
 
             if ch == '{' {
@@ -173,8 +242,8 @@ impl MathParser {
                 } else {
                     break;
                 }
-            } else if let Some(node) = self.parse_node() {
-                children.push(node);
+            } else {
+                self.parse_into(&mut children);
             }
         }
 
@@ -189,7 +258,8 @@ impl MathParser {
         }
     }
 
-    /// Parse plain text (alphanumeric)
+    /// Parse plain text (alphanumeric), or any other single character such
+    /// as a bracket
     fn parse_text(&mut self) -> Option<MathNode> {
         let mut text = String::new();
 
@@ -205,7 +275,9 @@ impl MathParser {
         }
 
         if text.is_empty() {
-            None
+            let ch = self.current();
+            self.advance();
+            Some(MathNode::Text(ch.to_string()))
         } else {
             Some(MathNode::Text(text))
         }
@@ -294,6 +366,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_scripts() {
+        let node = parse_latex("x_i^{2} + (y)");
+        let MathNode::Group { children } = node else {
+            panic!("Expected group");
+        };
+        assert_eq!(children[0].to_text(), "x_i^2");
+        assert!(matches!(children[0], MathNode::Superscript { .. }));
+        assert_eq!(children.len(), 5);
+        assert_eq!(parse_latex("e^\\pi").to_text(), "e^π");
+        assert_eq!(parse_latex("\\frac{ a_1 }{2}").to_text(), "(a_1) / (2)");
+    }
+
     #[test]
     fn test_parse_sqrt() {
         let node = parse_latex("\\sqrt{x}");
@@ -0,0 +1,440 @@
+//! MathML input
+//!
+//! Presentation MathML, as written by content pipelines and accessibility
+//! tools, parsed into the same [`MathNode`] tree as LaTeX. Tokens (`<mi>`,
+//! `<mn>`, `<mo>`, `<mtext>`), rows, fractions, roots and scripts are read;
+//! under- and overscripts are laid out as sub- and superscripts, and
+//! `<semantics>` annotations, spacing and phantoms are skipped.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::core::Color;
+//! use diomanim::math::MathExpression;
+//!
+//! let expr = MathExpression::from_mathml(
+//!     "<math><msup><mi>x</mi><mn>2</mn></msup><mo>+</mo><mn>1</mn></math>",
+//!     48.0,
+//!     Color::WHITE,
+//! )
+//! .unwrap();
+//! assert_eq!(expr.latex, "{x}^{2} + 1");
+//! ```
+
+use super::MathNode;
+
+/// Named entities MathML commonly uses besides XML's own
+const ENTITIES: &[(&str, &str)] = &[
+    ("InvisibleTimes", "\u{2062}"),
+    ("it", "\u{2062}"),
+    ("ApplyFunction", "\u{2061}"),
+    ("af", "\u{2061}"),
+    ("InvisibleComma", "\u{2063}"),
+    ("ic", "\u{2063}"),
+    ("minus", "−"),
+    ("times", "×"),
+    ("middot", "·"),
+    ("PlusMinus", "±"),
+    ("pm", "±"),
+    ("le", "≤"),
+    ("ge", "≥"),
+    ("ne", "≠"),
+    ("approx", "≈"),
+    ("infin", "∞"),
+    ("sum", "∑"),
+    ("prod", "∏"),
+    ("int", "∫"),
+    ("part", "∂"),
+    ("nabla", "∇"),
+    ("alpha", "α"),
+    ("beta", "β"),
+    ("gamma", "γ"),
+    ("delta", "δ"),
+    ("epsilon", "ε"),
+    ("theta", "θ"),
+    ("lambda", "λ"),
+    ("mu", "μ"),
+    ("pi", "π"),
+    ("sigma", "σ"),
+    ("phi", "φ"),
+    ("omega", "ω"),
+];
+
+/// An element of the document and what it contains
+#[derive(Debug)]
+struct Element {
+    name: String,
+    /// Raw attribute text of the start tag
+    attributes: String,
+    children: Vec<Content>,
+}
+
+#[derive(Debug)]
+enum Content {
+    Element(Element),
+    Text(String),
+}
+
+impl Element {
+    fn new(name: &str, attributes: &str) -> Self {
+        Self {
+            // Namespace prefixes (`m:mi`) are dropped
+            name: name.rsplit(':').next().unwrap_or(name).to_string(),
+            attributes: attributes.to_string(),
+            children: Vec::new(),
+        }
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|child| match child {
+            Content::Element(element) => Some(element),
+            Content::Text(_) => None,
+        })
+    }
+
+    /// Text of the element and its descendants, trimmed
+    fn text(&self) -> String {
+        let mut text = String::new();
+        for child in &self.children {
+            match child {
+                Content::Element(element) => text.push_str(&element.text()),
+                Content::Text(content) => text.push_str(content),
+            }
+        }
+        text.trim().to_string()
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        let mut rest = self.attributes.as_str();
+        loop {
+            let equals = rest.find('=')?;
+            let key = rest[..equals].trim();
+            let value = rest[equals + 1..].trim_start();
+            let quote = value.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+            let end = value[1..].find(quote)?;
+            if key == name {
+                return Some(&value[1..=end]);
+            }
+            rest = &value[end + 2..];
+        }
+    }
+}
+
+/// Parse a MathML document (a `<math>` element, or any presentation
+/// element) into a MathNode tree
+pub fn parse_mathml(source: &str) -> Result<MathNode, String> {
+    let root = parse_xml(source)?;
+    Ok(convert(&root)?.unwrap_or_else(|| MathNode::Text(String::new())))
+}
+
+/// The node for `element`, or `None` for elements that draw nothing
+fn convert(element: &Element) -> Result<Option<MathNode>, String> {
+    Ok(Some(match element.name.as_str() {
+        "mi" => {
+            let text = element.text();
+            let mut chars = text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if !c.is_ascii() => MathNode::Symbol(text),
+                _ => MathNode::Text(text),
+            }
+        }
+        "mn" | "mtext" | "ms" => MathNode::Text(element.text()),
+        "mo" => return Ok(operator(&element.text())),
+        "mfrac" => {
+            let [numerator, denominator] = arguments(element)?;
+            MathNode::Fraction {
+                numerator: Box::new(numerator),
+                denominator: Box::new(denominator),
+            }
+        }
+        "msqrt" => MathNode::SquareRoot {
+            content: Box::new(row(element)?),
+        },
+        "mroot" => {
+            // The index is drawn as a small script before the radical
+            let [content, index] = arguments(element)?;
+            MathNode::Group {
+                children: vec![
+                    MathNode::Superscript {
+                        base: Box::new(MathNode::Text(String::new())),
+                        exponent: Box::new(index),
+                    },
+                    MathNode::SquareRoot {
+                        content: Box::new(content),
+                    },
+                ],
+            }
+        }
+        "msup" | "mover" => {
+            let [base, exponent] = arguments(element)?;
+            MathNode::Superscript {
+                base: Box::new(base),
+                exponent: Box::new(exponent),
+            }
+        }
+        "msub" | "munder" => {
+            let [base, index] = arguments(element)?;
+            MathNode::Subscript {
+                base: Box::new(base),
+                index: Box::new(index),
+            }
+        }
+        "msubsup" | "munderover" => {
+            let [base, index, exponent] = arguments(element)?;
+            MathNode::Superscript {
+                base: Box::new(MathNode::Subscript {
+                    base: Box::new(base),
+                    index: Box::new(index),
+                }),
+                exponent: Box::new(exponent),
+            }
+        }
+        "mfenced" => {
+            let open = element.attribute("open").unwrap_or("(");
+            let close = element.attribute("close").unwrap_or(")");
+            let separator = element.attribute("separators").unwrap_or(",").trim();
+            let mut children = vec![MathNode::Text(open.to_string())];
+            for (i, child) in element.elements().enumerate() {
+                if i > 0 && !separator.is_empty() {
+                    children.push(MathNode::Text(separator.to_string()));
+                }
+                children.extend(convert(child)?);
+            }
+            children.push(MathNode::Text(close.to_string()));
+            MathNode::Group { children }
+        }
+        "semantics" => {
+            return match element.elements().next() {
+                Some(presentation) => convert(presentation),
+                None => Ok(None),
+            }
+        }
+        "annotation" | "annotation-xml" | "mspace" | "mphantom" | "none" | "mprescripts"
+        | "maligngroup" | "malignmark" => return Ok(None),
+        "mtable" | "mtr" | "mlabeledtr" => {
+            return Err(format!("<{}> is not supported", element.name))
+        }
+        // `math`, `mrow`, `mstyle`, `mpadded`, `menclose` and the like
+        _ => row(element)?,
+    }))
+}
+
+/// An operator node, or `None` for invisible operators
+fn operator(text: &str) -> Option<MathNode> {
+    Some(match text {
+        "" | "\u{2061}" | "\u{2062}" | "\u{2063}" | "\u{2064}" => return None,
+        "+" | "-" | "=" | "<" | ">" | "*" | "/" => MathNode::Operator(text.to_string()),
+        "−" => MathNode::Operator("-".to_string()),
+        "∑" => MathNode::Symbol("Σ".to_string()),
+        "∏" => MathNode::Symbol("Π".to_string()),
+        _ if text.chars().all(|c| c.is_ascii_punctuation()) => MathNode::Text(text.to_string()),
+        _ => MathNode::Symbol(text.to_string()),
+    })
+}
+
+/// The children of `element` as one node
+fn row(element: &Element) -> Result<MathNode, String> {
+    let mut children = Vec::new();
+    for child in element.elements() {
+        children.extend(convert(child)?);
+    }
+    Ok(match children.len() {
+        0 => MathNode::Text(String::new()),
+        1 => children.remove(0),
+        _ => MathNode::Group { children },
+    })
+}
+
+/// Exactly `N` child nodes of `element`
+fn arguments<const N: usize>(element: &Element) -> Result<[MathNode; N], String> {
+    let children = element
+        .elements()
+        .map(|child| Ok(convert(child)?.unwrap_or_else(|| MathNode::Text(String::new()))))
+        .collect::<Result<Vec<_>, String>>()?;
+    let found = children.len();
+    children
+        .try_into()
+        .map_err(|_| format!("<{}> needs {N} children, found {found}", element.name))
+}
+
+/// The first element of an XML document
+fn parse_xml(source: &str) -> Result<Element, String> {
+    // Open elements, innermost last, under a nameless document element
+    let mut stack = vec![Element::new("", "")];
+    let mut rest = source;
+    loop {
+        let text_end = rest.find('<').unwrap_or(rest.len());
+        let text = &rest[..text_end];
+        if !text.trim().is_empty() {
+            let text = decode_entities(text)?;
+            if let Some(open) = stack.last_mut() {
+                open.children.push(Content::Text(text));
+            }
+        }
+        if text_end == rest.len() {
+            break;
+        }
+        rest = &rest[text_end + 1..];
+
+        if let Some(comment) = rest.strip_prefix("!--") {
+            let end = comment.find("-->").ok_or("unterminated comment")?;
+            rest = &comment[end + 3..];
+            continue;
+        }
+        if let Some(data) = rest.strip_prefix("![CDATA[") {
+            let end = data.find("]]>").ok_or("unterminated CDATA section")?;
+            if let Some(open) = stack.last_mut() {
+                open.children.push(Content::Text(data[..end].to_string()));
+            }
+            rest = &data[end + 3..];
+            continue;
+        }
+        let close = rest.find('>').ok_or("unterminated tag")?;
+        let tag = &rest[..close];
+        rest = &rest[close + 1..];
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim();
+            let element = match stack.pop() {
+                Some(element) if !stack.is_empty() => element,
+                _ => return Err(format!("unexpected </{name}>")),
+            };
+            if element.name != name.rsplit(':').next().unwrap_or(name) {
+                return Err(format!("<{}> closed by </{name}>", element.name));
+            }
+            if let Some(parent) = stack.last_mut() {
+                parent.children.push(Content::Element(element));
+            }
+            continue;
+        }
+
+        let self_closing = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let name_end = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+        let element = Element::new(&tag[..name_end], &tag[name_end..]);
+        if self_closing {
+            if let Some(parent) = stack.last_mut() {
+                parent.children.push(Content::Element(element));
+            }
+        } else {
+            stack.push(element);
+        }
+    }
+
+    if stack.len() > 1 {
+        let open = stack.last().map_or("", |element| element.name.as_str());
+        return Err(format!("unclosed <{open}>"));
+    }
+    stack
+        .pop()
+        .and_then(|document| {
+            document.children.into_iter().find_map(|child| match child {
+                Content::Element(element) => Some(element),
+                Content::Text(_) => None,
+            })
+        })
+        .ok_or_else(|| "no MathML element".to_string())
+}
+
+/// Replace character and entity references in `text`
+fn decode_entities(text: &str) -> Result<String, String> {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(';')
+            .ok_or_else(|| format!("unterminated entity in `{text}`"))?;
+        let entity = &rest[start + 1..start + end];
+        let character = if let Some(hex) = entity
+            .strip_prefix("#x")
+            .or_else(|| entity.strip_prefix("#X"))
+        {
+            u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+        } else if let Some(decimal) = entity.strip_prefix('#') {
+            decimal.parse().ok().and_then(char::from_u32)
+        } else {
+            None
+        };
+        match (character, entity) {
+            (Some(c), _) => decoded.push(c),
+            (None, "lt") => decoded.push('<'),
+            (None, "gt") => decoded.push('>'),
+            (None, "amp") => decoded.push('&'),
+            (None, "quot") => decoded.push('"'),
+            (None, "apos") => decoded.push('\''),
+            (None, name) => decoded.push_str(
+                ENTITIES
+                    .iter()
+                    .find(|(known, _)| *known == name)
+                    .map(|(_, value)| *value)
+                    .ok_or_else(|| format!("unknown entity `&{name};`"))?,
+            ),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    decoded.push_str(rest);
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::expression::parse_latex;
+
+    #[test]
+    fn test_parse_mathml_matches_latex() {
+        let mathml = r#"<math xmlns="http://www.w3.org/1998/Math/MathML" display="block">
+              <semantics>
+                <mrow>
+                  <mfrac>
+                    <mrow><mo>&#x2212;</mo><mi>b</mi><mo>&PlusMinus;</mo>
+                      <msqrt><msup><mi>b</mi><mn>2</mn></msup><mo>-</mo><mn>4</mn>
+                        <mo>&InvisibleTimes;</mo><mi>a</mi><mi>c</mi></msqrt></mrow>
+                    <mrow><mn>2</mn><mi>a</mi></mrow>
+                  </mfrac>
+                  <mo>&lt;</mo>
+                  <msubsup><mi>x</mi><mi>i</mi><mi>&#x3c0;</mi></msubsup>
+                </mrow>
+                <annotation encoding="application/x-tex">ignored</annotation>
+              </semantics>
+            </math>"#;
+        let node = parse_mathml(mathml).unwrap();
+        assert_eq!(
+            node.to_text(),
+            parse_latex(r"\frac{-b \pm \sqrt{b^2 - 4 a c}}{2 a} < x_i^\pi").to_text()
+        );
+        // The LaTeX it converts to parses back into the same tree
+        assert_eq!(parse_latex(&node.to_latex()).to_text(), node.to_text());
+
+        // Characters special to LaTeX are escaped
+        let node =
+            parse_mathml("<math><mo>{</mo><mi>x</mi><mo>}</mo><mi>%^\\</mi></math>").unwrap();
+        let latex = node.to_latex();
+        assert_eq!(latex, r"\{ x \} \%\^{}\backslash ");
+        assert_eq!(parse_latex(&latex).to_text(), "({ x } % ^ \\)");
+    }
+
+    #[test]
+    fn test_parse_mathml_errors() {
+        assert!(parse_mathml("<math><mi>x</math>").is_err());
+        assert!(parse_mathml("<math><mfrac><mn>1</mn></mfrac></math>").is_err());
+        assert!(parse_mathml("<math><mi>&bogus;</mi></math>").is_err());
+        assert_eq!(
+            parse_mathml("<m:math><m:mfenced><m:mi>a</m:mi><m:mi>b</m:mi></m:mfenced></m:math>")
+                .unwrap()
+                .to_text(),
+            "(( a , b ))"
+        );
+        // Only quoted attribute values count
+        assert_eq!(
+            parse_mathml("<math><mfenced open=é><mi>a</mi></mfenced></math>")
+                .unwrap()
+                .to_text(),
+            "(( a ))"
+        );
+    }
+}
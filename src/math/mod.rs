@@ -2,12 +2,21 @@
 //!
 //! Provides support for rendering mathematical expressions with LaTeX syntax.
 //! Uses our text rendering system with a custom layout engine for math-specific formatting.
+//! LaTeX beyond the built-in parser can be typeset by TeX itself with
+//! [`TexCompiler`] (desktop only). Expressions can also be written in
+//! MathML, as produced by content pipelines and accessibility tools.
 
 pub mod expression;
 pub mod layout;
+pub mod mathml;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tex;
 
 pub use expression::*;
 pub use layout::*;
+pub use mathml::parse_mathml;
+#[cfg(not(target_arch = "wasm32"))]
+pub use tex::TexCompiler;
 
 use crate::core::Color;
 use std::fmt;
@@ -69,6 +78,18 @@ impl MathExpression {
         }
     }
 
+    /// Create a math expression from MathML, keeping equivalent LaTeX in
+    /// [`Self::latex`] for the renderers
+    pub fn from_mathml(mathml: &str, font_size: f32, color: Color) -> Result<Self, String> {
+        let root = mathml::parse_mathml(mathml)?;
+        Ok(MathExpression {
+            latex: root.to_latex(),
+            root,
+            font_size,
+            color,
+        })
+    }
+
     /// Get the width of this expression in pixels
     pub fn width(&self) -> f32 {
         // Placeholder: estimate based on text length
@@ -106,6 +127,53 @@ impl MathNode {
             MathNode::Symbol(sym) => sym.clone(),
         }
     }
+
+    /// LaTeX source for this tree
+    pub fn to_latex(&self) -> String {
+        match self {
+            MathNode::Text(s) | MathNode::Operator(s) => escape_latex(s),
+            MathNode::Fraction {
+                numerator,
+                denominator,
+            } => format!(
+                "\\frac{{{}}}{{{}}}",
+                numerator.to_latex(),
+                denominator.to_latex()
+            ),
+            MathNode::Superscript { base, exponent } => {
+                format!("{{{}}}^{{{}}}", base.to_latex(), exponent.to_latex())
+            }
+            MathNode::Subscript { base, index } => {
+                format!("{{{}}}_{{{}}}", base.to_latex(), index.to_latex())
+            }
+            MathNode::SquareRoot { content } => format!("\\sqrt{{{}}}", content.to_latex()),
+            MathNode::Group { children } => {
+                let parts: Vec<_> = children.iter().map(|c| c.to_latex()).collect();
+                parts.join(" ")
+            }
+            MathNode::Symbol(sym) => match expression::command_for_symbol(sym) {
+                Some(command) => format!("\\{command}"),
+                None => sym.clone(),
+            },
+        }
+    }
+}
+
+/// `text` with the characters LaTeX treats specially escaped
+fn escape_latex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '{' | '}' | '%' | '#' | '&' | '_' | '$' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '^' => escaped.push_str(r"\^{}"),
+            '\\' => escaped.push_str(r"\backslash "),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
 }
 
 impl fmt::Display for MathExpression {
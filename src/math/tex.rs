@@ -0,0 +1,291 @@
+//! LaTeX compiled by TeX
+//!
+//! The native parser covers everyday formulas and is what
+//! [`Renderable::Math`] draws. For everything beyond it (matrices, `align`,
+//! `\mathbb`, packages), a [`TexCompiler`] runs the `tectonic` engine on the
+//! expression and converts the result with `dvisvgm --no-fonts`, which
+//! writes every glyph as an outline. The SVG goes through the usual
+//! [`AssetServer`] pipeline, so the equation is drawn as filled vector
+//! shapes that match TeX's own typesetting exactly.
+//!
+//! Compiling takes a moment, so SVGs are cached on disk by their document;
+//! [`SceneGraph::add_tex`] falls back to the native renderer when the tools
+//! are missing or the LaTeX doesn't compile.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use diomanim::assets::AssetServer;
+//! use diomanim::core::Color;
+//! use diomanim::math::TexCompiler;
+//! use diomanim::scene::SceneGraph;
+//!
+//! let assets = AssetServer::new();
+//! let tex = TexCompiler::new().with_preamble(r"\usepackage{amsmath,amssymb}");
+//! let mut scene = SceneGraph::new();
+//! scene
+//!     .add_tex(
+//!         "identity",
+//!         r"\begin{pmatrix} 1 & 0 \\ 0 & 1 \end{pmatrix} \in \mathbb{R}^{2 \times 2}",
+//!         48.0,
+//!         Color::WHITE,
+//!         &tex,
+//!         &assets,
+//!     )
+//!     .at(0.0, 0.3, 0.0)
+//!     .build();
+//! ```
+
+use crate::assets::{AssetHandle, AssetServer};
+use crate::core::Color;
+use crate::pipeline::FrameHasher;
+use crate::scene::{NodeBuilder, Renderable, SceneGraph};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Size TeX sets the expression at, in points; scene sizes scale from it
+const TEX_POINT_SIZE: f32 = 10.0;
+
+/// Runs `tectonic` and `dvisvgm` to turn LaTeX into glyph outlines
+#[derive(Debug, Clone, PartialEq)]
+pub struct TexCompiler {
+    /// The `tectonic` executable
+    pub tectonic: PathBuf,
+    /// The `dvisvgm` executable
+    pub dvisvgm: PathBuf,
+    /// Lines added before `\begin{document}`, e.g. `\usepackage`s
+    pub preamble: String,
+    /// Where compiled SVGs are kept between runs
+    pub cache_dir: PathBuf,
+}
+
+impl Default for TexCompiler {
+    fn default() -> Self {
+        Self {
+            tectonic: PathBuf::from("tectonic"),
+            dvisvgm: PathBuf::from("dvisvgm"),
+            preamble: r"\usepackage{amsmath}".to_string(),
+            cache_dir: std::env::temp_dir().join("diomanim-tex"),
+        }
+    }
+}
+
+impl TexCompiler {
+    /// Tools found on `PATH`, with `amsmath` loaded
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_preamble(mut self, preamble: impl Into<String>) -> Self {
+        self.preamble = preamble.into();
+        self
+    }
+
+    pub fn with_cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = cache_dir.into();
+        self
+    }
+
+    pub fn with_programs(
+        mut self,
+        tectonic: impl Into<PathBuf>,
+        dvisvgm: impl Into<PathBuf>,
+    ) -> Self {
+        self.tectonic = tectonic.into();
+        self.dvisvgm = dvisvgm.into();
+        self
+    }
+
+    /// Whether both tools can be run
+    pub fn is_available(&self) -> bool {
+        let runs = |program: &Path| {
+            Command::new(program)
+                .arg("--version")
+                .output()
+                .is_ok_and(|output| output.status.success())
+        };
+        runs(&self.tectonic) && runs(&self.dvisvgm)
+    }
+
+    /// The standalone document typesetting `latex` in display math
+    ///
+    /// Glyphs are set in white so the renderable's color tints them, as
+    /// with the native renderer; `\color` in the expression still applies.
+    pub fn document(&self, latex: &str) -> String {
+        format!(
+            "\\documentclass[preview,{TEX_POINT_SIZE}pt]{{standalone}}\n\
+             \\usepackage{{xcolor}}\n\
+             {}\n\
+             \\begin{{document}}\n\
+             \\color{{white}}$\\displaystyle {latex}$\n\
+             \\end{{document}}\n",
+            self.preamble
+        )
+    }
+
+    /// The SVG of `latex`, compiled or read back from the cache
+    pub fn compile(&self, latex: &str) -> Result<String, String> {
+        let document = self.document(latex);
+        let svg_path = self.cache_path(&document);
+        if let Ok(svg) = std::fs::read_to_string(&svg_path) {
+            return Ok(svg);
+        }
+
+        let stem = svg_path.with_extension("");
+        let tex_path = stem.with_extension("tex");
+        let xdv_path = stem.with_extension("xdv");
+        std::fs::create_dir_all(&self.cache_dir)
+            .map_err(|e| format!("Failed to create {}: {e}", self.cache_dir.display()))?;
+        std::fs::write(&tex_path, &document)
+            .map_err(|e| format!("Failed to write {}: {e}", tex_path.display()))?;
+
+        let mut tectonic = Command::new(&self.tectonic);
+        tectonic
+            .args(["--outfmt", "xdv", "--chatter", "minimal", "--outdir"])
+            .arg(&self.cache_dir)
+            .arg(&tex_path);
+        run(tectonic, "tectonic")?;
+
+        let mut dvisvgm = Command::new(&self.dvisvgm);
+        dvisvgm
+            .args(["--no-fonts", "--exact-bbox", "--verbosity=1", "--output"])
+            .arg(&svg_path)
+            .arg(&xdv_path);
+        run(dvisvgm, "dvisvgm")?;
+
+        for intermediate in [&tex_path, &xdv_path] {
+            let _ = std::fs::remove_file(intermediate);
+        }
+        std::fs::read_to_string(&svg_path)
+            .map_err(|e| format!("dvisvgm wrote no SVG for `{latex}`: {e}"))
+    }
+
+    /// Compile `latex` and load it as a vector asset, returning its handle
+    /// and its size in points
+    pub fn load(
+        &self,
+        latex: &str,
+        assets: &AssetServer,
+    ) -> Result<(AssetHandle, [f32; 2]), String> {
+        let document = self.document(latex);
+        let name = self.cache_path(&document).to_string_lossy().into_owned();
+        let handle = assets
+            .load_bytes(&name, self.compile(latex)?.into_bytes())
+            .map_err(|e| format!("Failed to import TeX output for `{latex}`: {e}"))?;
+        let vector = assets
+            .vector(handle)
+            .ok_or_else(|| format!("TeX output for `{latex}` is not an SVG"))?;
+        Ok((handle, [vector.view_box[2], vector.view_box[3]]))
+    }
+
+    /// Where the SVG of `document` is cached
+    fn cache_path(&self, document: &str) -> PathBuf {
+        let mut hasher = FrameHasher::new();
+        hasher.write_str(&self.tectonic.to_string_lossy());
+        hasher.write_str(&self.dvisvgm.to_string_lossy());
+        hasher.write_str(document);
+        self.cache_dir.join(format!("{:016x}.svg", hasher.finish()))
+    }
+}
+
+/// Run `command`, turning a failure into its error output
+fn run(mut command: Command, program: &str) -> Result<(), String> {
+    let output = command
+        .output()
+        .map_err(|e| format!("{program} could not be run: {e}"))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let message = if stderr.trim().is_empty() {
+        stdout
+    } else {
+        stderr
+    };
+    Err(format!("{program} failed: {}", message.trim()))
+}
+
+impl SceneGraph {
+    /// Add `latex` typeset by TeX with `compiler`, sized like a native
+    /// [`Renderable::Math`] of the same `font_size`
+    ///
+    /// Falls back to the native renderer, with a warning, if compiling
+    /// fails.
+    pub fn add_tex(
+        &mut self,
+        name: impl Into<String>,
+        latex: impl Into<String>,
+        font_size: f32,
+        color: Color,
+        compiler: &TexCompiler,
+        assets: &AssetServer,
+    ) -> NodeBuilder<'_> {
+        let latex = latex.into();
+        let node_id = self.create_node(name.into());
+        let renderable = match compiler.load(&latex, assets) {
+            Ok((asset, [width, height])) => {
+                // A font size's em is `font_size / 1000` scene units
+                let scale = font_size / 1000.0 / TEX_POINT_SIZE;
+                Renderable::Svg {
+                    asset,
+                    width: width * scale,
+                    height: height * scale,
+                    color,
+                }
+            }
+            Err(e) => {
+                eprintln!("Falling back to native math: {e}");
+                Renderable::Math {
+                    latex,
+                    font_size,
+                    color,
+                }
+            }
+        };
+        if let Some(node) = self.get_node_mut(node_id) {
+            node.set_renderable(renderable);
+        }
+        NodeBuilder::new(self, node_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tex_document_and_fallback() {
+        let compiler = TexCompiler::new()
+            .with_preamble(r"\usepackage{amssymb}")
+            .with_programs("diomanim-missing-tectonic", "diomanim-missing-dvisvgm")
+            .with_cache_dir(std::env::temp_dir().join("diomanim-tex-test"));
+        let document = compiler.document(r"\mathbb{R}^n");
+        assert!(document.contains(r"\usepackage{amssymb}"));
+        assert!(document.contains(r"$\displaystyle \mathbb{R}^n$"));
+        assert_ne!(
+            compiler.cache_path(&document),
+            compiler.cache_path(&compiler.document("x"))
+        );
+        assert!(!compiler.is_available());
+
+        // Without the tools the native renderer draws it
+        let mut scene = SceneGraph::new();
+        let assets = AssetServer::new();
+        let id = scene
+            .add_tex(
+                "space",
+                r"\mathbb{R}^n",
+                48.0,
+                Color::WHITE,
+                &compiler,
+                &assets,
+            )
+            .build();
+        let renderable = scene.get_node(id).unwrap().renderable.as_ref().unwrap();
+        assert_eq!(
+            renderable.as_math().map(|(latex, _, _)| latex.as_str()),
+            Some(r"\mathbb{R}^n")
+        );
+    }
+}
@@ -0,0 +1,373 @@
+//! Dissolve into dots
+//!
+//! A [`Dissolve`] turns a shape into a cloud of small dots sampled from its
+//! geometry (see [`ShapeSampling`]) and animates them: [`Dissolve::scatter`]
+//! blows a shape away as dust, [`Dissolve::assemble`] gathers dust into a
+//! shape, and [`Dissolve::morph`] does both, breaking one shape up and
+//! settling the dust into another.
+//!
+//! The dots are plain circle nodes that share one radius, color and opacity
+//! animation, so the renderer draws the whole cloud as a single instanced
+//! batch however many dots it has. The shapes themselves cross-fade with
+//! the dots at the ends of the effect.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::core::*;
+//! use diomanim::mobjects::Dissolve;
+//! use diomanim::scene::SceneGraph;
+//!
+//! let mut scene = SceneGraph::new();
+//! let square = scene.add_rectangle("square", 0.6, 0.6, Color::BLUE).build();
+//! let circle = scene.add_circle("circle", 0.3, Color::RED).at(0.5, 0.0, 0.0).build();
+//!
+//! let dust = Dissolve::new(400).with_spread(0.4).morph(&mut scene, "dust", square, circle, 1.0, 2.0);
+//! assert_eq!(dust.dots.len(), 400);
+//! ```
+
+use crate::animation::effects;
+use crate::animation::procedural::unit_hash;
+use crate::animation::property::{
+    AnimationClip, AnimationInstance, AnimationTrack, InterpolationType, Keyframe,
+};
+use crate::core::{Color, TimeValue, Vector3};
+use crate::scene::{NodeId, Renderable, SceneGraph, ShapeSampling};
+
+/// Share of the effect spent cross-fading between shape and dots at each end
+const SWAP: f32 = 0.15;
+
+/// Dot nodes created by a [`Dissolve`] effect
+#[derive(Debug, Clone)]
+pub struct DissolveHandle {
+    /// One node per dot, in sampling order
+    pub dots: Vec<NodeId>,
+}
+
+/// Settings for breaking shapes into dots
+#[derive(Debug, Clone)]
+pub struct Dissolve {
+    /// Number of dots
+    pub count: usize,
+    /// Radius of each dot in scene units
+    pub dot_radius: f32,
+    /// Where on the shapes dots are taken from
+    pub sampling: ShapeSampling,
+    /// How far dust drifts from the shapes, in scene units
+    pub spread: f32,
+    /// Picks one of many equally random dust clouds
+    pub seed: u32,
+    /// Dot color (the first shape's color when `None`)
+    pub color: Option<Color>,
+}
+
+impl Dissolve {
+    pub fn new(count: usize) -> Self {
+        Self {
+            count,
+            dot_radius: 0.008,
+            sampling: ShapeSampling::Interior { seed: 0 },
+            spread: 0.3,
+            seed: 0,
+            color: None,
+        }
+    }
+
+    pub fn with_dot_radius(mut self, radius: f32) -> Self {
+        self.dot_radius = radius.max(0.0);
+        self
+    }
+
+    pub fn with_sampling(mut self, sampling: ShapeSampling) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    pub fn with_spread(mut self, spread: f32) -> Self {
+        self.spread = spread.max(0.0);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Break `source` into dots that drift apart and fade out, starting at
+    /// `start_time`; `source` fades out as its dots appear
+    pub fn scatter(
+        &self,
+        scene: &mut SceneGraph,
+        name: &str,
+        source: NodeId,
+        start_time: f32,
+        duration: f32,
+    ) -> DissolveHandle {
+        self.add_dots(scene, name, Some(source), None, start_time, duration)
+    }
+
+    /// Gather dust into `target`, starting at `start_time`; `target` stays
+    /// hidden until its dots settle and then fades in over them
+    pub fn assemble(
+        &self,
+        scene: &mut SceneGraph,
+        name: &str,
+        target: NodeId,
+        start_time: f32,
+        duration: f32,
+    ) -> DissolveHandle {
+        self.add_dots(scene, name, None, Some(target), start_time, duration)
+    }
+
+    /// Break `source` into dust over the first half of `duration` and
+    /// gather it into `target` over the second
+    pub fn morph(
+        &self,
+        scene: &mut SceneGraph,
+        name: &str,
+        source: NodeId,
+        target: NodeId,
+        start_time: f32,
+        duration: f32,
+    ) -> DissolveHandle {
+        self.add_dots(
+            scene,
+            name,
+            Some(source),
+            Some(target),
+            start_time,
+            duration,
+        )
+    }
+
+    fn add_dots(
+        &self,
+        scene: &mut SceneGraph,
+        name: &str,
+        source: Option<NodeId>,
+        target: Option<NodeId>,
+        start_time: f32,
+        duration: f32,
+    ) -> DissolveHandle {
+        scene.update_transforms();
+        let sample = |node: Option<NodeId>| {
+            node.map(|id| scene.sample_node(id, self.count, self.sampling))
+                .filter(|points| !points.is_empty())
+        };
+        let (from, to) = (sample(source), sample(target));
+        let Some(anchors) = from.as_ref().or(to.as_ref()) else {
+            return DissolveHandle { dots: Vec::new() };
+        };
+        let center = centroid(anchors);
+        let color = self.color.unwrap_or_else(|| {
+            [source, target]
+                .into_iter()
+                .flatten()
+                .find_map(|id| scene.get_node(id)?.renderable.as_ref())
+                .map_or(scene.theme().foreground, Renderable::color)
+        });
+
+        let duration = duration.max(f32::EPSILON);
+        let opacity = dot_opacity(from.is_some(), to.is_some(), duration);
+        let mut dots = Vec::with_capacity(anchors.len());
+        for i in 0..anchors.len() {
+            let start = from.as_ref().map(|points| points[i]);
+            let end = to.as_ref().map(|points| points[i]);
+            let anchor = match (start, end) {
+                (Some(start), Some(end)) => start.lerp(&end, 0.5),
+                (Some(point), None) | (None, Some(point)) => point,
+                (None, None) => unreachable!("dots come from a source or a target"),
+            };
+            let dust = self.dust_point(anchor, center, i);
+
+            let mut clip = AnimationClip::new("Dissolve".to_string());
+            clip.add_track(dot_path(start, dust, end, duration));
+            clip.add_track(opacity.clone());
+            let dot = scene
+                .add_circle(format!("{name}_dot{i}"), self.dot_radius, color)
+                .at_vec(start.unwrap_or(dust))
+                .opacity(0.0)
+                .id();
+            if let Some(node) = scene.get_node_mut(dot) {
+                node.add_animation(
+                    AnimationInstance::new(clip, TimeValue::new(start_time)).with_fill_before(true),
+                );
+            }
+            dots.push(dot);
+        }
+
+        // The shapes hand over to the dots at the ends of the effect
+        let swap = duration * SWAP;
+        if let Some(node) = source.and_then(|id| scene.get_node_mut(id)) {
+            node.add_animation(AnimationInstance::new(
+                effects::fade_out(swap),
+                TimeValue::new(start_time),
+            ));
+        }
+        if let Some(node) = target.and_then(|id| scene.get_node_mut(id)) {
+            node.add_animation(
+                AnimationInstance::new(
+                    effects::fade_in(swap),
+                    TimeValue::new(start_time + duration - swap),
+                )
+                .with_fill_before(true),
+            );
+        }
+
+        scene.update_transforms();
+        DissolveHandle { dots }
+    }
+
+    /// Where dot `index` of the cloud drifts to from `anchor`: away from the
+    /// shapes' `center`, jittered, and at most `spread` away
+    fn dust_point(&self, anchor: Vector3, center: Vector3, index: usize) -> Vector3 {
+        let hash = |k: usize| unit_hash(self.seed, (index * 3 + k) as i32);
+        let angle = hash(0) * std::f32::consts::TAU;
+        let jitter = Vector3::new(angle.cos(), angle.sin(), 0.0) * hash(1).sqrt();
+        let outward = (anchor - center).normalized();
+        anchor + (outward * (0.4 + 0.3 * hash(2)) + jitter * 0.3) * self.spread
+    }
+}
+
+/// Average of `points`
+fn centroid(points: &[Vector3]) -> Vector3 {
+    let sum = points
+        .iter()
+        .fold(Vector3::zero(), |sum, &point| sum + point);
+    sum * (1.0 / points.len().max(1) as f32)
+}
+
+/// A dot's position over the effect: held on the first shape while the
+/// dots fade in, out to `dust`, and held on the second shape while they
+/// fade out
+fn dot_path(
+    start: Option<Vector3>,
+    dust: Vector3,
+    end: Option<Vector3>,
+    duration: f32,
+) -> AnimationTrack<Vector3> {
+    let swap = duration * SWAP;
+    let mut keys = Vec::new();
+    match start {
+        Some(start) => keys.extend([(0.0, start), (swap, start)]),
+        None => keys.push((0.0, dust)),
+    }
+    if start.is_some() && end.is_some() {
+        keys.push((duration * 0.5, dust));
+    }
+    match end {
+        Some(end) => keys.extend([(duration - swap, end), (duration, end)]),
+        None => keys.push((duration, dust)),
+    }
+
+    let mut track = AnimationTrack::new("position".to_string());
+    for (time, position) in keys {
+        track.add_keyframe(
+            Keyframe::new(TimeValue::new(time), position)
+                .with_interpolation(InterpolationType::EaseInOut),
+        );
+    }
+    track
+}
+
+/// Opacity of every dot: faded in over a shape that is there at the start
+/// (or out of nowhere) and faded out under a shape that is there at the end
+/// (or into nothing)
+fn dot_opacity(from_shape: bool, to_shape: bool, duration: f32) -> AnimationTrack<Vector3> {
+    let swap = duration * SWAP;
+    let fade_in = if from_shape { swap } else { duration * 0.5 };
+    let fade_out = if to_shape { swap } else { duration * 0.5 };
+    let mut track = AnimationTrack::new("opacity".to_string());
+    for (time, opacity) in [
+        (0.0, 0.0),
+        (fade_in, 1.0),
+        (duration - fade_out, 1.0),
+        (duration, 0.0),
+    ] {
+        track.add_keyframe(Keyframe::new(
+            TimeValue::new(time),
+            Vector3::new(opacity, 0.0, 0.0),
+        ));
+    }
+    track
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_morph_passes_through_dust() {
+        let mut scene = SceneGraph::new();
+        let square = scene
+            .add_rectangle("square", 0.4, 0.4, Color::BLUE)
+            .at(-0.5, 0.0, 0.0)
+            .id();
+        let circle = scene
+            .add_circle("circle", 0.2, Color::RED)
+            .at(0.5, 0.0, 0.0)
+            .id();
+        let handle = Dissolve::new(50)
+            .with_sampling(ShapeSampling::Outline)
+            .with_spread(0.5)
+            .morph(&mut scene, "dust", square, circle, 0.0, 2.0);
+        assert_eq!(handle.dots.len(), 50);
+
+        let dot = |scene: &SceneGraph, i: usize| {
+            let node = scene.get_node(handle.dots[i]).unwrap();
+            (node.world_transform.position, node.opacity)
+        };
+        let opacity = |scene: &SceneGraph, id: NodeId| scene.get_node(id).unwrap().opacity;
+
+        // Before the effect the dots sit hidden on the square's outline and
+        // the circle is hidden too
+        scene.update_animations(TimeValue::new(0.0));
+        let (position, dot_opacity) = dot(&scene, 0);
+        assert!((position - Vector3::new(-0.7, -0.2, 0.0)).length() < 1e-4);
+        assert_eq!(dot_opacity, 0.0);
+        assert_eq!(opacity(&scene, circle), 0.0);
+        assert_eq!(opacity(&scene, square), 1.0);
+
+        // Halfway they are dust, all drawn alike so they batch together
+        scene.update_animations(TimeValue::new(1.0));
+        assert_eq!(opacity(&scene, square), 0.0);
+        let (_, halfway) = dot(&scene, 0);
+        assert!((halfway - 1.0).abs() < 1e-5);
+        assert!((1..50).all(|i| dot(&scene, i).1 == halfway));
+        let renderable = scene.get_node(handle.dots[0]).unwrap().renderable.clone();
+        assert!((1..50).all(|i| scene.get_node(handle.dots[i]).unwrap().renderable == renderable));
+
+        // At the end they lie on the circle, which has taken over
+        scene.update_animations(TimeValue::new(1.0));
+        let (position, dot_opacity) = dot(&scene, 0);
+        assert!((position - Vector3::new(0.7, 0.0, 0.0)).length() < 1e-4);
+        assert_eq!(dot_opacity, 0.0);
+        assert!((opacity(&scene, circle) - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_scatter_drifts_outwards() {
+        let mut scene = SceneGraph::new();
+        let dot = scene.add_circle("dot", 0.3, Color::GREEN).id();
+        let handle = Dissolve::new(100)
+            .with_spread(0.4)
+            .with_seed(3)
+            .scatter(&mut scene, "dust", dot, 0.0, 1.0);
+
+        // Scattered dust lies beyond the shape, no further than the spread
+        scene.update_animations(TimeValue::new(1.0));
+        for &id in &handle.dots {
+            let node = scene.get_node(id).unwrap();
+            let distance = node.world_transform.position.length();
+            assert!(distance > 0.0 && distance < 0.3 + 0.4 + 1e-4);
+            assert_eq!(node.renderable.as_ref().unwrap().color(), Color::GREEN);
+        }
+        let empty = Dissolve::new(10).scatter(&mut scene, "none", NodeId::new(999), 0.0, 1.0);
+        assert!(empty.dots.is_empty());
+    }
+}
@@ -0,0 +1,383 @@
+//! Attention effects
+//!
+//! Manim-style emphasis for pointing the viewer at something:
+//!
+//! - [`Indicate`] briefly enlarges a node and tints it
+//! - [`Flash`] radiates short lines out of a point
+//! - [`Circumscribe`] draws a rectangle or ellipse around a node's bounds
+//!   (see [`SceneGraph::bounds`]) and fades it away
+//!
+//! Flash and Circumscribe add their own nodes, hidden until their start
+//! time and transparent after it; each needs a name unique in the scene.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::core::*;
+//! use diomanim::mobjects::{Circumscribe, CircumscribeShape, Flash, Indicate};
+//! use diomanim::scene::SceneGraph;
+//!
+//! let mut scene = SceneGraph::new();
+//! let answer = scene.add_text("answer", "x = 42", 48.0, Color::WHITE).build();
+//!
+//! Indicate::new().apply(&mut scene, answer, 1.0, 1.0);
+//! Flash::new().add_to_scene(&mut scene, "flash", Vector3::new(0.3, 0.0, 0.0), 2.0, 0.8);
+//! Circumscribe::new(CircumscribeShape::Ellipse).add_to_scene(&mut scene, "ring", answer, 3.0, 1.5);
+//! ```
+
+use crate::animation::effects;
+use crate::animation::property::{AnimationClip, AnimationInstance, AnimationTrack, Keyframe};
+use crate::core::{Color, TimeValue, Vector3};
+use crate::scene::{NodeId, Renderable, SceneGraph};
+
+/// Points on the outline of an ellipse drawn by [`Circumscribe`]
+const ELLIPSE_SEGMENTS: usize = 64;
+
+/// Scale up and tint a node for a moment
+#[derive(Debug, Clone)]
+pub struct Indicate {
+    /// Largest scale relative to the node's own
+    pub scale_factor: f32,
+    pub color: Color,
+}
+
+impl Indicate {
+    pub fn new() -> Self {
+        Self {
+            scale_factor: 1.2,
+            color: Color::YELLOW,
+        }
+    }
+
+    pub fn with_scale_factor(mut self, scale_factor: f32) -> Self {
+        self.scale_factor = scale_factor;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Pulse `target` over `duration` seconds from `start_time`
+    pub fn apply(&self, scene: &mut SceneGraph, target: NodeId, start_time: f32, duration: f32) {
+        let Some(node) = scene.get_node_mut(target) else {
+            return;
+        };
+        let color = node
+            .renderable
+            .as_ref()
+            .map_or(self.color, Renderable::color);
+        let clip = effects::indicate(
+            node._local_transform.scale,
+            color,
+            self.color,
+            self.scale_factor,
+            duration,
+        );
+        node.add_animation(AnimationInstance::new(clip, TimeValue::new(start_time)));
+    }
+}
+
+impl Default for Indicate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lines bursting outwards from a point
+#[derive(Debug, Clone)]
+pub struct Flash {
+    /// Number of lines, evenly spread around the point
+    pub lines: usize,
+    /// Distance from the point to where the lines start
+    pub radius: f32,
+    pub line_length: f32,
+    /// Line thickness, in the units of [`Renderable::Line`]
+    pub thickness: f32,
+    /// Line color (the theme foreground when `None`)
+    pub color: Option<Color>,
+}
+
+impl Flash {
+    pub fn new() -> Self {
+        Self {
+            lines: 12,
+            radius: 0.05,
+            line_length: 0.1,
+            thickness: 2.0,
+            color: None,
+        }
+    }
+
+    pub fn with_lines(mut self, lines: usize) -> Self {
+        self.lines = lines;
+        self
+    }
+
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius.max(0.0);
+        self
+    }
+
+    pub fn with_line_length(mut self, line_length: f32) -> Self {
+        self.line_length = line_length.max(0.0);
+        self
+    }
+
+    pub fn with_thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Flash at `point` over `duration` seconds from `start_time`: the lines
+    /// grow outwards for the first half, then fly on and fade out
+    pub fn add_to_scene(
+        &self,
+        scene: &mut SceneGraph,
+        name: &str,
+        point: Vector3,
+        start_time: f32,
+        duration: f32,
+    ) -> Vec<NodeId> {
+        let color = self.color.unwrap_or(scene.theme().foreground);
+        let half = duration / 2.0;
+        (0..self.lines)
+            .map(|i| {
+                let angle = i as f32 / self.lines as f32 * std::f32::consts::TAU;
+                let direction = Vector3::new(angle.cos(), angle.sin(), 0.0);
+                let line = scene
+                    .add_line(
+                        format!("{name}_line{i}"),
+                        direction * self.radius,
+                        direction * (self.radius + self.line_length),
+                        color,
+                        self.thickness,
+                    )
+                    .at_vec(point)
+                    .id();
+
+                let mut clip = AnimationClip::new("Flash".to_string());
+                let flown = point + direction * self.line_length;
+                for (track, keys) in [
+                    ("progress", [(0.0, 0.0), (half, 1.0), (duration, 1.0)]),
+                    ("opacity", [(0.0, 1.0), (half, 1.0), (duration, 0.0)]),
+                ] {
+                    clip.add_track(scalar_track(track, &keys));
+                }
+                let mut position = AnimationTrack::new("position".to_string());
+                position.add_keyframe(Keyframe::new(TimeValue::new(half), point));
+                position.add_keyframe(Keyframe::new(TimeValue::new(duration), flown));
+                clip.add_track(position);
+                show_from(scene, line, clip, start_time);
+                line
+            })
+            .collect()
+    }
+}
+
+impl Default for Flash {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Shape drawn around a node by [`Circumscribe`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircumscribeShape {
+    Rectangle,
+    /// Ellipse through the corners of the padded bounds
+    Ellipse,
+}
+
+/// A frame drawn around a node, then faded out
+#[derive(Debug, Clone)]
+pub struct Circumscribe {
+    pub shape: CircumscribeShape,
+    /// Gap between the node's bounds and the frame
+    pub padding: f32,
+    /// Frame thickness, in the units of [`Renderable::Polyline`]
+    pub thickness: f32,
+    pub color: Color,
+}
+
+impl Circumscribe {
+    pub fn new(shape: CircumscribeShape) -> Self {
+        Self {
+            shape,
+            padding: 0.03,
+            thickness: 2.0,
+            color: Color::YELLOW,
+        }
+    }
+
+    pub fn with_padding(mut self, padding: f32) -> Self {
+        self.padding = padding.max(0.0);
+        self
+    }
+
+    pub fn with_thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// The frame's outline around `(min, max)` bounds, relative to their
+    /// center and closed back on its first point
+    pub fn outline(&self, min: Vector3, max: Vector3) -> Vec<Vector3> {
+        let half_width = (max.x - min.x) / 2.0 + self.padding;
+        let half_height = (max.y - min.y) / 2.0 + self.padding;
+        match self.shape {
+            CircumscribeShape::Rectangle => [
+                (-1.0, -1.0),
+                (1.0, -1.0),
+                (1.0, 1.0),
+                (-1.0, 1.0),
+                (-1.0, -1.0),
+            ]
+            .map(|(x, y)| Vector3::new(x * half_width, y * half_height, 0.0))
+            .to_vec(),
+            CircumscribeShape::Ellipse => (0..=ELLIPSE_SEGMENTS)
+                .map(|i| {
+                    // Passing through the box's corners
+                    let angle = i as f32 / ELLIPSE_SEGMENTS as f32 * std::f32::consts::TAU;
+                    Vector3::new(
+                        std::f32::consts::SQRT_2 * half_width * angle.cos(),
+                        std::f32::consts::SQRT_2 * half_height * angle.sin(),
+                        0.0,
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// Draw the frame around `target` over the first 60% of `duration`
+    /// from `start_time` and fade it out over the rest (`None` if `target`
+    /// draws nothing)
+    pub fn add_to_scene(
+        &self,
+        scene: &mut SceneGraph,
+        name: &str,
+        target: NodeId,
+        start_time: f32,
+        duration: f32,
+    ) -> Option<NodeId> {
+        scene.update_transforms();
+        let (min, max) = scene.bounds(target)?;
+        let frame = scene.create_node(name.to_string());
+        if let Some(node) = scene.get_node_mut(frame) {
+            node._local_transform.position = min.lerp(&max, 0.5);
+            node.set_renderable(Renderable::Polyline {
+                points: self.outline(min, max),
+                color: self.color,
+                thickness: self.thickness,
+                fade: 0.0,
+            });
+        }
+
+        let drawn = duration * 0.6;
+        let mut clip = AnimationClip::new("Circumscribe".to_string());
+        clip.add_track(scalar_track(
+            "progress",
+            &[(0.0, 0.0), (drawn, 1.0), (duration, 1.0)],
+        ));
+        clip.add_track(scalar_track(
+            "opacity",
+            &[(0.0, 1.0), (drawn, 1.0), (duration, 0.0)],
+        ));
+        show_from(scene, frame, clip, start_time);
+        scene.update_transforms();
+        Some(frame)
+    }
+}
+
+/// Track named `name` through `(time, value)` keys
+fn scalar_track(name: &str, keys: &[(f32, f32)]) -> AnimationTrack<Vector3> {
+    let mut track = AnimationTrack::new(name.to_string());
+    for &(time, value) in keys {
+        track.add_keyframe(Keyframe::new(
+            TimeValue::new(time),
+            Vector3::new(value, 0.0, 0.0),
+        ));
+    }
+    track
+}
+
+/// Play `clip` on `node` from `start_time`, showing its first frame (an
+/// undrawn shape) until then
+fn show_from(scene: &mut SceneGraph, node: NodeId, clip: AnimationClip, start_time: f32) {
+    if let Some(node) = scene.get_node_mut(node) {
+        node.progress = 0.0;
+        node.add_animation(
+            AnimationInstance::new(clip, TimeValue::new(start_time)).with_fill_before(true),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indicate_pulses_and_returns() {
+        let mut scene = SceneGraph::new();
+        let dot = scene.add_circle("dot", 0.1, Color::BLUE).id();
+        Indicate::new().apply(&mut scene, dot, 0.0, 1.0);
+
+        scene.update_animations(TimeValue::new(0.5));
+        let node = scene.get_node(dot).unwrap();
+        assert!((node.world_transform.scale.x - 1.2).abs() < 1e-5);
+        assert_eq!(node.renderable.as_ref().unwrap().color(), Color::YELLOW);
+
+        scene.update_animations(TimeValue::new(0.5));
+        let node = scene.get_node(dot).unwrap();
+        assert!((node.world_transform.scale.x - 1.0).abs() < 1e-5);
+        assert_eq!(node.renderable.as_ref().unwrap().color(), Color::BLUE);
+    }
+
+    #[test]
+    fn test_flash_and_circumscribe_come_and_go() {
+        let mut scene = SceneGraph::new();
+        let card = scene
+            .add_rectangle("card", 0.4, 0.2, Color::BLUE)
+            .at(0.5, 0.0, 0.0)
+            .id();
+        let lines =
+            Flash::new()
+                .with_lines(8)
+                .add_to_scene(&mut scene, "flash", Vector3::zero(), 1.0, 1.0);
+        let frame = Circumscribe::new(CircumscribeShape::Rectangle)
+            .with_padding(0.05)
+            .add_to_scene(&mut scene, "frame", card, 1.0, 1.0)
+            .unwrap();
+        assert_eq!(lines.len(), 8);
+
+        // Undrawn before they start
+        scene.update_animations(TimeValue::new(0.5));
+        assert_eq!(scene.get_node(lines[0]).unwrap().progress, 0.0);
+        assert_eq!(scene.get_node(frame).unwrap().progress, 0.0);
+
+        // The frame hugs the padded card
+        let (min, max) = scene.bounds(frame).unwrap();
+        assert!((min - Vector3::new(0.25, -0.15, 0.0)).length() < 1e-5);
+        assert!((max - Vector3::new(0.75, 0.15, 0.0)).length() < 1e-5);
+
+        // Fully drawn midway, gone at the end, the lines having flown outwards
+        scene.update_animations(TimeValue::new(1.1));
+        assert!(scene.get_node(frame).unwrap().progress > 0.999);
+        scene.update_animations(TimeValue::new(0.4));
+        let line = scene.get_node(lines[0]).unwrap();
+        assert_eq!(line.opacity, 0.0);
+        assert!((line.world_transform.position.x - 0.1).abs() < 1e-5);
+        assert_eq!(scene.get_node(frame).unwrap().opacity, 0.0);
+    }
+}
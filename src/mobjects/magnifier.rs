@@ -0,0 +1,239 @@
+//! Magnifying Glass
+//!
+//! A `Magnifier` adds a circular lens to a scene showing a zoomed region of
+//! everything else, like Manim's `ZoomedScene`. The lens is a
+//! [`RenderTarget`] capturing the scene through its own camera, displayed
+//! as a circle-clipped image with a border ring. Its focus can be set,
+//! panned with an animation, or made to follow a moving node.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::mobjects::Magnifier;
+//! use diomanim::scene::SceneGraph;
+//! use diomanim::core::*;
+//!
+//! let mut scene = SceneGraph::new();
+//! let dot = scene.add_circle("dot", 0.02, Color::RED).build();
+//!
+//! let lens = Magnifier::new(0.3, 4.0).add_to_scene(
+//!     &mut scene,
+//!     "lens",
+//!     Vector3::zero(),
+//!     Vector3::new(0.6, 0.5, 0.0),
+//! );
+//! Magnifier::pan_to(&mut scene, &lens, Vector3::new(0.2, 0.0, 0.0), 1.0, 2.0);
+//! Magnifier::follow(&mut scene, &lens, dot);
+//! ```
+
+use crate::animation::{effects, property::AnimationInstance};
+use crate::core::{Color, TimeValue, Vector3};
+use crate::scene::{ClipMask, NodeId, RenderTarget, SceneGraph, TargetSource};
+
+/// Scene nodes created by [`Magnifier::add_to_scene`]
+#[derive(Debug, Clone)]
+pub struct MagnifierHandle {
+    /// Render target node placed where the lens is shown; move it to move
+    /// the lens
+    pub lens: NodeId,
+    /// Ring drawn around the zoomed view
+    pub border: NodeId,
+    /// Circle-clipped image of the zoomed region
+    pub view: NodeId,
+}
+
+/// A circular inset showing part of the scene magnified
+#[derive(Debug, Clone)]
+pub struct Magnifier {
+    /// Radius of the lens in scene units
+    pub radius: f32,
+    /// How many times larger the lens shows the scene
+    pub magnification: f32,
+    /// Width and height of the lens texture in pixels
+    pub resolution: u32,
+    pub border_color: Color,
+    /// Width of the border ring in scene units (0 for none)
+    pub border_width: f32,
+    /// Color behind the zoomed scene (the theme background when `None`)
+    pub background: Option<Color>,
+}
+
+impl Magnifier {
+    pub fn new(radius: f32, magnification: f32) -> Self {
+        Self {
+            radius,
+            magnification,
+            resolution: 256,
+            border_color: Color::WHITE,
+            border_width: 0.01,
+            background: None,
+        }
+    }
+
+    pub fn with_resolution(mut self, resolution: u32) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    pub fn with_border(mut self, color: Color, width: f32) -> Self {
+        self.border_color = color;
+        self.border_width = width;
+        self
+    }
+
+    pub fn with_background(mut self, background: Color) -> Self {
+        self.background = Some(background);
+        self
+    }
+
+    /// Add a lens at `position` magnifying the region around `focus`
+    ///
+    /// The lens's render target is called `name`, so each magnifier in a
+    /// scene needs a different name.
+    pub fn add_to_scene(
+        &self,
+        scene: &mut SceneGraph,
+        name: &str,
+        focus: Vector3,
+        position: Vector3,
+    ) -> MagnifierHandle {
+        let background = self.background.unwrap_or(scene.theme().background);
+        let diameter = self.radius * 2.0;
+        let lens = scene
+            .add_render_target(name, self.resolution, self.resolution)
+            .at_vec(position)
+            .id();
+        if let Some(target) = scene
+            .get_node_mut(lens)
+            .and_then(|node| node.render_target.as_mut())
+        {
+            // The camera shows `radius / magnification` around the focus
+            // across the lens radius
+            target.source = TargetSource::Scene;
+            target.center = focus;
+            target.zoom = self.magnification / self.radius.max(f32::EPSILON);
+            target.background = background;
+        }
+
+        let border = scene
+            .add_circle(
+                format!("{name}_border"),
+                self.radius + self.border_width.max(0.0),
+                self.border_color,
+            )
+            .parent_to(lens)
+            .visible(self.border_width > 0.0)
+            .id();
+        let view = scene
+            .add_image(
+                format!("{name}_view"),
+                RenderTarget::handle_for(name),
+                diameter,
+                diameter,
+            )
+            .clip(ClipMask::Circle {
+                radius: self.radius,
+            })
+            .parent_to(lens)
+            .id();
+
+        scene.update_transforms();
+        MagnifierHandle { lens, border, view }
+    }
+
+    /// Magnify the region around `point`, no longer following a node
+    pub fn focus_on(scene: &mut SceneGraph, handle: &MagnifierHandle, point: Vector3) {
+        if let Some(target) = Self::target(scene, handle) {
+            target.follow = None;
+            target.center = point;
+        }
+    }
+
+    /// Keep the lens focused on `node` as it moves
+    pub fn follow(scene: &mut SceneGraph, handle: &MagnifierHandle, node: NodeId) {
+        if let Some(target) = Self::target(scene, handle) {
+            target.follow = Some(node);
+        }
+        scene.update_transforms();
+    }
+
+    /// Animate the focus from where it is to `point`, starting at
+    /// `start_time`; stops following a node
+    pub fn pan_to(
+        scene: &mut SceneGraph,
+        handle: &MagnifierHandle,
+        point: Vector3,
+        start_time: f32,
+        duration: f32,
+    ) {
+        let Some(target) = Self::target(scene, handle) else {
+            return;
+        };
+        target.follow = None;
+        let from = target.center;
+        if let Some(node) = scene.get_node_mut(handle.lens) {
+            node.add_animation(AnimationInstance::new(
+                effects::target_pan(from, point, duration),
+                TimeValue::new(start_time),
+            ));
+        }
+    }
+
+    fn target<'a>(
+        scene: &'a mut SceneGraph,
+        handle: &MagnifierHandle,
+    ) -> Option<&'a mut RenderTarget> {
+        scene
+            .get_node_mut(handle.lens)
+            .and_then(|node| node.render_target.as_mut())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_magnifier_focus() {
+        let mut scene = SceneGraph::new();
+        let dot = scene
+            .add_circle("dot", 0.05, Color::RED)
+            .at(0.2, 0.1, 0.0)
+            .id();
+        let handle = Magnifier::new(0.25, 4.0).add_to_scene(
+            &mut scene,
+            "lens",
+            Vector3::zero(),
+            Vector3::new(0.6, 0.6, 0.0),
+        );
+
+        // The lens captures the rest of the scene, not itself
+        let captured: Vec<NodeId> = scene
+            .render_target_nodes(handle.lens)
+            .iter()
+            .map(|node| node.id)
+            .collect();
+        assert_eq!(captured, [dot]);
+        let framed: Vec<NodeId> = scene
+            .visible_renderable_nodes()
+            .iter()
+            .map(|node| node.id)
+            .collect();
+        assert_eq!(framed, [dot, handle.border, handle.view]);
+
+        let center = |scene: &SceneGraph| {
+            scene
+                .get_node(handle.lens)
+                .and_then(|node| node.render_target.as_ref())
+                .map(|target| target.center)
+                .unwrap()
+        };
+        Magnifier::follow(&mut scene, &handle, dot);
+        assert_eq!(center(&scene), Vector3::new(0.2, 0.1, 0.0));
+
+        Magnifier::focus_on(&mut scene, &handle, Vector3::zero());
+        Magnifier::pan_to(&mut scene, &handle, Vector3::new(0.4, 0.0, 0.0), 0.0, 1.0);
+        scene.update_animations(TimeValue::new(0.5));
+        assert!((center(&scene) - Vector3::new(0.2, 0.0, 0.0)).length() < 1e-5);
+    }
+}
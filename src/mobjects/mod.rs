@@ -8,6 +8,13 @@
 //!
 //! - **Circle**: A circular shape with configurable radius and color
 //! - **Square**: A square shape with configurable side length and color
+//! - **DecimalNumber**: Text showing an animated value, for counters
+//! - **Tree**: Node-link diagram for hierarchical data with expand/collapse
+//! - **Magnifier**: Circular lens showing a zoomed region of the scene
+//! - **TracedPath**: Trail recording where a moving node has been
+//! - **AudioVisualizer**: Spectrum or waveform bars or line driven by a sound
+//! - **Dissolve**: Shapes breaking into dots that scatter or reassemble
+//! - **Indicate / Flash / Circumscribe**: Brief effects drawing attention to a node or point
 //!
 //! ## Example
 //!
@@ -24,8 +31,24 @@
 //! square.move_to(Vector3::new(-5.0, 0.0, 0.0));
 //! ```
 
+pub mod dissolve;
+pub mod emphasis;
+pub mod magnifier;
+pub mod number;
+pub mod trace;
+pub mod tree;
+pub mod visualizer;
+
 use crate::core::{Color, Vector3};
 
+pub use dissolve::{Dissolve, DissolveHandle};
+pub use emphasis::{Circumscribe, CircumscribeShape, Flash, Indicate};
+pub use magnifier::{Magnifier, MagnifierHandle};
+pub use number::{DecimalNumber, NumberFormat};
+pub use trace::TracedPath;
+pub use tree::{Tree, TreeHandle, TreeNode, TreeNodeId, TreeNodeShape};
+pub use visualizer::{AudioVisualizer, VisualizerSource, VisualizerStyle};
+
 #[derive(Debug, Clone)]
 pub struct Circle {
     pub radius: f32,
@@ -0,0 +1,167 @@
+//! Animated numbers
+//!
+//! A [`DecimalNumber`] shows the value of a [`ValueTracker`] as text and
+//! re-renders its digits every frame, for counters and "count up" stats:
+//!
+//! ```rust
+//! use diomanim::animation::{InterpolationType, ValueTracker};
+//! use diomanim::core::*;
+//! use diomanim::mobjects::{DecimalNumber, NumberFormat};
+//! use diomanim::scene::SceneGraph;
+//!
+//! let mut revenue = ValueTracker::new(0.0);
+//! revenue.animate_to(1_250_000.0, 0.0, 3.0, InterpolationType::EaseOut);
+//!
+//! let format = NumberFormat::new(0).with_grouping(',').with_prefix("$");
+//! let mut scene = SceneGraph::new();
+//! scene
+//!     .add_decimal_number("revenue", DecimalNumber::new(revenue, format), 64.0, Color::WHITE)
+//!     .at(0.0, 0.5, 0.0);
+//! ```
+
+use crate::animation::ValueTracker;
+use crate::core::TimeValue;
+
+/// How a number is written out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// Digits after the decimal point
+    pub precision: usize,
+    /// Write a `+` in front of positive numbers
+    pub show_sign: bool,
+    /// Separator between groups of three integer digits (`1,000,000`)
+    pub group_separator: Option<char>,
+    /// Text before the number, such as a currency symbol
+    pub prefix: String,
+    /// Text after the number, such as a unit or `%`
+    pub suffix: String,
+}
+
+impl NumberFormat {
+    /// Plain number with `precision` decimal places
+    pub fn new(precision: usize) -> Self {
+        Self {
+            precision,
+            show_sign: false,
+            group_separator: None,
+            prefix: String::new(),
+            suffix: String::new(),
+        }
+    }
+
+    pub fn with_sign(mut self) -> Self {
+        self.show_sign = true;
+        self
+    }
+
+    pub fn with_grouping(mut self, separator: char) -> Self {
+        self.group_separator = Some(separator);
+        self
+    }
+
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    pub fn with_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.suffix = suffix.into();
+        self
+    }
+
+    /// Write `value` out in this format
+    pub fn format(&self, value: f32) -> String {
+        let digits = format!("{:.*}", self.precision, f64::from(value).abs());
+        // Values that round to zero don't get a sign ("-0.00")
+        let is_zero = digits.bytes().all(|b| b == b'0' || b == b'.');
+        let sign = if is_zero {
+            ""
+        } else if value < 0.0 {
+            "-"
+        } else if self.show_sign {
+            "+"
+        } else {
+            ""
+        };
+
+        let (integer, fraction) = match digits.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (digits.as_str(), None),
+        };
+        let mut text = format!("{sign}{}", self.prefix);
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                if let Some(separator) = self.group_separator {
+                    text.push(separator);
+                }
+            }
+            text.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            text.push('.');
+            text.push_str(fraction);
+        }
+        text.push_str(&self.suffix);
+        text
+    }
+}
+
+impl Default for NumberFormat {
+    /// Two decimal places
+    fn default() -> Self {
+        Self::new(2)
+    }
+}
+
+/// Text showing the current value of a tracker
+#[derive(Debug, Clone)]
+pub struct DecimalNumber {
+    pub tracker: ValueTracker,
+    pub format: NumberFormat,
+}
+
+impl DecimalNumber {
+    pub fn new(tracker: ValueTracker, format: NumberFormat) -> Self {
+        Self { tracker, format }
+    }
+
+    /// A number that stays at `value`
+    pub fn constant(value: f32, format: NumberFormat) -> Self {
+        Self::new(ValueTracker::new(value), format)
+    }
+
+    /// The text for the tracker's current value
+    pub fn text(&self) -> String {
+        self.format.format(self.tracker.value())
+    }
+
+    /// Advance the tracker and return the text to show
+    pub fn advance(&mut self, delta_time: TimeValue) -> String {
+        self.tracker.advance(delta_time);
+        self.text()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::InterpolationType;
+
+    #[test]
+    fn test_number_format() {
+        assert_eq!(NumberFormat::new(2).format(1.23456), "1.23");
+        assert_eq!(NumberFormat::new(0).format(-0.4), "0");
+        assert_eq!(NumberFormat::new(1).with_sign().format(2.0), "+2.0");
+        let money = NumberFormat::new(0).with_grouping(',').with_prefix("$");
+        assert_eq!(money.format(1_234_567.0), "$1,234,567");
+        assert_eq!(money.format(-999.0), "-$999");
+        assert_eq!(NumberFormat::new(1).with_suffix("%").format(42.26), "42.3%");
+
+        let mut tracker = ValueTracker::new(0.0);
+        tracker.animate_to(100.0, 0.0, 1.0, InterpolationType::Linear);
+        let mut counter = DecimalNumber::new(tracker, NumberFormat::new(0));
+        assert_eq!(counter.text(), "0");
+        assert_eq!(counter.advance(TimeValue::new(0.5)), "50");
+        assert_eq!(counter.advance(TimeValue::new(1.0)), "100");
+    }
+}
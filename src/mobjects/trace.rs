@@ -0,0 +1,129 @@
+//! Traced paths
+//!
+//! A [`TracedPath`] records where a node has been: every frame the scene
+//! appends the node's world position to a polyline, optionally dropping
+//! points older than a tail length and fading the trail out towards its
+//! end. Handy for pendulums, orbits and parametric curves being drawn by a
+//! moving dot.
+//!
+//! ```rust
+//! use diomanim::core::*;
+//! use diomanim::mobjects::TracedPath;
+//! use diomanim::scene::SceneGraph;
+//!
+//! let mut scene = SceneGraph::new();
+//! let dot = scene
+//!     .add_circle("dot", 0.03, Color::YELLOW)
+//!     .move_to(0.0, Vector3::new(0.8, 0.0, 0.0), 2.0)
+//!     .build();
+//! scene.add_traced_path("trail", TracedPath::new(dot).with_tail(0.5), Color::YELLOW, 3.0);
+//!
+//! scene.update_animations(TimeValue::new(1.0 / 30.0));
+//! ```
+
+use crate::core::Vector3;
+use crate::scene::NodeId;
+
+/// The path a node has moved along, recorded once per update
+#[derive(Debug, Clone, PartialEq)]
+pub struct TracedPath {
+    /// Node whose position is traced
+    pub target: NodeId,
+    /// Seconds a point stays on the trail (`None` keeps the whole path);
+    /// trails with a tail fade out towards their end
+    pub tail: Option<f32>,
+    /// Smallest distance between recorded points
+    pub min_distance: f32,
+    /// Recorded positions, oldest first, with the time they were recorded
+    points: Vec<(Vector3, f32)>,
+    time: f32,
+}
+
+impl TracedPath {
+    pub fn new(target: NodeId) -> Self {
+        Self {
+            target,
+            tail: None,
+            min_distance: 0.001,
+            points: Vec::new(),
+            time: 0.0,
+        }
+    }
+
+    pub fn with_tail(mut self, seconds: f32) -> Self {
+        self.tail = Some(seconds.max(0.0));
+        self
+    }
+
+    pub fn with_min_distance(mut self, distance: f32) -> Self {
+        self.min_distance = distance.max(0.0);
+        self
+    }
+
+    /// Recorded positions, oldest first
+    pub fn points(&self) -> Vec<Vector3> {
+        self.points.iter().map(|&(point, _)| point).collect()
+    }
+
+    /// How much the trail fades towards its oldest point (0..1)
+    pub fn fade(&self) -> f32 {
+        if self.tail.is_some() {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    /// Forget the recorded path
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    /// Record `position` reached `delta_time` seconds after the previous
+    /// call, dropping points that fell off the tail
+    pub fn record(&mut self, position: Vector3, delta_time: f32) {
+        self.time += delta_time;
+        let moved = self
+            .points
+            .last()
+            .is_none_or(|&(last, _)| last.distance(&position) >= self.min_distance);
+        if moved {
+            self.points.push((position, self.time));
+        }
+        if let Some(tail) = self.tail {
+            let cutoff = self.time - tail;
+            let expired = self.points.iter().take_while(|&&(_, t)| t < cutoff).count();
+            self.points.drain(..expired);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_traced_path_tail() {
+        let mut trace = TracedPath::new(NodeId::new(1))
+            .with_tail(0.25)
+            .with_min_distance(0.01);
+        for i in 0..10 {
+            trace.record(Vector3::new(i as f32 * 0.1, 0.0, 0.0), 0.1);
+        }
+        // Points recorded in the last quarter second remain
+        let points = trace.points();
+        assert_eq!(points.len(), 3);
+        assert!((points[0].x - 0.7).abs() < 1e-5);
+        assert!((points[2].x - 0.9).abs() < 1e-5);
+        assert_eq!(trace.fade(), 1.0);
+
+        // Standing still adds nothing
+        let mut trace = TracedPath::new(NodeId::new(1)).with_min_distance(0.01);
+        trace.record(Vector3::zero(), 0.1);
+        trace.record(Vector3::new(0.001, 0.0, 0.0), 0.1);
+        assert_eq!(trace.points().len(), 1);
+        assert_eq!(trace.fade(), 0.0);
+        trace.clear();
+        assert!(trace.points().is_empty());
+    }
+}
@@ -0,0 +1,546 @@
+//! Tree Diagrams
+//!
+//! A `Tree` mobject lays out hierarchical data with a tidy-tree algorithm
+//! (subtrees are packed as tightly as their contours allow, parents centered
+//! over their children) and adds it to a scene as labeled nodes joined by
+//! edges. Subtrees can be collapsed and expanded with animations, which is
+//! handy for data-structure explainers.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::mobjects::tree::*;
+//! use diomanim::scene::SceneGraph;
+//!
+//! let mut tree = Tree::new("root");
+//! let left = tree.add_child(tree.root(), "L");
+//! let right = tree.add_child(tree.root(), "R");
+//! tree.add_child(left, "LL");
+//! tree.add_child(right, "RL");
+//!
+//! let mut scene = SceneGraph::new();
+//! let handle = tree.add_to_scene(&mut scene, "bst");
+//!
+//! // Fold the left subtree into its parent over half a second
+//! tree.collapse_animated(&mut scene, &handle, left, 1.0, 0.5);
+//! ```
+
+use crate::animation::{effects, property::AnimationInstance};
+use crate::core::{Color, TimeValue, Vector3};
+use crate::scene::{NodeId, Renderable, SceneGraph};
+
+/// Index of a node within a [`Tree`]
+pub type TreeNodeId = usize;
+
+/// Shape drawn for each tree node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeNodeShape {
+    Circle,
+    Rectangle,
+}
+
+/// A single node of hierarchical data
+#[derive(Debug, Clone)]
+pub struct TreeNode {
+    pub label: String,
+    pub parent: Option<TreeNodeId>,
+    pub children: Vec<TreeNodeId>,
+    /// Collapsed nodes hide their descendants
+    pub collapsed: bool,
+}
+
+/// Scene nodes created by [`Tree::add_to_scene`]
+#[derive(Debug, Clone)]
+pub struct TreeHandle {
+    /// Group node that owns the whole diagram
+    pub group: NodeId,
+    /// Shape node for each tree node (indexed by `TreeNodeId`)
+    pub nodes: Vec<NodeId>,
+    /// Label node for each tree node
+    pub labels: Vec<NodeId>,
+    /// Edge from each node to its parent (`None` for the root)
+    pub edges: Vec<Option<NodeId>>,
+}
+
+/// Hierarchical data laid out as a node-link diagram
+#[derive(Debug, Clone)]
+pub struct Tree {
+    nodes: Vec<TreeNode>,
+    pub shape: TreeNodeShape,
+    /// Circle radius, or half the rectangle height
+    pub node_size: f32,
+    /// Vertical distance between depths
+    pub level_spacing: f32,
+    /// Minimum horizontal gap between neighboring nodes
+    pub sibling_spacing: f32,
+    pub node_color: Color,
+    pub edge_color: Color,
+    pub label_color: Color,
+    pub font_size: f32,
+    pub edge_thickness: f32,
+}
+
+impl Tree {
+    /// Create a tree with a single root node
+    pub fn new(root_label: impl Into<String>) -> Self {
+        Self {
+            nodes: vec![TreeNode {
+                label: root_label.into(),
+                parent: None,
+                children: Vec::new(),
+                collapsed: false,
+            }],
+            shape: TreeNodeShape::Circle,
+            node_size: 0.06,
+            level_spacing: 0.3,
+            sibling_spacing: 0.18,
+            node_color: Color::BLUE,
+            edge_color: Color::GRAY,
+            label_color: Color::WHITE,
+            font_size: 24.0,
+            edge_thickness: 2.0,
+        }
+    }
+
+    pub fn with_shape(mut self, shape: TreeNodeShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    pub fn with_spacing(mut self, level_spacing: f32, sibling_spacing: f32) -> Self {
+        self.level_spacing = level_spacing;
+        self.sibling_spacing = sibling_spacing;
+        self
+    }
+
+    pub fn with_colors(mut self, node: Color, edge: Color, label: Color) -> Self {
+        self.node_color = node;
+        self.edge_color = edge;
+        self.label_color = label;
+        self
+    }
+
+    pub fn root(&self) -> TreeNodeId {
+        0
+    }
+
+    /// Append a child under `parent` and return its id
+    pub fn add_child(&mut self, parent: TreeNodeId, label: impl Into<String>) -> TreeNodeId {
+        let id = self.nodes.len();
+        self.nodes.push(TreeNode {
+            label: label.into(),
+            parent: Some(parent),
+            children: Vec::new(),
+            collapsed: false,
+        });
+        self.nodes[parent].children.push(id);
+        id
+    }
+
+    pub fn get(&self, id: TreeNodeId) -> Option<&TreeNode> {
+        self.nodes.get(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Depth of a node (root = 0)
+    pub fn depth(&self, id: TreeNodeId) -> usize {
+        let mut depth = 0;
+        let mut current = self.nodes[id].parent;
+        while let Some(parent) = current {
+            depth += 1;
+            current = self.nodes[parent].parent;
+        }
+        depth
+    }
+
+    /// Whether a node is shown (no collapsed ancestor)
+    pub fn is_visible(&self, id: TreeNodeId) -> bool {
+        self.visible_ancestor(id) == id
+    }
+
+    /// Nearest ancestor-or-self that is shown
+    fn visible_ancestor(&self, id: TreeNodeId) -> TreeNodeId {
+        let mut shown = id;
+        let mut current = self.nodes[id].parent;
+        while let Some(parent) = current {
+            if self.nodes[parent].collapsed {
+                shown = parent;
+            }
+            current = self.nodes[parent].parent;
+        }
+        shown
+    }
+
+    /// All descendants of `id` (excluding `id`), depth first
+    pub fn descendants(&self, id: TreeNodeId) -> Vec<TreeNodeId> {
+        let mut out = Vec::new();
+        let mut stack: Vec<TreeNodeId> = self.nodes[id].children.iter().rev().copied().collect();
+        while let Some(node) = stack.pop() {
+            out.push(node);
+            stack.extend(self.nodes[node].children.iter().rev());
+        }
+        out
+    }
+
+    pub fn collapse(&mut self, id: TreeNodeId) {
+        self.nodes[id].collapsed = true;
+    }
+
+    pub fn expand(&mut self, id: TreeNodeId) {
+        self.nodes[id].collapsed = false;
+    }
+
+    pub fn toggle(&mut self, id: TreeNodeId) {
+        self.nodes[id].collapsed = !self.nodes[id].collapsed;
+    }
+
+    /// Compute node positions relative to the root.
+    ///
+    /// Hidden nodes (under a collapsed ancestor) are placed on that ancestor,
+    /// so animating between layouts folds subtrees into their parent.
+    pub fn layout(&self) -> Vec<Vector3> {
+        let mut offsets = vec![0.0; self.nodes.len()];
+        self.layout_subtree(self.root(), &mut offsets);
+
+        // Accumulate relative offsets from the root down
+        let mut positions = vec![Vector3::zero(); self.nodes.len()];
+        let mut stack = vec![(self.root(), 0.0_f32, 0usize)];
+        while let Some((id, x, depth)) = stack.pop() {
+            positions[id] = Vector3::new(x, -(depth as f32) * self.level_spacing, 0.0);
+            if !self.nodes[id].collapsed {
+                for &child in &self.nodes[id].children {
+                    stack.push((child, x + offsets[child], depth + 1));
+                }
+            }
+        }
+
+        for id in 0..self.nodes.len() {
+            let shown = self.visible_ancestor(id);
+            if shown != id {
+                positions[id] = positions[shown];
+            }
+        }
+        positions
+    }
+
+    /// Lay out a subtree, storing each child's x offset from its parent.
+    /// Returns the (left, right) contour of the subtree per depth, relative to `id`.
+    fn layout_subtree(&self, id: TreeNodeId, offsets: &mut [f32]) -> (Vec<f32>, Vec<f32>) {
+        let node = &self.nodes[id];
+        if node.collapsed || node.children.is_empty() {
+            return (vec![0.0], vec![0.0]);
+        }
+
+        let separation = self.sibling_spacing + 2.0 * self.node_size;
+        let mut left: Vec<f32> = Vec::new();
+        let mut right: Vec<f32> = Vec::new();
+        let mut placed = Vec::with_capacity(node.children.len());
+
+        for &child in &node.children {
+            let (child_left, child_right) = self.layout_subtree(child, offsets);
+
+            // Shift the child right until it clears every depth of its left siblings
+            let shift = if placed.is_empty() {
+                0.0
+            } else {
+                right
+                    .iter()
+                    .zip(&child_left)
+                    .map(|(r, l)| r - l + separation)
+                    .fold(f32::MIN, f32::max)
+            };
+            placed.push(shift);
+
+            for (depth, (l, r)) in child_left.iter().zip(&child_right).enumerate() {
+                if depth < left.len() {
+                    right[depth] = r + shift;
+                } else {
+                    left.push(l + shift);
+                    right.push(r + shift);
+                }
+            }
+        }
+
+        // Center the parent over its first and last child
+        let mid = (placed[0] + placed[placed.len() - 1]) * 0.5;
+        for (&child, shift) in node.children.iter().zip(&placed) {
+            offsets[child] = shift - mid;
+        }
+
+        let mut contour_left = vec![0.0];
+        let mut contour_right = vec![0.0];
+        contour_left.extend(left.iter().map(|x| x - mid));
+        contour_right.extend(right.iter().map(|x| x - mid));
+        (contour_left, contour_right)
+    }
+
+    /// Approximate label offset so text is centered on its node
+    fn label_offset(&self, label: &str) -> Vector3 {
+        let char_width = 0.6 * self.font_size / 1000.0;
+        let height = self.font_size / 1000.0;
+        Vector3::new(
+            -char_width * label.chars().count() as f32 * 0.5,
+            height * 0.35,
+            0.0,
+        )
+    }
+
+    /// Create scene nodes for the diagram under a new group node
+    pub fn add_to_scene(&self, scene: &mut SceneGraph, name: &str) -> TreeHandle {
+        let positions = self.layout();
+        let group = scene.create_node(name.to_string());
+
+        // Edges first so they draw underneath the nodes
+        let mut edges = vec![None; self.nodes.len()];
+        for (id, node) in self.nodes.iter().enumerate() {
+            if let Some(parent) = node.parent {
+                let edge = scene
+                    .add_line(
+                        format!("{name}_edge_{id}"),
+                        Vector3::zero(),
+                        positions[id] - positions[parent],
+                        self.edge_color,
+                        self.edge_thickness,
+                    )
+                    .at_vec(positions[parent])
+                    .parent_to(group)
+                    .visible(self.is_visible(id))
+                    .id();
+                edges[id] = Some(edge);
+            }
+        }
+
+        let mut nodes = Vec::with_capacity(self.nodes.len());
+        let mut labels = Vec::with_capacity(self.nodes.len());
+        for (id, node) in self.nodes.iter().enumerate() {
+            let visible = self.is_visible(id);
+            let node_name = format!("{name}_node_{id}");
+            let shape_id = match self.shape {
+                TreeNodeShape::Circle => {
+                    scene.add_circle(node_name, self.node_size, self.node_color)
+                }
+                TreeNodeShape::Rectangle => scene.add_rectangle(
+                    node_name,
+                    self.node_size * 3.0,
+                    self.node_size * 2.0,
+                    self.node_color,
+                ),
+            }
+            .at_vec(positions[id])
+            .parent_to(group)
+            .visible(visible)
+            .id();
+
+            let label = scene
+                .add_text(
+                    format!("{name}_label_{id}"),
+                    node.label.clone(),
+                    self.font_size,
+                    self.label_color,
+                )
+                .at_vec(self.label_offset(&node.label))
+                .parent_to(shape_id)
+                .visible(visible)
+                .id();
+
+            nodes.push(shape_id);
+            labels.push(label);
+        }
+
+        scene.update_transforms();
+        TreeHandle {
+            group,
+            nodes,
+            labels,
+            edges,
+        }
+    }
+
+    /// Collapse `id` and animate its subtree folding into it
+    pub fn collapse_animated(
+        &mut self,
+        scene: &mut SceneGraph,
+        handle: &TreeHandle,
+        id: TreeNodeId,
+        start_time: f32,
+        duration: f32,
+    ) {
+        let before = self.layout();
+        self.collapse(id);
+        self.animate_layout(scene, handle, &before, start_time, duration);
+    }
+
+    /// Expand `id` and animate its subtree unfolding from it
+    pub fn expand_animated(
+        &mut self,
+        scene: &mut SceneGraph,
+        handle: &TreeHandle,
+        id: TreeNodeId,
+        start_time: f32,
+        duration: f32,
+    ) {
+        let before = self.layout();
+        self.expand(id);
+        self.animate_layout(scene, handle, &before, start_time, duration);
+    }
+
+    /// Animate every scene node from `before` to the current layout
+    fn animate_layout(
+        &self,
+        scene: &mut SceneGraph,
+        handle: &TreeHandle,
+        before: &[Vector3],
+        start_time: f32,
+        duration: f32,
+    ) {
+        let after = self.layout();
+        let start = TimeValue::new(start_time);
+
+        for id in 0..self.nodes.len() {
+            let now_visible = self.is_visible(id);
+            let was_visible = self.visible_ancestor_in(before, id);
+            let moved = before[id].distance(&after[id]) > f32::EPSILON;
+
+            if let Some(node) = scene.get_node_mut(handle.nodes[id]) {
+                if moved {
+                    node.add_animation(AnimationInstance::new(
+                        effects::move_to(before[id], after[id], duration),
+                        start,
+                    ));
+                }
+                if now_visible != was_visible {
+                    node.visible = true;
+                    let fade = if now_visible {
+                        effects::fade_in(duration)
+                    } else {
+                        effects::fade_out(duration)
+                    };
+                    node.add_animation(AnimationInstance::new(fade, start));
+                }
+            }
+
+            if now_visible != was_visible {
+                if let Some(label) = scene.get_node_mut(handle.labels[id]) {
+                    label.visible = true;
+                    let fade = if now_visible {
+                        effects::fade_in(duration)
+                    } else {
+                        effects::fade_out(duration)
+                    };
+                    label.add_animation(AnimationInstance::new(fade, start));
+                }
+            }
+
+            // Edges hang off their parent, so they follow it and grow/shrink with the child
+            let (Some(edge_id), Some(parent)) = (handle.edges[id], self.nodes[id].parent) else {
+                continue;
+            };
+            if let Some(edge) = scene.get_node_mut(edge_id) {
+                if before[parent].distance(&after[parent]) > f32::EPSILON {
+                    edge.add_animation(AnimationInstance::new(
+                        effects::move_to(before[parent], after[parent], duration),
+                        start,
+                    ));
+                }
+                if now_visible != was_visible {
+                    edge.visible = true;
+                    let scale = if now_visible {
+                        effects::grow_from_center(duration)
+                    } else {
+                        effects::shrink_to_center(duration)
+                    };
+                    edge.add_animation(AnimationInstance::new(scale, start));
+                } else if now_visible {
+                    if let Some(Renderable::Line { end, .. }) = &mut edge.renderable {
+                        *end = after[id] - after[parent];
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether `id` was shown in a previous layout (it sat on its own spot
+    /// rather than on a collapsed ancestor's)
+    fn visible_ancestor_in(&self, before: &[Vector3], id: TreeNodeId) -> bool {
+        match self.nodes[id].parent {
+            None => true,
+            Some(parent) => {
+                before[id].distance(&before[parent]) > f32::EPSILON
+                    && self.visible_ancestor_in(before, parent)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_tree() -> (Tree, TreeNodeId, TreeNodeId) {
+        let mut tree = Tree::new("root");
+        let left = tree.add_child(0, "L");
+        let right = tree.add_child(0, "R");
+        tree.add_child(left, "LL");
+        tree.add_child(left, "LR");
+        tree.add_child(right, "RL");
+        (tree, left, right)
+    }
+
+    #[test]
+    fn test_layout_centers_parent_and_separates_levels() {
+        let (tree, left, right) = sample_tree();
+        let positions = tree.layout();
+
+        assert_eq!(positions[0].x, 0.0);
+        assert!((positions[left].y + tree.level_spacing).abs() < 1e-6);
+        assert!(positions[left].x < 0.0 && positions[right].x > 0.0);
+        assert!((positions[0].x - (positions[left].x + positions[right].x) * 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_layout_has_no_overlaps() {
+        let (tree, _, _) = sample_tree();
+        let positions = tree.layout();
+        let min_gap = tree.sibling_spacing + 2.0 * tree.node_size - 1e-5;
+
+        for a in 0..tree.len() {
+            for b in (a + 1)..tree.len() {
+                if tree.depth(a) == tree.depth(b) {
+                    assert!((positions[a].x - positions[b].x).abs() >= min_gap);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_collapse_hides_descendants() {
+        let (mut tree, left, _) = sample_tree();
+        tree.collapse(left);
+
+        let positions = tree.layout();
+        for id in tree.descendants(left) {
+            assert!(!tree.is_visible(id));
+            assert_eq!(positions[id], positions[left]);
+        }
+        tree.expand(left);
+        assert!(tree.descendants(left).iter().all(|&id| tree.is_visible(id)));
+    }
+
+    #[test]
+    fn test_add_to_scene_creates_nodes_and_edges() {
+        let (tree, _, _) = sample_tree();
+        let mut scene = SceneGraph::new();
+        let handle = tree.add_to_scene(&mut scene, "tree");
+
+        assert_eq!(handle.nodes.len(), tree.len());
+        assert!(handle.edges[0].is_none());
+        assert_eq!(handle.edges.iter().flatten().count(), tree.len() - 1);
+    }
+}
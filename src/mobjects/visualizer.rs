@@ -0,0 +1,293 @@
+//! Audio visualizers
+//!
+//! An [`AudioVisualizer`] shows a sound's spectrum or waveform as bars or a
+//! line moving with it, for music-channel animations. Every update the
+//! scene measures the sound at the visualizer's playback time and resizes
+//! its bars (child rectangles) or reshapes its line.
+//! [`AudioVisualizer::sound_cue`] schedules the same sound on a timeline, so
+//! the preview and exported videos play it in sync.
+//!
+//! ```rust,no_run
+//! use diomanim::core::*;
+//! use diomanim::mobjects::AudioVisualizer;
+//! use diomanim::scene::SceneGraph;
+//!
+//! # fn example() -> Result<(), String> {
+//! let visualizer = AudioVisualizer::load("assets/song.wav", 32)?
+//!     .with_size(1.6, 0.6)
+//!     .starting_at(1.0);
+//!
+//! let mut timeline = Timeline::new();
+//! if let Some(cue) = visualizer.sound_cue() {
+//!     timeline.add_sound_cue(cue);
+//! }
+//! let mut scene = SceneGraph::new();
+//! scene
+//!     .add_audio_visualizer("spectrum", visualizer, Color::TEAL)
+//!     .at(0.0, -0.4, 0.0);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::audio::AudioSamples;
+use crate::core::{SoundCue, Vector3};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Fraction of its slot each bar is wide
+const BAR_FILL: f32 = 0.8;
+
+/// Seconds of sound a waveform spans
+const WAVEFORM_WINDOW: f32 = 0.05;
+
+/// How levels are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VisualizerStyle {
+    /// One rectangle per level
+    #[default]
+    Bars,
+    /// A polyline through the levels
+    Line,
+}
+
+/// What the levels measure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VisualizerSource {
+    /// Loudness of log-spaced frequency bands, rising from the bottom edge
+    #[default]
+    Spectrum,
+    /// The samples themselves, swinging about the middle
+    Waveform,
+}
+
+/// Bars or a line driven by a sound, added with
+/// [`SceneGraph::add_audio_visualizer`](crate::scene::SceneGraph::add_audio_visualizer)
+#[derive(Debug, Clone)]
+pub struct AudioVisualizer {
+    pub audio: Arc<AudioSamples>,
+    /// File the sound was loaded from, played by [`Self::sound_cue`]
+    pub path: Option<PathBuf>,
+    /// Number of bars or line points
+    pub bins: usize,
+    pub source: VisualizerSource,
+    pub style: VisualizerStyle,
+    /// Size of the box the levels are drawn in, centered on the node
+    pub width: f32,
+    pub height: f32,
+    /// Seconds for a falling spectrum level to drop by two thirds, so bars
+    /// jump up and settle back (0 follows the sound exactly)
+    pub decay: f32,
+    /// Scene time the sound starts playing
+    pub start_time: f32,
+    time: f32,
+    levels: Vec<f32>,
+}
+
+impl AudioVisualizer {
+    pub fn new(audio: AudioSamples, bins: usize) -> Self {
+        let bins = bins.max(1);
+        Self {
+            audio: Arc::new(audio),
+            path: None,
+            bins,
+            source: VisualizerSource::Spectrum,
+            style: VisualizerStyle::Bars,
+            width: 1.0,
+            height: 0.4,
+            decay: 0.15,
+            start_time: 0.0,
+            time: 0.0,
+            levels: vec![0.0; bins],
+        }
+    }
+
+    /// Visualize the audio file at `path` (see [`AudioSamples::load`])
+    pub fn load(path: impl AsRef<Path>, bins: usize) -> Result<Self, String> {
+        let mut visualizer = Self::new(AudioSamples::load(path.as_ref())?, bins);
+        visualizer.path = Some(path.as_ref().to_path_buf());
+        Ok(visualizer)
+    }
+
+    pub fn with_style(mut self, style: VisualizerStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn with_source(mut self, source: VisualizerSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn with_size(mut self, width: f32, height: f32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn with_decay(mut self, seconds: f32) -> Self {
+        self.decay = seconds.max(0.0);
+        self
+    }
+
+    /// Start the sound `seconds` into the scene
+    pub fn starting_at(mut self, seconds: f32) -> Self {
+        self.start_time = seconds;
+        self
+    }
+
+    /// The sound scheduled to play when the visualizer starts, for the
+    /// timeline of the preview or export (`None` for sounds not loaded
+    /// from a file)
+    pub fn sound_cue(&self) -> Option<SoundCue> {
+        self.path
+            .as_ref()
+            .map(|path| SoundCue::new(path.clone(), self.start_time))
+    }
+
+    /// Current levels: `0..1` for spectra, `-1..1` for waveforms
+    pub fn levels(&self) -> &[f32] {
+        &self.levels
+    }
+
+    /// Move `delta_time` seconds on and measure the sound there (silence
+    /// before it starts and after it ends)
+    pub fn advance(&mut self, delta_time: f32) {
+        self.time += delta_time;
+        let playing = self.time - self.start_time;
+        let measured = if (0.0..=self.audio.duration()).contains(&playing) {
+            match self.source {
+                VisualizerSource::Spectrum => self.audio.spectrum(playing, self.bins),
+                VisualizerSource::Waveform => {
+                    self.audio.waveform(playing, WAVEFORM_WINDOW, self.bins)
+                }
+            }
+        } else {
+            vec![0.0; self.bins]
+        };
+
+        if self.source == VisualizerSource::Spectrum && self.decay > 0.0 {
+            let falloff = (-delta_time.max(0.0) / self.decay).exp();
+            for (level, measured) in self.levels.iter_mut().zip(measured) {
+                *level = measured.max(*level * falloff);
+            }
+        } else {
+            self.levels = measured;
+        }
+    }
+
+    /// Width of each bar
+    pub fn bar_width(&self) -> f32 {
+        self.width / self.bins as f32 * BAR_FILL
+    }
+
+    /// Center and height of each bar in the node's space
+    pub fn bars(&self) -> Vec<(Vector3, f32)> {
+        self.levels
+            .iter()
+            .enumerate()
+            .map(|(bin, &level)| {
+                let x = self.bin_x(bin);
+                match self.source {
+                    VisualizerSource::Spectrum => {
+                        let height = level * self.height;
+                        (Vector3::new(x, (height - self.height) / 2.0, 0.0), height)
+                    }
+                    VisualizerSource::Waveform => (
+                        Vector3::new(x, level * self.height / 4.0, 0.0),
+                        level.abs() * self.height / 2.0,
+                    ),
+                }
+            })
+            .collect()
+    }
+
+    /// Points of the line in the node's space
+    pub fn line(&self) -> Vec<Vector3> {
+        self.levels
+            .iter()
+            .enumerate()
+            .map(|(bin, &level)| {
+                let y = match self.source {
+                    VisualizerSource::Spectrum => (level - 0.5) * self.height,
+                    VisualizerSource::Waveform => level * self.height / 2.0,
+                };
+                Vector3::new(self.bin_x(bin), y, 0.0)
+            })
+            .collect()
+    }
+
+    /// Center of the slot of bar or point `bin`
+    fn bin_x(&self, bin: usize) -> f32 {
+        (bin as f32 + 0.5) / self.bins as f32 * self.width - self.width / 2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Color, TimeValue};
+    use crate::scene::{NodeId, Renderable, SceneGraph};
+
+    fn tone() -> AudioSamples {
+        let rate = 44_100;
+        AudioSamples::new(
+            rate,
+            (0..rate)
+                .map(|i| (std::f32::consts::TAU * 1000.0 * i as f32 / rate as f32).sin())
+                .collect(),
+        )
+    }
+
+    fn bar_height(scene: &SceneGraph, bar: NodeId) -> f32 {
+        match scene.get_node(bar).unwrap().renderable {
+            Some(Renderable::Rectangle { height, .. }) => height,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_visualizer_follows_sound() {
+        let mut scene = SceneGraph::new();
+        let bars = scene
+            .add_audio_visualizer(
+                "bars",
+                AudioVisualizer::new(tone(), 10).starting_at(0.25),
+                Color::TEAL,
+            )
+            .id();
+        let line = scene
+            .add_audio_visualizer(
+                "line",
+                AudioVisualizer::new(tone(), 10).with_style(VisualizerStyle::Line),
+                Color::TEAL,
+            )
+            .id();
+
+        // Silent until the sound starts
+        scene.update_animations(TimeValue::new(0.2));
+        let children = scene.get_node(bars).unwrap().children.clone();
+        assert_eq!(children.len(), 10);
+        assert_eq!(bar_height(&scene, children[5]), 0.0);
+
+        // The band holding 1 kHz stands tallest, its bottom on the baseline
+        scene.update_animations(TimeValue::new(0.3));
+        let heights: Vec<f32> = children
+            .iter()
+            .map(|&bar| bar_height(&scene, bar))
+            .collect();
+        let tallest = (0..10)
+            .max_by(|&a, &b| heights[a].total_cmp(&heights[b]))
+            .unwrap();
+        assert_eq!(tallest, 5);
+        let bar = scene.get_node(children[5]).unwrap();
+        let bottom = bar._local_transform.position.y - heights[5] / 2.0;
+        assert!((bottom + 0.2).abs() < 1e-5);
+
+        let Some(Renderable::Polyline { points, .. }) = &scene.get_node(line).unwrap().renderable
+        else {
+            unreachable!();
+        };
+        assert_eq!(points.len(), 10);
+        assert!(points[5].y > points[0].y);
+    }
+}
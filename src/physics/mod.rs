@@ -0,0 +1,506 @@
+//! # Physics
+//!
+//! Rigid bodies and colliders simulated with rapier2d (`physics` feature),
+//! for bouncing balls, falling dominoes and other dynamic scenes.
+//!
+//! A [`PhysicsWorld`] belongs to a scene: nodes get bodies with
+//! [`SceneGraph::add_rigid_body`](crate::scene::SceneGraph::add_rigid_body),
+//! and every animation update steps the world by fixed timesteps and moves
+//! dynamic nodes to where their bodies went. Fixed steps keep the
+//! simulation deterministic: the same scene produces the same motion in the
+//! preview and in every export, whatever the frame rate.
+//!
+//! Bodies can be joined by springs, ropes and hinges; [`springs`] builds
+//! drawn springs, pendulums and soft chains from them.
+//!
+//! ```rust
+//! use diomanim::core::*;
+//! use diomanim::physics::RigidBody;
+//! use diomanim::scene::SceneGraph;
+//!
+//! # fn example() -> Result<(), String> {
+//! let mut scene = SceneGraph::new();
+//! let floor = scene
+//!     .add_rectangle("floor", 2.0, 0.05, Color::GRAY)
+//!     .at(0.0, -0.8, 0.0)
+//!     .build();
+//! let ball = scene.add_circle("ball", 0.08, Color::RED).at(0.0, 0.8, 0.0).build();
+//! scene.add_rigid_body(floor, RigidBody::fixed())?;
+//! scene.add_rigid_body(ball, RigidBody::dynamic().with_restitution(0.8))?;
+//!
+//! scene.update_animations(TimeValue::new(0.5));
+//! assert!(scene.get_node(ball).unwrap().world_transform.position.y < 0.8);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::core::Vector3;
+use crate::scene::{NodeId, Renderable};
+use rapier2d::na::{Isometry2, Point2, Vector2};
+use rapier2d::prelude::{
+    CCDSolver, CoefficientCombineRule, ColliderBuilder, ColliderSet, DefaultBroadPhase,
+    GenericJoint, ImpulseJointSet, IntegrationParameters, IslandManager, MultibodyJointSet,
+    NarrowPhase, PhysicsPipeline, QueryPipeline, RevoluteJointBuilder, RigidBodyBuilder,
+    RigidBodyHandle, RigidBodySet, RopeJointBuilder, SpringJointBuilder,
+};
+use std::collections::HashMap;
+
+pub mod springs;
+
+pub use springs::{Pendulum, SoftChain, Spring};
+
+/// Default simulation step, in seconds
+pub const DEFAULT_TIMESTEP: f32 = 1.0 / 120.0;
+
+/// How a body moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BodyKind {
+    /// Moved by gravity, collisions and impulses
+    #[default]
+    Dynamic,
+    /// Never moves, for floors and walls
+    Fixed,
+    /// Follows its node's animation, pushing dynamic bodies out of the way
+    Kinematic,
+}
+
+/// Outline a body collides with, in its node's space
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColliderShape {
+    Ball {
+        radius: f32,
+    },
+    Cuboid {
+        width: f32,
+        height: f32,
+    },
+    /// Convex hull of the points
+    ConvexHull(Vec<Vector3>),
+    /// Segment without thickness, for floors and ramps
+    Segment {
+        start: Vector3,
+        end: Vector3,
+    },
+}
+
+impl ColliderShape {
+    /// The outline of a circle, rectangle, polygon, line, image or SVG
+    pub fn from_renderable(renderable: &Renderable) -> Option<Self> {
+        match renderable {
+            Renderable::Circle { radius, .. } => Some(Self::Ball { radius: *radius }),
+            Renderable::Rectangle { width, height, .. }
+            | Renderable::Image { width, height, .. }
+            | Renderable::Svg { width, height, .. } => Some(Self::Cuboid {
+                width: *width,
+                height: *height,
+            }),
+            Renderable::Polygon { vertices, .. } => Some(Self::ConvexHull(vertices.clone())),
+            Renderable::Line { start, end, .. } | Renderable::Arrow { start, end, .. } => {
+                Some(Self::Segment {
+                    start: *start,
+                    end: *end,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// A rapier collider for the shape scaled by `scale` (`None` for hulls
+    /// of fewer than three distinct points)
+    fn collider(&self, scale: Vector3) -> Option<ColliderBuilder> {
+        let point = |p: &Vector3| Point2::new(p.x * scale.x, p.y * scale.y);
+        match self {
+            Self::Ball { radius } => Some(ColliderBuilder::ball(
+                radius * scale.x.abs().max(scale.y.abs()),
+            )),
+            Self::Cuboid { width, height } => Some(ColliderBuilder::cuboid(
+                width * scale.x.abs() / 2.0,
+                height * scale.y.abs() / 2.0,
+            )),
+            Self::ConvexHull(points) => {
+                let points: Vec<Point2<f32>> = points.iter().map(point).collect();
+                ColliderBuilder::convex_hull(&points)
+            }
+            Self::Segment { start, end } => {
+                Some(ColliderBuilder::segment(point(start), point(end)))
+            }
+        }
+    }
+}
+
+/// A body to give a node, see
+/// [`SceneGraph::add_rigid_body`](crate::scene::SceneGraph::add_rigid_body)
+#[derive(Debug, Clone, PartialEq)]
+pub struct RigidBody {
+    pub kind: BodyKind,
+    /// Outline, taken from the node's shape when `None`
+    pub shape: Option<ColliderShape>,
+    /// Bounciness, from 0 (stops dead) to 1 (keeps all its speed); the
+    /// bouncier of two bodies sets how they bounce off each other
+    pub restitution: f32,
+    pub friction: f32,
+    /// Mass per unit area
+    pub density: f32,
+    /// Starting velocity in units per second
+    pub velocity: Vector3,
+    /// Starting spin in radians per second, counterclockwise
+    pub angular_velocity: f32,
+}
+
+impl RigidBody {
+    fn new(kind: BodyKind) -> Self {
+        Self {
+            kind,
+            shape: None,
+            restitution: 0.0,
+            friction: 0.5,
+            density: 1.0,
+            velocity: Vector3::zero(),
+            angular_velocity: 0.0,
+        }
+    }
+
+    pub fn dynamic() -> Self {
+        Self::new(BodyKind::Dynamic)
+    }
+
+    pub fn fixed() -> Self {
+        Self::new(BodyKind::Fixed)
+    }
+
+    pub fn kinematic() -> Self {
+        Self::new(BodyKind::Kinematic)
+    }
+
+    pub fn with_shape(mut self, shape: ColliderShape) -> Self {
+        self.shape = Some(shape);
+        self
+    }
+
+    pub fn with_restitution(mut self, restitution: f32) -> Self {
+        self.restitution = restitution;
+        self
+    }
+
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    pub fn with_density(mut self, density: f32) -> Self {
+        self.density = density;
+        self
+    }
+
+    pub fn with_velocity(mut self, velocity: Vector3) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    pub fn with_angular_velocity(mut self, angular_velocity: f32) -> Self {
+        self.angular_velocity = angular_velocity;
+        self
+    }
+}
+
+/// The bodies of a scene and the rapier state simulating them
+pub struct PhysicsWorld {
+    /// Acceleration of dynamic bodies, in units per second squared
+    pub gravity: Vector3,
+    /// Seconds per simulation step
+    pub timestep: f32,
+    /// Time passed but not yet simulated, less than one step
+    accumulator: f32,
+    handles: HashMap<NodeId, RigidBodyHandle>,
+    /// Poses kinematic bodies are moving to over the current update
+    kinematic_targets: HashMap<NodeId, Isometry2<f32>>,
+    pipeline: PhysicsPipeline,
+    islands: IslandManager,
+    broad_phase: DefaultBroadPhase,
+    narrow_phase: NarrowPhase,
+    bodies: RigidBodySet,
+    colliders: ColliderSet,
+    impulse_joints: ImpulseJointSet,
+    multibody_joints: MultibodyJointSet,
+    ccd_solver: CCDSolver,
+    query_pipeline: QueryPipeline,
+}
+
+impl PhysicsWorld {
+    /// An empty world with Earth's gravity (a scene unit taken as a metre)
+    pub fn new() -> Self {
+        Self {
+            gravity: Vector3::new(0.0, -9.81, 0.0),
+            timestep: DEFAULT_TIMESTEP,
+            accumulator: 0.0,
+            handles: HashMap::new(),
+            kinematic_targets: HashMap::new(),
+            pipeline: PhysicsPipeline::new(),
+            islands: IslandManager::new(),
+            broad_phase: DefaultBroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            bodies: RigidBodySet::new(),
+            colliders: ColliderSet::new(),
+            impulse_joints: ImpulseJointSet::new(),
+            multibody_joints: MultibodyJointSet::new(),
+            ccd_solver: CCDSolver::new(),
+            query_pipeline: QueryPipeline::new(),
+        }
+    }
+
+    pub fn with_gravity(mut self, gravity: Vector3) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// Simulate in steps of `seconds` (smaller is more accurate and slower)
+    pub fn with_timestep(mut self, seconds: f32) -> Self {
+        self.timestep = seconds.max(1e-4);
+        self
+    }
+
+    /// Give `node` a body posed at `position` and `angle`, its shape scaled
+    /// by `scale`, replacing any body it had
+    pub fn insert(
+        &mut self,
+        node: NodeId,
+        body: &RigidBody,
+        shape: &ColliderShape,
+        position: Vector3,
+        angle: f32,
+        scale: Vector3,
+    ) -> Result<(), String> {
+        let collider = shape
+            .collider(scale)
+            .ok_or("A convex hull needs at least three distinct points")?
+            .restitution(body.restitution)
+            .restitution_combine_rule(CoefficientCombineRule::Max)
+            .friction(body.friction)
+            .density(body.density)
+            .build();
+        let builder = match body.kind {
+            BodyKind::Dynamic => RigidBodyBuilder::dynamic(),
+            BodyKind::Fixed => RigidBodyBuilder::fixed(),
+            BodyKind::Kinematic => RigidBodyBuilder::kinematic_position_based(),
+        };
+        let rigid_body = builder
+            .translation(Vector2::new(position.x, position.y))
+            .rotation(angle)
+            .linvel(Vector2::new(body.velocity.x, body.velocity.y))
+            .angvel(body.angular_velocity)
+            .build();
+
+        self.remove(node);
+        let handle = self.bodies.insert(rigid_body);
+        self.colliders
+            .insert_with_parent(collider, handle, &mut self.bodies);
+        self.handles.insert(node, handle);
+        Ok(())
+    }
+
+    /// Take away `node`'s body, returning whether it had one
+    pub fn remove(&mut self, node: NodeId) -> bool {
+        self.kinematic_targets.remove(&node);
+        let Some(handle) = self.handles.remove(&node) else {
+            return false;
+        };
+        self.bodies.remove(
+            handle,
+            &mut self.islands,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            true,
+        );
+        true
+    }
+
+    pub fn contains(&self, node: NodeId) -> bool {
+        self.handles.contains_key(&node)
+    }
+
+    /// Nodes with bodies
+    pub fn nodes(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.handles.keys().copied()
+    }
+
+    /// How `node`'s body moves
+    pub fn kind(&self, node: NodeId) -> Option<BodyKind> {
+        let body = self.bodies.get(*self.handles.get(&node)?)?;
+        Some(if body.is_dynamic() {
+            BodyKind::Dynamic
+        } else if body.is_kinematic() {
+            BodyKind::Kinematic
+        } else {
+            BodyKind::Fixed
+        })
+    }
+
+    /// Position and counterclockwise angle of `node`'s body
+    pub fn pose(&self, node: NodeId) -> Option<(Vector3, f32)> {
+        let body = self.bodies.get(*self.handles.get(&node)?)?;
+        let translation = body.translation();
+        Some((
+            Vector3::new(translation.x, translation.y, 0.0),
+            body.rotation().angle(),
+        ))
+    }
+
+    /// Velocity of `node`'s body in units per second
+    pub fn velocity(&self, node: NodeId) -> Option<Vector3> {
+        let body = self.bodies.get(*self.handles.get(&node)?)?;
+        let velocity = body.linvel();
+        Some(Vector3::new(velocity.x, velocity.y, 0.0))
+    }
+
+    pub fn set_velocity(&mut self, node: NodeId, velocity: Vector3) {
+        if let Some(body) = self.body_mut(node) {
+            body.set_linvel(Vector2::new(velocity.x, velocity.y), true);
+        }
+    }
+
+    /// Kick `node`'s body, changing its momentum by `impulse`
+    pub fn apply_impulse(&mut self, node: NodeId, impulse: Vector3) {
+        if let Some(body) = self.body_mut(node) {
+            body.apply_impulse(Vector2::new(impulse.x, impulse.y), true);
+        }
+    }
+
+    fn body_mut(&mut self, node: NodeId) -> Option<&mut rapier2d::prelude::RigidBody> {
+        self.bodies.get_mut(*self.handles.get(&node)?)
+    }
+
+    fn handle(&self, node: NodeId) -> Result<RigidBodyHandle, String> {
+        self.handles
+            .get(&node)
+            .copied()
+            .ok_or_else(|| format!("Node {node:?} has no rigid body"))
+    }
+
+    /// Join the centers of `a` and `b` with a spring pulling or pushing
+    /// them toward `rest_length` apart
+    pub fn add_spring(
+        &mut self,
+        a: NodeId,
+        b: NodeId,
+        rest_length: f32,
+        stiffness: f32,
+        damping: f32,
+    ) -> Result<(), String> {
+        self.insert_joint(
+            a,
+            b,
+            SpringJointBuilder::new(rest_length, stiffness, damping),
+        )
+    }
+
+    /// Tie the centers of `a` and `b` with a rope, keeping them at most
+    /// `length` apart
+    pub fn add_rope(&mut self, a: NodeId, b: NodeId, length: f32) -> Result<(), String> {
+        self.insert_joint(a, b, RopeJointBuilder::new(length))
+    }
+
+    /// Pin `a` and `b` together at the scene point `anchor`, around which
+    /// they turn freely
+    pub fn add_hinge(&mut self, a: NodeId, b: NodeId, anchor: Vector3) -> Result<(), String> {
+        let anchor = Point2::new(anchor.x, anchor.y);
+        let local = |node| -> Result<Point2<f32>, String> {
+            let body = &self.bodies[self.handle(node)?];
+            Ok(body.position().inverse_transform_point(&anchor))
+        };
+        let (anchor_a, anchor_b) = (local(a)?, local(b)?);
+        self.insert_joint(
+            a,
+            b,
+            RevoluteJointBuilder::new()
+                .local_anchor1(anchor_a)
+                .local_anchor2(anchor_b),
+        )
+    }
+
+    /// Add a joint between two bodies, which no longer collide with each
+    /// other
+    fn insert_joint(
+        &mut self,
+        a: NodeId,
+        b: NodeId,
+        joint: impl Into<GenericJoint>,
+    ) -> Result<(), String> {
+        let (a, b) = (self.handle(a)?, self.handle(b)?);
+        let mut joint = joint.into();
+        joint.set_contacts_enabled(false);
+        self.impulse_joints.insert(a, b, joint, true);
+        Ok(())
+    }
+
+    /// Move kinematic `node` to `position` and `angle` over the next update
+    pub fn set_kinematic_target(&mut self, node: NodeId, position: Vector3, angle: f32) {
+        if self.kind(node) == Some(BodyKind::Kinematic) {
+            self.kinematic_targets.insert(
+                node,
+                Isometry2::new(Vector2::new(position.x, position.y), angle),
+            );
+        }
+    }
+
+    /// Simulate `delta_time` seconds in as many whole steps as fit, carrying
+    /// the remainder over to the next call, and return the steps taken
+    pub fn advance(&mut self, delta_time: f32) -> usize {
+        self.accumulator += delta_time.max(0.0);
+        // A hair of slack so frame times that are whole steps don't drop one
+        let steps = ((self.accumulator + 1e-6) / self.timestep).floor() as usize;
+        self.accumulator = (self.accumulator - steps as f32 * self.timestep).max(0.0);
+        for step in 0..steps {
+            self.move_kinematic_bodies(steps - step);
+            self.step();
+        }
+        self.kinematic_targets.clear();
+        steps
+    }
+
+    /// Carry kinematic bodies a `1 / remaining_steps` of the way to their
+    /// targets, so they sweep there over the update instead of teleporting
+    fn move_kinematic_bodies(&mut self, remaining_steps: usize) {
+        let fraction = 1.0 / remaining_steps as f32;
+        for (node, target) in &self.kinematic_targets {
+            let Some(body) = self
+                .handles
+                .get(node)
+                .and_then(|&handle| self.bodies.get_mut(handle))
+            else {
+                continue;
+            };
+            let next = body.position().lerp_slerp(target, fraction);
+            body.set_next_kinematic_position(next);
+        }
+    }
+
+    /// Run one simulation step
+    pub fn step(&mut self) {
+        let parameters = IntegrationParameters {
+            dt: self.timestep,
+            ..IntegrationParameters::default()
+        };
+        self.pipeline.step(
+            &Vector2::new(self.gravity.x, self.gravity.y),
+            &parameters,
+            &mut self.islands,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            &mut self.ccd_solver,
+            Some(&mut self.query_pipeline),
+            &(),
+            &(),
+        );
+    }
+}
+
+impl Default for PhysicsWorld {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,362 @@
+//! Springs, pendulums and soft chains
+//!
+//! Joined bodies drawn with ordinary lines and polylines that follow them
+//! (see [`Updater`]): a zigzag [`Spring`] stretching between two bodies, a
+//! [`Pendulum`] swinging on a rigid rod, and a [`SoftChain`] of beads held
+//! together by springs, which sags and wobbles like a rope or soft body.
+//!
+//! ```rust
+//! use diomanim::core::*;
+//! use diomanim::physics::{RigidBody, Spring};
+//! use diomanim::scene::SceneGraph;
+//!
+//! # fn example() -> Result<(), String> {
+//! let mut scene = SceneGraph::new();
+//! let ceiling = scene
+//!     .add_rectangle("ceiling", 0.4, 0.05, Color::GRAY)
+//!     .at(0.0, 0.8, 0.0)
+//!     .build();
+//! let weight = scene.add_square("weight", 0.15, Color::BLUE).at(0.0, 0.2, 0.0).build();
+//! scene.add_rigid_body(ceiling, RigidBody::fixed())?;
+//! scene.add_rigid_body(weight, RigidBody::dynamic())?;
+//! scene.add_spring("spring", ceiling, weight, Spring::new(40.0).with_coils(8), None)?;
+//!
+//! scene.add_pendulum("pendulum", Vector3::new(0.6, 0.8, 0.0), 0.5, 0.4, 0.05, Color::RED)?;
+//! scene.add_soft_chain(
+//!     "rope",
+//!     Vector3::new(-0.9, 0.5, 0.0),
+//!     Vector3::new(-0.3, 0.5, 0.0),
+//!     12,
+//!     Spring::new(200.0),
+//!     true,
+//!     None,
+//! )?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{ColliderShape, RigidBody};
+use crate::core::{Color, Vector3};
+use crate::scene::{NodeId, Renderable, SceneGraph, SceneNode, Updater};
+
+/// Fraction of a spring's length drawn straight at each end
+const SPRING_LEAD: f32 = 0.1;
+
+/// Bead radius of a soft chain, as a fraction of the bead spacing
+const BEAD_RADIUS: f32 = 0.25;
+
+/// A spring's physics and how its zigzag is drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spring {
+    /// Length the spring pulls or pushes toward (the bodies' distance when
+    /// it is added if `None`)
+    pub rest_length: Option<f32>,
+    /// Force per unit of stretch
+    pub stiffness: f32,
+    /// How quickly oscillations die down
+    pub damping: f32,
+    /// Zigzag peaks drawn
+    pub coils: usize,
+    /// Width of the zigzag
+    pub width: f32,
+}
+
+impl Spring {
+    pub fn new(stiffness: f32) -> Self {
+        Self {
+            rest_length: None,
+            stiffness,
+            damping: 0.5,
+            coils: 6,
+            width: 0.08,
+        }
+    }
+
+    pub fn with_rest_length(mut self, rest_length: f32) -> Self {
+        self.rest_length = Some(rest_length);
+        self
+    }
+
+    pub fn with_damping(mut self, damping: f32) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    pub fn with_coils(mut self, coils: usize) -> Self {
+        self.coils = coils.max(1);
+        self
+    }
+
+    pub fn with_width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Points of the zigzag from `start` to `end`, straight for a short
+    /// lead at each end
+    pub fn zigzag(&self, start: Vector3, end: Vector3) -> Vec<Vector3> {
+        let along = end - start;
+        let length = along.length();
+        let normal = if length > f32::EPSILON {
+            Vector3::new(-along.y, along.x, 0.0) * (self.width / 2.0 / length)
+        } else {
+            Vector3::new(0.0, self.width / 2.0, 0.0)
+        };
+        let peaks = self.coils * 2;
+        let mut points = Vec::with_capacity(peaks + 4);
+        points.push(start);
+        points.push(start + along * SPRING_LEAD);
+        for peak in 0..peaks {
+            let t = SPRING_LEAD + (1.0 - 2.0 * SPRING_LEAD) * (peak as f32 + 0.5) / peaks as f32;
+            let side = if peak % 2 == 0 { 1.0 } else { -1.0 };
+            points.push(start + along * t + normal * side);
+        }
+        points.push(end - along * SPRING_LEAD);
+        points.push(end);
+        points
+    }
+}
+
+/// Nodes of a pendulum added with [`SceneGraph::add_pendulum`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pendulum {
+    /// Kinematic dot the rod hangs from; animate it to drag the pendulum
+    pub pivot: NodeId,
+    pub bob: NodeId,
+    pub rod: NodeId,
+}
+
+/// Nodes of a chain added with [`SceneGraph::add_soft_chain`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SoftChain {
+    pub beads: Vec<NodeId>,
+    /// Polyline through the beads
+    pub line: NodeId,
+}
+
+/// Scene position of `id` in the local space of `node`
+fn local_position(scene: &SceneGraph, node: &SceneNode, id: NodeId) -> Option<Vector3> {
+    let position = scene.get_node(id)?.world_transform.position;
+    Some(node.world_transform.inverse().transform_point(position))
+}
+
+impl SceneGraph {
+    /// Join bodies `a` and `b` with `spring`, drawn as a zigzag between
+    /// their centers
+    pub fn add_spring(
+        &mut self,
+        name: impl Into<String>,
+        a: NodeId,
+        b: NodeId,
+        spring: Spring,
+        color: impl Into<Option<Color>>,
+    ) -> Result<NodeId, String> {
+        self.update_transforms();
+        let rest_length = match spring.rest_length {
+            Some(rest_length) => rest_length,
+            None => {
+                let position = |id| {
+                    self.get_node(id)
+                        .map(|node| node.world_transform.position)
+                        .ok_or_else(|| format!("Node {id:?} does not exist"))
+                };
+                position(a)?.distance(&position(b)?)
+            }
+        };
+        self.physics_mut()
+            .ok_or("Springs join rigid bodies; add them first")?
+            .add_spring(a, b, rest_length, spring.stiffness, spring.damping)?;
+
+        let color = color.into().unwrap_or(self.theme().foreground);
+        let thickness = self.theme().stroke_width;
+        let updater = Updater::new(move |scene, node| {
+            let start = local_position(scene, node, a)?;
+            let end = local_position(scene, node, b)?;
+            Some(Renderable::Polyline {
+                points: spring.zigzag(start, end),
+                color,
+                thickness,
+                fade: 0.0,
+            })
+        });
+        Ok(self.add_always_redraw(name, updater).build())
+    }
+
+    /// Add a bob of `bob_radius` on a rigid rod of `length` hanging from
+    /// `pivot`, let go `angle` radians counterclockwise from straight down
+    pub fn add_pendulum(
+        &mut self,
+        name: impl Into<String>,
+        pivot: Vector3,
+        length: f32,
+        angle: f32,
+        bob_radius: f32,
+        color: impl Into<Option<Color>>,
+    ) -> Result<Pendulum, String> {
+        let name = name.into();
+        let color = color.into().unwrap_or(self.theme().fill);
+        let bob_position = pivot + Vector3::new(angle.sin(), -angle.cos(), 0.0) * length;
+        let pivot_radius = bob_radius / 3.0;
+
+        let pivot_id = self
+            .add_circle(format!("{name}_pivot"), pivot_radius, color)
+            .at_vec(pivot)
+            .build();
+        let bob = self
+            .add_circle(format!("{name}_bob"), bob_radius, color)
+            .at_vec(bob_position)
+            .build();
+        let rod = self
+            .add_always_redraw(
+                format!("{name}_rod"),
+                Updater::line_between(pivot_id, bob, color, self.theme().stroke_width),
+            )
+            .build();
+        self.add_rigid_body(pivot_id, RigidBody::kinematic())?;
+        self.add_rigid_body(bob, RigidBody::dynamic())?;
+        self.physics_mut()
+            .ok_or("Pendulum has no physics world")?
+            .add_hinge(pivot_id, bob, pivot)?;
+        Ok(Pendulum {
+            pivot: pivot_id,
+            bob,
+            rod,
+        })
+    }
+
+    /// Add `beads` beads from `start` to `end` held together by springs to
+    /// their next and next-but-one neighbours, so the chain resists bending
+    /// as well as stretching; `pin_ends` holds the end beads in place
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_soft_chain(
+        &mut self,
+        name: impl Into<String>,
+        start: Vector3,
+        end: Vector3,
+        beads: usize,
+        spring: Spring,
+        pin_ends: bool,
+        color: impl Into<Option<Color>>,
+    ) -> Result<SoftChain, String> {
+        let name = name.into();
+        let color = color.into().unwrap_or(self.theme().foreground);
+        let beads = beads.max(2);
+        let spacing = start.distance(&end) / (beads - 1) as f32;
+        let radius = spacing * BEAD_RADIUS;
+
+        let ids: Vec<NodeId> = (0..beads)
+            .map(|i| {
+                let t = i as f32 / (beads - 1) as f32;
+                self.add_circle(format!("{name}_bead_{i}"), radius, color)
+                    .at_vec(start + (end - start) * t)
+                    .build()
+            })
+            .collect();
+        for (i, &bead) in ids.iter().enumerate() {
+            let pinned = pin_ends && (i == 0 || i == beads - 1);
+            let body = if pinned {
+                RigidBody::kinematic()
+            } else {
+                RigidBody::dynamic()
+            };
+            self.add_rigid_body(bead, body.with_shape(ColliderShape::Ball { radius }))?;
+        }
+        let rest_length = spring.rest_length.unwrap_or(spacing);
+        let world = self
+            .physics_mut()
+            .ok_or("Soft chain has no physics world")?;
+        for reach in 1..=2 {
+            for pair in ids.windows(reach + 1) {
+                world.add_spring(
+                    pair[0],
+                    pair[reach],
+                    rest_length * reach as f32,
+                    spring.stiffness,
+                    spring.damping,
+                )?;
+            }
+        }
+
+        let thickness = self.theme().stroke_width;
+        let beads_drawn = ids.clone();
+        let line = self
+            .add_always_redraw(
+                format!("{name}_line"),
+                Updater::new(move |scene, node| {
+                    let points = beads_drawn
+                        .iter()
+                        .map(|&bead| local_position(scene, node, bead))
+                        .collect::<Option<Vec<_>>>()?;
+                    Some(Renderable::Polyline {
+                        points,
+                        color,
+                        thickness,
+                        fade: 0.0,
+                    })
+                }),
+            )
+            .build();
+        Ok(SoftChain { beads: ids, line })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::TimeValue;
+
+    #[test]
+    fn test_spring_zigzag() {
+        let points = Spring::new(10.0)
+            .with_coils(3)
+            .with_width(0.2)
+            .zigzag(Vector3::zero(), Vector3::new(1.0, 0.0, 0.0));
+        assert_eq!(points.len(), 10);
+        assert_eq!(points[1], Vector3::new(0.1, 0.0, 0.0));
+        assert!((points[2].y - 0.1).abs() < 1e-6 && (points[3].y + 0.1).abs() < 1e-6);
+        assert_eq!(points[9], Vector3::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_pendulum_swings_and_chain_sags() {
+        let mut scene = SceneGraph::new();
+        let pivot = Vector3::new(0.0, 0.5, 0.0);
+        let pendulum = scene
+            .add_pendulum("pendulum", pivot, 0.5, 0.3, 0.05, None)
+            .unwrap();
+        let chain = scene
+            .add_soft_chain(
+                "chain",
+                Vector3::new(1.0, 0.5, 0.0),
+                Vector3::new(2.0, 0.5, 0.0),
+                9,
+                Spring::new(500.0),
+                true,
+                None,
+            )
+            .unwrap();
+
+        // Half a period of sqrt(L / g) * PI later it has swung to the
+        // other side, still on its rod
+        for _ in 0..21 {
+            scene.update_animations(TimeValue::new(1.0 / 30.0));
+        }
+        let bob = scene
+            .get_node(pendulum.bob)
+            .unwrap()
+            .world_transform
+            .position;
+        assert!(bob.x < -0.1);
+        assert!((bob.distance(&pivot) - 0.5).abs() < 0.01);
+
+        let y = |id| scene.get_node(id).unwrap().world_transform.position.y;
+        assert_eq!(y(chain.beads[0]), 0.5);
+        assert!(y(chain.beads[4]) < 0.45);
+        let Some(Renderable::Polyline { points, .. }) =
+            &scene.get_node(chain.line).unwrap().renderable
+        else {
+            unreachable!();
+        };
+        assert_eq!(points.len(), 9);
+    }
+}
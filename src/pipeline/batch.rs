@@ -0,0 +1,217 @@
+//! Batch export to several resolutions and formats
+//!
+//! An [`ExportBatch`] lists every video one render should produce, such as
+//! a 1080p MP4, a 720p WebM and a 480p GIF. [`render_batch`] renders the
+//! scene once per distinct resolution and encodes each target of that
+//! resolution from the same frames, so an MP4 and a WebM at 1080p cost a
+//! single render.
+//!
+//! Each resolution's frames go to a `<width>x<height>` directory under
+//! `config.frames_dir`. A config with a framing (see [`super::framing`]) is
+//! re-framed for each resolution's shape, so one batch can hold landscape,
+//! vertical and square targets.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use diomanim::pipeline::batch::{render_batch, ExportBatch, ExportTarget};
+//! use diomanim::pipeline::{ConsoleProgress, FramePreset, RenderConfig};
+//! use diomanim::prelude::*;
+//! use diomanim::scene::SceneGraph;
+//!
+//! # async fn example(build_scene: impl FnMut() -> SceneGraph) -> Result<(), Box<dyn std::error::Error>> {
+//! let mut renderer = ShapeRenderer::new(1920, 1080).await?;
+//! let config = RenderConfig::new(1920, 1080, 30, 10.0)
+//!     .with_aspect_framing()
+//!     .with_frames_dir("output/frames");
+//! let batch = ExportBatch::new()
+//!     .with_target(ExportTarget::new("output/video.mp4", 1920, 1080))
+//!     .with_target(ExportTarget::new("output/video.webm", 1280, 720))
+//!     .with_target(ExportTarget::new("output/clip.gif", 854, 480))
+//!     .with_target(ExportTarget::preset("output/short.mp4", FramePreset::Vertical));
+//! render_batch(&mut renderer, build_scene, &config, &batch, &mut ConsoleProgress::default())?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::framing::FramePreset;
+use super::{render_frames_with_progress, ProgressSink, RenderConfig, RenderStage};
+use crate::core::{Matrix4, SoundCue};
+use crate::export::{export_video_ffmpeg_with_progress, VideoCodec, VideoExportSettings};
+use crate::render::ShapeRenderer;
+use crate::scene::SceneGraph;
+use std::path::PathBuf;
+
+/// One video of a batch
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportTarget {
+    pub output: PathBuf,
+    pub width: u32,
+    pub height: u32,
+    pub codec: VideoCodec,
+}
+
+impl ExportTarget {
+    /// `output` at `width` x `height`, encoded with the codec its extension
+    /// names (H.264 if none does)
+    pub fn new(output: impl Into<PathBuf>, width: u32, height: u32) -> Self {
+        let output = output.into();
+        let codec = output
+            .extension()
+            .and_then(|extension| VideoCodec::from_extension(&extension.to_string_lossy()))
+            .unwrap_or_default();
+        Self {
+            output,
+            width,
+            height,
+            codec,
+        }
+    }
+
+    /// `output` at `preset`'s size
+    pub fn preset(output: impl Into<PathBuf>, preset: FramePreset) -> Self {
+        let (width, height) = preset.size();
+        Self::new(output, width, height)
+    }
+
+    pub fn with_codec(mut self, codec: VideoCodec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+}
+
+/// The videos [`render_batch`] produces
+#[derive(Debug, Clone, Default)]
+pub struct ExportBatch {
+    pub targets: Vec<ExportTarget>,
+    /// Sounds mixed into every target with an audio track
+    pub sound_cues: Vec<SoundCue>,
+}
+
+impl ExportBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_target(mut self, target: ExportTarget) -> Self {
+        self.targets.push(target);
+        self
+    }
+
+    /// Mix the given sound cues (e.g. `timeline.sound_cues()`) into the videos
+    pub fn with_sound_cues(mut self, cues: &[SoundCue]) -> Self {
+        self.sound_cues = cues.to_vec();
+        self
+    }
+
+    /// Distinct target sizes, in the order the targets first use them
+    pub fn resolutions(&self) -> Vec<(u32, u32)> {
+        let mut sizes = Vec::new();
+        for target in &self.targets {
+            if !sizes.contains(&target.size()) {
+                sizes.push(target.size());
+            }
+        }
+        sizes
+    }
+}
+
+/// `config` at `width` x `height`, with frames in their own directory
+fn resolution_config(config: &RenderConfig, width: u32, height: u32) -> RenderConfig {
+    let mut sized = config
+        .clone()
+        .with_frames_dir(config.frames_dir.join(format!("{width}x{height}")));
+    (sized.width, sized.height) = (width, height);
+    if config.framing != Matrix4::identity() {
+        sized = sized.with_aspect_framing();
+    }
+    sized
+}
+
+/// Render `config` once per resolution of `batch` and encode every target
+/// from the frames of its resolution, returning the outputs in target order
+///
+/// Each resolution starts from a fresh scene built by `build_scene`.
+/// Rendering and encoding are reported to `progress`.
+pub fn render_batch(
+    renderer: &mut ShapeRenderer,
+    mut build_scene: impl FnMut() -> SceneGraph,
+    config: &RenderConfig,
+    batch: &ExportBatch,
+    progress: &mut dyn ProgressSink,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    if batch.targets.is_empty() {
+        return Err("export batch has no targets".into());
+    }
+
+    for (width, height) in batch.resolutions() {
+        let sized = resolution_config(config, width, height);
+        render_frames_with_progress(renderer, &mut build_scene(), &sized, progress)?;
+
+        for target in batch.targets.iter().filter(|t| t.size() == (width, height)) {
+            if let Some(parent) = target.output.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let settings = VideoExportSettings::new(
+                width,
+                height,
+                config.fps,
+                target.output.to_string_lossy().into_owned(),
+                sized.frame_pattern(),
+            )
+            .with_codec(target.codec)
+            .with_sound_cues(&batch.sound_cues)
+            .with_cancellation(config.cancel.clone());
+            export_video_ffmpeg_with_progress(&settings, progress)?;
+        }
+    }
+
+    progress.on_stage(RenderStage::Finished);
+    Ok(batch
+        .targets
+        .iter()
+        .map(|target| target.output.clone())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_renders_each_resolution_once() {
+        let batch = ExportBatch::new()
+            .with_target(ExportTarget::new("out/video.mp4", 1920, 1080))
+            .with_target(ExportTarget::new("out/video.webm", 1280, 720))
+            .with_target(ExportTarget::new("out/video.mov", 1920, 1080))
+            .with_target(ExportTarget::new("out/clip.GIF", 854, 480));
+        assert_eq!(batch.resolutions(), [(1920, 1080), (1280, 720), (854, 480)]);
+        let codecs: Vec<VideoCodec> = batch.targets.iter().map(|t| t.codec).collect();
+        assert_eq!(
+            codecs,
+            [
+                VideoCodec::H264,
+                VideoCodec::Vp9,
+                VideoCodec::ProRes4444,
+                VideoCodec::Gif
+            ]
+        );
+        assert_eq!(
+            ExportTarget::new("out/video", 640, 360).codec,
+            VideoCodec::H264
+        );
+
+        let config = RenderConfig::new(1920, 1080, 30, 1.0).with_frames_dir("frames");
+        let sized = resolution_config(&config, 1280, 720);
+        assert_eq!(sized.frames_dir, PathBuf::from("frames/1280x720"));
+        assert_eq!(sized.framing, Matrix4::identity());
+
+        // Framed renders keep square scene units in every shape
+        let framed = resolution_config(&config.with_aspect_framing(), 1080, 1920);
+        assert_eq!(framed.framing, FramePreset::Vertical.framing());
+    }
+}
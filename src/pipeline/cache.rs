@@ -0,0 +1,543 @@
+//! # Frame Cache
+//!
+//! Hashes everything that affects a rendered frame (visible renderables,
+//! transforms, opacity, materials, shader materials, clip masks, render
+//! target contents, node and post effects, lights and output settings) and keeps the resulting PNGs in
+//! a cache directory keyed by that hash. When only part of a scene changes, frames whose state
+//! hash is unchanged are copied from the cache instead of being rendered
+//! again.
+
+use crate::core::{Color, Vector3};
+use crate::scene::{
+    BlendMode, ClipMask, LightKind, Material, NodeEffect, PostEffect, PostEffectKind, Renderable,
+    SceneGraph,
+};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever the renderer output changes for an identical scene state,
+/// so stale cache entries are never reused
+const CACHE_VERSION: u32 = 1;
+
+/// Stable 64-bit FNV-1a hasher for frame state
+///
+/// Unlike `std::collections::hash_map::DefaultHasher`, the output is stable
+/// across runs and Rust versions, so hashes can name files on disk.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameHasher {
+    state: u64,
+}
+
+impl FrameHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    pub fn new() -> Self {
+        Self {
+            state: Self::OFFSET_BASIS,
+        }
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state ^= u64::from(byte);
+            self.state = self.state.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        // Treat -0.0 and 0.0 as the same value
+        let value = if value == 0.0 { 0.0 } else { value };
+        self.write_u32(value.to_bits());
+    }
+
+    pub fn write_str(&mut self, value: &str) {
+        self.write_u32(value.len() as u32);
+        self.write_bytes(value.as_bytes());
+    }
+
+    pub fn write_vector(&mut self, vector: Vector3) {
+        self.write_f32(vector.x);
+        self.write_f32(vector.y);
+        self.write_f32(vector.z);
+    }
+
+    pub fn write_color(&mut self, color: Color) {
+        self.write_f32(color.r);
+        self.write_f32(color.g);
+        self.write_f32(color.b);
+        self.write_f32(color.a);
+    }
+
+    pub fn write_matrix(&mut self, matrix: &[[f32; 4]; 4]) {
+        for &value in matrix.iter().flatten() {
+            self.write_f32(value);
+        }
+    }
+
+    pub fn write_material(&mut self, material: &Material) {
+        self.write_f32(material.diffuse);
+        self.write_f32(material.specular);
+        self.write_f32(material.shininess);
+    }
+
+    pub fn write_renderable(&mut self, renderable: &Renderable) {
+        match renderable {
+            Renderable::Circle { radius, color } => {
+                self.write_u32(0);
+                self.write_f32(*radius);
+                self.write_color(*color);
+            }
+            Renderable::Rectangle {
+                width,
+                height,
+                color,
+            } => {
+                self.write_u32(1);
+                self.write_f32(*width);
+                self.write_f32(*height);
+                self.write_color(*color);
+            }
+            Renderable::Line {
+                start,
+                end,
+                color,
+                thickness,
+            } => {
+                self.write_u32(2);
+                self.write_vector(*start);
+                self.write_vector(*end);
+                self.write_color(*color);
+                self.write_f32(*thickness);
+            }
+            Renderable::Arrow {
+                start,
+                end,
+                color,
+                thickness,
+            } => {
+                self.write_u32(3);
+                self.write_vector(*start);
+                self.write_vector(*end);
+                self.write_color(*color);
+                self.write_f32(*thickness);
+            }
+            Renderable::Polygon { vertices, color } => {
+                self.write_u32(4);
+                self.write_u32(vertices.len() as u32);
+                for vertex in vertices {
+                    self.write_vector(*vertex);
+                }
+                self.write_color(*color);
+            }
+            Renderable::Text {
+                content,
+                font_size,
+                color,
+            } => {
+                self.write_u32(5);
+                self.write_str(content);
+                self.write_f32(*font_size);
+                self.write_color(*color);
+            }
+            Renderable::Math {
+                latex,
+                font_size,
+                color,
+            } => {
+                self.write_u32(6);
+                self.write_str(latex);
+                self.write_f32(*font_size);
+                self.write_color(*color);
+            }
+            Renderable::RichText {
+                text,
+                font_size,
+                color,
+            } => {
+                self.write_u32(7);
+                self.write_u32(text.spans.len() as u32);
+                for span in &text.spans {
+                    self.write_str(&span.text);
+                    self.write_u32(u32::from(span.math) | u32::from(span.style.bold) << 1);
+                    self.write_f32(span.style.scale);
+                    match span.style.color {
+                        Some(color) => {
+                            self.write_u32(1);
+                            self.write_color(color);
+                        }
+                        None => self.write_u32(0),
+                    }
+                }
+                self.write_f32(*font_size);
+                self.write_color(*color);
+            }
+            Renderable::TextOnPath {
+                content,
+                path,
+                font_size,
+                color,
+            } => {
+                self.write_u32(8);
+                self.write_str(content);
+                let points = path.path.flatten();
+                self.write_u32(points.len() as u32);
+                for point in points {
+                    self.write_vector(point);
+                }
+                self.write_u32(path.align as u32);
+                self.write_f32(path.offset);
+                self.write_f32(*font_size);
+                self.write_color(*color);
+            }
+            Renderable::Image {
+                asset,
+                width,
+                height,
+                color,
+            } => {
+                self.write_u32(9);
+                self.write_bytes(&asset.0.to_le_bytes());
+                self.write_f32(*width);
+                self.write_f32(*height);
+                self.write_color(*color);
+            }
+            Renderable::Svg {
+                asset,
+                width,
+                height,
+                color,
+            } => {
+                self.write_u32(10);
+                self.write_bytes(&asset.0.to_le_bytes());
+                self.write_f32(*width);
+                self.write_f32(*height);
+                self.write_color(*color);
+            }
+            Renderable::Polyline {
+                points,
+                color,
+                thickness,
+                fade,
+            } => {
+                self.write_u32(11);
+                self.write_u32(points.len() as u32);
+                for point in points {
+                    self.write_vector(*point);
+                }
+                self.write_color(*color);
+                self.write_f32(*thickness);
+                self.write_f32(*fade);
+            }
+        }
+    }
+
+    pub fn write_node_effects(&mut self, effects: &[NodeEffect]) {
+        self.write_u32(effects.len() as u32);
+        for effect in effects {
+            match *effect {
+                NodeEffect::Shadow {
+                    offset,
+                    softness,
+                    color,
+                } => {
+                    self.write_u32(0);
+                    self.write_f32(offset.0);
+                    self.write_f32(offset.1);
+                    self.write_f32(softness);
+                    self.write_color(color);
+                }
+                NodeEffect::Glow { color, radius } => {
+                    self.write_u32(1);
+                    self.write_color(color);
+                    self.write_f32(radius);
+                }
+            }
+        }
+    }
+
+    pub fn write_clip_mask(&mut self, mask: &ClipMask) {
+        match mask {
+            ClipMask::Circle { radius } => {
+                self.write_u32(0);
+                self.write_f32(*radius);
+            }
+            ClipMask::Rectangle { width, height } => {
+                self.write_u32(1);
+                self.write_f32(*width);
+                self.write_f32(*height);
+            }
+            ClipMask::Polygon { vertices } => {
+                self.write_u32(2);
+                self.write_u32(vertices.len() as u32);
+                for vertex in vertices {
+                    self.write_vector(*vertex);
+                }
+            }
+        }
+    }
+
+    /// Feed the full visible state of `scene` into the hash
+    pub fn write_scene(&mut self, scene: &SceneGraph) {
+        let renderables = scene.get_visible_renderables_with_materials();
+        self.write_u32(renderables.len() as u32);
+        for (transform, renderable, opacity, material) in &renderables {
+            self.write_matrix(&transform.model_view_proj);
+            self.write_renderable(renderable);
+            self.write_f32(*opacity);
+            self.write_material(material);
+        }
+        for node in scene.visible_renderable_nodes() {
+            if !node.effects.is_empty() {
+                self.write_u32(node.id.0);
+                self.write_node_effects(&node.effects);
+            }
+            if node.blend != BlendMode::Normal {
+                self.write_u32(node.id.0);
+                self.write_u32(node.blend as u32);
+            }
+            if let Some(shader) = &node.shader {
+                self.write_u32(node.id.0);
+                self.write_str(shader.name);
+                self.write_str(&shader.fragment);
+                for value in shader.params.iter().flatten() {
+                    self.write_f32(*value);
+                }
+                self.write_f32(shader.time);
+            }
+            if let Some(clip) = &node.clip {
+                self.write_u32(node.id.0);
+                self.write_clip_mask(&clip.scaled(node.clip_scale));
+            }
+        }
+        for target_node in scene.render_targets() {
+            let Some(target) = &target_node.render_target else {
+                continue;
+            };
+            self.write_u32(target_node.id.0);
+            self.write_u32(target.width);
+            self.write_u32(target.height);
+            self.write_vector(target.center);
+            self.write_f32(target.zoom);
+            self.write_color(target.background);
+            self.write_matrix(&target_node.compute_model_matrix().model_view_proj);
+            let nodes = scene.render_target_nodes(target_node.id);
+            self.write_u32(nodes.len() as u32);
+            for node in nodes {
+                self.write_matrix(&node.compute_model_matrix().model_view_proj);
+                if let Some((renderable, opacity)) = node.drawn_renderable() {
+                    self.write_renderable(&renderable);
+                    self.write_f32(opacity);
+                }
+                self.write_u32(node.blend as u32);
+            }
+        }
+
+        self.write_u32(scene.lights().len() as u32);
+        for light in scene.lights() {
+            match light.kind {
+                LightKind::Directional { direction } => {
+                    self.write_u32(0);
+                    self.write_vector(direction);
+                }
+                LightKind::Point { position, range } => {
+                    self.write_u32(1);
+                    self.write_vector(position);
+                    self.write_f32(range);
+                }
+            }
+            self.write_color(light.color);
+            self.write_f32(light.intensity);
+        }
+        if scene.is_lit() {
+            self.write_color(scene.ambient_light());
+        }
+
+        let effects: Vec<&PostEffect> = scene
+            .post_effects()
+            .iter()
+            .filter(|effect| effect.is_active())
+            .collect();
+        if !effects.is_empty() {
+            self.write_u32(effects.len() as u32);
+            for effect in effects {
+                match effect.kind {
+                    PostEffectKind::Bloom { threshold, radius } => {
+                        self.write_u32(0);
+                        self.write_f32(threshold);
+                        self.write_f32(radius);
+                    }
+                    PostEffectKind::Vignette { radius, softness } => {
+                        self.write_u32(1);
+                        self.write_f32(radius);
+                        self.write_f32(softness);
+                    }
+                    PostEffectKind::Blur { radius } => {
+                        self.write_u32(2);
+                        self.write_f32(radius);
+                    }
+                }
+                self.write_f32(effect.intensity);
+            }
+        }
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.state
+    }
+}
+
+impl Default for FrameHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Hash of everything that determines a frame's pixels
+pub fn frame_state_hash(scene: &SceneGraph, width: u32, height: u32, background: Color) -> u64 {
+    let mut hasher = FrameHasher::new();
+    hasher.write_u32(CACHE_VERSION);
+    hasher.write_u32(width);
+    hasher.write_u32(height);
+    hasher.write_color(background);
+    hasher.write_scene(scene);
+    hasher.finish()
+}
+
+/// Directory of previously rendered frames keyed by [`frame_state_hash`]
+#[derive(Debug)]
+pub struct FrameCache {
+    dir: PathBuf,
+    hits: usize,
+    misses: usize,
+}
+
+impl FrameCache {
+    /// Open (and create if needed) a cache directory
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            hits: 0,
+            misses: 0,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Where the frame with `hash` is (or would be) stored
+    pub fn path_for(&self, hash: u64) -> PathBuf {
+        self.dir.join(format!("{hash:016x}.png"))
+    }
+
+    pub fn contains(&self, hash: u64) -> bool {
+        self.path_for(hash).is_file()
+    }
+
+    /// Copy a cached frame to `dest`; returns `false` on a cache miss
+    pub fn restore(&mut self, hash: u64, dest: impl AsRef<Path>) -> io::Result<bool> {
+        let cached = self.path_for(hash);
+        if !cached.is_file() {
+            self.misses += 1;
+            return Ok(false);
+        }
+        fs::copy(cached, dest)?;
+        self.hits += 1;
+        Ok(true)
+    }
+
+    /// Add a freshly rendered frame to the cache
+    pub fn store(&self, hash: u64, frame: impl AsRef<Path>) -> io::Result<()> {
+        // Copy to a temporary name first so an interrupted render never
+        // leaves a truncated frame under a valid hash
+        let target = self.path_for(hash);
+        let partial = target.with_extension("png.partial");
+        fs::copy(frame, &partial)?;
+        fs::rename(partial, target)
+    }
+
+    /// Frames restored from the cache so far
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Frames that had to be rendered so far
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Delete every cached frame
+    pub fn clear(&self) -> io::Result<()> {
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "png") {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Transform;
+
+    fn scene_with_circle(x: f32) -> SceneGraph {
+        let mut scene = SceneGraph::new();
+        let id = scene.create_node_with_transform(
+            "dot".to_string(),
+            Transform::from_translation(x, 0.0, 0.0),
+        );
+        scene
+            .get_node_mut(id)
+            .unwrap()
+            .set_renderable(Renderable::Circle {
+                radius: 0.5,
+                color: Color::RED,
+            });
+        scene.update_transforms();
+        scene
+    }
+
+    #[test]
+    fn test_frame_hash_tracks_scene_state() {
+        let hash = |scene: &SceneGraph| frame_state_hash(scene, 640, 360, Color::BLACK);
+
+        assert_eq!(hash(&scene_with_circle(1.0)), hash(&scene_with_circle(1.0)));
+        assert_ne!(hash(&scene_with_circle(1.0)), hash(&scene_with_circle(1.5)));
+        assert_ne!(
+            hash(&scene_with_circle(1.0)),
+            frame_state_hash(&scene_with_circle(1.0), 1280, 720, Color::BLACK)
+        );
+    }
+
+    #[test]
+    fn test_frame_cache_store_and_restore() {
+        let dir = std::env::temp_dir().join(format!("diomanim_cache_test_{}", std::process::id()));
+        let mut cache = FrameCache::new(dir.join("cache")).unwrap();
+
+        let frame = dir.join("frame.png");
+        fs::write(&frame, b"pixels").unwrap();
+        let restored = dir.join("restored.png");
+
+        assert!(!cache.restore(42, &restored).unwrap());
+        cache.store(42, &frame).unwrap();
+        assert!(cache.contains(42));
+        assert!(cache.restore(42, &restored).unwrap());
+        assert_eq!(fs::read(&restored).unwrap(), b"pixels");
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+        cache.clear().unwrap();
+        assert!(!cache.contains(42));
+        fs::remove_dir_all(dir).ok();
+    }
+}
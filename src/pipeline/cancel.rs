@@ -0,0 +1,117 @@
+//! Cooperative cancellation of renders and exports
+//!
+//! A [`CancellationToken`] is shared between the thread running a render
+//! and whoever may stop it (a GUI's cancel button, a server's job queue, a
+//! Ctrl-C handler). The pipeline checks it before each frame and the
+//! exporter before starting ffmpeg and while it runs; a cancelled render
+//! returns a [`RenderCancelled`] error saying how far it got, leaving the
+//! finished frames in place to resume from (see
+//! [`RenderConfig::with_resume`](super::RenderConfig::with_resume)).
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::pipeline::{CancellationToken, RenderCancelled, RenderConfig, RenderStage};
+//!
+//! let token = CancellationToken::new();
+//! let config = RenderConfig::new(640, 360, 30, 10.0).with_cancellation(token.clone());
+//!
+//! // From another thread, e.g. when the user presses "Cancel"
+//! token.cancel();
+//! assert!(config.cancel.is_cancelled());
+//!
+//! let error: Box<dyn std::error::Error> =
+//!     RenderCancelled::new(RenderStage::Rendering, 120, 300, "frames").into();
+//! let cancelled = error.downcast_ref::<RenderCancelled>().unwrap();
+//! assert_eq!(cancelled.frames_done, 120);
+//! ```
+
+use super::RenderStage;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Shared flag asking a render or export to stop
+///
+/// Clones share the flag. The default token is never cancelled unless
+/// [`CancellationToken::cancel`] is called on it or a clone.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask every render holding a clone of this token to stop
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Error returned by a render or export stopped through its
+/// [`CancellationToken`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderCancelled {
+    /// Stage that was running
+    pub stage: RenderStage,
+    /// Frames of that stage finished before it stopped
+    pub frames_done: u32,
+    /// Frames the stage would have finished
+    pub total: u32,
+    /// Directory holding the finished frames when rendering, or the video
+    /// that was not written when encoding
+    pub path: PathBuf,
+}
+
+impl RenderCancelled {
+    pub fn new(stage: RenderStage, frames_done: u32, total: u32, path: impl Into<PathBuf>) -> Self {
+        Self {
+            stage,
+            frames_done,
+            total,
+            path: path.into(),
+        }
+    }
+}
+
+impl fmt::Display for RenderCancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cancelled while {} after {} of {} frames ({})",
+            self.stage,
+            self.frames_done,
+            self.total,
+            self.path.display()
+        )
+    }
+}
+
+impl std::error::Error for RenderCancelled {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_cancellation() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!token.is_cancelled());
+        clone.cancel();
+        assert!(token.is_cancelled());
+        assert!(!CancellationToken::default().is_cancelled());
+
+        let error = RenderCancelled::new(RenderStage::Encoding, 3, 10, "out.mp4");
+        assert_eq!(
+            error.to_string(),
+            "cancelled while encoding after 3 of 10 frames (out.mp4)"
+        );
+    }
+}
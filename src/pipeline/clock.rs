@@ -0,0 +1,150 @@
+//! # Frame Clocks
+//!
+//! Decide when frames happen during a render. [`OfflineClock`] steps exactly
+//! one frame time per frame, so every run produces the same frames no matter
+//! how fast the machine is; [`RealTimeClock`] follows the wall clock like the
+//! live preview does.
+
+use crate::core::TimeValue;
+use instant::Instant;
+
+/// How a render advances time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ClockMode {
+    /// Fixed timestep: frame `n` is at exactly `n / fps` seconds
+    #[default]
+    Offline,
+    /// Wall-clock timestep: frames are taken as fast as they render
+    RealTime,
+}
+
+/// One frame produced by a [`FrameClock`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameTick {
+    /// Frame number, counted from 0
+    pub index: u32,
+    /// Scene time of the frame
+    pub time: TimeValue,
+    /// Time elapsed since the previous frame (zero for the first frame)
+    pub delta: TimeValue,
+}
+
+/// Source of frame times for a render
+pub trait FrameClock {
+    /// Advance to the next frame, or `None` once the render is complete
+    fn tick(&mut self) -> Option<FrameTick>;
+}
+
+/// Deterministic fixed-timestep clock
+///
+/// Frame times are computed from the frame index rather than by accumulating
+/// deltas, so reported times don't drift over long renders.
+#[derive(Debug, Clone)]
+pub struct OfflineClock {
+    fps: u32,
+    frame_count: u32,
+    next_frame: u32,
+}
+
+impl OfflineClock {
+    pub fn new(fps: u32, frame_count: u32) -> Self {
+        Self {
+            fps: fps.max(1),
+            frame_count,
+            next_frame: 0,
+        }
+    }
+
+    /// Scene time of frame `index`
+    pub fn frame_time(&self, index: u32) -> TimeValue {
+        TimeValue::new((f64::from(index) / f64::from(self.fps)) as f32)
+    }
+}
+
+impl FrameClock for OfflineClock {
+    fn tick(&mut self) -> Option<FrameTick> {
+        if self.next_frame >= self.frame_count {
+            return None;
+        }
+
+        let index = self.next_frame;
+        self.next_frame += 1;
+
+        let delta = if index == 0 {
+            0.0
+        } else {
+            1.0 / f64::from(self.fps)
+        };
+        Some(FrameTick {
+            index,
+            time: self.frame_time(index),
+            delta: TimeValue::new(delta as f32),
+        })
+    }
+}
+
+/// Wall-clock driven clock that runs for `duration` seconds
+#[derive(Debug, Clone)]
+pub struct RealTimeClock {
+    duration: f32,
+    started: Option<Instant>,
+    last_time: f32,
+    next_frame: u32,
+}
+
+impl RealTimeClock {
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            started: None,
+            last_time: 0.0,
+            next_frame: 0,
+        }
+    }
+}
+
+impl FrameClock for RealTimeClock {
+    fn tick(&mut self) -> Option<FrameTick> {
+        // Start timing at the first frame rather than at construction
+        let started = *self.started.get_or_insert_with(Instant::now);
+        let time = started.elapsed().as_secs_f32();
+        if time >= self.duration {
+            return None;
+        }
+
+        let tick = FrameTick {
+            index: self.next_frame,
+            time: TimeValue::new(time),
+            delta: TimeValue::new(time - self.last_time),
+        };
+        self.last_time = time;
+        self.next_frame += 1;
+        Some(tick)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offline_clock_is_fixed_step() {
+        let mut clock = OfflineClock::new(30, 90);
+        let ticks: Vec<FrameTick> = std::iter::from_fn(|| clock.tick()).collect();
+
+        assert_eq!(ticks.len(), 90);
+        assert_eq!(ticks[0].delta.value, 0.0);
+        assert!(ticks[1..]
+            .iter()
+            .all(|tick| (tick.delta.value - 1.0 / 30.0).abs() < 1e-7));
+        assert_eq!(ticks[89].time.value, (89.0_f64 / 30.0) as f32);
+
+        // A second run yields bit-identical times
+        let mut again = OfflineClock::new(30, 90);
+        let times: Vec<u32> = std::iter::from_fn(|| again.tick())
+            .map(|tick| tick.time.value.to_bits())
+            .collect();
+        let expected: Vec<u32> = ticks.iter().map(|tick| tick.time.value.to_bits()).collect();
+        assert_eq!(times, expected);
+    }
+}
@@ -0,0 +1,156 @@
+//! Resolution presets, aspect framing and safe areas
+//!
+//! Scene coordinates run from -1 to 1 across the frame on both axes, so by
+//! default a scene is stretched to the frame's shape: a circle comes out as
+//! an ellipse in 16:9. [`aspect_framing`] keeps scene units square instead,
+//! with the central square from -1 to 1 always in view: landscape frames show
+//! more of the scene to the sides, portrait frames more above and below.
+//! A scene laid out in that square therefore exports unchanged for YouTube
+//! (16:9), Shorts (9:16) and square posts, each [`FramePreset`] only adding
+//! margin around it.
+//!
+//! [`SafeArea`]s are the broadcast action- and title-safe rectangles of a
+//! frame; the preview draws them, with the shared square, as guides.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::pipeline::framing::FramePreset;
+//! use diomanim::pipeline::RenderConfig;
+//!
+//! let youtube = RenderConfig::new(1920, 1080, 30, 10.0).with_preset(FramePreset::Hd1080);
+//! let shorts = youtube.clone().with_preset(FramePreset::Vertical);
+//! assert_eq!((shorts.width, shorts.height), (1080, 1920));
+//! ```
+
+use crate::core::{Matrix4, Vector3};
+
+/// Rectangle as `(min, max)` corners in frame coordinates, which run from
+/// -1 to 1 on both axes with y up
+pub type FrameRect = ((f32, f32), (f32, f32));
+
+/// Output size and shape for a target platform
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FramePreset {
+    /// 1920x1080, 16:9
+    Hd1080,
+    /// 3840x2160, 16:9
+    Uhd4k,
+    /// 1080x1920, 9:16 for Shorts, Reels and TikTok
+    Vertical,
+    /// 1080x1080, 1:1
+    Square,
+}
+
+impl FramePreset {
+    pub const ALL: [Self; 4] = [Self::Hd1080, Self::Uhd4k, Self::Vertical, Self::Square];
+
+    /// Width and height in pixels
+    pub fn size(self) -> (u32, u32) {
+        match self {
+            Self::Hd1080 => (1920, 1080),
+            Self::Uhd4k => (3840, 2160),
+            Self::Vertical => (1080, 1920),
+            Self::Square => (1080, 1080),
+        }
+    }
+
+    /// Width over height
+    pub fn aspect_ratio(self) -> f32 {
+        let (width, height) = self.size();
+        width as f32 / height as f32
+    }
+
+    /// [`aspect_framing`] for this preset's size
+    pub fn framing(self) -> Matrix4 {
+        let (width, height) = self.size();
+        aspect_framing(width, height)
+    }
+
+    /// Preset named `name`: "1080p" (or "16:9"), "4k" (or "2160p"),
+    /// "vertical" (or "9:16") or "square" (or "1:1"), in any case
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "1080p" | "16:9" => Some(Self::Hd1080),
+            "4k" | "2160p" => Some(Self::Uhd4k),
+            "vertical" | "9:16" => Some(Self::Vertical),
+            "square" | "1:1" => Some(Self::Square),
+            _ => None,
+        }
+    }
+}
+
+/// Projection keeping scene units square on a `width` x `height` frame,
+/// with the central square from -1 to 1 filling its shorter side
+pub fn aspect_framing(width: u32, height: u32) -> Matrix4 {
+    let (width, height) = (width.max(1) as f32, height.max(1) as f32);
+    let short = width.min(height);
+    Matrix4::from_scale(Vector3::new(short / width, short / height, 1.0))
+}
+
+/// Part of the frame the central scene square covers under
+/// [`aspect_framing`]
+///
+/// Content inside it is in view in every preset.
+pub fn shared_area(width: u32, height: u32) -> FrameRect {
+    let framing = aspect_framing(width, height);
+    let (x, y) = (framing.data[0][0], framing.data[1][1]);
+    ((-x, -y), (x, y))
+}
+
+/// Broadcast safe areas, centered in the frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SafeArea {
+    /// 93% of the frame: keep important action inside
+    Action,
+    /// 90% of the frame: keep text inside
+    Title,
+}
+
+impl SafeArea {
+    /// Share of the frame's width and height inside the area
+    pub fn fraction(self) -> f32 {
+        match self {
+            Self::Action => 0.93,
+            Self::Title => 0.9,
+        }
+    }
+
+    pub fn rect(self) -> FrameRect {
+        let half = self.fraction();
+        ((-half, -half), (half, half))
+    }
+
+    /// Whether `rect` lies inside the area
+    pub fn contains(self, rect: FrameRect) -> bool {
+        let ((min_x, min_y), (max_x, max_y)) = rect;
+        let half = self.fraction();
+        min_x >= -half && min_y >= -half && max_x <= half && max_y <= half
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_and_framing() {
+        assert_eq!(FramePreset::from_name("9:16"), Some(FramePreset::Vertical));
+        assert_eq!(FramePreset::from_name("4K"), Some(FramePreset::Uhd4k));
+        assert_eq!(FramePreset::from_name("8k"), None);
+        assert!((FramePreset::Hd1080.aspect_ratio() - 16.0 / 9.0).abs() < 1e-6);
+
+        // A unit step covers the same pixels along both axes
+        let framing = FramePreset::Hd1080.framing();
+        let corner = framing.transform_point(Vector3::new(1.0, 1.0, 0.0));
+        assert!((corner.x * 1920.0 - corner.y * 1080.0).abs() < 1e-3);
+        assert_eq!(FramePreset::Square.framing(), Matrix4::identity());
+
+        let ((min_x, min_y), (max_x, max_y)) = shared_area(1080, 1920);
+        assert_eq!((min_x, max_x), (-1.0, 1.0));
+        assert!((max_y - 0.5625).abs() < 1e-6 && min_y == -max_y);
+
+        assert!(SafeArea::Action.contains(SafeArea::Title.rect()));
+        assert!(!SafeArea::Title.contains(((-0.95, 0.0), (0.0, 0.1))));
+    }
+}
@@ -0,0 +1,202 @@
+//! Seamless looping renders
+//!
+//! A clip loops cleanly when the frame after its last one, the scene at
+//! t=duration, looks like its first. [`render_loop`] renders the frames and
+//! then deals with that seam one of two ways:
+//!
+//! - [`LoopSeam::Validate`] renders the scene at t=duration and fails with a
+//!   [`LoopMismatch`] if it differs from the first frame by more than the
+//!   tolerance, for scenes built to return to their starting state
+//! - [`LoopSeam::Crossfade`] blends the last seconds of the render into the
+//!   first ones and drops them, for scenes that don't; the loop is shorter
+//!   than `duration` by the crossfade
+//!
+//! Encode the frames with [`VideoCodec::Gif`], which loops forever, or
+//! WebM for `<video loop>` players.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use diomanim::export::{export_video_ffmpeg, VideoCodec, VideoExportSettings};
+//! use diomanim::pipeline::{render_loop, LoopSeam, NoProgress, RenderConfig};
+//! use diomanim::prelude::*;
+//! use diomanim::scene::SceneGraph;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut renderer = ShapeRenderer::new(480, 480).await?;
+//! let mut scene = SceneGraph::new();
+//!
+//! let config = RenderConfig::new(480, 480, 30, 4.0).with_frames_dir("output/loop");
+//! render_loop(&mut renderer, &mut scene, &config, LoopSeam::Crossfade(0.5), &mut NoProgress)?;
+//! let settings = VideoExportSettings::new(
+//!     480, 480, 30,
+//!     "output/loop.gif".to_string(),
+//!     config.frame_pattern(),
+//! )
+//! .with_codec(VideoCodec::Gif);
+//! export_video_ffmpeg(&settings)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`VideoCodec::Gif`]: crate::export::VideoCodec::Gif
+
+use super::{render_frame, render_frames_with_progress, save_png, scene_delta, RenderConfig};
+use super::{ProgressSink, RenderStats};
+use crate::core::TimeValue;
+use crate::render::ShapeRenderer;
+use crate::scene::SceneGraph;
+use crate::testing::{compare, ImageDiff, Snapshot, Tolerance};
+use std::fmt;
+
+/// How [`render_loop`] makes the last frame lead back into the first
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoopSeam {
+    /// Fail unless the scene at t=duration matches the first frame
+    Validate(Tolerance),
+    /// Blend this many seconds at the end into the start
+    Crossfade(f32),
+}
+
+/// Error for a loop whose end doesn't match its start
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoopMismatch {
+    /// The scene at t=duration compared with the first frame
+    pub diff: ImageDiff,
+}
+
+impl fmt::Display for LoopMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the scene doesn't loop: its end differs from its first frame \
+             (SSIM {:.3}, {:.1}% of pixels differ); crossfade the seam or \
+             return the scene to its starting state",
+            self.diff.ssim,
+            self.diff.mismatch_ratio() * 100.0
+        )
+    }
+}
+
+impl std::error::Error for LoopMismatch {}
+
+/// Render `scene` like [`render_frames_with_progress`], then validate or
+/// crossfade the seam between its last and first frames (see [`LoopSeam`])
+///
+/// The frames should cover the whole duration, without a time range or
+/// chunk. The returned stats count every frame rendered, including those a
+/// crossfade blends away.
+pub fn render_loop(
+    renderer: &mut ShapeRenderer,
+    scene: &mut SceneGraph,
+    config: &RenderConfig,
+    seam: LoopSeam,
+    progress: &mut dyn ProgressSink,
+) -> Result<RenderStats, Box<dyn std::error::Error>> {
+    let stats = render_frames_with_progress(renderer, scene, config, progress)?;
+    match seam {
+        LoopSeam::Validate(tolerance) => {
+            let diff = seam_diff(renderer, scene, config, tolerance)?;
+            if !diff.passes(tolerance) {
+                return Err(LoopMismatch { diff }.into());
+            }
+        }
+        LoopSeam::Crossfade(seconds) => {
+            let frames = (seconds * config.fps as f32).round().max(0.0) as u32;
+            crossfade_frames(config, stats.total_frames(), frames)?;
+        }
+    }
+    Ok(stats)
+}
+
+/// Difference between the first frame on disk and the frame after the last,
+/// with `scene` left at the last frame by the render
+fn seam_diff(
+    renderer: &mut ShapeRenderer,
+    scene: &mut SceneGraph,
+    config: &RenderConfig,
+    tolerance: Tolerance,
+) -> Result<ImageDiff, Box<dyn std::error::Error>> {
+    let step = TimeValue::new(config.frame_time());
+    scene.update_animations(scene_delta(config, TimeValue::new(config.duration), step));
+    scene.update_transforms();
+    let end = Snapshot::new(
+        config.width,
+        config.height,
+        render_frame(renderer, scene, config)?,
+    );
+    let start = Snapshot::load_png(config.frame_path(0))?;
+    Ok(compare(&end, &start, tolerance.channel_threshold)?)
+}
+
+/// Blend the last `fade` of `count` frames in the frames directory of
+/// `config` into the first `fade`, then delete them, returning the frames
+/// left
+///
+/// Frame `i` of the fade shows `i / fade` of the original frame over the
+/// frame `count - fade` later, so playback runs from the last kept frame
+/// into the first without a jump. The fade is at most half the frames.
+pub fn crossfade_frames(
+    config: &RenderConfig,
+    count: u32,
+    fade: u32,
+) -> Result<u32, Box<dyn std::error::Error>> {
+    let fade = fade.min(count / 2);
+    if fade == 0 {
+        return Ok(count);
+    }
+    let kept = count - fade;
+    for index in 0..fade {
+        let head = Snapshot::load_png(config.frame_path(index))?;
+        let tail = Snapshot::load_png(config.frame_path(kept + index))?;
+        if (head.width, head.height) != (tail.width, tail.height) {
+            return Err(format!("frames {index} and {} differ in size", kept + index).into());
+        }
+        let weight = index as f32 / fade as f32;
+        let pixels: Vec<u8> = head
+            .pixels
+            .iter()
+            .zip(&tail.pixels)
+            .map(|(&head, &tail)| {
+                (f32::from(tail) + (f32::from(head) - f32::from(tail)) * weight).round() as u8
+            })
+            .collect();
+        save_png(config.frame_path(index), head.width, head.height, &pixels)?;
+    }
+    for index in kept..count {
+        std::fs::remove_file(config.frame_path(index))?;
+    }
+    // The blended frames no longer match the render, so never resume into them
+    let _ = std::fs::remove_file(config.frames_dir.join(super::resume::MANIFEST_FILE));
+    Ok(kept)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crossfade_frames_blends_tail_into_head() {
+        let dir = std::env::temp_dir().join(format!("diomanim_loop_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = RenderConfig::new(2, 1, 10, 1.0).with_frames_dir(&dir);
+        for index in 0..10u8 {
+            save_png(config.frame_path(u32::from(index)), 2, 1, &[index * 20; 8]).unwrap();
+        }
+
+        assert_eq!(crossfade_frames(&config, 10, 4).unwrap(), 6);
+        assert!(!config.frame_path(6).exists());
+        // The first frame is all tail, so it follows the last kept frame
+        let first = Snapshot::load_png(config.frame_path(0)).unwrap();
+        assert_eq!(first.pixels[0], 6 * 20);
+        // Halfway through the fade, half of each
+        let middle = Snapshot::load_png(config.frame_path(2)).unwrap();
+        assert_eq!(middle.pixels[0], (2 * 20 + 8 * 20) / 2);
+        let untouched = Snapshot::load_png(config.frame_path(5)).unwrap();
+        assert_eq!(untouched.pixels[0], 5 * 20);
+
+        // No fade leaves the frames alone
+        assert_eq!(crossfade_frames(&config, 6, 0).unwrap(), 6);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
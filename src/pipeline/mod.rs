@@ -0,0 +1,1311 @@
+//! # Offline Render Pipeline
+//!
+//! Renders a [`SceneGraph`] to a numbered PNG frame sequence that can then be
+//! encoded with [`crate::export::export_video_ffmpeg`].
+//!
+//! - **RenderConfig**: resolution, frame rate, duration and output settings
+//! - **OfflineClock**: fixed timestep so renders are identical across runs and machines
+//! - **render_frames**: steps the scene frame by frame and writes each frame
+//! - **render_frames_with_graph**: the same, with frames produced by a custom [`crate::render::RenderGraph`]
+//! - **render_frames_with_progress**: the same, reporting each finished frame to a [`ProgressSink`]
+//! - **CancellationToken**: stops a render or export between frames with a [`RenderCancelled`] error
+//! - **TileGrid**: splits frames larger than the GPU's texture limit into tiles stitched on the CPU
+//! - **Supersampling**: draws frames at 2x or 4x and filters them down for the cleanest edges
+//! - **TimeRemap**: keyframed playback speed for slow-motion ramps and speed-ups on export
+//! - **FramePreset**: 1080p, 4K, vertical and square sizes framed so scene units stay square (see [`framing`])
+//! - **Watermark**: a logo or text overlay drawn over every frame by a final render pass
+//! - **render_loop**: renders a seamless loop, checking or crossfading the seam between last and first frame
+//! - **render_frame**: renders the scene's current state to an RGBA buffer
+//! - **render_poster** / **render_contact_sheet**: a thumbnail PNG at one time, or a grid of evenly spaced frames
+//! - **FrameCache**: skips frames whose scene state is unchanged since a previous render
+//! - **Resume**: picks an interrupted render up after the last frame it finished (see [`resume`])
+//! - **render_batch**: renders once per resolution and encodes several formats from the same frames (see [`batch`])
+//! - **render_sections**: renders each timeline [`Section`] to its own video for concatenation
+//! - **render_slides**: renders each presentation cue to a WebM and writes an HTML slide deck
+//! - **render_chunk** / **merge_chunks**: split a long render across processes or machines and stitch the parts
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use diomanim::pipeline::{render_frames, RenderConfig};
+//! use diomanim::prelude::*;
+//! use diomanim::scene::SceneGraph;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut renderer = ShapeRenderer::new(1920, 1080).await?;
+//! let mut scene = SceneGraph::new();
+//!
+//! let config = RenderConfig::new(1920, 1080, 30, 12.0)
+//!     .with_frames_dir("output/frames")
+//!     .with_cache_dir(".diomanim_cache");
+//! let stats = render_frames(&mut renderer, &mut scene, &config)?;
+//! println!("{} rendered, {} reused", stats.frames_rendered, stats.frames_cached);
+//! # Ok(())
+//! # }
+//! ```
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod batch;
+pub mod cache;
+pub mod cancel;
+pub mod clock;
+pub mod framing;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod looping;
+pub mod poster;
+pub mod progress;
+pub mod remap;
+pub mod resume;
+pub mod supersample;
+pub mod tiles;
+pub mod watermark;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use batch::{render_batch, ExportBatch, ExportTarget};
+pub use cache::{frame_state_hash, FrameCache, FrameHasher};
+pub use cancel::{CancellationToken, RenderCancelled};
+pub use clock::{ClockMode, FrameClock, FrameTick, OfflineClock, RealTimeClock};
+pub use framing::{aspect_framing, FramePreset, SafeArea};
+#[cfg(not(target_arch = "wasm32"))]
+pub use looping::{render_loop, LoopMismatch, LoopSeam};
+pub use poster::{render_contact_sheet, render_poster, ContactSheet};
+pub use progress::{ConsoleProgress, NoProgress, ProgressSink, RenderStage};
+pub use remap::TimeRemap;
+pub use supersample::DownsampleFilter;
+pub use tiles::TileGrid;
+pub use watermark::{Watermark, WatermarkAnchor, WatermarkPass};
+
+use crate::core::{Color, Matrix4, Section, TimeValue, Vector3};
+use crate::render::graph::{self, DrawLayer, ReadbackPass, RenderGraph, ScenePass};
+use crate::render::PostProcessPass;
+use crate::render::{ShapeRenderer, StencilMode, TransformUniform};
+use crate::scene::{BlendMode, ClipMask, SceneGraph, SceneNode, Theme};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{
+    core::Timeline,
+    export::{
+        concat_videos, export_video_ffmpeg_with_progress,
+        slides::{write_slide_deck, Slide},
+        VideoCodec, VideoExportSettings,
+    },
+};
+use std::fmt::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Color format of offscreen frames, whatever format the renderer was built for
+const FRAME_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+/// Texture [`frame_graph`] draws each frame into
+pub const FRAME_TEXTURE: &str = "frame";
+
+/// Texture [`post_frame_graph`] draws the scene into before post-processing
+pub const SCENE_TEXTURE: &str = "scene";
+
+/// Settings for an offline render
+#[derive(Debug, Clone)]
+pub struct RenderConfig {
+    pub width: u32,
+    pub height: u32,
+    pub fps: u32,
+    /// Length of the render in seconds
+    pub duration: f32,
+    /// Directory receiving `frame_00000.png`, `frame_00001.png`, ...
+    pub frames_dir: PathBuf,
+    pub background: Color,
+    /// Reuse frames from this directory when their scene state is unchanged
+    pub cache_dir: Option<PathBuf>,
+    /// Only render `start..end` seconds (the whole duration if `None`)
+    pub time_range: Option<(f32, f32)>,
+    /// Only render scene frames `start..end`, within the time range if both are set
+    pub frames: Option<(u32, u32)>,
+    /// Fixed-timestep (default) or wall-clock frame timing
+    pub clock: ClockMode,
+    /// Checked before each frame and passed on to the video export
+    pub cancel: CancellationToken,
+    /// Keep frames a previous render of the same scene and settings
+    /// already wrote to `frames_dir`
+    pub resume: bool,
+    /// Largest tile side in pixels; frames above it (or above the device's
+    /// texture limit) are rendered in tiles
+    pub tile_size: Option<u32>,
+    /// Frames are drawn at this multiple of the resolution and filtered
+    /// down (1 for no supersampling)
+    pub supersample: u32,
+    pub downsample_filter: DownsampleFilter,
+    /// Playback speed over output time (the authored speed if `None`)
+    pub time_remap: Option<TimeRemap>,
+    /// Overlay drawn over every frame of the built-in frame graphs
+    pub watermark: Option<Watermark>,
+    /// Projection from scene to frame coordinates in the built-in frame
+    /// graphs; the identity stretches the scene's -1..1 over the frame
+    pub framing: Matrix4,
+}
+
+impl RenderConfig {
+    pub fn new(width: u32, height: u32, fps: u32, duration: f32) -> Self {
+        Self {
+            width,
+            height,
+            fps: fps.max(1),
+            duration: duration.max(0.0),
+            frames_dir: PathBuf::from("frames"),
+            background: Color::new(0.95, 0.95, 0.95),
+            cache_dir: None,
+            time_range: None,
+            frames: None,
+            clock: ClockMode::Offline,
+            cancel: CancellationToken::new(),
+            resume: false,
+            tile_size: None,
+            supersample: 1,
+            downsample_filter: DownsampleFilter::Box,
+            time_remap: None,
+            watermark: None,
+            framing: Matrix4::identity(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: ClockMode) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Stop the render (and its video export) when `token` is cancelled
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    /// Resume an interrupted render into the same `frames_dir` after its
+    /// last finished frame (see [`resume`])
+    pub fn with_resume(mut self) -> Self {
+        self.resume = true;
+        self
+    }
+
+    /// Render frames in tiles of at most `size` pixels per side, to bound
+    /// GPU memory (see [`tiles`])
+    ///
+    /// Frames larger than the device's texture limit are tiled anyway.
+    pub fn with_tile_size(mut self, size: u32) -> Self {
+        self.tile_size = Some(size.max(1));
+        self
+    }
+
+    /// Draw frames at `factor` (2 or 4) times the resolution and filter them
+    /// down with `filter` (see [`supersample`])
+    pub fn with_supersampling(mut self, factor: u32, filter: DownsampleFilter) -> Self {
+        self.supersample = factor.max(1);
+        self.downsample_filter = filter;
+        self
+    }
+
+    /// Advance the scene by `remap`'s speed curve instead of in real time
+    /// (see [`remap`])
+    pub fn with_time_remap(mut self, remap: TimeRemap) -> Self {
+        self.time_remap = Some(remap);
+        self
+    }
+
+    /// Draw `watermark` over every frame, after post effects
+    pub fn with_watermark(mut self, watermark: Watermark) -> Self {
+        self.watermark = Some(watermark);
+        self
+    }
+
+    /// Render at `preset`'s size, framed so scene units stay square (see
+    /// [`framing`])
+    pub fn with_preset(mut self, preset: FramePreset) -> Self {
+        (self.width, self.height) = preset.size();
+        self.with_aspect_framing()
+    }
+
+    /// Keep scene units square at the current size, showing at least the
+    /// scene's central -1..1 square (see [`aspect_framing`])
+    pub fn with_aspect_framing(mut self) -> Self {
+        self.framing = aspect_framing(self.width, self.height);
+        self
+    }
+
+    pub fn with_frames_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.frames_dir = dir.into();
+        self
+    }
+
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// Take the background from `theme`
+    pub fn with_theme(self, theme: &Theme) -> Self {
+        self.with_background(theme.background)
+    }
+
+    /// Enable frame caching in `dir`
+    pub fn with_cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Render only `start..end` seconds of the scene
+    pub fn with_time_range(mut self, start: f32, end: f32) -> Self {
+        self.time_range = Some((start.max(0.0), end.max(start)));
+        self
+    }
+
+    /// Render only scene frames `start..end`
+    pub fn with_frames(mut self, start: u32, end: u32) -> Self {
+        self.frames = Some((start, end.max(start)));
+        self
+    }
+
+    /// Render only chunk `index` of `count` near-equal chunks of the frame range
+    ///
+    /// Chunks tile the range in order, so rendering every chunk (in any
+    /// process, on any machine) and joining them with [`merge_chunks`] gives
+    /// the same frames as one render.
+    pub fn with_chunk(self, index: u32, count: u32) -> Self {
+        let range = self.frame_range();
+        let count = count.max(1);
+        let len = range.len() as u64;
+        let bound =
+            |i: u32| range.start + (len * u64::from(i.min(count)) / u64::from(count)) as u32;
+        self.with_frames(bound(index), bound(index + 1))
+    }
+
+    /// Render only the given timeline section (open sections run to `duration`)
+    pub fn with_section(self, section: &Section) -> Self {
+        let end = section.end.map_or(self.duration, |end| end.value);
+        self.with_time_range(section.start.value, end)
+    }
+
+    fn time_to_frame(&self, seconds: f32) -> u32 {
+        (seconds * self.fps as f32).round() as u32
+    }
+
+    /// Scene frame indices to output
+    ///
+    /// Section boundaries round to the same frame, so adjacent sections
+    /// concatenate without duplicated or missing frames.
+    pub fn frame_range(&self) -> Range<u32> {
+        let total = self.time_to_frame(self.duration);
+        let range = match self.time_range {
+            Some((start, end)) => {
+                let end = self.time_to_frame(end).min(total);
+                self.time_to_frame(start).min(end)..end
+            }
+            None => 0..total,
+        };
+        match self.frames {
+            Some((start, end)) => {
+                let end = end.min(range.end);
+                start.max(range.start).min(end)..end
+            }
+            None => range,
+        }
+    }
+
+    /// Number of frames written by a render
+    pub fn frame_count(&self) -> u32 {
+        self.frame_range().len() as u32
+    }
+
+    /// Seconds between frames
+    pub fn frame_time(&self) -> f32 {
+        1.0 / self.fps as f32
+    }
+
+    /// Output path of frame `index`
+    pub fn frame_path(&self, index: u32) -> PathBuf {
+        self.frames_dir.join(format!("frame_{index:05}.png"))
+    }
+
+    /// ffmpeg input pattern matching [`RenderConfig::frame_path`]
+    pub fn frame_pattern(&self) -> String {
+        self.frames_dir
+            .join("frame_%05d.png")
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// Summary of an offline render
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// Frames drawn on the GPU
+    pub frames_rendered: u32,
+    /// Frames copied from the frame cache
+    pub frames_cached: u32,
+    /// Frames kept from an interrupted render
+    pub frames_resumed: u32,
+}
+
+impl RenderStats {
+    pub fn total_frames(&self) -> u32 {
+        self.frames_rendered + self.frames_cached + self.frames_resumed
+    }
+}
+
+/// Record draw commands for every visible node of `scene`
+///
+/// Lit scenes use the lighting pipeline when the renderer has it initialized
+/// (see [`ShapeRenderer::init_lighting`]); `eye` is the camera position used
+/// for specular highlights.
+pub fn draw_scene(
+    renderer: &mut ShapeRenderer,
+    scene: &SceneGraph,
+    view_proj: &Matrix4,
+    eye: Vector3,
+    render_pass: &mut wgpu::RenderPass,
+) {
+    draw_scene_layer(renderer, scene, view_proj, eye, DrawLayer::All, render_pass);
+}
+
+/// Like [`draw_scene`], but only the renderables in `layer`
+///
+/// Lets a render graph draw shapes and text in separate passes.
+pub fn draw_scene_layer(
+    renderer: &mut ShapeRenderer,
+    scene: &SceneGraph,
+    view_proj: &Matrix4,
+    eye: Vector3,
+    layer: DrawLayer,
+    render_pass: &mut wgpu::RenderPass,
+) {
+    let nodes = scene.visible_renderable_nodes();
+    draw_nodes(renderer, scene, &nodes, view_proj, eye, layer, render_pass);
+}
+
+/// Draw the subtree of every [`RenderTarget`](crate::scene::RenderTarget)
+/// of `scene` into its texture, innermost first
+///
+/// Call once per frame after resetting the renderer's transforms and before
+/// the passes that draw the frame, so image nodes show this frame's content.
+pub fn draw_render_targets(
+    renderer: &mut ShapeRenderer,
+    scene: &SceneGraph,
+    eye: Vector3,
+    encoder: &mut wgpu::CommandEncoder,
+) {
+    for node in scene.render_targets() {
+        let Some(target) = &node.render_target else {
+            continue;
+        };
+        let view_proj = target.view_projection(&node.world_transform);
+        let nodes = scene.render_target_nodes(node.id);
+        let mut render_pass = renderer.begin_render_target_pass(encoder, target);
+        draw_nodes(
+            renderer,
+            scene,
+            &nodes,
+            &view_proj,
+            eye,
+            DrawLayer::All,
+            &mut render_pass,
+        );
+        drop(render_pass);
+        renderer.present_render_target(target.handle);
+    }
+}
+
+/// Record draw commands for `nodes` of `scene` (as returned by
+/// [`SceneGraph::visible_renderable_nodes`]) that belong to `layer`
+///
+/// Consecutive unlit, unclipped nodes that look the same (e.g. the dots of a
+/// grid) are drawn as one instanced batch.
+pub fn draw_nodes(
+    renderer: &mut ShapeRenderer,
+    scene: &SceneGraph,
+    nodes: &[&SceneNode],
+    view_proj: &Matrix4,
+    eye: Vector3,
+    layer: DrawLayer,
+    render_pass: &mut wgpu::RenderPass,
+) {
+    // Upload scene lights; shapes use the lit pipeline when any are present
+    let lit = scene.is_lit() && renderer.has_lighting();
+    if lit {
+        renderer.set_lighting(scene.lights(), scene.ambient_light(), eye);
+    }
+
+    // In a stencil pass, nodes are drawn inside the clip masks of their ancestors
+    let clipping = renderer.stencil_mode().is_some();
+
+    let mut index = 0;
+    while index < nodes.len() {
+        let node = nodes[index];
+        index += 1;
+
+        if clipping {
+            while let Some(clip) = renderer.innermost_clip() {
+                if scene.is_ancestor_or_self(clip, node.id) {
+                    break;
+                }
+                draw_clip_mask(
+                    renderer,
+                    scene.get_node(clip),
+                    view_proj,
+                    false,
+                    render_pass,
+                );
+            }
+            if node.clip.is_some() {
+                draw_clip_mask(renderer, Some(node), view_proj, true, render_pass);
+            }
+        }
+
+        // Partly created nodes draw the part of their shape revealed so far
+        let Some((renderable, opacity)) = node.drawn_renderable() else {
+            continue;
+        };
+        let renderable = renderable.as_ref();
+
+        // Text sets its own pipeline; everything else is a shape
+        let is_glyphs = renderable.is_glyphs();
+        if !layer.includes(is_glyphs) {
+            continue;
+        }
+
+        let batch_start = index - 1;
+        let deformation = node.deformation();
+        if !lit && !clipping && deformation.is_none() && node.shader.is_none() {
+            while index < nodes.len()
+                && nodes[index].opacity == opacity
+                && nodes[index].progress >= 1.0
+                && nodes[index].renderable.as_ref() == Some(renderable)
+                && nodes[index].deformers.is_empty()
+                && nodes[index].shader.is_none()
+                && nodes[index].blend == node.blend
+                && nodes[index].glyph_colors == node.glyph_colors
+            {
+                index += 1;
+            }
+        }
+        let uniforms: Vec<TransformUniform> = nodes[batch_start..index]
+            .iter()
+            .map(|node| {
+                TransformUniform::from_matrix(
+                    &(*view_proj * node.compute_model_matrix().to_matrix()),
+                )
+            })
+            .collect();
+        let transforms = renderer.update_transforms(&uniforms);
+
+        renderer.set_blend_mode(node.blend);
+        // A custom shader material takes precedence over scene lighting
+        if !is_glyphs {
+            if let Some(material) = &node.shader {
+                renderer.bind_shader_material(material, transforms.clone(), render_pass);
+            } else {
+                let bound_lit = lit && {
+                    let model = node.compute_model_matrix().to_matrix();
+                    renderer.bind_lit_object(
+                        &model,
+                        &node.material,
+                        transforms.clone(),
+                        render_pass,
+                    )
+                };
+                if !bound_lit {
+                    render_pass.set_pipeline(&renderer.current_pipeline());
+                }
+            }
+        }
+
+        // Deformed nodes bend their vertices, text the corners of its glyphs
+        let deformed = deformation.is_some();
+        if deformed {
+            renderer.set_deformation(deformation);
+        }
+        let tinted = is_glyphs && !node.glyph_colors.is_empty();
+        if tinted {
+            renderer.set_glyph_colors(node.glyph_colors.clone());
+        }
+
+        // Apply opacity to color, premultiplied for multiply and screen
+        // blending of shapes
+        let blend = if is_glyphs || renderable.as_image().is_some() {
+            BlendMode::Normal
+        } else {
+            node.blend
+        };
+        let apply_opacity = |color: Color| -> Color { blend.apply_opacity(color, opacity) };
+
+        if let Some((radius, color)) = renderable.as_circle() {
+            let circle = crate::mobjects::Circle {
+                radius: *radius,
+                color: apply_opacity(*color),
+                position: Vector3::zero(),
+            };
+            renderer.draw_circle(&circle, apply_opacity(*color), transforms, render_pass);
+        } else if let Some((width, height, color)) = renderable.as_rectangle() {
+            renderer.draw_rectangle(
+                *width,
+                *height,
+                apply_opacity(*color),
+                transforms,
+                render_pass,
+            );
+        } else if let Some((start, end, color, thickness)) = renderable.as_line() {
+            renderer.draw_line(
+                *start,
+                *end,
+                apply_opacity(*color),
+                *thickness,
+                transforms,
+                render_pass,
+            );
+        } else if let Some((start, end, color, thickness)) = renderable.as_arrow() {
+            renderer.draw_arrow(
+                *start,
+                *end,
+                apply_opacity(*color),
+                *thickness,
+                transforms,
+                render_pass,
+            );
+        } else if let Some((vertices, color)) = renderable.as_polygon() {
+            renderer.draw_polygon(vertices, apply_opacity(*color), transforms, render_pass);
+        } else if let Some((points, color, thickness, fade)) = renderable.as_polyline() {
+            renderer.draw_polyline(
+                points,
+                apply_opacity(*color),
+                *thickness,
+                *fade,
+                transforms,
+                render_pass,
+            );
+        } else if let Some((content, font_size, color)) = renderable.as_text() {
+            renderer.draw_text(
+                content,
+                *font_size,
+                apply_opacity(*color),
+                transforms,
+                render_pass,
+            );
+        } else if let Some((latex, font_size, color)) = renderable.as_math() {
+            renderer.draw_math(
+                latex,
+                *font_size,
+                apply_opacity(*color),
+                transforms,
+                render_pass,
+            );
+        } else if let Some((content, path, font_size, color)) = renderable.as_text_on_path() {
+            renderer.draw_text_on_path(
+                content,
+                path,
+                *font_size,
+                apply_opacity(*color),
+                transforms,
+                render_pass,
+            );
+        } else if let Some((text, font_size, color)) = renderable.as_rich_text() {
+            renderer.draw_rich_text(
+                text,
+                *font_size,
+                apply_opacity(*color),
+                transforms,
+                render_pass,
+            );
+        } else if let Some((asset, width, height, color)) = renderable.as_image() {
+            renderer.draw_image(
+                *asset,
+                *width,
+                *height,
+                apply_opacity(*color),
+                transforms,
+                render_pass,
+            );
+        } else if let Some((asset, width, height, color)) = renderable.as_svg() {
+            renderer.draw_svg(
+                *asset,
+                *width,
+                *height,
+                apply_opacity(*color),
+                transforms,
+                render_pass,
+            );
+        }
+        if deformed {
+            renderer.set_deformation(None);
+        }
+        if tinted {
+            renderer.set_glyph_colors(Vec::new());
+        }
+        renderer.set_blend_mode(BlendMode::Normal);
+    }
+}
+
+/// Push (write) or pop (erase) `node`'s clip mask in the stencil and update
+/// the renderer's clip stack and the pass's stencil reference to match
+fn draw_clip_mask(
+    renderer: &ShapeRenderer,
+    node: Option<&SceneNode>,
+    view_proj: &Matrix4,
+    push: bool,
+    render_pass: &mut wgpu::RenderPass,
+) {
+    let mask = node.and_then(|node| Some((node, node.clip.as_ref()?)));
+    if let Some((node, mask)) = mask {
+        let model = node.compute_model_matrix().to_matrix();
+        let transforms =
+            renderer.update_transform(&TransformUniform::from_matrix(&(*view_proj * model)));
+        renderer.set_stencil_mode(if push {
+            StencilMode::Increment
+        } else {
+            StencilMode::Decrement
+        });
+        render_pass.set_pipeline(&renderer.current_pipeline());
+
+        match mask.scaled(node.clip_scale) {
+            ClipMask::Circle { radius } => {
+                let circle = crate::mobjects::Circle {
+                    radius,
+                    color: Color::WHITE,
+                    position: Vector3::zero(),
+                };
+                renderer.draw_circle(&circle, Color::WHITE, transforms, render_pass);
+            }
+            ClipMask::Rectangle { width, height } => {
+                renderer.draw_rectangle(width, height, Color::WHITE, transforms, render_pass);
+            }
+            ClipMask::Polygon { vertices } => {
+                renderer.draw_polygon(&vertices, Color::WHITE, transforms, render_pass);
+            }
+        }
+        renderer.set_stencil_mode(StencilMode::Test);
+    }
+
+    if push {
+        if let Some(node) = node {
+            renderer.push_clip(node.id);
+        }
+    } else {
+        renderer.pop_clip();
+    }
+    render_pass.set_stencil_reference(renderer.clip_depth());
+}
+
+/// Copy an 8-bit RGBA or BGRA texture back to the CPU as tightly packed RGBA rows
+pub fn read_frame(
+    renderer: &ShapeRenderer,
+    texture: &wgpu::Texture,
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let staging_buffer = renderer
+        .get_device()
+        .create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Frame Readback Buffer"),
+            size: u64::from(graph::padded_bytes_per_row(width)) * u64::from(height),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+    let mut encoder =
+        renderer
+            .get_device()
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Frame Readback Encoder"),
+            });
+    graph::copy_to_buffer(&mut encoder, texture, &staging_buffer, width, height);
+    renderer
+        .get_queue()
+        .submit(std::iter::once(encoder.finish()));
+
+    graph::map_pixels(
+        renderer.get_device(),
+        &staging_buffer,
+        width,
+        height,
+        texture.format(),
+    )
+}
+
+/// Write tightly packed RGBA pixels to a PNG file
+pub fn save_png(
+    path: impl AsRef<Path>,
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = std::fs::File::create(path)?;
+    let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(pixels)?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Render `config.duration` seconds of `scene` to PNG frames
+///
+/// The scene is advanced by the configured clock, starting from its current
+/// state at t=0; with [`ClockMode::Offline`] that is exactly one frame time
+/// per frame, so output is byte-identical across runs. With a time range set,
+/// earlier frames are only simulated and output numbering starts at 0. With a cache directory
+/// configured, frames whose [`frame_state_hash`] matches a cached frame are
+/// copied instead of rendered.
+pub fn render_frames(
+    renderer: &mut ShapeRenderer,
+    scene: &mut SceneGraph,
+    config: &RenderConfig,
+) -> Result<RenderStats, Box<dyn std::error::Error>> {
+    render_frames_with_graph(
+        renderer,
+        scene,
+        config,
+        &mut scene_frame_graph(scene, config),
+        FRAME_TEXTURE,
+    )
+}
+
+/// Like [`render_frames`], reporting [`RenderStage::Rendering`] and then
+/// `on_frame(written, total)` after each frame is written (rendered or
+/// restored from the cache) to `progress`
+///
+/// Closures taking `(frame, total)` are sinks too.
+pub fn render_frames_with_progress(
+    renderer: &mut ShapeRenderer,
+    scene: &mut SceneGraph,
+    config: &RenderConfig,
+    progress: &mut dyn ProgressSink,
+) -> Result<RenderStats, Box<dyn std::error::Error>> {
+    render_graph_frames(
+        renderer,
+        scene,
+        config,
+        &mut scene_frame_graph(scene, config),
+        FRAME_TEXTURE,
+        progress,
+    )
+}
+
+/// Render the current state of `scene` as a single frame of tightly packed
+/// RGBA rows, without advancing animations or writing files
+pub fn render_frame(
+    renderer: &mut ShapeRenderer,
+    scene: &SceneGraph,
+    config: &RenderConfig,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut graph = scene_frame_graph(scene, config);
+    draw_graph_frame(renderer, scene, config, &mut graph, FRAME_TEXTURE)
+}
+
+/// Execute `graph` on the current state of `scene` and return the pixels of
+/// its `output` readback, supersampled if configured
+fn draw_graph_frame(
+    renderer: &mut ShapeRenderer,
+    scene: &SceneGraph,
+    config: &RenderConfig,
+    graph: &mut RenderGraph,
+    output: &str,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let factor = config.supersample.max(1);
+    let (width, height) = (config.width * factor, config.height * factor);
+    let pixels = draw_tiled_frame(renderer, scene, config, graph, output, (width, height))?;
+    if factor == 1 {
+        return Ok(pixels);
+    }
+    Ok(supersample::downsample(
+        &pixels,
+        width,
+        height,
+        factor,
+        config.downsample_filter,
+    ))
+}
+
+/// Like [`draw_graph_frame`] at `size` without supersampling, drawing the
+/// frame in tiles (see [`tiles`]) when it is larger than a texture may be
+fn draw_tiled_frame(
+    renderer: &mut ShapeRenderer,
+    scene: &SceneGraph,
+    config: &RenderConfig,
+    graph: &mut RenderGraph,
+    output: &str,
+    (width, height): (u32, u32),
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let texture_limit = renderer.get_device().limits().max_texture_dimension_2d;
+    let max_tile = config
+        .tile_size
+        .map_or(texture_limit, |size| size.min(texture_limit));
+    let grid = TileGrid::new(width, height, max_tile);
+    let mut draw = |graph: &mut RenderGraph| -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        graph.execute(renderer, scene, None)?;
+        Ok(graph
+            .take_readback(output)
+            .ok_or_else(|| format!("render graph has no readback of `{output}`"))?)
+    };
+    if grid.is_single() {
+        graph.resize(width, height);
+        return draw(graph);
+    }
+
+    let (view_proj, eye) = graph.camera();
+    graph.resize(grid.tile_width, grid.tile_height);
+    let mut frame = vec![0; width as usize * height as usize * 4];
+    let mut result = Ok(());
+    for tile in grid.tiles() {
+        graph.set_camera(grid.tile_projection(tile) * view_proj, eye);
+        match draw(graph) {
+            Ok(pixels) => grid.copy_tile(tile, &pixels, &mut frame),
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        }
+    }
+    graph.set_camera(view_proj, eye);
+    result.map(|()| frame)
+}
+
+/// [`post_frame_graph`] for scenes with post effects, otherwise [`frame_graph`]
+pub fn scene_frame_graph(scene: &SceneGraph, config: &RenderConfig) -> RenderGraph {
+    if scene.post_effects().is_empty() {
+        frame_graph(config)
+    } else {
+        post_frame_graph(config)
+    }
+}
+
+/// The graph [`render_frames`] uses: the whole scene drawn into
+/// [`FRAME_TEXTURE`], then read back
+pub fn frame_graph(config: &RenderConfig) -> RenderGraph {
+    let mut graph = framed_graph(config);
+    graph.add_texture(FRAME_TEXTURE, FRAME_FORMAT);
+    graph.add_pass(
+        ScenePass::new("scene", FRAME_TEXTURE, DrawLayer::All).with_clear(config.background),
+    );
+    add_watermark_pass(&mut graph, config);
+    graph.add_pass(ReadbackPass::new(FRAME_TEXTURE));
+    graph
+}
+
+/// The graph [`render_frames`] uses for scenes with post effects: the scene is
+/// drawn into [`SCENE_TEXTURE`] and a [`PostProcessPass`] writes
+/// [`FRAME_TEXTURE`]
+pub fn post_frame_graph(config: &RenderConfig) -> RenderGraph {
+    let mut graph = framed_graph(config);
+    graph
+        .add_texture(SCENE_TEXTURE, FRAME_FORMAT)
+        .add_texture(FRAME_TEXTURE, FRAME_FORMAT);
+    graph.add_pass(
+        ScenePass::new("scene", SCENE_TEXTURE, DrawLayer::All).with_clear(config.background),
+    );
+    graph.add_pass(PostProcessPass::new(SCENE_TEXTURE, FRAME_TEXTURE));
+    add_watermark_pass(&mut graph, config);
+    graph.add_pass(ReadbackPass::new(FRAME_TEXTURE));
+    graph
+}
+
+/// Empty graph the size of `config`'s frames, looking through its framing
+fn framed_graph(config: &RenderConfig) -> RenderGraph {
+    let mut graph = RenderGraph::new(config.width, config.height);
+    let (_, eye) = graph.camera();
+    graph.set_camera(config.framing, eye);
+    graph
+}
+
+/// Draw the watermark of `config`, if any, over [`FRAME_TEXTURE`]
+fn add_watermark_pass(graph: &mut RenderGraph, config: &RenderConfig) {
+    if let Some(watermark) = &config.watermark {
+        graph.add_pass(
+            WatermarkPass::new(
+                FRAME_TEXTURE,
+                watermark.clone(),
+                config.width,
+                config.height,
+            )
+            .with_framing(config.framing),
+        );
+    }
+}
+
+/// Like [`render_frames`], but each frame is produced by `graph`
+///
+/// `output` names the texture saved as the frame; the graph must read it back
+/// with a [`ReadbackPass`]. Cached frames are keyed on the graph's pass names
+/// as well as the scene state.
+pub fn render_frames_with_graph(
+    renderer: &mut ShapeRenderer,
+    scene: &mut SceneGraph,
+    config: &RenderConfig,
+    graph: &mut RenderGraph,
+    output: &str,
+) -> Result<RenderStats, Box<dyn std::error::Error>> {
+    render_graph_frames(renderer, scene, config, graph, output, &mut NoProgress)
+}
+
+/// Scene time passing in the output step of `delta` ending at `time`,
+/// after any time remap
+fn scene_delta(config: &RenderConfig, time: TimeValue, delta: TimeValue) -> TimeValue {
+    match &config.time_remap {
+        Some(remap) => TimeValue::new(
+            remap.scene_time(time.value) - remap.scene_time(time.value - delta.value),
+        ),
+        None => delta,
+    }
+}
+
+fn render_graph_frames(
+    renderer: &mut ShapeRenderer,
+    scene: &mut SceneGraph,
+    config: &RenderConfig,
+    graph: &mut RenderGraph,
+    output: &str,
+    progress: &mut dyn ProgressSink,
+) -> Result<RenderStats, Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(&config.frames_dir)?;
+    progress.on_stage(RenderStage::Rendering);
+    // Every frame is drawn with all its assets, however they were requested
+    if let Some(assets) = renderer.asset_server() {
+        assets.wait_until_loaded();
+    }
+    let mut cache = config.cache_dir.as_ref().map(FrameCache::new).transpose()?;
+    let mut passes = graph.pass_names()?.join(",");
+    // Supersampled frames differ from plain ones, so they are cached apart
+    if config.supersample > 1 {
+        let _ = write!(
+            passes,
+            ",ssaa {}x {:?}",
+            config.supersample, config.downsample_filter
+        );
+    }
+    if let Some(watermark) = &config.watermark {
+        let _ = write!(passes, ",{watermark:?}");
+    }
+    if config.framing != Matrix4::identity() {
+        let _ = write!(passes, ",framing {:?}", config.framing.data);
+    }
+
+    let mut stats = RenderStats::default();
+    scene.update_transforms();
+
+    let settings = resume::settings_hash(config, scene, &passes);
+    let resume_from = if config.resume {
+        resume::resume_point(config, settings)?
+    } else {
+        0
+    };
+    resume::write_manifest(&config.frames_dir, settings)?;
+
+    let range = config.frame_range();
+    let mut clock: Box<dyn FrameClock> = match config.clock {
+        ClockMode::Offline => Box::new(OfflineClock::new(config.fps, range.end)),
+        ClockMode::RealTime => Box::new(RealTimeClock::new(range.end as f32 * config.frame_time())),
+    };
+
+    let total = config.frame_count();
+    let mut written = 0;
+    while let Some(tick) = clock.tick() {
+        if config.cancel.is_cancelled() {
+            let frames_dir = config.frames_dir.clone();
+            return Err(
+                RenderCancelled::new(RenderStage::Rendering, written, total, frames_dir).into(),
+            );
+        }
+        if tick.delta.value > 0.0 {
+            scene.update_animations(scene_delta(config, tick.time, tick.delta));
+            scene.update_transforms();
+        }
+        if config.time_to_frame(tick.time.value) < range.start {
+            continue;
+        }
+
+        let frame_path = config.frame_path(written);
+        written += 1;
+        if written <= resume_from {
+            stats.frames_resumed += 1;
+            progress.on_frame(written, total);
+            continue;
+        }
+        let mut hasher = FrameHasher::new();
+        hasher.write_bytes(
+            &frame_state_hash(scene, config.width, config.height, config.background).to_le_bytes(),
+        );
+        hasher.write_str(&passes);
+        let hash = hasher.finish();
+        if let Some(cache) = &mut cache {
+            if cache.restore(hash, &frame_path)? {
+                stats.frames_cached += 1;
+                progress.on_frame(written, total);
+                continue;
+            }
+        }
+
+        let pixels = draw_graph_frame(renderer, scene, config, graph, output)?;
+        save_png(&frame_path, config.width, config.height, &pixels)?;
+        if let Some(cache) = &cache {
+            cache.store(hash, &frame_path)?;
+        }
+        stats.frames_rendered += 1;
+        progress.on_frame(written, total);
+    }
+
+    Ok(stats)
+}
+
+/// Render every timeline section to its own MP4 in `output_dir`
+///
+/// Each section starts from a fresh scene built by `build_scene`, so sections
+/// can be re-rendered independently. Returns the section videos in timeline
+/// order; pass `concat_output` to also join them into a single video.
+/// Each section's frames and encoding are reported to `progress`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn render_sections(
+    renderer: &mut ShapeRenderer,
+    build_scene: impl FnMut() -> SceneGraph,
+    timeline: &Timeline,
+    config: &RenderConfig,
+    output_dir: impl AsRef<Path>,
+    concat_output: Option<&Path>,
+    progress: &mut dyn ProgressSink,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let videos: Vec<PathBuf> = render_segments(
+        renderer,
+        build_scene,
+        timeline.sections(),
+        config,
+        output_dir.as_ref(),
+        VideoCodec::H264,
+        progress,
+    )?
+    .into_iter()
+    .map(|(_, video)| video)
+    .collect();
+
+    if let Some(output) = concat_output {
+        if config.cancel.is_cancelled() {
+            let count = videos.len() as u32;
+            return Err(RenderCancelled::new(RenderStage::Joining, 0, count, output).into());
+        }
+        progress.on_stage(RenderStage::Joining);
+        concat_videos(&videos, output)?;
+    }
+
+    progress.on_stage(RenderStage::Finished);
+    Ok(videos)
+}
+
+/// Render the scene as an HTML slide deck in `output_dir`: a WebM video per
+/// timeline cue (see [`Timeline::cues`]) and an `index.html` presenting them
+/// one key press at a time (see [`crate::export::slides`])
+///
+/// Like [`render_sections`], each cue starts from a fresh scene built by
+/// `build_scene`. Returns the path of the deck's page.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn render_slides(
+    renderer: &mut ShapeRenderer,
+    build_scene: impl FnMut() -> SceneGraph,
+    timeline: &Timeline,
+    config: &RenderConfig,
+    title: &str,
+    output_dir: impl AsRef<Path>,
+    progress: &mut dyn ProgressSink,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let output_dir = output_dir.as_ref();
+    let cues = timeline.cues(config.duration);
+    let videos = render_segments(
+        renderer,
+        build_scene,
+        &cues,
+        config,
+        output_dir,
+        VideoCodec::Vp9,
+        progress,
+    )?;
+
+    let slides: Vec<Slide> = videos
+        .iter()
+        .map(|(cue, video)| {
+            let file = video.file_name().unwrap_or_default().to_string_lossy();
+            Slide::new(&cue.name, file)
+        })
+        .collect();
+    let page = output_dir.join("index.html");
+    write_slide_deck(&page, title, &slides)?;
+    progress.on_stage(RenderStage::Finished);
+    Ok(page)
+}
+
+/// Render each of `segments` from a fresh scene to a `codec` video in
+/// `output_dir`, skipping empty ones
+#[cfg(not(target_arch = "wasm32"))]
+fn render_segments<'a>(
+    renderer: &mut ShapeRenderer,
+    mut build_scene: impl FnMut() -> SceneGraph,
+    segments: &'a [Section],
+    config: &RenderConfig,
+    output_dir: &Path,
+    codec: VideoCodec,
+    progress: &mut dyn ProgressSink,
+) -> Result<Vec<(&'a Section, PathBuf)>, Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(output_dir)?;
+
+    let mut videos = Vec::new();
+    for (i, segment) in segments.iter().enumerate() {
+        let segment_config = config
+            .clone()
+            .with_frames_dir(config.frames_dir.join(&segment.name))
+            .with_section(segment);
+        if segment_config.frame_count() == 0 {
+            continue;
+        }
+
+        let mut scene = build_scene();
+        render_frames_with_progress(renderer, &mut scene, &segment_config, progress)?;
+
+        let video = output_dir.join(format!("{:02}_{}.{}", i, segment.name, codec.extension()));
+        let settings = VideoExportSettings::new(
+            config.width,
+            config.height,
+            config.fps,
+            video.to_string_lossy().into_owned(),
+            segment_config.frame_pattern(),
+        )
+        .with_codec(codec)
+        .with_cancellation(config.cancel.clone());
+        export_video_ffmpeg_with_progress(&settings, progress)?;
+        videos.push((segment, video));
+    }
+    Ok(videos)
+}
+
+/// Video file of chunk `index` of `count` in `output_dir`
+pub fn chunk_path(output_dir: impl AsRef<Path>, index: u32, count: u32) -> PathBuf {
+    output_dir
+        .as_ref()
+        .join(format!("chunk_{index:04}_of_{count:04}.mp4"))
+}
+
+/// Render chunk `index` of `count` (see [`RenderConfig::with_chunk`]) and
+/// encode it to [`chunk_path`] in `output_dir`
+///
+/// Each process or machine of a render farm runs this for its own chunks with
+/// the same `config` and a freshly built `scene`; frames before the chunk are
+/// simulated but not drawn, so every chunk sees the exact scene state a single
+/// render would. Frames go to a `chunk_NNNN` directory under
+/// `config.frames_dir`. Rendering and encoding are reported to `progress`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn render_chunk(
+    renderer: &mut ShapeRenderer,
+    scene: &mut SceneGraph,
+    config: &RenderConfig,
+    index: u32,
+    count: u32,
+    output_dir: impl AsRef<Path>,
+    progress: &mut dyn ProgressSink,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if index >= count {
+        return Err(format!("chunk {index} out of range for {count} chunks").into());
+    }
+    let chunk_config = config
+        .clone()
+        .with_frames_dir(config.frames_dir.join(format!("chunk_{index:04}")))
+        .with_chunk(index, count);
+    if chunk_config.frame_count() == 0 {
+        return Err(format!("chunk {index} of {count} has no frames").into());
+    }
+
+    render_frames_with_progress(renderer, scene, &chunk_config, progress)?;
+
+    let output_dir = output_dir.as_ref();
+    std::fs::create_dir_all(output_dir)?;
+    let video = chunk_path(output_dir, index, count);
+    let settings = VideoExportSettings::new(
+        config.width,
+        config.height,
+        config.fps,
+        video.to_string_lossy().into_owned(),
+        chunk_config.frame_pattern(),
+    )
+    .with_cancellation(config.cancel.clone());
+    export_video_ffmpeg_with_progress(&settings, progress)?;
+    progress.on_stage(RenderStage::Finished);
+    Ok(video)
+}
+
+/// Join the `count` chunk videos rendered by [`render_chunk`] into `output`,
+/// in chunk order
+///
+/// Fails without writing anything if a chunk is missing, so a partially
+/// finished farm render never produces a short video.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn merge_chunks(
+    chunk_dir: impl AsRef<Path>,
+    count: u32,
+    output: impl AsRef<Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let chunks: Vec<PathBuf> = (0..count)
+        .map(|index| chunk_path(&chunk_dir, index, count))
+        .collect();
+    let missing: Vec<String> = chunks
+        .iter()
+        .filter(|chunk| !chunk.is_file())
+        .map(|chunk| chunk.display().to_string())
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("missing chunks: {}", missing.join(", ")).into());
+    }
+    concat_videos(&chunks, output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_config_frames() {
+        let config = RenderConfig::new(1280, 720, 30, 2.5).with_frames_dir("out/frames");
+        assert_eq!(config.frame_count(), 75);
+        assert!((config.frame_time() - 1.0 / 30.0).abs() < 1e-6);
+        assert_eq!(
+            config.frame_path(7),
+            Path::new("out/frames/frame_00007.png")
+        );
+        assert_eq!(config.frame_pattern(), "out/frames/frame_%05d.png");
+        assert!(config.cache_dir.is_none());
+    }
+
+    #[test]
+    fn test_section_frame_ranges_are_contiguous() {
+        let mut timeline = Timeline::new();
+        timeline.section("intro");
+        timeline.jump_to(TimeValue::new(1.01));
+        timeline.section("proof");
+
+        let config = RenderConfig::new(640, 360, 30, 3.0);
+        let intro = config.clone().with_section(&timeline.sections()[0]);
+        let proof = config.clone().with_section(&timeline.sections()[1]);
+
+        assert_eq!(intro.frame_range(), 0..30);
+        assert_eq!(proof.frame_range(), 30..90);
+        assert_eq!(
+            intro.frame_count() + proof.frame_count(),
+            config.frame_count()
+        );
+        assert_eq!(config.with_time_range(2.5, 10.0).frame_range(), 75..90);
+    }
+
+    #[test]
+    fn test_chunks_tile_frame_range() {
+        let config = RenderConfig::new(640, 360, 30, 3.3).with_time_range(0.5, 10.0);
+        assert_eq!(config.frame_range(), 15..99);
+
+        let chunks: Vec<Range<u32>> = (0..4)
+            .map(|index| config.clone().with_chunk(index, 4).frame_range())
+            .collect();
+        assert_eq!(chunks, vec![15..36, 36..57, 57..78, 78..99]);
+        assert_eq!(config.clone().with_chunk(4, 4).frame_count(), 0);
+        assert_eq!(config.clone().with_frames(0, 20).frame_range(), 15..20);
+        assert_eq!(
+            chunk_path("farm", 2, 4),
+            Path::new("farm/chunk_0002_of_0004.mp4")
+        );
+    }
+}
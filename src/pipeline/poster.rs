@@ -0,0 +1,219 @@
+//! Poster frames and contact sheets
+//!
+//! [`render_poster`] writes the frame a render would show at a given time
+//! as a single PNG, for video thumbnails. [`render_contact_sheet`] writes
+//! evenly spaced frames of the whole duration, scaled down into a grid in
+//! one PNG, for checking a long animation at a glance.
+//!
+//! Both step the scene frame by frame exactly like
+//! [`render_frames`](super::render_frames), so they show the same pixels as
+//! the video, and both start from the scene's current state: pass a freshly
+//! built scene.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use diomanim::pipeline::poster::{render_contact_sheet, render_poster, ContactSheet};
+//! use diomanim::pipeline::RenderConfig;
+//! use diomanim::prelude::*;
+//! use diomanim::scene::SceneGraph;
+//!
+//! # async fn example(build_scene: impl Fn() -> SceneGraph) -> Result<(), Box<dyn std::error::Error>> {
+//! let mut renderer = ShapeRenderer::new(1920, 1080).await?;
+//! let config = RenderConfig::new(1920, 1080, 30, 60.0);
+//!
+//! render_poster(&mut renderer, &mut build_scene(), &config, 12.5, "output/poster.png")?;
+//! let sheet = ContactSheet::new(16).with_cell_width(480);
+//! render_contact_sheet(&mut renderer, &mut build_scene(), &config, &sheet, "output/sheet.png")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{render_frame, save_png, scene_delta, RenderConfig};
+use crate::core::TimeValue;
+use crate::render::ShapeRenderer;
+use crate::scene::SceneGraph;
+use std::path::Path;
+
+/// Layout of a contact sheet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContactSheet {
+    /// Frames on the sheet
+    pub count: u32,
+    pub columns: u32,
+    /// Width of each frame on the sheet in pixels; heights keep the
+    /// render's aspect ratio
+    pub cell_width: u32,
+    /// Pixels between frames and around the edge
+    pub gap: u32,
+}
+
+impl ContactSheet {
+    /// `count` frames in a roughly square grid of 320-pixel-wide cells
+    pub fn new(count: u32) -> Self {
+        let count = count.max(1);
+        Self {
+            count,
+            columns: (count as f32).sqrt().ceil() as u32,
+            cell_width: 320,
+            gap: 8,
+        }
+    }
+
+    pub fn with_columns(mut self, columns: u32) -> Self {
+        self.columns = columns.max(1);
+        self
+    }
+
+    pub fn with_cell_width(mut self, width: u32) -> Self {
+        self.cell_width = width.max(1);
+        self
+    }
+
+    pub fn with_gap(mut self, gap: u32) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    pub fn rows(&self) -> u32 {
+        self.count.div_ceil(self.columns)
+    }
+
+    /// Indices of the frames shown out of `frame_count`, evenly spaced from
+    /// the first frame to the last
+    pub fn frame_indices(&self, frame_count: u32) -> Vec<u32> {
+        let last = frame_count.saturating_sub(1);
+        if self.count == 1 || last == 0 {
+            return vec![0; self.count.min(frame_count.max(1)) as usize];
+        }
+        let steps = u64::from(self.count - 1);
+        let mut indices: Vec<u32> = (0..self.count)
+            .map(|i| ((u64::from(i) * u64::from(last) + steps / 2) / steps) as u32)
+            .collect();
+        // Fewer frames than cells show each frame once
+        indices.dedup();
+        indices
+    }
+}
+
+/// Step `scene` from frame `from` to frame `to` of `config`, the way the
+/// render loop does
+fn step_frames(scene: &mut SceneGraph, config: &RenderConfig, from: u32, to: u32) {
+    let step = TimeValue::new(config.frame_time());
+    for frame in from + 1..=to {
+        let time = TimeValue::new(frame as f32 * config.frame_time());
+        scene.update_animations(scene_delta(config, time, step));
+        scene.update_transforms();
+    }
+}
+
+/// Write the frame of `config` shown at `time` seconds as a PNG
+pub fn render_poster(
+    renderer: &mut ShapeRenderer,
+    scene: &mut SceneGraph,
+    config: &RenderConfig,
+    time: f32,
+    path: impl AsRef<Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    scene.update_transforms();
+    step_frames(scene, config, 0, config.time_to_frame(time.max(0.0)));
+    let pixels = render_frame(renderer, scene, config)?;
+    if let Some(parent) = path.as_ref().parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    save_png(path, config.width, config.height, &pixels)
+}
+
+/// Write `sheet.count` evenly spaced frames of `config` as a grid in one PNG
+/// on the render's background, in reading order
+pub fn render_contact_sheet(
+    renderer: &mut ShapeRenderer,
+    scene: &mut SceneGraph,
+    config: &RenderConfig,
+    sheet: &ContactSheet,
+    path: impl AsRef<Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cell_width = sheet.cell_width;
+    let cell_height = (u64::from(cell_width) * u64::from(config.height))
+        .div_ceil(u64::from(config.width.max(1))) as u32;
+    let width = sheet.columns * (cell_width + sheet.gap) + sheet.gap;
+    let height = sheet.rows() * (cell_height + sheet.gap) + sheet.gap;
+    let (r, g, b) = config.background.to_rgb8();
+    let mut pixels = [r, g, b, 255].repeat(width as usize * height as usize);
+
+    scene.update_transforms();
+    let mut current = 0;
+    let total = (config.duration * config.fps as f32).round() as u32;
+    for (cell, &index) in sheet.frame_indices(total).iter().enumerate() {
+        step_frames(scene, config, current, index);
+        current = index;
+        let frame = render_frame(renderer, scene, config)?;
+        let thumbnail = resize(&frame, config.width, config.height, cell_width, cell_height);
+
+        let (column, row) = (cell as u32 % sheet.columns, cell as u32 / sheet.columns);
+        let x = (sheet.gap + column * (cell_width + sheet.gap)) as usize;
+        let y = (sheet.gap + row * (cell_height + sheet.gap)) as usize;
+        let row_bytes = cell_width as usize * 4;
+        for (line, source) in thumbnail.chunks_exact(row_bytes).enumerate() {
+            let dest = ((y + line) * width as usize + x) * 4;
+            pixels[dest..dest + row_bytes].copy_from_slice(source);
+        }
+    }
+
+    if let Some(parent) = path.as_ref().parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    save_png(path, width, height, &pixels)
+}
+
+/// Scale RGBA `pixels` to `to_width` x `to_height`, averaging the source
+/// pixels under each output pixel
+fn resize(pixels: &[u8], width: u32, height: u32, to_width: u32, to_height: u32) -> Vec<u8> {
+    // Source pixels `start..end` under output pixel `i` of `to` along an axis of `len`
+    let span = |i: u32, to: u32, len: u32| {
+        let start = (u64::from(i) * u64::from(len) / u64::from(to)) as u32;
+        let end = (u64::from(i + 1) * u64::from(len)).div_ceil(u64::from(to)) as u32;
+        start..end.max(start + 1).min(len)
+    };
+    let mut output = Vec::with_capacity(to_width as usize * to_height as usize * 4);
+    for out_y in 0..to_height {
+        let rows = span(out_y, to_height, height);
+        for out_x in 0..to_width {
+            let mut sum = [0u64; 4];
+            let mut count = 0;
+            for y in rows.clone() {
+                for x in span(out_x, to_width, width) {
+                    let i = (y as usize * width as usize + x as usize) * 4;
+                    for channel in 0..4 {
+                        sum[channel] += u64::from(pixels[i + channel]);
+                    }
+                    count += 1;
+                }
+            }
+            output.extend(sum.map(|value| ((value + count / 2) / count.max(1)) as u8));
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contact_sheet_layout() {
+        let sheet = ContactSheet::new(10);
+        assert_eq!((sheet.columns, sheet.rows()), (4, 3));
+        assert_eq!(sheet.with_columns(5).rows(), 2);
+
+        // First and last frames are always on the sheet
+        let indices = ContactSheet::new(5).frame_indices(301);
+        assert_eq!(indices, [0, 75, 150, 225, 300]);
+        assert_eq!(ContactSheet::new(8).frame_indices(3), [0, 1, 2]);
+        assert_eq!(ContactSheet::new(4).frame_indices(0), [0]);
+
+        // A 4x2 checker of black and white halves to two grey pixels
+        let checker = [[0u8, 0, 0, 255], [255; 4]].concat().repeat(4);
+        assert_eq!(resize(&checker, 4, 2, 2, 1), [128, 128, 128, 255].repeat(2));
+    }
+}
@@ -0,0 +1,130 @@
+//! Progress reporting for long renders
+//!
+//! Render and export functions report to a [`ProgressSink`] instead of
+//! printing, so a GUI can drive a progress bar, the render server can push
+//! notifications over its socket, and a CI job can log a line per stage.
+//! Any `FnMut(frame, total)` closure is a sink that only listens for
+//! frames; [`ConsoleProgress`] prints to stdout and [`NoProgress`] ignores
+//! everything.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::pipeline::{ProgressSink, RenderStage};
+//!
+//! #[derive(Default)]
+//! struct Log(Vec<String>);
+//!
+//! impl ProgressSink for Log {
+//!     fn on_frame(&mut self, frame: u32, total: u32) {
+//!         self.0.push(format!("{frame}/{total}"));
+//!     }
+//!     fn on_stage(&mut self, stage: RenderStage) {
+//!         self.0.push(stage.to_string());
+//!     }
+//! }
+//!
+//! let mut log = Log::default();
+//! log.on_stage(RenderStage::Rendering);
+//! log.on_frame(1, 30);
+//! assert_eq!(log.0, ["rendering", "1/30"]);
+//! ```
+
+use std::fmt;
+use std::io::Write;
+
+/// What a render is busy with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderStage {
+    /// Drawing frames; followed by `on_frame` for each frame written
+    Rendering,
+    /// Encoding frames with ffmpeg; followed by `on_frame` for each frame
+    /// encoded
+    Encoding,
+    /// Joining section or chunk videos
+    Joining,
+    /// Everything is written
+    Finished,
+}
+
+impl fmt::Display for RenderStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Rendering => "rendering",
+            Self::Encoding => "encoding",
+            Self::Joining => "joining",
+            Self::Finished => "finished",
+        })
+    }
+}
+
+/// Receives progress of a render or export
+///
+/// Both methods do nothing by default, so sinks implement only what they
+/// display.
+pub trait ProgressSink {
+    /// `frame` of `total` frames of the current stage is done (1-based)
+    fn on_frame(&mut self, _frame: u32, _total: u32) {}
+
+    /// A new stage started
+    fn on_stage(&mut self, _stage: RenderStage) {}
+}
+
+impl<F: FnMut(u32, u32)> ProgressSink for F {
+    fn on_frame(&mut self, frame: u32, total: u32) {
+        self(frame, total);
+    }
+}
+
+/// Ignores all progress
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl ProgressSink for NoProgress {}
+
+/// Prints each stage and a percentage line updated in place to stdout
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConsoleProgress {
+    stage: Option<RenderStage>,
+    percent: Option<u32>,
+}
+
+impl ProgressSink for ConsoleProgress {
+    fn on_frame(&mut self, frame: u32, total: u32) {
+        let percent = (u64::from(frame) * 100 / u64::from(total.max(1))) as u32;
+        if self.percent == Some(percent) {
+            return;
+        }
+        self.percent = Some(percent);
+        let stage = self.stage.unwrap_or(RenderStage::Rendering);
+        print!("\r  {stage} {frame}/{total} ({percent}%)");
+        if frame >= total {
+            println!();
+        }
+        let _ = std::io::stdout().flush();
+    }
+
+    fn on_stage(&mut self, stage: RenderStage) {
+        self.stage = Some(stage);
+        self.percent = None;
+        if stage == RenderStage::Finished {
+            println!("✅ Done");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closures_receive_frames() {
+        let mut frames = Vec::new();
+        let mut sink = |frame, total| frames.push((frame, total));
+        let sink: &mut dyn ProgressSink = &mut sink;
+        sink.on_stage(RenderStage::Encoding);
+        sink.on_frame(1, 2);
+        sink.on_frame(2, 2);
+        assert_eq!(frames, [(1, 2), (2, 2)]);
+    }
+}
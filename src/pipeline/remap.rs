@@ -0,0 +1,173 @@
+//! Time remapping on export
+//!
+//! A [`TimeRemap`] is a keyframed speed curve over output time: at speed 1
+//! the scene plays as authored, at 0.25 it plays in slow motion, at 2 it
+//! plays double speed, and at 0 it holds. Set on a render with
+//! [`RenderConfig::with_time_remap`], it decides how far the scene advances
+//! between output frames, so slow-motion ramps and speed-ups need no change
+//! to the animations themselves. The render's `duration` stays in output
+//! seconds.
+//!
+//! Sound cues are timed in scene seconds; move them onto the output
+//! timeline with [`TimeRemap::remap_sound_cues`].
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::pipeline::remap::TimeRemap;
+//!
+//! // Normal speed for a second, then ramp down to quarter speed
+//! let remap = TimeRemap::new().with_speed(1.0, 1.0).with_speed(2.0, 0.25);
+//! assert_eq!(remap.scene_time(1.0), 1.0);
+//! assert!((remap.scene_time(2.0) - 1.625).abs() < 1e-4);
+//! assert!((remap.scene_time(6.0) - 2.625).abs() < 1e-4);
+//! ```
+//!
+//! [`RenderConfig::with_time_remap`]: super::RenderConfig::with_time_remap
+
+use crate::animation::property::{AnimationTrack, InterpolationType, Keyframe};
+use crate::core::{SoundCue, TimeValue};
+
+/// Samples per keyframe segment when integrating the speed curve
+const SEGMENT_SAMPLES: u32 = 16;
+
+/// Speed curve mapping output time to scene time
+#[derive(Debug, Clone)]
+pub struct TimeRemap {
+    /// Scene seconds per output second, keyed on output time
+    pub speed: AnimationTrack<f32>,
+}
+
+impl Default for TimeRemap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeRemap {
+    /// Normal speed throughout
+    pub fn new() -> Self {
+        Self {
+            speed: AnimationTrack::with_default_value("speed".to_string(), 1.0),
+        }
+    }
+
+    /// Reach `speed` at `output_time`, ramping linearly from the previous
+    /// keyframe
+    pub fn with_speed(self, output_time: f32, speed: f32) -> Self {
+        self.with_eased_speed(output_time, speed, InterpolationType::Linear)
+    }
+
+    /// Set `speed` at `output_time`, easing into the next keyframe with
+    /// `interpolation`
+    pub fn with_eased_speed(
+        mut self,
+        output_time: f32,
+        speed: f32,
+        interpolation: InterpolationType,
+    ) -> Self {
+        self.speed.add_keyframe(
+            Keyframe::new(TimeValue::new(output_time), speed.max(0.0))
+                .with_interpolation(interpolation),
+        );
+        self
+    }
+
+    /// Scene seconds per output second at `output_time`
+    pub fn speed_at(&self, output_time: f32) -> f32 {
+        self.speed.sample(TimeValue::new(output_time)).max(0.0)
+    }
+
+    /// Scene time shown at `output_time`: the speed curve integrated from 0
+    pub fn scene_time(&self, output_time: f32) -> f32 {
+        if output_time <= 0.0 {
+            return 0.0;
+        }
+        // Integrate piecewise between keyframes, where the curve is smooth
+        let mut bounds = vec![0.0];
+        bounds.extend(
+            self.speed
+                .keyframes
+                .iter()
+                .map(|keyframe| keyframe.time.value)
+                .filter(|&time| time > 0.0 && time < output_time),
+        );
+        bounds.push(output_time);
+        bounds
+            .windows(2)
+            .map(|segment| {
+                let (start, end) = (segment[0], segment[1]);
+                let step = (end - start) / SEGMENT_SAMPLES as f32;
+                (0..SEGMENT_SAMPLES)
+                    .map(|i| self.speed_at(start + (i as f32 + 0.5) * step) * step)
+                    .sum::<f32>()
+            })
+            .sum()
+    }
+
+    /// Output time at which the scene reaches `scene_time`, or `None` if the
+    /// curve holds at speed 0 before reaching it
+    pub fn output_time(&self, scene_time: f32) -> Option<f32> {
+        if scene_time <= 0.0 {
+            return Some(0.0);
+        }
+        // Past the last keyframe the speed is constant
+        let last = self.speed.keyframes.last().map_or(0.0, |k| k.time.value);
+        let (last_scene, last_speed) = (self.scene_time(last), self.speed_at(last));
+        if scene_time >= last_scene {
+            return (last_speed > 0.0).then(|| last + (scene_time - last_scene) / last_speed);
+        }
+        // Scene time never decreases, so bisect
+        let (mut low, mut high) = (0.0, last);
+        for _ in 0..40 {
+            let middle = f32::midpoint(low, high);
+            if self.scene_time(middle) < scene_time {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        Some(high)
+    }
+
+    /// `cues` moved from scene time to output time; cues the output never
+    /// reaches are dropped
+    pub fn remap_sound_cues(&self, cues: &[SoundCue]) -> Vec<SoundCue> {
+        cues.iter()
+            .filter_map(|cue| {
+                let time = self.output_time(cue.time.value)?;
+                Some(SoundCue {
+                    time: TimeValue::new(time),
+                    ..cue.clone()
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_remap_round_trips() {
+        assert_eq!(TimeRemap::new().scene_time(3.0), 3.0);
+
+        // Hold between 1s and 2s, then double speed
+        let remap = TimeRemap::new()
+            .with_eased_speed(0.0, 1.0, InterpolationType::Step)
+            .with_eased_speed(1.0, 0.0, InterpolationType::Step)
+            .with_speed(2.0, 2.0);
+        assert!((remap.scene_time(1.5) - 1.0).abs() < 1e-5);
+        assert!((remap.scene_time(3.0) - 3.0).abs() < 1e-5);
+        let output = remap.output_time(2.0).unwrap();
+        assert!((output - 2.5).abs() < 1e-3);
+
+        let cues = remap.remap_sound_cues(&[SoundCue::new("pop.wav", 3.0)]);
+        assert!((cues[0].time.value - 3.0).abs() < 1e-3);
+
+        // A curve ending in a hold never reaches later scene times
+        let frozen = TimeRemap::new().with_speed(0.0, 1.0).with_speed(1.0, 0.0);
+        assert!(frozen.output_time(5.0).is_none());
+    }
+}
@@ -0,0 +1,152 @@
+//! Resuming interrupted renders
+//!
+//! Every render writes a small manifest into its frames directory holding
+//! a fingerprint of its settings and of the scene's starting state. With
+//! [`RenderConfig::with_resume`], a later render into the same directory
+//! checks the fingerprint and skips the frames already on disk: the scene
+//! is still stepped through them, but nothing is drawn or written until
+//! the first missing frame. The last frame found is decoded in full, so a
+//! frame cut short by a crash is rendered again.
+//!
+//! Resuming into a directory whose manifest doesn't match (another scene,
+//! resolution, frame rate or range) fails instead of mixing frames of two
+//! renders.
+
+use super::cache::{frame_state_hash, FrameHasher};
+use super::{ClockMode, RenderConfig};
+use crate::scene::SceneGraph;
+use std::path::Path;
+
+/// Manifest file written into each frames directory
+pub const MANIFEST_FILE: &str = "render_manifest.txt";
+
+/// First line of every manifest, so unrelated files are never trusted
+const MANIFEST_HEADER: &str = "diomanim render manifest v1";
+
+/// Fingerprint of everything that decides the frames of a render
+///
+/// `scene` must be in its starting state; `passes` names the render graph's
+/// passes.
+pub fn settings_hash(config: &RenderConfig, scene: &SceneGraph, passes: &str) -> u64 {
+    let mut hasher = FrameHasher::new();
+    hasher.write_u32(config.fps);
+    hasher.write_f32(config.duration);
+    let range = config.frame_range();
+    hasher.write_u32(range.start);
+    hasher.write_u32(range.end);
+    hasher.write_u32(u32::from(config.clock == ClockMode::RealTime));
+    hasher.write_str(passes);
+    if let Some(remap) = &config.time_remap {
+        for keyframe in &remap.speed.keyframes {
+            hasher.write_f32(keyframe.time.value);
+            hasher.write_f32(keyframe.value);
+            hasher.write_str(&format!("{:?}", keyframe.interpolation));
+        }
+    }
+    hasher.write_bytes(
+        &frame_state_hash(scene, config.width, config.height, config.background).to_le_bytes(),
+    );
+    hasher.finish()
+}
+
+/// Record `settings` (see [`settings_hash`]) in the manifest of `frames_dir`
+pub fn write_manifest(frames_dir: &Path, settings: u64) -> std::io::Result<()> {
+    std::fs::write(
+        frames_dir.join(MANIFEST_FILE),
+        format!("{MANIFEST_HEADER}\nsettings {settings:016x}\n"),
+    )
+}
+
+/// Settings fingerprint recorded in the manifest of `frames_dir`, if any
+pub fn read_manifest(frames_dir: &Path) -> Option<u64> {
+    let text = std::fs::read_to_string(frames_dir.join(MANIFEST_FILE)).ok()?;
+    let mut lines = text.lines();
+    if lines.next() != Some(MANIFEST_HEADER) {
+        return None;
+    }
+    let settings = lines.next()?.strip_prefix("settings ")?;
+    u64::from_str_radix(settings, 16).ok()
+}
+
+/// Number of leading frames of `config` already complete in its frames
+/// directory, for a render fingerprinted as `settings`
+///
+/// Returns 0 when the directory has no manifest, and an error when it was
+/// rendered with other settings.
+pub fn resume_point(config: &RenderConfig, settings: u64) -> Result<u32, String> {
+    let Some(recorded) = read_manifest(&config.frames_dir) else {
+        return Ok(0);
+    };
+    if recorded != settings {
+        return Err(format!(
+            "can't resume: the frames in {} were rendered from a different scene or settings",
+            config.frames_dir.display()
+        ));
+    }
+    let mut done = (0..config.frame_count())
+        .take_while(|&index| config.frame_path(index).is_file())
+        .count() as u32;
+    while done > 0 && !is_complete_frame(&config.frame_path(done - 1), config.width, config.height)
+    {
+        done -= 1;
+    }
+    Ok(done)
+}
+
+/// Whether `path` is a PNG of the given size that decodes to the end
+fn is_complete_frame(path: &Path, width: u32, height: u32) -> bool {
+    let Ok(file) = std::fs::File::open(path) else {
+        return false;
+    };
+    let Ok(mut reader) = png::Decoder::new(std::io::BufReader::new(file)).read_info() else {
+        return false;
+    };
+    let info = reader.info();
+    if (info.width, info.height) != (width, height) {
+        return false;
+    }
+    let mut pixels = vec![0; reader.output_buffer_size().unwrap_or(0)];
+    reader.next_frame(&mut pixels).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Color;
+    use crate::pipeline::save_png;
+
+    #[test]
+    fn test_resume_point_skips_complete_frames() {
+        let dir = std::env::temp_dir().join(format!("diomanim_resume_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = RenderConfig::new(4, 2, 10, 1.0).with_frames_dir(&dir);
+        let mut scene = SceneGraph::new();
+        scene.add_circle("dot", 0.1, Color::RED).build();
+        let settings = settings_hash(&config, &scene, "scene");
+
+        // Frames without a manifest are not trusted
+        let pixels = [255; 4 * 2 * 4];
+        for index in 0..4 {
+            save_png(config.frame_path(index), 4, 2, &pixels).unwrap();
+        }
+        assert_eq!(resume_point(&config, settings), Ok(0));
+
+        write_manifest(&dir, settings).unwrap();
+        assert_eq!(read_manifest(&dir), Some(settings));
+        assert_eq!(resume_point(&config, settings), Ok(4));
+
+        // A truncated last frame is rendered again
+        let bytes = std::fs::read(config.frame_path(3)).unwrap();
+        std::fs::write(config.frame_path(3), &bytes[..bytes.len() / 2]).unwrap();
+        assert_eq!(resume_point(&config, settings), Ok(3));
+
+        // Other settings or scenes don't resume
+        let other = RenderConfig::new(4, 2, 24, 1.0).with_frames_dir(&dir);
+        assert_ne!(settings_hash(&other, &scene, "scene"), settings);
+        scene.add_circle("other", 0.1, Color::BLUE).build();
+        let changed = settings_hash(&config, &scene, "scene");
+        assert!(resume_point(&config, changed).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
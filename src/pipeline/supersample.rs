@@ -0,0 +1,113 @@
+//! Supersampling (SSAA)
+//!
+//! With [`RenderConfig::with_supersampling`], each frame is drawn at 2x or
+//! 4x the output resolution and filtered down to it on the CPU before it is
+//! saved. Unlike MSAA, which only smooths geometry edges, this also
+//! anti-aliases text, images, shader materials and post effects, at the
+//! cost of drawing 4x or 16x the pixels; frames too large for the GPU are
+//! tiled (see [`super::tiles`]).
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::pipeline::supersample::{downsample, DownsampleFilter};
+//!
+//! // A 2x2 checker of black and white averages to mid grey
+//! let pixels = [0, 0, 0, 255, 255, 255, 255, 255, 255, 255, 255, 255, 0, 0, 0, 255];
+//! let pixel = downsample(&pixels, 2, 2, 2, DownsampleFilter::Box);
+//! assert_eq!(pixel, [128, 128, 128, 255]);
+//! ```
+//!
+//! [`RenderConfig::with_supersampling`]: super::RenderConfig::with_supersampling
+
+/// Filter averaging supersampled pixels into output pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DownsampleFilter {
+    /// Plain average of the pixels covering each output pixel
+    #[default]
+    Box,
+    /// Triangle weights reaching half an output pixel past its edges, for
+    /// slightly softer, less aliased edges
+    Tent,
+}
+
+/// Filter `width` x `height` RGBA pixels down by `factor` in each direction
+///
+/// Colors are weighted by alpha, so transparent pixels don't darken edges.
+pub fn downsample(
+    pixels: &[u8],
+    width: u32,
+    height: u32,
+    factor: u32,
+    filter: DownsampleFilter,
+) -> Vec<u8> {
+    let factor = factor.max(1);
+    let (out_width, out_height) = (width / factor, height / factor);
+    let radius = match filter {
+        DownsampleFilter::Box => factor as f32 / 2.0,
+        DownsampleFilter::Tent => factor as f32,
+    };
+    let weight = |distance: f32| match filter {
+        DownsampleFilter::Box => 1.0,
+        DownsampleFilter::Tent => (1.0 - distance / radius).max(0.0),
+    };
+
+    let mut output = Vec::with_capacity(out_width as usize * out_height as usize * 4);
+    for out_y in 0..out_height {
+        let center_y = (out_y as f32 + 0.5) * factor as f32;
+        let rows = span(center_y, radius, height);
+        for out_x in 0..out_width {
+            let center_x = (out_x as f32 + 0.5) * factor as f32;
+            let mut sum = [0.0f32; 4];
+            let mut total = 0.0;
+            for y in rows.clone() {
+                let weight_y = weight((y as f32 + 0.5 - center_y).abs());
+                for x in span(center_x, radius, width) {
+                    let w = weight_y * weight((x as f32 + 0.5 - center_x).abs());
+                    let i = (y as usize * width as usize + x as usize) * 4;
+                    let alpha = f32::from(pixels[i + 3]) * w;
+                    for channel in 0..3 {
+                        sum[channel] += f32::from(pixels[i + channel]) * alpha;
+                    }
+                    sum[3] += alpha;
+                    total += w;
+                }
+            }
+            let alpha = sum[3].max(f32::EPSILON);
+            for &value in &sum[..3] {
+                output.push((value / alpha).round().clamp(0.0, 255.0) as u8);
+            }
+            output.push((sum[3] / total.max(f32::EPSILON)).round().clamp(0.0, 255.0) as u8);
+        }
+    }
+    output
+}
+
+/// Source pixels within `radius` of `center`, clamped to `0..len`
+fn span(center: f32, radius: f32, len: u32) -> std::ops::Range<u32> {
+    let start = (center - radius).floor().max(0.0) as u32;
+    let end = ((center + radius).ceil() as u32).min(len);
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_downsample_weights_by_alpha() {
+        // Left half opaque red, right half transparent black
+        let mut pixels = Vec::new();
+        for _ in 0..4 {
+            pixels.extend_from_slice(&[255, 0, 0, 255, 255, 0, 0, 255, 0, 0, 0, 0, 0, 0, 0, 0]);
+        }
+        let output = downsample(&pixels, 4, 4, 4, DownsampleFilter::Box);
+        assert_eq!(output, [255, 0, 0, 128]);
+
+        // Tent filtering keeps solid areas solid
+        let solid = [9u8, 99, 199, 255].repeat(8 * 8);
+        let output = downsample(&solid, 8, 8, 2, DownsampleFilter::Tent);
+        assert_eq!(output.len(), 4 * 4 * 4);
+        assert!(output.chunks(4).all(|pixel| pixel == [9, 99, 199, 255]));
+    }
+}
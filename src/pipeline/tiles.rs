@@ -0,0 +1,145 @@
+//! Tiled rendering of frames larger than the GPU allows
+//!
+//! A frame wider or taller than the device's maximum texture size (or the
+//! tile size set with [`RenderConfig::with_tile_size`]) is split into a
+//! grid of equal tiles. Each tile is drawn with the camera's projection
+//! narrowed to its part of the frame, read back, and copied into the full
+//! frame on the CPU, so 8K renders work on GPUs limited to 4K textures and
+//! need only a tile's worth of GPU memory.
+//!
+//! Post effects that sample neighbouring pixels (blur, bloom) only see the
+//! tile they run in, and may show seams at tile edges.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::pipeline::tiles::TileGrid;
+//!
+//! let grid = TileGrid::new(7680, 4320, 4096);
+//! assert_eq!((grid.columns, grid.rows), (2, 2));
+//! assert_eq!((grid.tile_width, grid.tile_height), (3840, 2160));
+//! assert_eq!(grid.tiles().count(), 4);
+//! ```
+//!
+//! [`RenderConfig::with_tile_size`]: super::RenderConfig::with_tile_size
+
+use crate::core::{Matrix4, Vector3};
+
+/// Equal tiles covering a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileGrid {
+    pub width: u32,
+    pub height: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub columns: u32,
+    pub rows: u32,
+}
+
+/// One tile of a [`TileGrid`], in frame pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    /// Pixels of the tile inside the frame; edge tiles may be cut short
+    pub width: u32,
+    pub height: u32,
+}
+
+impl TileGrid {
+    /// The fewest equal tiles of at most `max_tile` pixels per side covering
+    /// a `width` x `height` frame
+    pub fn new(width: u32, height: u32, max_tile: u32) -> Self {
+        let max_tile = max_tile.max(1);
+        let (width, height) = (width.max(1), height.max(1));
+        let columns = width.div_ceil(max_tile);
+        let rows = height.div_ceil(max_tile);
+        Self {
+            width,
+            height,
+            tile_width: width.div_ceil(columns),
+            tile_height: height.div_ceil(rows),
+            columns,
+            rows,
+        }
+    }
+
+    /// Whether the frame fits in a single tile
+    pub fn is_single(&self) -> bool {
+        self.columns == 1 && self.rows == 1
+    }
+
+    /// Tiles in row-major order, from the top left
+    pub fn tiles(&self) -> impl Iterator<Item = Tile> + '_ {
+        (0..self.rows).flat_map(move |row| {
+            (0..self.columns).map(move |column| {
+                let (x, y) = (column * self.tile_width, row * self.tile_height);
+                Tile {
+                    x,
+                    y,
+                    width: self.tile_width.min(self.width - x),
+                    height: self.tile_height.min(self.height - y),
+                }
+            })
+        })
+    }
+
+    /// Matrix applied after the frame's view-projection so a
+    /// `tile_width` x `tile_height` target shows only `tile`
+    pub fn tile_projection(&self, tile: Tile) -> Matrix4 {
+        let scale_x = self.width as f32 / self.tile_width as f32;
+        let scale_y = self.height as f32 / self.tile_height as f32;
+        // Tile center in the frame's normalized device coordinates (y up)
+        let center_x =
+            2.0 * (tile.x as f32 + self.tile_width as f32 / 2.0) / self.width as f32 - 1.0;
+        let center_y =
+            1.0 - 2.0 * (tile.y as f32 + self.tile_height as f32 / 2.0) / self.height as f32;
+        Matrix4::from_translation(Vector3::new(-scale_x * center_x, -scale_y * center_y, 0.0))
+            * Matrix4::from_scale(Vector3::new(scale_x, scale_y, 1.0))
+    }
+
+    /// Copy the part of `pixels` (a full `tile_width` x `tile_height` RGBA
+    /// readback of `tile`) inside the frame into `frame`
+    pub fn copy_tile(&self, tile: Tile, pixels: &[u8], frame: &mut [u8]) {
+        let row_bytes = tile.width as usize * 4;
+        for row in 0..tile.height as usize {
+            let source = row * self.tile_width as usize * 4;
+            let dest = ((tile.y as usize + row) * self.width as usize + tile.x as usize) * 4;
+            frame[dest..dest + row_bytes].copy_from_slice(&pixels[source..source + row_bytes]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tile_projection_maps_tile_to_full_target() {
+        let grid = TileGrid::new(1000, 600, 400);
+        assert_eq!((grid.columns, grid.rows), (3, 2));
+        assert_eq!((grid.tile_width, grid.tile_height), (334, 300));
+        let tiles: Vec<Tile> = grid.tiles().collect();
+        assert_eq!(tiles[2].width, 1000 - 2 * 334);
+        assert!(TileGrid::new(640, 360, 4096).is_single());
+
+        // The top-left corner of the bottom-middle tile lands on the top-left
+        // of its target
+        let tile = tiles[4];
+        let corner = Vector3::new(
+            2.0 * tile.x as f32 / 1000.0 - 1.0,
+            1.0 - 2.0 * tile.y as f32 / 600.0,
+            0.0,
+        );
+        let mapped = grid.tile_projection(tile).transform_point(corner);
+        assert!((mapped.x + 1.0).abs() < 1e-5 && (mapped.y - 1.0).abs() < 1e-5);
+
+        // Stitching puts each tile's pixels at its offset
+        let mut frame = vec![0; 1000 * 600 * 4];
+        let pixels = vec![7; 334 * 300 * 4];
+        grid.copy_tile(tiles[5], &pixels, &mut frame);
+        let last = frame.len() - 4;
+        assert_eq!(frame[last], 7);
+        assert_eq!(frame[0], 0);
+    }
+}
@@ -0,0 +1,274 @@
+//! Watermarks and logo overlays
+//!
+//! A [`Watermark`] set with [`RenderConfig::with_watermark`] is drawn over
+//! every frame by a [`WatermarkPass`] after post effects, so channel logos
+//! and "DRAFT" stamps are part of the frames themselves: no ffmpeg filter,
+//! and the same result in video, image sequence and poster exports. It is
+//! pinned to a corner (or the center) of the frame, `margin` pixels in,
+//! whatever the scene does.
+//!
+//! Text watermarks use the renderer's font, so text rendering must be
+//! initialized (see [`ShapeRenderer::init_text_rendering`]); image
+//! watermarks show a PNG loaded by the renderer's asset server.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::core::Color;
+//! use diomanim::pipeline::watermark::{Watermark, WatermarkAnchor};
+//! use diomanim::pipeline::RenderConfig;
+//!
+//! let config = RenderConfig::new(1920, 1080, 30, 10.0).with_watermark(
+//!     Watermark::text("DRAFT", 24.0, Color::WHITE)
+//!         .with_anchor(WatermarkAnchor::TopRight)
+//!         .with_margin(32)
+//!         .with_opacity(0.5),
+//! );
+//! assert!(config.watermark.is_some());
+//! ```
+//!
+//! [`RenderConfig::with_watermark`]: super::RenderConfig::with_watermark
+
+use super::draw_scene_layer;
+use crate::assets::AssetHandle;
+use crate::core::{Color, Matrix4};
+use crate::render::graph::{DrawLayer, PassContext, RenderNode};
+use crate::render::{PassCategory, ShapeRenderer};
+use crate::scene::SceneGraph;
+
+/// What a watermark shows
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatermarkContent {
+    Text {
+        content: String,
+        font_size: f32,
+        color: Color,
+    },
+    /// `width` x `height` in scene units
+    Image {
+        asset: AssetHandle,
+        width: f32,
+        height: f32,
+    },
+}
+
+/// Part of the frame a watermark is pinned to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WatermarkAnchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+    Center,
+}
+
+/// Overlay drawn over every frame of a render
+#[derive(Debug, Clone, PartialEq)]
+pub struct Watermark {
+    pub content: WatermarkContent,
+    pub anchor: WatermarkAnchor,
+    /// Distance from the anchored frame edges in pixels
+    pub margin: u32,
+    pub opacity: f32,
+}
+
+impl Watermark {
+    /// Fully opaque text in the bottom right corner, 16 pixels in
+    pub fn text(content: impl Into<String>, font_size: f32, color: Color) -> Self {
+        Self::new(WatermarkContent::Text {
+            content: content.into(),
+            font_size,
+            color,
+        })
+    }
+
+    /// A loaded image (see [`crate::assets::AssetServer`]), placed like
+    /// [`Self::text`]
+    pub fn image(asset: AssetHandle, width: f32, height: f32) -> Self {
+        Self::new(WatermarkContent::Image {
+            asset,
+            width,
+            height,
+        })
+    }
+
+    fn new(content: WatermarkContent) -> Self {
+        Self {
+            content,
+            anchor: WatermarkAnchor::default(),
+            margin: 16,
+            opacity: 1.0,
+        }
+    }
+
+    pub fn with_anchor(mut self, anchor: WatermarkAnchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    pub fn with_margin(mut self, pixels: u32) -> Self {
+        self.margin = pixels;
+        self
+    }
+
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Extent of the content around its node's origin, as `(min, max)`
+    /// corners in scene units
+    fn bounds(&self, renderer: &ShapeRenderer) -> ((f32, f32), (f32, f32)) {
+        match &self.content {
+            WatermarkContent::Text {
+                content, font_size, ..
+            } => renderer.text_bounds(content, *font_size),
+            WatermarkContent::Image { width, height, .. } => {
+                ((-width / 2.0, -height / 2.0), (width / 2.0, height / 2.0))
+            }
+        }
+    }
+
+    /// Position of the content's origin that puts its `bounds` at the
+    /// anchor of a `width` x `height` frame
+    pub fn position(
+        &self,
+        ((min_x, min_y), (max_x, max_y)): ((f32, f32), (f32, f32)),
+        width: u32,
+        height: u32,
+    ) -> (f32, f32) {
+        // Frame coordinates run from -1 to 1 on both axes
+        let margin_x = 2.0 * self.margin as f32 / width.max(1) as f32;
+        let margin_y = 2.0 * self.margin as f32 / height.max(1) as f32;
+        let left = -1.0 + margin_x - min_x;
+        let right = 1.0 - margin_x - max_x;
+        let top = 1.0 - margin_y - max_y;
+        let bottom = -1.0 + margin_y - min_y;
+        match self.anchor {
+            WatermarkAnchor::TopLeft => (left, top),
+            WatermarkAnchor::TopRight => (right, top),
+            WatermarkAnchor::BottomLeft => (left, bottom),
+            WatermarkAnchor::BottomRight => (right, bottom),
+            WatermarkAnchor::Center => (-(min_x + max_x) / 2.0, -(min_y + max_y) / 2.0),
+        }
+    }
+}
+
+/// Render graph pass drawing a [`Watermark`] over `output`
+pub struct WatermarkPass {
+    output: String,
+    watermark: Watermark,
+    /// Size of the whole frame, which may be drawn in smaller tiles
+    frame: (u32, u32),
+    /// Projection the graph's scene passes frame the scene with, which the
+    /// watermark ignores
+    framing: Matrix4,
+    /// Scene holding the watermark node, laid out on the first run
+    overlay: Option<SceneGraph>,
+}
+
+impl WatermarkPass {
+    /// Draw `watermark` on top of a `width` x `height` frame in `output`
+    pub fn new(output: &str, watermark: Watermark, width: u32, height: u32) -> Self {
+        Self {
+            output: output.to_string(),
+            watermark,
+            frame: (width, height),
+            framing: Matrix4::identity(),
+            overlay: None,
+        }
+    }
+
+    /// Stay pinned to the frame when the graph's camera includes `framing`
+    /// (see [`RenderConfig::framing`](super::RenderConfig::framing))
+    pub fn with_framing(mut self, framing: Matrix4) -> Self {
+        self.framing = framing;
+        self
+    }
+}
+
+impl RenderNode for WatermarkPass {
+    fn name(&self) -> &'static str {
+        "watermark"
+    }
+
+    fn inputs(&self) -> Vec<&str> {
+        Vec::new()
+    }
+
+    fn outputs(&self) -> Vec<&str> {
+        vec![&self.output]
+    }
+
+    fn category(&self) -> PassCategory {
+        PassCategory::Post
+    }
+
+    fn run(&mut self, ctx: &mut PassContext) {
+        let watermark = &self.watermark;
+        let (width, height) = self.frame;
+        let overlay = self.overlay.get_or_insert_with(|| {
+            let (x, y) = watermark.position(watermark.bounds(ctx.renderer), width, height);
+            let mut overlay = SceneGraph::new();
+            let node = match &watermark.content {
+                WatermarkContent::Text {
+                    content,
+                    font_size,
+                    color,
+                } => overlay.add_text("watermark", content.clone(), *font_size, *color),
+                WatermarkContent::Image {
+                    asset,
+                    width,
+                    height,
+                } => overlay.add_image("watermark", *asset, *width, *height),
+            };
+            node.at(x, y, 0.0).opacity(watermark.opacity);
+            overlay.update_transforms();
+            overlay
+        });
+
+        // Keep any tile projection, drop the framing
+        let unframed = glam::Mat4::from(self.framing).inverse();
+        let view_proj = ctx.view_proj * Matrix4::from(unframed);
+        let format = ctx.format(&self.output);
+        let mut render_pass = ctx.renderer.begin_render_pass_with_load(
+            ctx.encoder,
+            ctx.view(&self.output),
+            format,
+            wgpu::LoadOp::Load,
+        );
+        draw_scene_layer(
+            ctx.renderer,
+            overlay,
+            &view_proj,
+            ctx.eye,
+            DrawLayer::All,
+            &mut render_pass,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watermark_keeps_margin_from_anchored_edges() {
+        let bounds = ((-0.1, -0.05), (0.1, 0.05));
+        let watermark = Watermark::image(AssetHandle(1), 0.2, 0.1).with_margin(50);
+        let (x, y) = watermark.position(bounds, 1000, 500);
+        assert!((x + 0.1 - (1.0 - 0.1)).abs() < 1e-6);
+        assert!((y - 0.05 - (-1.0 + 0.2)).abs() < 1e-6);
+
+        let top_left = watermark.with_anchor(WatermarkAnchor::TopLeft);
+        let (x, y) = top_left.position(((0.0, -0.3), (0.4, 0.0)), 1000, 500);
+        assert!((x - (-0.9)).abs() < 1e-6);
+        assert!((y - 0.8).abs() < 1e-6);
+        let center = top_left.with_anchor(WatermarkAnchor::Center);
+        assert_eq!(
+            center.position(((0.0, -0.3), (0.4, 0.0)), 1000, 500),
+            (-0.2, 0.15)
+        );
+    }
+}
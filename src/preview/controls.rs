@@ -0,0 +1,339 @@
+//! Camera navigation for the preview window
+//!
+//! Two navigation modes are supported:
+//! - **2D**: pan and zoom over the scene plane (the default, matching the
+//!   framing used by exported frames)
+//! - **3D**: turntable orbit around a target point, with scroll zoom and pan
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::preview::controls::{CameraController, NavigationMode};
+//!
+//! let mut controls = CameraController::new(16.0 / 9.0);
+//! controls.toggle_mode();
+//! assert_eq!(controls.mode, NavigationMode::ThreeD);
+//!
+//! controls.orbit(120.0, -40.0);
+//! controls.zoom(2.0);
+//! let view_proj = controls.view_projection();
+//! # let _ = view_proj;
+//! ```
+
+use crate::core::{Camera, Matrix4, Vector2, Vector3};
+
+/// Radians of orbit per pixel of mouse drag
+const ORBIT_SENSITIVITY: f32 = 0.01;
+
+/// Zoom factor applied per scroll line
+const ZOOM_STEP: f32 = 1.1;
+
+/// Pitch limit, kept just short of the poles so `look_at` stays well defined
+const MAX_PITCH: f32 = 89.0 * std::f32::consts::PI / 180.0;
+
+const MIN_DISTANCE: f32 = 0.1;
+const MAX_DISTANCE: f32 = 100.0;
+const MIN_ZOOM: f32 = 0.05;
+const MAX_ZOOM: f32 = 50.0;
+
+/// Vertical field of view used for 3D navigation, in degrees
+const FOV_DEGREES: f32 = 60.0;
+
+/// How mouse input is interpreted by the preview camera
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavigationMode {
+    /// Pan and zoom over the XY plane
+    TwoD,
+    /// Orbit around a target point
+    ThreeD,
+}
+
+/// Mouse-driven camera controller for the preview window
+#[derive(Debug, Clone)]
+pub struct CameraController {
+    /// Camera updated from the orbit state in 3D mode
+    pub camera: Camera,
+    /// Active navigation mode
+    pub mode: NavigationMode,
+    /// Point the 3D camera orbits around
+    pub target: Vector3,
+    /// Rotation around the world Y axis, in radians
+    pub yaw: f32,
+    /// Elevation above the XY plane, in radians
+    pub pitch: f32,
+    /// Distance from the camera to the target
+    pub distance: f32,
+    /// 2D pan offset in scene units
+    pub offset: Vector2,
+    /// 2D zoom factor (1.0 = unscaled)
+    pub zoom: f32,
+    /// Projection applied after 2D pan and zoom, matching an export's
+    /// [`RenderConfig::framing`](crate::pipeline::RenderConfig::framing)
+    pub framing: Matrix4,
+}
+
+impl CameraController {
+    /// Create a controller in 2D mode for the given viewport aspect ratio
+    pub fn new(aspect_ratio: f32) -> Self {
+        let mut controller = Self {
+            camera: Camera::new()
+                .with_fov(FOV_DEGREES)
+                .with_aspect_ratio(aspect_ratio),
+            mode: NavigationMode::TwoD,
+            target: Vector3::zero(),
+            yaw: 0.0,
+            pitch: 0.0,
+            distance: Self::default_distance(),
+            offset: Vector2::zero(),
+            zoom: 1.0,
+            framing: Matrix4::identity(),
+        };
+        controller.update_camera();
+        controller
+    }
+
+    /// Distance at which the unit square fills the view vertically,
+    /// so switching modes keeps roughly the same framing
+    fn default_distance() -> f32 {
+        1.0 / (FOV_DEGREES.to_radians() * 0.5).tan()
+    }
+
+    /// Switch between 2D and 3D navigation
+    pub fn toggle_mode(&mut self) {
+        self.mode = match self.mode {
+            NavigationMode::TwoD => NavigationMode::ThreeD,
+            NavigationMode::ThreeD => NavigationMode::TwoD,
+        };
+    }
+
+    /// Restore the default view, keeping the current mode
+    pub fn reset(&mut self) {
+        self.target = Vector3::zero();
+        self.yaw = 0.0;
+        self.pitch = 0.0;
+        self.distance = Self::default_distance();
+        self.offset = Vector2::zero();
+        self.zoom = 1.0;
+        self.update_camera();
+    }
+
+    /// Update the viewport aspect ratio (e.g. after a resize)
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f32) {
+        self.camera.aspect_ratio = aspect_ratio;
+    }
+
+    /// Orbit around the target by a mouse drag delta in pixels
+    pub fn orbit(&mut self, dx: f32, dy: f32) {
+        self.yaw -= dx * ORBIT_SENSITIVITY;
+        self.pitch = (self.pitch + dy * ORBIT_SENSITIVITY).clamp(-MAX_PITCH, MAX_PITCH);
+        self.update_camera();
+    }
+
+    /// Pan the view by a mouse drag delta in pixels
+    pub fn pan(&mut self, dx: f32, dy: f32, viewport_width: f32, viewport_height: f32) {
+        if viewport_width <= 0.0 || viewport_height <= 0.0 {
+            return;
+        }
+
+        match self.mode {
+            NavigationMode::TwoD => {
+                // Clip space spans 2 units across the viewport
+                let (scale_x, scale_y) = (self.framing.data[0][0], self.framing.data[1][1]);
+                self.offset.x -= 2.0 * dx / viewport_width / self.zoom / scale_x;
+                self.offset.y += 2.0 * dy / viewport_height / self.zoom / scale_y;
+            }
+            NavigationMode::ThreeD => {
+                // Scale so the point under the cursor follows it on the target plane
+                let units_per_pixel =
+                    2.0 * self.distance * (self.camera.fov * 0.5).tan() / viewport_height;
+                let forward = (self.target - self.eye_position()).normalized();
+                let right = forward.cross(&Vector3::up()).normalized();
+                let up = right.cross(&forward);
+                self.target =
+                    self.target - right * (dx * units_per_pixel) + up * (dy * units_per_pixel);
+                self.update_camera();
+            }
+        }
+    }
+
+    /// Zoom by a number of scroll lines (positive zooms in)
+    pub fn zoom(&mut self, lines: f32) {
+        let factor = ZOOM_STEP.powf(lines);
+        match self.mode {
+            NavigationMode::TwoD => {
+                self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+            }
+            NavigationMode::ThreeD => {
+                self.distance = (self.distance / factor).clamp(MIN_DISTANCE, MAX_DISTANCE);
+                self.update_camera();
+            }
+        }
+    }
+
+    /// Camera position implied by the current orbit state
+    pub fn eye_position(&self) -> Vector3 {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        self.target
+            + Vector3::new(cos_pitch * sin_yaw, sin_pitch, cos_pitch * cos_yaw) * self.distance
+    }
+
+    /// Move the camera to the orbit position and aim it at the target
+    pub fn update_camera(&mut self) {
+        self.camera.transform.position = self.eye_position();
+        self.camera.transform.look_at(self.target, Vector3::up());
+    }
+
+    /// Combined view-projection matrix for the active mode (row-major)
+    pub fn view_projection(&self) -> Matrix4 {
+        match self.mode {
+            NavigationMode::TwoD => {
+                let scale = Matrix4::from_scale(Vector3::new(self.zoom, self.zoom, 1.0));
+                let translation =
+                    Matrix4::from_translation(Vector3::new(-self.offset.x, -self.offset.y, 0.0));
+                self.framing * scale * translation
+            }
+            NavigationMode::ThreeD => {
+                self.camera.projection_matrix()
+                    * self.camera.look_at_matrix(self.target, Vector3::up())
+            }
+        }
+    }
+
+    /// Point on the scene's z = 0 plane under the pixel (`x`, `y`) of a
+    /// `viewport_width` x `viewport_height` view, for picking nodes
+    pub fn scene_point(
+        &self,
+        x: f32,
+        y: f32,
+        viewport_width: f32,
+        viewport_height: f32,
+    ) -> Option<Vector3> {
+        if viewport_width <= 0.0 || viewport_height <= 0.0 {
+            return None;
+        }
+        let ndc_x = x / viewport_width * 2.0 - 1.0;
+        let ndc_y = 1.0 - y / viewport_height * 2.0;
+        let inverse = glam::Mat4::from(self.view_projection()).inverse();
+        let near = inverse.project_point3(glam::Vec3::new(ndc_x, ndc_y, 0.0));
+        let far = inverse.project_point3(glam::Vec3::new(ndc_x, ndc_y, 1.0));
+
+        // Follow the pixel's ray to where it crosses z = 0
+        let depth = far.z - near.z;
+        if depth.abs() <= f32::EPSILON {
+            return Some(Vector3::new(near.x, near.y, 0.0));
+        }
+        let t = -near.z / depth;
+        let point = near + (far - near) * t;
+        point
+            .is_finite()
+            .then(|| Vector3::new(point.x, point.y, 0.0))
+    }
+}
+
+impl Default for CameraController {
+    fn default() -> Self {
+        Self::new(16.0 / 9.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Project a point through a row-major matrix to NDC x/y
+    fn project(matrix: &Matrix4, point: Vector3) -> (f32, f32) {
+        let clip = matrix.transform_point(point);
+        let row = matrix.data[3];
+        let w = row[0] * point.x + row[1] * point.y + row[2] * point.z + row[3];
+        (clip.x / w, clip.y / w)
+    }
+
+    #[test]
+    fn test_default_2d_view_is_identity() {
+        let controls = CameraController::new(1.0);
+        assert_eq!(controls.mode, NavigationMode::TwoD);
+        assert_eq!(controls.view_projection(), Matrix4::identity());
+    }
+
+    #[test]
+    fn test_toggle_mode() {
+        let mut controls = CameraController::default();
+        controls.toggle_mode();
+        assert_eq!(controls.mode, NavigationMode::ThreeD);
+        controls.toggle_mode();
+        assert_eq!(controls.mode, NavigationMode::TwoD);
+    }
+
+    #[test]
+    fn test_orbit_keeps_target_centered_and_clamps_pitch() {
+        let mut controls = CameraController::new(1.0);
+        controls.toggle_mode();
+        controls.orbit(80.0, 10_000.0);
+        assert!(controls.pitch <= MAX_PITCH);
+
+        let (x, y) = project(&controls.view_projection(), controls.target);
+        assert!(x.abs() < 1e-4 && y.abs() < 1e-4);
+        assert!(
+            (controls
+                .camera
+                .transform
+                .position
+                .distance(&controls.target)
+                - controls.distance)
+                .abs()
+                < 1e-4
+        );
+    }
+
+    #[test]
+    fn test_default_3d_framing_matches_2d() {
+        let mut controls = CameraController::new(1.0);
+        controls.toggle_mode();
+        let (_, y) = project(&controls.view_projection(), Vector3::new(0.0, 1.0, 0.0));
+        assert!((y - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_zoom_clamps() {
+        let mut controls = CameraController::new(1.0);
+        controls.zoom(1000.0);
+        assert_eq!(controls.zoom, MAX_ZOOM);
+        controls.toggle_mode();
+        controls.zoom(1000.0);
+        assert_eq!(controls.distance, MIN_DISTANCE);
+    }
+
+    #[test]
+    fn test_scene_point_inverts_view() {
+        let mut controls = CameraController::new(1.0);
+        let center = controls.scene_point(50.0, 50.0, 100.0, 100.0).unwrap();
+        assert!(center.length() < 1e-5);
+        let corner = controls.scene_point(100.0, 0.0, 100.0, 100.0).unwrap();
+        assert!((corner - Vector3::new(1.0, 1.0, 0.0)).length() < 1e-5);
+
+        // The point under a pixel projects back onto that pixel
+        controls.zoom(3.0);
+        controls.pan(10.0, -20.0, 100.0, 100.0);
+        for _ in 0..2 {
+            let point = controls.scene_point(70.0, 40.0, 100.0, 100.0).unwrap();
+            let (x, y) = project(&controls.view_projection(), point);
+            assert!((x - 0.4).abs() < 1e-4 && (y - 0.2).abs() < 1e-4);
+            controls.toggle_mode();
+            controls.orbit(30.0, 15.0);
+        }
+    }
+
+    #[test]
+    fn test_framed_pan_follows_cursor() {
+        let mut controls = CameraController::new(2.0);
+        controls.framing = crate::pipeline::aspect_framing(200, 100);
+        let corner = controls.scene_point(200.0, 0.0, 200.0, 100.0).unwrap();
+        assert!((corner - Vector3::new(2.0, 1.0, 0.0)).length() < 1e-5);
+
+        let grabbed = controls.scene_point(120.0, 50.0, 200.0, 100.0).unwrap();
+        controls.pan(30.0, 10.0, 200.0, 100.0);
+        let after = controls.scene_point(150.0, 60.0, 200.0, 100.0).unwrap();
+        assert!((after - grabbed).length() < 1e-5);
+    }
+}
@@ -0,0 +1,95 @@
+//! Framing guides for the preview window
+//!
+//! Outlines drawn over the preview to check a scene's framing before
+//! export: the action- and title-safe areas of the frame (see
+//! [`SafeArea`]) and, for framed previews, the central square every
+//! [`FramePreset`](crate::pipeline::FramePreset) shows (see
+//! [`shared_area`]). They are pinned to the window, not the scene, so they
+//! stay put while panning and zooming.
+
+use crate::core::{Color, Matrix4, Vector3};
+use crate::pipeline::framing::{shared_area, FrameRect, SafeArea};
+use crate::render::{ShapeRenderer, TransformUniform};
+
+/// Outline width, in pixels
+const LINE_WIDTH: f32 = 1.5;
+
+/// Safe-area and shared-square outlines
+#[derive(Debug, Clone)]
+pub struct FrameGuides {
+    /// Whether the guides are drawn
+    pub visible: bool,
+    /// Whether to outline the square shown in every preset
+    pub shared: bool,
+}
+
+impl FrameGuides {
+    /// Create hidden guides
+    pub fn new(shared: bool) -> Self {
+        Self {
+            visible: false,
+            shared,
+        }
+    }
+
+    /// Show or hide the guides
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Rectangles to outline, with their colors
+    pub fn rects(&self, width: u32, height: u32) -> Vec<(FrameRect, Color)> {
+        let mut rects = vec![
+            (SafeArea::Action.rect(), Color::rgba(0.2, 0.8, 1.0, 0.8)),
+            (SafeArea::Title.rect(), Color::rgba(1.0, 0.8, 0.2, 0.8)),
+        ];
+        if self.shared {
+            rects.push((shared_area(width, height), Color::rgba(1.0, 0.3, 0.6, 0.8)));
+        }
+        rects
+    }
+
+    /// Draw the guides into a pass over a `width` x `height` target
+    pub fn draw(
+        &self,
+        renderer: &mut ShapeRenderer,
+        width: u32,
+        height: u32,
+        render_pass: &mut wgpu::RenderPass,
+    ) {
+        // Line width in clip space along each axis
+        let line_x = 2.0 * LINE_WIDTH / width.max(1) as f32;
+        let line_y = 2.0 * LINE_WIDTH / height.max(1) as f32;
+        let at = |x: f32, y: f32| {
+            TransformUniform::from_matrix(&Matrix4::from_translation(Vector3::new(x, y, 0.0)))
+        };
+
+        render_pass.set_pipeline(&renderer.current_pipeline());
+        for (((min_x, min_y), (max_x, max_y)), color) in self.rects(width, height) {
+            let center_x = f32::midpoint(min_x, max_x);
+            let center_y = f32::midpoint(min_y, max_y);
+            let edges = renderer.update_transforms(&[at(center_x, min_y), at(center_x, max_y)]);
+            renderer.draw_rectangle(max_x - min_x + line_x, line_y, color, edges, render_pass);
+            let edges = renderer.update_transforms(&[at(min_x, center_y), at(max_x, center_y)]);
+            renderer.draw_rectangle(line_x, max_y - min_y + line_y, color, edges, render_pass);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guide_rects() {
+        let guides = FrameGuides::new(false);
+        assert!(!guides.visible);
+        assert_eq!(guides.rects(1920, 1080).len(), 2);
+
+        // Landscape frames narrow the shared square
+        let rects = FrameGuides::new(true).rects(1920, 1080);
+        let ((min_x, min_y), (max_x, max_y)) = rects[2].0;
+        assert!((max_x - 0.5625).abs() < 1e-6 && min_x == -max_x);
+        assert_eq!((min_y, max_y), (-1.0, 1.0));
+    }
+}
@@ -0,0 +1,215 @@
+//! Performance HUD for the preview window
+//!
+//! A stats panel drawn over the top-left corner of the preview with the
+//! rolling frame rate, CPU and GPU frame times, draw calls and animated node
+//! count. The GPU time comes from [`crate::render::GpuTimer`] and reads
+//! "n/a" on adapters without timestamp queries.
+
+use crate::core::{Color, Matrix4, Vector3};
+use crate::render::{ShapeRenderer, TransformUniform};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Frames the FPS and frame time are averaged over
+const ROLLING_FRAMES: usize = 60;
+
+/// Height of one text line on screen, in pixels
+const LINE_HEIGHT: f32 = 18.0;
+
+/// Gap between the panel edge and the window edge or text, in pixels
+const MARGIN: f32 = 8.0;
+
+/// Panel width, in pixels
+const PANEL_WIDTH: f32 = 230.0;
+
+/// Rolling frame statistics and the overlay that shows them
+#[derive(Debug, Clone)]
+pub struct PerfHud {
+    /// Whether the overlay is drawn
+    pub visible: bool,
+    /// Pixel size the text atlas was rasterized at
+    atlas_size: f32,
+    /// Time between consecutive frames, newest last
+    intervals: VecDeque<Duration>,
+    /// CPU time spent updating and recording each frame, newest last
+    cpu_times: VecDeque<Duration>,
+    gpu_time: Option<Duration>,
+    draw_calls: u32,
+    animated_nodes: usize,
+    nodes: usize,
+}
+
+impl PerfHud {
+    /// Create a hidden HUD for text rasterized at `atlas_size` pixels
+    pub fn new(atlas_size: f32) -> Self {
+        Self {
+            visible: false,
+            atlas_size,
+            intervals: VecDeque::with_capacity(ROLLING_FRAMES),
+            cpu_times: VecDeque::with_capacity(ROLLING_FRAMES),
+            gpu_time: None,
+            draw_calls: 0,
+            animated_nodes: 0,
+            nodes: 0,
+        }
+    }
+
+    /// Show or hide the overlay
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Record a frame that started `interval` after the previous one and
+    /// took `cpu_time` to update and record
+    pub fn record_frame(&mut self, interval: Duration, cpu_time: Duration) {
+        for (samples, sample) in [
+            (&mut self.intervals, interval),
+            (&mut self.cpu_times, cpu_time),
+        ] {
+            if samples.len() == ROLLING_FRAMES {
+                samples.pop_front();
+            }
+            samples.push_back(sample);
+        }
+    }
+
+    /// Record the latest GPU pass time
+    pub fn record_gpu_time(&mut self, gpu_time: Duration) {
+        self.gpu_time = Some(gpu_time);
+    }
+
+    /// Record the scene's size and the draw calls of the last frame
+    pub fn record_counts(&mut self, draw_calls: u32, animated_nodes: usize, nodes: usize) {
+        self.draw_calls = draw_calls;
+        self.animated_nodes = animated_nodes;
+        self.nodes = nodes;
+    }
+
+    /// Frames per second over the rolling window
+    pub fn fps(&self) -> f32 {
+        let total: Duration = self.intervals.iter().sum();
+        if total.is_zero() {
+            0.0
+        } else {
+            self.intervals.len() as f32 / total.as_secs_f32()
+        }
+    }
+
+    /// Average time between frames over the rolling window
+    pub fn frame_time(&self) -> Duration {
+        average(&self.intervals)
+    }
+
+    /// Average CPU time per frame over the rolling window
+    pub fn cpu_time(&self) -> Duration {
+        average(&self.cpu_times)
+    }
+
+    /// The overlay's text, one entry per line
+    pub fn lines(&self) -> Vec<String> {
+        let gpu = self.gpu_time.map_or_else(
+            || "n/a".to_string(),
+            |time| format!("{:.2} ms", millis(time)),
+        );
+        vec![
+            format!(
+                "FPS {:.1} ({:.2} ms)",
+                self.fps(),
+                millis(self.frame_time())
+            ),
+            format!("CPU {:.2} ms", millis(self.cpu_time())),
+            format!("GPU {gpu}"),
+            format!("Draw calls {}", self.draw_calls),
+            format!("Animated {} / {} nodes", self.animated_nodes, self.nodes),
+        ]
+    }
+
+    /// Draw the overlay into a pass over a `width` x `height` target
+    ///
+    /// Needs text rendering (see [`ShapeRenderer::init_text_rendering`]).
+    pub fn draw(
+        &self,
+        renderer: &mut ShapeRenderer,
+        width: u32,
+        height: u32,
+        render_pass: &mut wgpu::RenderPass,
+    ) {
+        let lines = self.lines();
+        let (width, height) = (width.max(1) as f32, height.max(1) as f32);
+        // Pixels to clip space; y flips because glyph quads grow downwards
+        let to_clip =
+            |x: f32, y: f32| Vector3::new(2.0 * x / width - 1.0, 1.0 - 2.0 * y / height, 0.0);
+
+        let panel_height = lines.len() as f32 * LINE_HEIGHT + 2.0 * MARGIN;
+        let panel_center = to_clip(MARGIN + PANEL_WIDTH / 2.0, MARGIN + panel_height / 2.0);
+        let panel = renderer.update_transform(&TransformUniform::from_matrix(
+            &Matrix4::from_translation(panel_center),
+        ));
+        render_pass.set_pipeline(&renderer.current_pipeline());
+        renderer.draw_rectangle(
+            2.0 * PANEL_WIDTH / width,
+            2.0 * panel_height / height,
+            Color::rgba(0.0, 0.0, 0.0, 0.6),
+            panel,
+            render_pass,
+        );
+
+        // Atlas pixels to screen pixels, so each line is LINE_HEIGHT tall
+        let text_scale = LINE_HEIGHT * 0.8 / self.atlas_size;
+        let glyph_scale = Matrix4::from_scale(Vector3::new(
+            2.0 * text_scale / width,
+            -2.0 * text_scale / height,
+            1.0,
+        ));
+        for (row, line) in lines.iter().enumerate() {
+            let baseline = to_clip(2.0 * MARGIN, MARGIN + (row as f32 + 0.8) * LINE_HEIGHT);
+            let transforms = renderer.update_transform(&TransformUniform::from_matrix(
+                &(Matrix4::from_translation(baseline) * glyph_scale),
+            ));
+            // A font size of 1000 draws glyphs at their atlas pixel size
+            renderer.draw_text(line, 1000.0, Color::WHITE, transforms, render_pass);
+        }
+    }
+}
+
+fn average(samples: &VecDeque<Duration>) -> Duration {
+    if samples.is_empty() {
+        return Duration::ZERO;
+    }
+    samples.iter().sum::<Duration>() / samples.len() as u32
+}
+
+fn millis(time: Duration) -> f64 {
+    time.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolling_stats() {
+        let mut hud = PerfHud::new(48.0);
+        assert!(hud.fps().abs() < f32::EPSILON);
+
+        for _ in 0..ROLLING_FRAMES {
+            hud.record_frame(Duration::from_millis(100), Duration::from_millis(4));
+        }
+        for _ in 0..ROLLING_FRAMES {
+            hud.record_frame(Duration::from_millis(20), Duration::from_millis(2));
+        }
+        // Only the most recent frames count
+        assert!((hud.fps() - 50.0).abs() < 0.01);
+        assert_eq!(hud.frame_time(), Duration::from_millis(20));
+        assert_eq!(hud.cpu_time(), Duration::from_millis(2));
+
+        hud.record_counts(12, 3, 40);
+        let lines = hud.lines();
+        assert_eq!(lines[2], "GPU n/a");
+        assert_eq!(lines[3], "Draw calls 12");
+        assert_eq!(lines[4], "Animated 3 / 40 nodes");
+
+        hud.record_gpu_time(Duration::from_micros(1500));
+        assert_eq!(hud.lines()[2], "GPU 1.50 ms");
+    }
+}
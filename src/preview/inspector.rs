@@ -0,0 +1,209 @@
+//! Scene inspector
+//!
+//! A tree view of the scene's node hierarchy for debugging deep scenes
+//! without printing them: each row shows a node's name and shape, with a
+//! checkbox toggling its visibility. Clicking a shape in the preview
+//! selects its row (expanding its ancestors), and selecting a row shows the
+//! node's sliders in the tweak panel. With the `tweak` feature the desktop
+//! preview draws it with egui, toggled with F4.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::core::*;
+//! use diomanim::preview::inspector::Inspector;
+//! use diomanim::scene::SceneGraph;
+//!
+//! let mut scene = SceneGraph::new();
+//! let group = scene.create_node("group".to_string());
+//! let dot = scene.add_circle("dot", 0.1, Color::RED).parent_to(group).build();
+//!
+//! let mut inspector = Inspector::default();
+//! inspector.toggle_collapsed(group);
+//! assert_eq!(inspector.rows(&scene).len(), 1);
+//!
+//! // Selecting a node reveals it
+//! inspector.select(&scene, Some(dot));
+//! let rows = inspector.rows(&scene);
+//! assert_eq!((rows[1].name.as_str(), rows[1].depth, rows[1].kind), ("dot", 1, "Circle"));
+//! ```
+
+use crate::scene::{NodeId, Renderable, SceneGraph};
+use std::collections::HashSet;
+
+/// One line of the inspector's tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct InspectorRow {
+    pub id: NodeId,
+    /// Number of ancestors
+    pub depth: usize,
+    pub name: String,
+    /// Renderable variant, or "Group" for nodes without one
+    pub kind: &'static str,
+    pub visible: bool,
+    pub has_children: bool,
+}
+
+/// Tree view state: which nodes are collapsed and which is selected
+#[derive(Debug, Clone, Default)]
+pub struct Inspector {
+    /// Whether the inspector is shown
+    pub visible: bool,
+    pub selected: Option<NodeId>,
+    collapsed: HashSet<NodeId>,
+}
+
+impl Inspector {
+    /// Rows of the expanded part of the tree, in draw order
+    pub fn rows(&self, scene: &SceneGraph) -> Vec<InspectorRow> {
+        let mut rows: Vec<InspectorRow> = Vec::new();
+        // Depth of the collapsed node whose subtree is being skipped
+        let mut skip_below: Option<usize> = None;
+        let mut depths = std::collections::HashMap::new();
+        for node in scene.nodes_in_draw_order() {
+            let depth = node
+                .parent
+                .and_then(|parent| depths.get(&parent))
+                .map_or(0, |depth| depth + 1);
+            depths.insert(node.id, depth);
+            if skip_below.is_some_and(|collapsed| depth > collapsed) {
+                continue;
+            }
+            skip_below = self.collapsed.contains(&node.id).then_some(depth);
+            rows.push(InspectorRow {
+                id: node.id,
+                depth,
+                name: node.name.clone(),
+                kind: kind(node.renderable.as_ref()),
+                visible: node.visible,
+                has_children: !node.children.is_empty(),
+            });
+        }
+        rows
+    }
+
+    pub fn is_collapsed(&self, id: NodeId) -> bool {
+        self.collapsed.contains(&id)
+    }
+
+    pub fn toggle_collapsed(&mut self, id: NodeId) {
+        if !self.collapsed.remove(&id) {
+            self.collapsed.insert(id);
+        }
+    }
+
+    /// Select `id` (e.g. the node picked under the cursor), expanding its
+    /// ancestors so its row shows
+    pub fn select(&mut self, scene: &SceneGraph, id: Option<NodeId>) {
+        self.selected = id;
+        let mut parent = id
+            .and_then(|id| scene.get_node(id))
+            .and_then(|node| node.parent);
+        while let Some(node) = parent.and_then(|id| scene.get_node(id)) {
+            self.collapsed.remove(&node.id);
+            parent = node.parent;
+        }
+    }
+
+    /// Draw the tree into an egui window, applying visibility toggles and
+    /// selection
+    #[cfg(all(feature = "tweak", not(target_arch = "wasm32")))]
+    pub fn show(&mut self, context: &egui::Context, scene: &mut SceneGraph) {
+        if !self.visible {
+            return;
+        }
+        let rows = self.rows(scene);
+        egui::Window::new("Scene")
+            .default_width(240.0)
+            .default_pos([10.0, 10.0])
+            .show(context, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for row in rows {
+                        ui.horizontal(|ui| {
+                            ui.add_space(row.depth as f32 * 12.0);
+                            if row.has_children {
+                                let arrow = if self.is_collapsed(row.id) {
+                                    "▸"
+                                } else {
+                                    "▾"
+                                };
+                                if ui.small_button(arrow).clicked() {
+                                    self.toggle_collapsed(row.id);
+                                }
+                            }
+                            let mut visible = row.visible;
+                            if ui.checkbox(&mut visible, "").changed() {
+                                if let Some(node) = scene.get_node_mut(row.id) {
+                                    node.visible = visible;
+                                }
+                            }
+                            let label = format!("{} ({})", row.name, row.kind);
+                            let selected = self.selected == Some(row.id);
+                            if ui.selectable_label(selected, label).clicked() {
+                                self.selected = (!selected).then_some(row.id);
+                            }
+                        });
+                    }
+                });
+            });
+    }
+}
+
+/// Name of the renderable's variant, shown next to the node's name
+fn kind(renderable: Option<&Renderable>) -> &'static str {
+    match renderable {
+        None => "Group",
+        Some(Renderable::Circle { .. }) => "Circle",
+        Some(Renderable::Rectangle { .. }) => "Rectangle",
+        Some(Renderable::Line { .. }) => "Line",
+        Some(Renderable::Arrow { .. }) => "Arrow",
+        Some(Renderable::Polygon { .. }) => "Polygon",
+        Some(Renderable::Polyline { .. }) => "Polyline",
+        Some(Renderable::Text { .. }) => "Text",
+        Some(Renderable::Math { .. }) => "Math",
+        Some(Renderable::RichText { .. }) => "RichText",
+        Some(Renderable::TextOnPath { .. }) => "TextOnPath",
+        Some(Renderable::Image { .. }) => "Image",
+        Some(Renderable::Svg { .. }) => "Svg",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Color;
+
+    #[test]
+    fn test_inspector_rows_follow_hierarchy() {
+        let mut scene = SceneGraph::new();
+        let root = scene.create_node("root".to_string());
+        let branch = scene
+            .add_square("branch", 0.1, None)
+            .parent_to(root)
+            .build();
+        let leaf = scene
+            .add_circle("leaf", 0.1, Color::RED)
+            .parent_to(branch)
+            .visible(false)
+            .build();
+        let other = scene.add_text("other", "x", 12.0, None).build();
+
+        let mut inspector = Inspector::default();
+        let rows = inspector.rows(&scene);
+        let order: Vec<(NodeId, usize)> = rows.iter().map(|row| (row.id, row.depth)).collect();
+        assert_eq!(order, [(root, 0), (branch, 1), (leaf, 2), (other, 0)]);
+        assert_eq!(rows[0].kind, "Group");
+        assert!(rows[0].has_children && !rows[2].has_children);
+        assert!(!rows[2].visible);
+
+        // Collapsing hides descendants only
+        inspector.toggle_collapsed(root);
+        let ids: Vec<NodeId> = inspector.rows(&scene).iter().map(|row| row.id).collect();
+        assert_eq!(ids, [root, other]);
+
+        inspector.select(&scene, Some(leaf));
+        assert!(!inspector.is_collapsed(root));
+        assert_eq!(inspector.rows(&scene).len(), 4);
+        assert_eq!(inspector.selected, Some(leaf));
+    }
+}
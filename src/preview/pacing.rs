@@ -0,0 +1,128 @@
+//! Frame pacing for the preview window
+//!
+//! Without pacing the preview redraws as soon as the event loop is idle,
+//! which keeps the GPU busy even when the display can't show the frames.
+//! [`FramePacing`] picks how frames are presented and how often they are
+//! drawn:
+//!
+//! ```rust,no_run
+//! use diomanim::preview::{FramePacing, PreviewApp};
+//! use diomanim::scene::SceneGraph;
+//!
+//! // Present as fast as the GPU allows, but draw at most 30 frames a second
+//! let pacing = FramePacing::new(wgpu::PresentMode::Mailbox).with_target_fps(30.0);
+//! let app = PreviewApp::new(SceneGraph::new(), 5.0, 800, 600, pacing);
+//! ```
+
+use std::time::{Duration, Instant};
+
+/// How the preview presents and schedules frames
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FramePacing {
+    /// Surface present mode (`Fifo` waits for vsync, `Mailbox` and
+    /// `Immediate` don't); unsupported modes fall back to `Fifo`
+    pub present_mode: wgpu::PresentMode,
+    /// Upper bound on redraws per second (`None` redraws whenever idle)
+    pub target_fps: Option<f32>,
+    /// Sleep the thread until the next frame instead of waiting for events,
+    /// which hits the target more precisely but delays input handling
+    pub sleep: bool,
+}
+
+/// What the event loop should do before the next frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameWait {
+    /// The next frame is due, redraw now
+    Redraw,
+    /// Handle events until the deadline, then redraw
+    Until(Instant),
+    /// Block the thread this long, then redraw
+    Sleep(Duration),
+}
+
+impl FramePacing {
+    /// Present with `present_mode`, without a frame rate limit
+    pub fn new(present_mode: wgpu::PresentMode) -> Self {
+        Self {
+            present_mode,
+            target_fps: None,
+            sleep: false,
+        }
+    }
+
+    /// Wait for vsync and redraw at the display's refresh rate
+    pub fn vsync() -> Self {
+        Self::new(wgpu::PresentMode::Fifo)
+    }
+
+    /// Present without vsync and redraw as fast as possible (for profiling)
+    pub fn uncapped() -> Self {
+        Self::new(wgpu::PresentMode::Immediate)
+    }
+
+    /// Limit redraws to `fps` frames per second
+    pub fn with_target_fps(mut self, fps: f32) -> Self {
+        self.target_fps = (fps > 0.0).then_some(fps);
+        self
+    }
+
+    /// Sleep between frames rather than waiting on the event loop
+    pub fn with_sleep(mut self, sleep: bool) -> Self {
+        self.sleep = sleep;
+        self
+    }
+
+    /// Minimum time between two frames
+    pub fn frame_interval(&self) -> Option<Duration> {
+        self.target_fps
+            .map(|fps| Duration::from_secs_f32(1.0 / fps))
+    }
+
+    /// Decide how to wait for the frame after one drawn at `last_frame`
+    pub fn wait(&self, last_frame: Instant, now: Instant) -> FrameWait {
+        let Some(deadline) = self.frame_interval().map(|interval| last_frame + interval) else {
+            return FrameWait::Redraw;
+        };
+        if now >= deadline {
+            FrameWait::Redraw
+        } else if self.sleep {
+            FrameWait::Sleep(deadline - now)
+        } else {
+            FrameWait::Until(deadline)
+        }
+    }
+}
+
+impl Default for FramePacing {
+    /// Vsync, capped at 60 frames per second
+    fn default() -> Self {
+        Self::vsync().with_target_fps(60.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_wait() {
+        let start = Instant::now();
+        let early = start + Duration::from_millis(10);
+        let late = start + Duration::from_millis(40);
+
+        let capped = FramePacing::vsync().with_target_fps(30.0);
+        let deadline = start + capped.frame_interval().unwrap();
+        assert_eq!(capped.wait(start, early), FrameWait::Until(deadline));
+        assert_eq!(capped.wait(start, late), FrameWait::Redraw);
+        assert_eq!(
+            capped.with_sleep(true).wait(start, early),
+            FrameWait::Sleep(deadline - early)
+        );
+
+        assert_eq!(
+            FramePacing::uncapped().wait(start, early),
+            FrameWait::Redraw
+        );
+        assert_eq!(FramePacing::vsync().with_target_fps(0.0).target_fps, None);
+    }
+}
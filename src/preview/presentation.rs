@@ -0,0 +1,147 @@
+//! Presentation mode
+//!
+//! Plays a scene one cue at a time for live lectures, like manim-slides:
+//! the timeline is split at its section boundaries (see
+//! [`Timeline::cues`]), playback holds at the end of each cue, and a key
+//! press moves on to the next one.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::core::*;
+//! use diomanim::preview::{PlaybackState, Presentation};
+//!
+//! let mut timeline = Timeline::new();
+//! timeline.add_section("intro", 0.0, 2.0);
+//! timeline.add_section("proof", 2.0, 5.0);
+//!
+//! let mut playback = PlaybackState::new(5.0);
+//! let mut presentation = Presentation::new(&timeline, 5.0);
+//! presentation.start(&mut playback);
+//!
+//! playback.update(3.0);
+//! presentation.hold(&mut playback);
+//! assert_eq!(playback.current_time, 2.0);
+//! assert!(!playback.playing);
+//!
+//! presentation.advance(&mut playback);
+//! assert_eq!(presentation.current().name, "proof");
+//! ```
+
+use super::PlaybackState;
+use crate::core::{Section, Timeline};
+
+/// Cues of a presented scene and which one is playing
+#[derive(Debug, Clone)]
+pub struct Presentation {
+    cues: Vec<Section>,
+    current: usize,
+}
+
+impl Presentation {
+    /// Present the first `duration` seconds of a scene, paused at each of
+    /// the timeline's cues
+    pub fn new(timeline: &Timeline, duration: f32) -> Self {
+        Self {
+            cues: timeline.cues(duration),
+            current: 0,
+        }
+    }
+
+    pub fn cues(&self) -> &[Section] {
+        &self.cues
+    }
+
+    /// Index of the cue playing or holding
+    pub fn index(&self) -> usize {
+        self.current
+    }
+
+    /// The cue playing or holding
+    pub fn current(&self) -> &Section {
+        &self.cues[self.current]
+    }
+
+    /// Whether the last cue is playing or holding
+    pub fn is_last(&self) -> bool {
+        self.current + 1 >= self.cues.len()
+    }
+
+    fn cue_end(&self) -> f32 {
+        self.current().end.map_or(0.0, |end| end.value)
+    }
+
+    /// Play the first cue from the beginning
+    pub fn start(&mut self, playback: &mut PlaybackState) {
+        self.current = 0;
+        playback.looping = false;
+        playback.current_time = 0.0;
+        playback.playing = true;
+    }
+
+    /// Stop playback at the end of the current cue, once it is reached
+    pub fn hold(&self, playback: &mut PlaybackState) {
+        let end = self.cue_end();
+        if playback.current_time >= end {
+            playback.current_time = end;
+            playback.playing = false;
+        }
+    }
+
+    /// Move on: finish the current cue if it is still playing, or start
+    /// the next one if it has finished
+    ///
+    /// Returns the seconds skipped to finish the current cue, which the
+    /// scene has to be advanced by.
+    pub fn advance(&mut self, playback: &mut PlaybackState) -> f32 {
+        let end = self.cue_end();
+        if playback.current_time < end {
+            let skipped = end - playback.current_time;
+            playback.current_time = end;
+            playback.playing = false;
+            return skipped;
+        }
+        if !self.is_last() {
+            self.current += 1;
+            playback.playing = true;
+        }
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presentation_holds_at_cues() {
+        let mut timeline = Timeline::new();
+        timeline.add_section("a", 1.0, 2.0);
+        let mut playback = PlaybackState::new(4.0);
+        let mut presentation = Presentation::new(&timeline, 4.0);
+        assert_eq!(presentation.cues().len(), 3);
+        presentation.start(&mut playback);
+
+        // Skipping finishes the cue before moving on
+        playback.update(0.25);
+        assert_eq!(presentation.advance(&mut playback), 0.75);
+        assert_eq!(presentation.index(), 0);
+        assert_eq!(presentation.advance(&mut playback), 0.0);
+        assert_eq!(presentation.index(), 1);
+        assert!(playback.playing);
+
+        playback.update(5.0);
+        presentation.hold(&mut playback);
+        assert_eq!(playback.current_time, 2.0);
+        presentation.advance(&mut playback);
+        playback.update(5.0);
+        presentation.hold(&mut playback);
+        assert_eq!(playback.current_time, 4.0);
+
+        // The last cue stays put
+        assert!(presentation.is_last());
+        presentation.advance(&mut playback);
+        assert_eq!(presentation.index(), 2);
+        assert!(!playback.playing);
+    }
+}
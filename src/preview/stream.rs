@@ -0,0 +1,255 @@
+//! Network preview: plays a scene headlessly and streams its frames as MJPEG
+//! over HTTP (requires the `stream` feature)
+//!
+//! For watching a preview on a machine without a display, such as a render
+//! box reached over SSH. Open the stream with `mpv http://host:8090/` or an
+//! `<img src="http://host:8090/">` in a browser:
+//!
+//! ```rust,no_run
+//! use diomanim::preview::run_stream_preview;
+//! use diomanim::scene::SceneGraph;
+//!
+//! run_stream_preview("0.0.0.0:8090", SceneGraph::new, 5.0, 1280, 720)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Frames are drawn like offline renders, so the stream shows what
+//! [`render_frames`](crate::pipeline::render_frames) would write.
+
+use super::TEXT_ATLAS_SIZE;
+use crate::pipeline::{scene_frame_graph, FrameClock, OfflineClock, RenderConfig, FRAME_TEXTURE};
+use crate::render::ShapeRenderer;
+use crate::scene::SceneGraph;
+use std::error::Error;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Frame rate of [`run_stream_preview`]
+const STREAM_FPS: u32 = 30;
+
+/// Separates frames in the multipart response
+const BOUNDARY: &str = "frame";
+
+/// How long a frame may take to reach a viewer before the viewer is dropped,
+/// so one slow connection can't stall playback
+const WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// An MJPEG HTTP endpoint that sends each frame to every connected viewer
+pub struct FrameStream {
+    addr: SocketAddr,
+    viewers: Arc<Mutex<Vec<TcpStream>>>,
+    /// JPEG quality, 1 to 100
+    quality: u8,
+}
+
+impl FrameStream {
+    /// Listen on `addr`, accepting viewers in the background
+    pub fn bind(addr: impl ToSocketAddrs) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let viewers = Arc::new(Mutex::new(Vec::new()));
+
+        let accepted = Arc::clone(&viewers);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let viewers = Arc::clone(&accepted);
+                // The request is read off the accept thread so a client that
+                // never sends one can't block others
+                std::thread::spawn(move || {
+                    if let Ok(stream) = start_response(stream) {
+                        viewers.lock().unwrap().push(stream);
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            addr,
+            viewers,
+            quality: 80,
+        })
+    }
+
+    pub fn with_quality(mut self, quality: u8) -> Self {
+        self.quality = quality.clamp(1, 100);
+        self
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Number of connected viewers
+    pub fn viewers(&self) -> usize {
+        self.viewers.lock().unwrap().len()
+    }
+
+    /// Send a frame of tightly packed RGBA rows to every viewer, dropping
+    /// viewers that disconnected
+    pub fn send_frame(&self, width: u32, height: u32, rgba: &[u8]) -> Result<(), Box<dyn Error>> {
+        let mut viewers = self.viewers.lock().unwrap();
+        if viewers.is_empty() {
+            return Ok(());
+        }
+        let jpeg = encode_jpeg(width, height, rgba, self.quality)?;
+        let header = format!(
+            "--{BOUNDARY}\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\n\r\n",
+            jpeg.len()
+        );
+        viewers.retain_mut(|viewer| {
+            viewer
+                .write_all(header.as_bytes())
+                .and_then(|()| viewer.write_all(&jpeg))
+                .and_then(|()| viewer.write_all(b"\r\n"))
+                .is_ok()
+        });
+        Ok(())
+    }
+}
+
+/// Read a viewer's HTTP request and answer with the multipart header
+fn start_response(stream: TcpStream) -> std::io::Result<TcpStream> {
+    stream.set_read_timeout(Some(WRITE_TIMEOUT))?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    // Any path is the stream; the headers are skipped up to the blank line
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+    }
+
+    let mut stream = reader.into_inner();
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\n\
+         Content-Type: multipart/x-mixed-replace; boundary={BOUNDARY}\r\n\
+         Cache-Control: no-cache\r\n\
+         Connection: close\r\n\r\n"
+    )?;
+    Ok(stream)
+}
+
+/// Encode tightly packed RGBA rows as a JPEG (alpha is dropped)
+pub fn encode_jpeg(
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+    quality: u8,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let (Ok(w), Ok(h)) = (u16::try_from(width), u16::try_from(height)) else {
+        return Err(format!("{width}x{height} is too large for a JPEG").into());
+    };
+    let mut jpeg = Vec::new();
+    jpeg_encoder::Encoder::new(&mut jpeg, quality).encode(
+        rgba,
+        w,
+        h,
+        jpeg_encoder::ColorType::Rgba,
+    )?;
+    Ok(jpeg)
+}
+
+/// Play the scene from `build_scene` on a loop, streaming it to viewers of
+/// `addr` in real time
+///
+/// Each loop starts from a freshly built scene. Frames are only rendered
+/// while someone is watching. Runs until rendering fails.
+pub fn run_stream_preview(
+    addr: impl ToSocketAddrs,
+    mut build_scene: impl FnMut() -> SceneGraph,
+    duration: f32,
+    width: u32,
+    height: u32,
+) -> Result<(), Box<dyn Error>> {
+    let stream = FrameStream::bind(addr)?;
+    println!("Streaming preview at http://{}/", stream.local_addr());
+
+    let mut renderer = pollster::block_on(ShapeRenderer::new(width, height))?;
+    // Scenes without text still stream when no font is installed
+    if let Err(e) = renderer.init_text_rendering(TEXT_ATLAS_SIZE) {
+        println!("Stream preview: text rendering unavailable: {e}");
+    }
+    renderer.init_lighting();
+
+    loop {
+        let mut scene = build_scene();
+        let config =
+            RenderConfig::new(width, height, STREAM_FPS, duration).with_theme(scene.theme());
+        let mut graph = scene_frame_graph(&scene, &config);
+        scene.update_transforms();
+
+        let mut clock = OfflineClock::new(STREAM_FPS, config.frame_count().max(1));
+        let start = Instant::now();
+        while let Some(tick) = clock.tick() {
+            if tick.delta.value > 0.0 {
+                scene.update_animations(tick.delta);
+                scene.update_transforms();
+            }
+            if stream.viewers() > 0 {
+                graph.execute(&mut renderer, &scene, None)?;
+                let pixels = graph
+                    .take_readback(FRAME_TEXTURE)
+                    .ok_or("frame graph produced no readback")?;
+                stream.send_frame(width, height, &pixels)?;
+            }
+
+            let next_frame = start + Duration::from_secs_f32(tick.time.value + config.frame_time());
+            if let Some(wait) = next_frame.checked_duration_since(Instant::now()) {
+                std::thread::sleep(wait);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_encode_jpeg() {
+        let jpeg = encode_jpeg(8, 4, &[200; 8 * 4 * 4], 90).unwrap();
+        assert_eq!(&jpeg[..2], &[0xFF, 0xD8]);
+        assert_eq!(&jpeg[jpeg.len() - 2..], &[0xFF, 0xD9]);
+        assert!(encode_jpeg(70_000, 1, &[], 90).is_err());
+    }
+
+    #[test]
+    fn test_stream_sends_frames_to_viewers() {
+        let stream = FrameStream::bind("127.0.0.1:0").unwrap();
+        // Without viewers, frames aren't even encoded
+        stream.send_frame(70_000, 1, &[]).unwrap();
+
+        let mut viewer = TcpStream::connect(stream.local_addr()).unwrap();
+        viewer
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while stream.viewers() == 0 {
+            assert!(Instant::now() < deadline, "viewer was never accepted");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        stream.send_frame(4, 4, &[255; 4 * 4 * 4]).unwrap();
+        viewer
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut response = Vec::new();
+        let mut chunk = [0; 4096];
+        while !response.ends_with(b"\xFF\xD9\r\n") {
+            let read = viewer.read(&mut chunk).unwrap();
+            assert!(read > 0, "stream closed before the frame ended");
+            response.extend_from_slice(&chunk[..read]);
+        }
+        let text = String::from_utf8_lossy(&response);
+        assert!(text.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(text.contains("multipart/x-mixed-replace; boundary=frame"));
+        assert!(text.contains("--frame\r\nContent-Type: image/jpeg\r\n"));
+        assert!(response.windows(2).any(|w| w == [0xFF, 0xD8]));
+    }
+}
@@ -0,0 +1,347 @@
+//! Live parameter tweaking
+//!
+//! With the `tweak` feature, the desktop preview window can show an egui
+//! panel with sliders for selected nodes: position, scale, color, and the
+//! start and duration of each of their animations. Values change the running scene
+//! immediately, so authors can tune a layout or timing by eye, then press
+//! "Copy" to get the matching builder calls (see [`tweak_snippet`]) on the
+//! clipboard and paste them back into code.
+//!
+//! Positions and scales set by a playing animation track are overwritten
+//! by it every frame; tweak those through the animation instead.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::core::*;
+//! use diomanim::preview::tweak::tweak_snippet;
+//! use diomanim::scene::SceneGraph;
+//!
+//! let mut scene = SceneGraph::new();
+//! let dot = scene
+//!     .add_circle("dot", 0.1, Color::RED)
+//!     .at(0.25, -0.5, 0.0)
+//!     .fade_in(0.5, 1.0)
+//!     .build();
+//!
+//! let code = tweak_snippet(scene.get_node(dot).unwrap());
+//! assert!(code.contains(".at(0.250, -0.500, 0.000)"));
+//! assert!(code.contains(".fade_in(0.50, 1.00)"));
+//! ```
+
+use crate::animation::property::AnimationInstance;
+use crate::scene::SceneNode;
+use std::fmt::Write;
+#[cfg(all(feature = "tweak", not(target_arch = "wasm32")))]
+use {
+    super::inspector::Inspector,
+    crate::{
+        core::{Color, Vector3},
+        scene::{NodeId, SceneGraph},
+    },
+    std::sync::Arc,
+    winit::window::Window,
+};
+
+/// Builder methods taking `(start_time, duration)`, by the name of the
+/// clip they add
+const TIMED_BUILDERS: [(&str, &str); 8] = [
+    ("FadeIn", "fade_in"),
+    ("FadeOut", "fade_out"),
+    ("Create", "create"),
+    ("Uncreate", "uncreate"),
+    ("GrowFromCenter", "grow"),
+    ("ShrinkToCenter", "shrink"),
+    ("IrisIn", "iris_in"),
+    ("IrisOut", "iris_out"),
+];
+
+/// Seconds an animation lasts at its current speed
+pub fn animation_duration(animation: &AnimationInstance) -> f32 {
+    let speed = (animation.rate * animation.clip.speed).abs();
+    animation.clip.duration().value / speed.max(f32::EPSILON)
+}
+
+/// Make `animation` last `seconds`, by changing its clip's speed
+pub fn set_animation_duration(animation: &mut AnimationInstance, seconds: f32) {
+    let length = animation.clip.duration().value;
+    if length > 0.0 && seconds > 0.0 {
+        animation.clip.speed = length / (seconds * animation.rate.abs().max(f32::EPSILON));
+    }
+}
+
+/// Builder calls reproducing the node's position, scale, color and
+/// animation timing, for pasting back into scene code
+///
+/// Animations without a matching builder method are listed as comments.
+pub fn tweak_snippet(node: &SceneNode) -> String {
+    let transform = &node._local_transform;
+    let (position, scale) = (transform.position, transform.scale);
+    let mut code = format!("// {}\n", node.name);
+    let _ = writeln!(
+        code,
+        ".at({:.3}, {:.3}, {:.3})",
+        position.x, position.y, position.z
+    );
+    if scale.x == scale.y && scale.y == scale.z {
+        let _ = writeln!(code, ".scale({:.3})", scale.x);
+    } else {
+        let _ = writeln!(
+            code,
+            ".scale_xyz({:.3}, {:.3}, {:.3})",
+            scale.x, scale.y, scale.z
+        );
+    }
+    if let Some(renderable) = &node.renderable {
+        let mut renderable = renderable.clone();
+        let color = *renderable.color_mut();
+        let _ = writeln!(
+            code,
+            ".recolor(Color::rgba({:.3}, {:.3}, {:.3}, {:.3}))",
+            color.r, color.g, color.b, color.a
+        );
+    }
+    for animation in &node.animations {
+        let start = animation.start_time.value;
+        let duration = animation_duration(animation);
+        match TIMED_BUILDERS
+            .iter()
+            .find(|(clip, _)| *clip == animation.clip.name)
+        {
+            Some((_, method)) => {
+                let _ = writeln!(code, ".{method}({start:.2}, {duration:.2})");
+            }
+            None => {
+                let _ = writeln!(
+                    code,
+                    "// {}: starts at {start:.2}s, lasts {duration:.2}s",
+                    animation.clip.name
+                );
+            }
+        }
+    }
+    code
+}
+
+/// Egui overlay with sliders for the properties of selected nodes, and
+/// the scene [`Inspector`]
+#[cfg(all(feature = "tweak", not(target_arch = "wasm32")))]
+pub struct TweakPanel {
+    nodes: Vec<NodeId>,
+    context: egui::Context,
+    state: egui_winit::State,
+    renderer: egui_wgpu::Renderer,
+    /// Whether the sliders are drawn and take input
+    pub visible: bool,
+    /// Node tree; its selected node gets sliders too
+    pub inspector: Inspector,
+}
+
+#[cfg(all(feature = "tweak", not(target_arch = "wasm32")))]
+impl TweakPanel {
+    /// A panel for `nodes` (shown once one is given or selected), drawn into `window`'s surface of `format`
+    pub fn new(
+        window: &Arc<Window>,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        nodes: Vec<NodeId>,
+    ) -> Self {
+        let context = egui::Context::default();
+        let state = egui_winit::State::new(
+            context.clone(),
+            egui::ViewportId::ROOT,
+            window.as_ref(),
+            Some(window.scale_factor() as f32),
+            None,
+            Some(device.limits().max_texture_dimension_2d as usize),
+        );
+        let renderer =
+            egui_wgpu::Renderer::new(device, format, egui_wgpu::RendererOptions::default());
+        Self {
+            visible: !nodes.is_empty(),
+            nodes,
+            context,
+            state,
+            renderer,
+            inspector: Inspector::default(),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Pass a window event to the panel; returns true if the panel used it
+    /// and the preview should ignore it
+    pub fn on_window_event(&mut self, window: &Window, event: &winit::event::WindowEvent) -> bool {
+        (self.visible || self.inspector.visible)
+            && self.state.on_window_event(window, event).consumed
+    }
+
+    /// Lay out the panel, apply slider changes to `scene`, and draw it over
+    /// `view` (a `width` x `height` texture)
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw(
+        &mut self,
+        window: &Window,
+        scene: &mut SceneGraph,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        size: (u32, u32),
+    ) {
+        if !self.visible && !self.inspector.visible {
+            return;
+        }
+        let input = self.state.take_egui_input(window);
+        let (nodes, inspector, visible) = (&self.nodes, &mut self.inspector, self.visible);
+        let output = self.context.run(input, |context| {
+            inspector.show(context, scene);
+            let selected = inspector.selected.filter(|id| !nodes.contains(id));
+            if !visible && selected.is_none() {
+                return;
+            }
+            egui::Window::new("Tweak")
+                .default_width(260.0)
+                .show(context, |ui| {
+                    for &id in nodes.iter().chain(&selected) {
+                        node_controls(ui, scene, id);
+                    }
+                });
+        });
+        self.state
+            .handle_platform_output(window, output.platform_output);
+        scene.update_transforms();
+
+        let primitives = self
+            .context
+            .tessellate(output.shapes, output.pixels_per_point);
+        for (id, delta) in &output.textures_delta.set {
+            self.renderer.update_texture(device, queue, *id, delta);
+        }
+        let screen = egui_wgpu::ScreenDescriptor {
+            size_in_pixels: [size.0, size.1],
+            pixels_per_point: output.pixels_per_point,
+        };
+        let commands = self
+            .renderer
+            .update_buffers(device, queue, encoder, &primitives, &screen);
+        queue.submit(commands);
+
+        let mut pass = encoder
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Tweak Panel Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    depth_slice: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            })
+            .forget_lifetime();
+        self.renderer.render(&mut pass, &primitives, &screen);
+        drop(pass);
+        for id in &output.textures_delta.free {
+            self.renderer.free_texture(id);
+        }
+    }
+}
+
+/// Sliders for one node's transform, color and animations
+#[cfg(all(feature = "tweak", not(target_arch = "wasm32")))]
+fn node_controls(ui: &mut egui::Ui, scene: &mut SceneGraph, id: NodeId) {
+    let Some(node) = scene.get_node_mut(id) else {
+        return;
+    };
+    egui::CollapsingHeader::new(node.name.clone())
+        .id_salt(id.0)
+        .default_open(true)
+        .show(ui, |ui| {
+            let position = &mut node._local_transform.position;
+            ui.add(egui::Slider::new(&mut position.x, -2.0..=2.0).text("x"));
+            ui.add(egui::Slider::new(&mut position.y, -2.0..=2.0).text("y"));
+
+            let mut scale = node._local_transform.scale.x;
+            if ui
+                .add(egui::Slider::new(&mut scale, 0.0..=4.0).text("scale"))
+                .changed()
+            {
+                node._local_transform.scale = Vector3::new(scale, scale, scale);
+            }
+
+            if let Some(renderable) = &mut node.renderable {
+                let color = renderable.color_mut();
+                let mut rgba = [color.r, color.g, color.b, color.a];
+                ui.horizontal(|ui| {
+                    ui.label("color");
+                    if ui.color_edit_button_rgba_unmultiplied(&mut rgba).changed() {
+                        *color = Color::rgba(rgba[0], rgba[1], rgba[2], rgba[3]);
+                    }
+                });
+            }
+
+            for animation in &mut node.animations {
+                ui.label(animation.clip.name.clone());
+                ui.add(
+                    egui::Slider::new(&mut animation.start_time.value, 0.0..=20.0)
+                        .text("start")
+                        .suffix(" s"),
+                );
+                let mut duration = animation_duration(animation);
+                if ui
+                    .add(
+                        egui::Slider::new(&mut duration, 0.05..=10.0)
+                            .text("duration")
+                            .suffix(" s"),
+                    )
+                    .changed()
+                {
+                    set_animation_duration(animation, duration);
+                }
+            }
+
+            if ui.button("Copy").clicked() {
+                let code = tweak_snippet(node);
+                println!("{code}");
+                ui.ctx().copy_text(code);
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Color, TimeValue};
+    use crate::scene::SceneGraph;
+
+    #[test]
+    fn test_tweak_snippet() {
+        let mut scene = SceneGraph::new();
+        let id = scene
+            .add_rectangle("bar", 0.2, 0.6, Color::BLUE)
+            .at(0.1, 0.2, 0.0)
+            .scale_xyz(1.0, 2.0, 1.0)
+            .grow(1.0, 0.5)
+            .move_to(0.0, crate::core::Vector3::zero(), 2.0)
+            .build();
+        let node = scene.get_node_mut(id).unwrap();
+        set_animation_duration(&mut node.animations[0], 2.0);
+        assert!((animation_duration(&node.animations[0]) - 2.0).abs() < 1e-5);
+        node.animations[0].start_time = TimeValue::new(1.5);
+
+        let code = tweak_snippet(node);
+        assert!(code.starts_with("// bar\n"));
+        assert!(code.contains(".at(0.100, 0.200, 0.000)"));
+        assert!(code.contains(".scale_xyz(1.000, 2.000, 1.000)"));
+        assert!(code.contains(".recolor(Color::rgba("));
+        assert!(code.contains(".grow(1.50, 2.00)"));
+        assert!(code.contains("// MoveTo: starts at 0.00s, lasts 2.00s"));
+    }
+}
@@ -0,0 +1,232 @@
+//! Browser preview: plays a scene on an HTML canvas through WebGPU
+//!
+//! ```rust,ignore
+//! use diomanim::preview::web::{run_canvas_preview, CanvasPreview};
+//!
+//! wasm_bindgen_futures::spawn_local(async move {
+//!     let preview = CanvasPreview::new(canvas, scene, 5.0).await.unwrap();
+//!     // Keep the handle to drive playback from the page
+//!     let preview = run_canvas_preview(preview);
+//!     preview.borrow_mut().playback_mut().looping = false;
+//! });
+//! ```
+//!
+//! There is no filesystem in the browser, so text rendering needs a font
+//! fetched by the page (see [`ShapeRenderer::init_text_rendering_with_font_data`]).
+
+use super::controls::CameraController;
+use super::PlaybackState;
+use crate::core::{Color, Matrix4, TimeValue, Vector3};
+use crate::pipeline::{draw_render_targets, draw_scene};
+use crate::render::graph::to_wgpu_color;
+use crate::render::{RendererDescriptor, ShapeRenderer};
+use crate::scene::SceneGraph;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use web_sys::HtmlCanvasElement;
+
+/// `requestAnimationFrame` callback, given the frame's timestamp
+type FrameCallback = Closure<dyn FnMut(f64)>;
+
+/// A renderer drawing to a canvas
+pub struct CanvasSurface {
+    canvas: HtmlCanvasElement,
+    renderer: ShapeRenderer,
+    surface: wgpu::Surface<'static>,
+    config: wgpu::SurfaceConfiguration,
+}
+
+impl CanvasSurface {
+    /// Create a renderer for `canvas`, sized to the canvas's drawing buffer
+    pub async fn new(canvas: HtmlCanvasElement) -> Result<Self, Box<dyn std::error::Error>> {
+        let descriptor = RendererDescriptor::new(canvas.width().max(1), canvas.height().max(1));
+        let instance = descriptor.create_instance();
+        let surface = instance.create_surface(wgpu::SurfaceTarget::Canvas(canvas.clone()))?;
+        let (renderer, config) =
+            ShapeRenderer::for_surface(&descriptor, instance, &surface).await?;
+        surface.configure(renderer.get_device(), &config);
+        Ok(Self {
+            canvas,
+            renderer,
+            surface,
+            config,
+        })
+    }
+
+    pub fn canvas(&self) -> &HtmlCanvasElement {
+        &self.canvas
+    }
+
+    pub fn renderer_mut(&mut self) -> &mut ShapeRenderer {
+        &mut self.renderer
+    }
+
+    /// Resize the canvas's drawing buffer (not its CSS size)
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.canvas.set_width(width.max(1));
+        self.canvas.set_height(height.max(1));
+        self.fit_canvas();
+    }
+
+    /// Reconfigure the surface if the canvas was resized since the last frame
+    fn fit_canvas(&mut self) {
+        let (width, height) = (self.canvas.width().max(1), self.canvas.height().max(1));
+        if (width, height) != (self.config.width, self.config.height) {
+            self.config.width = width;
+            self.config.height = height;
+            self.surface
+                .configure(self.renderer.get_device(), &self.config);
+        }
+    }
+
+    /// Draw `scene` through `view_proj` over a `background` clear
+    pub fn draw(
+        &mut self,
+        scene: &SceneGraph,
+        view_proj: &Matrix4,
+        eye: Vector3,
+        background: Color,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.fit_canvas();
+        let surface_texture = match self.surface.get_current_texture() {
+            Ok(texture) => texture,
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                self.surface
+                    .configure(self.renderer.get_device(), &self.config);
+                self.surface.get_current_texture()?
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let view = surface_texture
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        self.renderer.reset_transform_offset();
+        let mut encoder =
+            self.renderer
+                .get_device()
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Canvas Render Encoder"),
+                });
+        draw_render_targets(&mut self.renderer, scene, eye, &mut encoder);
+        let mut render_pass =
+            self.renderer
+                .begin_render_pass(&mut encoder, &view, Some(to_wgpu_color(background)));
+        draw_scene(&mut self.renderer, scene, view_proj, eye, &mut render_pass);
+        drop(render_pass);
+
+        self.renderer
+            .get_queue()
+            .submit(std::iter::once(encoder.finish()));
+        surface_texture.present();
+        Ok(())
+    }
+}
+
+/// Scene playback on a canvas, advanced once per animation frame
+pub struct CanvasPreview {
+    surface: CanvasSurface,
+    scene: SceneGraph,
+    playback: PlaybackState,
+    controls: CameraController,
+    /// `requestAnimationFrame` timestamp of the previous frame, in milliseconds
+    last_timestamp: Option<f64>,
+}
+
+impl CanvasPreview {
+    /// Create a preview of `scene` (`duration` seconds long) playing on `canvas`
+    pub async fn new(
+        canvas: HtmlCanvasElement,
+        scene: SceneGraph,
+        duration: f32,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let surface = CanvasSurface::new(canvas).await?;
+        let aspect_ratio = surface.config.width as f32 / surface.config.height as f32;
+        let mut playback = PlaybackState::new(duration);
+        playback.playing = true;
+        Ok(Self {
+            surface,
+            scene,
+            playback,
+            controls: CameraController::new(aspect_ratio),
+            last_timestamp: None,
+        })
+    }
+
+    pub fn surface_mut(&mut self) -> &mut CanvasSurface {
+        &mut self.surface
+    }
+
+    pub fn scene_mut(&mut self) -> &mut SceneGraph {
+        &mut self.scene
+    }
+
+    pub fn playback(&self) -> &PlaybackState {
+        &self.playback
+    }
+
+    /// Play/pause, seek, loop and speed controls
+    pub fn playback_mut(&mut self) -> &mut PlaybackState {
+        &mut self.playback
+    }
+
+    pub fn controls_mut(&mut self) -> &mut CameraController {
+        &mut self.controls
+    }
+
+    /// Advance playback to `timestamp` (milliseconds, as passed to
+    /// `requestAnimationFrame` callbacks) and draw the frame
+    pub fn frame(&mut self, timestamp: f64) -> Result<(), Box<dyn std::error::Error>> {
+        let delta = self
+            .last_timestamp
+            .replace(timestamp)
+            .map_or(0.0, |last| ((timestamp - last) / 1000.0) as f32);
+
+        let previous_time = self.playback.current_time;
+        self.playback.update(delta);
+        self.scene
+            .update_animations(TimeValue::new(self.playback.current_time - previous_time));
+        self.scene.update_transforms();
+
+        let canvas = self.surface.canvas();
+        let aspect_ratio = canvas.width().max(1) as f32 / canvas.height().max(1) as f32;
+        self.controls.set_aspect_ratio(aspect_ratio);
+        let background = self.scene.theme().background;
+        self.surface.draw(
+            &self.scene,
+            &self.controls.view_projection(),
+            self.controls.camera.transform.position,
+            background,
+        )
+    }
+}
+
+/// Drive `preview` from `requestAnimationFrame` for as long as the page is
+/// open, returning a handle for controlling it from the page
+pub fn run_canvas_preview(preview: CanvasPreview) -> Rc<RefCell<CanvasPreview>> {
+    let preview = Rc::new(RefCell::new(preview));
+    let callback: Rc<RefCell<Option<FrameCallback>>> = Rc::new(RefCell::new(None));
+
+    let playing = Rc::clone(&preview);
+    let next_frame = Rc::clone(&callback);
+    *callback.borrow_mut() = Some(Closure::new(move |timestamp: f64| {
+        if let Err(e) = playing.borrow_mut().frame(timestamp) {
+            web_sys::console::error_1(&format!("Preview frame failed: {e}").into());
+        }
+        if let Some(next_frame) = next_frame.borrow().as_ref() {
+            request_animation_frame(next_frame);
+        }
+    }));
+    if let Some(first_frame) = callback.borrow().as_ref() {
+        request_animation_frame(first_frame);
+    }
+    preview
+}
+
+fn request_animation_frame(callback: &FrameCallback) {
+    if let Some(window) = web_sys::window() {
+        // Only fails for a detached window, which won't draw anyway
+        let _ = window.request_animation_frame(callback.as_ref().unchecked_ref());
+    }
+}
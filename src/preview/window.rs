@@ -0,0 +1,888 @@
+//! The desktop preview window (winit), playing a scene with the keyboard
+//! and mouse controls listed in [`super`]
+
+use super::controls::{CameraController, NavigationMode};
+use super::guides::FrameGuides;
+use super::hud::PerfHud;
+use super::pacing::{FramePacing, FrameWait};
+use super::presentation::Presentation;
+#[cfg(feature = "tweak")]
+use super::tweak::TweakPanel;
+use super::{PlaybackState, TEXT_ATLAS_SIZE};
+use crate::assets::AssetServer;
+use crate::audio::CuePlayer;
+use crate::core::*;
+use crate::pipeline::{aspect_framing, draw_render_targets, draw_scene, FramePreset};
+use crate::render::{GpuTimer, RendererDescriptor, ShapeRenderer};
+use crate::scene::*;
+#[cfg(feature = "scripting")]
+use crate::scripting::SceneScript;
+use std::sync::Arc;
+use std::time::Instant;
+use winit::{
+    application::ApplicationHandler,
+    event::*,
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
+    keyboard::{KeyCode, PhysicalKey},
+    window::{Window, WindowId},
+};
+
+/// How often a scene script's file is checked for changes
+#[cfg(feature = "scripting")]
+const SCRIPT_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Pixels the cursor may move between press and release for a click
+const CLICK_SLOP: f64 = 4.0;
+
+/// Preview window application state
+pub struct PreviewApp {
+    window: Option<Arc<Window>>,
+    renderer: Option<ShapeRenderer>,
+    surface: Option<wgpu::Surface<'static>>,
+    surface_config: Option<wgpu::SurfaceConfiguration>,
+    /// Measures GPU frame time when the adapter supports timestamp queries
+    gpu_timer: Option<GpuTimer>,
+    hud: PerfHud,
+    /// Safe-area outlines, toggled with G
+    guides: FrameGuides,
+    /// Whether the view keeps scene units square like a framed export
+    framed: bool,
+    scene: SceneGraph,
+    playback: PlaybackState,
+    timeline: Timeline,
+    /// Assets the scene's images and SVGs come from, drawn as they finish loading
+    assets: Option<AssetServer>,
+    audio: Option<CuePlayer>,
+    /// Cues playback holds at, when presenting
+    presentation: Option<Presentation>,
+    /// Nodes the tweak panel shows sliders for
+    #[cfg(feature = "tweak")]
+    tweak_nodes: Vec<NodeId>,
+    #[cfg(feature = "tweak")]
+    tweak: Option<TweakPanel>,
+    controls: CameraController,
+    cursor_position: Option<(f64, f64)>,
+    drag_button: Option<MouseButton>,
+    /// Where the left button went down, to tell clicks from drags
+    press_position: Option<(f64, f64)>,
+    last_update: Instant,
+    pacing: FramePacing,
+    /// When the last frame started drawing
+    last_frame: Instant,
+    /// Script the scene is rebuilt from when its file changes
+    #[cfg(feature = "scripting")]
+    script: Option<SceneScript>,
+    #[cfg(feature = "scripting")]
+    last_script_poll: Instant,
+    width: u32,
+    height: u32,
+}
+
+impl PreviewApp {
+    /// Create a new preview application
+    ///
+    /// `pacing` selects the present mode and frame rate cap; use
+    /// [`FramePacing::default`] for vsync at up to 60 FPS.
+    pub fn new(
+        scene: SceneGraph,
+        duration: f32,
+        width: u32,
+        height: u32,
+        pacing: FramePacing,
+    ) -> Self {
+        Self {
+            window: None,
+            renderer: None,
+            surface: None,
+            surface_config: None,
+            gpu_timer: None,
+            hud: PerfHud::new(TEXT_ATLAS_SIZE),
+            guides: FrameGuides::new(false),
+            framed: false,
+            scene,
+            playback: PlaybackState::new(duration),
+            timeline: Timeline::new(),
+            assets: None,
+            audio: None,
+            presentation: None,
+            #[cfg(feature = "tweak")]
+            tweak_nodes: Vec::new(),
+            #[cfg(feature = "tweak")]
+            tweak: None,
+            controls: CameraController::new(width as f32 / height.max(1) as f32),
+            cursor_position: None,
+            drag_button: None,
+            press_position: None,
+            last_update: Instant::now(),
+            pacing,
+            last_frame: Instant::now(),
+            #[cfg(feature = "scripting")]
+            script: None,
+            #[cfg(feature = "scripting")]
+            last_script_poll: Instant::now(),
+            width,
+            height,
+        }
+    }
+
+    /// Attach a timeline whose sound cues play along with the preview
+    pub fn with_timeline(mut self, timeline: Timeline) -> Self {
+        self.timeline = timeline;
+        self
+    }
+
+    /// Present the scene one cue at a time (see [`Presentation`]), holding
+    /// at each of the timeline's section boundaries until Space or → is pressed
+    pub fn with_presentation(mut self, timeline: Timeline) -> Self {
+        let mut presentation = Presentation::new(&timeline, self.playback.duration);
+        presentation.start(&mut self.playback);
+        self.presentation = Some(presentation);
+        self.with_timeline(timeline)
+    }
+
+    /// Show a panel of sliders for `nodes`' position, scale, color and
+    /// animation timing (see [`super::tweak`]), toggled with F2
+    #[cfg(feature = "tweak")]
+    pub fn with_tweaks(mut self, nodes: impl IntoIterator<Item = NodeId>) -> Self {
+        self.tweak_nodes = nodes.into_iter().collect();
+        self
+    }
+
+    /// Frame the scene like an export with `preset` (see
+    /// [`crate::pipeline::framing`]): the window takes the preset's shape at
+    /// the requested height, and the guides (G) outline the square every
+    /// preset shows
+    pub fn with_preset(mut self, preset: FramePreset) -> Self {
+        self.width = (self.height as f32 * preset.aspect_ratio()).round() as u32;
+        self.controls.set_aspect_ratio(preset.aspect_ratio());
+        self.framed = true;
+        self.guides.shared = true;
+        self.controls.framing = aspect_framing(self.width, self.height);
+        self
+    }
+
+    /// Draw image and SVG nodes from `assets`
+    ///
+    /// Assets requested with [`AssetServer::load_async`] pop in once they
+    /// have loaded, without stalling the window.
+    pub fn with_assets(mut self, assets: AssetServer) -> Self {
+        self.assets = Some(assets);
+        self
+    }
+
+    /// Rebuild the scene from `script` whenever its file changes
+    #[cfg(feature = "scripting")]
+    pub fn with_script(mut self, script: SceneScript) -> Self {
+        self.script = Some(script);
+        self
+    }
+
+    /// Re-run the script if it was edited, keeping the playhead where it is
+    #[cfg(feature = "scripting")]
+    fn reload_script(&mut self) {
+        if self.last_script_poll.elapsed() < SCRIPT_POLL_INTERVAL {
+            return;
+        }
+        self.last_script_poll = Instant::now();
+        let Some(script) = &mut self.script else {
+            return;
+        };
+
+        let built = match script.reload_if_changed() {
+            Ok(true) => script.run(),
+            Ok(false) => return,
+            Err(e) => Err(e),
+        };
+        match built {
+            Ok(mut built) => {
+                self.playback.duration = built.duration;
+                self.playback.current_time = self.playback.current_time.min(built.duration);
+                built
+                    .scene
+                    .update_animations(TimeValue::new(self.playback.current_time));
+
+                // Patch the running scene so untouched nodes keep playing
+                *self.scene.lights_mut() = built.scene.lights().to_vec();
+                self.scene.set_ambient_light(built.scene.ambient_light());
+                *self.scene.post_effects_mut() = built.scene.post_effects().to_vec();
+                self.scene.set_theme(built.scene.theme().clone());
+                let patch = ScenePatch::diff(&self.scene, built.scene);
+                let edits = patch.len();
+                match self.scene.apply_patch(patch) {
+                    Ok(()) => println!("Reloaded scene script ({edits} edits)"),
+                    Err(e) => eprintln!("Scene script patch failed: {e}"),
+                }
+            }
+            Err(e) => eprintln!("Scene script error: {e}"),
+        }
+    }
+
+    /// Stop sounds that no longer match the playhead (after a seek or reset)
+    fn stop_sounds(&mut self) {
+        if let Some(audio) = &mut self.audio {
+            audio.stop();
+        }
+    }
+
+    /// Render the current frame
+    fn render(&mut self) {
+        let Some(renderer) = &mut self.renderer else {
+            return;
+        };
+        let Some(surface) = &self.surface else { return };
+
+        // Get surface texture
+        let surface_texture = match surface.get_current_texture() {
+            Ok(texture) => texture,
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                // Recreate surface
+                if let Some(config) = &self.surface_config {
+                    surface.configure(renderer.get_device(), config);
+                }
+                return;
+            }
+            Err(wgpu::SurfaceError::OutOfMemory) => {
+                eprintln!("Out of memory!");
+                return;
+            }
+            Err(wgpu::SurfaceError::Timeout) => {
+                return;
+            }
+            Err(wgpu::SurfaceError::Other) => {
+                eprintln!("Unknown surface error!");
+                return;
+            }
+        };
+
+        let view = surface_texture
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        // Reset transform offset counter before starting new frame
+        renderer.reset_transform_offset();
+
+        // Create command encoder
+        let mut encoder =
+            renderer
+                .get_device()
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Preview Render Encoder"),
+                });
+        draw_render_targets(
+            renderer,
+            &self.scene,
+            self.controls.camera.transform.position,
+            &mut encoder,
+        );
+
+        // Begin render pass
+        let mut render_pass = match &self.gpu_timer {
+            Some(timer) => renderer.begin_timed_render_pass(&mut encoder, &view, None, timer),
+            None => renderer.begin_render_pass(&mut encoder, &view, None),
+        };
+
+        // Render all visible objects through the preview camera
+        draw_scene(
+            renderer,
+            &self.scene,
+            &self.controls.view_projection(),
+            self.controls.camera.transform.position,
+            &mut render_pass,
+        );
+
+        // Stats overlay, counting only the scene's draw calls
+        self.hud.record_counts(
+            renderer.draw_call_count(),
+            self.scene.animated_node_count(),
+            self.scene.node_count(),
+        );
+        if self.guides.visible {
+            self.guides
+                .draw(renderer, self.width, self.height, &mut render_pass);
+        }
+        if self.hud.visible {
+            self.hud
+                .draw(renderer, self.width, self.height, &mut render_pass);
+        }
+
+        // End render pass
+        drop(render_pass);
+
+        #[cfg(feature = "tweak")]
+        if let (Some(panel), Some(window)) = (&mut self.tweak, &self.window) {
+            panel.draw(
+                window,
+                &mut self.scene,
+                renderer.get_device(),
+                renderer.get_queue(),
+                &mut encoder,
+                &view,
+                (self.width, self.height),
+            );
+        }
+        if let Some(timer) = &mut self.gpu_timer {
+            timer.resolve(&mut encoder);
+        }
+
+        // Submit commands
+        renderer
+            .get_queue()
+            .submit(std::iter::once(encoder.finish()));
+        if let Some(gpu_time) = self
+            .gpu_timer
+            .as_mut()
+            .and_then(|timer| timer.collect(renderer.get_device()))
+        {
+            self.hud.record_gpu_time(gpu_time);
+        }
+
+        // Present frame; the next redraw is scheduled in `about_to_wait`
+        surface_texture.present();
+    }
+
+    /// Update the scene based on current time
+    fn update_scene(&mut self) {
+        #[cfg(feature = "scripting")]
+        self.reload_script();
+
+        // Calculate delta time
+        let now = Instant::now();
+        let delta_time = now.duration_since(self.last_update).as_secs_f32();
+        self.last_update = now;
+
+        // Update playback state
+        let previous_time = self.playback.current_time;
+        self.playback.update(delta_time);
+        if let Some(presentation) = &self.presentation {
+            presentation.hold(&mut self.playback);
+        }
+
+        // Fire sound cues crossed since the last frame
+        if (self.playback.current_time - previous_time).abs() > 0.0
+            && !self.timeline.sound_cues().is_empty()
+        {
+            self.audio.get_or_insert_with(CuePlayer::new).update(
+                &self.timeline,
+                previous_time,
+                self.playback.current_time,
+                self.playback.duration,
+            );
+        }
+
+        // Update scene to current time, so it stops while paused or holding
+        // at a cue
+        // Note: This is simplified - ideally we'd seek to absolute time
+        let frame_delta = TimeValue::new(self.playback.current_time - previous_time);
+        self.scene.update_animations(frame_delta);
+        self.scene.update_transforms();
+    }
+
+    /// Finish the playing cue, or start the next one
+    fn advance_cue(&mut self) {
+        let Some(presentation) = &mut self.presentation else {
+            return;
+        };
+        let skipped = presentation.advance(&mut self.playback);
+        if skipped > 0.0 {
+            if let Some(audio) = &mut self.audio {
+                audio.stop();
+            }
+            self.scene.update_animations(TimeValue::new(skipped));
+        }
+        println!(
+            "Cue {}/{}: {}",
+            presentation.index() + 1,
+            presentation.cues().len(),
+            presentation.current().name
+        );
+    }
+
+    /// Handle keyboard input
+    fn handle_keyboard(&mut self, key_code: KeyCode, state: ElementState) {
+        if state != ElementState::Pressed {
+            return;
+        }
+
+        if self.presentation.is_some()
+            && matches!(
+                key_code,
+                KeyCode::Space | KeyCode::ArrowRight | KeyCode::PageDown
+            )
+        {
+            self.advance_cue();
+            return;
+        }
+
+        match key_code {
+            KeyCode::Space => {
+                self.playback.toggle_play();
+                if let Some(audio) = &self.audio {
+                    if self.playback.playing {
+                        audio.resume();
+                    } else {
+                        audio.pause();
+                    }
+                }
+                println!(
+                    "Playback: {}",
+                    if self.playback.playing {
+                        "▶ Playing"
+                    } else {
+                        "⏸ Paused"
+                    }
+                );
+            }
+            KeyCode::KeyR => {
+                self.playback.reset();
+                self.stop_sounds();
+                println!("⏮ Reset to beginning");
+            }
+            KeyCode::ArrowRight => {
+                self.playback.step_forward();
+                self.stop_sounds();
+                println!("⏭ Step forward (time: {:.2}s)", self.playback.current_time);
+            }
+            KeyCode::ArrowLeft => {
+                self.playback.step_backward();
+                self.stop_sounds();
+                println!("⏮ Step backward (time: {:.2}s)", self.playback.current_time);
+            }
+            KeyCode::KeyL => {
+                self.playback.looping = !self.playback.looping;
+                println!("Loop: {}", if self.playback.looping { "ON" } else { "OFF" });
+            }
+            KeyCode::BracketRight => {
+                self.playback.speed += 0.25;
+                println!("Speed: {:.2}x", self.playback.speed);
+            }
+            KeyCode::BracketLeft => {
+                self.playback.speed = (self.playback.speed - 0.25).max(0.25);
+                println!("Speed: {:.2}x", self.playback.speed);
+            }
+            KeyCode::Tab => {
+                self.controls.toggle_mode();
+                println!(
+                    "Navigation: {}",
+                    match self.controls.mode {
+                        NavigationMode::TwoD => "2D (drag to pan)",
+                        NavigationMode::ThreeD => "3D (drag to orbit)",
+                    }
+                );
+            }
+            #[cfg(feature = "tweak")]
+            KeyCode::F2 => {
+                if let Some(panel) = &mut self.tweak {
+                    panel.toggle();
+                }
+            }
+            #[cfg(feature = "tweak")]
+            KeyCode::F4 => {
+                if let Some(panel) = &mut self.tweak {
+                    panel.inspector.visible = !panel.inspector.visible;
+                }
+            }
+            KeyCode::F3 => {
+                self.hud.toggle();
+                println!("HUD: {}", if self.hud.visible { "ON" } else { "OFF" });
+            }
+            KeyCode::KeyG => {
+                self.guides.toggle();
+                println!("Guides: {}", if self.guides.visible { "ON" } else { "OFF" });
+            }
+            KeyCode::Home => {
+                self.controls.reset();
+                println!("View reset");
+            }
+            KeyCode::Escape => {
+                // Window will close automatically on next event loop iteration
+            }
+            _ => {}
+        }
+    }
+
+    /// Scene point under the cursor, if it is over the window
+    fn cursor_scene_point(&self) -> Option<Vector3> {
+        let (x, y) = self.cursor_position?;
+        self.controls
+            .scene_point(x as f32, y as f32, self.width as f32, self.height as f32)
+    }
+
+    /// Track button presses for dragging, and run click handlers when the
+    /// left button is released where it was pressed
+    fn handle_mouse_input(&mut self, state: ElementState, button: MouseButton) {
+        self.drag_button = match state {
+            ElementState::Pressed => Some(button),
+            ElementState::Released => None,
+        };
+        if button != MouseButton::Left {
+            return;
+        }
+        match state {
+            ElementState::Pressed => self.press_position = self.cursor_position,
+            ElementState::Released => {
+                let pressed = self.press_position.take();
+                let still = match (pressed, self.cursor_position) {
+                    (Some((x0, y0)), Some((x1, y1))) => (x1 - x0).hypot(y1 - y0) <= CLICK_SLOP,
+                    _ => false,
+                };
+                if let Some(point) = self.cursor_scene_point().filter(|_| still) {
+                    #[cfg(feature = "tweak")]
+                    if let Some(panel) = &mut self.tweak {
+                        let picked = self.scene.pick(point);
+                        panel.inspector.select(&self.scene, picked);
+                    }
+                    self.scene.click(point);
+                }
+            }
+        }
+    }
+
+    /// Handle mouse motion: hover handlers, or navigation while a button is
+    /// held
+    fn handle_cursor_moved(&mut self, x: f64, y: f64) {
+        let previous = self.cursor_position.replace((x, y));
+        let Some(button) = self.drag_button else {
+            let point = self.cursor_scene_point();
+            self.scene.hover(point);
+            return;
+        };
+        let Some((last_x, last_y)) = previous else {
+            return;
+        };
+
+        let dx = (x - last_x) as f32;
+        let dy = (y - last_y) as f32;
+
+        match (button, self.controls.mode) {
+            (MouseButton::Left, NavigationMode::ThreeD) => self.controls.orbit(dx, dy),
+            (MouseButton::Left | MouseButton::Right | MouseButton::Middle, _) => {
+                self.controls
+                    .pan(dx, dy, self.width as f32, self.height as f32);
+            }
+            _ => {}
+        }
+    }
+
+    /// Handle scroll wheel zoom
+    fn handle_mouse_wheel(&mut self, delta: MouseScrollDelta) {
+        let lines = match delta {
+            MouseScrollDelta::LineDelta(_, y) => y,
+            // Roughly one line per 40 pixels of trackpad scroll
+            MouseScrollDelta::PixelDelta(position) => position.y as f32 / 40.0,
+        };
+        self.controls.zoom(lines);
+    }
+}
+
+impl ApplicationHandler for PreviewApp {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_some() {
+            return;
+        }
+
+        // Create window
+        let window_attributes = Window::default_attributes()
+            .with_title("Diomanim Preview - [Space] Play/Pause | [R] Reset | [←/→] Step | [L] Loop | [Tab] 2D/3D | [F3] HUD | [Esc] Quit")
+            .with_inner_size(winit::dpi::PhysicalSize::new(self.width, self.height));
+
+        let window = Arc::new(
+            event_loop
+                .create_window(window_attributes)
+                .expect("Failed to create window"),
+        );
+
+        // Initialize renderer and surface (async operation)
+        let (renderer, surface, surface_config) = pollster::block_on(async {
+            // Create the surface first so the renderer's pipelines can target
+            // whichever format the surface supports
+            let descriptor = RendererDescriptor::new(self.width, self.height)
+                .with_present_mode(self.pacing.present_mode)
+                .with_optional_features(wgpu::Features::TIMESTAMP_QUERY);
+            let instance = descriptor.create_instance();
+            let surface = instance
+                .create_surface(Arc::clone(&window))
+                .expect("Failed to create surface");
+
+            let (mut renderer, surface_config) =
+                ShapeRenderer::for_surface(&descriptor, instance, &surface)
+                    .await
+                    .expect("Failed to create renderer");
+
+            // Initialize text rendering
+            renderer
+                .init_text_rendering(TEXT_ATLAS_SIZE)
+                .expect("Failed to initialize text rendering");
+
+            // Initialize lit pipeline for scenes with lights
+            renderer.init_lighting();
+            if let Some(assets) = &self.assets {
+                renderer.set_asset_server(assets.clone());
+            }
+
+            surface.configure(renderer.get_device(), &surface_config);
+
+            (renderer, surface, surface_config)
+        });
+
+        self.gpu_timer = GpuTimer::new(&renderer);
+        #[cfg(feature = "tweak")]
+        {
+            self.tweak = Some(TweakPanel::new(
+                &window,
+                renderer.get_device(),
+                surface_config.format,
+                std::mem::take(&mut self.tweak_nodes),
+            ));
+        }
+        self.window = Some(window);
+        self.renderer = Some(renderer);
+        self.surface = Some(surface);
+        self.surface_config = Some(surface_config);
+        self.last_update = Instant::now();
+
+        println!("\n╔═══════════════════════════════════════════════════════════════╗");
+        println!("║  Diomanim Live Preview                                       ║");
+        println!("╚═══════════════════════════════════════════════════════════════╝");
+        println!("\n🎬 Controls:");
+        println!("  [Space]    Play / Pause");
+        println!("  [R]        Reset to beginning");
+        println!("  [←/→]      Step backward / forward");
+        println!("  [L]        Toggle loop");
+        println!("  [[/]]      Decrease / increase speed");
+        println!("  [Tab]      Toggle 2D / 3D navigation");
+        println!("  [Home]     Reset view");
+        #[cfg(feature = "tweak")]
+        {
+            println!("  [F2]       Toggle tweak panel");
+            println!("  [F4]       Toggle scene inspector");
+        }
+        println!("  [F3]       Toggle performance HUD");
+        println!("  [Mouse]    Drag to orbit (3D) or pan, right-drag to pan, scroll to zoom");
+        println!("  [Click]    Run the click handler of the shape under the cursor");
+        if self.presentation.is_some() {
+            println!("  Presenting: [Space]/[→]/[PgDn] finish the cue or start the next one");
+        }
+        println!("  [Esc]      Quit\n");
+        let target_fps = self
+            .pacing
+            .target_fps
+            .map_or_else(|| "uncapped".to_string(), |fps| format!("{fps}"));
+        println!(
+            "Duration: {:.1}s | FPS: {} | Present: {:?}",
+            self.playback.duration,
+            target_fps,
+            self.surface_config
+                .as_ref()
+                .map_or(self.pacing.present_mode, |config| config.present_mode)
+        );
+        println!("─────────────────────────────────────────────────────────────────\n");
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let Some(window) = &self.window else { return };
+        if window.id() != window_id {
+            return;
+        }
+        #[cfg(feature = "tweak")]
+        if let Some(panel) = &mut self.tweak {
+            if panel.on_window_event(window, &event) {
+                return;
+            }
+        }
+
+        match event {
+            WindowEvent::CloseRequested => {
+                println!("\n👋 Closing preview window...");
+                event_loop.exit();
+            }
+            WindowEvent::RedrawRequested => {
+                let frame_start = Instant::now();
+                let interval = frame_start.duration_since(self.last_frame);
+                self.last_frame = frame_start;
+                self.update_scene();
+                self.render();
+                self.hud.record_frame(interval, frame_start.elapsed());
+            }
+            WindowEvent::KeyboardInput { event, .. } => {
+                if let PhysicalKey::Code(key_code) = event.physical_key {
+                    self.handle_keyboard(key_code, event.state);
+                }
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                self.handle_mouse_input(state, button);
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.handle_cursor_moved(position.x, position.y);
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.cursor_position = None;
+                self.scene.hover(None);
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.handle_mouse_wheel(delta);
+            }
+            WindowEvent::Resized(new_size) => {
+                if new_size.width > 0 && new_size.height > 0 {
+                    self.width = new_size.width;
+                    self.height = new_size.height;
+                    self.controls
+                        .set_aspect_ratio(new_size.width as f32 / new_size.height as f32);
+                    if self.framed {
+                        self.controls.framing = aspect_framing(self.width, self.height);
+                    }
+
+                    if let (Some(surface), Some(renderer), Some(config)) =
+                        (&self.surface, &self.renderer, &mut self.surface_config)
+                    {
+                        config.width = new_size.width;
+                        config.height = new_size.height;
+                        surface.configure(renderer.get_device(), config);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        let Some(window) = &self.window else { return };
+
+        match self.pacing.wait(self.last_frame, Instant::now()) {
+            FrameWait::Redraw => {
+                event_loop.set_control_flow(ControlFlow::Poll);
+                window.request_redraw();
+            }
+            FrameWait::Sleep(duration) => {
+                std::thread::sleep(duration);
+                event_loop.set_control_flow(ControlFlow::Poll);
+                window.request_redraw();
+            }
+            FrameWait::Until(deadline) => {
+                event_loop.set_control_flow(ControlFlow::WaitUntil(deadline));
+            }
+        }
+    }
+}
+
+/// Run the live preview window with the default [`FramePacing`]
+pub fn run_preview(
+    scene: SceneGraph,
+    duration: f32,
+    width: u32,
+    height: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let event_loop = EventLoop::new()?;
+    event_loop.set_control_flow(ControlFlow::Poll);
+
+    let mut app = PreviewApp::new(scene, duration, width, height, FramePacing::default());
+    event_loop.run_app(&mut app)?;
+
+    Ok(())
+}
+
+/// Run the live preview framed like an export with `preset`, in a window
+/// `height` pixels tall (see [`PreviewApp::with_preset`])
+pub fn run_preview_with_preset(
+    scene: SceneGraph,
+    duration: f32,
+    preset: FramePreset,
+    height: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let event_loop = EventLoop::new()?;
+    event_loop.set_control_flow(ControlFlow::Poll);
+
+    let mut app = PreviewApp::new(scene, duration, height, height, FramePacing::default())
+        .with_preset(preset);
+    event_loop.run_app(&mut app)?;
+
+    Ok(())
+}
+
+/// Run the live preview window, playing the timeline's sound cues in sync
+pub fn run_preview_with_timeline(
+    scene: SceneGraph,
+    timeline: Timeline,
+    duration: f32,
+    width: u32,
+    height: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let event_loop = EventLoop::new()?;
+    event_loop.set_control_flow(ControlFlow::Poll);
+
+    let mut app = PreviewApp::new(scene, duration, width, height, FramePacing::default())
+        .with_timeline(timeline);
+    event_loop.run_app(&mut app)?;
+
+    Ok(())
+}
+
+/// Run the live preview with a tweak panel of sliders for `nodes` (see
+/// [`super::tweak`])
+#[cfg(feature = "tweak")]
+pub fn run_preview_with_tweaks(
+    scene: SceneGraph,
+    duration: f32,
+    width: u32,
+    height: u32,
+    nodes: impl IntoIterator<Item = NodeId>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let event_loop = EventLoop::new()?;
+    event_loop.set_control_flow(ControlFlow::Poll);
+
+    let mut app =
+        PreviewApp::new(scene, duration, width, height, FramePacing::default()).with_tweaks(nodes);
+    event_loop.run_app(&mut app)?;
+
+    Ok(())
+}
+
+/// Present a scene cue by cue, holding at each of the timeline's section
+/// boundaries until a key is pressed (see [`Presentation`])
+pub fn run_presentation(
+    scene: SceneGraph,
+    timeline: Timeline,
+    duration: f32,
+    width: u32,
+    height: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let event_loop = EventLoop::new()?;
+    event_loop.set_control_flow(ControlFlow::Poll);
+
+    let mut app = PreviewApp::new(scene, duration, width, height, FramePacing::default())
+        .with_presentation(timeline);
+    event_loop.run_app(&mut app)?;
+
+    Ok(())
+}
+
+/// Run the live preview of a scene script, rebuilding the scene each time
+/// the script file is saved
+#[cfg(feature = "scripting")]
+pub fn run_script_preview(
+    path: impl AsRef<std::path::Path>,
+    width: u32,
+    height: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let script = SceneScript::load(path)?;
+    let built = script.run()?;
+
+    let event_loop = EventLoop::new()?;
+    event_loop.set_control_flow(ControlFlow::Poll);
+
+    let mut app = PreviewApp::new(
+        built.scene,
+        built.duration,
+        width,
+        height,
+        FramePacing::default(),
+    )
+    .with_script(script);
+    event_loop.run_app(&mut app)?;
+
+    Ok(())
+}
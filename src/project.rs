@@ -0,0 +1,358 @@
+//! # Multi-Scene Projects
+//!
+//! A [`Project`] registers several named scenes (like the `Scene` classes of
+//! a Manim file) that share a theme, an asset directory and render settings.
+//! A binary hands its command-line arguments to [`Project::run_cli`] to list
+//! the scenes or render some of them:
+//!
+//! ```text
+//! diomanim list
+//! diomanim render --scene Intro --scene Proof
+//! diomanim render            # every scene
+//! ```
+//!
+//! ```rust,no_run
+//! use diomanim::core::Color;
+//! use diomanim::project::Project;
+//! use diomanim::scene::Theme;
+//!
+//! let mut project = Project::new("lecture").with_theme(Theme::light());
+//! project.add_scene("Intro", 3.0, |_, scene| {
+//!     scene.add_text("title", "Fourier Series", 48.0, None);
+//! });
+//! project.add_scene("Proof", 10.0, |project, scene| {
+//!     let _diagram = project.asset_path("diagram.png");
+//!     scene.add_circle("dot", 0.2, Color::RED).fade_in(0.0, 1.0);
+//! });
+//! project.run_cli(std::env::args().skip(1))?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::pipeline::RenderConfig;
+use crate::scene::{SceneGraph, Theme};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{
+    export::{export_video_ffmpeg, VideoExportSettings},
+    pipeline::{render_frames_with_progress, ConsoleProgress, RenderStats},
+    render::ShapeRenderer,
+};
+use std::path::PathBuf;
+
+/// Size of the glyph atlas used for scene text
+#[cfg(not(target_arch = "wasm32"))]
+const TEXT_ATLAS_SIZE: f32 = 48.0;
+
+/// Builds a scene's content into a graph already styled with the project theme
+pub type SceneBuilder = Box<dyn Fn(&Project, &mut SceneGraph)>;
+
+/// A scene registered with a [`Project`]
+pub struct ProjectScene {
+    pub name: String,
+    /// Length of the scene in seconds
+    pub duration: f32,
+    build: SceneBuilder,
+}
+
+/// Named scenes sharing a theme, assets and render settings
+pub struct Project {
+    pub name: String,
+    /// Theme every scene starts with
+    pub theme: Theme,
+    /// Directory [`Project::asset_path`] resolves against
+    pub assets_dir: PathBuf,
+    /// Directory receiving `<scene>.mp4` videos and their frames
+    pub output_dir: PathBuf,
+    /// Resolution, frame rate and caching shared by every scene (the
+    /// duration comes from the scene)
+    pub config: RenderConfig,
+    scenes: Vec<ProjectScene>,
+}
+
+impl Project {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            theme: Theme::default(),
+            assets_dir: PathBuf::from("assets"),
+            output_dir: PathBuf::from("output"),
+            config: RenderConfig::new(1920, 1080, 30, 0.0),
+            scenes: Vec::new(),
+        }
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn with_assets_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.assets_dir = dir.into();
+        self
+    }
+
+    pub fn with_output_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.output_dir = dir.into();
+        self
+    }
+
+    /// Render settings for every scene; the duration is ignored
+    pub fn with_config(mut self, config: RenderConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Register a scene of `duration` seconds, replacing any scene of the
+    /// same name
+    ///
+    /// `build` receives the project (for assets) and a fresh scene using the
+    /// project theme; it runs again for every render.
+    pub fn add_scene(
+        &mut self,
+        name: impl Into<String>,
+        duration: f32,
+        build: impl Fn(&Project, &mut SceneGraph) + 'static,
+    ) -> &mut Self {
+        let scene = ProjectScene {
+            name: name.into(),
+            duration: duration.max(0.0),
+            build: Box::new(build),
+        };
+        match self.scenes.iter_mut().find(|s| s.name == scene.name) {
+            Some(existing) => *existing = scene,
+            None => self.scenes.push(scene),
+        }
+        self
+    }
+
+    /// Registered scenes in registration order
+    pub fn scenes(&self) -> &[ProjectScene] {
+        &self.scenes
+    }
+
+    pub fn scene(&self, name: &str) -> Option<&ProjectScene> {
+        self.scenes.iter().find(|scene| scene.name == name)
+    }
+
+    /// Path of a shared asset, e.g. `assets/diagram.png` for `"diagram.png"`
+    pub fn asset_path(&self, relative: impl AsRef<std::path::Path>) -> PathBuf {
+        self.assets_dir.join(relative)
+    }
+
+    /// A fresh scene graph of the scene called `name`
+    pub fn build_scene(&self, name: &str) -> Option<SceneGraph> {
+        let entry = self.scene(name)?;
+        let mut scene = SceneGraph::new();
+        scene.set_theme(self.theme.clone());
+        (entry.build)(self, &mut scene);
+        Some(scene)
+    }
+
+    /// Render settings of the scene called `name`: the project config with
+    /// the scene's duration, the theme background and its own frame directory
+    pub fn scene_config(&self, name: &str) -> Option<RenderConfig> {
+        let entry = self.scene(name)?;
+        let mut config = self
+            .config
+            .clone()
+            .with_theme(&self.theme)
+            .with_frames_dir(self.output_dir.join("frames").join(name));
+        config.duration = entry.duration;
+        Some(config)
+    }
+
+    /// Video file the scene called `name` renders to
+    pub fn video_path(&self, name: &str) -> PathBuf {
+        self.output_dir.join(format!("{name}.mp4"))
+    }
+
+    /// Render the scene called `name` to frames and encode
+    /// [`Project::video_path`]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn render_scene(
+        &self,
+        renderer: &mut ShapeRenderer,
+        name: &str,
+    ) -> Result<RenderStats, Box<dyn std::error::Error>> {
+        let (Some(mut scene), Some(config)) = (self.build_scene(name), self.scene_config(name))
+        else {
+            return Err(self.unknown_scene(name).into());
+        };
+        let stats = render_frames_with_progress(
+            renderer,
+            &mut scene,
+            &config,
+            &mut ConsoleProgress::default(),
+        )?;
+
+        let settings = VideoExportSettings::new(
+            config.width,
+            config.height,
+            config.fps,
+            self.video_path(name).to_string_lossy().into_owned(),
+            config.frame_pattern(),
+        );
+        export_video_ffmpeg(&settings)?;
+        Ok(stats)
+    }
+
+    /// Run a command-line invocation (see [`ProjectCommand::parse`]),
+    /// typically `std::env::args().skip(1)`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn run_cli(
+        &self,
+        args: impl IntoIterator<Item = String>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match ProjectCommand::parse(args)? {
+            ProjectCommand::List => {
+                println!("Scenes in {}:", self.name);
+                for scene in &self.scenes {
+                    println!("  {:<24} {:.1}s", scene.name, scene.duration);
+                }
+                Ok(())
+            }
+            ProjectCommand::Render { scenes } => {
+                let names: Vec<&str> = if scenes.is_empty() {
+                    self.scenes
+                        .iter()
+                        .map(|scene| scene.name.as_str())
+                        .collect()
+                } else {
+                    scenes.iter().map(String::as_str).collect()
+                };
+                // Check every name before spending time on renders
+                if let Some(name) = names.iter().find(|name| self.scene(name).is_none()) {
+                    return Err(self.unknown_scene(name).into());
+                }
+
+                let mut renderer =
+                    pollster::block_on(ShapeRenderer::new(self.config.width, self.config.height))?;
+                // Scenes without text still render when no font is installed
+                if let Err(e) =
+                    renderer.init_text_rendering_with_font(self.theme.font_path(), TEXT_ATLAS_SIZE)
+                {
+                    println!("Project: text rendering unavailable: {e}");
+                }
+                renderer.init_lighting();
+
+                for name in names {
+                    println!("Rendering scene {name}...");
+                    let stats = self.render_scene(&mut renderer, name)?;
+                    println!(
+                        "✓ {name}: {} frames ({} reused) -> {}",
+                        stats.total_frames(),
+                        stats.frames_cached,
+                        self.video_path(name).display()
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn unknown_scene(&self, name: &str) -> String {
+        let names: Vec<&str> = self.scenes.iter().map(|s| s.name.as_str()).collect();
+        format!("no scene named `{name}` (available: {})", names.join(", "))
+    }
+}
+
+/// A command-line invocation of a project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProjectCommand {
+    /// Print the registered scenes
+    List,
+    /// Render the named scenes (all of them if empty)
+    Render { scenes: Vec<String> },
+}
+
+impl ProjectCommand {
+    /// Parse `list` or `render [--scene NAME]...` (also `--scene=NAME`);
+    /// no arguments means `render`
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut args = args.into_iter();
+        match args.next().as_deref() {
+            Some("list") => match args.next() {
+                Some(arg) => Err(format!("unexpected argument `{arg}`")),
+                None => Ok(Self::List),
+            },
+            Some("render") | None => {
+                let mut scenes = Vec::new();
+                while let Some(arg) = args.next() {
+                    if let Some(name) = arg.strip_prefix("--scene=") {
+                        scenes.push(name.to_string());
+                    } else if arg == "--scene" {
+                        scenes.push(args.next().ok_or("--scene needs a scene name")?);
+                    } else {
+                        return Err(format!("unexpected argument `{arg}`"));
+                    }
+                }
+                Ok(Self::Render { scenes })
+            }
+            Some(command) => Err(format!(
+                "unknown command `{command}` (expected `list` or `render`)"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Color;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_project_scenes() {
+        let mut project = Project::new("demo")
+            .with_theme(Theme::light())
+            .with_output_dir("out");
+        project
+            .add_scene("Intro", 2.0, |_, scene| {
+                scene.add_circle("dot", 0.2, Color::RED);
+            })
+            .add_scene("Outro", 1.0, |_, _| {})
+            .add_scene("Intro", 3.0, |project, scene| {
+                scene.add_circle(project.name.clone(), 0.2, None);
+            });
+
+        let names: Vec<&str> = project.scenes().iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Intro", "Outro"]);
+
+        let scene = project.build_scene("Intro").unwrap();
+        assert_eq!(scene.theme(), &Theme::light());
+        assert!(scene
+            .nodes_in_draw_order()
+            .iter()
+            .any(|node| node.name == "demo"));
+        assert!(project.build_scene("Missing").is_none());
+
+        let config = project.scene_config("Intro").unwrap();
+        assert_eq!(config.duration, 3.0);
+        assert_eq!(config.background, Theme::light().background);
+        assert_eq!(config.frames_dir, PathBuf::from("out/frames/Intro"));
+        assert_eq!(project.video_path("Intro"), PathBuf::from("out/Intro.mp4"));
+    }
+
+    #[test]
+    fn test_parse_project_command() {
+        assert_eq!(
+            ProjectCommand::parse(args("")),
+            Ok(ProjectCommand::Render { scenes: vec![] })
+        );
+        assert_eq!(
+            ProjectCommand::parse(args("render --scene Intro --scene=Proof")),
+            Ok(ProjectCommand::Render {
+                scenes: vec!["Intro".to_string(), "Proof".to_string()]
+            })
+        );
+        assert_eq!(
+            ProjectCommand::parse(args("list")),
+            Ok(ProjectCommand::List)
+        );
+        assert!(ProjectCommand::parse(args("render --scene")).is_err());
+        assert!(ProjectCommand::parse(args("play")).is_err());
+    }
+}
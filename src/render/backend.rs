@@ -0,0 +1,117 @@
+//! # Renderer Backends
+//!
+//! Selects the kind of adapter a [`super::ShapeRenderer`] runs on. Machines
+//! without a GPU (CI runners, headless cloud servers) fall back to wgpu's
+//! software adapter (e.g. llvmpipe or WARP) instead of failing.
+//!
+//! The `DIOMANIM_BACKEND` environment variable (`gpu` / `software`) overrides
+//! [`RendererBackend::Auto`] without code changes.
+
+/// Environment variable consulted by [`RendererBackend::Auto`]
+pub const BACKEND_ENV_VAR: &str = "DIOMANIM_BACKEND";
+
+/// Which adapter a renderer should use
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RendererBackend {
+    /// Hardware GPU if available, otherwise the software adapter
+    #[default]
+    Auto,
+    /// Require a hardware GPU
+    Hardware,
+    /// Always use the software (CPU) adapter
+    Software,
+}
+
+impl RendererBackend {
+    /// Parse a backend name (`auto`, `gpu`/`hardware`, `cpu`/`software`)
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "auto" => Some(Self::Auto),
+            "gpu" | "hardware" => Some(Self::Hardware),
+            "cpu" | "software" | "fallback" => Some(Self::Software),
+            _ => None,
+        }
+    }
+
+    /// Apply the `DIOMANIM_BACKEND` override to `Auto`
+    pub fn resolve(self) -> Self {
+        if self != Self::Auto {
+            return self;
+        }
+        std::env::var(BACKEND_ENV_VAR)
+            .ok()
+            .and_then(|name| Self::parse(&name))
+            .unwrap_or(Self::Auto)
+    }
+
+    /// Values of `force_fallback_adapter` to try, in order
+    pub fn adapter_attempts(self) -> &'static [bool] {
+        match self {
+            Self::Auto => &[false, true],
+            Self::Hardware => &[false],
+            Self::Software => &[true],
+        }
+    }
+
+    /// Classify an adapter that was actually obtained
+    pub fn of_adapter(info: &wgpu::AdapterInfo) -> Self {
+        if info.device_type == wgpu::DeviceType::Cpu {
+            Self::Software
+        } else {
+            Self::Hardware
+        }
+    }
+}
+
+/// Request an adapter for `backend`, falling back to software rendering for `Auto`
+pub(crate) async fn request_adapter(
+    instance: &wgpu::Instance,
+    backend: RendererBackend,
+    power_preference: wgpu::PowerPreference,
+    compatible_surface: Option<&wgpu::Surface<'_>>,
+) -> Result<wgpu::Adapter, Box<dyn std::error::Error>> {
+    let mut last_error = None;
+    for &force_fallback_adapter in backend.resolve().adapter_attempts() {
+        match instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference,
+                compatible_surface,
+                force_fallback_adapter,
+            })
+            .await
+        {
+            Ok(adapter) => return Ok(adapter),
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    Err(match last_error {
+        Some(e) => format!("no {backend:?} adapter available: {e}").into(),
+        None => "no adapter requested".into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_parsing_and_attempts() {
+        assert_eq!(
+            RendererBackend::parse(" CPU "),
+            Some(RendererBackend::Software)
+        );
+        assert_eq!(
+            RendererBackend::parse("gpu"),
+            Some(RendererBackend::Hardware)
+        );
+        assert_eq!(RendererBackend::parse("vulkan"), None);
+
+        assert_eq!(RendererBackend::Auto.adapter_attempts(), &[false, true]);
+        assert_eq!(RendererBackend::Software.adapter_attempts(), &[true]);
+        assert_eq!(
+            RendererBackend::Hardware.resolve(),
+            RendererBackend::Hardware
+        );
+    }
+}
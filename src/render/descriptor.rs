@@ -0,0 +1,227 @@
+//! # Renderer Configuration
+//!
+//! [`RendererDescriptor`] collects everything [`super::ShapeRenderer`] used
+//! to hardcode: which adapter and graphics APIs to use, the power preference,
+//! device features and limits, and the color format render targets use.
+//!
+//! ```rust,no_run
+//! use diomanim::render::{RendererBackend, RendererDescriptor, ShapeRenderer};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let descriptor = RendererDescriptor::new(1920, 1080)
+//!     .with_backend(RendererBackend::Software)
+//!     .with_backends(wgpu::Backends::VULKAN);
+//! let renderer = ShapeRenderer::with_descriptor(&descriptor).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::RendererBackend;
+
+/// Settings used to create a [`super::ShapeRenderer`]
+#[derive(Debug, Clone)]
+pub struct RendererDescriptor {
+    pub width: u32,
+    pub height: u32,
+    /// Hardware GPU, software adapter, or automatic fallback
+    pub backend: RendererBackend,
+    /// Graphics APIs wgpu may use (Vulkan, Metal, DX12, GL, ...)
+    pub backends: wgpu::Backends,
+    pub power_preference: wgpu::PowerPreference,
+    pub required_features: wgpu::Features,
+    /// Features enabled only when the adapter supports them
+    pub optional_features: wgpu::Features,
+    /// Device limits to request (`None` picks defaults the adapter supports)
+    pub required_limits: Option<wgpu::Limits>,
+    /// Color format of render targets (surfaces may negotiate a different one)
+    pub format: wgpu::TextureFormat,
+    /// How surfaces present frames (falls back to `Fifo` when unsupported)
+    pub present_mode: wgpu::PresentMode,
+}
+
+/// Every native graphics API, or the browser's WebGPU in wasm builds
+fn default_backends() -> wgpu::Backends {
+    if cfg!(target_arch = "wasm32") {
+        wgpu::Backends::BROWSER_WEBGPU
+    } else {
+        wgpu::Backends::all()
+    }
+}
+
+impl RendererDescriptor {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            backend: RendererBackend::Auto,
+            backends: default_backends(),
+            power_preference: wgpu::PowerPreference::default(),
+            required_features: wgpu::Features::empty(),
+            optional_features: wgpu::Features::empty(),
+            required_limits: None,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            present_mode: wgpu::PresentMode::Fifo,
+        }
+    }
+
+    pub fn with_backend(mut self, backend: RendererBackend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    pub fn with_backends(mut self, backends: wgpu::Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    pub fn with_power_preference(mut self, power_preference: wgpu::PowerPreference) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+
+    pub fn with_features(mut self, features: wgpu::Features) -> Self {
+        self.required_features = features;
+        self
+    }
+
+    pub fn with_optional_features(mut self, features: wgpu::Features) -> Self {
+        self.optional_features = features;
+        self
+    }
+
+    pub fn with_limits(mut self, limits: wgpu::Limits) -> Self {
+        self.required_limits = Some(limits);
+        self
+    }
+
+    pub fn with_format(mut self, format: wgpu::TextureFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_present_mode(mut self, present_mode: wgpu::PresentMode) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+
+    /// Create a wgpu instance for the configured graphics APIs
+    pub fn create_instance(&self) -> wgpu::Instance {
+        wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: self.backends,
+            ..Default::default()
+        })
+    }
+
+    /// Limits to request from `adapter`
+    ///
+    /// Without explicit limits, software adapters that can't reach the
+    /// defaults get the downlevel set, which covers everything the renderer uses.
+    pub(crate) fn limits_for(&self, adapter: &wgpu::Adapter) -> wgpu::Limits {
+        if let Some(limits) = &self.required_limits {
+            return limits.clone();
+        }
+        let adapter_limits = adapter.limits();
+        if wgpu::Limits::default().check_limits(&adapter_limits) {
+            wgpu::Limits::default()
+        } else {
+            wgpu::Limits::downlevel_defaults().using_resolution(adapter_limits)
+        }
+    }
+}
+
+/// Pick a surface format from `supported`, as close to `preferred` as possible
+///
+/// Tries the exact format, then its sRGB/linear counterpart, then the same
+/// encoding with swapped RGBA/BGRA channel order, then the surface's first format.
+pub fn negotiate_surface_format(
+    supported: &[wgpu::TextureFormat],
+    preferred: wgpu::TextureFormat,
+) -> Option<wgpu::TextureFormat> {
+    use wgpu::TextureFormat as F;
+
+    let toggled_srgb = if preferred.is_srgb() {
+        preferred.remove_srgb_suffix()
+    } else {
+        preferred.add_srgb_suffix()
+    };
+    let swapped = match preferred {
+        F::Rgba8Unorm => F::Bgra8Unorm,
+        F::Rgba8UnormSrgb => F::Bgra8UnormSrgb,
+        F::Bgra8Unorm => F::Rgba8Unorm,
+        F::Bgra8UnormSrgb => F::Rgba8UnormSrgb,
+        other => other,
+    };
+
+    [preferred, toggled_srgb, swapped]
+        .into_iter()
+        .find(|format| supported.contains(format))
+        .or_else(|| supported.first().copied())
+}
+
+/// Pick a present mode from `supported`, falling back when `preferred` isn't there
+///
+/// `Mailbox` falls back to `Immediate` (both skip waiting for vsync), and
+/// everything ends at `Fifo`, which every surface supports. The `Auto*`
+/// modes are resolved by wgpu when the surface is configured.
+pub fn negotiate_present_mode(
+    supported: &[wgpu::PresentMode],
+    preferred: wgpu::PresentMode,
+) -> wgpu::PresentMode {
+    use wgpu::PresentMode as P;
+
+    let fallbacks: &[P] = match preferred {
+        P::Mailbox => &[P::Mailbox, P::Immediate],
+        P::AutoVsync | P::AutoNoVsync => return preferred,
+        other => &[other],
+    };
+    fallbacks
+        .iter()
+        .copied()
+        .find(|mode| supported.contains(mode))
+        .unwrap_or(P::Fifo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wgpu::TextureFormat as F;
+
+    #[test]
+    fn test_surface_format_negotiation() {
+        let bgra_only = [F::Bgra8UnormSrgb, F::Bgra8Unorm];
+        assert_eq!(
+            negotiate_surface_format(&bgra_only, F::Rgba8Unorm),
+            Some(F::Bgra8Unorm)
+        );
+        assert_eq!(
+            negotiate_surface_format(&bgra_only, F::Bgra8UnormSrgb),
+            Some(F::Bgra8UnormSrgb)
+        );
+        assert_eq!(
+            negotiate_surface_format(&[F::Rgba8UnormSrgb], F::Rgba8Unorm),
+            Some(F::Rgba8UnormSrgb)
+        );
+        assert_eq!(
+            negotiate_surface_format(&[F::Rgba16Float], F::Rgba8Unorm),
+            Some(F::Rgba16Float)
+        );
+        assert_eq!(negotiate_surface_format(&[], F::Rgba8Unorm), None);
+    }
+
+    #[test]
+    fn test_present_mode_negotiation() {
+        use wgpu::PresentMode as P;
+
+        let all = [P::Fifo, P::Mailbox, P::Immediate];
+        assert_eq!(negotiate_present_mode(&all, P::Immediate), P::Immediate);
+        assert_eq!(
+            negotiate_present_mode(&[P::Fifo, P::Immediate], P::Mailbox),
+            P::Immediate
+        );
+        assert_eq!(negotiate_present_mode(&[P::Fifo], P::Mailbox), P::Fifo);
+        assert_eq!(
+            negotiate_present_mode(&[P::Fifo], P::AutoNoVsync),
+            P::AutoNoVsync
+        );
+    }
+}
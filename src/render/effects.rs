@@ -0,0 +1,196 @@
+//! # Node Effects
+//!
+//! Renders the [`NodeEffect`]s (drop shadows, outer glows) of scene nodes.
+//! A [`super::graph::ScenePass`] hands scenes with effects to
+//! [`NodeEffectRenderer`], which splits the scene into runs of plain nodes.
+//! Before each node with effects it draws the node's silhouette into an
+//! offscreen mask, blurs the mask and blends it, tinted, into the target.
+
+use super::graph::{DrawLayer, PassContext, SceneTarget};
+use super::post::{PostParams, Step, StepResources, Target, INTERMEDIATE_FORMAT};
+use crate::core::{Matrix4, Vector3};
+use crate::scene::{NodeEffect, SceneNode};
+
+/// Parameter slots per frame; each effect uses three
+const MAX_EFFECT_STEPS: usize = 192;
+
+/// Coverage multiplier for glows, so the blurred edge stays visible next to the node
+const GLOW_STRENGTH: f32 = 2.0;
+
+/// Draws scenes whose nodes have shadows or glows
+pub(super) struct NodeEffectRenderer {
+    steps: StepResources,
+    masks: Option<Masks>,
+}
+
+/// Silhouette mask and blur scratch at the target size
+struct Masks {
+    size: (u32, u32),
+    mask: Target,
+    scratch: Target,
+}
+
+impl NodeEffectRenderer {
+    pub fn new(renderer: &super::ShapeRenderer) -> Self {
+        Self {
+            steps: StepResources::new(renderer, MAX_EFFECT_STEPS),
+            masks: None,
+        }
+    }
+
+    /// Draw `layer` of the scene into `target` (starting with `load`), with
+    /// each node's effects composited just before the node itself
+    pub fn draw_scene(
+        &mut self,
+        ctx: &mut PassContext,
+        target: &SceneTarget,
+        layer: DrawLayer,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) {
+        let size = target.size;
+        if self.masks.as_ref().is_none_or(|masks| masks.size != size) {
+            let device = ctx.renderer.get_device();
+            self.masks = Some(Masks {
+                size,
+                mask: Target::new(device, "Node Effect Mask", size),
+                scratch: Target::new(device, "Node Effect Blur", size),
+            });
+        }
+        let Some(masks) = &self.masks else {
+            return;
+        };
+
+        let scene = ctx.scene;
+        let nodes = scene.visible_renderable_nodes();
+        let mut load = load;
+        let mut first = true;
+        let mut start = 0;
+        let mut slot = 0;
+        for (index, node) in nodes.iter().enumerate() {
+            let has_effects = node
+                .renderable
+                .as_ref()
+                .is_some_and(|renderable| layer.includes(renderable.is_glyphs()));
+            if !has_effects || node.effects.is_empty() {
+                continue;
+            }
+
+            // Everything before this node, then its effects underneath it
+            {
+                let mut render_pass = target.begin(ctx.renderer, ctx.encoder, load, first);
+                crate::pipeline::draw_nodes(
+                    ctx.renderer,
+                    scene,
+                    &nodes[start..index],
+                    &ctx.view_proj,
+                    ctx.eye,
+                    layer,
+                    &mut render_pass,
+                );
+            }
+            load = wgpu::LoadOp::Load;
+            first = false;
+            start = index;
+
+            for effect in &node.effects {
+                slot = self.draw_effect(ctx, node, effect, masks, target, slot);
+            }
+        }
+
+        let mut render_pass = target.begin(ctx.renderer, ctx.encoder, load, first);
+        crate::pipeline::draw_nodes(
+            ctx.renderer,
+            scene,
+            &nodes[start..],
+            &ctx.view_proj,
+            ctx.eye,
+            layer,
+            &mut render_pass,
+        );
+    }
+
+    /// Mask, blur and composite one effect; returns the next free parameter slot
+    fn draw_effect(
+        &self,
+        ctx: &mut PassContext,
+        node: &SceneNode,
+        effect: &NodeEffect,
+        masks: &Masks,
+        target: &SceneTarget,
+        slot: usize,
+    ) -> usize {
+        if slot + 3 > self.steps.slots() {
+            return slot;
+        }
+
+        let (offset, strength) = match *effect {
+            NodeEffect::Shadow { offset, .. } => (Vector3::new(offset.0, offset.1, 0.0), 1.0),
+            NodeEffect::Glow { .. } => (Vector3::zero(), GLOW_STRENGTH),
+        };
+        let view_proj = ctx.view_proj * Matrix4::from_translation(offset);
+        {
+            let mut render_pass = ctx.renderer.begin_render_pass_with_load(
+                ctx.encoder,
+                &masks.mask.view,
+                INTERMEDIATE_FORMAT,
+                wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+            );
+            crate::pipeline::draw_nodes(
+                ctx.renderer,
+                ctx.scene,
+                &[node],
+                &view_proj,
+                ctx.eye,
+                DrawLayer::All,
+                &mut render_pass,
+            );
+        }
+
+        let blur = PostParams {
+            texel: [1.0 / masks.size.0 as f32, 1.0 / masks.size.1 as f32],
+            radius: effect.blur_radius(),
+            ..PostParams::default()
+        };
+        let color = effect.color();
+        let steps = [
+            Step {
+                entry: "fs_blur",
+                input: &masks.mask.view,
+                base: &masks.mask.view,
+                target: &masks.scratch.view,
+                format: INTERMEDIATE_FORMAT,
+                blend: None,
+                params: PostParams {
+                    direction: [1.0, 0.0],
+                    ..blur
+                },
+            },
+            Step {
+                entry: "fs_blur",
+                input: &masks.scratch.view,
+                base: &masks.scratch.view,
+                target: &masks.mask.view,
+                format: INTERMEDIATE_FORMAT,
+                blend: None,
+                params: PostParams {
+                    direction: [0.0, 1.0],
+                    ..blur
+                },
+            },
+            Step {
+                entry: "fs_tint",
+                input: &masks.mask.view,
+                base: &masks.mask.view,
+                target: target.view,
+                format: target.format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                params: PostParams {
+                    intensity: strength,
+                    color: [color.r, color.g, color.b, color.a],
+                    ..PostParams::default()
+                },
+            },
+        ];
+        self.steps.run(ctx.renderer, ctx.encoder, slot, &steps)
+    }
+}
@@ -0,0 +1,175 @@
+//! # GPU Pass Timing
+//!
+//! [`GpuTimer`] writes a timestamp at the start and end of a render pass and
+//! reads them back without stalling: results are mapped asynchronously and
+//! picked up a frame or two later, so each reading is slightly stale and
+//! frames recorded while a readback is in flight are not measured.
+//!
+//! Timing needs `wgpu::Features::TIMESTAMP_QUERY`; request it with
+//! [`super::RendererDescriptor::with_optional_features`] so adapters without
+//! it still work, and [`GpuTimer::new`] returns `None` there.
+//!
+//! ```rust,no_run
+//! use diomanim::render::{GpuTimer, ShapeRenderer};
+//!
+//! # fn example(renderer: &ShapeRenderer, view: &wgpu::TextureView) {
+//! let mut timer = GpuTimer::new(renderer).expect("timestamp queries unsupported");
+//! let mut encoder = renderer
+//!     .get_device()
+//!     .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+//! let pass = renderer.begin_timed_render_pass(&mut encoder, view, None, &timer);
+//! drop(pass);
+//! timer.resolve(&mut encoder);
+//! renderer.get_queue().submit(std::iter::once(encoder.finish()));
+//! if let Some(gpu_time) = timer.collect(renderer.get_device()) {
+//!     println!("GPU: {:.2} ms", gpu_time.as_secs_f64() * 1000.0);
+//! }
+//! # }
+//! ```
+
+use super::ShapeRenderer;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Bytes of the two resolved timestamps
+const TIMESTAMP_BYTES: u64 = 2 * std::mem::size_of::<u64>() as u64;
+
+/// Where the readback buffer is in its resolve/map cycle
+enum ReadbackState {
+    /// Free to receive the next pass's timestamps
+    Idle,
+    /// Holds timestamps that are copied but not mapped yet
+    Resolved,
+    /// Mapping, with the map result arriving on the receiver
+    Mapping(mpsc::Receiver<Result<(), wgpu::BufferAsyncError>>),
+}
+
+/// Measures the GPU time of one render pass per frame with timestamp queries
+pub struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    /// Nanoseconds per timestamp tick
+    period: f32,
+    state: ReadbackState,
+}
+
+impl GpuTimer {
+    /// Create a timer, or `None` if the device lacks timestamp queries
+    pub fn new(renderer: &ShapeRenderer) -> Option<Self> {
+        let device = renderer.get_device();
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("GPU Timer Queries"),
+            ty: wgpu::QueryType::Timestamp,
+            count: 2,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Timer Resolve Buffer"),
+            size: TIMESTAMP_BYTES,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Timer Readback Buffer"),
+            size: TIMESTAMP_BYTES,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            period: renderer.get_queue().get_timestamp_period(),
+            state: ReadbackState::Idle,
+        })
+    }
+
+    /// Timestamp writes for the measured pass
+    pub fn timestamp_writes(&self) -> wgpu::RenderPassTimestampWrites<'_> {
+        wgpu::RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(0),
+            end_of_pass_write_index: Some(1),
+        }
+    }
+
+    /// Copy the pass's timestamps for readback (call after the pass ends)
+    ///
+    /// Skipped while the previous reading is still being mapped.
+    pub fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if !matches!(self.state, ReadbackState::Idle) {
+            return;
+        }
+        encoder.resolve_query_set(&self.query_set, 0..2, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            TIMESTAMP_BYTES,
+        );
+        self.state = ReadbackState::Resolved;
+    }
+
+    /// Start reading back resolved timestamps and return a finished reading
+    ///
+    /// Call once per frame after submitting; never blocks on the GPU.
+    pub fn collect(&mut self, device: &wgpu::Device) -> Option<Duration> {
+        match &self.state {
+            ReadbackState::Idle => None,
+            ReadbackState::Resolved => {
+                let (sender, receiver) = mpsc::channel();
+                self.readback_buffer
+                    .slice(..)
+                    .map_async(wgpu::MapMode::Read, move |result| {
+                        let _ = sender.send(result);
+                    });
+                self.state = ReadbackState::Mapping(receiver);
+                None
+            }
+            ReadbackState::Mapping(receiver) => {
+                let _ = device.poll(wgpu::PollType::Poll);
+                match receiver.try_recv() {
+                    Ok(Ok(())) => {}
+                    Err(mpsc::TryRecvError::Empty) => return None,
+                    // Mapping failed, measure a later frame instead
+                    Ok(Err(_)) | Err(mpsc::TryRecvError::Disconnected) => {
+                        self.state = ReadbackState::Idle;
+                        return None;
+                    }
+                }
+                let timestamps: [u64; 2] = {
+                    let data = self.readback_buffer.slice(..).get_mapped_range();
+                    bytemuck::pod_read_unaligned(&data[..TIMESTAMP_BYTES as usize])
+                };
+                self.readback_buffer.unmap();
+                self.state = ReadbackState::Idle;
+                Some(elapsed(timestamps[0], timestamps[1], self.period))
+            }
+        }
+    }
+}
+
+/// Time between two timestamps `period` nanoseconds per tick apart
+fn elapsed(begin: u64, end: u64, period: f32) -> Duration {
+    let ticks = end.saturating_sub(begin);
+    Duration::from_nanos((ticks as f64 * f64::from(period)) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elapsed() {
+        assert_eq!(elapsed(1_000, 3_000, 1.0), Duration::from_micros(2));
+        assert_eq!(elapsed(100, 300, 40.0), Duration::from_micros(8));
+        // Timestamps can arrive out of order on some drivers
+        assert_eq!(elapsed(300, 100, 1.0), Duration::ZERO);
+    }
+}
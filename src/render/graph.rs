@@ -0,0 +1,1012 @@
+//! # Render Graph
+//!
+//! A frame as a list of passes that declare which textures they read and
+//! write. The graph allocates the intermediate textures, orders the passes so
+//! every texture is fully written before it is read, records them into one
+//! command encoder and collects readbacks once the GPU is done. Effects such
+//! as blur or bloom become extra passes instead of edits to the frame loop.
+//!
+//! Built-in passes:
+//! - **ScenePass**: draws the scene's shapes, glyphs, or both (with node shadows, glows and clip masks) into a texture
+//! - **FullscreenPass**: runs a WGSL fragment shader over an input texture (post-processing)
+//! - **ReadbackPass**: copies a texture back to the CPU
+//!
+//! With [`RenderGraph::enable_profiling`], every pass is timed on the GPU
+//! (see [`super::profiler`]).
+//!
+//! ```rust,no_run
+//! use diomanim::prelude::*;
+//! use diomanim::render::graph::*;
+//! use diomanim::scene::SceneGraph;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut renderer = ShapeRenderer::new(1280, 720).await?;
+//! let scene = SceneGraph::new();
+//!
+//! let format = wgpu::TextureFormat::Rgba8Unorm;
+//! let mut graph = RenderGraph::new(1280, 720);
+//! graph.add_texture("scene", format);
+//! graph.add_texture("final", format);
+//! graph.add_pass(ScenePass::new("main", "scene", DrawLayer::Shapes).with_clear(Color::BLACK));
+//! graph.add_pass(ScenePass::new("text", "scene", DrawLayer::Glyphs));
+//! graph.add_pass(FullscreenPass::new("invert", "scene", "final", "
+//!     @fragment
+//!     fn fs_main(in: FullscreenOutput) -> @location(0) vec4<f32> {
+//!         let color = textureSample(t_input, s_input, in.uv);
+//!         return vec4<f32>(1.0 - color.rgb, color.a);
+//!     }
+//! "));
+//! graph.add_pass(ReadbackPass::new("final"));
+//!
+//! graph.execute(&mut renderer, &scene, None)?;
+//! let pixels = graph.take_readback("final");
+//! # Ok(())
+//! # }
+//! ```
+
+use super::effects::NodeEffectRenderer;
+use super::profiler::{FrameProfile, PassCategory, Profiler};
+use super::resources::{BudgetExceeded, ResourceKind, ResourceTracker};
+use super::{PipelineKey, ShapeRenderer, STENCIL_FORMAT};
+use crate::core::{Color, Matrix4, Vector3};
+use crate::scene::SceneGraph;
+use std::collections::HashMap;
+
+/// Resource name of the external target passed to [`RenderGraph::execute`]
+pub const TARGET: &str = "target";
+
+/// Which renderables a [`ScenePass`] draws
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DrawLayer {
+    /// Everything, in scene order
+    #[default]
+    All,
+    /// Everything except text and math
+    Shapes,
+    /// Only text and math
+    Glyphs,
+}
+
+impl DrawLayer {
+    /// Whether a renderable (text/math or not) belongs to this layer
+    pub fn includes(self, is_glyphs: bool) -> bool {
+        match self {
+            Self::All => true,
+            Self::Shapes => !is_glyphs,
+            Self::Glyphs => is_glyphs,
+        }
+    }
+}
+
+/// A texture a pass can draw to or sample from
+pub struct GraphTexture {
+    texture: Option<wgpu::Texture>,
+    view: wgpu::TextureView,
+    format: wgpu::TextureFormat,
+    size: (u32, u32),
+}
+
+/// What a pass gets while it records its commands
+pub struct PassContext<'a> {
+    pub renderer: &'a mut ShapeRenderer,
+    pub scene: &'a SceneGraph,
+    pub encoder: &'a mut wgpu::CommandEncoder,
+    /// Camera view-projection for scene passes
+    pub view_proj: Matrix4,
+    /// Camera position for specular lighting
+    pub eye: Vector3,
+    textures: &'a HashMap<String, GraphTexture>,
+    readbacks: &'a mut Vec<PendingReadback>,
+    profiler: Option<&'a mut Profiler>,
+}
+
+impl<'a> PassContext<'a> {
+    fn resource(&self, name: &str) -> &'a GraphTexture {
+        let textures: &'a HashMap<String, GraphTexture> = self.textures;
+        textures
+            .get(name)
+            .unwrap_or_else(|| panic!("render graph texture `{name}` is not declared"))
+    }
+
+    /// View of texture `name` (or of [`TARGET`])
+    pub fn view(&self, name: &str) -> &'a wgpu::TextureView {
+        &self.resource(name).view
+    }
+
+    /// The texture itself; `None` for the external target
+    pub fn texture(&self, name: &str) -> Option<&'a wgpu::Texture> {
+        self.resource(name).texture.as_ref()
+    }
+
+    pub fn format(&self, name: &str) -> wgpu::TextureFormat {
+        self.resource(name).format
+    }
+
+    pub fn size(&self, name: &str) -> (u32, u32) {
+        self.resource(name).size
+    }
+
+    /// Whether the graph is profiling this frame
+    pub fn is_profiling(&self) -> bool {
+        self.profiler.is_some()
+    }
+
+    /// End a profiled span here, splitting the current pass's time
+    ///
+    /// The rest of the pass is still labelled with the pass's name.
+    pub fn profile_mark(&mut self, label: &str, category: PassCategory) {
+        if let Some(profiler) = &mut self.profiler {
+            profiler.mark(self.encoder, label, category);
+        }
+    }
+
+    /// Copy texture `name` back to the CPU once the frame is submitted
+    ///
+    /// The pixels become available from [`RenderGraph::take_readback`].
+    pub fn read_back(&mut self, name: &str) {
+        let Some(texture) = self.texture(name) else {
+            return;
+        };
+        let (width, height) = self.size(name);
+        let padded_bytes_per_row = padded_bytes_per_row(width);
+        let buffer = self
+            .renderer
+            .get_device()
+            .create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Graph Readback Buffer"),
+                size: u64::from(padded_bytes_per_row) * u64::from(height),
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            });
+        copy_to_buffer(self.encoder, texture, &buffer, width, height);
+        self.readbacks.push(PendingReadback {
+            name: name.to_string(),
+            buffer,
+            width,
+            height,
+            format: self.format(name),
+        });
+    }
+}
+
+/// One step of a frame
+pub trait RenderNode {
+    /// Label for errors and debugging
+    fn name(&self) -> &str;
+    /// Textures this pass samples or copies from
+    fn inputs(&self) -> Vec<&str>;
+    /// Textures this pass draws to
+    fn outputs(&self) -> Vec<&str>;
+    /// What the pass's GPU time counts as when profiling
+    fn category(&self) -> PassCategory {
+        PassCategory::Other
+    }
+    /// Record the pass's commands
+    fn run(&mut self, ctx: &mut PassContext);
+}
+
+/// A copy into a mappable buffer waiting for the frame to finish
+struct PendingReadback {
+    name: String,
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+}
+
+/// A frame described as passes over named textures
+pub struct RenderGraph {
+    width: u32,
+    height: u32,
+    view_proj: Matrix4,
+    eye: Vector3,
+    /// Declared textures (name, format, size relative to the graph)
+    declared: Vec<(String, wgpu::TextureFormat, f32)>,
+    textures: HashMap<String, GraphTexture>,
+    passes: Vec<Box<dyn RenderNode>>,
+    readbacks: HashMap<String, Vec<u8>>,
+    profiler: Option<Profiler>,
+    profile: Option<FrameProfile>,
+}
+
+impl RenderGraph {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            view_proj: Matrix4::identity(),
+            eye: Vector3::new(0.0, 0.0, 5.0),
+            declared: Vec::new(),
+            textures: HashMap::new(),
+            passes: Vec::new(),
+            readbacks: HashMap::new(),
+            profiler: None,
+            profile: None,
+        }
+    }
+
+    /// Declare a full-size intermediate texture
+    pub fn add_texture(&mut self, name: &str, format: wgpu::TextureFormat) -> &mut Self {
+        self.add_scaled_texture(name, format, 1.0)
+    }
+
+    /// Declare a texture at `scale` times the graph size (e.g. 0.5 for a blur chain)
+    pub fn add_scaled_texture(
+        &mut self,
+        name: &str,
+        format: wgpu::TextureFormat,
+        scale: f32,
+    ) -> &mut Self {
+        self.declared.retain(|(declared, _, _)| declared != name);
+        self.declared.push((name.to_string(), format, scale));
+        self.textures.remove(name);
+        self
+    }
+
+    pub fn add_pass(&mut self, pass: impl RenderNode + 'static) -> &mut Self {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    /// Camera used by scene passes
+    pub fn set_camera(&mut self, view_proj: Matrix4, eye: Vector3) {
+        self.view_proj = view_proj;
+        self.eye = eye;
+    }
+
+    /// View-projection and eye position used by scene passes
+    pub fn camera(&self) -> (Matrix4, Vector3) {
+        (self.view_proj, self.eye)
+    }
+
+    /// Change the output size; textures are reallocated on the next execute
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if (width, height) != (self.width, self.height) {
+            self.width = width;
+            self.height = height;
+            self.textures.clear();
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Time every pass on the GPU from the next [`Self::execute`] on
+    ///
+    /// Returns `false` (and leaves profiling off) when the renderer's device
+    /// lacks [`Profiler::FEATURES`].
+    pub fn enable_profiling(&mut self, renderer: &ShapeRenderer) -> bool {
+        if self.profiler.is_none() {
+            self.profiler = Profiler::new(renderer);
+        }
+        self.profiler.is_some()
+    }
+
+    /// Stop profiling and drop the profiler's GPU resources
+    pub fn disable_profiling(&mut self) {
+        self.profiler = None;
+        self.profile = None;
+    }
+
+    /// GPU timings of the last [`Self::execute`], when profiling
+    pub fn take_profile(&mut self) -> Option<FrameProfile> {
+        self.profile.take()
+    }
+
+    /// Names of the passes in execution order
+    pub fn pass_names(&self) -> Result<Vec<&str>, String> {
+        Ok(self
+            .execution_order()?
+            .into_iter()
+            .map(|index| self.passes[index].name())
+            .collect())
+    }
+
+    /// Order passes so each texture's writers run (in insertion order) before its readers
+    fn execution_order(&self) -> Result<Vec<usize>, String> {
+        let declared =
+            |name: &str| name == TARGET || self.declared.iter().any(|(d, _, _)| d == name);
+        let mut writers: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, pass) in self.passes.iter().enumerate() {
+            for output in pass.outputs() {
+                if !declared(output) {
+                    return Err(format!(
+                        "pass `{}` writes undeclared texture `{output}`",
+                        pass.name()
+                    ));
+                }
+                writers.entry(output).or_default().push(index);
+            }
+        }
+
+        let mut edges: Vec<Vec<usize>> = vec![Vec::new(); self.passes.len()];
+        for chain in writers.values() {
+            for pair in chain.windows(2) {
+                edges[pair[0]].push(pair[1]);
+            }
+        }
+        for (index, pass) in self.passes.iter().enumerate() {
+            for input in pass.inputs() {
+                let Some(chain) = writers.get(input) else {
+                    return Err(format!(
+                        "pass `{}` reads `{input}`, which no pass writes",
+                        pass.name()
+                    ));
+                };
+                for &writer in chain.iter().filter(|&&writer| writer != index) {
+                    edges[writer].push(index);
+                }
+            }
+        }
+
+        // Kahn's algorithm, always taking the earliest ready pass
+        let mut in_degree = vec![0; self.passes.len()];
+        for targets in &edges {
+            for &target in targets {
+                in_degree[target] += 1;
+            }
+        }
+        let mut order = Vec::with_capacity(self.passes.len());
+        let mut ready: Vec<usize> = (0..self.passes.len())
+            .filter(|&index| in_degree[index] == 0)
+            .collect();
+        while let Some(position) = ready
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, &i)| i)
+            .map(|(p, _)| p)
+        {
+            let index = ready.swap_remove(position);
+            order.push(index);
+            for &target in &edges[index] {
+                in_degree[target] -= 1;
+                if in_degree[target] == 0 {
+                    ready.push(target);
+                }
+            }
+        }
+
+        if order.len() == self.passes.len() {
+            Ok(order)
+        } else {
+            Err("render graph has a dependency cycle".to_string())
+        }
+    }
+
+    /// Create the declared textures not allocated yet, recording them in the
+    /// renderer's [`ResourceTracker`]
+    fn allocate_textures(&mut self, renderer: &mut ShapeRenderer) -> Result<(), BudgetExceeded> {
+        for (name, format, scale) in &self.declared {
+            if self.textures.contains_key(name) {
+                continue;
+            }
+            let size = (
+                ((self.width as f32 * scale).round() as u32).max(1),
+                ((self.height as f32 * scale).round() as u32).max(1),
+            );
+            renderer.resources_mut().try_allocate(
+                &format!("graph {name}"),
+                ResourceKind::Texture,
+                ResourceTracker::texture_size(size.0, size.1, *format),
+            )?;
+            let texture = renderer
+                .get_device()
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some(name),
+                    size: wgpu::Extent3d {
+                        width: size.0,
+                        height: size.1,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: *format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                        | wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::COPY_SRC,
+                    view_formats: &[],
+                });
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            self.textures.insert(
+                name.clone(),
+                GraphTexture {
+                    texture: Some(texture),
+                    view,
+                    format: *format,
+                    size,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Record and submit every pass, then resolve readbacks
+    ///
+    /// `target` is the external view (e.g. a surface texture) bound to
+    /// [`TARGET`], with its format.
+    pub fn execute(
+        &mut self,
+        renderer: &mut ShapeRenderer,
+        scene: &SceneGraph,
+        target: Option<(&wgpu::TextureView, wgpu::TextureFormat)>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let order = self.execution_order()?;
+        self.allocate_textures(renderer)?;
+        match target {
+            Some((view, format)) => {
+                self.textures.insert(
+                    TARGET.to_string(),
+                    GraphTexture {
+                        texture: None,
+                        view: view.clone(),
+                        format,
+                        size: (self.width, self.height),
+                    },
+                );
+            }
+            None => {
+                self.textures.remove(TARGET);
+            }
+        }
+
+        renderer.reset_transform_offset();
+        let mut encoder =
+            renderer
+                .get_device()
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Render Graph Encoder"),
+                });
+        crate::pipeline::draw_render_targets(renderer, scene, self.eye, &mut encoder);
+        let mut pending = Vec::new();
+        if let Some(profiler) = &mut self.profiler {
+            profiler.begin_frame(&mut encoder);
+        }
+        for index in order {
+            let pass = &mut self.passes[index];
+            let mut ctx = PassContext {
+                renderer: &mut *renderer,
+                scene,
+                encoder: &mut encoder,
+                view_proj: self.view_proj,
+                eye: self.eye,
+                textures: &self.textures,
+                readbacks: &mut pending,
+                profiler: self.profiler.as_mut(),
+            };
+            pass.run(&mut ctx);
+            if let Some(profiler) = &mut self.profiler {
+                profiler.mark(&mut encoder, pass.name(), pass.category());
+            }
+        }
+        if let Some(profiler) = &self.profiler {
+            profiler.resolve(&mut encoder);
+        }
+        renderer
+            .get_queue()
+            .submit(std::iter::once(encoder.finish()));
+        self.textures.remove(TARGET);
+        if let Some(profiler) = &mut self.profiler {
+            self.profile = Some(profiler.read(renderer.get_device())?);
+        }
+
+        for readback in pending {
+            let pixels = map_readback(renderer.get_device(), &readback)?;
+            self.readbacks.insert(readback.name, pixels);
+        }
+        Ok(())
+    }
+
+    /// Tightly packed RGBA pixels read back from texture `name` by the last execute
+    pub fn take_readback(&mut self, name: &str) -> Option<Vec<u8>> {
+        self.readbacks.remove(name)
+    }
+}
+
+/// Draws the scene (or one [`DrawLayer`] of it) into a texture
+pub struct ScenePass {
+    name: &'static str,
+    output: String,
+    layer: DrawLayer,
+    clear: Option<Color>,
+    /// Created the first time the scene has node shadows or glows
+    effects: Option<NodeEffectRenderer>,
+    /// Created the first time the scene has clip masks
+    stencil: Option<StencilTarget>,
+}
+
+impl ScenePass {
+    /// Draw `layer` on top of whatever `output` already holds
+    pub fn new(name: &'static str, output: &str, layer: DrawLayer) -> Self {
+        Self {
+            name,
+            output: output.to_string(),
+            layer,
+            clear: None,
+            effects: None,
+            stencil: None,
+        }
+    }
+
+    /// Clear `output` to `color` first
+    pub fn with_clear(mut self, color: Color) -> Self {
+        self.clear = Some(color);
+        self
+    }
+}
+
+impl RenderNode for ScenePass {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn inputs(&self) -> Vec<&str> {
+        Vec::new()
+    }
+
+    fn outputs(&self) -> Vec<&str> {
+        vec![&self.output]
+    }
+
+    fn category(&self) -> PassCategory {
+        match self.layer {
+            DrawLayer::Glyphs => PassCategory::Text,
+            DrawLayer::All | DrawLayer::Shapes => PassCategory::Shapes,
+        }
+    }
+
+    fn run(&mut self, ctx: &mut PassContext) {
+        let size = ctx.size(&self.output);
+        let stencil = if ctx.scene.has_clip_masks() {
+            if self
+                .stencil
+                .as_ref()
+                .is_none_or(|stencil| stencil.size != size)
+            {
+                self.stencil = Some(StencilTarget::new(ctx.renderer.get_device(), size));
+            }
+            self.stencil.as_ref().map(|stencil| &stencil.view)
+        } else {
+            None
+        };
+        let target = SceneTarget {
+            view: ctx.view(&self.output),
+            format: ctx.format(&self.output),
+            size,
+            stencil,
+        };
+        let mut load = match self.clear {
+            Some(color) => wgpu::LoadOp::Clear(to_wgpu_color(color)),
+            None => wgpu::LoadOp::Load,
+        };
+        if self.clear.is_some() && ctx.is_profiling() {
+            // Clear in a pass of its own so the profile times it separately
+            drop(ctx.renderer.begin_render_pass_with_load(
+                ctx.encoder,
+                target.view,
+                target.format,
+                load,
+            ));
+            ctx.profile_mark(&format!("{} clear", self.name), PassCategory::Clear);
+            load = wgpu::LoadOp::Load;
+        }
+        if ctx.scene.has_node_effects() {
+            let effects = self
+                .effects
+                .get_or_insert_with(|| NodeEffectRenderer::new(ctx.renderer));
+            effects.draw_scene(ctx, &target, self.layer, load);
+            return;
+        }
+
+        let mut render_pass = target.begin(ctx.renderer, ctx.encoder, load, true);
+        crate::pipeline::draw_scene_layer(
+            ctx.renderer,
+            ctx.scene,
+            &ctx.view_proj,
+            ctx.eye,
+            self.layer,
+            &mut render_pass,
+        );
+    }
+}
+
+/// Stencil attachment a [`ScenePass`] clips with
+struct StencilTarget {
+    size: (u32, u32),
+    _texture: wgpu::Texture,
+    view: wgpu::TextureView,
+}
+
+impl StencilTarget {
+    fn new(device: &wgpu::Device, size: (u32, u32)) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Scene Stencil"),
+            size: wgpu::Extent3d {
+                width: size.0,
+                height: size.1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: STENCIL_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Self {
+            size,
+            _texture: texture,
+            view,
+        }
+    }
+}
+
+/// Color target of a scene pass, plus its stencil when the scene has clip masks
+pub(super) struct SceneTarget<'a> {
+    pub view: &'a wgpu::TextureView,
+    pub format: wgpu::TextureFormat,
+    pub size: (u32, u32),
+    pub stencil: Option<&'a wgpu::TextureView>,
+}
+
+impl<'a> SceneTarget<'a> {
+    /// Begin a pass drawing to the target; the stencil is cleared on the
+    /// `first` pass of a frame and kept by later ones
+    pub fn begin<'e>(
+        &self,
+        renderer: &ShapeRenderer,
+        encoder: &'e mut wgpu::CommandEncoder,
+        load: wgpu::LoadOp<wgpu::Color>,
+        first: bool,
+    ) -> wgpu::RenderPass<'e>
+    where
+        'a: 'e,
+    {
+        match self.stencil {
+            Some(stencil) => {
+                let stencil_load = if first {
+                    wgpu::LoadOp::Clear(0)
+                } else {
+                    wgpu::LoadOp::Load
+                };
+                renderer.begin_render_pass_with_stencil(
+                    encoder,
+                    self.view,
+                    self.format,
+                    load,
+                    stencil,
+                    stencil_load,
+                )
+            }
+            None => renderer.begin_render_pass_with_load(encoder, self.view, self.format, load),
+        }
+    }
+}
+
+/// Vertex stage and bindings shared by every [`FullscreenPass`] shader
+///
+/// Fragment shaders sample `t_input` with `s_input` at `in.uv`.
+pub const FULLSCREEN_PRELUDE: &str = "
+struct FullscreenOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@group(0) @binding(0) var t_input: texture_2d<f32>;
+@group(0) @binding(1) var s_input: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    // One triangle covering the whole viewport
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: FullscreenOutput;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+";
+
+/// Runs a fragment shader over every pixel of `input`, writing `output`
+pub struct FullscreenPass {
+    name: &'static str,
+    input: String,
+    output: String,
+    fragment_source: &'static str,
+    resources: Option<(wgpu::BindGroupLayout, wgpu::Sampler)>,
+}
+
+impl FullscreenPass {
+    /// `fragment_source` defines `fs_main(in: FullscreenOutput)`; it is appended
+    /// to [`FULLSCREEN_PRELUDE`] and compiled under the pass name
+    pub fn new(
+        name: &'static str,
+        input: &str,
+        output: &str,
+        fragment_source: &'static str,
+    ) -> Self {
+        Self {
+            name,
+            input: input.to_string(),
+            output: output.to_string(),
+            fragment_source,
+            resources: None,
+        }
+    }
+}
+
+impl RenderNode for FullscreenPass {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn inputs(&self) -> Vec<&str> {
+        vec![&self.input]
+    }
+
+    fn outputs(&self) -> Vec<&str> {
+        vec![&self.output]
+    }
+
+    fn category(&self) -> PassCategory {
+        PassCategory::Post
+    }
+
+    fn run(&mut self, ctx: &mut PassContext) {
+        let device = ctx.renderer.get_device();
+        let (layout, sampler) = self.resources.get_or_insert_with(|| {
+            let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Fullscreen Input Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+            let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+                label: Some("Fullscreen Input Sampler"),
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                ..Default::default()
+            });
+            (layout, sampler)
+        });
+
+        let cache = ctx.renderer.pipeline_cache();
+        cache.register_shader(
+            device,
+            self.name,
+            &format!("{FULLSCREEN_PRELUDE}{}", self.fragment_source),
+            &[layout],
+        );
+        let key = PipelineKey::new(self.name, &[], ctx.format(&self.output)).with_blend(None);
+        let pipeline = cache.get(device, &key);
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Fullscreen Input Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(ctx.view(&self.input)),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        });
+
+        let mut render_pass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(self.name),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: ctx.view(&self.output),
+                resolve_target: None,
+                depth_slice: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        render_pass.set_pipeline(&pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+/// Copies a texture back to the CPU (see [`RenderGraph::take_readback`])
+pub struct ReadbackPass {
+    input: String,
+}
+
+impl ReadbackPass {
+    pub fn new(input: &str) -> Self {
+        Self {
+            input: input.to_string(),
+        }
+    }
+}
+
+impl RenderNode for ReadbackPass {
+    fn name(&self) -> &'static str {
+        "readback"
+    }
+
+    fn inputs(&self) -> Vec<&str> {
+        vec![&self.input]
+    }
+
+    fn outputs(&self) -> Vec<&str> {
+        Vec::new()
+    }
+
+    fn category(&self) -> PassCategory {
+        PassCategory::Copy
+    }
+
+    fn run(&mut self, ctx: &mut PassContext) {
+        ctx.read_back(&self.input);
+    }
+}
+
+pub(crate) fn to_wgpu_color(color: Color) -> wgpu::Color {
+    wgpu::Color {
+        r: f64::from(color.r),
+        g: f64::from(color.g),
+        b: f64::from(color.b),
+        a: f64::from(color.a),
+    }
+}
+
+/// Row pitch of a `width`-pixel, 4-byte-per-pixel texture copy
+pub(crate) fn padded_bytes_per_row(width: u32) -> u32 {
+    (width * 4).div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT
+}
+
+/// Record a copy of `texture` into `buffer` with padded rows
+pub(crate) fn copy_to_buffer(
+    encoder: &mut wgpu::CommandEncoder,
+    texture: &wgpu::Texture,
+    buffer: &wgpu::Buffer,
+    width: u32,
+    height: u32,
+) {
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row(width)),
+                rows_per_image: Some(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+}
+
+/// Map a submitted readback and return tightly packed RGBA rows
+fn map_readback(
+    device: &wgpu::Device,
+    readback: &PendingReadback,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    map_pixels(
+        device,
+        &readback.buffer,
+        readback.width,
+        readback.height,
+        readback.format,
+    )
+}
+
+/// Wait for `buffer` (filled by [`copy_to_buffer`]) and unpack it to RGBA
+pub(crate) fn map_pixels(
+    device: &wgpu::Device,
+    buffer: &wgpu::Buffer,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let buffer_slice = buffer.slice(..);
+    let (tx, rx) = std::sync::mpsc::channel();
+    buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = tx.send(result);
+    });
+    device.poll(wgpu::PollType::wait_indefinitely())?;
+    rx.recv()??;
+
+    let unpadded_bytes_per_row = (width * 4) as usize;
+    let mapped = buffer_slice.get_mapped_range();
+    let mut pixels = Vec::with_capacity(unpadded_bytes_per_row * height as usize);
+    for row in mapped.chunks(padded_bytes_per_row(width) as usize) {
+        pixels.extend_from_slice(&row[..unpadded_bytes_per_row]);
+    }
+    drop(mapped);
+    buffer.unmap();
+
+    if matches!(
+        format,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+    ) {
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+    Ok(pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pass that only declares resources, for ordering tests
+    struct Declared(&'static str, Vec<&'static str>, Vec<&'static str>);
+
+    impl RenderNode for Declared {
+        fn name(&self) -> &str {
+            self.0
+        }
+        fn inputs(&self) -> Vec<&str> {
+            self.1.clone()
+        }
+        fn outputs(&self) -> Vec<&str> {
+            self.2.clone()
+        }
+        fn run(&mut self, _ctx: &mut PassContext) {}
+    }
+
+    #[test]
+    fn test_passes_ordered_by_resources() {
+        let format = wgpu::TextureFormat::Rgba8Unorm;
+        let mut graph = RenderGraph::new(64, 64);
+        graph
+            .add_texture("scene", format)
+            .add_texture("blur", format);
+        // Added out of order: readers before the passes that write their inputs
+        graph
+            .add_pass(Declared("readback", vec!["blur"], vec![]))
+            .add_pass(Declared("blur", vec!["scene"], vec!["blur"]))
+            .add_pass(Declared("main", vec![], vec!["scene"]))
+            .add_pass(Declared("text", vec![], vec!["scene"]))
+            .add_pass(Declared("present", vec!["blur"], vec![TARGET]));
+
+        assert_eq!(
+            graph.pass_names().unwrap(),
+            ["main", "text", "blur", "readback", "present"]
+        );
+
+        let mut missing = RenderGraph::new(64, 64);
+        missing.add_texture("scene", format);
+        missing.add_pass(Declared("blur", vec!["scene"], vec!["scene"]));
+        missing.add_pass(Declared("bloom", vec!["glow"], vec!["scene"]));
+        assert!(missing.pass_names().unwrap_err().contains("glow"));
+
+        let mut cyclic = RenderGraph::new(64, 64);
+        cyclic.add_texture("a", format).add_texture("b", format);
+        cyclic.add_pass(Declared("x", vec!["b"], vec!["a"]));
+        cyclic.add_pass(Declared("y", vec!["a"], vec!["b"]));
+        assert!(cyclic.pass_names().is_err());
+    }
+}
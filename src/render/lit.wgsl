@@ -0,0 +1,97 @@
+// Blinn-Phong lit shader for shapes under scene lights
+const MAX_LIGHTS: u32 = 8u;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) world_position: vec3<f32>,
+    @location(2) @interpolate(flat) instance: u32,
+};
+
+struct Uniforms {
+    model_view_proj: mat4x4<f32>,
+};
+
+// position.w = 0 for directional lights (xyz = direction), 1 for point lights
+// color.rgb is pre-multiplied by intensity, color.w = point light range
+struct Light {
+    position: vec4<f32>,
+    color: vec4<f32>,
+};
+
+struct Lighting {
+    ambient: vec4<f32>,
+    eye: vec4<f32>,
+    count: vec4<u32>,
+    lights: array<Light, MAX_LIGHTS>,
+};
+
+// material = (diffuse, specular, shininess, unused)
+struct Object {
+    model: mat4x4<f32>,
+    normal: vec4<f32>,
+    material: vec4<f32>,
+};
+
+// Transforms and objects are parallel arrays indexed by the draw's instance
+@group(0) @binding(0) var<storage, read> transforms: array<Uniforms>;
+@group(1) @binding(0) var<uniform> lighting: Lighting;
+@group(2) @binding(0) var<storage, read> objects: array<Object>;
+
+@vertex
+fn vs_main(model: VertexInput, @builtin(instance_index) instance: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let local_pos = vec4<f32>(model.position, 1.0);
+    out.clip_position = transforms[instance].model_view_proj * local_pos;
+    out.world_position = (objects[instance].model * local_pos).xyz;
+    out.color = model.color;
+    out.instance = instance;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let object = objects[in.instance];
+    let view = normalize(lighting.eye.xyz - in.world_position);
+    var normal = normalize(object.normal.xyz);
+    // Flat shapes are two-sided: light whichever face looks at the camera
+    if (dot(normal, view) < 0.0) {
+        normal = -normal;
+    }
+
+    let diffuse_strength = object.material.x;
+    let specular_strength = object.material.y;
+    let shininess = object.material.z;
+
+    var rgb = lighting.ambient.rgb * in.color.rgb;
+    let count = min(lighting.count.x, MAX_LIGHTS);
+    for (var i = 0u; i < count; i = i + 1u) {
+        let light = lighting.lights[i];
+
+        var to_light = -light.position.xyz;
+        var attenuation = 1.0;
+        if (light.position.w > 0.5) {
+            let delta = light.position.xyz - in.world_position;
+            let falloff = clamp(1.0 - length(delta) / light.color.w, 0.0, 1.0);
+            to_light = delta;
+            attenuation = falloff * falloff;
+        }
+        to_light = normalize(to_light);
+
+        let lambert = max(dot(normal, to_light), 0.0);
+        if (lambert <= 0.0 || attenuation <= 0.0) {
+            continue;
+        }
+
+        let half_dir = normalize(to_light + view);
+        let highlight = pow(max(dot(normal, half_dir), 0.0), shininess) * specular_strength;
+        rgb += light.color.rgb * attenuation * (lambert * diffuse_strength * in.color.rgb + vec3<f32>(highlight));
+    }
+
+    return vec4<f32>(clamp(rgb, vec3<f32>(0.0), vec3<f32>(1.0)), in.color.a);
+}
@@ -8,6 +8,19 @@
 //! - **ShapeRenderer**: Main rendering engine for geometric shapes
 //! - **Vertex**: GPU-compatible vertex data structure
 //! - **TransformUniform**: Transform matrix uniform buffer for GPU shaders
+//! - **LightingUniform**: Scene lights for the optional lit (Blinn-Phong) pipeline
+//! - **MaterialObjectUniform**: Params and time of a node's custom shader material
+//! - **Render targets**: Offscreen textures a scene subtree is drawn into, shown by image nodes
+//! - **ParticleSystem**: Compute-shader particle simulation with instanced rendering
+//! - **RendererBackend**: Hardware GPU or software adapter selection (with automatic fallback)
+//! - **RendererDescriptor**: Backend, power preference, limits and target format for a renderer
+//! - **RenderGraph**: Frame passes (scene, text, post-process, readback) ordered by the textures they use
+//! - **PipelineCache**: Shader modules and render pipelines built once per format, blend and sample count
+//! - **PostProcessPass**: Render graph pass applying the scene's bloom, vignette and blur effects
+//! - **GpuTimer**: Timestamp queries measuring how long the GPU spends on a render pass
+//! - **Profiler**: Per-pass GPU timings of render graph frames, exportable as a Chrome trace
+//! - **ResourceTracker**: GPU memory held by textures and buffers, with an optional budget
+//! - **export_svg**: Vector snapshot of a scene's visible renderables as an SVG document
 //!
 //! ## Architecture
 //!
@@ -38,17 +51,70 @@
 //! # }
 //! ```
 
-use crate::core::{Color, Vector3};
+pub mod backend;
+pub mod descriptor;
+mod effects;
+pub mod gpu_timer;
+pub mod graph;
+pub mod particles;
+pub mod pipeline_cache;
+pub mod post;
+pub mod profiler;
+pub mod resources;
+mod storage_buffer;
+pub mod svg;
+
+pub use backend::RendererBackend;
+pub use descriptor::{negotiate_present_mode, negotiate_surface_format, RendererDescriptor};
+pub use gpu_timer::GpuTimer;
+pub use graph::RenderGraph;
+pub use pipeline_cache::{PipelineCache, PipelineKey, StencilMode, STENCIL_FORMAT};
+pub use post::PostProcessPass;
+pub use profiler::{ChromeTrace, FrameProfile, PassCategory, Profiler};
+pub use resources::{BudgetPolicy, ResourceKind, ResourceTracker};
+pub use svg::{export_svg, scene_to_svg};
+
+use crate::animation::deform::Deformation;
+use crate::assets::{AssetHandle, AssetServer};
+use crate::core::{Color, Matrix4, Vector3};
 use crate::mobjects::Circle;
-use crate::text::GlyphAtlas;
+use crate::scene::{
+    BlendMode, Light, LightKind, Material, RenderTarget, ShaderMaterial, MAX_LIGHTS,
+};
+use crate::text::rich::BOLD_OFFSET;
+use crate::text::{GlyphAtlas, RichText, ShapedGlyph, TextPath};
+use std::collections::HashMap;
+use std::ops::Range;
 use std::sync::{Arc, Mutex};
+use storage_buffer::StorageArray;
 use wgpu::util::DeviceExt;
 
-/// Maximum number of objects that can be rendered in a single pass
-const MAX_OBJECTS_PER_PASS: usize = 1024;
+/// Per-draw transforms allocated up front; the buffers grow past this as needed
+const INITIAL_OBJECTS_PER_PASS: u32 = 1024;
 
-/// Alignment requirement for uniform buffers (must be 256 bytes on most GPUs)
-const UNIFORM_ALIGNMENT: u64 = 256;
+/// Quads a line is split into while deformed
+const DEFORMED_LINE_PIECES: usize = 48;
+
+/// Rows and columns of quads a rectangle is split into while deformed
+const DEFORMED_RECTANGLE_PIECES: usize = 24;
+
+/// Most points of a polyline drawn in one call
+const MAX_POLYLINE_POINTS: usize = u16::MAX as usize / 2;
+
+/// Background of passes begun without a clear color
+const DEFAULT_CLEAR_COLOR: wgpu::Color = wgpu::Color {
+    r: 0.95,
+    g: 0.95,
+    b: 0.95,
+    a: 1.0,
+};
+
+/// [`PipelineCache`] name of the flat shape shader (`shapes.wgsl`)
+pub const SHAPE_SHADER: &str = "shapes";
+/// [`PipelineCache`] name of the glyph shader (`text.wgsl`)
+pub const TEXT_SHADER: &str = "text";
+/// [`PipelineCache`] name of the Blinn-Phong shader (`lit.wgsl`)
+pub const LIT_SHADER: &str = "lit";
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
@@ -57,6 +123,15 @@ pub struct Vertex {
     pub color: [f32; 4],
 }
 
+impl Vertex {
+    /// Vertex buffer layout shared by the shape and lit pipelines
+    pub const BUFFERS: &'static [wgpu::VertexBufferLayout<'static>] = &[wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x4],
+    }];
+}
+
 // Text vertex with UV coordinates for texture sampling
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
@@ -66,6 +141,15 @@ pub struct TextVertex {
     pub color: [f32; 4],
 }
 
+impl TextVertex {
+    /// Vertex buffer layout of the text pipeline (position, uv, color)
+    pub const BUFFERS: &'static [wgpu::VertexBufferLayout<'static>] = &[wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<TextVertex>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x2, 2 => Float32x4],
+    }];
+}
+
 // Uniform buffer for transform matrices
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
@@ -84,6 +168,141 @@ impl TransformUniform {
             ],
         }
     }
+
+    /// Create a uniform from a row-major [`Matrix4`]
+    pub fn from_matrix(matrix: &Matrix4) -> Self {
+        Self {
+            model_view_proj: matrix.to_cols_array_2d(),
+        }
+    }
+
+    /// Convert back to a row-major [`Matrix4`]
+    pub fn to_matrix(&self) -> Matrix4 {
+        Matrix4::from_cols_array_2d(&self.model_view_proj)
+    }
+}
+
+// Single light as laid out in lit.wgsl
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct LightUniform {
+    /// Direction (w = 0) or position (w = 1)
+    pub position: [f32; 4],
+    /// Color pre-multiplied by intensity, w = point light range
+    pub color: [f32; 4],
+}
+
+impl From<&Light> for LightUniform {
+    fn from(light: &Light) -> Self {
+        let color = [
+            light.color.r * light.intensity,
+            light.color.g * light.intensity,
+            light.color.b * light.intensity,
+        ];
+        match light.kind {
+            LightKind::Directional { direction } => Self {
+                position: [direction.x, direction.y, direction.z, 0.0],
+                color: [color[0], color[1], color[2], 0.0],
+            },
+            LightKind::Point { position, range } => Self {
+                position: [position.x, position.y, position.z, 1.0],
+                color: [color[0], color[1], color[2], range],
+            },
+        }
+    }
+}
+
+// Uniform buffer holding all scene lights for the lit pipeline
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct LightingUniform {
+    pub ambient: [f32; 4],
+    /// Camera position, used for specular highlights
+    pub eye: [f32; 4],
+    /// Number of active lights in `x`
+    pub count: [u32; 4],
+    pub lights: [LightUniform; MAX_LIGHTS],
+}
+
+impl LightingUniform {
+    pub fn new(lights: &[Light], ambient: Color, eye: Vector3) -> Self {
+        let mut uniform = Self {
+            ambient: [ambient.r, ambient.g, ambient.b, 1.0],
+            eye: [eye.x, eye.y, eye.z, 1.0],
+            count: [0; 4],
+            lights: [LightUniform::default(); MAX_LIGHTS],
+        };
+        for (slot, light) in uniform.lights.iter_mut().zip(lights) {
+            *slot = LightUniform::from(light);
+        }
+        uniform.count[0] = lights.len().min(MAX_LIGHTS) as u32;
+        uniform
+    }
+}
+
+// Per-object data for the lit pipeline (model matrix, world normal, material)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct LitObjectUniform {
+    pub model: [[f32; 4]; 4],
+    pub normal: [f32; 4],
+    /// (diffuse, specular, shininess, unused)
+    pub material: [f32; 4],
+}
+
+impl LitObjectUniform {
+    /// Build from a row-major model matrix; shapes are flat, facing +Z in local space
+    pub fn new(model: &Matrix4, material: &Material) -> Self {
+        let axis =
+            |col: usize| Vector3::new(model.data[0][col], model.data[1][col], model.data[2][col]);
+        let normal = axis(0).cross(&axis(1)).normalized();
+        Self {
+            model: model.to_cols_array_2d(),
+            normal: [normal.x, normal.y, normal.z, 0.0],
+            material: [
+                material.diffuse,
+                material.specular,
+                material.shininess.max(1.0),
+                0.0,
+            ],
+        }
+    }
+}
+
+// Per-object data of a custom shader material (its params and time)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MaterialObjectUniform {
+    pub params: [[f32; 4]; crate::scene::shader::SHADER_PARAMS],
+    /// (time, unused, unused, unused)
+    pub time: [f32; 4],
+}
+
+impl MaterialObjectUniform {
+    pub fn new(material: &ShaderMaterial) -> Self {
+        Self {
+            params: material.params,
+            time: [material.time, 0.0, 0.0, 0.0],
+        }
+    }
+}
+
+/// GPU resources for the lit pipeline, created by [`ShapeRenderer::init_lighting`]
+struct LitResources {
+    lighting_buffer: wgpu::Buffer,
+    lighting_bind_group: wgpu::BindGroup,
+    /// Parallel to the transforms: entry `i` belongs to the draw using transform `i`
+    objects: StorageArray,
+}
+
+/// The two textures of a render target: one is drawn into while image
+/// nodes sample the other, then they swap
+struct TargetTextures {
+    size: (u32, u32),
+    views: [wgpu::TextureView; 2],
+    bind_groups: [wgpu::BindGroup; 2],
+    /// Index of the texture holding the last finished frame
+    front: usize,
 }
 
 pub struct ShapeRenderer {
@@ -92,74 +311,157 @@ pub struct ShapeRenderer {
     #[allow(dead_code)]
     height: u32,
     instance: wgpu::Instance,
+    adapter_info: wgpu::AdapterInfo,
+    /// Color format of the render targets pipelines are built for
+    format: wgpu::TextureFormat,
+    /// Format of the target of the most recently begun render pass
+    target_format: std::cell::Cell<wgpu::TextureFormat>,
+    /// Stencil use of the pipelines for the current render pass
+    stencil_mode: std::cell::Cell<Option<StencilMode>>,
+    /// Clip mask nodes enclosing what is drawn next, innermost last
+    clip_stack: std::cell::RefCell<Vec<crate::scene::NodeId>>,
+    /// Deformation applied to the vertices of the shapes drawn next
+    deformation: std::cell::RefCell<Option<Deformation>>,
+    /// Colors of ranges of glyphs in the text drawn next, see
+    /// [`Self::set_glyph_colors`]
+    glyph_colors: std::cell::RefCell<Vec<(Range<usize>, Color)>>,
+    /// Glyphs of the current text drawn before the piece being drawn now
+    glyph_offset: std::cell::Cell<usize>,
+    /// Blending of the shapes drawn next
+    blend_mode: std::cell::Cell<BlendMode>,
     device: wgpu::Device,
     queue: wgpu::Queue,
+    /// Shape pipeline for `format`, kept out of the cache for [`Self::get_pipeline`]
     pipeline: wgpu::RenderPipeline,
-    transform_bind_group: wgpu::BindGroup,
-    transform_buffer: wgpu::Buffer,
-    /// Current offset into transform buffer (in aligned units)
-    current_transform_offset: std::cell::Cell<u32>,
-    /// Size of each aligned transform slot
-    aligned_transform_size: u64,
+    pipelines: PipelineCache,
+    /// Per-draw transforms, indexed by instance in the vertex shader
+    transforms: StorageArray,
+    /// [`StorageArray::generation`] of the transforms bound in the current pass
+    bound_transforms: std::cell::Cell<Option<u64>>,
+    /// Draw calls recorded since the frame started
+    draw_calls: std::cell::Cell<u32>,
     // Text rendering components
-    text_pipeline: Option<wgpu::RenderPipeline>,
     text_atlas: Option<Arc<Mutex<GlyphAtlas>>>,
     text_texture: Option<wgpu::Texture>,
     text_bind_group: Option<wgpu::BindGroup>,
+    /// Layout of the textured pipeline's group 1, once registered
+    texture_layout: Option<wgpu::BindGroupLayout>,
+    // Image components
+    assets: Option<AssetServer>,
+    /// One uploaded texture per image asset
+    image_bind_groups: HashMap<AssetHandle, wgpu::BindGroup>,
+    // Lit (3D shading) components
+    lit: Option<LitResources>,
+    /// Per-draw shader material data, parallel to the transforms; created
+    /// when the first material is drawn
+    materials: Option<StorageArray>,
+    /// Offscreen textures of the scene's render targets
+    render_targets: HashMap<AssetHandle, TargetTextures>,
+    /// Long-lived GPU allocations and the memory budget
+    resources: ResourceTracker,
 }
 
 impl ShapeRenderer {
+    /// Create a renderer on the best available adapter
+    ///
+    /// Uses a hardware GPU when present and falls back to the software adapter
+    /// otherwise (see [`RendererBackend::Auto`]).
     pub async fn new(width: u32, height: u32) -> Result<Self, Box<dyn std::error::Error>> {
-        // Create instance and adapter
-        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::all(),
-            ..Default::default()
-        });
+        Self::with_backend(width, height, RendererBackend::Auto).await
+    }
 
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: None,
-                force_fallback_adapter: false,
-            })
-            .await?;
+    /// Create a renderer on a specific kind of adapter
+    pub async fn with_backend(
+        width: u32,
+        height: u32,
+        backend: RendererBackend,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::with_descriptor(&RendererDescriptor::new(width, height).with_backend(backend)).await
+    }
+
+    /// Create an offscreen renderer from a full [`RendererDescriptor`]
+    pub async fn with_descriptor(
+        descriptor: &RendererDescriptor,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let instance = descriptor.create_instance();
+        let adapter = backend::request_adapter(
+            &instance,
+            descriptor.backend,
+            descriptor.power_preference,
+            None,
+        )
+        .await?;
+        Self::from_adapter(descriptor, instance, &adapter, descriptor.format).await
+    }
+
+    /// Create a renderer that draws to `surface`
+    ///
+    /// The surface must come from `instance` (see
+    /// [`RendererDescriptor::create_instance`]). Its format is negotiated from
+    /// `descriptor.format`, its present mode from `descriptor.present_mode`,
+    /// and the returned configuration is ready to pass to
+    /// `surface.configure`.
+    pub async fn for_surface(
+        descriptor: &RendererDescriptor,
+        instance: wgpu::Instance,
+        surface: &wgpu::Surface<'_>,
+    ) -> Result<(Self, wgpu::SurfaceConfiguration), Box<dyn std::error::Error>> {
+        let adapter = backend::request_adapter(
+            &instance,
+            descriptor.backend,
+            descriptor.power_preference,
+            Some(surface),
+        )
+        .await?;
+
+        let capabilities = surface.get_capabilities(&adapter);
+        let format = negotiate_surface_format(&capabilities.formats, descriptor.format)
+            .ok_or("surface is not supported by the selected adapter")?;
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width: descriptor.width.max(1),
+            height: descriptor.height.max(1),
+            present_mode: negotiate_present_mode(
+                &capabilities.present_modes,
+                descriptor.present_mode,
+            ),
+            alpha_mode: capabilities
+                .alpha_modes
+                .first()
+                .copied()
+                .unwrap_or(wgpu::CompositeAlphaMode::Auto),
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+
+        let renderer = Self::from_adapter(descriptor, instance, &adapter, format).await?;
+        Ok((renderer, surface_config))
+    }
+
+    async fn from_adapter(
+        descriptor: &RendererDescriptor,
+        instance: wgpu::Instance,
+        adapter: &wgpu::Adapter,
+        format: wgpu::TextureFormat,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let (width, height) = (descriptor.width, descriptor.height);
+        let adapter_info = adapter.get_info();
 
         // Create device and queue
         let (device, queue) = adapter
             .request_device(&wgpu::DeviceDescriptor {
                 label: None,
-                required_features: wgpu::Features::empty(),
-                required_limits: wgpu::Limits::default(),
+                required_features: descriptor.required_features
+                    | (descriptor.optional_features & adapter.features()),
+                required_limits: descriptor.limits_for(adapter),
                 memory_hints: wgpu::MemoryHints::Performance,
                 trace: wgpu::Trace::Off,
                 experimental_features: wgpu::ExperimentalFeatures::disabled(),
             })
             .await?;
 
-        // Calculate aligned size for each transform (256-byte alignment required)
-        let base_size = std::mem::size_of::<TransformUniform>() as u64;
-        let aligned_transform_size =
-            ((base_size + UNIFORM_ALIGNMENT - 1) / UNIFORM_ALIGNMENT) * UNIFORM_ALIGNMENT;
-
-        // Create buffer large enough for MAX_OBJECTS_PER_PASS transforms
-        let buffer_size = aligned_transform_size * MAX_OBJECTS_PER_PASS as u64;
-
-        let transform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Transform Uniform Buffer"),
-            size: buffer_size,
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-
-        // Initialize first transform to identity
-        let transform_uniform = TransformUniform::identity();
-        queue.write_buffer(
-            &transform_buffer,
-            0,
-            bytemuck::cast_slice(&[transform_uniform]),
-        );
-
-        // Create bind group layout for transform with dynamic offsets enabled
+        // Transforms are one storage array, indexed by instance in the shaders
         let transform_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 label: Some("Transform Bind Group Layout"),
@@ -167,8 +469,8 @@ impl ShapeRenderer {
                     binding: 0,
                     visibility: wgpu::ShaderStages::VERTEX,
                     ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: true, // Enable dynamic offsets
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
                         min_binding_size: std::num::NonZeroU64::new(std::mem::size_of::<
                             TransformUniform,
                         >()
@@ -178,133 +480,318 @@ impl ShapeRenderer {
                 }],
             });
 
-        // Create bind group (bind only one slot, dynamic offset will shift to others)
-        let transform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Transform Bind Group"),
-            layout: &transform_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
-                    buffer: &transform_buffer,
-                    offset: 0,
-                    size: std::num::NonZeroU64::new(aligned_transform_size),
-                }),
-            }],
-        });
-
-        // Create shader module
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Shape Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("shapes.wgsl").into()),
-        });
-
-        // Create pipeline layout
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Shape Pipeline Layout"),
-            bind_group_layouts: &[&transform_bind_group_layout],
-            push_constant_ranges: &[],
-        });
+        let transforms = StorageArray::new(
+            &device,
+            "Transform Storage Buffer",
+            transform_bind_group_layout.clone(),
+            std::mem::size_of::<TransformUniform>() as u64,
+            INITIAL_OBJECTS_PER_PASS,
+        );
 
-        // Create render pipeline
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Shape Render Pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[wgpu::VertexBufferLayout {
-                    array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
-                    step_mode: wgpu::VertexStepMode::Vertex,
-                    attributes: &[
-                        wgpu::VertexAttribute {
-                            offset: 0,
-                            shader_location: 0,
-                            format: wgpu::VertexFormat::Float32x3,
-                        },
-                        wgpu::VertexAttribute {
-                            offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
-                            shader_location: 1,
-                            format: wgpu::VertexFormat::Float32x4,
-                        },
-                    ],
-                }],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: wgpu::TextureFormat::Rgba8Unorm,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: None, // Disable back-face culling for debugging
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-            cache: None,
-        });
+        // Shaders are compiled once; pipelines for other target formats are
+        // built when first drawn to
+        let pipelines = PipelineCache::new();
+        pipelines.register_shader(
+            &device,
+            SHAPE_SHADER,
+            include_str!("shapes.wgsl"),
+            &[&transform_bind_group_layout],
+        );
+        let pipeline = pipelines.get(
+            &device,
+            &PipelineKey::new(SHAPE_SHADER, Vertex::BUFFERS, format),
+        );
 
         Ok(Self {
             width,
             height,
             instance,
+            adapter_info,
+            format,
+            target_format: std::cell::Cell::new(format),
+            stencil_mode: std::cell::Cell::new(None),
+            clip_stack: std::cell::RefCell::new(Vec::new()),
+            deformation: std::cell::RefCell::new(None),
+            glyph_colors: std::cell::RefCell::new(Vec::new()),
+            glyph_offset: std::cell::Cell::new(0),
+            blend_mode: std::cell::Cell::new(BlendMode::Normal),
             device,
             queue,
             pipeline,
-            transform_bind_group,
-            transform_buffer,
-            current_transform_offset: std::cell::Cell::new(0),
-            aligned_transform_size,
-            text_pipeline: None,
+            pipelines,
+            transforms,
+            bound_transforms: std::cell::Cell::new(None),
+            draw_calls: std::cell::Cell::new(0),
             text_atlas: None,
             text_texture: None,
             text_bind_group: None,
+            texture_layout: None,
+            assets: None,
+            image_bind_groups: HashMap::new(),
+            lit: None,
+            materials: None,
+            render_targets: HashMap::new(),
+            resources: ResourceTracker::new(),
         })
     }
 
+    /// Begin a pass on a target of the renderer's own format
     pub fn begin_render_pass<'a>(
         &self,
         encoder: &'a mut wgpu::CommandEncoder,
         output_view: &'a wgpu::TextureView,
         clear_color: Option<wgpu::Color>,
     ) -> wgpu::RenderPass<'a> {
-        let clear_color = clear_color.unwrap_or(wgpu::Color {
-            r: 0.95,
-            g: 0.95,
-            b: 0.95,
-            a: 1.0,
-        });
+        self.begin_render_pass_with_format(encoder, output_view, self.format, clear_color)
+    }
+
+    /// Begin a pass on a target of any color format
+    ///
+    /// Draws recorded until the next pass begins use pipelines built for
+    /// `format`, so one renderer can draw to both a window surface and an
+    /// offscreen texture of a different (e.g. sRGB) format.
+    pub fn begin_render_pass_with_format<'a>(
+        &self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        output_view: &'a wgpu::TextureView,
+        format: wgpu::TextureFormat,
+        clear_color: Option<wgpu::Color>,
+    ) -> wgpu::RenderPass<'a> {
+        self.begin_render_pass_with_load(
+            encoder,
+            output_view,
+            format,
+            wgpu::LoadOp::Clear(clear_color.unwrap_or(DEFAULT_CLEAR_COLOR)),
+        )
+    }
+
+    /// Begin a pass with an explicit load operation
+    ///
+    /// `wgpu::LoadOp::Load` keeps what the target already holds, for passes
+    /// that draw on top of an earlier one.
+    pub fn begin_render_pass_with_load<'a>(
+        &self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        output_view: &'a wgpu::TextureView,
+        format: wgpu::TextureFormat,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) -> wgpu::RenderPass<'a> {
+        self.begin_color_pass(encoder, output_view, format, load, None)
+    }
 
-        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+    /// Like [`Self::begin_render_pass`], with the pass's GPU time measured by
+    /// `timer` (see [`GpuTimer`])
+    pub fn begin_timed_render_pass<'a>(
+        &self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        output_view: &'a wgpu::TextureView,
+        clear_color: Option<wgpu::Color>,
+        timer: &'a GpuTimer,
+    ) -> wgpu::RenderPass<'a> {
+        self.begin_color_pass(
+            encoder,
+            output_view,
+            self.format,
+            wgpu::LoadOp::Clear(clear_color.unwrap_or(DEFAULT_CLEAR_COLOR)),
+            Some(timer.timestamp_writes()),
+        )
+    }
+
+    fn begin_color_pass<'a>(
+        &self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        output_view: &wgpu::TextureView,
+        format: wgpu::TextureFormat,
+        load: wgpu::LoadOp<wgpu::Color>,
+        timestamp_writes: Option<wgpu::RenderPassTimestampWrites<'a>>,
+    ) -> wgpu::RenderPass<'a> {
+        self.target_format.set(format);
+        self.stencil_mode.set(None);
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Shape Render Pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                 view: output_view,
                 resolve_target: None,
                 depth_slice: None,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(clear_color),
+                    load,
                     store: wgpu::StoreOp::Store,
                 },
             })],
             depth_stencil_attachment: None,
             occlusion_query_set: None,
+            timestamp_writes,
+        });
+        self.bind_transforms(&mut render_pass);
+        render_pass
+    }
+
+    /// Begin a pass with a [`STENCIL_FORMAT`] attachment for clipping masks
+    ///
+    /// Pipelines for the pass test the stencil (see [`StencilMode`]).
+    /// Clearing the stencil also forgets the clip stack; with
+    /// `wgpu::LoadOp::Load` the pass continues inside the clips left by the
+    /// previous one.
+    pub fn begin_render_pass_with_stencil<'a>(
+        &self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        output_view: &'a wgpu::TextureView,
+        format: wgpu::TextureFormat,
+        load: wgpu::LoadOp<wgpu::Color>,
+        stencil_view: &'a wgpu::TextureView,
+        stencil_load: wgpu::LoadOp<u32>,
+    ) -> wgpu::RenderPass<'a> {
+        self.target_format.set(format);
+        self.stencil_mode.set(Some(StencilMode::Test));
+        if matches!(stencil_load, wgpu::LoadOp::Clear(_)) {
+            self.clip_stack.borrow_mut().clear();
+        }
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Clipped Shape Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: output_view,
+                resolve_target: None,
+                depth_slice: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: stencil_view,
+                depth_ops: None,
+                stencil_ops: Some(wgpu::Operations {
+                    load: stencil_load,
+                    store: wgpu::StoreOp::Store,
+                }),
+            }),
+            occlusion_query_set: None,
             timestamp_writes: None,
-        })
+        });
+        render_pass.set_stencil_reference(self.clip_depth());
+        self.bind_transforms(&mut render_pass);
+        render_pass
+    }
+
+    /// Bind the transform array to group 0 of `render_pass`
+    ///
+    /// Passes begun by the renderer start with it bound, and `draw_*` calls
+    /// rebind it when the array has grown. Passes begun directly on an
+    /// encoder must call this before drawing.
+    pub fn bind_transforms(&self, render_pass: &mut wgpu::RenderPass) {
+        render_pass.set_bind_group(0, &self.transforms.bind_group(), &[]);
+        self.bound_transforms
+            .set(Some(self.transforms.generation()));
+    }
+
+    /// Rebind the transform array if it has grown since it was last bound
+    pub(crate) fn rebind_grown_transforms(&self, render_pass: &mut wgpu::RenderPass) {
+        if self.bound_transforms.get() != Some(self.transforms.generation()) {
+            self.bind_transforms(render_pass);
+        }
+    }
+
+    /// Stencil use of the current pass's pipelines (`None` without a stencil attachment)
+    pub fn stencil_mode(&self) -> Option<StencilMode> {
+        self.stencil_mode.get()
+    }
+
+    /// Switch the stencil use of pipelines handed out for the current pass
+    ///
+    /// Only meaningful in a pass begun with [`Self::begin_render_pass_with_stencil`].
+    pub fn set_stencil_mode(&self, mode: StencilMode) {
+        if self.stencil_mode.get().is_some() {
+            self.stencil_mode.set(Some(mode));
+        }
+    }
+
+    /// Number of clip masks enclosing what is drawn next (the stencil reference)
+    pub fn clip_depth(&self) -> u32 {
+        self.clip_stack.borrow().len() as u32
+    }
+
+    /// Innermost clip mask node, if any
+    pub fn innermost_clip(&self) -> Option<crate::scene::NodeId> {
+        self.clip_stack.borrow().last().copied()
+    }
+
+    /// Record that `node`'s mask has been written to the stencil
+    pub fn push_clip(&self, node: crate::scene::NodeId) {
+        self.clip_stack.borrow_mut().push(node);
+    }
+
+    /// Forget the innermost clip mask after it has been removed from the stencil
+    pub fn pop_clip(&self) -> Option<crate::scene::NodeId> {
+        self.clip_stack.borrow_mut().pop()
+    }
+
+    /// Deform the vertices of the shapes drawn from now on (`None` stops)
+    pub fn set_deformation(&self, deformation: Option<Deformation>) {
+        *self.deformation.borrow_mut() = deformation;
+    }
+
+    /// Color ranges of glyphs of the text drawn from now on instead of its
+    /// own color (empty stops)
+    ///
+    /// Ranges index the characters of
+    /// [`Renderable::glyph_text`](crate::scene::Renderable::glyph_text) and
+    /// take on the opacity of the text's color.
+    pub fn set_glyph_colors(&self, glyph_colors: Vec<(Range<usize>, Color)>) {
+        *self.glyph_colors.borrow_mut() = glyph_colors;
+    }
+
+    /// Color of the glyph shaped from byte `cluster` of `content`: the last
+    /// glyph color covering it, or `color`
+    fn glyph_color(&self, content: &str, cluster: usize, color: Color) -> [f32; 4] {
+        let glyph_colors = self.glyph_colors.borrow();
+        if glyph_colors.is_empty() {
+            return color.to_f32_array();
+        }
+        let index =
+            self.glyph_offset.get() + content.get(..cluster).map_or(0, |s| s.chars().count());
+        glyph_colors
+            .iter()
+            .rev()
+            .find(|(glyphs, _)| glyphs.contains(&index))
+            .map_or(color, |(_, c)| c.with_opacity(c.a * color.a))
+            .to_f32_array()
+    }
+
+    /// Blend the shapes drawn from now on with `mode`
+    ///
+    /// Applies to the pipelines handed out by [`Self::current_pipeline`],
+    /// [`Self::bind_lit_object`] and [`Self::bind_shader_material`]; text and
+    /// images only follow [`BlendMode::Additive`].
+    pub fn set_blend_mode(&self, mode: BlendMode) {
+        self.blend_mode.set(mode);
+    }
+
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode.get()
+    }
+
+    /// Blend mode of textured draws, which aren't premultiplied
+    fn textured_blend_mode(&self) -> BlendMode {
+        match self.blend_mode.get() {
+            BlendMode::Additive => BlendMode::Additive,
+            _ => BlendMode::Normal,
+        }
+    }
+
+    /// Whether shapes are currently drawn deformed
+    pub fn is_deforming(&self) -> bool {
+        self.deformation.borrow().is_some()
+    }
+
+    /// Run generated vertices through the current deformation before upload
+    fn deform_vertices(&self, vertices: &mut [Vertex]) {
+        if let Some(deformation) = self.deformation.borrow().as_ref() {
+            for vertex in vertices {
+                let [x, y, z] = vertex.position;
+                let moved = deformation.apply(Vector3::new(x, y, z));
+                vertex.position = [moved.x, moved.y, moved.z];
+            }
+        }
     }
 
     pub fn render_circle(&self, circle: &Circle, color: Color, output_view: &wgpu::TextureView) {
@@ -390,14 +877,16 @@ impl ShapeRenderer {
 
         // Set pipeline and bind groups
         render_pass.set_pipeline(&self.pipeline);
-        render_pass.set_bind_group(0, &self.transform_bind_group, &[]);
+        let transforms = self.update_transform(&TransformUniform::identity());
+        render_pass.set_bind_group(0, &self.transforms.bind_group(), &[]);
 
         // Set vertex and index buffers
         render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
         render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
 
         // Draw
-        render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+        self.count_draw_call();
+        render_pass.draw_indexed(0..indices.len() as u32, 0, transforms);
 
         // Drop render_pass to release borrow
         drop(render_pass);
@@ -410,7 +899,7 @@ impl ShapeRenderer {
         &self,
         circle: &Circle,
         color: Color,
-        dynamic_offset: u32,
+        transforms: Range<u32>,
         render_pass: &mut wgpu::RenderPass,
     ) {
         // Create vertices for a circle centered at origin
@@ -446,6 +935,7 @@ impl ShapeRenderer {
             indices.push(i as u16);
             indices.push((i + 1) as u16);
         }
+        self.deform_vertices(&mut vertices);
 
         // Create GPU buffers
         let vertex_buffer = self
@@ -464,15 +954,15 @@ impl ShapeRenderer {
                 usage: wgpu::BufferUsages::INDEX,
             });
 
-        // Set bind group with dynamic offset
-        render_pass.set_bind_group(0, &self.transform_bind_group, &[dynamic_offset]);
+        self.rebind_grown_transforms(render_pass);
 
         // Set vertex and index buffers
         render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
         render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
 
         // Draw
-        render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+        self.count_draw_call();
+        render_pass.draw_indexed(0..indices.len() as u32, 0, transforms);
     }
 
     pub fn draw_rectangle(
@@ -480,37 +970,43 @@ impl ShapeRenderer {
         width: f32,
         height: f32,
         color: Color,
-        dynamic_offset: u32,
+        transforms: Range<u32>,
         render_pass: &mut wgpu::RenderPass,
     ) {
-        let center = [0.0, 0.0, 0.0]; // Position handled by transform uniform
+        // Position handled by transform uniform
         let half_width = width / 2.0;
         let half_height = height / 2.0;
 
         let color_array = color.to_f32_array();
 
-        // Create vertices for a rectangle (two triangles)
-        let vertices = vec![
-            Vertex {
-                position: [center[0] - half_width, center[1] - half_height, center[2]],
-                color: color_array,
-            },
-            Vertex {
-                position: [center[0] + half_width, center[1] - half_height, center[2]],
-                color: color_array,
-            },
-            Vertex {
-                position: [center[0] + half_width, center[1] + half_height, center[2]],
-                color: color_array,
-            },
-            Vertex {
-                position: [center[0] - half_width, center[1] + half_height, center[2]],
-                color: color_array,
-            },
-        ];
-
-        // Create index buffer for two triangles (CCW winding)
-        let indices: Vec<u16> = vec![0, 1, 2, 0, 2, 3];
+        // A grid of quads (two triangles each, CCW winding): a single quad,
+        // or a finer grid when deformed so the rectangle can bend
+        let pieces = if self.is_deforming() {
+            DEFORMED_RECTANGLE_PIECES
+        } else {
+            1
+        };
+        let mut vertices = Vec::with_capacity((pieces + 1) * (pieces + 1));
+        for row in 0..=pieces {
+            let y = (row as f32 / pieces as f32 * 2.0 - 1.0) * half_height;
+            for column in 0..=pieces {
+                let x = (column as f32 / pieces as f32 * 2.0 - 1.0) * half_width;
+                vertices.push(Vertex {
+                    position: [x, y, 0.0],
+                    color: color_array,
+                });
+            }
+        }
+        let mut indices: Vec<u16> = Vec::with_capacity(6 * pieces * pieces);
+        let stride = pieces as u16 + 1;
+        for row in 0..pieces as u16 {
+            for column in 0..pieces as u16 {
+                let corner = row * stride + column;
+                let above = corner + stride;
+                indices.extend([corner, corner + 1, above + 1, corner, above + 1, above]);
+            }
+        }
+        self.deform_vertices(&mut vertices);
 
         // Create GPU buffers
         let vertex_buffer = self
@@ -529,15 +1025,15 @@ impl ShapeRenderer {
                 usage: wgpu::BufferUsages::INDEX,
             });
 
-        // Set bind group with dynamic offset
-        render_pass.set_bind_group(0, &self.transform_bind_group, &[dynamic_offset]);
+        self.rebind_grown_transforms(render_pass);
 
         // Set vertex and index buffers
         render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
         render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
 
         // Draw
-        render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+        self.count_draw_call();
+        render_pass.draw_indexed(0..indices.len() as u32, 0, transforms);
     }
 
     pub fn draw_line(
@@ -546,7 +1042,7 @@ impl ShapeRenderer {
         end: Vector3,
         color: Color,
         thickness: f32,
-        dynamic_offset: u32,
+        transforms: Range<u32>,
         render_pass: &mut wgpu::RenderPass,
     ) {
         let dir = Vector3::new(end.x - start.x, end.y - start.y, 0.0);
@@ -569,44 +1065,35 @@ impl ShapeRenderer {
 
         let color_array = color.to_f32_array();
 
-        // Create vertices for a thick line (rectangle)
-        let vertices = vec![
-            Vertex {
-                position: [
-                    center_x - dir_norm.x * half_length - perp.x * half_thickness,
-                    center_y - dir_norm.y * half_length - perp.y * half_thickness,
-                    0.0,
-                ],
-                color: color_array,
-            },
-            Vertex {
-                position: [
-                    center_x + dir_norm.x * half_length - perp.x * half_thickness,
-                    center_y + dir_norm.y * half_length - perp.y * half_thickness,
-                    0.0,
-                ],
-                color: color_array,
-            },
-            Vertex {
-                position: [
-                    center_x + dir_norm.x * half_length + perp.x * half_thickness,
-                    center_y + dir_norm.y * half_length + perp.y * half_thickness,
-                    0.0,
-                ],
-                color: color_array,
-            },
-            Vertex {
-                position: [
-                    center_x - dir_norm.x * half_length + perp.x * half_thickness,
-                    center_y - dir_norm.y * half_length + perp.y * half_thickness,
-                    0.0,
-                ],
-                color: color_array,
-            },
-        ];
+        // Create vertices for a thick line (a strip of quads, subdivided when
+        // deformed so the line can bend)
+        let pieces = if self.is_deforming() {
+            DEFORMED_LINE_PIECES
+        } else {
+            1
+        };
+        let mut vertices = Vec::with_capacity(2 * (pieces + 1));
+        for i in 0..=pieces {
+            let along = (i as f32 / pieces as f32 * 2.0 - 1.0) * half_length;
+            for side in [-half_thickness, half_thickness] {
+                vertices.push(Vertex {
+                    position: [
+                        center_x + dir_norm.x * along + perp.x * side,
+                        center_y + dir_norm.y * along + perp.y * side,
+                        0.0,
+                    ],
+                    color: color_array,
+                });
+            }
+        }
 
-        // Create index buffer for two triangles
-        let indices: Vec<u16> = vec![0, 1, 2, 0, 2, 3];
+        // Two triangles per quad
+        let mut indices: Vec<u16> = Vec::with_capacity(6 * pieces);
+        for i in 0..pieces as u16 {
+            let (bottom, top) = (2 * i, 2 * i + 1);
+            indices.extend([bottom, bottom + 2, bottom + 3, bottom, bottom + 3, top]);
+        }
+        self.deform_vertices(&mut vertices);
 
         // Create GPU buffers
         let vertex_buffer = self
@@ -625,90 +1112,149 @@ impl ShapeRenderer {
                 usage: wgpu::BufferUsages::INDEX,
             });
 
-        // Set bind group with dynamic offset
-        render_pass.set_bind_group(0, &self.transform_bind_group, &[dynamic_offset]);
+        self.rebind_grown_transforms(render_pass);
 
         // Set vertex and index buffers
         render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
         render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
 
         // Draw
-        render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+        self.count_draw_call();
+        render_pass.draw_indexed(0..indices.len() as u32, 0, transforms);
     }
 
-    pub fn draw_arrow(
+    /// Draw a thick open path through `points`, fading out towards the
+    /// first point by `fade` (0..1)
+    pub fn draw_polyline(
         &self,
-        start: Vector3,
-        end: Vector3,
+        points: &[Vector3],
         color: Color,
         thickness: f32,
-        dynamic_offset: u32,
+        fade: f32,
+        transforms: Range<u32>,
         render_pass: &mut wgpu::RenderPass,
     ) {
-        // First draw the line (shaft)
-        let dir = Vector3::new(end.x - start.x, end.y - start.y, 0.0);
-        let length = (dir.x * dir.x + dir.y * dir.y).sqrt();
-
-        if length < 0.001 {
-            return; // Skip degenerate arrows
+        // Two vertices per point with 16-bit indices; long paths keep their
+        // newest points
+        let points = &points[points.len().saturating_sub(MAX_POLYLINE_POINTS)..];
+        if points.len() < 2 {
+            return;
         }
 
-        let tip_size = 0.05; // 5% of scene size for tip
-
-        // Calculate line end (where tip starts)
-        let line_end = if length > tip_size {
-            Vector3::new(
-                start.x + dir.x * (1.0 - tip_size / length),
-                start.y + dir.y * (1.0 - tip_size / length),
-                start.z,
-            )
-        } else {
-            start // Very short arrow, minimal shaft
+        let half_thickness = thickness / 200.0; // Same units as lines
+        let premultiplied = self.blend_mode().is_premultiplied();
+        let fade = fade.clamp(0.0, 1.0);
+        let last = (points.len() - 1) as f32;
+        let direction = |from: Vector3, to: Vector3| {
+            let (x, y) = (to.x - from.x, to.y - from.y);
+            let length = (x * x + y * y).sqrt();
+            if length < 1e-6 {
+                None
+            } else {
+                Some((x / length, y / length))
+            }
         };
 
-        // Draw the shaft
-        self.draw_line(
-            start,
-            line_end,
-            color,
-            thickness,
-            dynamic_offset,
-            render_pass,
-        );
+        let mut vertices = Vec::with_capacity(points.len() * 2);
+        let mut previous = (1.0, 0.0);
+        for (i, &point) in points.iter().enumerate() {
+            // Offset along the average of the neighbouring segments' normals
+            let incoming = (i > 0).then(|| direction(points[i - 1], point)).flatten();
+            let outgoing = points.get(i + 1).and_then(|&next| direction(point, next));
+            let (dx, dy) = match (incoming, outgoing) {
+                (Some(a), Some(b)) => {
+                    direction(Vector3::zero(), Vector3::new(a.0 + b.0, a.1 + b.1, 0.0)).unwrap_or(b)
+                }
+                (Some(d), None) | (None, Some(d)) => d,
+                (None, None) => previous,
+            };
+            previous = (dx, dy);
+            let perp = (-dy, dx);
+
+            let strength = 1.0 - fade * (1.0 - i as f32 / last);
+            let faded = if premultiplied {
+                Color::rgba(
+                    color.r * strength,
+                    color.g * strength,
+                    color.b * strength,
+                    color.a * strength,
+                )
+            } else {
+                Color::rgba(color.r, color.g, color.b, color.a * strength)
+            };
+            for side in [-half_thickness, half_thickness] {
+                vertices.push(Vertex {
+                    position: [point.x + perp.0 * side, point.y + perp.1 * side, 0.0],
+                    color: faded.to_f32_array(),
+                });
+            }
+        }
 
-        // Draw the triangular tip
-        let dir_norm = Vector3::new(dir.x / length, dir.y / length, 0.0);
-        let perp = Vector3::new(-dir_norm.y, dir_norm.x, 0.0);
-        let tip_half_width = tip_size * 0.5;
+        let mut indices: Vec<u16> = Vec::with_capacity(6 * (points.len() - 1));
+        for i in 0..(points.len() - 1) as u16 {
+            let (bottom, top) = (2 * i, 2 * i + 1);
+            indices.extend([bottom, bottom + 2, bottom + 3, bottom, bottom + 3, top]);
+        }
+        self.deform_vertices(&mut vertices);
 
-        let color_array = color.to_f32_array();
+        let vertex_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Polyline Vertex Buffer"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        let index_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Polyline Index Buffer"),
+                contents: bytemuck::cast_slice(&indices),
+                usage: wgpu::BufferUsages::INDEX,
+            });
 
-        // Tip vertices - triangle pointing to end point
-        let vertices = vec![
-            Vertex {
-                position: [end.x, end.y, end.z],
-                color: color_array,
-            },
-            Vertex {
-                position: [
-                    line_end.x + perp.x * tip_half_width,
-                    line_end.y + perp.y * tip_half_width,
-                    end.z,
-                ],
-                color: color_array,
-            },
-            Vertex {
-                position: [
-                    line_end.x - perp.x * tip_half_width,
-                    line_end.y - perp.y * tip_half_width,
-                    end.z,
-                ],
+        self.rebind_grown_transforms(render_pass);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        self.count_draw_call();
+        render_pass.draw_indexed(0..indices.len() as u32, 0, transforms);
+    }
+
+    pub fn draw_arrow(
+        &self,
+        start: Vector3,
+        end: Vector3,
+        color: Color,
+        thickness: f32,
+        transforms: Range<u32>,
+        render_pass: &mut wgpu::RenderPass,
+    ) {
+        let Some((line_end, tip)) = arrow_geometry(start, end) else {
+            return; // Skip degenerate arrows
+        };
+
+        // Draw the shaft
+        self.draw_line(
+            start,
+            line_end,
+            color,
+            thickness,
+            transforms.clone(),
+            render_pass,
+        );
+
+        // Draw the triangular tip
+        let color_array = color.to_f32_array();
+        let mut vertices: Vec<Vertex> = tip
+            .iter()
+            .map(|corner| Vertex {
+                position: [corner.x, corner.y, corner.z],
                 color: color_array,
-            },
-        ];
+            })
+            .collect();
 
         // Triangle indices
         let indices: Vec<u16> = vec![0, 1, 2];
+        self.deform_vertices(&mut vertices);
 
         // Create GPU buffers
         let vertex_buffer = self
@@ -727,43 +1273,73 @@ impl ShapeRenderer {
                 usage: wgpu::BufferUsages::INDEX,
             });
 
-        // Set bind group with dynamic offset
-        render_pass.set_bind_group(0, &self.transform_bind_group, &[dynamic_offset]);
+        self.rebind_grown_transforms(render_pass);
 
         // Set vertex and index buffers
         render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
         render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
 
         // Draw the tip
-        render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+        self.count_draw_call();
+        render_pass.draw_indexed(0..indices.len() as u32, 0, transforms);
     }
 
     /// Update transform for the next draw call
-    /// Returns the offset to use with set_bind_group()
-    pub fn update_transform(&self, transform: &TransformUniform) -> u32 {
-        // Get current offset
-        let offset_index = self.current_transform_offset.get();
-        let byte_offset = offset_index as u64 * self.aligned_transform_size;
-
-        // Write transform to the appropriate offset
-        self.queue.write_buffer(
-            &self.transform_buffer,
-            byte_offset,
-            bytemuck::cast_slice(&[*transform]),
-        );
-
-        // Increment offset for next object (with wraparound)
-        let next_offset = (offset_index + 1) % MAX_OBJECTS_PER_PASS as u32;
-        self.current_transform_offset.set(next_offset);
+    /// Returns the instance range to pass to `draw_*`
+    ///
+    /// The transform buffer grows when a frame draws more objects than it
+    /// holds, so earlier transforms of the frame are never overwritten.
+    pub fn update_transform(&self, transform: &TransformUniform) -> Range<u32> {
+        self.update_transforms(std::slice::from_ref(transform))
+    }
 
-        // Return the dynamic offset for set_bind_group
-        // NOTE: set_bind_group expects offset in BYTES, not indices
-        offset_index * self.aligned_transform_size as u32
+    /// Upload transforms for one batched draw
+    ///
+    /// Drawing a shape with the returned range draws one instance per
+    /// transform, all in a single call.
+    pub fn update_transforms(&self, transforms: &[TransformUniform]) -> Range<u32> {
+        self.transforms
+            .push(&self.device, &self.queue, bytemuck::cast_slice(transforms))
     }
 
     /// Reset transform offset counter (call at start of each frame)
+    ///
+    /// Also restarts [`Self::draw_call_count`].
     pub fn reset_transform_offset(&self) {
-        self.current_transform_offset.set(0);
+        self.transforms.reset();
+        self.draw_calls.set(0);
+        if let Some(lit) = &self.lit {
+            lit.objects.reset();
+        }
+        if let Some(materials) = &self.materials {
+            materials.reset();
+        }
+    }
+
+    /// Draw calls recorded since [`Self::reset_transform_offset`]
+    pub fn draw_call_count(&self) -> u32 {
+        self.draw_calls.get()
+    }
+
+    pub(crate) fn count_draw_call(&self) {
+        self.draw_calls.set(self.draw_calls.get() + 1);
+    }
+
+    /// Number of per-draw transforms currently allocated
+    pub fn transform_capacity(&self) -> u32 {
+        self.transforms.capacity()
+    }
+
+    /// GPU memory held by the renderer's textures, including those of the
+    /// render graphs it executes
+    pub fn resources(&self) -> &ResourceTracker {
+        &self.resources
+    }
+
+    /// Tracked GPU memory, e.g. to set a budget with
+    /// [`ResourceTracker::set_budget`]
+    pub fn resources_mut(&mut self) -> &mut ResourceTracker {
+        &mut self.resources
     }
 
     pub fn get_device(&self) -> &wgpu::Device {
@@ -774,21 +1350,75 @@ impl ShapeRenderer {
         &self.queue
     }
 
+    /// Shape pipeline for the renderer's own format (see [`Self::format`])
     pub fn get_pipeline(&self) -> &wgpu::RenderPipeline {
         &self.pipeline
     }
 
-    pub fn get_transform_bind_group(&self) -> &wgpu::BindGroup {
-        &self.transform_bind_group
+    /// Shape pipeline for render targets of `format`, built on first use
+    pub fn get_pipeline_for(&self, format: wgpu::TextureFormat) -> wgpu::RenderPipeline {
+        self.pipelines.get(
+            &self.device,
+            &PipelineKey::new(SHAPE_SHADER, Vertex::BUFFERS, format),
+        )
+    }
+
+    /// Shader modules and pipelines built by this renderer
+    ///
+    /// Register custom shaders here to get pipelines for any target format;
+    /// group 0 should be the transform layout of [`Self::get_pipeline`].
+    pub fn pipeline_cache(&self) -> &PipelineCache {
+        &self.pipelines
+    }
+
+    /// Shape pipeline matching the target and stencil use of the current render pass
+    pub fn current_pipeline(&self) -> wgpu::RenderPipeline {
+        self.pipelines.get(
+            &self.device,
+            &PipelineKey::new(SHAPE_SHADER, Vertex::BUFFERS, self.target_format.get())
+                .with_stencil(self.stencil_mode.get())
+                .with_blend_mode(self.blend_mode.get()),
+        )
+    }
+
+    /// Bind group holding the transforms uploaded with [`Self::update_transform`]
+    ///
+    /// Changes when the transform buffer grows, so fetch it after
+    /// `update_transform` rather than holding on to it.
+    pub fn get_transform_bind_group(&self) -> wgpu::BindGroup {
+        self.transforms.bind_group()
     }
 
     /// Initialize text rendering system
     pub fn init_text_rendering(
         &mut self,
         font_size: f32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.init_text_rendering_with_font(crate::text::SystemFonts::sans_serif(), font_size)
+    }
+
+    /// Initialize text rendering with the font at `font_path` (such as
+    /// [`Theme::font_path`](crate::scene::Theme::font_path)), falling back
+    /// to the system fallback fonts
+    pub fn init_text_rendering_with_font(
+        &mut self,
+        font_path: &str,
+        font_size: f32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.init_text_rendering_with_font_data(std::fs::read(font_path)?, font_size)
+    }
+
+    /// Initialize text rendering with a font file already in memory, for
+    /// builds without a filesystem to load fonts from (such as the browser)
+    pub fn init_text_rendering_with_font_data(
+        &mut self,
+        font_data: Vec<u8>,
+        font_size: f32,
     ) -> Result<(), Box<dyn std::error::Error>> {
         // Create glyph atlas
-        let atlas = Arc::new(Mutex::new(GlyphAtlas::from_system_font(font_size)?));
+        let atlas = GlyphAtlas::new(font_data, font_size)?
+            .with_fallback_paths(crate::text::SystemFonts::fallbacks());
+        let atlas = Arc::new(Mutex::new(atlas));
 
         // Get atlas dimensions and data
         let (atlas_width, atlas_height) = {
@@ -797,6 +1427,15 @@ impl ShapeRenderer {
         };
 
         // Create texture for glyph atlas
+        self.resources.try_allocate(
+            "glyph atlas",
+            ResourceKind::Texture,
+            ResourceTracker::texture_size(
+                atlas_width,
+                atlas_height,
+                wgpu::TextureFormat::Rgba8Unorm,
+            ),
+        )?;
         let texture = self.device.create_texture(&wgpu::TextureDescriptor {
             label: Some("Glyph Atlas Texture"),
             size: wgpu::Extent3d {
@@ -812,10 +1451,65 @@ impl ShapeRenderer {
             view_formats: &[],
         });
 
-        // Create texture view and sampler
+        let text_bind_group = self.texture_bind_group(&texture, "Text Bind Group");
+
+        // Store everything
+        self.text_atlas = Some(atlas);
+        self.text_texture = Some(texture);
+        self.text_bind_group = Some(text_bind_group);
+
+        Ok(())
+    }
+
+    /// Bind group layout of textured draws (the glyph atlas and images),
+    /// registering the textured pipeline on first use
+    fn texture_layout(&mut self) -> wgpu::BindGroupLayout {
+        if let Some(layout) = &self.texture_layout {
+            return layout.clone();
+        }
+
+        let layout = self
+            .device
+            .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Texture Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+
+        // Group 0 is the transform layout shared with the shape pipeline
+        let transform_bind_group_layout = self.pipeline.get_bind_group_layout(0);
+        self.pipelines.register_shader(
+            &self.device,
+            TEXT_SHADER,
+            include_str!("text.wgsl"),
+            &[&transform_bind_group_layout, &layout],
+        );
+        self.texture_layout = Some(layout.clone());
+        layout
+    }
+
+    /// Bind group sampling `texture` with linear filtering, for [`TEXT_SHADER`]
+    fn texture_bind_group(&mut self, texture: &wgpu::Texture, label: &str) -> wgpu::BindGroup {
+        let layout = self.texture_layout();
         let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
         let sampler = self.device.create_sampler(&wgpu::SamplerDescriptor {
-            label: Some("Glyph Atlas Sampler"),
+            label: Some(label),
             address_mode_u: wgpu::AddressMode::ClampToEdge,
             address_mode_v: wgpu::AddressMode::ClampToEdge,
             address_mode_w: wgpu::AddressMode::ClampToEdge,
@@ -824,36 +1518,9 @@ impl ShapeRenderer {
             mipmap_filter: wgpu::FilterMode::Nearest,
             ..Default::default()
         });
-
-        // Create bind group layout for text
-        let text_bind_group_layout =
-            self.device
-                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                    label: Some("Text Bind Group Layout"),
-                    entries: &[
-                        wgpu::BindGroupLayoutEntry {
-                            binding: 0,
-                            visibility: wgpu::ShaderStages::FRAGMENT,
-                            ty: wgpu::BindingType::Texture {
-                                sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                                view_dimension: wgpu::TextureViewDimension::D2,
-                                multisampled: false,
-                            },
-                            count: None,
-                        },
-                        wgpu::BindGroupLayoutEntry {
-                            binding: 1,
-                            visibility: wgpu::ShaderStages::FRAGMENT,
-                            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                            count: None,
-                        },
-                    ],
-                });
-
-        // Create bind group
-        let text_bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Text Bind Group"),
-            layout: &text_bind_group_layout,
+        self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(label),
+            layout: &layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
@@ -864,110 +1531,259 @@ impl ShapeRenderer {
                     resource: wgpu::BindingResource::Sampler(&sampler),
                 },
             ],
-        });
+        })
+    }
 
-        // Load text shader
-        let text_shader = self
-            .device
-            .create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: Some("Text Shader"),
-                source: wgpu::ShaderSource::Wgsl(include_str!("text.wgsl").into()),
-            });
+    /// Share `assets` with this renderer, so it can draw
+    /// [`Renderable::Image`](crate::scene::Renderable::Image) and
+    /// [`Renderable::Svg`](crate::scene::Renderable::Svg) nodes
+    ///
+    /// Each image is uploaded to the GPU once, the first time it is drawn.
+    pub fn set_asset_server(&mut self, assets: AssetServer) {
+        self.assets = Some(assets);
+        self.image_bind_groups.clear();
+    }
 
-        // Get transform bind group layout from existing pipeline
-        let transform_bind_group_layout = self.pipeline.get_bind_group_layout(0);
+    pub fn asset_server(&self) -> Option<&AssetServer> {
+        self.assets.as_ref()
+    }
+
+    /// Whether this renderer runs on a hardware GPU or the software adapter
+    pub fn backend(&self) -> RendererBackend {
+        RendererBackend::of_adapter(&self.adapter_info)
+    }
+
+    /// Color format of the render targets this renderer draws to by default
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    /// Color format of the current render pass target
+    pub fn target_format(&self) -> wgpu::TextureFormat {
+        self.target_format.get()
+    }
+
+    /// Name, driver and backend API of the adapter in use
+    pub fn adapter_info(&self) -> &wgpu::AdapterInfo {
+        &self.adapter_info
+    }
+
+    pub fn get_instance(&self) -> &wgpu::Instance {
+        &self.instance
+    }
+
+    /// Initialize the lit pipeline used for scenes with lights
+    pub fn init_lighting(&mut self) {
+        let lighting_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Lighting Uniform Buffer"),
+            size: std::mem::size_of::<LightingUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        self.queue.write_buffer(
+            &lighting_buffer,
+            0,
+            bytemuck::cast_slice(&[LightingUniform::new(&[], Color::WHITE, Vector3::zero())]),
+        );
 
-        // Create text pipeline layout
-        let text_pipeline_layout =
+        let object_size = std::mem::size_of::<LitObjectUniform>() as u64;
+        let lighting_bind_group_layout =
             self.device
-                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                    label: Some("Text Pipeline Layout"),
-                    bind_group_layouts: &[&transform_bind_group_layout, &text_bind_group_layout],
-                    push_constant_ranges: &[],
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Lighting Bind Group Layout"),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    }],
                 });
 
-        // Create text rendering pipeline
-        let text_pipeline = self
-            .device
-            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("Text Render Pipeline"),
-                layout: Some(&text_pipeline_layout),
-                vertex: wgpu::VertexState {
-                    module: &text_shader,
-                    entry_point: Some("vs_main"),
-                    buffers: &[wgpu::VertexBufferLayout {
-                        array_stride: std::mem::size_of::<TextVertex>() as wgpu::BufferAddress,
-                        step_mode: wgpu::VertexStepMode::Vertex,
-                        attributes: &[
-                            // position
-                            wgpu::VertexAttribute {
-                                offset: 0,
-                                shader_location: 0,
-                                format: wgpu::VertexFormat::Float32x3,
-                            },
-                            // uv
-                            wgpu::VertexAttribute {
-                                offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
-                                shader_location: 1,
-                                format: wgpu::VertexFormat::Float32x2,
-                            },
-                            // color
-                            wgpu::VertexAttribute {
-                                offset: std::mem::size_of::<[f32; 5]>() as wgpu::BufferAddress,
-                                shader_location: 2,
-                                format: wgpu::VertexFormat::Float32x4,
-                            },
-                        ],
+        let object_bind_group_layout =
+            self.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Lit Object Bind Group Layout"),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: std::num::NonZeroU64::new(object_size),
+                        },
+                        count: None,
                     }],
-                    compilation_options: wgpu::PipelineCompilationOptions::default(),
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &text_shader,
-                    entry_point: Some("fs_main"),
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: wgpu::TextureFormat::Rgba8Unorm,
-                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                    compilation_options: wgpu::PipelineCompilationOptions::default(),
-                }),
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: None,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    unclipped_depth: false,
-                    conservative: false,
-                },
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                multiview: None,
-                cache: None,
-            });
+                });
 
-        // Store everything
-        self.text_pipeline = Some(text_pipeline);
-        self.text_atlas = Some(atlas);
-        self.text_texture = Some(texture);
-        self.text_bind_group = Some(text_bind_group);
+        let lighting_bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Lighting Bind Group"),
+            layout: &lighting_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: lighting_buffer.as_entire_binding(),
+            }],
+        });
 
-        Ok(())
+        // Group 0 is shared with the shape pipeline so draw_* can bind transforms
+        let transform_bind_group_layout = self.pipeline.get_bind_group_layout(0);
+        self.pipelines.register_shader(
+            &self.device,
+            LIT_SHADER,
+            include_str!("lit.wgsl"),
+            &[
+                &transform_bind_group_layout,
+                &lighting_bind_group_layout,
+                &object_bind_group_layout,
+            ],
+        );
+
+        let objects = StorageArray::new(
+            &self.device,
+            "Lit Object Storage Buffer",
+            object_bind_group_layout,
+            object_size,
+            INITIAL_OBJECTS_PER_PASS,
+        );
+
+        self.lit = Some(LitResources {
+            lighting_buffer,
+            lighting_bind_group,
+            objects,
+        });
     }
 
-    pub fn get_instance(&self) -> &wgpu::Instance {
-        &self.instance
+    /// Whether `init_lighting` has been called
+    pub fn has_lighting(&self) -> bool {
+        self.lit.is_some()
+    }
+
+    /// Upload the scene lights for this frame
+    pub fn set_lighting(&self, lights: &[Light], ambient: Color, eye: Vector3) {
+        if let Some(lit) = &self.lit {
+            self.queue.write_buffer(
+                &lit.lighting_buffer,
+                0,
+                bytemuck::cast_slice(&[LightingUniform::new(lights, ambient, eye)]),
+            );
+        }
+    }
+
+    /// Switch the render pass to the lit pipeline for the next shape draw.
+    ///
+    /// `model` is the node's row-major world matrix and `transforms` the
+    /// range from [`Self::update_transform`] the shape is drawn with. Any
+    /// `draw_*` shape call that follows is shaded with the scene lights.
+    /// Returns `false` (leaving the pipeline untouched) if lighting has not
+    /// been initialized.
+    pub fn bind_lit_object(
+        &self,
+        model: &Matrix4,
+        material: &Material,
+        transforms: Range<u32>,
+        render_pass: &mut wgpu::RenderPass,
+    ) -> bool {
+        let Some(lit) = &self.lit else {
+            return false;
+        };
+
+        let object = LitObjectUniform::new(model, material);
+        for index in transforms {
+            lit.objects.write(
+                &self.device,
+                &self.queue,
+                index,
+                bytemuck::cast_slice(&[object]),
+            );
+        }
+
+        let key = PipelineKey::new(LIT_SHADER, Vertex::BUFFERS, self.target_format.get())
+            .with_stencil(self.stencil_mode.get())
+            .with_blend_mode(self.blend_mode.get());
+        render_pass.set_pipeline(&self.pipelines.get(&self.device, &key));
+        render_pass.set_bind_group(1, &lit.lighting_bind_group, &[]);
+        render_pass.set_bind_group(2, &lit.objects.bind_group(), &[]);
+        true
+    }
+
+    /// Switch the render pass to `material`'s shader for the next shape draw
+    ///
+    /// Compiles the material's snippet the first time its name is drawn.
+    /// `transforms` is the range from [`Self::update_transform`] the shape is
+    /// drawn with; any `draw_*` shape call that follows is shaded by the
+    /// material instead of its flat color.
+    pub fn bind_shader_material(
+        &mut self,
+        material: &ShaderMaterial,
+        transforms: Range<u32>,
+        render_pass: &mut wgpu::RenderPass,
+    ) {
+        let object_size = std::mem::size_of::<MaterialObjectUniform>() as u64;
+        let materials = self.materials.get_or_insert_with(|| {
+            let layout = self
+                .device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Shader Material Bind Group Layout"),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: std::num::NonZeroU64::new(object_size),
+                        },
+                        count: None,
+                    }],
+                });
+            StorageArray::new(
+                &self.device,
+                "Shader Material Storage Buffer",
+                layout,
+                object_size,
+                INITIAL_OBJECTS_PER_PASS,
+            )
+        });
+
+        if !self.pipelines.has_shader(material.name) {
+            let source = format!(
+                "{}\n{}",
+                include_str!("shader_material.wgsl"),
+                material.fragment
+            );
+            let transform_bind_group_layout = self.pipeline.get_bind_group_layout(0);
+            self.pipelines.register_shader(
+                &self.device,
+                material.name,
+                &source,
+                &[&transform_bind_group_layout, materials.layout()],
+            );
+        }
+
+        let object = MaterialObjectUniform::new(material);
+        for index in transforms {
+            materials.write(
+                &self.device,
+                &self.queue,
+                index,
+                bytemuck::cast_slice(&[object]),
+            );
+        }
+
+        let key = PipelineKey::new(material.name, Vertex::BUFFERS, self.target_format.get())
+            .with_stencil(self.stencil_mode.get())
+            .with_blend_mode(self.blend_mode.get());
+        render_pass.set_pipeline(&self.pipelines.get(&self.device, &key));
+        render_pass.set_bind_group(1, &materials.bind_group(), &[]);
     }
 
     pub fn draw_polygon(
         &self,
         vertices: &[Vector3],
         color: Color,
-        dynamic_offset: u32,
+        transforms: Range<u32>,
         render_pass: &mut wgpu::RenderPass,
     ) {
         if vertices.len() < 3 {
@@ -977,7 +1793,7 @@ impl ShapeRenderer {
         let color_array = color.to_f32_array();
 
         // Create vertex buffer from polygon vertices
-        let gpu_vertices: Vec<Vertex> = vertices
+        let mut gpu_vertices: Vec<Vertex> = vertices
             .iter()
             .map(|v| Vertex {
                 position: [v.x, v.y, v.z],
@@ -992,6 +1808,7 @@ impl ShapeRenderer {
             indices.push(i as u16);
             indices.push((i + 1) as u16);
         }
+        self.deform_vertices(&mut gpu_vertices);
 
         // Create GPU buffers
         let vertex_buffer = self
@@ -1010,15 +1827,53 @@ impl ShapeRenderer {
                 usage: wgpu::BufferUsages::INDEX,
             });
 
-        // Set bind group with dynamic offset
-        render_pass.set_bind_group(0, &self.transform_bind_group, &[dynamic_offset]);
+        self.rebind_grown_transforms(render_pass);
 
         // Set vertex and index buffers
         render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
         render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
 
         // Draw
-        render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+        self.count_draw_call();
+        render_pass.draw_indexed(0..indices.len() as u32, 0, transforms);
+    }
+
+    /// Smallest box holding the origin and what [`Self::draw_text`] draws
+    /// for `content`, as `(min, max)` corners in the text node's local units
+    ///
+    /// Without text rendering initialized this is the placeholder rectangle.
+    pub fn text_bounds(&self, content: &str, font_size: f32) -> ((f32, f32), (f32, f32)) {
+        let fallback = || {
+            let half_width = 0.3 * font_size / 1000.0 * content.len() as f32;
+            let half_height = font_size / 2000.0;
+            ((-half_width, -half_height), (half_width, half_height))
+        };
+        let Some(text_atlas) = &self.text_atlas else {
+            return fallback();
+        };
+        let Ok(mut atlas_guard) = text_atlas.lock() else {
+            return fallback();
+        };
+        let Ok(shaped) = atlas_guard.shape(content) else {
+            return fallback();
+        };
+        let (mut vertices, mut indices) = (Vec::new(), Vec::new());
+        push_glyph_quads(
+            &atlas_guard,
+            &shaped.glyphs,
+            (0.0, 0.0),
+            font_size / 1000.0,
+            |_| [0.0; 4],
+            &mut vertices,
+            &mut indices,
+        );
+        vertices.iter().fold(
+            ((0.0f32, 0.0f32), (0.0f32, 0.0f32)),
+            |((min_x, min_y), (max_x, max_y)), vertex| {
+                let [x, y, _] = vertex.position;
+                ((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y)))
+            },
+        )
     }
 
     /// Draw text using glyph atlas
@@ -1027,33 +1882,206 @@ impl ShapeRenderer {
         content: &str,
         font_size: f32,
         color: Color,
-        dynamic_offset: u32,
+        transforms: Range<u32>,
         render_pass: &mut wgpu::RenderPass,
     ) {
         // Check if text rendering is initialized
-        let (text_pipeline, text_atlas, text_bind_group) =
-            match (&self.text_pipeline, &self.text_atlas, &self.text_bind_group) {
-                (Some(pipeline), Some(atlas), Some(bind_group)) => (pipeline, atlas, bind_group),
-                _ => {
-                    // Fallback to rectangle if not initialized
-                    let char_width = 0.6 * font_size / 1000.0;
-                    let width = char_width * content.len() as f32;
-                    let height = font_size / 1000.0;
-                    self.draw_rectangle(width, height, color, dynamic_offset, render_pass);
+        let Some(text_atlas) = &self.text_atlas else {
+            // Fallback to rectangle if not initialized
+            let char_width = 0.6 * font_size / 1000.0;
+            let width = char_width * content.len() as f32;
+            let height = font_size / 1000.0;
+            self.draw_rectangle(width, height, color, transforms, render_pass);
+            return;
+        };
+
+        // Lock atlas, shape the text and rasterize its glyphs
+        let mut atlas_guard = text_atlas.lock().unwrap();
+        let shaped = match atlas_guard.shape(content) {
+            Ok(shaped) => shaped,
+            Err(e) => {
+                eprintln!("Failed to rasterize text: {}", e);
+                return;
+            }
+        };
+        self.upload_text_atlas(&atlas_guard);
+
+        // Build vertices for each glyph
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let scale = font_size / 1000.0; // Normalize to screen space
+        push_glyph_quads(
+            &atlas_guard,
+            &shaped.glyphs,
+            (0.0, 0.0),
+            scale,
+            |glyph| self.glyph_color(content, glyph.cluster, color),
+            &mut vertices,
+            &mut indices,
+        );
+        drop(atlas_guard);
+
+        self.submit_text(&mut vertices, &indices, transforms, render_pass);
+    }
+
+    /// Draw styled spans along one baseline
+    ///
+    /// `color` is used for spans without a color of their own; span colors
+    /// take on its opacity. Bold spans are drawn twice, slightly offset.
+    pub fn draw_rich_text(
+        &mut self,
+        text: &RichText,
+        font_size: f32,
+        color: Color,
+        transforms: Range<u32>,
+        render_pass: &mut wgpu::RenderPass,
+    ) {
+        let Some(text_atlas) = &self.text_atlas else {
+            self.draw_text(
+                &text.plain_text(),
+                font_size,
+                color,
+                transforms,
+                render_pass,
+            );
+            return;
+        };
+
+        let Ok(mut atlas_guard) = text_atlas.lock() else {
+            return;
+        };
+        let em = atlas_guard.font_size();
+        let runs = text.layout(em, |span| {
+            atlas_guard.measure_text(span).unwrap_or_default()
+        });
+        let mut shaped_runs = Vec::with_capacity(runs.len());
+        for run in &runs {
+            match atlas_guard.shape(&run.text) {
+                Ok(shaped) => shaped_runs.push(shaped),
+                Err(e) => {
+                    eprintln!("Failed to rasterize text: {e}");
                     return;
                 }
+            }
+        }
+        self.upload_text_atlas(&atlas_guard);
+
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let scale = font_size / 1000.0;
+        let first_glyph = self.glyph_offset.get();
+        for (run, shaped) in runs.iter().zip(&shaped_runs) {
+            let run_color = run.color.map_or(color, |c| c.with_opacity(c.a * color.a));
+            let origin = (run.x * scale, run.y * scale);
+            let passes: &[f32] = if run.bold {
+                &[0.0, BOLD_OFFSET]
+            } else {
+                &[0.0]
             };
+            for offset in passes {
+                push_glyph_quads(
+                    &atlas_guard,
+                    &shaped.glyphs,
+                    (origin.0 + offset * em * run.scale * scale, origin.1),
+                    scale * run.scale,
+                    |glyph| self.glyph_color(&run.text, glyph.cluster, run_color),
+                    &mut vertices,
+                    &mut indices,
+                );
+            }
+            self.glyph_offset
+                .set(self.glyph_offset.get() + run.text.chars().count());
+        }
+        self.glyph_offset.set(first_glyph);
+        drop(atlas_guard);
 
-        // Lock atlas and rasterize all glyphs
-        let mut atlas_guard = text_atlas.lock().unwrap();
-        if let Err(e) = atlas_guard.rasterize_string(content) {
-            eprintln!("Failed to rasterize text: {}", e);
+        self.submit_text(&mut vertices, &indices, transforms, render_pass);
+    }
+
+    /// Draw text with each glyph placed and turned along a path
+    pub fn draw_text_on_path(
+        &mut self,
+        content: &str,
+        path: &TextPath,
+        font_size: f32,
+        color: Color,
+        transforms: Range<u32>,
+        render_pass: &mut wgpu::RenderPass,
+    ) {
+        let Some(text_atlas) = &self.text_atlas else {
+            self.draw_text(content, font_size, color, transforms, render_pass);
+            return;
+        };
+
+        let Ok(mut atlas_guard) = text_atlas.lock() else {
             return;
+        };
+        let shaped = match atlas_guard.shape(content) {
+            Ok(shaped) => shaped,
+            Err(e) => {
+                eprintln!("Failed to rasterize text: {e}");
+                return;
+            }
+        };
+        self.upload_text_atlas(&atlas_guard);
+
+        let scale = font_size / 1000.0;
+        let advances: Vec<f32> = shaped
+            .glyphs
+            .iter()
+            .map(|glyph| glyph.advance * scale)
+            .collect();
+        let placed = path.layout(&advances);
+
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        // Pen position of each glyph along the unbent line
+        let pens: Vec<f32> = shaped
+            .glyphs
+            .iter()
+            .scan(0.0, |pen, glyph| {
+                let start = *pen;
+                *pen += glyph.advance;
+                Some(start)
+            })
+            .collect();
+        for glyph in placed {
+            let first = vertices.len();
+            // Build the glyph around the middle of its baseline, then turn
+            // it to the path's direction and move it onto the path
+            let shaped_glyph = shaped.glyphs[glyph.index];
+            let local = ShapedGlyph {
+                x: shaped_glyph.x - pens[glyph.index],
+                ..shaped_glyph
+            };
+            push_glyph_quads(
+                &atlas_guard,
+                &[local],
+                (-glyph.advance / 2.0, 0.0),
+                scale,
+                |glyph| self.glyph_color(content, glyph.cluster, color),
+                &mut vertices,
+                &mut indices,
+            );
+            let (sin, cos) = glyph.angle.sin_cos();
+            for vertex in &mut vertices[first..] {
+                let [x, y, z] = vertex.position;
+                vertex.position = [
+                    glyph.position.x + x * cos - y * sin,
+                    glyph.position.y + x * sin + y * cos,
+                    glyph.position.z + z,
+                ];
+            }
         }
+        drop(atlas_guard);
+
+        self.submit_text(&mut vertices, &indices, transforms, render_pass);
+    }
 
-        // Update texture with atlas data
+    /// Copy the glyph atlas into its texture after new glyphs were rasterized
+    fn upload_text_atlas(&self, atlas: &GlyphAtlas) {
         if let Some(texture) = &self.text_texture {
-            let (atlas_width, atlas_height) = atlas_guard.atlas_dimensions();
+            let (atlas_width, atlas_height) = atlas.atlas_dimensions();
             self.queue.write_texture(
                 wgpu::TexelCopyTextureInfo {
                     texture,
@@ -1061,7 +2089,7 @@ impl ShapeRenderer {
                     origin: wgpu::Origin3d::ZERO,
                     aspect: wgpu::TextureAspect::All,
                 },
-                atlas_guard.atlas_data(),
+                atlas.atlas_data(),
                 wgpu::TexelCopyBufferLayout {
                     offset: 0,
                     bytes_per_row: Some(atlas_width * 4),
@@ -1074,69 +2102,38 @@ impl ShapeRenderer {
                 },
             );
         }
+    }
 
-        // Build vertices for each glyph
-        let mut vertices = Vec::new();
-        let mut indices = Vec::new();
-        let mut cursor_x = 0.0f32;
-        let color_array = color.to_f32_array();
-
-        let scale = font_size / 1000.0; // Normalize to screen space
-
-        for c in content.chars() {
-            if let Some(glyph) = atlas_guard.get_glyph(c) {
-                if glyph.width > 0 && glyph.height > 0 {
-                    let glyph_width = glyph.width as f32 * scale;
-                    let glyph_height = glyph.height as f32 * scale;
-                    let bearing_x = glyph.bearing_x * scale;
-                    let bearing_y = glyph.bearing_y * scale;
-
-                    let x0 = cursor_x + bearing_x;
-                    let y0 = -bearing_y;
-                    let x1 = x0 + glyph_width;
-                    let y1 = y0 + glyph_height;
-
-                    let base_idx = vertices.len() as u16;
-
-                    // Create quad for this glyph
-                    vertices.push(TextVertex {
-                        position: [x0, y0, 0.0],
-                        uv: [glyph.uv.0, glyph.uv.1],
-                        color: color_array,
-                    });
-                    vertices.push(TextVertex {
-                        position: [x1, y0, 0.0],
-                        uv: [glyph.uv.2, glyph.uv.1],
-                        color: color_array,
-                    });
-                    vertices.push(TextVertex {
-                        position: [x1, y1, 0.0],
-                        uv: [glyph.uv.2, glyph.uv.3],
-                        color: color_array,
-                    });
-                    vertices.push(TextVertex {
-                        position: [x0, y1, 0.0],
-                        uv: [glyph.uv.0, glyph.uv.3],
-                        color: color_array,
-                    });
-
-                    // Two triangles for the quad
-                    indices.extend_from_slice(&[
-                        base_idx,
-                        base_idx + 1,
-                        base_idx + 2,
-                        base_idx,
-                        base_idx + 2,
-                        base_idx + 3,
-                    ]);
-                }
-
-                cursor_x += glyph.advance * scale;
+    /// Draw glyph quads with the text pipeline, moving their corners
+    /// through the current deformation
+    fn submit_text(
+        &self,
+        vertices: &mut [TextVertex],
+        indices: &[u16],
+        transforms: Range<u32>,
+        render_pass: &mut wgpu::RenderPass,
+    ) {
+        if let Some(deformation) = self.deformation.borrow().as_ref() {
+            for vertex in vertices.iter_mut() {
+                let [x, y, z] = vertex.position;
+                let moved = deformation.apply(Vector3::new(x, y, z));
+                vertex.position = [moved.x, moved.y, moved.z];
             }
         }
+        if let Some(text_bind_group) = &self.text_bind_group {
+            self.submit_textured(vertices, indices, text_bind_group, transforms, render_pass);
+        }
+    }
 
-        drop(atlas_guard);
-
+    /// Draw textured triangles with the text pipeline, sampling `bind_group`
+    fn submit_textured(
+        &self,
+        vertices: &[TextVertex],
+        indices: &[u16],
+        bind_group: &wgpu::BindGroup,
+        transforms: Range<u32>,
+        render_pass: &mut wgpu::RenderPass,
+    ) {
         if vertices.is_empty() {
             return;
         }
@@ -1146,7 +2143,7 @@ impl ShapeRenderer {
             .device
             .create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: Some("Text Vertex Buffer"),
-                contents: bytemuck::cast_slice(&vertices),
+                contents: bytemuck::cast_slice(vertices),
                 usage: wgpu::BufferUsages::VERTEX,
             });
 
@@ -1154,17 +2151,229 @@ impl ShapeRenderer {
             .device
             .create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: Some("Text Index Buffer"),
-                contents: bytemuck::cast_slice(&indices),
+                contents: bytemuck::cast_slice(indices),
                 usage: wgpu::BufferUsages::INDEX,
             });
 
         // Render text
-        render_pass.set_pipeline(text_pipeline);
-        render_pass.set_bind_group(0, &self.transform_bind_group, &[dynamic_offset]);
-        render_pass.set_bind_group(1, text_bind_group, &[]);
+        let key = PipelineKey::new(TEXT_SHADER, TextVertex::BUFFERS, self.target_format.get())
+            .with_stencil(self.stencil_mode.get())
+            .with_blend_mode(self.textured_blend_mode());
+        render_pass.set_pipeline(&self.pipelines.get(&self.device, &key));
+        self.rebind_grown_transforms(render_pass);
+        render_pass.set_bind_group(1, bind_group, &[]);
         render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
         render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-        render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+        self.count_draw_call();
+        render_pass.draw_indexed(0..indices.len() as u32, 0, transforms);
+    }
+
+    /// Draw an image asset stretched over `width` x `height`, centered on the
+    /// origin and multiplied by `color`
+    ///
+    /// Draws nothing without an asset server or while the image is loading.
+    pub fn draw_image(
+        &mut self,
+        asset: AssetHandle,
+        width: f32,
+        height: f32,
+        color: Color,
+        transforms: Range<u32>,
+        render_pass: &mut wgpu::RenderPass,
+    ) {
+        let Some(bind_group) = self.image_bind_group(asset) else {
+            return;
+        };
+
+        let (x, y) = (width / 2.0, height / 2.0);
+        let color = color.to_f32_array();
+        let corner = |position: [f32; 2], uv: [f32; 2]| TextVertex {
+            position: [position[0], position[1], 0.0],
+            uv,
+            color,
+        };
+        let vertices = [
+            corner([-x, y], [0.0, 0.0]),
+            corner([x, y], [1.0, 0.0]),
+            corner([x, -y], [1.0, 1.0]),
+            corner([-x, -y], [0.0, 1.0]),
+        ];
+        self.submit_textured(
+            &vertices,
+            &[0, 1, 2, 0, 2, 3],
+            &bind_group,
+            transforms,
+            render_pass,
+        );
+    }
+
+    /// Begin a pass drawing into the texture of `target`, cleared to its
+    /// background
+    ///
+    /// Image nodes keep showing the target's previous frame until
+    /// [`Self::present_render_target`] is called after the pass ends.
+    pub fn begin_render_target_pass<'a>(
+        &mut self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        target: &RenderTarget,
+    ) -> wgpu::RenderPass<'a> {
+        let textures = self.target_textures(target);
+        let view = textures.views[1 - textures.front].clone();
+        self.begin_color_pass(
+            encoder,
+            &view,
+            self.format,
+            wgpu::LoadOp::Clear(graph::to_wgpu_color(target.background)),
+            None,
+        )
+    }
+
+    /// Show the frame just drawn into the target with `handle` on the image
+    /// nodes displaying it
+    pub fn present_render_target(&mut self, handle: AssetHandle) {
+        if let Some(textures) = self.render_targets.get_mut(&handle) {
+            textures.front = 1 - textures.front;
+            self.image_bind_groups
+                .insert(handle, textures.bind_groups[textures.front].clone());
+        }
+    }
+
+    /// The textures of `target`, (re)created when its size changes
+    fn target_textures(&mut self, target: &RenderTarget) -> &TargetTextures {
+        let size = (target.width, target.height);
+        let stale = self
+            .render_targets
+            .get(&target.handle)
+            .is_none_or(|textures| textures.size != size);
+        if stale {
+            // Render targets have to exist, so they are only warned about
+            self.resources.allocate(
+                &format!("render target {}", target.handle.0),
+                ResourceKind::Texture,
+                2 * ResourceTracker::texture_size(size.0, size.1, self.format),
+            );
+            let textures: [wgpu::Texture; 2] = std::array::from_fn(|_| {
+                self.device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some("Render Target Texture"),
+                    size: wgpu::Extent3d {
+                        width: size.0,
+                        height: size.1,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: self.format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                        | wgpu::TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                })
+            });
+            let bind_groups = [
+                self.texture_bind_group(&textures[0], "Render Target Bind Group"),
+                self.texture_bind_group(&textures[1], "Render Target Bind Group"),
+            ];
+            let views = textures
+                .each_ref()
+                .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()));
+            // Images show nothing until the first frame is drawn
+            self.image_bind_groups.remove(&target.handle);
+            self.render_targets.insert(
+                target.handle,
+                TargetTextures {
+                    size,
+                    views,
+                    bind_groups,
+                    front: 0,
+                },
+            );
+        }
+        &self.render_targets[&target.handle]
+    }
+
+    /// The bind group of an image asset, uploading it on first use
+    fn image_bind_group(&mut self, asset: AssetHandle) -> Option<wgpu::BindGroup> {
+        if let Some(bind_group) = self.image_bind_groups.get(&asset) {
+            return Some(bind_group.clone());
+        }
+        let image = self.assets.as_ref()?.image(asset)?;
+
+        let bytes = ResourceTracker::texture_size(
+            image.width,
+            image.height,
+            wgpu::TextureFormat::Rgba8Unorm,
+        );
+        let label = format!("image {}", asset.0);
+        if let Err(e) = self
+            .resources
+            .try_allocate(&label, ResourceKind::Texture, bytes)
+        {
+            eprintln!("Skipping image: {e}");
+            return None;
+        }
+        let size = wgpu::Extent3d {
+            width: image.width,
+            height: image.height,
+            depth_or_array_layers: 1,
+        };
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Image Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        self.queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &image.pixels,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(image.width * 4),
+                rows_per_image: Some(image.height),
+            },
+            size,
+        );
+
+        let bind_group = self.texture_bind_group(&texture, "Image Bind Group");
+        self.image_bind_groups.insert(asset, bind_group.clone());
+        Some(bind_group)
+    }
+
+    /// Draw an SVG asset's shapes fitted into `width` x `height`, centered on
+    /// the origin, with their fills multiplied by `color`
+    ///
+    /// Shapes are fan-filled like [`ShapeRenderer::draw_polygon`], so concave
+    /// outlines may fill outside their edges. Draws nothing without an asset
+    /// server or while the document is loading.
+    pub fn draw_svg(
+        &self,
+        asset: AssetHandle,
+        width: f32,
+        height: f32,
+        color: Color,
+        transforms: Range<u32>,
+        render_pass: &mut wgpu::RenderPass,
+    ) {
+        let Some(vector) = self.assets.as_ref().and_then(|assets| assets.vector(asset)) else {
+            return;
+        };
+        for (outline, fill) in vector.fitted_outlines(width, height) {
+            let fill = Color::rgba(
+                fill.r * color.r,
+                fill.g * color.g,
+                fill.b * color.b,
+                fill.a * color.a,
+            );
+            self.draw_polygon(&outline, fill, transforms.clone(), render_pass);
+        }
     }
 
     /// Draw a mathematical expression using LaTeX notation
@@ -1176,7 +2385,7 @@ impl ShapeRenderer {
         latex: &str,
         base_font_size: f32,
         color: Color,
-        dynamic_offset: u32,
+        transforms: Range<u32>,
         render_pass: &mut wgpu::RenderPass,
     ) {
         use crate::math::{expression::parse_latex, layout::MathLayout};
@@ -1194,10 +2403,117 @@ impl ShapeRenderer {
         // For now, we'll render all elements with the identity transform
         // TODO: In the future, we should properly position each element
         // based on the layout positions
+        let first_glyph = self.glyph_offset.get();
         for (_position, text, font_size) in elements {
             // Draw the text at its relative position
             // The positioning is handled by the layout system
-            self.draw_text(&text, font_size, color, dynamic_offset, render_pass);
+            self.draw_text(&text, font_size, color, transforms.clone(), render_pass);
+            self.glyph_offset
+                .set(self.glyph_offset.get() + text.chars().count());
+        }
+        self.glyph_offset.set(first_glyph);
+    }
+}
+
+/// Where an arrow's shaft ends and the corners of its tip (point first)
+///
+/// The tip is 5% of the scene size long; `None` for arrows too short to
+/// draw. Vector exports use this to match [`ShapeRenderer::draw_arrow`].
+pub fn arrow_geometry(start: Vector3, end: Vector3) -> Option<(Vector3, [Vector3; 3])> {
+    let dir = Vector3::new(end.x - start.x, end.y - start.y, 0.0);
+    let length = (dir.x * dir.x + dir.y * dir.y).sqrt();
+    if length < 0.001 {
+        return None;
+    }
+
+    let tip_size = 0.05;
+    let line_end = if length > tip_size {
+        Vector3::new(
+            start.x + dir.x * (1.0 - tip_size / length),
+            start.y + dir.y * (1.0 - tip_size / length),
+            start.z,
+        )
+    } else {
+        start // Very short arrow, minimal shaft
+    };
+
+    let perp = Vector3::new(-dir.y / length, dir.x / length, 0.0);
+    let tip_half_width = tip_size * 0.5;
+    let corner = |side: f32| {
+        Vector3::new(
+            line_end.x + perp.x * tip_half_width * side,
+            line_end.y + perp.y * tip_half_width * side,
+            end.z,
+        )
+    };
+    Some((line_end, [end, corner(1.0), corner(-1.0)]))
+}
+
+/// Append quads for shaped glyphs, positioned relative to the baseline
+/// point `origin` and colored by `color`
+fn push_glyph_quads(
+    atlas: &GlyphAtlas,
+    shaped: &[ShapedGlyph],
+    origin: (f32, f32),
+    scale: f32,
+    color: impl Fn(&ShapedGlyph) -> [f32; 4],
+    vertices: &mut Vec<TextVertex>,
+    indices: &mut Vec<u16>,
+) {
+    for placed in shaped {
+        if let Some(glyph) = atlas.glyph(placed.font, placed.glyph_id) {
+            if glyph.width > 0 && glyph.height > 0 {
+                let glyph_width = glyph.width as f32 * scale;
+                let glyph_height = glyph.height as f32 * scale;
+                let bearing_x = glyph.bearing_x * scale;
+                let bearing_y = glyph.bearing_y * scale;
+
+                let x0 = origin.0 + placed.x * scale + bearing_x;
+                let y0 = origin.1 + placed.y * scale - bearing_y;
+                let x1 = x0 + glyph_width;
+                let y1 = y0 + glyph_height;
+
+                let base_idx = vertices.len() as u16;
+                // Color glyphs keep their own colors, only taking the opacity
+                let color = color(placed);
+                let color = if glyph.is_color {
+                    [1.0, 1.0, 1.0, color[3]]
+                } else {
+                    color
+                };
+
+                // Create quad for this glyph
+                vertices.push(TextVertex {
+                    position: [x0, y0, 0.0],
+                    uv: [glyph.uv.0, glyph.uv.1],
+                    color,
+                });
+                vertices.push(TextVertex {
+                    position: [x1, y0, 0.0],
+                    uv: [glyph.uv.2, glyph.uv.1],
+                    color,
+                });
+                vertices.push(TextVertex {
+                    position: [x1, y1, 0.0],
+                    uv: [glyph.uv.2, glyph.uv.3],
+                    color,
+                });
+                vertices.push(TextVertex {
+                    position: [x0, y1, 0.0],
+                    uv: [glyph.uv.0, glyph.uv.3],
+                    color,
+                });
+
+                // Two triangles for the quad
+                indices.extend_from_slice(&[
+                    base_idx,
+                    base_idx + 1,
+                    base_idx + 2,
+                    base_idx,
+                    base_idx + 2,
+                    base_idx + 3,
+                ]);
+            }
         }
     }
 }
@@ -0,0 +1,580 @@
+//! GPU Particle Systems
+//!
+//! Integrates large point clouds (tens of thousands of particles) in a WGSL
+//! compute pass and draws them as instanced point sprites straight from the
+//! same storage buffer, so particle state never round-trips through the CPU.
+//!
+//! Two simulation modes are available:
+//! - **Gravity**: constant acceleration plus an optional point attractor
+//! - **Boids**: separation / alignment / cohesion flocking (O(n²) neighbor search)
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use diomanim::render::particles::*;
+//! use diomanim::render::*;
+//! use diomanim::core::*;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let renderer = ShapeRenderer::new(1920, 1080).await?;
+//!
+//! let particles = Particle::grid(100, 100, 0.02);
+//! let mut system = ParticleSystem::new(
+//!     &renderer,
+//!     &particles,
+//!     ParticleSimulation::gravity(Vector3::new(0.0, -0.5, 0.0)),
+//! );
+//!
+//! // Each frame: step the simulation, then draw inside a render pass
+//! let mut encoder = renderer
+//!     .get_device()
+//!     .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+//! system.step(&renderer, &mut encoder, 1.0 / 60.0);
+//! // system.draw(&renderer, transforms, &mut render_pass);
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{PipelineCache, PipelineKey, ShapeRenderer};
+use crate::core::{Color, Vector3};
+use wgpu::util::DeviceExt;
+
+/// Threads per compute workgroup (must match `particles_compute.wgsl`)
+const WORKGROUP_SIZE: u32 = 64;
+
+/// [`PipelineCache`] name of the sprite shader (`particles.wgsl`)
+const PARTICLE_SHADER: &str = "particles";
+
+/// Vertex buffers of the sprite pipeline: quad corners, then per-instance particles
+const PARTICLE_VERTEX_BUFFERS: &[wgpu::VertexBufferLayout<'static>] = &[
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &wgpu::vertex_attr_array![0 => Float32x2],
+    },
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<Particle>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &wgpu::vertex_attr_array![1 => Float32x4],
+    },
+];
+
+/// Particle state as stored on the GPU
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Particle {
+    /// xyz = position, w = point size (0 = use the system default)
+    pub position: [f32; 4],
+    /// xyz = velocity
+    pub velocity: [f32; 4],
+}
+
+impl Particle {
+    pub fn new(position: Vector3, velocity: Vector3) -> Self {
+        Self {
+            position: [position.x, position.y, position.z, 0.0],
+            velocity: [velocity.x, velocity.y, velocity.z, 0.0],
+        }
+    }
+
+    /// Set an explicit point size for this particle
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.position[3] = size;
+        self
+    }
+
+    pub fn position(&self) -> Vector3 {
+        Vector3::new(self.position[0], self.position[1], self.position[2])
+    }
+
+    pub fn velocity(&self) -> Vector3 {
+        Vector3::new(self.velocity[0], self.velocity[1], self.velocity[2])
+    }
+
+    /// Particles at rest on a `columns` x `rows` grid spanning [-1, 1]
+    pub fn grid(columns: usize, rows: usize, size: f32) -> Vec<Self> {
+        let step = |i: usize, n: usize| {
+            if n > 1 {
+                -1.0 + 2.0 * i as f32 / (n - 1) as f32
+            } else {
+                0.0
+            }
+        };
+        (0..rows)
+            .flat_map(|row| {
+                (0..columns).map(move |col| {
+                    Self::new(
+                        Vector3::new(step(col, columns), step(row, rows), 0.0),
+                        Vector3::zero(),
+                    )
+                    .with_size(size)
+                })
+            })
+            .collect()
+    }
+}
+
+/// Forces applied by the compute pass
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParticleSimulation {
+    /// Constant acceleration with an optional inverse-square attractor
+    Gravity {
+        gravity: Vector3,
+        attractor: Option<(Vector3, f32)>,
+    },
+    /// Reynolds flocking within `radius` of each particle
+    Boids {
+        radius: f32,
+        separation: f32,
+        alignment: f32,
+        cohesion: f32,
+    },
+}
+
+impl ParticleSimulation {
+    pub fn gravity(gravity: Vector3) -> Self {
+        Self::Gravity {
+            gravity,
+            attractor: None,
+        }
+    }
+
+    /// Pull all particles towards `position` with the given strength
+    pub fn attractor(position: Vector3, strength: f32) -> Self {
+        Self::Gravity {
+            gravity: Vector3::zero(),
+            attractor: Some((position, strength)),
+        }
+    }
+
+    /// Flocking with commonly used default weights
+    pub fn boids(radius: f32) -> Self {
+        Self::Boids {
+            radius,
+            separation: 0.002,
+            alignment: 0.5,
+            cohesion: 0.3,
+        }
+    }
+}
+
+/// Uniform parameters for `particles_compute.wgsl`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ParticleParams {
+    pub gravity: [f32; 4],
+    pub attractor: [f32; 4],
+    pub boids: [f32; 4],
+    /// dt, damping, max speed, mode
+    pub settings: [f32; 4],
+    pub count: [u32; 4],
+}
+
+impl ParticleParams {
+    pub fn new(
+        simulation: &ParticleSimulation,
+        dt: f32,
+        damping: f32,
+        max_speed: f32,
+        count: u32,
+    ) -> Self {
+        let mut params = Self {
+            gravity: [0.0; 4],
+            attractor: [0.0; 4],
+            boids: [0.0; 4],
+            settings: [dt, damping, max_speed, 0.0],
+            count: [count, 0, 0, 0],
+        };
+        match *simulation {
+            ParticleSimulation::Gravity { gravity, attractor } => {
+                params.gravity = [gravity.x, gravity.y, gravity.z, 0.0];
+                if let Some((position, strength)) = attractor {
+                    params.attractor = [position.x, position.y, position.z, strength];
+                }
+            }
+            ParticleSimulation::Boids {
+                radius,
+                separation,
+                alignment,
+                cohesion,
+            } => {
+                params.boids = [radius, separation, alignment, cohesion];
+                params.settings[3] = 1.0;
+            }
+        }
+        params
+    }
+}
+
+// Render style uniform for `particles.wgsl`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct ParticleStyle {
+    color: [f32; 4],
+    size: [f32; 4],
+}
+
+/// Byte offset of the transform index that follows [`ParticleStyle`] in the style buffer
+const STYLE_TRANSFORM_OFFSET: u64 = std::mem::size_of::<ParticleStyle>() as u64;
+
+/// A GPU-simulated particle system
+pub struct ParticleSystem {
+    /// Forces applied each step
+    pub simulation: ParticleSimulation,
+    /// Velocity multiplier per step (1.0 = no damping)
+    pub damping: f32,
+    /// Speed clamp (0.0 = unlimited)
+    pub max_speed: f32,
+    count: u32,
+    params_buffer: wgpu::Buffer,
+    style_buffer: wgpu::Buffer,
+    /// Ping-pong particle buffers
+    particle_buffers: [wgpu::Buffer; 2],
+    /// Bind group `i` reads buffer `i` and writes buffer `1 - i`
+    compute_bind_groups: [wgpu::BindGroup; 2],
+    style_bind_group: wgpu::BindGroup,
+    quad_buffer: wgpu::Buffer,
+    compute_pipeline: wgpu::ComputePipeline,
+    /// Sprite pipelines per target format (the style layout is per system)
+    render_pipelines: PipelineCache,
+    /// Index of the buffer holding the latest state
+    current: usize,
+}
+
+impl ParticleSystem {
+    /// Upload particles and build the compute and render pipelines
+    pub fn new(
+        renderer: &ShapeRenderer,
+        particles: &[Particle],
+        simulation: ParticleSimulation,
+    ) -> Self {
+        let device = renderer.get_device();
+        let count = particles.len() as u32;
+
+        // Storage buffers double as instance vertex buffers for rendering
+        let usage = wgpu::BufferUsages::STORAGE
+            | wgpu::BufferUsages::VERTEX
+            | wgpu::BufferUsages::COPY_DST
+            | wgpu::BufferUsages::COPY_SRC;
+        // Keep buffers non-empty so bind groups stay valid
+        let contents: Vec<Particle> = if particles.is_empty() {
+            vec![Particle::new(Vector3::zero(), Vector3::zero())]
+        } else {
+            particles.to_vec()
+        };
+        let particle_buffers = [0, 1].map(|i| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(if i == 0 {
+                    "Particle Buffer A"
+                } else {
+                    "Particle Buffer B"
+                }),
+                contents: bytemuck::cast_slice(&contents),
+                usage,
+            })
+        });
+
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Particle Params Buffer"),
+            contents: bytemuck::cast_slice(&[ParticleParams::new(
+                &simulation,
+                0.0,
+                1.0,
+                0.0,
+                count,
+            )]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let style_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Particle Style Buffer"),
+            size: STYLE_TRANSFORM_OFFSET + std::mem::size_of::<[u32; 4]>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        renderer.get_queue().write_buffer(
+            &style_buffer,
+            0,
+            bytemuck::cast_slice(&[ParticleStyle {
+                color: Color::BLUE.to_f32_array(),
+                size: [0.01, 0.0, 0.0, 0.0],
+            }]),
+        );
+
+        // Unit quad (two triangles) expanded per instance in the vertex shader
+        let quad: [[f32; 2]; 6] = [
+            [-1.0, -1.0],
+            [1.0, -1.0],
+            [1.0, 1.0],
+            [-1.0, -1.0],
+            [1.0, 1.0],
+            [-1.0, 1.0],
+        ];
+        let quad_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Particle Quad Buffer"),
+            contents: bytemuck::cast_slice(&quad),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        // Compute pipeline
+        let compute_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Particle Compute Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("particles_compute.wgsl").into()),
+        });
+
+        let storage_entry = |binding: u32, read_only: bool| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let compute_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Particle Compute Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    storage_entry(1, true),
+                    storage_entry(2, false),
+                ],
+            });
+
+        let compute_bind_groups = [0, 1].map(|src| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Particle Compute Bind Group"),
+                layout: &compute_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: params_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: particle_buffers[src].as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: particle_buffers[1 - src].as_entire_binding(),
+                    },
+                ],
+            })
+        });
+
+        let compute_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Particle Compute Pipeline Layout"),
+                bind_group_layouts: &[&compute_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Particle Compute Pipeline"),
+            layout: Some(&compute_pipeline_layout),
+            module: &compute_shader,
+            entry_point: Some("cs_main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        let style_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Particle Style Bind Group Layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+        let style_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Particle Style Bind Group"),
+            layout: &style_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: style_buffer.as_entire_binding(),
+            }],
+        });
+
+        // Group 0 is the shared transform layout so draws reuse update_transform()
+        let transform_bind_group_layout = renderer.get_pipeline().get_bind_group_layout(0);
+
+        let render_pipelines = PipelineCache::new();
+        render_pipelines.register_shader(
+            device,
+            PARTICLE_SHADER,
+            include_str!("particles.wgsl"),
+            &[&transform_bind_group_layout, &style_bind_group_layout],
+        );
+
+        Self {
+            simulation,
+            damping: 1.0,
+            max_speed: 0.0,
+            count,
+            params_buffer,
+            style_buffer,
+            particle_buffers,
+            compute_bind_groups,
+            style_bind_group,
+            quad_buffer,
+            compute_pipeline,
+            render_pipelines,
+            current: 0,
+        }
+    }
+
+    /// Number of simulated particles
+    pub fn len(&self) -> usize {
+        self.count as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Set the point color and default point size
+    pub fn set_style(&self, renderer: &ShapeRenderer, color: Color, size: f32) {
+        renderer.get_queue().write_buffer(
+            &self.style_buffer,
+            0,
+            bytemuck::cast_slice(&[ParticleStyle {
+                color: color.to_f32_array(),
+                size: [size, 0.0, 0.0, 0.0],
+            }]),
+        );
+    }
+
+    /// Replace the particle state (e.g. to restart the simulation)
+    pub fn reset(&mut self, renderer: &ShapeRenderer, particles: &[Particle]) {
+        let len = particles.len().min(self.len());
+        renderer.get_queue().write_buffer(
+            &self.particle_buffers[self.current],
+            0,
+            bytemuck::cast_slice(&particles[..len]),
+        );
+    }
+
+    /// Record one integration step of `dt` seconds into `encoder`
+    pub fn step(&mut self, renderer: &ShapeRenderer, encoder: &mut wgpu::CommandEncoder, dt: f32) {
+        if self.is_empty() {
+            return;
+        }
+
+        let params = ParticleParams::new(
+            &self.simulation,
+            dt,
+            self.damping,
+            self.max_speed,
+            self.count,
+        );
+        renderer
+            .get_queue()
+            .write_buffer(&self.params_buffer, 0, bytemuck::cast_slice(&[params]));
+
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Particle Compute Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.compute_pipeline);
+        pass.set_bind_group(0, &self.compute_bind_groups[self.current], &[]);
+        pass.dispatch_workgroups(self.count.div_ceil(WORKGROUP_SIZE), 1, 1);
+        drop(pass);
+
+        self.current = 1 - self.current;
+    }
+
+    /// Draw all particles as instanced sprites.
+    ///
+    /// `transforms` comes from [`ShapeRenderer::update_transform`] and
+    /// positions the whole cloud (usually the camera view-projection). The
+    /// transform index is stored with the system's style, so a system is
+    /// drawn once per submitted frame.
+    pub fn draw(
+        &self,
+        renderer: &ShapeRenderer,
+        transforms: std::ops::Range<u32>,
+        render_pass: &mut wgpu::RenderPass,
+    ) {
+        if self.is_empty() {
+            return;
+        }
+        renderer.get_queue().write_buffer(
+            &self.style_buffer,
+            STYLE_TRANSFORM_OFFSET,
+            bytemuck::cast_slice(&[transforms.start, 0, 0, 0]),
+        );
+
+        let key = PipelineKey::new(
+            PARTICLE_SHADER,
+            PARTICLE_VERTEX_BUFFERS,
+            renderer.target_format(),
+        );
+        render_pass.set_pipeline(&self.render_pipelines.get(renderer.get_device(), &key));
+        renderer.rebind_grown_transforms(render_pass);
+        render_pass.set_bind_group(1, &self.style_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.quad_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.particle_buffers[self.current].slice(..));
+        renderer.count_draw_call();
+        render_pass.draw(0..6, 0..self.count);
+    }
+
+    /// Buffer holding the latest particle state (for readback or custom passes)
+    pub fn current_buffer(&self) -> &wgpu::Buffer {
+        &self.particle_buffers[self.current]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_particle_layout_matches_shader() {
+        assert_eq!(std::mem::size_of::<Particle>(), 32);
+        assert_eq!(std::mem::size_of::<ParticleParams>(), 80);
+    }
+
+    #[test]
+    fn test_params_select_mode() {
+        let gravity = ParticleParams::new(
+            &ParticleSimulation::attractor(Vector3::new(1.0, 2.0, 3.0), 0.5),
+            0.016,
+            0.99,
+            0.0,
+            10,
+        );
+        assert_eq!(gravity.attractor, [1.0, 2.0, 3.0, 0.5]);
+        assert_eq!(gravity.settings[3], 0.0);
+        assert_eq!(gravity.count[0], 10);
+
+        let boids = ParticleParams::new(&ParticleSimulation::boids(0.1), 0.016, 1.0, 2.0, 10);
+        assert_eq!(boids.boids[0], 0.1);
+        assert_eq!(boids.settings[3], 1.0);
+    }
+
+    #[test]
+    fn test_grid_spans_unit_square() {
+        let grid = Particle::grid(3, 2, 0.05);
+        assert_eq!(grid.len(), 6);
+        assert_eq!(grid[0].position(), Vector3::new(-1.0, -1.0, 0.0));
+        assert_eq!(grid[5].position(), Vector3::new(1.0, 1.0, 0.0));
+        assert_eq!(grid[0].position[3], 0.05);
+    }
+}
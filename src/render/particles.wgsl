@@ -0,0 +1,55 @@
+// Instanced point-sprite rendering for particle systems
+struct VertexInput {
+    // Unit quad corner in [-1, 1]
+    @location(0) corner: vec2<f32>,
+};
+
+struct InstanceInput {
+    // xyz = position, w = point size (0 = use default)
+    @location(1) position: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) corner: vec2<f32>,
+};
+
+struct Uniforms {
+    model_view_proj: mat4x4<f32>,
+};
+
+struct Style {
+    color: vec4<f32>,
+    // x = default point size
+    size: vec4<f32>,
+    // x = index of the cloud's transform
+    transform: vec4<u32>,
+};
+
+@group(0) @binding(0) var<storage, read> transforms: array<Uniforms>;
+@group(1) @binding(0) var<uniform> style: Style;
+
+@vertex
+fn vs_main(vertex: VertexInput, instance: InstanceInput) -> VertexOutput {
+    var out: VertexOutput;
+    var size = instance.position.w;
+    if (size <= 0.0) {
+        size = style.size.x;
+    }
+    let offset = vec3<f32>(vertex.corner * size, 0.0);
+    let model_view_proj = transforms[style.transform.x].model_view_proj;
+    out.clip_position = model_view_proj * vec4<f32>(instance.position.xyz + offset, 1.0);
+    out.corner = vertex.corner;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Round points with a soft edge
+    let dist = length(in.corner);
+    if (dist > 1.0) {
+        discard;
+    }
+    let alpha = 1.0 - smoothstep(0.8, 1.0, dist);
+    return vec4<f32>(style.color.rgb, style.color.a * alpha);
+}
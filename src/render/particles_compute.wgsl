@@ -0,0 +1,94 @@
+// Particle integration compute pass (gravity / attractor / boids)
+struct Particle {
+    // xyz = position, w = point size (0 = use render default)
+    position: vec4<f32>,
+    // xyz = velocity, w = unused
+    velocity: vec4<f32>,
+};
+
+struct Params {
+    // xyz = constant acceleration
+    gravity: vec4<f32>,
+    // xyz = attractor position, w = strength (0 disables)
+    attractor: vec4<f32>,
+    // x = neighbor radius, y = separation, z = alignment, w = cohesion
+    boids: vec4<f32>,
+    // x = dt, y = damping, z = max speed (0 = unlimited), w = mode (0 = gravity, 1 = boids)
+    settings: vec4<f32>,
+    // x = particle count
+    count: vec4<u32>,
+};
+
+// Added to the squared distance in the attractor's inverse-square pull
+const ATTRACTOR_SOFTENING: f32 = 0.01;
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> src: array<Particle>;
+@group(0) @binding(2) var<storage, read_write> dst: array<Particle>;
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    let count = params.count.x;
+    if (index >= count) {
+        return;
+    }
+
+    let particle = src[index];
+    let position = particle.position.xyz;
+    var velocity = particle.velocity.xyz;
+    var accel = params.gravity.xyz;
+
+    if (params.attractor.w != 0.0) {
+        let delta = params.attractor.xyz - position;
+        let dist_sq = dot(delta, delta);
+        // Softened so the pull stays finite up close; no direction at the centre
+        if (dist_sq > 1e-8) {
+            accel += normalize(delta) * (params.attractor.w / (dist_sq + ATTRACTOR_SOFTENING));
+        }
+    }
+
+    if (params.settings.w > 0.5) {
+        let radius = params.boids.x;
+        var separation = vec3<f32>(0.0);
+        var heading = vec3<f32>(0.0);
+        var center = vec3<f32>(0.0);
+        var neighbors = 0u;
+
+        for (var j = 0u; j < count; j = j + 1u) {
+            if (j == index) {
+                continue;
+            }
+            let other = src[j];
+            let delta = other.position.xyz - position;
+            let dist = length(delta);
+            if (dist < radius) {
+                neighbors = neighbors + 1u;
+                center += other.position.xyz;
+                heading += other.velocity.xyz;
+                if (dist > 0.0) {
+                    separation -= delta / (dist * dist);
+                }
+            }
+        }
+
+        if (neighbors > 0u) {
+            let n = f32(neighbors);
+            accel += separation * params.boids.y;
+            accel += (heading / n - velocity) * params.boids.z;
+            accel += (center / n - position) * params.boids.w;
+        }
+    }
+
+    let dt = params.settings.x;
+    velocity = (velocity + accel * dt) * params.settings.y;
+
+    let max_speed = params.settings.z;
+    let speed = length(velocity);
+    if (max_speed > 0.0 && speed > max_speed) {
+        velocity = velocity / speed * max_speed;
+    }
+
+    dst[index].position = vec4<f32>(position + velocity * dt, particle.position.w);
+    dst[index].velocity = vec4<f32>(velocity, particle.velocity.w);
+}
@@ -0,0 +1,331 @@
+//! # Pipeline Cache
+//!
+//! Render pipelines are expensive to build and most of them differ only in a
+//! few settings. [`PipelineCache`] compiles each registered WGSL shader once
+//! and builds one pipeline per distinct [`PipelineKey`] (shader, vertex
+//! layout, target format, blend state, sample count and stencil use), handing
+//! out the same pipeline on every later request.
+//!
+//! ```rust,no_run
+//! use diomanim::render::{PipelineKey, ShapeRenderer, Vertex, SHAPE_SHADER};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let renderer = ShapeRenderer::new(1920, 1080).await?;
+//!
+//! // Shapes drawn opaque into a 4x multisampled sRGB target
+//! let key = PipelineKey::new(SHAPE_SHADER, Vertex::BUFFERS, wgpu::TextureFormat::Rgba8UnormSrgb)
+//!     .with_blend(None)
+//!     .with_sample_count(4);
+//! let pipeline = renderer.pipeline_cache().get(renderer.get_device(), &key);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::scene::BlendMode;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// Format of the stencil attachment used for clipping masks
+pub const STENCIL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Stencil8;
+
+/// How a pipeline uses the stencil attachment
+///
+/// Every mode only touches pixels whose stencil value equals the pass's
+/// stencil reference, i.e. pixels inside all enclosing clip masks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StencilMode {
+    /// Draw color where the stencil matches
+    Test,
+    /// Draw no color; raise the stencil inside the shape (push a clip mask)
+    Increment,
+    /// Draw no color; lower the stencil inside the shape (pop a clip mask)
+    Decrement,
+}
+
+/// Everything that distinguishes one cached render pipeline from another
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PipelineKey {
+    /// Name the shader was registered under
+    pub shader: &'static str,
+    /// Fragment entry point within the shader
+    pub fragment_entry: &'static str,
+    pub vertex_buffers: &'static [wgpu::VertexBufferLayout<'static>],
+    /// Color format of the render target
+    pub format: wgpu::TextureFormat,
+    /// Blending of the color target (`None` overwrites)
+    pub blend: Option<wgpu::BlendState>,
+    /// MSAA sample count of the render target
+    pub sample_count: u32,
+    /// Stencil use, for passes with a [`STENCIL_FORMAT`] attachment
+    pub stencil: Option<StencilMode>,
+}
+
+impl PipelineKey {
+    /// Alpha-blended, single-sampled pipeline for `shader`
+    pub fn new(
+        shader: &'static str,
+        vertex_buffers: &'static [wgpu::VertexBufferLayout<'static>],
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        Self {
+            shader,
+            fragment_entry: "fs_main",
+            vertex_buffers,
+            format,
+            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+            sample_count: 1,
+            stencil: None,
+        }
+    }
+
+    /// Use fragment entry point `entry` instead of `fs_main`
+    pub fn with_fragment_entry(mut self, entry: &'static str) -> Self {
+        self.fragment_entry = entry;
+        self
+    }
+
+    pub fn with_format(mut self, format: wgpu::TextureFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_blend(mut self, blend: Option<wgpu::BlendState>) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    /// Blend the color target with `mode`
+    pub fn with_blend_mode(self, mode: BlendMode) -> Self {
+        self.with_blend(Some(blend_state(mode)))
+    }
+
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count.max(1);
+        self
+    }
+
+    pub fn with_stencil(mut self, stencil: Option<StencilMode>) -> Self {
+        self.stencil = stencil;
+        self
+    }
+}
+
+/// GPU blend state of `mode`; multiply and screen expect colors
+/// premultiplied by their alpha (see [`BlendMode::apply_opacity`])
+pub fn blend_state(mode: BlendMode) -> wgpu::BlendState {
+    let color = |src_factor, dst_factor| wgpu::BlendComponent {
+        src_factor,
+        dst_factor,
+        operation: wgpu::BlendOperation::Add,
+    };
+    let color = match mode {
+        BlendMode::Normal => return wgpu::BlendState::ALPHA_BLENDING,
+        BlendMode::Additive => color(wgpu::BlendFactor::SrcAlpha, wgpu::BlendFactor::One),
+        BlendMode::Multiply => color(wgpu::BlendFactor::Dst, wgpu::BlendFactor::OneMinusSrcAlpha),
+        BlendMode::Screen => color(wgpu::BlendFactor::One, wgpu::BlendFactor::OneMinusSrc),
+    };
+    wgpu::BlendState {
+        color,
+        alpha: wgpu::BlendComponent::OVER,
+    }
+}
+
+/// A compiled shader and the pipeline layout its bind groups use
+struct CachedShader {
+    module: wgpu::ShaderModule,
+    layout: wgpu::PipelineLayout,
+}
+
+/// Shader modules and render pipelines, each created once and reused
+#[derive(Default)]
+pub struct PipelineCache {
+    shaders: RefCell<HashMap<&'static str, CachedShader>>,
+    pipelines: RefCell<HashMap<PipelineKey, wgpu::RenderPipeline>>,
+}
+
+impl PipelineCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compile WGSL `source` as shader `name`
+    ///
+    /// `bind_group_layouts` are the groups the shader uses, in group order.
+    /// Registering a name that is already known does nothing, so callers can
+    /// register unconditionally before requesting pipelines.
+    pub fn register_shader(
+        &self,
+        device: &wgpu::Device,
+        name: &'static str,
+        source: &str,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+    ) {
+        if self.has_shader(name) {
+            return;
+        }
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(name),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(name),
+            bind_group_layouts,
+            push_constant_ranges: &[],
+        });
+        self.shaders
+            .borrow_mut()
+            .insert(name, CachedShader { module, layout });
+    }
+
+    pub fn has_shader(&self, name: &str) -> bool {
+        self.shaders.borrow().contains_key(name)
+    }
+
+    /// Pipeline for `key`, building it the first time it is requested
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key.shader` has not been registered with
+    /// [`Self::register_shader`].
+    pub fn get(&self, device: &wgpu::Device, key: &PipelineKey) -> wgpu::RenderPipeline {
+        if let Some(pipeline) = self.pipelines.borrow().get(key) {
+            return pipeline.clone();
+        }
+
+        let pipeline = {
+            let shaders = self.shaders.borrow();
+            let shader = shaders
+                .get(key.shader)
+                .unwrap_or_else(|| panic!("shader `{}` is not registered", key.shader));
+            build_pipeline(device, shader, key)
+        };
+        self.pipelines
+            .borrow_mut()
+            .insert(key.clone(), pipeline.clone());
+        pipeline
+    }
+
+    /// Number of pipelines built so far
+    pub fn len(&self) -> usize {
+        self.pipelines.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pipelines.borrow().is_empty()
+    }
+
+    /// Number of shader modules compiled so far
+    pub fn shader_count(&self) -> usize {
+        self.shaders.borrow().len()
+    }
+}
+
+fn stencil_state(mode: StencilMode) -> wgpu::DepthStencilState {
+    let pass_op = match mode {
+        StencilMode::Test => wgpu::StencilOperation::Keep,
+        StencilMode::Increment => wgpu::StencilOperation::IncrementClamp,
+        StencilMode::Decrement => wgpu::StencilOperation::DecrementClamp,
+    };
+    let face = wgpu::StencilFaceState {
+        compare: wgpu::CompareFunction::Equal,
+        fail_op: wgpu::StencilOperation::Keep,
+        depth_fail_op: wgpu::StencilOperation::Keep,
+        pass_op,
+    };
+    wgpu::DepthStencilState {
+        format: STENCIL_FORMAT,
+        depth_write_enabled: false,
+        depth_compare: wgpu::CompareFunction::Always,
+        stencil: wgpu::StencilState {
+            front: face,
+            back: face,
+            read_mask: 0xff,
+            write_mask: 0xff,
+        },
+        bias: wgpu::DepthBiasState::default(),
+    }
+}
+
+fn build_pipeline(
+    device: &wgpu::Device,
+    shader: &CachedShader,
+    key: &PipelineKey,
+) -> wgpu::RenderPipeline {
+    // Mask shapes only write the stencil
+    let write_mask = match key.stencil {
+        Some(StencilMode::Increment | StencilMode::Decrement) => wgpu::ColorWrites::empty(),
+        _ => wgpu::ColorWrites::ALL,
+    };
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(key.shader),
+        layout: Some(&shader.layout),
+        vertex: wgpu::VertexState {
+            module: &shader.module,
+            entry_point: Some("vs_main"),
+            buffers: key.vertex_buffers,
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader.module,
+            entry_point: Some(key.fragment_entry),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: key.format,
+                blend: key.blend,
+                write_mask,
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: key.stencil.map(stencil_state),
+        multisample: wgpu::MultisampleState {
+            count: key.sample_count,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+        cache: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    const BUFFERS: &[wgpu::VertexBufferLayout<'static>] = &[wgpu::VertexBufferLayout {
+        array_stride: 28,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x4],
+    }];
+
+    #[test]
+    fn test_pipeline_keys_distinguish_settings() {
+        let base = PipelineKey::new("shapes", BUFFERS, wgpu::TextureFormat::Rgba8Unorm);
+        let keys: HashSet<PipelineKey> = [
+            base.clone(),
+            base.clone(),
+            base.clone()
+                .with_format(wgpu::TextureFormat::Bgra8UnormSrgb),
+            base.clone().with_blend(None),
+            base.clone().with_blend_mode(BlendMode::Additive),
+            base.clone().with_sample_count(4),
+            base.clone().with_fragment_entry("fs_outline"),
+            base.clone().with_stencil(Some(StencilMode::Test)),
+            PipelineKey::new("lit", BUFFERS, wgpu::TextureFormat::Rgba8Unorm),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(keys.len(), 8);
+        assert_eq!(base.clone().with_sample_count(0).sample_count, 1);
+    }
+}
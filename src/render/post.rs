@@ -0,0 +1,542 @@
+//! # Post-Processing
+//!
+//! [`PostProcessPass`] is a render graph pass that applies the scene's
+//! [`PostEffect`]s (bloom, vignette, blur) to a finished frame. Each effect is
+//! a short chain of full-screen passes over intermediate textures owned by the
+//! pass; intensities are read from the scene every frame, so keyframed
+//! effects animate with the rest of the scene.
+//!
+//! ```rust,no_run
+//! use diomanim::prelude::*;
+//! use diomanim::render::graph::*;
+//! use diomanim::render::post::PostProcessPass;
+//! use diomanim::scene::{PostEffect, SceneGraph};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut renderer = ShapeRenderer::new(1280, 720).await?;
+//! let mut scene = SceneGraph::new();
+//! scene.add_post_effect(PostEffect::bloom(0.6, 1.0));
+//!
+//! let format = wgpu::TextureFormat::Rgba8Unorm;
+//! let mut graph = RenderGraph::new(1280, 720);
+//! graph.add_texture("scene", format).add_texture("final", format);
+//! graph.add_pass(ScenePass::new("main", "scene", DrawLayer::All).with_clear(Color::BLACK));
+//! graph.add_pass(PostProcessPass::new("scene", "final"));
+//! graph.add_pass(ReadbackPass::new("final"));
+//! graph.execute(&mut renderer, &scene, None)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::graph::{PassContext, RenderNode, FULLSCREEN_PRELUDE};
+use super::profiler::PassCategory;
+use super::{PipelineKey, ShapeRenderer};
+use crate::scene::{PostEffect, PostEffectKind};
+
+/// [`super::PipelineCache`] name of the post-processing shader (`post.wgsl`)
+const POST_SHADER: &str = "post";
+
+/// Format of intermediate textures (extra range keeps bloom from banding)
+pub(super) const INTERMEDIATE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Maximum full-screen steps per frame (bloom uses 4, blur 3, vignette 1)
+const MAX_STEPS: usize = 64;
+
+/// Byte stride between per-step parameter slots
+const PARAMS_STRIDE: u64 = 256;
+
+/// Compile `post.wgsl` into the renderer's pipeline cache (once)
+pub(super) fn register_post_shader(renderer: &ShapeRenderer, layout: &wgpu::BindGroupLayout) {
+    renderer.pipeline_cache().register_shader(
+        renderer.get_device(),
+        POST_SHADER,
+        &format!("{FULLSCREEN_PRELUDE}{}", include_str!("post.wgsl")),
+        &[layout],
+    );
+}
+
+/// Parameters for one full-screen step (must match `PostParams` in `post.wgsl`)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(super) struct PostParams {
+    pub texel: [f32; 2],
+    pub direction: [f32; 2],
+    pub intensity: f32,
+    pub threshold: f32,
+    pub radius: f32,
+    pub softness: f32,
+    pub color: [f32; 4],
+}
+
+/// A texture and its view
+pub(super) struct Target {
+    _texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+}
+
+impl Target {
+    pub fn new(device: &wgpu::Device, label: &str, size: (u32, u32)) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: size.0,
+                height: size.1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: INTERMEDIATE_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Self {
+            _texture: texture,
+            view,
+        }
+    }
+}
+
+/// Intermediate textures for one output size
+struct Targets {
+    size: (u32, u32),
+    /// Results of effects that are followed by another effect
+    chain: [Target; 2],
+    /// Full-resolution blur scratch
+    full: [Target; 2],
+    /// Half-resolution bloom scratch
+    half: [Target; 2],
+}
+
+impl Targets {
+    fn new(device: &wgpu::Device, size: (u32, u32)) -> Self {
+        let half_size = ((size.0 / 2).max(1), (size.1 / 2).max(1));
+        Self {
+            size,
+            chain: [
+                Target::new(device, "Post Chain A", size),
+                Target::new(device, "Post Chain B", size),
+            ],
+            full: [
+                Target::new(device, "Post Blur A", size),
+                Target::new(device, "Post Blur B", size),
+            ],
+            half: [
+                Target::new(device, "Post Bloom A", half_size),
+                Target::new(device, "Post Bloom B", half_size),
+            ],
+        }
+    }
+}
+
+/// Bind group layout, sampler and parameter slots for full-screen steps
+pub(super) struct StepResources {
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    params_buffer: wgpu::Buffer,
+    slots: usize,
+}
+
+impl StepResources {
+    /// Resources for up to `slots` steps per frame
+    pub fn new(renderer: &ShapeRenderer, slots: usize) -> Self {
+        let device = renderer.get_device();
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Post Bind Group Layout"),
+            entries: &[
+                texture_entry(0),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                texture_entry(2),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Post Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Post Params Buffer"),
+            size: PARAMS_STRIDE * slots as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        register_post_shader(renderer, &layout);
+        Self {
+            layout,
+            sampler,
+            params_buffer,
+            slots,
+        }
+    }
+
+    /// Number of steps [`Self::run`] can record per frame
+    pub fn slots(&self) -> usize {
+        self.slots
+    }
+
+    /// Record `steps` using parameter slots from `first_slot` on and return
+    /// the next free slot
+    ///
+    /// Slots are written with `Queue::write_buffer`, so each step in one
+    /// submission needs its own; steps past [`Self::slots`] are dropped.
+    pub fn run(
+        &self,
+        renderer: &ShapeRenderer,
+        encoder: &mut wgpu::CommandEncoder,
+        first_slot: usize,
+        steps: &[Step],
+    ) -> usize {
+        let device = renderer.get_device();
+        let count = steps.len().min(self.slots.saturating_sub(first_slot));
+        let steps = steps[..count].iter().zip(first_slot..);
+        for (step, slot) in steps.clone() {
+            renderer.get_queue().write_buffer(
+                &self.params_buffer,
+                slot as u64 * PARAMS_STRIDE,
+                bytemuck::cast_slice(&[step.params]),
+            );
+        }
+
+        for (step, slot) in steps {
+            let key = PipelineKey::new(POST_SHADER, &[], step.format)
+                .with_fragment_entry(step.entry)
+                .with_blend(step.blend);
+            let pipeline = renderer.pipeline_cache().get(device, &key);
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Post Bind Group"),
+                layout: &self.layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(step.input),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::TextureView(step.base),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                            buffer: &self.params_buffer,
+                            offset: slot as u64 * PARAMS_STRIDE,
+                            size: std::num::NonZeroU64::new(
+                                std::mem::size_of::<PostParams>() as u64
+                            ),
+                        }),
+                    },
+                ],
+            });
+
+            // Blended steps draw over what the target already holds
+            let load = if step.blend.is_some() {
+                wgpu::LoadOp::Load
+            } else {
+                wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT)
+            };
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(step.entry),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: step.target,
+                    resolve_target: None,
+                    depth_slice: None,
+                    ops: wgpu::Operations {
+                        load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+            render_pass.set_pipeline(&pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+        first_slot + count
+    }
+}
+
+/// One full-screen draw of a `post.wgsl` fragment entry
+pub(super) struct Step<'a> {
+    pub entry: &'static str,
+    /// Sampled as `t_input`
+    pub input: &'a wgpu::TextureView,
+    /// Loaded as `t_base`
+    pub base: &'a wgpu::TextureView,
+    pub target: &'a wgpu::TextureView,
+    pub format: wgpu::TextureFormat,
+    /// `None` overwrites the target, otherwise the step blends over it
+    pub blend: Option<wgpu::BlendState>,
+    pub params: PostParams,
+}
+
+/// Applies the scene's post effects from `input` to `output`
+///
+/// With no active effects the frame is copied through unchanged.
+pub struct PostProcessPass {
+    input: String,
+    output: String,
+    resources: Option<StepResources>,
+    targets: Option<Targets>,
+}
+
+impl PostProcessPass {
+    pub fn new(input: &str, output: &str) -> Self {
+        Self {
+            input: input.to_string(),
+            output: output.to_string(),
+            resources: None,
+            targets: None,
+        }
+    }
+}
+
+/// Texel size of a `(width, height)` texture
+fn texel(size: (u32, u32)) -> [f32; 2] {
+    [1.0 / size.0 as f32, 1.0 / size.1 as f32]
+}
+
+/// The full-screen steps that apply `effects` to `input`, ending in `output`
+fn plan_steps<'a>(
+    effects: &[PostEffect],
+    input: &'a wgpu::TextureView,
+    output: (&'a wgpu::TextureView, wgpu::TextureFormat),
+    targets: &'a Targets,
+) -> Vec<Step<'a>> {
+    let full_texel = texel(targets.size);
+    let half_size = ((targets.size.0 / 2).max(1), (targets.size.1 / 2).max(1));
+    let half_texel = texel(half_size);
+
+    if effects.is_empty() {
+        return vec![Step {
+            entry: "fs_copy",
+            input,
+            base: input,
+            target: output.0,
+            format: output.1,
+            blend: None,
+            params: PostParams::default(),
+        }];
+    }
+
+    let mut steps = Vec::new();
+    let mut current = input;
+    for (index, effect) in effects.iter().enumerate() {
+        let (target, format) = if index + 1 == effects.len() {
+            output
+        } else {
+            (&targets.chain[index % 2].view, INTERMEDIATE_FORMAT)
+        };
+        let scratch = |entry, input, target, texel, params: PostParams| Step {
+            entry,
+            input,
+            base: input,
+            target,
+            format: INTERMEDIATE_FORMAT,
+            blend: None,
+            params: PostParams { texel, ..params },
+        };
+
+        match effect.kind {
+            PostEffectKind::Vignette { radius, softness } => {
+                steps.push(Step {
+                    entry: "fs_vignette",
+                    input: current,
+                    base: current,
+                    target,
+                    format,
+                    blend: None,
+                    params: PostParams {
+                        texel: full_texel,
+                        intensity: effect.intensity,
+                        radius,
+                        softness: softness.max(0.001),
+                        ..PostParams::default()
+                    },
+                });
+            }
+            PostEffectKind::Blur { radius } => {
+                let blur = PostParams {
+                    radius,
+                    ..PostParams::default()
+                };
+                steps.push(scratch(
+                    "fs_blur",
+                    current,
+                    &targets.full[0].view,
+                    full_texel,
+                    PostParams {
+                        direction: [1.0, 0.0],
+                        ..blur
+                    },
+                ));
+                steps.push(scratch(
+                    "fs_blur",
+                    &targets.full[0].view,
+                    &targets.full[1].view,
+                    full_texel,
+                    PostParams {
+                        direction: [0.0, 1.0],
+                        ..blur
+                    },
+                ));
+                steps.push(Step {
+                    entry: "fs_mix",
+                    input: &targets.full[1].view,
+                    base: current,
+                    target,
+                    format,
+                    blend: None,
+                    params: PostParams {
+                        texel: full_texel,
+                        intensity: effect.intensity,
+                        ..PostParams::default()
+                    },
+                });
+            }
+            PostEffectKind::Bloom { threshold, radius } => {
+                // Highlights are blurred at half resolution, so the radius halves too
+                let blur = PostParams {
+                    radius: radius / 2.0,
+                    ..PostParams::default()
+                };
+                steps.push(scratch(
+                    "fs_bright",
+                    current,
+                    &targets.half[0].view,
+                    full_texel,
+                    PostParams {
+                        threshold,
+                        ..PostParams::default()
+                    },
+                ));
+                steps.push(scratch(
+                    "fs_blur",
+                    &targets.half[0].view,
+                    &targets.half[1].view,
+                    half_texel,
+                    PostParams {
+                        direction: [1.0, 0.0],
+                        ..blur
+                    },
+                ));
+                steps.push(scratch(
+                    "fs_blur",
+                    &targets.half[1].view,
+                    &targets.half[0].view,
+                    half_texel,
+                    PostParams {
+                        direction: [0.0, 1.0],
+                        ..blur
+                    },
+                ));
+                steps.push(Step {
+                    entry: "fs_bloom",
+                    input: &targets.half[0].view,
+                    base: current,
+                    target,
+                    format,
+                    blend: None,
+                    params: PostParams {
+                        texel: full_texel,
+                        intensity: effect.intensity,
+                        ..PostParams::default()
+                    },
+                });
+            }
+        }
+        current = target;
+    }
+    steps
+}
+
+impl RenderNode for PostProcessPass {
+    fn name(&self) -> &'static str {
+        "post-process"
+    }
+
+    fn inputs(&self) -> Vec<&str> {
+        vec![&self.input]
+    }
+
+    fn outputs(&self) -> Vec<&str> {
+        vec![&self.output]
+    }
+
+    fn category(&self) -> PassCategory {
+        PassCategory::Post
+    }
+
+    fn run(&mut self, ctx: &mut PassContext) {
+        let size = ctx.size(&self.output);
+        let resources = self
+            .resources
+            .get_or_insert_with(|| StepResources::new(ctx.renderer, MAX_STEPS));
+        if self
+            .targets
+            .as_ref()
+            .is_none_or(|targets| targets.size != size)
+        {
+            self.targets = Some(Targets::new(ctx.renderer.get_device(), size));
+        }
+        let Some(targets) = &self.targets else {
+            return;
+        };
+
+        let effects: Vec<PostEffect> = ctx
+            .scene
+            .post_effects()
+            .iter()
+            .filter(|effect| effect.is_active())
+            .cloned()
+            .collect();
+        let output = (ctx.view(&self.output), ctx.format(&self.output));
+        let steps = plan_steps(&effects, ctx.view(&self.input), output, targets);
+        resources.run(ctx.renderer, ctx.encoder, 0, &steps);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_params_layout_matches_shader() {
+        // PostParams in post.wgsl: two vec2, four f32 and a vec4, 48 bytes
+        assert_eq!(std::mem::size_of::<PostParams>(), 48);
+        assert!(std::mem::size_of::<PostParams>() as u64 <= PARAMS_STRIDE);
+    }
+}
@@ -0,0 +1,89 @@
+// Post-processing fragment stages.
+// Appended to the render graph's fullscreen prelude, which provides the
+// vertex stage, `t_input` and `s_input`.
+
+struct PostParams {
+    // 1 / size of t_input in pixels
+    texel: vec2<f32>,
+    // Blur direction: (1, 0) or (0, 1)
+    direction: vec2<f32>,
+    intensity: f32,
+    threshold: f32,
+    radius: f32,
+    softness: f32,
+    // Tint for fs_tint
+    color: vec4<f32>,
+};
+
+// Full-resolution frame the effect is applied to
+@group(0) @binding(2) var t_base: texture_2d<f32>;
+@group(0) @binding(3) var<uniform> params: PostParams;
+
+fn base_color(position: vec4<f32>) -> vec4<f32> {
+    return textureLoad(t_base, vec2<i32>(position.xy), 0);
+}
+
+// Unchanged copy of the base frame
+@fragment
+fn fs_copy(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    return base_color(in.position);
+}
+
+// Keep only pixels brighter than the threshold
+@fragment
+fn fs_bright(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_input, s_input, in.uv);
+    let brightness = max(color.r, max(color.g, color.b));
+    let keep = smoothstep(params.threshold, params.threshold + 0.1, brightness);
+    return vec4<f32>(color.rgb * keep * color.a, 1.0);
+}
+
+// One direction of a separable gaussian blur
+@fragment
+fn fs_blur(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let sigma = max(params.radius, 0.001) * 0.5;
+    let step = params.direction * params.texel * (params.radius / 4.0);
+    var sum = vec4<f32>(0.0);
+    var weight_sum = 0.0;
+    for (var i = -4; i <= 4; i = i + 1) {
+        let offset = f32(i) * params.radius / 4.0;
+        let weight = exp(-(offset * offset) / (2.0 * sigma * sigma));
+        sum = sum + textureSample(t_input, s_input, in.uv + step * f32(i)) * weight;
+        weight_sum = weight_sum + weight;
+    }
+    return sum / weight_sum;
+}
+
+// Add the blurred highlights back onto the frame
+@fragment
+fn fs_bloom(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let base = base_color(in.position);
+    let glow = textureSample(t_input, s_input, in.uv).rgb * params.intensity;
+    return vec4<f32>(min(base.rgb + glow, vec3<f32>(1.0)), base.a);
+}
+
+// Blend between the frame and its blurred copy
+@fragment
+fn fs_mix(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let base = base_color(in.position);
+    let blurred = textureSample(t_input, s_input, in.uv);
+    return mix(base, blurred, clamp(params.intensity, 0.0, 1.0));
+}
+
+// Darken towards the corners
+@fragment
+fn fs_vignette(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let base = base_color(in.position);
+    // 0 at the center, 1 at the corners
+    let distance = length(in.uv - vec2<f32>(0.5)) * 1.41421356;
+    let shade = 1.0 - smoothstep(params.radius, params.radius + params.softness, distance);
+    let factor = mix(1.0, shade, clamp(params.intensity, 0.0, 1.0));
+    return vec4<f32>(base.rgb * factor, base.a);
+}
+
+// Blurred silhouette (alpha of t_input) filled with params.color, for shadows and glows
+@fragment
+fn fs_tint(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let coverage = min(textureSample(t_input, s_input, in.uv).a * params.intensity, 1.0);
+    return vec4<f32>(params.color.rgb, params.color.a * coverage);
+}
@@ -0,0 +1,434 @@
+//! # GPU Frame Profiling
+//!
+//! [`Profiler`] writes GPU timestamps between the commands of a frame and
+//! splits the frame into spans: clearing, drawing shapes and text,
+//! post-processing and copies back to the CPU. A [`RenderGraph`] with
+//! profiling enabled marks the end of every pass, so each pass becomes one
+//! span (a clearing [`ScenePass`] becomes two). Profiles can be collected
+//! into a [`ChromeTrace`] and opened in `chrome://tracing` or Perfetto.
+//!
+//! Profiling needs `wgpu::Features::TIMESTAMP_QUERY` and
+//! `TIMESTAMP_QUERY_INSIDE_ENCODERS` (see [`Profiler::FEATURES`]) and waits
+//! for the GPU after every frame, so it is meant for investigation rather
+//! than shipping builds.
+//!
+//! ```rust,no_run
+//! use diomanim::pipeline::{frame_graph, RenderConfig};
+//! use diomanim::render::{ChromeTrace, Profiler, RendererDescriptor, ShapeRenderer};
+//! use diomanim::scene::SceneGraph;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let descriptor = RendererDescriptor::new(1280, 720).with_optional_features(Profiler::FEATURES);
+//! let mut renderer = ShapeRenderer::with_descriptor(&descriptor).await?;
+//! let scene = SceneGraph::new();
+//!
+//! let mut graph = frame_graph(&RenderConfig::new(1280, 720, 30, 1.0));
+//! graph.enable_profiling(&renderer);
+//! let mut trace = ChromeTrace::new();
+//! for _ in 0..10 {
+//!     graph.execute(&mut renderer, &scene, None)?;
+//!     if let Some(profile) = graph.take_profile() {
+//!         println!("{profile}");
+//!         trace.add_frame(&profile);
+//!     }
+//! }
+//! trace.save("frame_trace.json")?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`RenderGraph`]: super::RenderGraph
+//! [`ScenePass`]: super::graph::ScenePass
+
+use super::ShapeRenderer;
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+/// Timestamps one frame can hold; marks past this are dropped
+const MAX_TIMESTAMPS: u32 = 64;
+
+/// What a span of the frame spent its time on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum PassCategory {
+    /// Clearing a target before drawing
+    Clear,
+    /// Drawing shapes (and anything else that isn't text)
+    Shapes,
+    /// Drawing text and math
+    Text,
+    /// Fullscreen post-processing
+    Post,
+    /// Copying textures back to the CPU
+    Copy,
+    /// Custom passes
+    Other,
+}
+
+impl PassCategory {
+    /// Every category, in frame order
+    pub const ALL: [PassCategory; 6] = [
+        PassCategory::Clear,
+        PassCategory::Shapes,
+        PassCategory::Text,
+        PassCategory::Post,
+        PassCategory::Copy,
+        PassCategory::Other,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PassCategory::Clear => "clear",
+            PassCategory::Shapes => "shapes",
+            PassCategory::Text => "text",
+            PassCategory::Post => "post",
+            PassCategory::Copy => "copy",
+            PassCategory::Other => "other",
+        }
+    }
+}
+
+/// One timed stretch of a frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileSpan {
+    pub label: String,
+    pub category: PassCategory,
+    /// Offset from the start of the frame
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+/// GPU timings of one frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameProfile {
+    /// Frames profiled before this one
+    pub index: u64,
+    /// Start of the frame, relative to the first profiled frame
+    pub start: Duration,
+    /// Consecutive spans, in the order the GPU ran them
+    pub spans: Vec<ProfileSpan>,
+}
+
+impl FrameProfile {
+    /// GPU time from the first span's start to the last span's end
+    pub fn total(&self) -> Duration {
+        self.spans
+            .last()
+            .map_or(Duration::ZERO, |span| span.start + span.duration)
+    }
+
+    /// Time spent on `category`
+    pub fn category_total(&self, category: PassCategory) -> Duration {
+        self.spans
+            .iter()
+            .filter(|span| span.category == category)
+            .map(|span| span.duration)
+            .sum()
+    }
+
+    /// Time per category, skipping categories the frame didn't use
+    pub fn breakdown(&self) -> Vec<(PassCategory, Duration)> {
+        PassCategory::ALL
+            .into_iter()
+            .filter(|&category| self.spans.iter().any(|span| span.category == category))
+            .map(|category| (category, self.category_total(category)))
+            .collect()
+    }
+}
+
+impl fmt::Display for FrameProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "frame {}: {:.3} ms",
+            self.index,
+            self.total().as_secs_f64() * 1000.0
+        )?;
+        for (category, time) in self.breakdown() {
+            write!(
+                f,
+                " | {} {:.3} ms",
+                category.name(),
+                time.as_secs_f64() * 1000.0
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Records GPU timestamps between commands and reads them back per frame
+///
+/// Call [`Self::begin_frame`], then [`Self::mark`] after each piece of work
+/// to time, [`Self::resolve`] before finishing the encoder and
+/// [`Self::read`] after submitting it. [`super::RenderGraph::enable_profiling`]
+/// does all of this for graph passes.
+pub struct Profiler {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    /// Nanoseconds per timestamp tick
+    period: f32,
+    /// Label and category of the span ending at each timestamp after the first
+    marks: Vec<(String, PassCategory)>,
+    /// Timestamps written this frame
+    written: u32,
+    /// First timestamp of the first frame, in ticks
+    epoch: Option<u64>,
+    frames: u64,
+}
+
+impl Profiler {
+    /// Device features profiling needs
+    pub const FEATURES: wgpu::Features =
+        wgpu::Features::TIMESTAMP_QUERY.union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS);
+
+    /// Create a profiler, or `None` if the device lacks [`Self::FEATURES`]
+    pub fn new(renderer: &ShapeRenderer) -> Option<Self> {
+        let device = renderer.get_device();
+        if !device.features().contains(Self::FEATURES) {
+            return None;
+        }
+
+        let size = u64::from(MAX_TIMESTAMPS) * std::mem::size_of::<u64>() as u64;
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Profiler Queries"),
+            ty: wgpu::QueryType::Timestamp,
+            count: MAX_TIMESTAMPS,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Profiler Resolve Buffer"),
+            size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Profiler Readback Buffer"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            period: renderer.get_queue().get_timestamp_period(),
+            marks: Vec::new(),
+            written: 0,
+            epoch: None,
+            frames: 0,
+        })
+    }
+
+    /// Write the frame's first timestamp
+    pub fn begin_frame(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        self.marks.clear();
+        self.written = 0;
+        self.write_timestamp(encoder);
+    }
+
+    /// End a span labelled `label` here; it started at the previous mark
+    pub fn mark(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        label: &str,
+        category: PassCategory,
+    ) {
+        if self.written == 0 || self.written >= MAX_TIMESTAMPS {
+            return;
+        }
+        self.marks.push((label.to_string(), category));
+        self.write_timestamp(encoder);
+    }
+
+    fn write_timestamp(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.write_timestamp(&self.query_set, self.written);
+        self.written += 1;
+    }
+
+    /// Copy this frame's timestamps for [`Self::read`]
+    pub fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        if self.written == 0 {
+            return;
+        }
+        let size = u64::from(self.written) * std::mem::size_of::<u64>() as u64;
+        encoder.resolve_query_set(&self.query_set, 0..self.written, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &self.readback_buffer, 0, size);
+    }
+
+    /// Wait for the submitted frame and turn its timestamps into spans
+    pub fn read(
+        &mut self,
+        device: &wgpu::Device,
+    ) -> Result<FrameProfile, Box<dyn std::error::Error>> {
+        if self.written == 0 {
+            return Err("profiler frame was never begun".into());
+        }
+        let count = self.written as usize;
+        let slice = self
+            .readback_buffer
+            .slice(..(count * std::mem::size_of::<u64>()) as u64);
+        let (tx, rx) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        device.poll(wgpu::PollType::wait_indefinitely())?;
+        rx.recv()??;
+        let timestamps: Vec<u64> = bytemuck::pod_collect_to_vec(&slice.get_mapped_range());
+        self.readback_buffer.unmap();
+
+        let epoch = *self.epoch.get_or_insert(timestamps[0]);
+        let profile = build_profile(self.frames, epoch, &timestamps, &self.marks, self.period);
+        self.frames += 1;
+        self.written = 0;
+        Ok(profile)
+    }
+}
+
+/// Spans between consecutive `timestamps`, labelled by `marks`
+fn build_profile(
+    index: u64,
+    epoch: u64,
+    timestamps: &[u64],
+    marks: &[(String, PassCategory)],
+    period: f32,
+) -> FrameProfile {
+    let to_duration = |ticks: u64| Duration::from_nanos((ticks as f64 * f64::from(period)) as u64);
+    let frame_start = timestamps[0];
+    let spans = timestamps
+        .windows(2)
+        .zip(marks)
+        .map(|(pair, (label, category))| ProfileSpan {
+            label: label.clone(),
+            category: *category,
+            start: to_duration(pair[0].saturating_sub(frame_start)),
+            duration: to_duration(pair[1].saturating_sub(pair[0])),
+        })
+        .collect();
+    FrameProfile {
+        index,
+        start: to_duration(frame_start.saturating_sub(epoch)),
+        spans,
+    }
+}
+
+/// One complete event in the Chrome trace event format
+#[derive(Debug, Serialize)]
+struct TraceEvent {
+    name: String,
+    cat: &'static str,
+    ph: &'static str,
+    /// Microseconds
+    ts: f64,
+    /// Microseconds
+    dur: f64,
+    pid: u32,
+    tid: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TraceFile<'a> {
+    trace_events: &'a [TraceEvent],
+    display_time_unit: &'static str,
+}
+
+/// Frame profiles in the Chrome trace event format
+///
+/// Each frame is an event on one track with its spans nested under it.
+#[derive(Debug, Default)]
+pub struct ChromeTrace {
+    events: Vec<TraceEvent>,
+}
+
+impl ChromeTrace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `profile`'s frame and spans
+    pub fn add_frame(&mut self, profile: &FrameProfile) {
+        let micros = |time: Duration| time.as_secs_f64() * 1e6;
+        self.events.push(TraceEvent {
+            name: format!("frame {}", profile.index),
+            cat: "frame",
+            ph: "X",
+            ts: micros(profile.start),
+            dur: micros(profile.total()),
+            pid: 1,
+            tid: 1,
+        });
+        for span in &profile.spans {
+            self.events.push(TraceEvent {
+                name: span.label.clone(),
+                cat: span.category.name(),
+                ph: "X",
+                ts: micros(profile.start + span.start),
+                dur: micros(span.duration),
+                pid: 1,
+                tid: 1,
+            });
+        }
+    }
+
+    /// Number of recorded events (frames plus spans)
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(&TraceFile {
+            trace_events: &self.events,
+            display_time_unit: "ms",
+        })
+    }
+
+    /// Write the trace as JSON to `path`
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_spans_and_trace() {
+        let marks = vec![
+            ("scene clear".to_string(), PassCategory::Clear),
+            ("scene".to_string(), PassCategory::Shapes),
+            ("readback".to_string(), PassCategory::Copy),
+        ];
+        let profile = build_profile(2, 1_000, &[3_000, 3_500, 7_500, 9_000], &marks, 1.0);
+
+        assert_eq!(profile.start, Duration::from_micros(2));
+        assert_eq!(profile.spans[1].start, Duration::from_nanos(500));
+        assert_eq!(profile.spans[1].duration, Duration::from_micros(4));
+        assert_eq!(profile.total(), Duration::from_micros(6));
+        assert_eq!(
+            profile.breakdown(),
+            vec![
+                (PassCategory::Clear, Duration::from_nanos(500)),
+                (PassCategory::Shapes, Duration::from_micros(4)),
+                (PassCategory::Copy, Duration::from_nanos(1_500)),
+            ]
+        );
+
+        let mut trace = ChromeTrace::new();
+        trace.add_frame(&profile);
+        assert_eq!(trace.len(), 4);
+        let json: serde_json::Value = serde_json::from_str(&trace.to_json().unwrap()).unwrap();
+        let events = json["traceEvents"].as_array().unwrap();
+        assert_eq!(events[0]["name"], "frame 2");
+        assert_eq!(events[2]["cat"], "shapes");
+        assert_eq!(events[2]["ts"], 2.5);
+        assert_eq!(events[2]["dur"], 4.0);
+    }
+}
@@ -0,0 +1,246 @@
+//! GPU memory tracking
+//!
+//! A [`ResourceTracker`] records the long-lived buffers and textures a
+//! renderer allocates (render graph textures, render targets, uploaded
+//! images, the glyph atlas) with their labels and sizes, so a render can
+//! report how much GPU memory it holds. With a budget set it warns when an
+//! allocation would exceed it, or rejects the allocation outright, which
+//! turns a 4K render on a small GPU into a clear error instead of a driver
+//! crash. Transient per-draw vertex buffers are not tracked.
+//!
+//! Allocations are keyed by label: allocating a label again (say, a graph
+//! texture recreated at a new size) replaces its previous size.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::render::resources::{BudgetPolicy, ResourceKind, ResourceTracker};
+//!
+//! let mut tracker = ResourceTracker::new().with_budget(48 << 20, BudgetPolicy::Reject);
+//! let frame = ResourceTracker::texture_size(3840, 2160, wgpu::TextureFormat::Rgba8Unorm);
+//! tracker.try_allocate("graph frame", ResourceKind::Texture, frame).unwrap();
+//! assert_eq!(tracker.usage(), 3840 * 2160 * 4);
+//!
+//! // A second 4K texture doesn't fit in 48 MiB
+//! assert!(tracker.try_allocate("graph scene", ResourceKind::Texture, frame).is_err());
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Kind of GPU resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    Buffer,
+    Texture,
+}
+
+/// One tracked buffer or texture
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceAllocation {
+    pub kind: ResourceKind,
+    /// Size in bytes
+    pub size: u64,
+}
+
+/// What happens when an allocation would exceed the budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BudgetPolicy {
+    /// Allocate anyway and print a warning
+    #[default]
+    Warn,
+    /// Refuse allocations that can fail (see [`ResourceTracker::try_allocate`])
+    Reject,
+}
+
+/// Error for an allocation refused by a [`BudgetPolicy::Reject`] budget
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetExceeded {
+    pub label: String,
+    /// Bytes requested
+    pub size: u64,
+    /// Bytes in use before the allocation
+    pub usage: u64,
+    pub budget: u64,
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "allocating `{}` ({}) would exceed the GPU memory budget ({} of {} in use)",
+            self.label,
+            format_bytes(self.size),
+            format_bytes(self.usage),
+            format_bytes(self.budget)
+        )
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+/// Labelled GPU allocations, with an optional budget
+#[derive(Debug, Clone, Default)]
+pub struct ResourceTracker {
+    allocations: BTreeMap<String, ResourceAllocation>,
+    usage: u64,
+    peak: u64,
+    budget: Option<(u64, BudgetPolicy)>,
+    /// Whether the current overrun was already reported
+    warned: bool,
+}
+
+impl ResourceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit tracked allocations to `bytes`
+    pub fn with_budget(mut self, bytes: u64, policy: BudgetPolicy) -> Self {
+        self.set_budget(Some((bytes, policy)));
+        self
+    }
+
+    /// Set or remove the budget
+    pub fn set_budget(&mut self, budget: Option<(u64, BudgetPolicy)>) {
+        self.budget = budget;
+        self.warned = false;
+    }
+
+    /// Budget in bytes and its policy, if one is set
+    pub fn budget(&self) -> Option<(u64, BudgetPolicy)> {
+        self.budget
+    }
+
+    /// Bytes currently allocated
+    pub fn usage(&self) -> u64 {
+        self.usage
+    }
+
+    /// Most bytes allocated at once
+    pub fn peak(&self) -> u64 {
+        self.peak
+    }
+
+    /// Tracked allocations by label
+    pub fn allocations(&self) -> impl Iterator<Item = (&str, &ResourceAllocation)> {
+        self.allocations
+            .iter()
+            .map(|(label, allocation)| (label.as_str(), allocation))
+    }
+
+    /// Bytes of an uncompressed 2D texture
+    pub fn texture_size(width: u32, height: u32, format: wgpu::TextureFormat) -> u64 {
+        // Combined depth-stencil formats have no single copy size
+        let texel = format.block_copy_size(None).unwrap_or(4);
+        u64::from(width) * u64::from(height) * u64::from(texel)
+    }
+
+    /// Usage after `label` is (re)allocated with `size` bytes
+    fn usage_with(&self, label: &str, size: u64) -> u64 {
+        let previous = self.allocations.get(label).map_or(0, |a| a.size);
+        self.usage - previous + size
+    }
+
+    /// Record an allocation that can be skipped, refusing it if a
+    /// [`BudgetPolicy::Reject`] budget would be exceeded
+    pub fn try_allocate(
+        &mut self,
+        label: &str,
+        kind: ResourceKind,
+        size: u64,
+    ) -> Result<(), BudgetExceeded> {
+        if let Some((budget, BudgetPolicy::Reject)) = self.budget {
+            if self.usage_with(label, size) > budget {
+                return Err(BudgetExceeded {
+                    label: label.to_string(),
+                    size,
+                    usage: self.usage,
+                    budget,
+                });
+            }
+        }
+        self.allocate(label, kind, size);
+        Ok(())
+    }
+
+    /// Record an allocation, warning once when it takes usage over budget
+    pub fn allocate(&mut self, label: &str, kind: ResourceKind, size: u64) {
+        self.usage = self.usage_with(label, size);
+        self.peak = self.peak.max(self.usage);
+        self.allocations
+            .insert(label.to_string(), ResourceAllocation { kind, size });
+        match self.budget {
+            Some((budget, _)) if self.usage > budget => {
+                if !self.warned {
+                    eprintln!(
+                        "GPU memory budget exceeded: {} of {} in use after allocating `{label}`",
+                        format_bytes(self.usage),
+                        format_bytes(budget)
+                    );
+                    self.warned = true;
+                }
+            }
+            _ => self.warned = false,
+        }
+    }
+
+    /// Forget the allocation of `label`, once its resource is dropped
+    pub fn free(&mut self, label: &str) {
+        if let Some(allocation) = self.allocations.remove(label) {
+            self.usage -= allocation.size;
+        }
+    }
+}
+
+/// `bytes` in the largest binary unit that keeps it at least 1
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_replaces_and_frees_by_label() {
+        let mut tracker = ResourceTracker::new().with_budget(1000, BudgetPolicy::Warn);
+        tracker.allocate("a", ResourceKind::Texture, 400);
+        tracker.allocate("b", ResourceKind::Buffer, 300);
+        tracker.allocate("a", ResourceKind::Texture, 600);
+        assert_eq!(tracker.usage(), 900);
+
+        // Warn budgets still allocate
+        tracker.allocate("c", ResourceKind::Buffer, 200);
+        assert_eq!(tracker.usage(), 1100);
+        tracker.free("a");
+        tracker.free("missing");
+        assert_eq!(tracker.usage(), 500);
+        assert_eq!(tracker.peak(), 1100);
+        let labels: Vec<&str> = tracker.allocations().map(|(label, _)| label).collect();
+        assert_eq!(labels, ["b", "c"]);
+
+        tracker.set_budget(Some((600, BudgetPolicy::Reject)));
+        let error = tracker
+            .try_allocate("d", ResourceKind::Texture, 200)
+            .unwrap_err();
+        assert_eq!(error.usage, 500);
+        assert_eq!(tracker.usage(), 500);
+        // Shrinking an existing allocation always fits
+        tracker
+            .try_allocate("b", ResourceKind::Buffer, 100)
+            .unwrap();
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+    }
+}
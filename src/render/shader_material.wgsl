@@ -0,0 +1,55 @@
+// Prelude of custom shader materials: the material's snippet is appended
+// and defines `fn shade(in: ShadeInput) -> vec4<f32>`
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) local: vec3<f32>,
+    @location(2) @interpolate(flat) instance: u32,
+};
+
+struct Uniforms {
+    model_view_proj: mat4x4<f32>,
+};
+
+// time = (seconds, unused, unused, unused)
+struct MaterialObject {
+    params: array<vec4<f32>, 4>,
+    time: vec4<f32>,
+};
+
+struct ShadeInput {
+    color: vec4<f32>,
+    local: vec3<f32>,
+    time: f32,
+    params: array<vec4<f32>, 4>,
+};
+
+// Transforms and materials are parallel arrays indexed by the draw's instance
+@group(0) @binding(0) var<storage, read> transforms: array<Uniforms>;
+@group(1) @binding(0) var<storage, read> materials: array<MaterialObject>;
+
+@vertex
+fn vs_main(model: VertexInput, @builtin(instance_index) instance: u32) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = transforms[instance].model_view_proj * vec4<f32>(model.position, 1.0);
+    out.color = model.color;
+    out.local = model.position;
+    out.instance = instance;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let material = materials[in.instance];
+    var input: ShadeInput;
+    input.color = in.color;
+    input.local = in.local;
+    input.time = material.time.x;
+    input.params = material.params;
+    return shade(input);
+}
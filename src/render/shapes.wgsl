@@ -13,13 +13,14 @@ struct Uniforms {
     model_view_proj: mat4x4<f32>,
 };
 
-@group(0) @binding(0) var<uniform> uniforms: Uniforms;
+// One entry per draw, selected by the draw's instance
+@group(0) @binding(0) var<storage, read> transforms: array<Uniforms>;
 
 @vertex 
-fn vs_main(model: VertexInput) -> VertexOutput {
+fn vs_main(model: VertexInput, @builtin(instance_index) instance: u32) -> VertexOutput {
     var out: VertexOutput;
     let world_pos = vec4<f32>(model.position, 1.0);
-    out.clip_position = uniforms.model_view_proj * world_pos;
+    out.clip_position = transforms[instance].model_view_proj * world_pos;
     out.color = model.color;
     return out;
 }
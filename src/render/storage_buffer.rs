@@ -0,0 +1,205 @@
+//! # Per-Draw Storage Buffers
+//!
+//! Transforms and lit object data are written into one storage buffer per
+//! kind, an array the vertex shader indexes with `@builtin(instance_index)`.
+//! A draw picks its entry through the first instance of its instance range,
+//! so the bind group is set once per pass rather than once per object, and
+//! objects with the same geometry can be drawn in one instanced call.
+//!
+//! [`StorageArray`] grows its buffer when a frame draws more objects than it
+//! holds: a buffer twice the size is created, the entries written so far are
+//! copied over and the bind group is rebuilt. Draws recorded before the
+//! growth keep using the old buffer, which stays alive until they are
+//! submitted, so no entry is overwritten within a frame.
+
+use std::cell::RefCell;
+use std::ops::Range;
+
+/// A growable GPU array of fixed-size entries bound as a read-only storage buffer
+pub(super) struct StorageArray {
+    label: &'static str,
+    layout: wgpu::BindGroupLayout,
+    /// Bytes per entry
+    stride: u64,
+    state: RefCell<Entries>,
+}
+
+struct Entries {
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    capacity: u32,
+    /// Entries written this frame
+    len: u32,
+    /// Bumped whenever the buffer (and so the bind group) is replaced
+    generation: u64,
+    /// Whether running out of entries at the device limit was reported
+    overflow_reported: bool,
+}
+
+impl StorageArray {
+    pub fn new(
+        device: &wgpu::Device,
+        label: &'static str,
+        layout: wgpu::BindGroupLayout,
+        stride: u64,
+        capacity: u32,
+    ) -> Self {
+        let (buffer, bind_group) = Self::allocate(device, label, &layout, stride, capacity);
+        Self {
+            label,
+            layout,
+            stride,
+            state: RefCell::new(Entries {
+                buffer,
+                bind_group,
+                capacity,
+                len: 0,
+                generation: 0,
+                overflow_reported: false,
+            }),
+        }
+    }
+
+    fn allocate(
+        device: &wgpu::Device,
+        label: &str,
+        layout: &wgpu::BindGroupLayout,
+        stride: u64,
+        capacity: u32,
+    ) -> (wgpu::Buffer, wgpu::BindGroup) {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: stride * u64::from(capacity),
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(label),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+        (buffer, bind_group)
+    }
+
+    /// Append `data` (a whole number of entries) and return the indices written
+    pub fn push(&self, device: &wgpu::Device, queue: &wgpu::Queue, data: &[u8]) -> Range<u32> {
+        let count = (data.len() as u64 / self.stride) as u32;
+        let mut state = self.state.borrow_mut();
+        let start = self.reserve(device, queue, &mut state, count);
+        queue.write_buffer(
+            &state.buffer,
+            u64::from(start) * self.stride,
+            &data[..(u64::from(count) * self.stride) as usize],
+        );
+        start..start + count
+    }
+
+    /// Write one entry at `index`, growing the array to hold it
+    pub fn write(&self, device: &wgpu::Device, queue: &wgpu::Queue, index: u32, data: &[u8]) {
+        let mut state = self.state.borrow_mut();
+        if index >= state.len {
+            let missing = index + 1 - state.len;
+            self.reserve(device, queue, &mut state, missing);
+        }
+        if index < state.capacity {
+            queue.write_buffer(&state.buffer, u64::from(index) * self.stride, data);
+        }
+    }
+
+    /// Make room for `count` more entries and return the first of them
+    ///
+    /// At the device's buffer size limit the buffer is kept and entries are
+    /// reused from the start, which corrupts earlier draws of the frame.
+    fn reserve(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        state: &mut Entries,
+        count: u32,
+    ) -> u32 {
+        let needed = state.len.saturating_add(count);
+        if needed > state.capacity {
+            let limits = device.limits();
+            let max_entries = (limits
+                .max_buffer_size
+                .min(u64::from(limits.max_storage_buffer_binding_size))
+                / self.stride)
+                .min(u64::from(u32::MAX)) as u32;
+            let capacity = needed
+                .checked_next_power_of_two()
+                .unwrap_or(u32::MAX)
+                .max(state.capacity.saturating_mul(2))
+                .min(max_entries);
+            if capacity < needed {
+                if !state.overflow_reported {
+                    eprintln!(
+                        "{}: more than {} draws in one frame, earlier draws will be corrupted",
+                        self.label, state.capacity
+                    );
+                    state.overflow_reported = true;
+                }
+                state.len = count.min(state.capacity);
+                return 0;
+            }
+            self.grow(device, queue, state, capacity);
+        }
+
+        let start = state.len;
+        state.len = needed;
+        start
+    }
+
+    /// Replace the buffer with one of `capacity` entries, keeping those written so far
+    fn grow(&self, device: &wgpu::Device, queue: &wgpu::Queue, state: &mut Entries, capacity: u32) {
+        let (buffer, bind_group) =
+            Self::allocate(device, self.label, &self.layout, self.stride, capacity);
+        // Submitted right away: queued writes to the old buffer land first,
+        // and later writes to the new buffer land after the copy
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Storage Buffer Growth"),
+        });
+        encoder.copy_buffer_to_buffer(
+            &state.buffer,
+            0,
+            &buffer,
+            0,
+            u64::from(state.len) * self.stride,
+        );
+        queue.submit(std::iter::once(encoder.finish()));
+
+        state.buffer = buffer;
+        state.bind_group = bind_group;
+        state.capacity = capacity;
+        state.generation += 1;
+    }
+
+    /// Layout of [`Self::bind_group`]
+    pub fn layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    /// Bind group holding every entry written this frame
+    pub fn bind_group(&self) -> wgpu::BindGroup {
+        self.state.borrow().bind_group.clone()
+    }
+
+    /// Changes whenever [`Self::bind_group`] does
+    pub fn generation(&self) -> u64 {
+        self.state.borrow().generation
+    }
+
+    /// Start writing entries from the beginning (call once per frame)
+    pub fn reset(&self) {
+        self.state.borrow_mut().len = 0;
+    }
+
+    /// Number of entries the buffer currently holds
+    pub fn capacity(&self) -> u32 {
+        self.state.borrow().capacity
+    }
+}
@@ -0,0 +1,393 @@
+//! SVG snapshots of a scene
+//!
+//! [`export_svg`] writes the renderables visible at one moment as an SVG
+//! document, for crisp figures in papers and slides. Every node becomes a
+//! group carrying its world transform inside a group that maps scene units
+//! (-1..1, y up) onto the frame, so shapes keep their exact geometry at any
+//! zoom.
+//!
+//! Text is set in the viewer's fonts at the size it renders at; math is
+//! placed by the math layout engine. Images and SVG assets, clip masks, node
+//! and post effects, lighting and deformers are not exported.
+//!
+//! ```rust,no_run
+//! use diomanim::core::Color;
+//! use diomanim::render::export_svg;
+//! use diomanim::scene::SceneGraph;
+//!
+//! let mut scene = SceneGraph::new();
+//! scene.add_circle("dot", 0.2, Color::RED).fade_in(0.0, 1.0);
+//! export_svg(&mut scene, 0.5, "output/figure.svg")?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use super::arrow_geometry;
+use crate::core::path::{Path as ScenePath, PathSegment};
+use crate::core::{Color, TimeValue, Vector3};
+use crate::scene::{Renderable, SceneGraph, SceneNode};
+use crate::text::{PathTextAlign, RichText, TextPath};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Size of the documents [`export_svg`] writes
+pub const SVG_SIZE: (u32, u32) = (1920, 1080);
+
+/// Scene units per pixel of text laid out at its font size, for text drawn
+/// with the 48px glyph atlas the preview and render server use
+pub(crate) const TEXT_UNIT: f32 = 48.0 / 1000.0;
+
+/// Advance `scene` by `time` seconds and write what is visible as an SVG
+/// document of [`SVG_SIZE`]
+///
+/// Animations are advanced from the scene's current state, so pass a freshly
+/// built scene to get the frame at `time`.
+pub fn export_svg(
+    scene: &mut SceneGraph,
+    time: f32,
+    path: impl AsRef<Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    advance_scene(scene, time);
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, scene_to_svg(scene, SVG_SIZE.0, SVG_SIZE.1))?;
+    Ok(())
+}
+
+/// Play `scene`'s animations forward `time` seconds in one step, for
+/// snapshot exports
+pub(crate) fn advance_scene(scene: &mut SceneGraph, time: f32) {
+    scene.update_transforms();
+    if time > 0.0 {
+        scene.update_animations(TimeValue::new(time));
+        scene.update_transforms();
+    }
+}
+
+/// The visible renderables of `scene`, as last updated, as an SVG document
+/// of `width` x `height` pixels on the theme's background
+pub fn scene_to_svg(scene: &SceneGraph, width: u32, height: u32) -> String {
+    let half_width = width as f32 / 2.0;
+    let half_height = height as f32 / 2.0;
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    );
+    let _ = writeln!(
+        svg,
+        r#"  <rect width="100%" height="100%" fill="{}"/>"#,
+        scene.theme().background.to_hex()
+    );
+    let _ = writeln!(
+        svg,
+        r#"  <g transform="matrix({half_width} 0 0 {} {half_width} {half_height})">"#,
+        -half_height
+    );
+    for node in scene.visible_renderable_nodes() {
+        if let Some((renderable, opacity)) = node.drawn_renderable() {
+            write_node(&mut svg, node, &renderable, opacity);
+        }
+    }
+    svg.push_str("  </g>\n</svg>\n");
+    svg
+}
+
+/// A group with the node's world transform and `opacity` around `renderable`
+fn write_node(svg: &mut String, node: &SceneNode, renderable: &Renderable, opacity: f32) {
+    let [x_axis, y_axis, _, translation] = node.world_transform.matrix().to_cols_array_2d();
+    let _ = write!(
+        svg,
+        r#"    <g transform="matrix({} {} {} {} {} {})""#,
+        x_axis[0], x_axis[1], y_axis[0], y_axis[1], translation[0], translation[1]
+    );
+    if opacity < 1.0 {
+        let _ = write!(svg, r#" opacity="{opacity}""#);
+    }
+    svg.push_str(">\n");
+
+    match renderable {
+        Renderable::Circle { radius, color } => {
+            let _ = writeln!(svg, r#"      <circle r="{radius}"{}/>"#, fill(*color));
+        }
+        Renderable::Rectangle {
+            width,
+            height,
+            color,
+        } => {
+            let _ = writeln!(
+                svg,
+                r#"      <rect x="{}" y="{}" width="{width}" height="{height}"{}/>"#,
+                -width / 2.0,
+                -height / 2.0,
+                fill(*color)
+            );
+        }
+        Renderable::Polygon { vertices, color } => {
+            let _ = writeln!(
+                svg,
+                r#"      <polygon points="{}"{}/>"#,
+                points(vertices),
+                fill(*color)
+            );
+        }
+        Renderable::Line {
+            start,
+            end,
+            color,
+            thickness,
+        } => write_line(svg, *start, *end, *color, *thickness),
+        Renderable::Polyline {
+            points: vertices,
+            color,
+            thickness,
+            ..
+        } => {
+            let _ = write!(
+                svg,
+                r#"      <polyline points="{}" fill="none" stroke="{}" stroke-width="{}""#,
+                points(vertices),
+                color.to_hex(),
+                thickness / 100.0
+            );
+            if color.a < 1.0 {
+                let _ = write!(svg, r#" stroke-opacity="{}""#, color.a);
+            }
+            svg.push_str("/>\n");
+        }
+        Renderable::Arrow {
+            start,
+            end,
+            color,
+            thickness,
+        } => {
+            if let Some((shaft_end, tip)) = arrow_geometry(*start, *end) {
+                write_line(svg, *start, shaft_end, *color, *thickness);
+                let _ = writeln!(
+                    svg,
+                    r#"      <polygon points="{}"{}/>"#,
+                    points(&tip),
+                    fill(*color)
+                );
+            }
+        }
+        Renderable::Text {
+            content,
+            font_size,
+            color,
+        } => {
+            let _ = writeln!(
+                svg,
+                r#"      <text transform="scale(1 -1)" font-family="sans-serif" font-size="{}"{}>{}</text>"#,
+                font_size * TEXT_UNIT,
+                fill(*color),
+                escape_xml(content)
+            );
+        }
+        Renderable::Math {
+            latex,
+            font_size,
+            color,
+        } => write_math(svg, latex, *font_size, *color),
+        Renderable::RichText {
+            text,
+            font_size,
+            color,
+        } => write_rich_text(svg, text, *font_size, *color),
+        Renderable::TextOnPath {
+            content,
+            path,
+            font_size,
+            color,
+        } => write_text_on_path(svg, node, content, path, *font_size, *color),
+        Renderable::Image { .. } | Renderable::Svg { .. } => {}
+    }
+    svg.push_str("    </g>\n");
+}
+
+/// Stroke `thickness` wide, in the renderer's line units (1/100 scene unit)
+fn write_line(svg: &mut String, start: Vector3, end: Vector3, color: Color, thickness: f32) {
+    let _ = write!(
+        svg,
+        r#"      <line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}""#,
+        start.x,
+        start.y,
+        end.x,
+        end.y,
+        color.to_hex(),
+        thickness / 100.0
+    );
+    if color.a < 1.0 {
+        let _ = write!(svg, r#" stroke-opacity="{}""#, color.a);
+    }
+    svg.push_str("/>\n");
+}
+
+/// Math elements at their layout positions (y grows down, hence the flip)
+fn write_math(svg: &mut String, latex: &str, font_size: f32, color: Color) {
+    use crate::math::{expression::parse_latex, layout::MathLayout};
+
+    let layout = MathLayout::layout_node(&parse_latex(latex), font_size);
+    let _ = writeln!(
+        svg,
+        r#"      <g transform="scale(1 -1)" font-family="serif" font-style="italic"{}>"#,
+        fill(color)
+    );
+    for (position, text, size) in layout.flatten() {
+        let _ = writeln!(
+            svg,
+            r#"        <text x="{}" y="{}" font-size="{}">{}</text>"#,
+            position.x * TEXT_UNIT,
+            position.y * TEXT_UNIT,
+            size * TEXT_UNIT,
+            escape_xml(&text)
+        );
+    }
+    svg.push_str("      </g>\n");
+}
+
+/// One `<text>` with a `<tspan>` per span; math spans are set in italics
+fn write_rich_text(svg: &mut String, text: &RichText, font_size: f32, color: Color) {
+    let _ = write!(
+        svg,
+        r#"      <text transform="scale(1 -1)" font-family="sans-serif" font-size="{}"{}>"#,
+        font_size * TEXT_UNIT,
+        fill(color)
+    );
+    for span in &text.spans {
+        svg.push_str("<tspan");
+        if let Some(span_color) = span.style.color {
+            svg.push_str(&fill(span_color.with_opacity(span_color.a * color.a)));
+        }
+        if span.style.bold {
+            svg.push_str(r#" font-weight="bold""#);
+        }
+        if span.math {
+            svg.push_str(r#" font-family="serif" font-style="italic""#);
+        }
+        if span.style.scale != 1.0 {
+            let _ = write!(
+                svg,
+                r#" font-size="{}""#,
+                font_size * span.style.scale * TEXT_UNIT
+            );
+        }
+        let _ = write!(svg, ">{}</tspan>", escape_xml(&span.text));
+    }
+    svg.push_str("</text>\n");
+}
+
+/// A `<textPath>` along the path mirrored into the text's flipped space
+fn write_text_on_path(
+    svg: &mut String,
+    node: &SceneNode,
+    content: &str,
+    text_path: &TextPath,
+    font_size: f32,
+    color: Color,
+) {
+    let id = format!("text-path-{}", node.id.0);
+    let length = text_path.path.length();
+    let (offset, anchor) = match text_path.align {
+        PathTextAlign::Start => (text_path.offset, "start"),
+        PathTextAlign::Center => (length / 2.0 + text_path.offset, "middle"),
+        PathTextAlign::End => (length + text_path.offset, "end"),
+    };
+    let _ = writeln!(
+        svg,
+        r##"      <g transform="scale(1 -1)"><defs><path id="{id}" d="{}"/></defs><text font-family="sans-serif" font-size="{}" text-anchor="{anchor}"{}><textPath xlink:href="#{id}" startOffset="{offset}">{}</textPath></text></g>"##,
+        path_data(&text_path.path, -1.0),
+        font_size * TEXT_UNIT,
+        fill(color),
+        escape_xml(content)
+    );
+}
+
+/// `fill` (and `fill-opacity` when translucent) attributes
+fn fill(color: Color) -> String {
+    if color.a < 1.0 {
+        format!(r#" fill="{}" fill-opacity="{}""#, color.to_hex(), color.a)
+    } else {
+        format!(r#" fill="{}""#, color.to_hex())
+    }
+}
+
+fn points(vertices: &[Vector3]) -> String {
+    vertices
+        .iter()
+        .map(|vertex| format!("{},{}", vertex.x, vertex.y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// SVG path data for `path`, with y multiplied by `y_scale`
+fn path_data(path: &ScenePath, y_scale: f32) -> String {
+    // Adding zero turns -0 into 0
+    let point = |p: Vector3| format!("{} {}", p.x, p.y * y_scale + 0.0);
+    let mut data = format!("M {}", point(path.start));
+    for segment in &path.segments {
+        let _ = match *segment {
+            PathSegment::Line(end) => write!(data, " L {}", point(end)),
+            PathSegment::Quadratic(control, end) => {
+                write!(data, " Q {} {}", point(control), point(end))
+            }
+            PathSegment::Cubic(c1, c2, end) => {
+                write!(data, " C {} {} {}", point(c1), point(c2), point(end))
+            }
+        };
+    }
+    if path.closed {
+        data.push_str(" Z");
+    }
+    data
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::transform::Quaternion;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_scene_to_svg() {
+        let mut scene = SceneGraph::new();
+        scene.add_circle("dot", 0.25, Color::RED).at(0.5, 0.0, 0.0);
+        let square = scene.add_square("square", 0.5, Color::BLUE).build();
+        scene
+            .get_node_mut(square)
+            .unwrap()
+            ._local_transform
+            .rotation = Quaternion::from_rotation_z(FRAC_PI_2);
+        scene.add_text("label", "a < b", 24.0, Color::WHITE);
+        let hidden = scene.add_circle("hidden", 0.1, Color::RED).build();
+        scene.get_node_mut(hidden).unwrap().visible = false;
+        scene.update_transforms();
+
+        let svg = scene_to_svg(&scene, 200, 100);
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains(r#"viewBox="0 0 200 100""#));
+        assert!(svg.contains(r#"<g transform="matrix(100 0 0 -50 100 50)">"#));
+        assert!(svg.contains(r#"<g transform="matrix(1 0 0 1 0.5 0)">"#));
+        assert!(svg.contains(r##"<circle r="0.25" fill="#ff0000"/>"##));
+        assert!(svg.contains(r#"<rect x="-0.25" y="-0.25" width="0.5" height="0.5""#));
+        assert!(svg.contains(">a &lt; b</text>"));
+        assert_eq!(svg.matches("<circle").count(), 1);
+    }
+
+    #[test]
+    fn test_path_data() {
+        let path = ScenePath::new(Vector3::new(0.0, 1.0, 0.0))
+            .line_to(Vector3::new(1.0, 1.0, 0.0))
+            .quadratic_to(Vector3::new(2.0, 2.0, 0.0), Vector3::new(3.0, 0.0, 0.0))
+            .close();
+        assert_eq!(path_data(&path, -1.0), "M 0 -1 L 1 -1 Q 2 -2 3 0 L 0 -1 Z");
+    }
+}
@@ -6,7 +6,7 @@ struct TransformUniform {
 };
 
 @group(0) @binding(0)
-var<uniform> transform: TransformUniform;
+var<storage, read> transforms: array<TransformUniform>;
 
 @group(1) @binding(0)
 var atlas_texture: texture_2d<f32>;
@@ -27,9 +27,9 @@ struct VertexOutput {
 };
 
 @vertex
-fn vs_main(in: VertexInput) -> VertexOutput {
+fn vs_main(in: VertexInput, @builtin(instance_index) instance: u32) -> VertexOutput {
     var out: VertexOutput;
-    out.clip_position = transform.model_view_proj * vec4<f32>(in.position, 1.0);
+    out.clip_position = transforms[instance].model_view_proj * vec4<f32>(in.position, 1.0);
     out.uv = in.uv;
     out.color = in.color;
     return out;
@@ -37,9 +37,10 @@ fn vs_main(in: VertexInput) -> VertexOutput {
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    // Sample the texture atlas
-    let alpha = textureSample(atlas_texture, atlas_sampler, in.uv).a;
+    // Sample the texture atlas: outline glyphs are white, color glyphs
+    // (emoji) carry their own colors
+    let texel = textureSample(atlas_texture, atlas_sampler, in.uv);
 
-    // Multiply text color by glyph alpha
-    return vec4<f32>(in.color.rgb, in.color.a * alpha);
+    // Tint by the text color and apply glyph coverage
+    return vec4<f32>(in.color.rgb * texel.rgb, in.color.a * texel.a);
 }
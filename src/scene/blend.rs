@@ -0,0 +1,119 @@
+//! Blend Modes
+//!
+//! How a node's colors combine with what is already drawn behind it. Every
+//! mode maps to a variant of the renderer's pipelines:
+//!
+//! - **Normal**: straight alpha blending (the default)
+//! - **Additive**: adds the color, scaled by its alpha, for glows and light
+//! - **Multiply**: darkens by multiplying with the background
+//! - **Screen**: lightens by multiplying the inverted colors
+//!
+//! Multiply and screen blend premultiplied colors, which shapes (and
+//! vector images) provide; text and raster images drawn with them blend
+//! normally.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::scene::*;
+//! use diomanim::core::*;
+//!
+//! let mut scene = SceneGraph::new();
+//! scene.add_circle("red", 0.4, Color::RED).at(-0.2, 0.0, 0.0).blend(BlendMode::Additive);
+//! scene.add_circle("green", 0.4, Color::GREEN).at(0.2, 0.0, 0.0).blend(BlendMode::Additive);
+//! ```
+
+use crate::core::Color;
+
+/// How a node's colors combine with the pixels behind it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Additive,
+    Multiply,
+    Screen,
+}
+
+impl BlendMode {
+    /// Whether the mode blends colors premultiplied by their alpha
+    pub fn is_premultiplied(self) -> bool {
+        matches!(self, Self::Multiply | Self::Screen)
+    }
+
+    /// `color` faded by `opacity`, premultiplied if the mode expects it
+    pub fn apply_opacity(self, color: Color, opacity: f32) -> Color {
+        let alpha = color.a * opacity;
+        if self.is_premultiplied() {
+            Color::rgba(color.r * alpha, color.g * alpha, color.b * alpha, alpha)
+        } else {
+            Color::rgba(color.r, color.g, color.b, alpha)
+        }
+    }
+
+    /// Result of blending `src` over `dst` (both straight alpha, `dst`
+    /// opaque), mirroring the GPU blend states
+    pub fn composite(self, src: Color, dst: Color) -> Color {
+        let a = src.a;
+        let channel = |s: f32, d: f32| match self {
+            Self::Normal => s * a + d * (1.0 - a),
+            Self::Additive => (s * a + d).min(1.0),
+            Self::Multiply => s * a * d + d * (1.0 - a),
+            Self::Screen => s * a + d * (1.0 - s * a),
+        };
+        Color::rgba(
+            channel(src.r, dst.r),
+            channel(src.g, dst.g),
+            channel(src.b, dst.b),
+            1.0,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Color, b: Color) -> bool {
+        (a.r - b.r).abs() < 1e-5 && (a.g - b.g).abs() < 1e-5 && (a.b - b.b).abs() < 1e-5
+    }
+
+    #[test]
+    fn test_blend_mode_composite() {
+        let grey = Color::new(0.5, 0.5, 0.5);
+        let red = Color::RED;
+        assert!(close(BlendMode::Normal.composite(red, grey), red));
+        assert!(close(
+            BlendMode::Additive.composite(red, grey),
+            Color::new(1.0, 0.5, 0.5)
+        ));
+        assert!(close(
+            BlendMode::Multiply.composite(red, grey),
+            Color::new(0.5, 0.0, 0.0)
+        ));
+        assert!(close(
+            BlendMode::Screen.composite(red, grey),
+            Color::new(1.0, 0.5, 0.5)
+        ));
+
+        // A transparent source leaves the background alone in every mode
+        let clear = Color::rgba(0.2, 0.9, 0.4, 0.0);
+        for mode in [
+            BlendMode::Normal,
+            BlendMode::Additive,
+            BlendMode::Multiply,
+            BlendMode::Screen,
+        ] {
+            assert!(close(mode.composite(clear, grey), grey));
+        }
+    }
+
+    #[test]
+    fn test_premultiplied_opacity() {
+        let color = BlendMode::Multiply.apply_opacity(Color::rgba(1.0, 0.5, 0.0, 0.8), 0.5);
+        assert!(close(color, Color::new(0.4, 0.2, 0.0)));
+        assert!((color.a - 0.4).abs() < 1e-6);
+        let color = BlendMode::Normal.apply_opacity(Color::rgba(1.0, 0.5, 0.0, 0.8), 0.5);
+        assert!(close(color, Color::new(1.0, 0.5, 0.0)));
+    }
+}
@@ -0,0 +1,104 @@
+//! Bounding boxes of nodes
+//!
+//! The smallest axis-aligned box around what a node draws, for placing
+//! things next to it: highlight frames, labels and braces. Shapes are
+//! measured along their outline (see [`Renderable::outline`]); text is
+//! estimated from its length and font size, since the scene doesn't know
+//! the font's glyph shapes.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::core::*;
+//! use diomanim::scene::SceneGraph;
+//!
+//! let mut scene = SceneGraph::new();
+//! let card = scene.add_rectangle("card", 0.4, 0.2, Color::BLUE).at(0.5, 0.0, 0.0).build();
+//! scene.update_transforms();
+//!
+//! let (min, max) = scene.bounds(card).unwrap();
+//! assert!((min - Vector3::new(0.3, -0.1, 0.0)).length() < 1e-5);
+//! assert!((max - Vector3::new(0.7, 0.1, 0.0)).length() < 1e-5);
+//! ```
+
+use super::{NodeId, Renderable, SceneGraph};
+use crate::core::Vector3;
+
+impl Renderable {
+    /// `(min, max)` corners of the box around the renderable in its local
+    /// coordinates
+    pub fn local_bounds(&self) -> (Vector3, Vector3) {
+        // Same estimate as the renderer uses without a font
+        let text = |characters: usize, font_size: f32| {
+            let half_width = 0.3 * font_size / 1000.0 * characters as f32;
+            let half_height = font_size / 2000.0;
+            (
+                Vector3::new(-half_width, -half_height, 0.0),
+                Vector3::new(half_width, half_height, 0.0),
+            )
+        };
+        match self {
+            Renderable::Text {
+                content, font_size, ..
+            } => text(content.chars().count(), *font_size),
+            Renderable::Math {
+                latex, font_size, ..
+            } => text(latex.chars().count(), *font_size),
+            Renderable::RichText {
+                text: rich,
+                font_size,
+                ..
+            } => text(rich.plain_text().chars().count(), *font_size),
+            Renderable::TextOnPath {
+                path, font_size, ..
+            } => {
+                let half_height = font_size / 2000.0;
+                let (min, max) =
+                    enclosing(path.path.flatten()).unwrap_or((Vector3::zero(), Vector3::zero()));
+                (
+                    min - Vector3::new(half_height, half_height, 0.0),
+                    max + Vector3::new(half_height, half_height, 0.0),
+                )
+            }
+            _ => self
+                .outline()
+                .and_then(|outline| enclosing(outline.flatten()))
+                .unwrap_or((Vector3::zero(), Vector3::zero())),
+        }
+    }
+}
+
+impl SceneGraph {
+    /// `(min, max)` corners of the box around everything `id` and its
+    /// subtree draw, in scene coordinates as of the last transform update
+    /// (`None` if nothing in it has a renderable)
+    pub fn bounds(&self, id: NodeId) -> Option<(Vector3, Vector3)> {
+        let corners = self
+            .subtree(id)
+            .into_iter()
+            .filter_map(|id| {
+                let node = self.nodes.get(&id)?;
+                let (min, max) = node.renderable.as_ref()?.local_bounds();
+                let corners = [
+                    Vector3::new(min.x, min.y, 0.0),
+                    Vector3::new(max.x, min.y, 0.0),
+                    Vector3::new(max.x, max.y, 0.0),
+                    Vector3::new(min.x, max.y, 0.0),
+                ];
+                Some(corners.map(|corner| node.world_transform.transform_point(corner)))
+            })
+            .flatten();
+        enclosing(corners)
+    }
+}
+
+/// `(min, max)` corners of the box around `points` in the xy plane
+fn enclosing(points: impl IntoIterator<Item = Vector3>) -> Option<(Vector3, Vector3)> {
+    points.into_iter().fold(None, |bounds, p| {
+        let (min, max) = bounds.unwrap_or((p, p));
+        Some((
+            Vector3::new(min.x.min(p.x), min.y.min(p.y), 0.0),
+            Vector3::new(max.x.max(p.x), max.y.max(p.y), 0.0),
+        ))
+    })
+}
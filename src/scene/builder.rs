@@ -21,9 +21,17 @@
 //!     .rotate_z(45.0);
 //! ```
 
-use super::{NodeId, Renderable, SceneGraph};
-use crate::animation::{effects, property::AnimationInstance};
+use super::{
+    BlendMode, ClipMask, Constraint, EventHandler, Material, NodeEffect, NodeId, RenderTarget,
+    Renderable, SceneGraph, ShaderMaterial, Updater,
+};
+use crate::animation::{
+    deform::VertexDeformer, effects, procedural::ProceduralModifier, property::AnimationInstance,
+};
+use crate::assets::AssetHandle;
 use crate::core::{transform::Quaternion, Color, TimeValue, Vector3};
+use crate::mobjects::{AudioVisualizer, DecimalNumber, TracedPath, VisualizerStyle};
+use crate::text::{RichText, TextPath};
 
 /// Builder for constructing and configuring scene nodes
 pub struct NodeBuilder<'a> {
@@ -111,12 +119,192 @@ impl<'a> NodeBuilder<'a> {
         self
     }
 
+    /// Set the surface material used under scene lights
+    pub fn material(self, material: Material) -> Self {
+        if let Some(node) = self.scene.get_node_mut(self.node_id) {
+            node.material = material;
+        }
+        self
+    }
+
+    /// Set the diffuse and specular strength of the node's material
+    pub fn shading(self, diffuse: f32, specular: f32) -> Self {
+        if let Some(node) = self.scene.get_node_mut(self.node_id) {
+            node.material.diffuse = diffuse;
+            node.material.specular = specular;
+        }
+        self
+    }
+
+    /// Shade the node's shape with a custom WGSL fragment snippet
+    pub fn shader(self, shader: ShaderMaterial) -> Self {
+        if let Some(node) = self.scene.get_node_mut(self.node_id) {
+            node.shader = Some(shader);
+        }
+        self
+    }
+
+    /// Combine the node's colors with the background using `mode`
+    pub fn blend(self, mode: BlendMode) -> Self {
+        if let Some(node) = self.scene.get_node_mut(self.node_id) {
+            node.blend = mode;
+        }
+        self
+    }
+
+    /// Point a render target's camera at `center` of its local space,
+    /// magnified `zoom` times
+    pub fn target_camera(self, center: Vector3, zoom: f32) -> Self {
+        if let Some(target) = self
+            .scene
+            .get_node_mut(self.node_id)
+            .and_then(|node| node.render_target.as_mut())
+        {
+            target.center = center;
+            target.zoom = zoom;
+        }
+        self
+    }
+
+    /// Color a render target's texture is cleared to
+    pub fn target_background(self, color: Color) -> Self {
+        if let Some(target) = self
+            .scene
+            .get_node_mut(self.node_id)
+            .and_then(|node| node.render_target.as_mut())
+        {
+            target.background = color;
+        }
+        self
+    }
+
+    /// Tie the node to another node (see [`Constraint`]), enforced whenever
+    /// transforms are updated
+    pub fn constrain(self, constraint: Constraint) -> Self {
+        if let Some(node) = self.scene.get_node_mut(self.node_id) {
+            node.constraints.push(constraint);
+        }
+        self
+    }
+
+    /// Rebuild the node's renderable with `updater` whenever transforms are
+    /// updated
+    pub fn updater(self, updater: Updater) -> Self {
+        if let Some(node) = self.scene.get_node_mut(self.node_id) {
+            node.updater = Some(updater);
+        }
+        self.scene.update_transforms();
+        self
+    }
+
+    /// Run `handler` when the node (or a child without its own click
+    /// handler) is clicked in the preview
+    pub fn on_click(self, handler: EventHandler) -> Self {
+        if let Some(node) = self.scene.get_node_mut(self.node_id) {
+            node.handlers.on_click = Some(handler);
+        }
+        self
+    }
+
+    /// Run `handler` when the pointer moves onto the node
+    pub fn on_hover(self, handler: EventHandler) -> Self {
+        if let Some(node) = self.scene.get_node_mut(self.node_id) {
+            node.handlers.on_hover = Some(handler);
+        }
+        self
+    }
+
+    /// Run `handler` when the pointer moves off the node
+    pub fn on_hover_end(self, handler: EventHandler) -> Self {
+        if let Some(node) = self.scene.get_node_mut(self.node_id) {
+            node.handlers.on_hover_end = Some(handler);
+        }
+        self
+    }
+
+    /// Layer a procedural modifier (jitter, wiggle, breathe) on the transform
+    pub fn modifier(self, modifier: ProceduralModifier) -> Self {
+        if let Some(node) = self.scene.get_node_mut(self.node_id) {
+            node.add_modifier(modifier);
+        }
+        self
+    }
+
+    /// Bend the node's geometry with a deformer (wave, squash and stretch)
+    pub fn deform(self, deformer: impl VertexDeformer + 'static) -> Self {
+        if let Some(node) = self.scene.get_node_mut(self.node_id) {
+            node.add_deformer(deformer);
+        }
+        self
+    }
+
+    /// Add an outer glow of `radius` pixels
+    pub fn glow(self, color: Color, radius: f32) -> Self {
+        self.effect(NodeEffect::glow(color, radius))
+    }
+
+    /// Add a half-transparent black drop shadow offset by `(offset_x, offset_y)`
+    /// scene units and blurred by `softness` pixels
+    pub fn shadow(self, offset_x: f32, offset_y: f32, softness: f32) -> Self {
+        self.effect(NodeEffect::shadow(offset_x, offset_y, softness))
+    }
+
+    /// Only draw this node and its children inside `mask`
+    pub fn clip(self, mask: ClipMask) -> Self {
+        if let Some(node) = self.scene.get_node_mut(self.node_id) {
+            node.clip = Some(mask);
+        }
+        self
+    }
+
+    /// Add a shadow or glow drawn behind the node
+    pub fn effect(self, effect: NodeEffect) -> Self {
+        if let Some(node) = self.scene.get_node_mut(self.node_id) {
+            node.effects.push(effect);
+        }
+        self
+    }
+
+    /// Tag the node for [`SceneGraph::find_by_tag`]
+    pub fn tag(self, tag: impl Into<String>) -> Self {
+        if let Some(node) = self.scene.get_node_mut(self.node_id) {
+            let tag = tag.into();
+            if !node.has_tag(&tag) {
+                node.tags.push(tag);
+            }
+        }
+        self
+    }
+
     /// Parent this node to another
     pub fn parent_to(self, parent_id: NodeId) -> Self {
         self.scene.parent(self.node_id, parent_id).ok();
         self
     }
 
+    /// Set the color of every renderable in this node's subtree
+    pub fn recolor(self, color: Color) -> Self {
+        self.recolor_where(color, |_| true)
+    }
+
+    /// Set the color of the renderables called `name` in this node's subtree
+    pub fn recolor_part(self, name: &str, color: Color) -> Self {
+        self.recolor_where(color, |node_name| node_name == name)
+    }
+
+    fn recolor_where(self, color: Color, matches: impl Fn(&str) -> bool) -> Self {
+        for id in self.scene.subtree(self.node_id) {
+            if let Some(node) = self.scene.get_node_mut(id) {
+                if matches(&node.name) {
+                    if let Some(renderable) = &mut node.renderable {
+                        *renderable.color_mut() = color;
+                    }
+                }
+            }
+        }
+        self
+    }
+
     // ========== Animation Methods ==========
 
     /// Add fade in animation
@@ -137,7 +325,7 @@ impl<'a> NodeBuilder<'a> {
         self
     }
 
-    /// Add create animation (fade in + scale from 0)
+    /// Add create animation (draws the shape along its outline)
     pub fn create(self, start_time: f32, duration: f32) -> Self {
         let anim = effects::create(duration);
         if let Some(node) = self.scene.get_node_mut(self.node_id) {
@@ -146,6 +334,24 @@ impl<'a> NodeBuilder<'a> {
         self
     }
 
+    /// Add create animation drawing the outline at `speed` scene units per
+    /// second; shapes without an outline (text) take one second
+    pub fn create_at_speed(self, start_time: f32, speed: f32) -> Self {
+        if let Some(node) = self.scene.get_node_mut(self.node_id) {
+            let length = node
+                .renderable
+                .as_ref()
+                .and_then(Renderable::outline)
+                .map(|outline| outline.length());
+            let anim = match length {
+                Some(length) => effects::create_at_speed(length, speed),
+                None => effects::create(1.0),
+            };
+            node.add_animation(AnimationInstance::new(anim, TimeValue::new(start_time)));
+        }
+        self
+    }
+
     /// Add uncreate animation (reverse of create)
     pub fn uncreate(self, start_time: f32, duration: f32) -> Self {
         let anim = effects::uncreate(duration);
@@ -173,6 +379,25 @@ impl<'a> NodeBuilder<'a> {
         self
     }
 
+    /// Open the node's clip mask from nothing (iris reveal)
+    pub fn iris_in(self, start_time: f32, duration: f32) -> Self {
+        let anim = effects::iris_in(duration);
+        if let Some(node) = self.scene.get_node_mut(self.node_id) {
+            node.clip_scale = 0.0;
+            node.add_animation(AnimationInstance::new(anim, TimeValue::new(start_time)));
+        }
+        self
+    }
+
+    /// Close the node's clip mask to nothing
+    pub fn iris_out(self, start_time: f32, duration: f32) -> Self {
+        let anim = effects::iris_out(duration);
+        if let Some(node) = self.scene.get_node_mut(self.node_id) {
+            node.add_animation(AnimationInstance::new(anim, TimeValue::new(start_time)));
+        }
+        self
+    }
+
     /// Add move to position animation
     pub fn move_to(self, start_time: f32, target: Vector3, duration: f32) -> Self {
         if let Some(node) = self.scene.get_node_mut(self.node_id) {
@@ -230,8 +455,9 @@ impl SceneGraph {
         &mut self,
         name: impl Into<String>,
         radius: f32,
-        color: Color,
+        color: impl Into<Option<Color>>,
     ) -> NodeBuilder {
+        let color = color.into().unwrap_or(self.theme.fill);
         let node_id = self.create_node(name.into());
         self.get_node_mut(node_id)
             .unwrap()
@@ -245,8 +471,9 @@ impl SceneGraph {
         name: impl Into<String>,
         width: f32,
         height: f32,
-        color: Color,
+        color: impl Into<Option<Color>>,
     ) -> NodeBuilder {
+        let color = color.into().unwrap_or(self.theme.fill);
         let node_id = self.create_node(name.into());
         self.get_node_mut(node_id)
             .unwrap()
@@ -259,7 +486,12 @@ impl SceneGraph {
     }
 
     /// Create a square with fluent API
-    pub fn add_square(&mut self, name: impl Into<String>, side: f32, color: Color) -> NodeBuilder {
+    pub fn add_square(
+        &mut self,
+        name: impl Into<String>,
+        side: f32,
+        color: impl Into<Option<Color>>,
+    ) -> NodeBuilder {
         self.add_rectangle(name, side, side, color)
     }
 
@@ -269,9 +501,11 @@ impl SceneGraph {
         name: impl Into<String>,
         start: Vector3,
         end: Vector3,
-        color: Color,
-        thickness: f32,
+        color: impl Into<Option<Color>>,
+        thickness: impl Into<Option<f32>>,
     ) -> NodeBuilder {
+        let color = color.into().unwrap_or(self.theme.foreground);
+        let thickness = thickness.into().unwrap_or(self.theme.stroke_width);
         let node_id = self.create_node(name.into());
         self.get_node_mut(node_id)
             .unwrap()
@@ -290,9 +524,11 @@ impl SceneGraph {
         name: impl Into<String>,
         start: Vector3,
         end: Vector3,
-        color: Color,
-        thickness: f32,
+        color: impl Into<Option<Color>>,
+        thickness: impl Into<Option<f32>>,
     ) -> NodeBuilder {
+        let color = color.into().unwrap_or(self.theme.foreground);
+        let thickness = thickness.into().unwrap_or(self.theme.stroke_width);
         let node_id = self.create_node(name.into());
         self.get_node_mut(node_id)
             .unwrap()
@@ -310,8 +546,9 @@ impl SceneGraph {
         &mut self,
         name: impl Into<String>,
         vertices: Vec<Vector3>,
-        color: Color,
+        color: impl Into<Option<Color>>,
     ) -> NodeBuilder {
+        let color = color.into().unwrap_or(self.theme.fill);
         let node_id = self.create_node(name.into());
         self.get_node_mut(node_id)
             .unwrap()
@@ -325,7 +562,7 @@ impl SceneGraph {
         name: impl Into<String>,
         sides: usize,
         radius: f32,
-        color: Color,
+        color: impl Into<Option<Color>>,
     ) -> NodeBuilder {
         let angle_step = 2.0 * std::f32::consts::PI / sides as f32;
         let vertices: Vec<Vector3> = (0..sides)
@@ -342,7 +579,7 @@ impl SceneGraph {
         &mut self,
         name: impl Into<String>,
         size: f32,
-        color: Color,
+        color: impl Into<Option<Color>>,
     ) -> NodeBuilder {
         self.add_regular_polygon(name, 3, size, color)
     }
@@ -352,13 +589,18 @@ impl SceneGraph {
         &mut self,
         name: impl Into<String>,
         size: f32,
-        color: Color,
+        color: impl Into<Option<Color>>,
     ) -> NodeBuilder {
         self.add_regular_polygon(name, 5, size, color)
     }
 
     /// Create a hexagon
-    pub fn add_hexagon(&mut self, name: impl Into<String>, size: f32, color: Color) -> NodeBuilder {
+    pub fn add_hexagon(
+        &mut self,
+        name: impl Into<String>,
+        size: f32,
+        color: impl Into<Option<Color>>,
+    ) -> NodeBuilder {
         self.add_regular_polygon(name, 6, size, color)
     }
 
@@ -369,7 +611,7 @@ impl SceneGraph {
         points: usize,
         outer_radius: f32,
         inner_radius: f32,
-        color: Color,
+        color: impl Into<Option<Color>>,
     ) -> NodeBuilder {
         let angle_step = std::f32::consts::PI / points as f32;
         let vertices: Vec<Vector3> = (0..(points * 2))
@@ -391,9 +633,11 @@ impl SceneGraph {
         &mut self,
         name: impl Into<String>,
         content: impl Into<String>,
-        font_size: f32,
-        color: Color,
+        font_size: impl Into<Option<f32>>,
+        color: impl Into<Option<Color>>,
     ) -> NodeBuilder {
+        let font_size = font_size.into().unwrap_or(self.theme.font_size);
+        let color = color.into().unwrap_or(self.theme.foreground);
         let node_id = self.create_node(name.into());
         self.get_node_mut(node_id)
             .unwrap()
@@ -404,4 +648,209 @@ impl SceneGraph {
             });
         NodeBuilder::new(self, node_id)
     }
+
+    /// Add text with styled spans; `color` is used where a span sets none
+    pub fn add_rich_text(
+        &mut self,
+        name: impl Into<String>,
+        text: RichText,
+        font_size: impl Into<Option<f32>>,
+        color: impl Into<Option<Color>>,
+    ) -> NodeBuilder<'_> {
+        let font_size = font_size.into().unwrap_or(self.theme.font_size);
+        let color = color.into().unwrap_or(self.theme.foreground);
+        let node_id = self.create_node(name.into());
+        if let Some(node) = self.get_node_mut(node_id) {
+            node.set_renderable(Renderable::RichText {
+                text,
+                font_size,
+                color,
+            });
+        }
+        NodeBuilder::new(self, node_id)
+    }
+
+    /// Add text laid out along a path (in the node's local space)
+    pub fn add_text_on_path(
+        &mut self,
+        name: impl Into<String>,
+        content: impl Into<String>,
+        path: impl Into<TextPath>,
+        font_size: impl Into<Option<f32>>,
+        color: impl Into<Option<Color>>,
+    ) -> NodeBuilder<'_> {
+        let font_size = font_size.into().unwrap_or(self.theme.font_size);
+        let color = color.into().unwrap_or(self.theme.foreground);
+        let node_id = self.create_node(name.into());
+        if let Some(node) = self.get_node_mut(node_id) {
+            node.set_renderable(Renderable::TextOnPath {
+                content: content.into(),
+                path: path.into(),
+                font_size,
+                color,
+            });
+        }
+        NodeBuilder::new(self, node_id)
+    }
+
+    /// Add a PNG loaded by an [`AssetServer`](crate::assets::AssetServer),
+    /// `width` x `height` in scene units
+    pub fn add_image(
+        &mut self,
+        name: impl Into<String>,
+        asset: AssetHandle,
+        width: f32,
+        height: f32,
+    ) -> NodeBuilder<'_> {
+        let node_id = self.create_node(name.into());
+        if let Some(node) = self.get_node_mut(node_id) {
+            node.set_renderable(Renderable::Image {
+                asset,
+                width,
+                height,
+                color: Color::WHITE,
+            });
+        }
+        NodeBuilder::new(self, node_id)
+    }
+
+    /// Add an SVG loaded by an [`AssetServer`](crate::assets::AssetServer),
+    /// fitted into `width` x `height` scene units
+    pub fn add_svg(
+        &mut self,
+        name: impl Into<String>,
+        asset: AssetHandle,
+        width: f32,
+        height: f32,
+    ) -> NodeBuilder<'_> {
+        let node_id = self.create_node(name.into());
+        if let Some(node) = self.get_node_mut(node_id) {
+            node.set_renderable(Renderable::Svg {
+                asset,
+                width,
+                height,
+                color: Color::WHITE,
+            });
+        }
+        NodeBuilder::new(self, node_id)
+    }
+
+    /// Add a node whose subtree is drawn into a `width` x `height` pixel
+    /// texture, shown by image nodes using
+    /// [`RenderTarget::handle_for`]`(name)`
+    pub fn add_render_target(
+        &mut self,
+        name: impl Into<String>,
+        width: u32,
+        height: u32,
+    ) -> NodeBuilder<'_> {
+        let name = name.into();
+        let target = RenderTarget::new(&name, width, height);
+        let node_id = self.create_node(name);
+        if let Some(node) = self.get_node_mut(node_id) {
+            node.render_target = Some(target);
+        }
+        NodeBuilder::new(self, node_id)
+    }
+
+    /// Add a `thickness` wide trail that records the path of the node
+    /// traced by `trace` every update
+    pub fn add_traced_path(
+        &mut self,
+        name: impl Into<String>,
+        trace: TracedPath,
+        color: impl Into<Option<Color>>,
+        thickness: f32,
+    ) -> NodeBuilder<'_> {
+        let color = color.into().unwrap_or(self.theme.foreground);
+        let node_id = self.create_node(name.into());
+        if let Some(node) = self.get_node_mut(node_id) {
+            node.set_renderable(Renderable::Polyline {
+                points: trace.points(),
+                color,
+                thickness,
+                fade: trace.fade(),
+            });
+            node.trace = Some(trace);
+        }
+        NodeBuilder::new(self, node_id)
+    }
+
+    /// Add bars or a line showing `visualizer`'s sound as it plays, resized
+    /// every update (bars are child rectangles named `{name}_bar_{i}`)
+    pub fn add_audio_visualizer(
+        &mut self,
+        name: impl Into<String>,
+        visualizer: AudioVisualizer,
+        color: impl Into<Option<Color>>,
+    ) -> NodeBuilder<'_> {
+        let name = name.into();
+        let color = color.into().unwrap_or(self.theme.foreground);
+        let node_id = self.create_node(name.clone());
+        match visualizer.style {
+            VisualizerStyle::Line => {
+                let thickness = self.theme.stroke_width;
+                if let Some(node) = self.get_node_mut(node_id) {
+                    node.set_renderable(Renderable::Polyline {
+                        points: visualizer.line(),
+                        color,
+                        thickness,
+                        fade: 0.0,
+                    });
+                }
+            }
+            VisualizerStyle::Bars => {
+                let width = visualizer.bar_width();
+                for (i, (center, height)) in visualizer.bars().into_iter().enumerate() {
+                    let bar = self
+                        .add_rectangle(format!("{name}_bar_{i}"), width, height, color)
+                        .at_vec(center)
+                        .id();
+                    let _ = self.parent(bar, node_id);
+                }
+            }
+        }
+        if let Some(node) = self.get_node_mut(node_id) {
+            node.visualizer = Some(visualizer);
+        }
+        NodeBuilder::new(self, node_id)
+    }
+
+    /// Add a node whose renderable `updater` rebuilds from other nodes
+    /// whenever transforms are updated
+    pub fn add_always_redraw(
+        &mut self,
+        name: impl Into<String>,
+        updater: Updater,
+    ) -> NodeBuilder<'_> {
+        let node_id = self.create_node(name.into());
+        if let Some(node) = self.get_node_mut(node_id) {
+            node.updater = Some(updater);
+        }
+        self.update_transforms();
+        NodeBuilder::new(self, node_id)
+    }
+
+    /// Add text showing an animated number, updated every frame
+    pub fn add_decimal_number(
+        &mut self,
+        name: impl Into<String>,
+        number: DecimalNumber,
+        font_size: impl Into<Option<f32>>,
+        color: impl Into<Option<Color>>,
+    ) -> NodeBuilder<'_> {
+        let font_size = font_size.into().unwrap_or(self.theme.font_size);
+        let color = color.into().unwrap_or(self.theme.foreground);
+        let content = number.text();
+        let node_id = self.create_node(name.into());
+        if let Some(node) = self.get_node_mut(node_id) {
+            node.set_renderable(Renderable::Text {
+                content,
+                font_size,
+                color,
+            });
+            node.number = Some(number);
+        }
+        NodeBuilder::new(self, node_id)
+    }
 }
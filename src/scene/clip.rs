@@ -0,0 +1,103 @@
+//! Clipping Masks
+//!
+//! A node with a [`ClipMask`] only shows itself and its children inside the
+//! mask shape. The mask is placed in the node's local space, so it moves and
+//! scales with the node, and its size can be animated separately through the
+//! node's `clip_scale` (e.g. an iris reveal). Masks nest: a clipped child of a
+//! clipped node is limited to the intersection of both.
+//!
+//! Clipping is done with the stencil buffer by the render graph's scene pass,
+//! which [`crate::pipeline::render_frames`] uses.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::scene::*;
+//! use diomanim::core::*;
+//!
+//! let mut scene = SceneGraph::new();
+//! // Window that only shows the part of the content inside a 4x3 rectangle
+//! let window = scene
+//!     .add_rectangle("window", 4.0, 3.0, Color::WHITE)
+//!     .clip(ClipMask::Rectangle { width: 4.0, height: 3.0 })
+//!     .build();
+//! scene.add_circle("content", 3.0, Color::BLUE).parent_to(window);
+//!
+//! // Circle that opens from nothing over the first second
+//! scene
+//!     .add_square("reveal", 2.0, Color::RED)
+//!     .clip(ClipMask::Circle { radius: 1.5 })
+//!     .iris_in(0.0, 1.0);
+//! ```
+
+use crate::core::Vector3;
+
+/// Shape that limits where a node and its subtree are drawn
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClipMask {
+    Circle {
+        radius: f32,
+    },
+    /// Centered on the node
+    Rectangle {
+        width: f32,
+        height: f32,
+    },
+    Polygon {
+        vertices: Vec<Vector3>,
+    },
+}
+
+impl ClipMask {
+    /// The mask with its size multiplied by `scale` around the node's origin
+    pub fn scaled(&self, scale: f32) -> Self {
+        match self {
+            Self::Circle { radius } => Self::Circle {
+                radius: radius * scale,
+            },
+            Self::Rectangle { width, height } => Self::Rectangle {
+                width: width * scale,
+                height: height * scale,
+            },
+            Self::Polygon { vertices } => Self::Polygon {
+                vertices: vertices.iter().map(|vertex| *vertex * scale).collect(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Color;
+    use crate::scene::SceneGraph;
+
+    #[test]
+    fn test_clip_applies_to_subtree() {
+        let mut scene = SceneGraph::new();
+        let window = scene
+            .add_rectangle("window", 2.0, 2.0, Color::WHITE)
+            .clip(ClipMask::Circle { radius: 1.0 })
+            .build();
+        let inside = scene.add_circle("inside", 0.5, Color::RED).build();
+        scene.parent(inside, window).unwrap();
+        let outside = scene.add_circle("outside", 0.5, Color::BLUE).build();
+
+        assert!(scene.is_ancestor_or_self(window, window));
+        assert!(scene.is_ancestor_or_self(window, inside));
+        assert!(!scene.is_ancestor_or_self(window, outside));
+        assert!(scene.has_clip_masks());
+
+        assert_eq!(
+            ClipMask::Rectangle {
+                width: 2.0,
+                height: 1.0
+            }
+            .scaled(0.5),
+            ClipMask::Rectangle {
+                width: 1.0,
+                height: 0.5
+            }
+        );
+    }
+}
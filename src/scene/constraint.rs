@@ -0,0 +1,262 @@
+//! Constraints
+//!
+//! A [`Constraint`] ties a node to another node's position after every
+//! transform update, so relationships like "the label stays above its dot"
+//! or "the arrow ends at the moving circle" hold without recomputing
+//! coordinates by hand. Constraints run after animations, in draw order,
+//! and move the constrained node along with its subtree; a node
+//! constrained to a target that is itself constrained should come after it
+//! in draw order.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::scene::*;
+//! use diomanim::core::*;
+//!
+//! let mut scene = SceneGraph::new();
+//! let dot = scene
+//!     .add_circle("dot", 0.05, Color::RED)
+//!     .move_to(0.0, Vector3::new(0.5, 0.5, 0.0), 2.0)
+//!     .build();
+//! scene
+//!     .add_text("label", "p", 24.0, None)
+//!     .constrain(Constraint::above(dot, 0.1));
+//! scene
+//!     .add_arrow("pointer", Vector3::new(-0.8, -0.8, 0.0), Vector3::zero(), None, 2.0)
+//!     .constrain(Constraint::EndAt { target: dot });
+//! ```
+
+use super::{NodeId, Renderable, SceneGraph};
+use crate::core::{transform::Quaternion, Transform, Vector3};
+
+/// A relationship between a node and a target node, enforced after every
+/// transform update
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Constraint {
+    /// Stay at `offset` (in scene coordinates) from the target's position
+    Offset { target: NodeId, offset: Vector3 },
+    /// Stay `distance` units from the target, on the side the node is on
+    Distance { target: NodeId, distance: f32 },
+    /// Turn so the node's x axis points at the target
+    LookAt { target: NodeId },
+    /// Share the target's x coordinate
+    AlignX { target: NodeId },
+    /// Share the target's y coordinate
+    AlignY { target: NodeId },
+    /// Move the start of the node's line or arrow onto the target
+    StartAt { target: NodeId },
+    /// Move the end of the node's line or arrow onto the target
+    EndAt { target: NodeId },
+}
+
+impl Constraint {
+    /// Stay `distance` units right of `target`
+    pub fn right_of(target: NodeId, distance: f32) -> Self {
+        Self::Offset {
+            target,
+            offset: Vector3::new(distance, 0.0, 0.0),
+        }
+    }
+
+    /// Stay `distance` units left of `target`
+    pub fn left_of(target: NodeId, distance: f32) -> Self {
+        Self::Offset {
+            target,
+            offset: Vector3::new(-distance, 0.0, 0.0),
+        }
+    }
+
+    /// Stay `distance` units above `target`
+    pub fn above(target: NodeId, distance: f32) -> Self {
+        Self::Offset {
+            target,
+            offset: Vector3::new(0.0, distance, 0.0),
+        }
+    }
+
+    /// Stay `distance` units below `target`
+    pub fn below(target: NodeId, distance: f32) -> Self {
+        Self::Offset {
+            target,
+            offset: Vector3::new(0.0, -distance, 0.0),
+        }
+    }
+
+    pub fn target(&self) -> NodeId {
+        match *self {
+            Self::Offset { target, .. }
+            | Self::Distance { target, .. }
+            | Self::LookAt { target }
+            | Self::AlignX { target }
+            | Self::AlignY { target }
+            | Self::StartAt { target }
+            | Self::EndAt { target } => target,
+        }
+    }
+}
+
+impl SceneGraph {
+    /// Enforce every node's constraints, moving constrained subtrees
+    pub(super) fn apply_constraints(&mut self) {
+        if self.nodes.values().all(|node| node.constraints.is_empty()) {
+            return;
+        }
+        let constrained: Vec<NodeId> = self
+            .nodes_in_draw_order()
+            .into_iter()
+            .filter(|node| !node.constraints.is_empty())
+            .map(|node| node.id)
+            .collect();
+
+        for id in constrained {
+            let Some(node) = self.nodes.get(&id) else {
+                continue;
+            };
+            let parent_world = node
+                .parent
+                .and_then(|parent| self.nodes.get(&parent))
+                .map_or_else(Transform::new, |parent| parent.world_transform);
+            let mut position = node.world_transform.position;
+            let mut rotation = None;
+            let (mut start_at, mut end_at) = (None, None);
+
+            for constraint in &node.constraints {
+                let Some(target) = self
+                    .nodes
+                    .get(&constraint.target())
+                    .map(|target| target.world_transform.position)
+                else {
+                    continue;
+                };
+                match *constraint {
+                    Constraint::Offset { offset, .. } => position = target + offset,
+                    Constraint::Distance { distance, .. } => {
+                        let away = position - target;
+                        let direction = if away.length() > f32::EPSILON {
+                            away.normalized()
+                        } else {
+                            Vector3::new(1.0, 0.0, 0.0)
+                        };
+                        position = target + direction * distance;
+                    }
+                    Constraint::LookAt { .. } => {
+                        let angle = (target.y - position.y).atan2(target.x - position.x);
+                        rotation = Some(Quaternion::from_rotation_z(angle));
+                    }
+                    Constraint::AlignX { .. } => position.x = target.x,
+                    Constraint::AlignY { .. } => position.y = target.y,
+                    Constraint::StartAt { .. } => start_at = Some(target),
+                    Constraint::EndAt { .. } => end_at = Some(target),
+                }
+            }
+
+            // Endpoints are in the node's space once it is placed
+            let mut world = node.world_transform;
+            world.position = position;
+            world.rotation = rotation.unwrap_or(world.rotation);
+            let to_local = world.inverse();
+            let mut renderable = node.renderable.clone();
+            if let Some(
+                Renderable::Line { start, end, .. } | Renderable::Arrow { start, end, .. },
+            ) = &mut renderable
+            {
+                if let Some(target) = start_at {
+                    *start = to_local.transform_point(target);
+                }
+                if let Some(target) = end_at {
+                    *end = to_local.transform_point(target);
+                }
+            }
+
+            // Turn the solved world placement back into a local transform
+            let to_parent = parent_world.inverse();
+            let Some(node) = self.nodes.get_mut(&id) else {
+                continue;
+            };
+            node._local_transform.position =
+                to_parent.transform_point(position) - node.modifier_offset.position;
+            if let Some(rotation) = rotation {
+                node._local_transform.rotation = to_parent.rotation * rotation;
+            }
+            node.renderable = renderable;
+            self.update_node_transform_recursive(id, parent_world);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Color, TimeValue};
+
+    fn position(scene: &SceneGraph, id: NodeId) -> Vector3 {
+        scene.get_node(id).unwrap().world_transform.position
+    }
+
+    #[test]
+    fn test_constraints_follow_target() {
+        let mut scene = SceneGraph::new();
+        let dot = scene
+            .add_circle("dot", 0.05, Color::RED)
+            .move_to(0.0, Vector3::new(0.5, 0.5, 0.0), 1.0)
+            .build();
+        let label = scene
+            .add_text("label", "p", 24.0, None)
+            .constrain(Constraint::above(dot, 0.1))
+            .build();
+        // The child moves with its constrained parent
+        let underline = scene
+            .add_line(
+                "underline",
+                Vector3::zero(),
+                Vector3::new(0.1, 0.0, 0.0),
+                None,
+                1.0,
+            )
+            .at(0.0, -0.05, 0.0)
+            .parent_to(label)
+            .build();
+        let neighbor = scene
+            .add_square("neighbor", 0.1, None)
+            .at(0.0, -0.7, 0.0)
+            .constrain(Constraint::AlignX { target: dot })
+            .build();
+        let orbit = scene
+            .add_circle("orbit", 0.02, None)
+            .at(0.5, 0.0, 0.0)
+            .constrain(Constraint::Distance {
+                target: dot,
+                distance: 0.2,
+            })
+            .build();
+        let pointer = scene
+            .add_arrow(
+                "pointer",
+                Vector3::zero(),
+                Vector3::new(0.1, 0.0, 0.0),
+                None,
+                2.0,
+            )
+            .at(-0.5, 0.0, 0.0)
+            .constrain(Constraint::EndAt { target: dot })
+            .constrain(Constraint::LookAt { target: dot })
+            .build();
+
+        scene.update_animations(TimeValue::new(1.0));
+        let dot_at = Vector3::new(0.5, 0.5, 0.0);
+        assert!((position(&scene, label) - Vector3::new(0.5, 0.6, 0.0)).length() < 1e-5);
+        assert!((position(&scene, underline) - Vector3::new(0.5, 0.55, 0.0)).length() < 1e-5);
+        assert!((position(&scene, neighbor) - Vector3::new(0.5, -0.7, 0.0)).length() < 1e-5);
+        assert!(((position(&scene, orbit) - dot_at).length() - 0.2).abs() < 1e-5);
+
+        // The arrow ends on the dot, and is turned to face it
+        let node = scene.get_node(pointer).unwrap();
+        let (_, end, _, _) = node.renderable.as_ref().unwrap().as_arrow().unwrap();
+        assert!((node.world_transform.transform_point(*end) - dot_at).length() < 1e-4);
+        let facing = node
+            .world_transform
+            .transform_vector(Vector3::new(1.0, 0.0, 0.0));
+        assert!((facing.normalized() - Vector3::new(1.0, 0.5, 0.0).normalized()).length() < 1e-4);
+    }
+}
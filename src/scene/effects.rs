@@ -0,0 +1,101 @@
+//! Per-Node Effects
+//!
+//! Drop shadows and outer glows drawn behind a single node. The renderer
+//! draws the node's silhouette into an offscreen mask, blurs it and
+//! composites it in the node's color slot before drawing the node itself, so
+//! the effect sits between the node and whatever is behind it.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::scene::*;
+//! use diomanim::core::*;
+//!
+//! let mut scene = SceneGraph::new();
+//! scene.add_circle("sun", 1.0, Color::YELLOW).glow(Color::YELLOW, 24.0);
+//! scene.add_rectangle("card", 3.0, 2.0, Color::WHITE).shadow(0.1, -0.1, 8.0);
+//! ```
+
+use crate::core::Color;
+
+/// A shadow or glow drawn behind a node
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeEffect {
+    /// Silhouette shifted by `offset` (scene units), blurred by `softness` pixels
+    Shadow {
+        offset: (f32, f32),
+        softness: f32,
+        color: Color,
+    },
+    /// Silhouette blurred by `radius` pixels around the node
+    Glow { color: Color, radius: f32 },
+}
+
+impl NodeEffect {
+    /// Half-transparent black shadow
+    pub fn shadow(offset_x: f32, offset_y: f32, softness: f32) -> Self {
+        Self::Shadow {
+            offset: (offset_x, offset_y),
+            softness: softness.max(0.0),
+            color: Color::rgba(0.0, 0.0, 0.0, 0.5),
+        }
+    }
+
+    pub fn glow(color: Color, radius: f32) -> Self {
+        Self::Glow {
+            color,
+            radius: radius.max(0.0),
+        }
+    }
+
+    /// Replace the shadow or glow color
+    pub fn with_color(mut self, new_color: Color) -> Self {
+        match &mut self {
+            Self::Shadow { color, .. } | Self::Glow { color, .. } => *color = new_color,
+        }
+        self
+    }
+
+    /// Blur radius in pixels
+    pub fn blur_radius(&self) -> f32 {
+        match *self {
+            Self::Shadow { softness, .. } => softness,
+            Self::Glow { radius, .. } => radius,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match *self {
+            Self::Shadow { color, .. } | Self::Glow { color, .. } => color,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::SceneGraph;
+
+    #[test]
+    fn test_builder_adds_node_effects() {
+        let mut scene = SceneGraph::new();
+        scene.add_circle("plain", 1.0, Color::RED);
+        assert!(!scene.has_node_effects());
+
+        let id = scene
+            .add_square("card", 1.0, Color::WHITE)
+            .shadow(0.1, -0.1, 6.0)
+            .glow(Color::BLUE, 12.0)
+            .build();
+        assert!(scene.has_node_effects());
+
+        let effects = &scene.get_node(id).unwrap().effects;
+        assert_eq!(effects.len(), 2);
+        assert_eq!(effects[0].blur_radius(), 6.0);
+        assert_eq!(effects[1], NodeEffect::glow(Color::BLUE, 12.0));
+
+        // Hidden nodes draw no effects
+        scene.get_node_mut(id).unwrap().visible = false;
+        assert!(!scene.has_node_effects());
+    }
+}
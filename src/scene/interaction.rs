@@ -0,0 +1,316 @@
+//! Interaction
+//!
+//! Nodes can register [`EventHandler`]s that run when the node is clicked
+//! or hovered in the live preview, turning a scene into a small interactive
+//! demo: clicking a bar can highlight it and play a grow animation.
+//! Handlers get the scene and the node and usually queue animations on it;
+//! an animation added with a start time of zero plays from the moment of
+//! the click.
+//!
+//! [`SceneGraph::pick`] finds the topmost visible shape under a point in
+//! scene coordinates. Circles, rectangles, polygons, lines, polylines,
+//! images and SVGs can be picked; text cannot (give it a handler through a
+//! parent, or put a shape behind it). Events go to the picked node, or to
+//! its nearest ancestor with a handler for the event, so clicking any part
+//! of a group reaches the group's handler.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::animation::{effects, property::AnimationInstance};
+//! use diomanim::scene::*;
+//! use diomanim::core::*;
+//!
+//! let mut scene = SceneGraph::new();
+//! let bar = scene
+//!     .add_rectangle("bar", 0.2, 0.6, Color::BLUE)
+//!     .on_click(EventHandler::new(|scene, id| {
+//!         if let Some(node) = scene.get_node_mut(id) {
+//!             if let Some(shape) = &mut node.renderable {
+//!                 *shape.color_mut() = Color::YELLOW;
+//!             }
+//!             node.add_animation(AnimationInstance::new(
+//!                 effects::grow_from_center(0.5),
+//!                 TimeValue::new(0.0),
+//!             ));
+//!         }
+//!     }))
+//!     .build();
+//!
+//! assert_eq!(scene.click(Vector3::new(0.05, 0.2, 0.0)), Some(bar));
+//! ```
+
+use super::{NodeId, Renderable, SceneGraph, SceneNode};
+use crate::core::Vector3;
+use std::sync::Arc;
+
+/// Distance in scene units within which lines and polylines are hit
+const LINE_PICK_TOLERANCE: f32 = 0.02;
+
+type Handle = dyn Fn(&mut SceneGraph, NodeId) + Send + Sync;
+
+/// Closure run when a node is clicked or hovered
+///
+/// It receives the scene and the id of the node the handler belongs to.
+#[derive(Clone)]
+pub struct EventHandler(Arc<Handle>);
+
+impl std::fmt::Debug for EventHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EventHandler")
+    }
+}
+
+impl EventHandler {
+    pub fn new(handle: impl Fn(&mut SceneGraph, NodeId) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(handle))
+    }
+
+    /// Run the handler for `node`
+    pub fn call(&self, scene: &mut SceneGraph, node: NodeId) {
+        (self.0)(scene, node);
+    }
+}
+
+/// Pointer events a node can handle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerEvent {
+    Click,
+    /// The pointer moved onto the node
+    HoverStart,
+    /// The pointer moved off the node
+    HoverEnd,
+}
+
+/// A node's pointer event handlers
+#[derive(Debug, Clone, Default)]
+pub struct EventHandlers {
+    pub on_click: Option<EventHandler>,
+    pub on_hover: Option<EventHandler>,
+    pub on_hover_end: Option<EventHandler>,
+}
+
+impl EventHandlers {
+    /// The handler for `event`, if one is registered
+    pub fn get(&self, event: PointerEvent) -> Option<&EventHandler> {
+        match event {
+            PointerEvent::Click => self.on_click.as_ref(),
+            PointerEvent::HoverStart => self.on_hover.as_ref(),
+            PointerEvent::HoverEnd => self.on_hover_end.as_ref(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.on_click.is_none() && self.on_hover.is_none() && self.on_hover_end.is_none()
+    }
+}
+
+impl SceneGraph {
+    /// The topmost visible node whose shape contains `point` (scene
+    /// coordinates), as of the last transform update
+    pub fn pick(&self, point: Vector3) -> Option<NodeId> {
+        self.visible_renderable_nodes()
+            .into_iter()
+            .rev()
+            .find(|node| node.opacity > 0.0 && contains(node, point))
+            .map(|node| node.id)
+    }
+
+    /// Click at `point`, running the click handler of the picked node or
+    /// its nearest ancestor with one; returns the node whose handler ran
+    pub fn click(&mut self, point: Vector3) -> Option<NodeId> {
+        let picked = self.pick(point)?;
+        self.dispatch(picked, PointerEvent::Click)
+    }
+
+    /// Move the pointer to `point` (`None` when it left the view), running
+    /// hover handlers of the nodes it moved off and onto; returns the node
+    /// now hovered
+    pub fn hover(&mut self, point: Option<Vector3>) -> Option<NodeId> {
+        let hovered = point.and_then(|point| self.pick(point)).and_then(|picked| {
+            self.handling_node(picked, &[PointerEvent::HoverStart, PointerEvent::HoverEnd])
+        });
+        if hovered != self.hovered {
+            if let Some(previous) = self.hovered.take() {
+                self.dispatch(previous, PointerEvent::HoverEnd);
+            }
+            if let Some(node) = hovered {
+                self.dispatch(node, PointerEvent::HoverStart);
+            }
+            self.hovered = hovered;
+        }
+        hovered
+    }
+
+    /// Run the handler for `event` on `id` or its nearest ancestor with one,
+    /// returning the node whose handler ran
+    pub fn dispatch(&mut self, id: NodeId, event: PointerEvent) -> Option<NodeId> {
+        let target = self.handling_node(id, &[event])?;
+        let handler = self.nodes.get(&target)?.handlers.get(event)?.clone();
+        handler.call(self, target);
+        self.update_transforms();
+        Some(target)
+    }
+
+    /// `id` or its nearest ancestor with a handler for one of `events`
+    fn handling_node(&self, id: NodeId, events: &[PointerEvent]) -> Option<NodeId> {
+        let mut current = Some(id);
+        while let Some(node) = current.and_then(|id| self.nodes.get(&id)) {
+            if events
+                .iter()
+                .any(|&event| node.handlers.get(event).is_some())
+            {
+                return Some(node.id);
+            }
+            current = node.parent;
+        }
+        None
+    }
+}
+
+/// Whether `point` (scene coordinates) lies on the node's shape
+fn contains(node: &SceneNode, point: Vector3) -> bool {
+    let Some(renderable) = &node.renderable else {
+        return false;
+    };
+    let local = node.world_transform.inverse().transform_point(point);
+    match renderable {
+        Renderable::Circle { radius, .. } => local.x.hypot(local.y) <= *radius,
+        Renderable::Rectangle { width, height, .. }
+        | Renderable::Image { width, height, .. }
+        | Renderable::Svg { width, height, .. } => {
+            local.x.abs() <= width / 2.0 && local.y.abs() <= height / 2.0
+        }
+        Renderable::Polygon { vertices, .. } => polygon_contains(vertices, local),
+        Renderable::Line { start, end, .. } | Renderable::Arrow { start, end, .. } => {
+            segment_distance(*start, *end, local) <= LINE_PICK_TOLERANCE
+        }
+        Renderable::Polyline { points, .. } => points
+            .windows(2)
+            .any(|pair| segment_distance(pair[0], pair[1], local) <= LINE_PICK_TOLERANCE),
+        Renderable::Text { .. }
+        | Renderable::Math { .. }
+        | Renderable::RichText { .. }
+        | Renderable::TextOnPath { .. } => false,
+    }
+}
+
+/// Even-odd test of `point` against a polygon in the xy plane
+pub(super) fn polygon_contains(vertices: &[Vector3], point: Vector3) -> bool {
+    let mut inside = false;
+    let Some(&(mut previous)) = vertices.last() else {
+        return false;
+    };
+    for &vertex in vertices {
+        if (vertex.y > point.y) != (previous.y > point.y) {
+            let x =
+                vertex.x + (point.y - vertex.y) * (previous.x - vertex.x) / (previous.y - vertex.y);
+            if point.x < x {
+                inside = !inside;
+            }
+        }
+        previous = vertex;
+    }
+    inside
+}
+
+/// Distance in the xy plane from `point` to the segment `start`-`end`
+fn segment_distance(start: Vector3, end: Vector3, point: Vector3) -> f32 {
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq > f32::EPSILON {
+        (((point.x - start.x) * dx + (point.y - start.y) * dy) / length_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (point.x - start.x - t * dx).hypot(point.y - start.y - t * dy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Color;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_pick_topmost_shape() {
+        let mut scene = SceneGraph::new();
+        let back = scene.add_rectangle("back", 1.0, 1.0, None).build();
+        let front = scene
+            .add_circle("front", 0.1, None)
+            .at(0.3, 0.0, 0.0)
+            .build();
+        let line = scene
+            .add_line(
+                "line",
+                Vector3::new(-0.4, -0.8, 0.0),
+                Vector3::new(0.4, -0.8, 0.0),
+                None,
+                2.0,
+            )
+            .build();
+        let triangle = scene
+            .add_triangle("triangle", 0.2, None)
+            .at(-0.7, 0.7, 0.0)
+            .build();
+        scene
+            .add_text("label", "text", 24.0, None)
+            .at(0.7, -0.7, 0.0);
+        scene
+            .add_circle("hidden", 0.5, None)
+            .at(0.0, 0.0, 0.0)
+            .visible(false);
+        scene.update_transforms();
+
+        assert_eq!(scene.pick(Vector3::new(0.35, 0.05, 0.0)), Some(front));
+        assert_eq!(scene.pick(Vector3::new(-0.2, 0.2, 0.0)), Some(back));
+        assert_eq!(scene.pick(Vector3::new(0.1, -0.81, 0.0)), Some(line));
+        assert_eq!(scene.pick(Vector3::new(-0.7, 0.7, 0.0)), Some(triangle));
+        assert_eq!(scene.pick(Vector3::new(0.7, -0.7, 0.0)), None);
+        assert_eq!(scene.pick(Vector3::new(0.9, 0.9, 0.0)), None);
+    }
+
+    #[test]
+    fn test_events_reach_nearest_handler() {
+        let clicks = Arc::new(AtomicUsize::new(0));
+        let hovers = Arc::new(AtomicUsize::new(0));
+        let mut scene = SceneGraph::new();
+        let counted = |count: &Arc<AtomicUsize>| {
+            let count = Arc::clone(count);
+            EventHandler::new(move |scene, id| {
+                count.fetch_add(1, Ordering::SeqCst);
+                if let Some(shape) = scene
+                    .get_node_mut(id)
+                    .and_then(|node| node.renderable.as_mut())
+                {
+                    *shape.color_mut() = Color::YELLOW;
+                }
+            })
+        };
+        let group = scene
+            .add_rectangle("group", 0.4, 0.4, Color::BLUE)
+            .on_click(counted(&clicks))
+            .on_hover(counted(&hovers))
+            .build();
+        scene
+            .add_circle("dot", 0.05, None)
+            .at(0.1, 0.1, 0.0)
+            .parent_to(group);
+        scene.update_transforms();
+
+        // Clicking the child runs the group's handler
+        assert_eq!(scene.click(Vector3::new(0.1, 0.1, 0.0)), Some(group));
+        assert_eq!(clicks.load(Ordering::SeqCst), 1);
+        assert_eq!(scene.click(Vector3::new(0.8, 0.8, 0.0)), None);
+        let color = scene.get_node(group).unwrap().renderable.as_ref().unwrap();
+        assert_eq!(color.as_rectangle().unwrap().2, &Color::YELLOW);
+
+        // Hovering fires once on entering, not again while moving over it
+        assert_eq!(scene.hover(Some(Vector3::zero())), Some(group));
+        assert_eq!(scene.hover(Some(Vector3::new(0.1, 0.1, 0.0))), Some(group));
+        assert_eq!(hovers.load(Ordering::SeqCst), 1);
+        assert_eq!(scene.hover(None), None);
+        assert_eq!(scene.hover(Some(Vector3::zero())), Some(group));
+        assert_eq!(hovers.load(Ordering::SeqCst), 2);
+    }
+}
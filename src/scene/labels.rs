@@ -0,0 +1,363 @@
+//! Label de-overlap
+//!
+//! Dense labels (chart values, point names) often land on top of each
+//! other. Label layout nudges their bounding boxes apart, each along
+//! whichever axis needs the smaller move, and draws a leader line from
+//! where a label was to where it went once it has moved clear of that
+//! spot. [`SceneGraph::separate_labels`] lays labels out once, moving them
+//! for good; [`SceneGraph::keep_labels_apart`] does it after every
+//! transform update, so labels following moving points never collide and
+//! settle back when there is room again.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::core::*;
+//! use diomanim::scene::{LabelLayout, SceneGraph};
+//!
+//! let mut scene = SceneGraph::new();
+//! let labels: Vec<_> = ["12.5", "12.9", "13.1"]
+//!     .iter()
+//!     .enumerate()
+//!     .map(|(i, value)| {
+//!         let x = 0.01 * i as f32;
+//!         scene.add_text(format!("value{i}"), *value, 24.0, None).at(x, 0.0, 0.0).build()
+//!     })
+//!     .collect();
+//! let leaders = scene.separate_labels(&labels, LabelLayout::default());
+//! ```
+
+use super::{NodeId, Renderable, SceneGraph};
+use crate::core::{Color, Transform, Vector3};
+
+/// Overlap below which boxes count as touching rather than overlapping
+const OVERLAP_EPSILON: f32 = 1e-6;
+
+/// How labels are pushed apart and marked
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LabelLayout {
+    /// Gap kept between label boxes
+    pub padding: f32,
+    /// Most relaxation passes; each moves every overlapping pair apart
+    pub iterations: usize,
+    /// Labels whose box ends up further than this from where their center
+    /// was get a leader line back to it
+    pub leader_threshold: f32,
+    /// Leader color, the theme's foreground when `None`
+    pub leader_color: Option<Color>,
+    /// Leader thickness, the theme's stroke width when `None`
+    pub leader_thickness: Option<f32>,
+}
+
+impl Default for LabelLayout {
+    fn default() -> Self {
+        Self {
+            padding: 0.01,
+            iterations: 100,
+            leader_threshold: 0.02,
+            leader_color: None,
+            leader_thickness: None,
+        }
+    }
+}
+
+impl LabelLayout {
+    pub fn with_padding(mut self, padding: f32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn with_leader_threshold(mut self, threshold: f32) -> Self {
+        self.leader_threshold = threshold;
+        self
+    }
+
+    pub fn with_leader_style(mut self, color: Color, thickness: f32) -> Self {
+        self.leader_color = Some(color);
+        self.leader_thickness = Some(thickness);
+        self
+    }
+}
+
+/// Labels kept apart after every transform update, with one leader each
+#[derive(Debug, Clone)]
+pub(super) struct LabelGroup {
+    labels: Vec<NodeId>,
+    leaders: Vec<NodeId>,
+    layout: LabelLayout,
+}
+
+/// Offsets moving the `(min, max)` boxes apart until no two overlap
+/// (closer than `padding`) or `iterations` passes have run
+///
+/// Each overlapping pair splits the move between them along the axis
+/// where they overlap least, so labels shift as little as they can.
+pub fn separate_boxes(
+    boxes: &[(Vector3, Vector3)],
+    padding: f32,
+    iterations: usize,
+) -> Vec<Vector3> {
+    let mut offsets = vec![Vector3::zero(); boxes.len()];
+    for _ in 0..iterations {
+        let mut moved = false;
+        for i in 0..boxes.len() {
+            for j in i + 1..boxes.len() {
+                let (a_min, a_max) = (boxes[i].0 + offsets[i], boxes[i].1 + offsets[i]);
+                let (b_min, b_max) = (boxes[j].0 + offsets[j], boxes[j].1 + offsets[j]);
+                let overlap_x = a_max.x.min(b_max.x) - a_min.x.max(b_min.x) + padding;
+                let overlap_y = a_max.y.min(b_max.y) - a_min.y.max(b_min.y) + padding;
+                if overlap_x <= OVERLAP_EPSILON || overlap_y <= OVERLAP_EPSILON {
+                    continue;
+                }
+                moved = true;
+                // `j` goes right or up unless it is already left or below
+                let push = if overlap_x < overlap_y {
+                    let side = if b_min.x + b_max.x >= a_min.x + a_max.x {
+                        1.0
+                    } else {
+                        -1.0
+                    };
+                    Vector3::new(side * overlap_x / 2.0, 0.0, 0.0)
+                } else {
+                    let side = if b_min.y + b_max.y >= a_min.y + a_max.y {
+                        1.0
+                    } else {
+                        -1.0
+                    };
+                    Vector3::new(0.0, side * overlap_y / 2.0, 0.0)
+                };
+                offsets[i] = offsets[i] - push;
+                offsets[j] = offsets[j] + push;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+    offsets
+}
+
+/// Endpoints of the leader from where a label's center was to the nearest
+/// point of its moved box, if that is further than `threshold`
+fn leader(
+    bounds: (Vector3, Vector3),
+    offset: Vector3,
+    threshold: f32,
+) -> Option<(Vector3, Vector3)> {
+    let (min, max) = bounds;
+    let anchor = (min + max) * 0.5;
+    let (min, max) = (min + offset, max + offset);
+    let end = Vector3::new(
+        anchor.x.clamp(min.x, max.x),
+        anchor.y.clamp(min.y, max.y),
+        anchor.z,
+    );
+    (anchor.distance(&end) > threshold).then_some((anchor, end))
+}
+
+impl SceneGraph {
+    /// Move `labels` apart once and return the leader lines added for the
+    /// labels that moved clear of where they were
+    pub fn separate_labels(&mut self, labels: &[NodeId], layout: LabelLayout) -> Vec<NodeId> {
+        self.update_transforms();
+        let (ids, boxes) = self.label_boxes(labels);
+        let offsets = separate_boxes(&boxes, layout.padding, layout.iterations);
+
+        let mut leaders = Vec::new();
+        for ((id, bounds), offset) in ids.into_iter().zip(boxes).zip(offsets) {
+            if offset.length() <= f32::EPSILON {
+                continue;
+            }
+            let Some(node) = self.nodes.get(&id) else {
+                continue;
+            };
+            let parent_world = node
+                .parent
+                .and_then(|parent| self.nodes.get(&parent))
+                .map_or_else(Transform::new, |parent| parent.world_transform);
+            let position = parent_world
+                .inverse()
+                .transform_point(node.world_transform.position + offset)
+                - node.modifier_offset.position;
+            let name = format!("{}_leader", node.name);
+            if let Some(node) = self.nodes.get_mut(&id) {
+                node._local_transform.position = position;
+            }
+            if let Some((start, end)) = leader(bounds, offset, layout.leader_threshold) {
+                leaders.push(
+                    self.add_line(
+                        name,
+                        start,
+                        end,
+                        layout.leader_color,
+                        layout.leader_thickness,
+                    )
+                    .build(),
+                );
+            }
+        }
+        self.update_transforms();
+        leaders
+    }
+
+    /// Keep `labels` apart after every transform update, drawing leaders
+    /// for labels moved clear of where they would be
+    ///
+    /// The labels' own transforms are left alone: each update measures
+    /// them where their animations and constraints put them, then shifts
+    /// what is drawn.
+    pub fn keep_labels_apart(&mut self, labels: Vec<NodeId>, layout: LabelLayout) {
+        let leaders = labels
+            .iter()
+            .map(|&label| {
+                let name = self
+                    .nodes
+                    .get(&label)
+                    .map_or_else(|| "label".to_string(), |node| node.name.clone());
+                self.add_line(
+                    format!("{name}_leader"),
+                    Vector3::zero(),
+                    Vector3::zero(),
+                    layout.leader_color,
+                    layout.leader_thickness,
+                )
+                .visible(false)
+                .build()
+            })
+            .collect();
+        self.label_groups.push(LabelGroup {
+            labels,
+            leaders,
+            layout,
+        });
+        self.update_transforms();
+    }
+
+    /// Shift the labels of every group kept apart to where they don't
+    /// overlap, and point their leaders at them
+    pub(super) fn apply_label_layouts(&mut self) {
+        for index in 0..self.label_groups.len() {
+            let group = self.label_groups[index].clone();
+            let (ids, boxes) = self.label_boxes(&group.labels);
+            let offsets = separate_boxes(&boxes, group.layout.padding, group.layout.iterations);
+            for ((id, bounds), offset) in ids.into_iter().zip(boxes).zip(offsets) {
+                for node in self.subtree(id) {
+                    if let Some(node) = self.nodes.get_mut(&node) {
+                        node.world_transform.position = node.world_transform.position + offset;
+                    }
+                }
+
+                let Some(position) = group.labels.iter().position(|&label| label == id) else {
+                    continue;
+                };
+                let Some(leader_node) = self.nodes.get_mut(&group.leaders[position]) else {
+                    continue;
+                };
+                match leader(bounds, offset, group.layout.leader_threshold) {
+                    Some((anchor, end)) => {
+                        leader_node.visible = true;
+                        if let Some(Renderable::Line {
+                            start,
+                            end: line_end,
+                            ..
+                        }) = &mut leader_node.renderable
+                        {
+                            // Leaders are root nodes at the origin
+                            *start = anchor;
+                            *line_end = end;
+                        }
+                    }
+                    None => leader_node.visible = false,
+                }
+            }
+        }
+    }
+
+    /// Labels that draw something, with their scene bounding boxes
+    fn label_boxes(&self, labels: &[NodeId]) -> (Vec<NodeId>, Vec<(Vector3, Vector3)>) {
+        labels
+            .iter()
+            .filter_map(|&id| Some((id, self.bounds(id)?)))
+            .unzip()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::TimeValue;
+
+    fn overlapping(scene: &SceneGraph, labels: &[NodeId]) -> bool {
+        let (_, boxes) = scene.label_boxes(labels);
+        boxes.iter().enumerate().any(|(i, a)| {
+            boxes[i + 1..].iter().any(|b| {
+                a.0.x < b.1.x - 1e-4
+                    && b.0.x < a.1.x - 1e-4
+                    && a.0.y < b.1.y - 1e-4
+                    && b.0.y < a.1.y - 1e-4
+            })
+        })
+    }
+
+    #[test]
+    fn test_separate_labels_once() {
+        let mut scene = SceneGraph::new();
+        let labels: Vec<NodeId> = (0..4)
+            .map(|i| {
+                scene
+                    .add_text(format!("label{i}"), "value", 24.0, None)
+                    .at(0.005 * i as f32, 0.0, 0.0)
+                    .build()
+            })
+            .collect();
+        let far = scene
+            .add_text("far", "alone", 24.0, None)
+            .at(1.0, 1.0, 0.0)
+            .build();
+        scene.update_transforms();
+        assert!(overlapping(&scene, &labels));
+
+        let mut all = labels.clone();
+        all.push(far);
+        let leaders = scene.separate_labels(&all, LabelLayout::default());
+        assert!(!overlapping(&scene, &all));
+        // Stacked vertically, since text is wider than it is tall
+        let x = scene
+            .get_node(labels[0])
+            .unwrap()
+            .world_transform
+            .position
+            .x;
+        assert!(x.abs() < 0.01);
+        assert_eq!(
+            scene.get_node(far).unwrap().world_transform.position,
+            Vector3::new(1.0, 1.0, 0.0)
+        );
+        assert!(!leaders.is_empty() && leaders.len() <= 4);
+    }
+
+    #[test]
+    fn test_labels_kept_apart_while_moving() {
+        let mut scene = SceneGraph::new();
+        let still = scene.add_text("still", "fixed", 24.0, None).build();
+        let moving = scene
+            .add_text("moving", "mover", 24.0, None)
+            .at(1.0, 0.0, 0.0)
+            .move_to(0.0, Vector3::zero(), 1.0)
+            .build();
+        scene.keep_labels_apart(vec![still, moving], LabelLayout::default());
+
+        scene.update_animations(TimeValue::new(0.5));
+        assert_eq!(
+            scene.get_node(still).unwrap().world_transform.position,
+            Vector3::zero()
+        );
+        scene.update_animations(TimeValue::new(0.5));
+        assert!(!overlapping(&scene, &[still, moving]));
+        // Drawn apart, but the animation still ends where it was sent
+        let local = scene.get_node(moving).unwrap()._local_transform.position;
+        assert_eq!(local, Vector3::zero());
+        assert!(scene.find_by_name("still_leader").is_some());
+        assert!(scene.find_by_name("moving_leader").is_some());
+    }
+}
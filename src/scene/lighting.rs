@@ -0,0 +1,208 @@
+//! Lights and Materials
+//!
+//! Scene-level light sources and per-node surface materials used by the lit
+//! 3D shader. Lighting follows the Blinn-Phong model:
+//!
+//! - **Light**: directional or point source with color and intensity
+//! - **Material**: diffuse/specular response of a node's surface
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::scene::*;
+//! use diomanim::core::*;
+//!
+//! let mut scene = SceneGraph::new();
+//! scene.add_light(Light::directional(Vector3::new(-1.0, -1.0, -1.0), Color::WHITE, 1.0));
+//! scene.add_light(Light::point(Vector3::new(0.0, 2.0, 2.0), Color::YELLOW, 0.8));
+//!
+//! scene.add_circle("ball", 1.0, Color::BLUE)
+//!     .material(Material::glossy());
+//! ```
+
+use crate::core::{Color, Vector3};
+
+/// Maximum number of lights uploaded to the GPU per frame
+pub const MAX_LIGHTS: usize = 8;
+
+/// Kind of light source
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightKind {
+    /// Parallel rays travelling along `direction` (e.g. sunlight)
+    Directional { direction: Vector3 },
+    /// Omnidirectional light at `position`, fading out to zero at `range`
+    Point { position: Vector3, range: f32 },
+}
+
+/// A light source stored on the scene graph
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Light {
+    pub kind: LightKind,
+    pub color: Color,
+    pub intensity: f32,
+}
+
+impl Light {
+    /// Create a directional light shining along `direction`
+    pub fn directional(direction: Vector3, color: Color, intensity: f32) -> Self {
+        Self {
+            kind: LightKind::Directional {
+                direction: direction.normalized(),
+            },
+            color,
+            intensity,
+        }
+    }
+
+    /// Create a point light at `position` with a default range of 10 units
+    pub fn point(position: Vector3, color: Color, intensity: f32) -> Self {
+        Self {
+            kind: LightKind::Point {
+                position,
+                range: 10.0,
+            },
+            color,
+            intensity,
+        }
+    }
+
+    /// Set the falloff range (point lights only)
+    pub fn with_range(mut self, new_range: f32) -> Self {
+        if let LightKind::Point { range, .. } = &mut self.kind {
+            *range = new_range.max(0.001);
+        }
+        self
+    }
+
+    /// Direction from `point` towards the light and the attenuation at `point`
+    pub fn incidence(&self, point: Vector3) -> (Vector3, f32) {
+        match self.kind {
+            LightKind::Directional { direction } => (-direction, 1.0),
+            LightKind::Point { position, range } => {
+                let to_light = position - point;
+                let distance = to_light.length();
+                let falloff = (1.0 - distance / range).clamp(0.0, 1.0);
+                (to_light.normalized(), falloff * falloff)
+            }
+        }
+    }
+}
+
+/// Surface response of a node to scene lights
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Material {
+    /// Diffuse reflectance multiplier applied to the node's color
+    pub diffuse: f32,
+    /// Specular highlight strength
+    pub specular: f32,
+    /// Specular exponent (higher = tighter highlight)
+    pub shininess: f32,
+}
+
+impl Material {
+    pub fn new(diffuse: f32, specular: f32, shininess: f32) -> Self {
+        Self {
+            diffuse,
+            specular,
+            shininess,
+        }
+    }
+
+    /// Fully diffuse surface with no highlight
+    pub fn matte() -> Self {
+        Self::new(1.0, 0.0, 1.0)
+    }
+
+    /// Diffuse surface with a sharp specular highlight
+    pub fn glossy() -> Self {
+        Self::new(0.9, 0.6, 64.0)
+    }
+
+    /// Shade a surface point on the CPU (mirrors the lit WGSL shader)
+    pub fn shade(
+        &self,
+        base: Color,
+        point: Vector3,
+        normal: Vector3,
+        eye: Vector3,
+        lights: &[Light],
+        ambient: Color,
+    ) -> Color {
+        let view = (eye - point).normalized();
+        let mut normal = normal.normalized();
+        // Flat shapes are two-sided: light whichever face looks at the camera
+        if normal.dot(&view) < 0.0 {
+            normal = -normal;
+        }
+
+        let mut r = ambient.r * base.r;
+        let mut g = ambient.g * base.g;
+        let mut b = ambient.b * base.b;
+
+        for light in lights.iter().take(MAX_LIGHTS) {
+            let (to_light, attenuation) = light.incidence(point);
+            let lambert = normal.dot(&to_light).max(0.0);
+            if lambert <= 0.0 || attenuation <= 0.0 {
+                continue;
+            }
+
+            let half = (to_light + view).normalized();
+            let highlight = normal.dot(&half).max(0.0).powf(self.shininess) * self.specular;
+            let diffuse = lambert * self.diffuse;
+            let strength = light.intensity * attenuation;
+
+            r += light.color.r * strength * (diffuse * base.r + highlight);
+            g += light.color.g * strength * (diffuse * base.g + highlight);
+            b += light.color.b * strength * (diffuse * base.b + highlight);
+        }
+
+        Color::rgba(r, g, b, base.a)
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self::new(1.0, 0.25, 32.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directional_light_faces_surface() {
+        let light = Light::directional(Vector3::new(0.0, 0.0, -1.0), Color::WHITE, 1.0);
+        let eye = Vector3::new(0.0, 0.0, 5.0);
+        let lit = Material::matte().shade(
+            Color::RED,
+            Vector3::zero(),
+            Vector3::forward(),
+            eye,
+            &[light],
+            Color::BLACK,
+        );
+        assert!((lit.r - 1.0).abs() < 1e-5);
+        assert!(lit.g.abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_unlit_scene_uses_ambient() {
+        let shaded = Material::default().shade(
+            Color::WHITE,
+            Vector3::zero(),
+            Vector3::forward(),
+            Vector3::new(0.0, 0.0, 5.0),
+            &[],
+            Color::new(0.2, 0.2, 0.2),
+        );
+        assert!((shaded.r - 0.2).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_point_light_range_falloff() {
+        let light = Light::point(Vector3::new(0.0, 0.0, 2.0), Color::WHITE, 1.0).with_range(1.0);
+        let (_, attenuation) = light.incidence(Vector3::zero());
+        assert_eq!(attenuation, 0.0);
+    }
+}
@@ -10,6 +10,24 @@
 //! - **SceneNode**: A node in the hierarchy with transform, children, and renderable
 //! - **NodeId**: Unique identifier for scene nodes
 //! - **Renderable**: Attachable visual representation (Circle, Square, etc.)
+//! - **Light / Material**: Scene lights and per-node surface response for 3D shading
+//! - **PostEffect**: Bloom, vignette and blur applied to the finished frame
+//! - **NodeEffect**: Drop shadow or outer glow drawn behind a single node
+//! - **ClipMask**: Shape that limits a node and its subtree (stencil clipping)
+//! - **Theme**: Default background, colors, stroke width and font for builders
+//! - **Prefab**: Subtree built once and instantiated many times
+//! - **ScenePatch**: Node additions, removals and property changes applied in place
+//! - **ShaderMaterial**: Custom WGSL fragment snippet shading a node's shape
+//! - **BlendMode**: Normal, additive, multiply or screen compositing per node
+//! - **RenderTarget**: Subtree (or zoomed scene) rendered to its own texture, shown by image nodes
+//! - **Constraint**: Offset, distance, look-at, alignment and endpoint ties to other nodes
+//! - **Updater**: Rebuilds a node's renderable from other nodes after every transform update
+//! - **EventHandler**: Click and hover callbacks run by the live preview, with hit-testing
+//! - **ShapeSampling**: Points evenly spaced along a shape's outline or spread over its area
+//! - **Bounds**: Box around what a node and its subtree draw, for placing things beside it
+//! - **LabelLayout**: Overlapping labels nudged apart with leader lines, once or every update
+//! - **Progress**: How much of a node's geometry is drawn, swept along its outline by Create
+//! - **NodeState**: Snapshot of a node morphed into by MoveToTarget, or saved and later restored
 //!
 //! ## Hierarchy
 //!
@@ -40,14 +58,59 @@
 //! scene.update_transforms();
 //! ```
 
+pub mod blend;
+pub mod bounds;
 pub mod builder;
-
-use crate::animation::property::AnimationInstance;
-use crate::core::{TimeValue, Transform, Vector3};
+pub mod clip;
+pub mod constraint;
+pub mod effects;
+pub mod interaction;
+pub mod labels;
+pub mod lighting;
+pub mod patch;
+#[cfg(feature = "physics")]
+pub mod physics;
+pub mod post;
+pub mod prefab;
+pub mod reveal;
+pub mod sampling;
+pub mod shader;
+pub mod state;
+pub mod target;
+pub mod theme;
+pub mod updater;
+
+use crate::animation::{
+    deform::{DeformContext, Deformation, VertexDeformer},
+    effects::{parse_glyph_color_track, Stagger},
+    procedural::{self, ModifierOffset, ProceduralModifier},
+    property::AnimationInstance,
+};
+use crate::assets::AssetHandle;
+use crate::core::{transform::Quaternion, Color, TimeValue, Timeline, Transform, Vector3};
+use crate::mobjects::{AudioVisualizer, DecimalNumber, TracedPath, VisualizerStyle};
 use crate::render::TransformUniform;
 use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
 
+pub use blend::BlendMode;
 pub use builder::NodeBuilder;
+pub use clip::ClipMask;
+pub use constraint::Constraint;
+pub use effects::NodeEffect;
+pub use interaction::{EventHandler, EventHandlers, PointerEvent};
+pub use labels::LabelLayout;
+pub use lighting::{Light, LightKind, Material, MAX_LIGHTS};
+pub use patch::{NodeChange, PatchOp, ScenePatch};
+pub use post::{PostEffect, PostEffectKind};
+pub use prefab::Prefab;
+pub use sampling::ShapeSampling;
+pub use shader::ShaderMaterial;
+pub use state::NodeState;
+pub use target::{RenderTarget, TargetSource};
+pub use theme::Theme;
+pub use updater::Updater;
 
 /// Unique identifier for scene nodes
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -75,10 +138,64 @@ pub struct SceneNode {
     pub visible: bool,
     /// Opacity (0.0 = fully transparent, 1.0 = fully opaque)
     pub opacity: f32,
+    /// How much of the renderable's geometry is drawn (0..1), see
+    /// [`SceneNode::drawn_renderable`]
+    pub progress: f32,
     /// Attached renderable object
     pub renderable: Option<Renderable>,
+    /// State [`effects::move_to_target`](crate::animation::effects::move_to_target)
+    /// animates the node into, see [`SceneNode::generate_target`]
+    pub target: Option<NodeState>,
+    /// State kept by [`SceneNode::save_state`] for
+    /// [`effects::restore`](crate::animation::effects::restore) to return to
+    pub saved_state: Option<NodeState>,
+    /// Where the running transition started from
+    transition_start: Option<NodeState>,
+    /// Colors replacing the text color on ranges of glyphs (characters of
+    /// [`Renderable::glyph_text`]); later ranges win where they overlap
+    pub glyph_colors: Vec<(Range<usize>, Color)>,
+    /// Surface material used when the scene is lit
+    pub material: Material,
+    /// Custom fragment shader drawn in place of the shape's flat color
+    pub shader: Option<ShaderMaterial>,
+    /// How the node's colors combine with what is drawn behind it
+    pub blend: BlendMode,
     /// Active animations on this node
     pub animations: Vec<AnimationInstance>,
+    /// Shadows and glows drawn behind this node
+    pub effects: Vec<NodeEffect>,
+    /// Mask limiting where this node and its children are drawn
+    pub clip: Option<ClipMask>,
+    /// Size of `clip` relative to its declared size (animated by iris effects)
+    pub clip_scale: f32,
+    /// Noise and pulse motion layered on the animated transform
+    pub modifiers: Vec<ProceduralModifier>,
+    /// Time since the node's modifiers started, for sampling them
+    modifier_time: f32,
+    /// Current offset of `modifiers`, applied to the world transform
+    modifier_offset: ModifierOffset,
+    /// Effects bending the node's geometry when it is drawn
+    pub deformers: Vec<Arc<dyn VertexDeformer>>,
+    /// Deformation time and the node's velocity over the last update
+    deform_context: DeformContext,
+    /// Animated number whose digits replace the node's text every update
+    pub number: Option<DecimalNumber>,
+    /// Trail whose points replace the node's polyline every update
+    pub trace: Option<TracedPath>,
+    /// Sound whose levels resize the node's bars or reshape its line
+    /// every update
+    pub visualizer: Option<AudioVisualizer>,
+    /// Rebuilds the node's renderable from other nodes whenever transforms
+    /// are updated
+    pub updater: Option<Updater>,
+    /// Ties to other nodes enforced after every transform update
+    pub constraints: Vec<Constraint>,
+    /// Labels for finding groups of nodes with [`SceneGraph::find_by_tag`]
+    pub tags: Vec<String>,
+    /// Texture the node's subtree is drawn into instead of the frame
+    pub render_target: Option<RenderTarget>,
+    /// Callbacks run when the node is clicked or hovered
+    pub handlers: EventHandlers,
 }
 
 impl SceneNode {
@@ -92,8 +209,35 @@ impl SceneNode {
             children: Vec::new(),
             visible: true,
             opacity: 1.0,
+            progress: 1.0,
             renderable: None,
+            target: None,
+            saved_state: None,
+            transition_start: None,
+            glyph_colors: Vec::new(),
+            material: Material::default(),
+            shader: None,
+            blend: BlendMode::Normal,
             animations: Vec::new(),
+            effects: Vec::new(),
+            clip: None,
+            clip_scale: 1.0,
+            modifiers: Vec::new(),
+            modifier_time: 0.0,
+            modifier_offset: ModifierOffset::default(),
+            deformers: Vec::new(),
+            deform_context: DeformContext {
+                time: 0.0,
+                velocity: Vector3::zero(),
+            },
+            number: None,
+            trace: None,
+            visualizer: None,
+            updater: None,
+            constraints: Vec::new(),
+            tags: Vec::new(),
+            render_target: None,
+            handlers: EventHandlers::default(),
         }
     }
 
@@ -107,8 +251,35 @@ impl SceneNode {
             children: Vec::new(),
             visible: true,
             opacity: 1.0,
+            progress: 1.0,
             renderable: None,
+            target: None,
+            saved_state: None,
+            transition_start: None,
+            glyph_colors: Vec::new(),
+            material: Material::default(),
+            shader: None,
+            blend: BlendMode::Normal,
             animations: Vec::new(),
+            effects: Vec::new(),
+            clip: None,
+            clip_scale: 1.0,
+            modifiers: Vec::new(),
+            modifier_time: 0.0,
+            modifier_offset: ModifierOffset::default(),
+            deformers: Vec::new(),
+            deform_context: DeformContext {
+                time: 0.0,
+                velocity: Vector3::zero(),
+            },
+            number: None,
+            trace: None,
+            visualizer: None,
+            updater: None,
+            constraints: Vec::new(),
+            tags: Vec::new(),
+            render_target: None,
+            handlers: EventHandlers::default(),
         }
     }
 
@@ -117,44 +288,102 @@ impl SceneNode {
         self.renderable = Some(renderable);
     }
 
+    /// Draw `glyphs` of the node's text in `color`, replacing an earlier
+    /// color for exactly the same glyphs
+    pub fn set_glyph_color(&mut self, glyphs: Range<usize>, color: Color) {
+        match self.glyph_colors.iter_mut().find(|(g, _)| *g == glyphs) {
+            Some(entry) => entry.1 = color,
+            None => self.glyph_colors.push((glyphs, color)),
+        }
+    }
+
+    /// Whether the node carries `tag`
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
     /// Add an animation to this node
     pub fn add_animation(&mut self, animation: AnimationInstance) {
         self.animations.push(animation);
     }
 
+    /// Layer a procedural modifier on this node's transform
+    pub fn add_modifier(&mut self, modifier: ProceduralModifier) {
+        self.modifiers.push(modifier);
+    }
+
+    /// Offset the modifiers currently add to the transform
+    pub fn modifier_offset(&self) -> ModifierOffset {
+        self.modifier_offset
+    }
+
+    /// Bend the node's geometry with `deformer` when it is drawn
+    pub fn add_deformer(&mut self, deformer: impl VertexDeformer + 'static) {
+        self.deformers.push(Arc::new(deformer));
+    }
+
+    /// The deformation to draw the node with, if it has deformers
+    pub fn deformation(&self) -> Option<Deformation> {
+        (!self.deformers.is_empty()).then(|| Deformation {
+            deformers: self.deformers.clone(),
+            context: self.deform_context,
+        })
+    }
+
+    /// Advance deformation time and measure how far the world position moved
+    fn update_deform_context(&mut self, previous_position: Vector3, delta_time: TimeValue) {
+        if self.deformers.is_empty() || delta_time.value <= 0.0 {
+            return;
+        }
+        self.deform_context.time += delta_time.value;
+        self.deform_context.velocity =
+            (self.world_transform.position - previous_position) / delta_time.value;
+    }
+
     /// Update animations and return true if the transform was modified
     pub fn update_animations(&mut self, delta_time: TimeValue) -> bool {
         let mut transform_changed = false;
 
+        if let Some(number) = &mut self.number {
+            let text = number.advance(delta_time);
+            if let Some(Renderable::Text { content, .. }) = &mut self.renderable {
+                *content = text;
+            }
+        }
+
+        if let Some(shader) = &mut self.shader {
+            shader.time += delta_time.value;
+        }
+
+        if !self.modifiers.is_empty() {
+            self.modifier_time += delta_time.value;
+            self.modifier_offset = procedural::sample_all(&self.modifiers, self.modifier_time);
+            transform_changed = true;
+        }
+
+        // State the node is moving to and how far, applied after the tracks
+        let mut transition = None;
+        // Glyph recolors, applied once the animations are no longer borrowed
+        let mut glyph_colors = Vec::new();
         for anim in &mut self.animations {
             if anim.is_playing {
-                // Update animation time
-                let local_time = anim.current_time;
-                let duration = anim.clip.duration();
-                let new_time = local_time + delta_time;
-
-                if duration > TimeValue::new(0.0) {
-                    if anim.clip.loop_animation {
-                        let loop_time = (new_time % duration).seconds();
-                        anim.current_time = TimeValue::new(loop_time);
-                    } else if new_time >= duration {
-                        anim.is_playing = false;
-                        anim.current_time = duration;
-                    } else {
-                        anim.current_time = new_time;
-                    }
-                } else {
-                    anim.current_time = new_time;
+                // Update animation time (rate and play mode)
+                anim.advance(delta_time);
+                if !anim.has_started() && !anim.fill_before {
+                    continue;
                 }
 
                 // Sample each track at current time
                 for track_box in &anim.clip.tracks {
+                    if !track_box.is_enabled() {
+                        continue;
+                    }
                     // Downcast to concrete AnimationTrack<Vector3>
                     if let Some(track) = track_box
                         .as_any()
                         .downcast_ref::<crate::animation::property::AnimationTrack<Vector3>>(
                     ) {
-                        let sample = track.sample(anim.current_time);
+                        let sample = track.sample_clip(anim.current_time);
 
                         match track.name.as_str() {
                             "position" => {
@@ -162,8 +391,9 @@ impl SceneNode {
                                 transform_changed = true;
                             }
                             "rotation" => {
-                                // For now, we only use Z rotation (2D)
-                                self._local_transform.rotation.z = sample.z;
+                                // Euler angles in radians: roll, pitch, yaw
+                                self._local_transform.rotation =
+                                    Quaternion::from_euler_angles(sample.x, sample.y, sample.z);
                                 transform_changed = true;
                             }
                             "scale" => {
@@ -173,16 +403,64 @@ impl SceneNode {
                             "opacity" => {
                                 self.opacity = sample.x.clamp(0.0, 1.0);
                             }
+                            "progress" => {
+                                self.progress = sample.x.clamp(0.0, 1.0);
+                            }
+                            "visible" => {
+                                self.visible = sample.x >= 0.5;
+                            }
+                            "move_to_target" => {
+                                transition = self.target.clone().map(|to| (to, sample.x));
+                            }
+                            "restore" => {
+                                transition = self.saved_state.clone().map(|to| (to, sample.x));
+                            }
+                            "clip_scale" => {
+                                self.clip_scale = sample.x.max(0.0);
+                            }
+                            "target_center" => {
+                                if let Some(target) = &mut self.render_target {
+                                    target.center = sample;
+                                }
+                            }
                             _ => {}
                         }
+                    } else if let Some(track) = track_box
+                        .as_any()
+                        .downcast_ref::<crate::animation::property::AnimationTrack<
+                        Quaternion,
+                    >>() {
+                        if track.name == "rotation" {
+                            self._local_transform.rotation = track.sample_clip(anim.current_time);
+                            transform_changed = true;
+                        }
+                    } else if let Some(track) = track_box
+                        .as_any()
+                        .downcast_ref::<crate::animation::property::AnimationTrack<Color>>(
+                    ) {
+                        if track.name == "color" {
+                            if let Some(renderable) = &mut self.renderable {
+                                *renderable.color_mut() = track.sample_clip(anim.current_time);
+                            }
+                        } else if let Some(glyphs) = parse_glyph_color_track(&track.name) {
+                            glyph_colors.push((glyphs, track.sample_clip(anim.current_time)));
+                        }
                     }
                 }
             }
         }
 
-        // Remove finished non-looping animations
-        self.animations
-            .retain(|anim| anim.is_playing || anim.clip.loop_animation);
+        for (glyphs, color) in glyph_colors {
+            self.set_glyph_color(glyphs, color);
+        }
+
+        if let Some((to, progress)) = transition {
+            self.step_transition(&to, progress);
+            transform_changed = true;
+        }
+
+        // Remove animations that played once to their end
+        self.animations.retain(|anim| !anim.is_finished());
 
         transform_changed
     }
@@ -192,28 +470,15 @@ impl SceneNode {
         // Create a column-major 4x4 transformation matrix for WebGPU
         // WebGPU/WGSL uses column-major matrices by default
 
-        let pos = self.world_transform.position;
-        let scale = self.world_transform.scale;
-
-        // Simple transform without rotation (2D)
-        // Column-major matrix: each array is a column
-        // | sx  0   0   tx |
-        // | 0   sy  0   ty |
-        // | 0   0   sz  tz |
-        // | 0   0   0   1  |
+        // translation * rotation * scale
         TransformUniform {
-            model_view_proj: [
-                [scale.x, 0.0, 0.0, 0.0],   // Column 0: X axis
-                [0.0, scale.y, 0.0, 0.0],   // Column 1: Y axis
-                [0.0, 0.0, scale.z, 0.0],   // Column 2: Z axis
-                [pos.x, pos.y, pos.z, 1.0], // Column 3: Translation
-            ],
+            model_view_proj: self.world_transform.matrix().to_cols_array_2d(),
         }
     }
 }
 
 /// Renderable objects that can be attached to scene nodes
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Renderable {
     Circle {
         radius: f32,
@@ -240,6 +505,14 @@ pub enum Renderable {
         vertices: Vec<Vector3>,
         color: crate::core::Color,
     },
+    /// Open path through `points`; `fade` (0..1) fades it out towards the
+    /// first point, for trails
+    Polyline {
+        points: Vec<Vector3>,
+        color: crate::core::Color,
+        thickness: f32,
+        fade: f32,
+    },
     Text {
         content: String,
         font_size: f32,
@@ -250,6 +523,34 @@ pub enum Renderable {
         font_size: f32,
         color: crate::core::Color,
     },
+    /// Text with per-span styling; `color` is the color of unstyled spans
+    RichText {
+        text: crate::text::RichText,
+        font_size: f32,
+        color: crate::core::Color,
+    },
+    /// Text whose glyphs follow a path
+    TextOnPath {
+        content: String,
+        path: crate::text::TextPath,
+        font_size: f32,
+        color: crate::core::Color,
+    },
+    /// A PNG loaded by an [`AssetServer`](crate::assets::AssetServer),
+    /// stretched over `width` x `height` and multiplied by `color`
+    Image {
+        asset: AssetHandle,
+        width: f32,
+        height: f32,
+        color: crate::core::Color,
+    },
+    /// An SVG's filled shapes fitted into `width` x `height`, multiplied by `color`
+    Svg {
+        asset: AssetHandle,
+        width: f32,
+        height: f32,
+        color: crate::core::Color,
+    },
     // Future: Mesh, Sprite, etc.
 }
 
@@ -303,6 +604,67 @@ impl Renderable {
         }
     }
 
+    pub fn as_polyline(&self) -> Option<(&Vec<Vector3>, &crate::core::Color, &f32, &f32)> {
+        match self {
+            Renderable::Polyline {
+                points,
+                color,
+                thickness,
+                fade,
+            } => Some((points, color, thickness, fade)),
+            _ => None,
+        }
+    }
+
+    /// Whether this is drawn with the glyph (text) pipeline rather than as a shape
+    pub fn is_glyphs(&self) -> bool {
+        matches!(
+            self,
+            Renderable::Text { .. }
+                | Renderable::Math { .. }
+                | Renderable::RichText { .. }
+                | Renderable::TextOnPath { .. }
+        )
+    }
+
+    /// The characters a text-like renderable draws, in drawing order, which
+    /// [`SceneNode::glyph_colors`] index: math is its laid-out symbols
+    /// rather than its LaTeX source
+    pub fn glyph_text(&self) -> Option<String> {
+        use crate::math::{expression::parse_latex, layout::MathLayout};
+        match self {
+            Renderable::Text { content, .. } | Renderable::TextOnPath { content, .. } => {
+                Some(content.clone())
+            }
+            Renderable::Math {
+                latex, font_size, ..
+            } => Some(
+                MathLayout::layout_node(&parse_latex(latex), *font_size)
+                    .flatten()
+                    .into_iter()
+                    .map(|(_, text, _)| text)
+                    .collect(),
+            ),
+            Renderable::RichText {
+                text, font_size, ..
+            } => Some(
+                text.layout(*font_size, |_| 0.0)
+                    .into_iter()
+                    .map(|run| run.text)
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Glyphs of the first `term` in [`Self::glyph_text`], for recoloring
+    /// one part of a text or equation
+    pub fn find_glyphs(&self, term: &str) -> Option<Range<usize>> {
+        let text = self.glyph_text()?;
+        let start = text[..text.find(term)?].chars().count();
+        Some(start..start + term.chars().count())
+    }
+
     pub fn as_text(&self) -> Option<(&String, &f32, &crate::core::Color)> {
         match self {
             Renderable::Text {
@@ -324,6 +686,92 @@ impl Renderable {
             _ => None,
         }
     }
+
+    pub fn as_rich_text(&self) -> Option<(&crate::text::RichText, &f32, &crate::core::Color)> {
+        match self {
+            Renderable::RichText {
+                text,
+                font_size,
+                color,
+            } => Some((text, font_size, color)),
+            _ => None,
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub fn as_text_on_path(
+        &self,
+    ) -> Option<(&String, &crate::text::TextPath, &f32, &crate::core::Color)> {
+        match self {
+            Renderable::TextOnPath {
+                content,
+                path,
+                font_size,
+                color,
+            } => Some((content, path, font_size, color)),
+            _ => None,
+        }
+    }
+
+    pub fn as_image(&self) -> Option<(&AssetHandle, &f32, &f32, &crate::core::Color)> {
+        match self {
+            Renderable::Image {
+                asset,
+                width,
+                height,
+                color,
+            } => Some((asset, width, height, color)),
+            _ => None,
+        }
+    }
+
+    pub fn as_svg(&self) -> Option<(&AssetHandle, &f32, &f32, &crate::core::Color)> {
+        match self {
+            Renderable::Svg {
+                asset,
+                width,
+                height,
+                color,
+            } => Some((asset, width, height, color)),
+            _ => None,
+        }
+    }
+
+    /// The renderable's color
+    pub fn color(&self) -> crate::core::Color {
+        match self {
+            Renderable::Circle { color, .. }
+            | Renderable::Rectangle { color, .. }
+            | Renderable::Line { color, .. }
+            | Renderable::Arrow { color, .. }
+            | Renderable::Polygon { color, .. }
+            | Renderable::Polyline { color, .. }
+            | Renderable::Text { color, .. }
+            | Renderable::Math { color, .. }
+            | Renderable::RichText { color, .. }
+            | Renderable::TextOnPath { color, .. }
+            | Renderable::Image { color, .. }
+            | Renderable::Svg { color, .. } => *color,
+        }
+    }
+
+    /// The renderable's color, for recoloring it in place
+    pub fn color_mut(&mut self) -> &mut crate::core::Color {
+        match self {
+            Renderable::Circle { color, .. }
+            | Renderable::Rectangle { color, .. }
+            | Renderable::Line { color, .. }
+            | Renderable::Arrow { color, .. }
+            | Renderable::Polygon { color, .. }
+            | Renderable::Polyline { color, .. }
+            | Renderable::Text { color, .. }
+            | Renderable::Math { color, .. }
+            | Renderable::RichText { color, .. }
+            | Renderable::TextOnPath { color, .. }
+            | Renderable::Image { color, .. }
+            | Renderable::Svg { color, .. } => color,
+        }
+    }
 }
 
 /// Scene graph manages the hierarchy of scene nodes
@@ -331,6 +779,17 @@ pub struct SceneGraph {
     nodes: HashMap<NodeId, SceneNode>,
     root_nodes: Vec<NodeId>,
     next_id: u32,
+    lights: Vec<Light>,
+    ambient_light: Color,
+    post_effects: Vec<PostEffect>,
+    theme: Theme,
+    /// Node whose hover handler last ran, until the pointer moves off it
+    hovered: Option<NodeId>,
+    /// Labels pushed apart after every transform update
+    label_groups: Vec<labels::LabelGroup>,
+    /// Rigid bodies stepped every update
+    #[cfg(feature = "physics")]
+    physics: Option<crate::physics::PhysicsWorld>,
 }
 
 impl SceneGraph {
@@ -339,9 +798,27 @@ impl SceneGraph {
             nodes: HashMap::new(),
             root_nodes: Vec::new(),
             next_id: 1, // Start from 1, 0 is reserved
+            lights: Vec::new(),
+            ambient_light: Color::new(0.15, 0.15, 0.15),
+            post_effects: Vec::new(),
+            theme: Theme::default(),
+            hovered: None,
+            label_groups: Vec::new(),
+            #[cfg(feature = "physics")]
+            physics: None,
         }
     }
 
+    /// Styling defaults used by the builder methods
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Set the styling defaults for nodes added from now on
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
     /// Create a new node and return its ID
     pub fn create_node(&mut self, name: String) -> NodeId {
         let id = NodeId::new(self.next_id);
@@ -449,6 +926,111 @@ impl SceneGraph {
         for root_id in root_ids {
             self.update_node_transform_recursive(root_id, Transform::new());
         }
+
+        self.apply_constraints();
+        self.apply_label_layouts();
+
+        // Point scene captures at the nodes they follow
+        let follows: Vec<(NodeId, Vector3)> = self
+            .nodes
+            .values()
+            .filter_map(|node| {
+                let followed = node.render_target.as_ref()?.follow?;
+                Some((node.id, self.nodes.get(&followed)?.world_transform.position))
+            })
+            .collect();
+        for (id, center) in follows {
+            if let Some(target) = self
+                .nodes
+                .get_mut(&id)
+                .and_then(|node| node.render_target.as_mut())
+            {
+                target.center = center;
+            }
+        }
+
+        self.run_updaters();
+    }
+
+    /// Rebuild the renderables of nodes with an [`Updater`]
+    fn run_updaters(&mut self) {
+        let rebuilt: Vec<(NodeId, Renderable)> = self
+            .nodes
+            .values()
+            .filter_map(|node| {
+                let renderable = node.updater.as_ref()?.evaluate(self, node)?;
+                Some((node.id, renderable))
+            })
+            .collect();
+        for (id, renderable) in rebuilt {
+            if let Some(node) = self.nodes.get_mut(&id) {
+                node.renderable = Some(renderable);
+            }
+        }
+    }
+
+    /// Append the current position of every traced node to its trail
+    fn update_traced_paths(&mut self, delta_time: TimeValue) {
+        let positions: Vec<(NodeId, Vector3)> = self
+            .nodes
+            .values()
+            .filter_map(|node| {
+                let target = self.nodes.get(&node.trace.as_ref()?.target)?;
+                // Trail points live in the trail node's own space
+                let position = node
+                    .world_transform
+                    .inverse()
+                    .transform_point(target.world_transform.position);
+                Some((node.id, position))
+            })
+            .collect();
+        for (id, position) in positions {
+            let Some(node) = self.nodes.get_mut(&id) else {
+                continue;
+            };
+            let Some(trace) = &mut node.trace else {
+                continue;
+            };
+            trace.record(position, delta_time.value);
+            if let Some(Renderable::Polyline { points, fade, .. }) = &mut node.renderable {
+                *points = trace.points();
+                *fade = trace.fade();
+            }
+        }
+    }
+
+    /// Measure every visualizer's sound and resize its bars or reshape its
+    /// line, returning whether any bars moved
+    fn update_visualizers(&mut self, delta_time: TimeValue) -> bool {
+        let mut bars = Vec::new();
+        for node in self.nodes.values_mut() {
+            let Some(visualizer) = &mut node.visualizer else {
+                continue;
+            };
+            visualizer.advance(delta_time.value);
+            match visualizer.style {
+                VisualizerStyle::Line => {
+                    if let Some(Renderable::Polyline { points, .. }) = &mut node.renderable {
+                        *points = visualizer.line();
+                    }
+                }
+                VisualizerStyle::Bars => {
+                    bars.extend(node.children.iter().copied().zip(visualizer.bars()));
+                }
+            }
+        }
+
+        let moved = !bars.is_empty();
+        for (id, (center, bar_height)) in bars {
+            let Some(bar) = self.nodes.get_mut(&id) else {
+                continue;
+            };
+            bar._local_transform.position = center;
+            if let Some(Renderable::Rectangle { height, .. }) = &mut bar.renderable {
+                *height = bar_height;
+            }
+        }
+        moved
     }
 
     /// Recursively update node transforms - uses internal helper to avoid borrow conflicts
@@ -456,17 +1038,29 @@ impl SceneGraph {
         // First, collect all the data we need without holding borrows
         let (children, _local_transform) = {
             if let Some(node) = self.nodes.get_mut(&node_id) {
-                // Update the node's world transform
+                // Update the node's world transform, with procedural modifiers on top.
+                // The local position is in the parent's rotated, scaled frame.
+                let offset = node.modifier_offset;
+                let local_transform = node._local_transform;
+                let local = local_transform.position + offset.position;
+                let scaled = Vector3::new(
+                    local.x * parent_world.scale.x,
+                    local.y * parent_world.scale.y,
+                    local.z * parent_world.scale.z,
+                );
                 node.world_transform.position =
-                    parent_world.position + node._local_transform.position;
-                node.world_transform.rotation = node._local_transform.rotation; // Simplified
+                    parent_world.position + parent_world.rotation.rotate_vector(scaled);
+                node.world_transform.rotation = (parent_world.rotation
+                    * local_transform.rotation
+                    * Quaternion::from_rotation_z(offset.rotation))
+                .normalized();
                 node.world_transform.scale = Vector3::new(
-                    parent_world.scale.x * node._local_transform.scale.x,
-                    parent_world.scale.y * node._local_transform.scale.y,
-                    parent_world.scale.z * node._local_transform.scale.z,
+                    parent_world.scale.x * local_transform.scale.x * offset.scale,
+                    parent_world.scale.y * local_transform.scale.y * offset.scale,
+                    parent_world.scale.z * local_transform.scale.z,
                 );
 
-                (node.children.clone(), node._local_transform.clone())
+                (node.children.clone(), local_transform)
             } else {
                 (Vec::new(), Transform::new())
             }
@@ -488,47 +1082,278 @@ impl SceneGraph {
     /// Update animations for all nodes
     pub fn update_animations(&mut self, delta_time: TimeValue) {
         let mut update_transforms = false;
+        let previous_positions: Vec<(NodeId, Vector3)> = self
+            .nodes
+            .values()
+            .filter(|node| !node.deformers.is_empty())
+            .map(|node| (node.id, node.world_transform.position))
+            .collect();
 
         for node in self.nodes.values_mut() {
             if node.update_animations(delta_time) {
                 update_transforms = true;
             }
         }
+        for effect in &mut self.post_effects {
+            effect.advance(delta_time);
+        }
+        if self.update_visualizers(delta_time) {
+            update_transforms = true;
+        }
 
         if update_transforms {
             self.update_transforms();
         }
+        #[cfg(feature = "physics")]
+        if self.update_physics(delta_time) {
+            self.update_transforms();
+        }
+        self.update_traced_paths(delta_time);
+        for (id, previous_position) in previous_positions {
+            if let Some(node) = self.nodes.get_mut(&id) {
+                node.update_deform_context(previous_position, delta_time);
+            }
+        }
+    }
+
+    /// Attach a staggered effect to its nodes, starting at `start_time`,
+    /// and return when the last one ends
+    pub fn add_staggered(&mut self, stagger: Stagger<NodeId>, start_time: f32) -> TimeValue {
+        let end = TimeValue::new(start_time + stagger.duration());
+        for (node_id, instance) in stagger.instances(start_time) {
+            if let Some(node) = self.nodes.get_mut(&node_id) {
+                node.add_animation(instance);
+            }
+        }
+        end
+    }
+
+    /// Attach a staggered effect at the timeline's current time and move the
+    /// timeline past it, so consecutive calls play one after another
+    ///
+    /// ```rust
+    /// use diomanim::animation::effects;
+    /// use diomanim::core::*;
+    /// use diomanim::scene::SceneGraph;
+    ///
+    /// let mut scene = SceneGraph::new();
+    /// let dots: Vec<_> = (0..50)
+    ///     .map(|i| scene.add_circle(format!("dot{i}"), 0.1, Color::WHITE).build())
+    ///     .collect();
+    ///
+    /// let mut timeline = Timeline::new();
+    /// scene.play_staggered(&mut timeline, effects::staggered(&dots, |_, _| effects::fade_in(1.0), 0.05));
+    /// assert!((timeline.current_time().seconds() - 3.45).abs() < 1e-4);
+    /// ```
+    pub fn play_staggered(&mut self, timeline: &mut Timeline, stagger: Stagger<NodeId>) {
+        let end = self.add_staggered(stagger, timeline.current_time().seconds());
+        timeline.jump_to(end);
     }
 
     /// Get all visible renderable objects with their transforms and opacity
     pub fn get_visible_renderables(&self) -> Vec<(TransformUniform, Renderable, f32)> {
-        let mut renderables = Vec::new();
+        self.get_visible_renderables_with_materials()
+            .into_iter()
+            .map(|(transform, renderable, opacity, _)| (transform, renderable, opacity))
+            .collect()
+    }
+
+    /// Get all visible renderable objects along with their materials
+    pub fn get_visible_renderables_with_materials(
+        &self,
+    ) -> Vec<(TransformUniform, Renderable, f32, Material)> {
+        self.visible_renderable_nodes()
+            .into_iter()
+            .filter_map(|node| {
+                let (renderable, opacity) = node.drawn_renderable()?;
+                Some((
+                    node.compute_model_matrix(),
+                    renderable.into_owned(),
+                    opacity,
+                    node.material,
+                ))
+            })
+            .collect()
+    }
+
+    /// Visible nodes that have a renderable or clip mask, in draw order
+    ///
+    /// Nodes are listed depth-first, so each node's subtree directly follows it.
+    pub fn visible_renderable_nodes(&self) -> Vec<&SceneNode> {
+        let mut nodes = Vec::new();
 
         for &root_id in &self.root_nodes {
-            self.gather_renderables_recursive(root_id, &mut renderables);
+            self.gather_nodes_recursive(root_id, &mut nodes);
         }
 
-        renderables
+        nodes
     }
 
-    /// Recursively gather renderables with opacity
-    fn gather_renderables_recursive(
-        &self,
-        node_id: NodeId,
-        renderables: &mut Vec<(TransformUniform, Renderable, f32)>,
-    ) {
+    /// Nodes with a [`RenderTarget`], innermost first and scene captures
+    /// last, so that a target's texture is up to date before the targets
+    /// showing it are drawn
+    pub fn render_targets(&self) -> Vec<&SceneNode> {
+        let mut targets: Vec<&SceneNode> = self
+            .nodes_in_draw_order()
+            .into_iter()
+            .filter(|node| node.render_target.is_some())
+            .collect();
+        targets.reverse();
+        targets.sort_by_key(|node| {
+            node.render_target
+                .as_ref()
+                .is_some_and(|target| target.source == TargetSource::Scene)
+        });
+        targets
+    }
+
+    /// Visible nodes drawn into the render target of `target`, in draw order
+    ///
+    /// Like [`Self::visible_renderable_nodes`] for the target's subtree,
+    /// without the target itself or the contents of nested targets; for a
+    /// [`TargetSource::Scene`] capture, the frame's nodes outside the
+    /// target's subtree. A hidden target has no contents.
+    pub fn render_target_nodes(&self, target: NodeId) -> Vec<&SceneNode> {
+        let Some(node) = self.nodes.get(&target) else {
+            return Vec::new();
+        };
+        if !node.visible || node.opacity <= 0.0 {
+            return Vec::new();
+        }
+        let mut nodes = Vec::new();
+        match node.render_target.as_ref().map(|target| target.source) {
+            Some(TargetSource::Scene) => {
+                nodes = self.visible_renderable_nodes();
+                nodes.retain(|node| !self.is_ancestor_or_self(target, node.id));
+            }
+            _ => {
+                for &child_id in &node.children {
+                    self.gather_nodes_recursive(child_id, &mut nodes);
+                }
+            }
+        }
+        nodes
+    }
+
+    /// Every node in draw order, including hidden nodes and nodes without
+    /// renderables
+    pub fn nodes_in_draw_order(&self) -> Vec<&SceneNode> {
+        let mut nodes = Vec::new();
+        let mut pending: Vec<NodeId> = self.root_nodes.iter().rev().copied().collect();
+        while let Some(id) = pending.pop() {
+            if let Some(node) = self.nodes.get(&id) {
+                nodes.push(node);
+                pending.extend(node.children.iter().rev());
+            }
+        }
+        nodes
+    }
+
+    /// Every node in draw order, for querying with iterator adapters
+    ///
+    /// ```rust
+    /// # use diomanim::scene::SceneGraph;
+    /// # let scene = SceneGraph::new();
+    /// let hidden: Vec<_> = scene
+    ///     .iter_nodes()
+    ///     .filter(|node| !node.visible)
+    ///     .map(|node| node.id)
+    ///     .collect();
+    /// ```
+    pub fn iter_nodes(&self) -> impl Iterator<Item = &SceneNode> {
+        self.nodes_in_draw_order().into_iter()
+    }
+
+    /// Ids of the nodes matching `predicate`, in draw order
+    pub fn query(&self, predicate: impl Fn(&SceneNode) -> bool) -> Vec<NodeId> {
+        self.iter_nodes()
+            .filter(|node| predicate(node))
+            .map(|node| node.id)
+            .collect()
+    }
+
+    /// The first node in draw order called `name`
+    pub fn find_by_name(&self, name: &str) -> Option<NodeId> {
+        self.iter_nodes()
+            .find(|node| node.name == name)
+            .map(|node| node.id)
+    }
+
+    /// Every node tagged `tag`, in draw order
+    pub fn find_by_tag(&self, tag: &str) -> Vec<NodeId> {
+        self.query(|node| node.has_tag(tag))
+    }
+
+    /// `root` followed by all of its descendants, parents before children
+    pub fn subtree(&self, root: NodeId) -> Vec<NodeId> {
+        let mut ids = Vec::new();
+        let mut pending = vec![root];
+        while let Some(id) = pending.pop() {
+            if let Some(node) = self.nodes.get(&id) {
+                ids.push(id);
+                pending.extend(node.children.iter().rev());
+            }
+        }
+        ids
+    }
+
+    /// Number of nodes in the graph
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Number of nodes with at least one active animation
+    pub fn animated_node_count(&self) -> usize {
+        self.nodes
+            .values()
+            .filter(|node| !node.animations.is_empty())
+            .count()
+    }
+
+    /// Whether any visible node has a shadow or glow
+    pub fn has_node_effects(&self) -> bool {
+        self.visible_renderable_nodes()
+            .iter()
+            .any(|node| !node.effects.is_empty())
+    }
+
+    /// Whether any visible node has a clip mask
+    pub fn has_clip_masks(&self) -> bool {
+        self.visible_renderable_nodes()
+            .iter()
+            .any(|node| node.clip.is_some())
+    }
+
+    /// Whether `ancestor` is `node` or one of its parents
+    pub fn is_ancestor_or_self(&self, ancestor: NodeId, node: NodeId) -> bool {
+        let mut current = Some(node);
+        while let Some(id) = current {
+            if id == ancestor {
+                return true;
+            }
+            current = self.nodes.get(&id).and_then(|node| node.parent);
+        }
+        false
+    }
+
+    /// Recursively gather visible nodes with renderables or clip masks
+    fn gather_nodes_recursive<'a>(&'a self, node_id: NodeId, nodes: &mut Vec<&'a SceneNode>) {
         if let Some(node) = self.nodes.get(&node_id) {
             if node.visible && node.opacity > 0.0 {
-                if let Some(renderable) = &node.renderable {
-                    renderables.push((
-                        node.compute_model_matrix(),
-                        renderable.clone(),
-                        node.opacity,
-                    ));
+                if node.renderable.is_some() || node.clip.is_some() {
+                    nodes.push(node);
+                }
+                // The subtree of a render target is drawn into its texture
+                if node
+                    .render_target
+                    .as_ref()
+                    .is_some_and(|target| target.source == TargetSource::Subtree)
+                {
+                    return;
                 }
 
                 for &child_id in &node.children {
-                    self.gather_renderables_recursive(child_id, renderables);
+                    self.gather_nodes_recursive(child_id, nodes);
                 }
             }
         }
@@ -536,6 +1361,8 @@ impl SceneGraph {
 
     /// Remove a node and its children from the scene
     pub fn remove_node(&mut self, node_id: NodeId) -> Option<SceneNode> {
+        #[cfg(feature = "physics")]
+        self.remove_rigid_body(node_id);
         if let Some(node) = self.nodes.remove(&node_id) {
             // Remove from root nodes if present
             self.root_nodes.retain(|&id| id != node_id);
@@ -557,6 +1384,65 @@ impl SceneGraph {
             None
         }
     }
+
+    /// Add a light to the scene and return its index
+    pub fn add_light(&mut self, light: Light) -> usize {
+        self.lights.push(light);
+        self.lights.len() - 1
+    }
+
+    /// Remove a light by index
+    pub fn remove_light(&mut self, index: usize) -> Option<Light> {
+        (index < self.lights.len()).then(|| self.lights.remove(index))
+    }
+
+    /// Get all lights in the scene
+    pub fn lights(&self) -> &[Light] {
+        &self.lights
+    }
+
+    /// Get mutable access to the scene lights
+    pub fn lights_mut(&mut self) -> &mut Vec<Light> {
+        &mut self.lights
+    }
+
+    /// Whether the scene should be rendered with the lit shader
+    pub fn is_lit(&self) -> bool {
+        !self.lights.is_empty()
+    }
+
+    /// Ambient light applied to every lit surface
+    pub fn ambient_light(&self) -> Color {
+        self.ambient_light
+    }
+
+    /// Set the ambient light color
+    pub fn set_ambient_light(&mut self, color: Color) {
+        self.ambient_light = color;
+    }
+
+    /// Add a post-processing effect and return its index
+    ///
+    /// Effects are applied in the order they were added.
+    pub fn add_post_effect(&mut self, effect: PostEffect) -> usize {
+        self.post_effects.push(effect);
+        self.post_effects.len() - 1
+    }
+
+    /// Remove a post-processing effect by index
+    pub fn remove_post_effect(&mut self, index: usize) -> Option<PostEffect> {
+        (index < self.post_effects.len()).then(|| self.post_effects.remove(index))
+    }
+
+    /// Post-processing effects in application order
+    pub fn post_effects(&self) -> &[PostEffect] {
+        &self.post_effects
+    }
+
+    /// Get mutable access to the post-processing effects
+    pub fn post_effects_mut(&mut self) -> &mut Vec<PostEffect> {
+        &mut self.post_effects
+    }
 }
 
 impl Default for SceneGraph {
@@ -624,6 +1510,52 @@ mod tests {
         assert_eq!(child_node.world_transform.position.x, 15.0);
     }
 
+    #[test]
+    fn test_rotation_in_hierarchy_and_tracks() {
+        use crate::animation::property::{AnimationClip, AnimationTrack, Keyframe};
+        use std::f32::consts::FRAC_PI_2;
+
+        let close = |a: Vector3, b: Vector3| (a - b).length() < 1e-4;
+        let mut graph = SceneGraph::new();
+        let parent = graph
+            .add_square("parent", 1.0, Color::RED)
+            .rotate_z(FRAC_PI_2)
+            .id();
+        let child = graph
+            .add_circle("child", 0.1, Color::RED)
+            .at(1.0, 0.0, 0.0)
+            .id();
+        graph.parent(child, parent).unwrap();
+        graph.update_transforms();
+
+        // The child's offset turns with its parent, and so does its model matrix
+        let child_node = graph.get_node(child).unwrap();
+        assert!(close(
+            child_node.world_transform.position,
+            Vector3::new(0.0, 1.0, 0.0)
+        ));
+        let model = child_node.compute_model_matrix().to_matrix();
+        let tip = model.transform_point(Vector3::new(1.0, 0.0, 0.0));
+        assert!(close(tip, Vector3::new(0.0, 2.0, 0.0)));
+
+        // Turning a card a quarter turn about y, sampled halfway by slerp
+        let mut track = AnimationTrack::new("rotation".to_string());
+        track.add_keyframe(Keyframe::new(TimeValue::new(0.0), Quaternion::identity()));
+        let turned = Quaternion::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), FRAC_PI_2);
+        track.add_keyframe(Keyframe::new(TimeValue::new(1.0), turned));
+        let mut clip = AnimationClip::new("flip".to_string());
+        clip.add_track(track);
+        let node = graph.get_node_mut(parent).unwrap();
+        node.add_animation(AnimationInstance::new(clip, TimeValue::new(0.0)));
+        node.update_animations(TimeValue::new(0.5));
+        let facing = node
+            ._local_transform
+            .rotation
+            .rotate_vector(Vector3::new(1.0, 0.0, 0.0));
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        assert!(close(facing, Vector3::new(half, 0.0, -half)));
+    }
+
     #[test]
     fn test_renderable_gathering() {
         let mut graph = SceneGraph::new();
@@ -648,4 +1580,268 @@ mod tests {
             panic!("Expected Circle renderable");
         }
     }
+
+    #[test]
+    fn test_staggered_fade_in() {
+        use crate::animation::effects;
+
+        let mut scene = SceneGraph::new();
+        let nodes: Vec<_> = (0..3)
+            .map(|i| scene.add_circle(format!("c{i}"), 1.0, Color::RED).build())
+            .collect();
+        let mut timeline = Timeline::new();
+        scene.play_staggered(
+            &mut timeline,
+            effects::staggered(&nodes, |_, _| effects::fade_in(1.0), 1.0),
+        );
+        assert_eq!(timeline.current_time(), TimeValue::new(3.0));
+
+        scene.update_animations(TimeValue::new(1.5));
+        let opacity = |scene: &SceneGraph, i: usize| scene.get_node(nodes[i]).unwrap().opacity;
+        assert!((opacity(&scene, 0) - 1.0).abs() < 1e-5);
+        assert!((opacity(&scene, 1) - 0.5).abs() < 1e-5);
+        // Still waiting for its turn, held at the first frame
+        assert!(opacity(&scene, 2).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_modifiers_layer_on_transform() {
+        let mut scene = SceneGraph::new();
+        let dot = scene
+            .add_circle("dot", 1.0, Color::RED)
+            .at(2.0, 0.0, 0.0)
+            .modifier(ProceduralModifier::jitter(0.5, 1.0).with_seed(11))
+            .build();
+
+        for _ in 0..3 {
+            scene.update_animations(TimeValue::new(0.3));
+        }
+        let node = scene.get_node(dot).unwrap();
+        let offset = node.modifier_offset();
+        assert!(!offset.is_identity());
+        // Offsets don't accumulate into the local transform
+        assert_eq!(
+            node.world_transform.position,
+            Vector3::new(2.0, 0.0, 0.0) + offset.position
+        );
+    }
+
+    #[test]
+    fn test_deformation_tracks_velocity() {
+        use crate::animation::deform::SquashStretch;
+
+        let mut scene = SceneGraph::new();
+        let ball = scene
+            .add_circle("ball", 0.5, Color::RED)
+            .deform(SquashStretch::new(0.2))
+            .move_to(0.0, Vector3::new(4.0, 0.0, 0.0), 1.0)
+            .build();
+        scene.update_transforms();
+        scene.update_animations(TimeValue::new(0.25));
+
+        let deformation = scene.get_node(ball).unwrap().deformation().unwrap();
+        assert!((deformation.context.velocity.x - 4.0).abs() < 1e-4);
+        assert!((deformation.context.time - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_decimal_number_updates_text() {
+        use crate::animation::{InterpolationType, ValueTracker};
+        use crate::mobjects::NumberFormat;
+
+        let mut tracker = ValueTracker::new(0.0);
+        tracker.animate_to(50.0, 0.0, 2.0, InterpolationType::Linear);
+        let mut scene = SceneGraph::new();
+        let counter = scene
+            .add_decimal_number(
+                "counter",
+                DecimalNumber::new(tracker, NumberFormat::new(1)),
+                32.0,
+                Color::WHITE,
+            )
+            .build();
+        let text = |scene: &SceneGraph| {
+            let node = scene.get_node(counter).unwrap();
+            node.renderable
+                .as_ref()
+                .unwrap()
+                .as_text()
+                .unwrap()
+                .0
+                .clone()
+        };
+        assert_eq!(text(&scene), "0.0");
+        scene.update_animations(TimeValue::new(0.5));
+        assert_eq!(text(&scene), "12.5");
+    }
+
+    #[test]
+    fn test_glyph_highlight() {
+        use crate::animation::effects::highlight_glyphs;
+
+        let mut node = SceneNode::new(NodeId::new(0), "sum".to_string());
+        node.set_renderable(Renderable::Text {
+            content: "a + bé = c".to_string(),
+            font_size: 48.0,
+            color: Color::WHITE,
+        });
+        let renderable = node.renderable.as_ref().unwrap();
+        assert_eq!(renderable.find_glyphs("= c"), Some(7..10));
+        let term = renderable.find_glyphs("bé").unwrap();
+        assert_eq!(term, 4..6);
+        assert_eq!(renderable.find_glyphs("d"), None);
+
+        let clip = highlight_glyphs(term.clone(), Color::WHITE, Color::YELLOW, 1.0);
+        node.add_animation(AnimationInstance::new(clip, TimeValue::new(0.0)));
+        node.update_animations(TimeValue::new(0.5));
+        assert_eq!(node.glyph_colors, vec![(term, Color::YELLOW)]);
+        // The rest of the text keeps its color
+        assert_eq!(node.renderable.as_ref().unwrap().color(), Color::WHITE);
+    }
+
+    #[test]
+    fn test_glyph_color_track_with_transform_track() {
+        use crate::animation::property::{AnimationClip, AnimationTrack, Keyframe};
+
+        let mut node = SceneNode::new(NodeId::new(0), "label".to_string());
+        node.set_renderable(Renderable::Text {
+            content: "x = 1".to_string(),
+            font_size: 48.0,
+            color: Color::WHITE,
+        });
+        let mut clip = AnimationClip::new("recolor".to_string());
+        let mut colors = AnimationTrack::new("glyph_color[4..5]".to_string());
+        colors.add_keyframe(Keyframe::new(TimeValue::new(0.0), Color::WHITE));
+        colors.add_keyframe(Keyframe::new(TimeValue::new(1.0), Color::RED));
+        clip.add_track(colors);
+        let mut position = AnimationTrack::new("position".to_string());
+        position.add_keyframe(Keyframe::new(TimeValue::new(0.0), Vector3::zero()));
+        position.add_keyframe(Keyframe::new(
+            TimeValue::new(1.0),
+            Vector3::new(1.0, 0.0, 0.0),
+        ));
+        clip.add_track(position);
+        node.add_animation(AnimationInstance::new(clip, TimeValue::new(0.0)));
+
+        assert!(node.update_animations(TimeValue::new(0.5)));
+        node.update_animations(TimeValue::new(0.5));
+        assert_eq!(node.glyph_colors, vec![(4..5, Color::RED)]);
+        assert_eq!(node._local_transform.position, Vector3::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_traced_path_follows_node() {
+        let mut scene = SceneGraph::new();
+        let dot = scene
+            .add_circle("dot", 0.05, Color::RED)
+            .move_to(0.0, Vector3::new(1.0, 0.0, 0.0), 1.0)
+            .build();
+        let trail = scene
+            .add_traced_path("trail", TracedPath::new(dot), Color::RED, 2.0)
+            .at(0.0, 0.5, 0.0)
+            .build();
+        scene.update_transforms();
+        for _ in 0..4 {
+            scene.update_animations(TimeValue::new(0.25));
+        }
+
+        let renderable = scene.get_node(trail).unwrap().renderable.clone().unwrap();
+        let (points, _, _, fade) = renderable.as_polyline().unwrap();
+        assert_eq!(points.len(), 4);
+        assert_eq!(*fade, 0.0);
+        // Points are relative to the trail node
+        assert!((points[3] - Vector3::new(1.0, -0.5, 0.0)).length() < 1e-5);
+    }
+
+    #[test]
+    fn test_scene_lights() {
+        let mut graph = SceneGraph::new();
+        assert!(!graph.is_lit());
+
+        let index = graph.add_light(Light::point(Vector3::zero(), Color::WHITE, 1.0));
+        assert!(graph.is_lit());
+        assert_eq!(graph.lights().len(), 1);
+
+        let id = graph
+            .add_circle("lit", 1.0, Color::RED)
+            .material(Material::glossy())
+            .id();
+        let (_, _, _, material) = graph.get_visible_renderables_with_materials().remove(0);
+        assert_eq!(material, Material::glossy());
+        assert_eq!(graph.get_node(id).unwrap().material, Material::glossy());
+
+        assert!(graph.remove_light(index).is_some());
+        assert!(graph.remove_light(index).is_none());
+        assert!(!graph.is_lit());
+    }
+
+    #[test]
+    fn test_query_nodes() {
+        let mut graph = SceneGraph::new();
+        let axis = graph
+            .add_line(
+                "x_axis",
+                Vector3::new(-1.0, 0.0, 0.0),
+                Vector3::new(1.0, 0.0, 0.0),
+                None,
+                2.0,
+            )
+            .tag("axis")
+            .build();
+        let dot = graph
+            .add_circle("dot", 0.1, None)
+            .tag("point")
+            .tag("point")
+            .build();
+        let label = graph
+            .add_text("dot", "p", 24.0, None)
+            .parent_to(axis)
+            .tag("axis")
+            .visible(false)
+            .build();
+
+        // The label is drawn right after its parent, before the circle
+        assert_eq!(graph.find_by_name("dot"), Some(label));
+        assert_eq!(graph.find_by_name("missing"), None);
+        assert_eq!(graph.find_by_tag("axis"), [axis, label]);
+        assert_eq!(graph.get_node(dot).unwrap().tags, ["point"]);
+        assert_eq!(graph.query(|node| !node.visible), [label]);
+        assert_eq!(
+            graph.iter_nodes().filter(|node| node.name == "dot").count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_render_target_nodes() {
+        let mut graph = SceneGraph::new();
+        let outer = graph.add_render_target("outer", 64, 64).build();
+        let inside = graph
+            .add_circle("inside", 0.1, None)
+            .parent_to(outer)
+            .build();
+        let inner = graph
+            .add_render_target("inner", 32, 32)
+            .parent_to(outer)
+            .build();
+        let nested = graph
+            .add_square("nested", 0.1, None)
+            .parent_to(inner)
+            .build();
+        let view = graph
+            .add_image("view", RenderTarget::handle_for("inner"), 0.5, 0.5)
+            .parent_to(outer)
+            .build();
+        let frame = graph.add_circle("frame", 0.1, None).build();
+
+        // Target contents are drawn into their textures, not the frame
+        let ids = |nodes: Vec<&SceneNode>| nodes.iter().map(|node| node.id).collect::<Vec<_>>();
+        assert_eq!(ids(graph.visible_renderable_nodes()), [frame]);
+        assert_eq!(ids(graph.render_target_nodes(outer)), [inside, view]);
+        assert_eq!(ids(graph.render_target_nodes(inner)), [nested]);
+        assert_eq!(ids(graph.render_targets()), [inner, outer]);
+
+        graph.get_node_mut(outer).unwrap().visible = false;
+        assert!(graph.render_target_nodes(outer).is_empty());
+    }
 }
@@ -0,0 +1,436 @@
+//! # Scene Patches
+//!
+//! A [`ScenePatch`] describes node additions, removals and property changes
+//! so a running scene can be updated in place instead of being rebuilt.
+//! Nodes are addressed by their path of names from the root
+//! (`"legend/entry_0/swatch"`), which stays the same when a script or an
+//! external editor rebuilds the scene, unlike node ids.
+//!
+//! Nodes a patch doesn't touch keep their animation state, so a preview can
+//! take edits without restarting playback:
+//!
+//! ```rust
+//! use diomanim::core::Color;
+//! use diomanim::scene::{ScenePatch, SceneGraph};
+//!
+//! let mut running = SceneGraph::new();
+//! running.add_circle("dot", 0.2, Color::RED);
+//!
+//! let mut edited = SceneGraph::new();
+//! edited.add_circle("dot", 0.2, Color::BLUE);
+//! edited.add_square("box", 0.3, None);
+//!
+//! let patch = ScenePatch::diff(&running, edited);
+//! assert_eq!(patch.len(), 2); // recolor "dot", add "box"
+//! running.apply_patch(patch).unwrap();
+//! assert_eq!(running.node_count(), 2);
+//! ```
+
+use super::{
+    BlendMode, Material, NodeId, RenderTarget, Renderable, SceneGraph, SceneNode, ShaderMaterial,
+};
+use crate::animation::property::AnimationInstance;
+use crate::core::Transform;
+use std::collections::{HashMap, HashSet};
+
+/// Separator between the names of a node path
+pub const PATH_SEPARATOR: char = '/';
+
+/// A new value for one property of an existing node
+pub enum NodeChange {
+    Transform(Transform),
+    Visible(bool),
+    Opacity(f32),
+    Renderable(Option<Renderable>),
+    Material(Material),
+    /// Replace the node's shader material (its time included)
+    Shader(Option<ShaderMaterial>),
+    Blend(BlendMode),
+    RenderTarget(Option<RenderTarget>),
+    Tags(Vec<String>),
+    /// Replace the node's animations (their playheads included)
+    Animations(Vec<AnimationInstance>),
+}
+
+/// One edit of a [`ScenePatch`]
+pub enum PatchOp {
+    /// Add `node` (and none of its children) at `path`; its parent is the
+    /// node at the path's prefix, or the scene root for a bare name
+    Add { path: String, node: Box<SceneNode> },
+    /// Remove the node at `path` along with its children
+    Remove { path: String },
+    /// Change one property of the node at `path`
+    Set { path: String, change: NodeChange },
+}
+
+/// Edits applied in order by [`SceneGraph::apply_patch`]
+#[derive(Default)]
+pub struct ScenePatch {
+    pub ops: Vec<PatchOp>,
+}
+
+impl ScenePatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a node at `path`; its id, parent and children are assigned when
+    /// the patch is applied
+    pub fn add(&mut self, path: impl Into<String>, node: SceneNode) -> &mut Self {
+        self.ops.push(PatchOp::Add {
+            path: path.into(),
+            node: Box::new(node),
+        });
+        self
+    }
+
+    pub fn remove(&mut self, path: impl Into<String>) -> &mut Self {
+        self.ops.push(PatchOp::Remove { path: path.into() });
+        self
+    }
+
+    pub fn set(&mut self, path: impl Into<String>, change: NodeChange) -> &mut Self {
+        self.ops.push(PatchOp::Set {
+            path: path.into(),
+            change,
+        });
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// The edits turning `old` into `new`, moving added nodes and changed
+    /// animations out of `new`
+    ///
+    /// Animations are compared by clip name, track count, start time, rate
+    /// and play mode, so advance both scenes to the same time first. Shader
+    /// materials are compared by everything but their time. Effects, clip
+    /// masks, modifiers and deformers of existing nodes aren't compared.
+    pub fn diff(old: &SceneGraph, mut new: SceneGraph) -> Self {
+        let old_paths = old.node_paths();
+        let new_paths = new.node_paths();
+        // Paths resolve to their first node, as in `find_by_path`
+        let mut old_by_path: HashMap<&str, NodeId> = HashMap::new();
+        for (id, path) in &old_paths {
+            old_by_path.entry(path.as_str()).or_insert(*id);
+        }
+        let new_by_path: HashSet<&str> = new_paths.iter().map(|(_, path)| path.as_str()).collect();
+        let mut patch = Self::new();
+
+        // Only the topmost missing node of a removed subtree needs an op
+        for (id, path) in &old_paths {
+            let parent_kept = old
+                .get_node(*id)
+                .and_then(|node| node.parent)
+                .is_none_or(|parent| {
+                    old_paths
+                        .iter()
+                        .find(|(other, _)| *other == parent)
+                        .is_some_and(|(_, parent_path)| new_by_path.contains(parent_path.as_str()))
+                });
+            if parent_kept && !new_by_path.contains(path.as_str()) {
+                patch.remove(path.clone());
+            }
+        }
+
+        for (id, path) in &new_paths {
+            let Some(old_node) = old_by_path
+                .get(path.as_str())
+                .and_then(|&id| old.get_node(id))
+            else {
+                if let Some(node) = new.nodes.remove(id) {
+                    patch.add(path.clone(), node);
+                }
+                continue;
+            };
+            let Some(node) = new.nodes.get_mut(id) else {
+                continue;
+            };
+            if node._local_transform != old_node._local_transform {
+                patch.set(path.clone(), NodeChange::Transform(node._local_transform));
+            }
+            if node.visible != old_node.visible {
+                patch.set(path.clone(), NodeChange::Visible(node.visible));
+            }
+            if node.opacity != old_node.opacity {
+                patch.set(path.clone(), NodeChange::Opacity(node.opacity));
+            }
+            if node.renderable != old_node.renderable {
+                patch.set(path.clone(), NodeChange::Renderable(node.renderable.take()));
+            }
+            if node.material != old_node.material {
+                patch.set(path.clone(), NodeChange::Material(node.material));
+            }
+            if !same_shader(node.shader.as_ref(), old_node.shader.as_ref()) {
+                patch.set(path.clone(), NodeChange::Shader(node.shader.take()));
+            }
+            if node.blend != old_node.blend {
+                patch.set(path.clone(), NodeChange::Blend(node.blend));
+            }
+            if node.render_target != old_node.render_target {
+                patch.set(
+                    path.clone(),
+                    NodeChange::RenderTarget(node.render_target.take()),
+                );
+            }
+            if node.tags != old_node.tags {
+                patch.set(
+                    path.clone(),
+                    NodeChange::Tags(std::mem::take(&mut node.tags)),
+                );
+            }
+            if !same_animations(&node.animations, &old_node.animations) {
+                let animations = std::mem::take(&mut node.animations);
+                patch.set(path.clone(), NodeChange::Animations(animations));
+            }
+        }
+        patch
+    }
+}
+
+/// Whether two materials match apart from how long they have been running
+fn same_shader(a: Option<&ShaderMaterial>, b: Option<&ShaderMaterial>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.name == b.name && a.fragment == b.fragment && a.params == b.params,
+        (a, b) => a.is_none() && b.is_none(),
+    }
+}
+
+fn same_animations(a: &[AnimationInstance], b: &[AnimationInstance]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|(a, b)| {
+            a.clip.name == b.clip.name
+                && a.clip.tracks.len() == b.clip.tracks.len()
+                && a.start_time.value == b.start_time.value
+                && a.rate == b.rate
+                && a.play_mode == b.play_mode
+        })
+}
+
+impl SceneGraph {
+    /// Names from the root down to `id`, joined by [`PATH_SEPARATOR`]
+    pub fn node_path(&self, id: NodeId) -> Option<String> {
+        let mut names = vec![self.get_node(id)?.name.as_str()];
+        let mut current = self.get_node(id)?.parent;
+        while let Some(parent) = current.and_then(|id| self.get_node(id)) {
+            names.push(&parent.name);
+            current = parent.parent;
+        }
+        names.reverse();
+        Some(names.join(&PATH_SEPARATOR.to_string()))
+    }
+
+    /// The first node in draw order whose path is `path`
+    pub fn find_by_path(&self, path: &str) -> Option<NodeId> {
+        let mut names = path.split(PATH_SEPARATOR);
+        let first = names.next()?;
+        let mut current = self
+            .root_nodes
+            .iter()
+            .copied()
+            .find(|&id| self.get_node(id).is_some_and(|node| node.name == first))?;
+        for name in names {
+            current = self
+                .get_node(current)?
+                .children
+                .iter()
+                .copied()
+                .find(|&id| self.get_node(id).is_some_and(|node| node.name == name))?;
+        }
+        Some(current)
+    }
+
+    /// Every node with its path, parents before children
+    fn node_paths(&self) -> Vec<(NodeId, String)> {
+        let mut paths: Vec<(NodeId, String)> = Vec::new();
+        let mut pending: Vec<(NodeId, String)> = self
+            .root_nodes
+            .iter()
+            .rev()
+            .map(|&id| (id, String::new()))
+            .collect();
+        while let Some((id, prefix)) = pending.pop() {
+            let Some(node) = self.get_node(id) else {
+                continue;
+            };
+            let path = if prefix.is_empty() {
+                node.name.clone()
+            } else {
+                format!("{prefix}{PATH_SEPARATOR}{}", node.name)
+            };
+            pending.extend(node.children.iter().rev().map(|&id| (id, path.clone())));
+            paths.push((id, path));
+        }
+        paths
+    }
+
+    /// Apply the edits of `patch` in order, stopping at the first one that
+    /// names a missing node
+    pub fn apply_patch(&mut self, patch: ScenePatch) -> Result<(), String> {
+        for op in patch.ops {
+            match op {
+                PatchOp::Add { path, node } => {
+                    let parent = match path.rsplit_once(PATH_SEPARATOR) {
+                        Some((parent_path, _)) => Some(
+                            self.find_by_path(parent_path)
+                                .ok_or_else(|| format!("no node at `{parent_path}`"))?,
+                        ),
+                        None => None,
+                    };
+                    let id = NodeId::new(self.next_id);
+                    self.next_id += 1;
+                    let mut node = *node;
+                    node.id = id;
+                    node.parent = None;
+                    node.children.clear();
+                    self.nodes.insert(id, node);
+                    self.root_nodes.push(id);
+                    if let Some(parent) = parent {
+                        self.parent(id, parent)?;
+                    }
+                }
+                PatchOp::Remove { path } => {
+                    let id = self
+                        .find_by_path(&path)
+                        .ok_or_else(|| format!("no node at `{path}`"))?;
+                    self.remove_node(id);
+                }
+                PatchOp::Set { path, change } => {
+                    let node = self
+                        .find_by_path(&path)
+                        .and_then(|id| self.nodes.get_mut(&id))
+                        .ok_or_else(|| format!("no node at `{path}`"))?;
+                    match change {
+                        NodeChange::Transform(transform) => node._local_transform = transform,
+                        NodeChange::Visible(visible) => node.visible = visible,
+                        NodeChange::Opacity(opacity) => node.opacity = opacity,
+                        NodeChange::Renderable(renderable) => node.renderable = renderable,
+                        NodeChange::Material(material) => node.material = material,
+                        NodeChange::Shader(shader) => node.shader = shader,
+                        NodeChange::Blend(blend) => node.blend = blend,
+                        NodeChange::RenderTarget(target) => node.render_target = target,
+                        NodeChange::Tags(tags) => node.tags = tags,
+                        NodeChange::Animations(animations) => node.animations = animations,
+                    }
+                }
+            }
+        }
+        self.update_transforms();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Color, TimeValue, Vector3};
+
+    fn build(label: &str, with_extra: bool) -> SceneGraph {
+        let mut scene = SceneGraph::new();
+        let group = scene.create_node("group".to_string());
+        scene
+            .add_circle("dot", 0.2, Color::RED)
+            .parent_to(group)
+            .fade_in(0.0, 2.0);
+        scene
+            .add_text("label", label, 24.0, None)
+            .at(0.0, 0.3, 0.0)
+            .parent_to(group);
+        if with_extra {
+            let extra = scene.add_square("extra", 0.1, None).build();
+            scene.add_circle("inner", 0.05, None).parent_to(extra);
+        }
+        scene
+    }
+
+    #[test]
+    fn test_node_paths() {
+        let scene = build("a", true);
+        let label = scene.find_by_path("group/label").unwrap();
+        assert_eq!(scene.node_path(label).as_deref(), Some("group/label"));
+        assert!(scene.find_by_path("extra/inner").is_some());
+        assert!(scene.find_by_path("group/inner").is_none());
+    }
+
+    #[test]
+    fn test_diff_round_trip_keeps_playback() {
+        let mut running = build("a", true);
+        running.update_animations(TimeValue::new(1.0));
+        let dot = running.find_by_path("group/dot").unwrap();
+        let opacity = running.get_node(dot).unwrap().opacity;
+        assert!(opacity > 0.0 && opacity < 1.0);
+
+        let mut edited = build("b", false);
+        edited.update_animations(TimeValue::new(1.0));
+        let patch = ScenePatch::diff(&running, edited);
+        // Remove the extra subtree once and update the label text
+        assert_eq!(patch.len(), 2);
+        running.apply_patch(patch).unwrap();
+
+        assert_eq!(running.node_count(), 3);
+        assert_eq!(running.find_by_path("group/dot"), Some(dot));
+        assert_eq!(running.get_node(dot).unwrap().animations.len(), 1);
+        let label = running.find_by_path("group/label").unwrap();
+        let (text, _, _) = running
+            .get_node(label)
+            .unwrap()
+            .renderable
+            .as_ref()
+            .unwrap()
+            .as_text()
+            .unwrap();
+        assert_eq!(text, "b");
+
+        // Adding nodes brings their children and animations along
+        let mut edited = build("b", true);
+        edited
+            .add_circle("late", 0.1, None)
+            .parent_to(NodeId::new(1))
+            .fade_in(0.5, 1.0);
+        edited.update_animations(TimeValue::new(1.0));
+        running
+            .apply_patch(ScenePatch::diff(&running, edited))
+            .unwrap();
+        assert_eq!(running.node_count(), 6);
+        let late = running.find_by_path("group/late").unwrap();
+        assert_eq!(running.get_node(late).unwrap().animations.len(), 1);
+        assert!(running.find_by_path("extra/inner").is_some());
+    }
+
+    #[test]
+    fn test_patch_ops() {
+        let mut scene = build("a", false);
+        let mut node = SceneNode::new(NodeId::new(0), "note".to_string());
+        node.set_renderable(Renderable::Circle {
+            radius: 0.1,
+            color: Color::GREEN,
+        });
+        let mut moved = Transform::new();
+        moved.position = Vector3::new(0.5, 0.0, 0.0);
+
+        let mut patch = ScenePatch::new();
+        patch
+            .add("group/note", node)
+            .set("group", NodeChange::Transform(moved))
+            .set("group/dot", NodeChange::Visible(false));
+        scene.apply_patch(patch).unwrap();
+
+        let note = scene.find_by_path("group/note").unwrap();
+        assert_eq!(
+            scene.get_node(note).unwrap().world_transform.position.x,
+            0.5
+        );
+        let dot = scene.find_by_path("group/dot").unwrap();
+        assert!(!scene.get_node(dot).unwrap().visible);
+
+        let mut patch = ScenePatch::new();
+        patch.remove("group/missing");
+        assert!(scene.apply_patch(patch).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! Post-Processing Effects
+//!
+//! Full-screen effects applied to the finished frame, stored on the scene like
+//! its lights. Each effect has an intensity that can be keyframed:
+//!
+//! - **Bloom**: bright shapes glow into their surroundings
+//! - **Vignette**: the frame darkens towards its edges
+//! - **Blur**: gaussian blur of the whole frame, e.g. behind an overlay
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::scene::*;
+//!
+//! let mut scene = SceneGraph::new();
+//! scene.add_post_effect(PostEffect::vignette(0.4));
+//! // Glow fades in over the first second
+//! scene.add_post_effect(PostEffect::bloom(0.7, 0.0).animate_intensity(&[(0.0, 0.0), (1.0, 1.2)]));
+//! ```
+
+use crate::animation::property::{AnimationTrack, Keyframe};
+use crate::core::{TimeValue, Vector3};
+
+/// What a post-processing effect does
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PostEffectKind {
+    /// Pixels brighter than `threshold` are blurred by `radius` pixels and added back
+    Bloom { threshold: f32, radius: f32 },
+    /// Darkening that starts `radius` from the center (1.0 = corner) over `softness`
+    Vignette { radius: f32, softness: f32 },
+    /// Gaussian blur with a `radius` in pixels
+    Blur { radius: f32 },
+}
+
+/// A post-processing effect and its (possibly animated) intensity
+#[derive(Debug, Clone)]
+pub struct PostEffect {
+    pub kind: PostEffectKind,
+    /// Strength of the effect; 0 disables it
+    pub intensity: f32,
+    /// Keyframed intensity (stored in `x`, like opacity tracks)
+    intensity_track: Option<AnimationTrack<Vector3>>,
+    /// Time since the effect was added, for sampling `intensity_track`
+    time: TimeValue,
+}
+
+impl PostEffect {
+    pub fn new(kind: PostEffectKind, intensity: f32) -> Self {
+        Self {
+            kind,
+            intensity,
+            intensity_track: None,
+            time: TimeValue::new(0.0),
+        }
+    }
+
+    /// Glow around pixels brighter than `threshold` (0-1)
+    pub fn bloom(threshold: f32, intensity: f32) -> Self {
+        Self::new(
+            PostEffectKind::Bloom {
+                threshold,
+                radius: 12.0,
+            },
+            intensity,
+        )
+    }
+
+    /// Darkened frame edges
+    pub fn vignette(intensity: f32) -> Self {
+        Self::new(
+            PostEffectKind::Vignette {
+                radius: 0.6,
+                softness: 0.5,
+            },
+            intensity,
+        )
+    }
+
+    /// Full-strength gaussian blur of `radius` pixels
+    pub fn blur(radius: f32) -> Self {
+        Self::new(PostEffectKind::Blur { radius }, 1.0)
+    }
+
+    /// Set the blur radius in pixels (bloom and blur) or the vignette radius
+    pub fn with_radius(mut self, new_radius: f32) -> Self {
+        match &mut self.kind {
+            PostEffectKind::Bloom { radius, .. }
+            | PostEffectKind::Vignette { radius, .. }
+            | PostEffectKind::Blur { radius } => *radius = new_radius.max(0.0),
+        }
+        self
+    }
+
+    /// Keyframe the intensity as `(seconds, intensity)` pairs
+    pub fn animate_intensity(mut self, keyframes: &[(f32, f32)]) -> Self {
+        let mut track = AnimationTrack::new("intensity".to_string());
+        for &(time, value) in keyframes {
+            track.add_keyframe(Keyframe::new(
+                TimeValue::new(time),
+                Vector3::new(value, 0.0, 0.0),
+            ));
+        }
+        self.intensity_track = Some(track);
+        self.time = TimeValue::new(0.0);
+        self
+    }
+
+    /// Step the intensity animation forward
+    pub fn advance(&mut self, delta_time: TimeValue) {
+        self.time += delta_time;
+        if let Some(track) = &self.intensity_track {
+            self.intensity = track.sample(self.time).x.max(0.0);
+        }
+    }
+
+    /// Whether the effect changes the frame at all
+    pub fn is_active(&self) -> bool {
+        self.intensity > 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intensity_animation() {
+        let mut bloom = PostEffect::bloom(0.8, 0.0).animate_intensity(&[(0.0, 0.0), (2.0, 1.0)]);
+        assert!(!bloom.is_active());
+
+        bloom.advance(TimeValue::new(1.0));
+        assert!((bloom.intensity - 0.5).abs() < 1e-6);
+        bloom.advance(TimeValue::new(5.0));
+        assert!((bloom.intensity - 1.0).abs() < 1e-6);
+
+        let vignette = PostEffect::vignette(0.3).with_radius(0.8);
+        assert_eq!(
+            vignette.kind,
+            PostEffectKind::Vignette {
+                radius: 0.8,
+                softness: 0.5
+            }
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! # Prefabs
+//!
+//! A [`Prefab`] is a subtree built once and stamped into a scene any number
+//! of times, so repeated constructs (legend entries, labeled axes, table
+//! cells) aren't rebuilt by hand. Every instance gets fresh nodes whose
+//! transforms, parents and colors can be overridden through the returned
+//! [`NodeBuilder`](super::NodeBuilder), while the template itself sits
+//! behind an `Arc` and is shared by every clone of the prefab.
+//!
+//! ```rust
+//! use diomanim::core::Color;
+//! use diomanim::scene::{Prefab, SceneGraph};
+//!
+//! let entry = Prefab::new("entry", |scene, root| {
+//!     scene.add_square("swatch", 0.1, Color::WHITE).parent_to(root);
+//!     scene.add_text("label", "series", 24.0, None).at(0.3, 0.0, 0.0).parent_to(root);
+//! });
+//!
+//! let mut scene = SceneGraph::new();
+//! let legend = scene.create_node("legend".to_string());
+//! for (i, color) in [Color::RED, Color::BLUE].into_iter().enumerate() {
+//!     scene
+//!         .instantiate(&entry, format!("entry_{i}"))
+//!         .parent_to(legend)
+//!         .at(0.0, -0.2 * i as f32, 0.0)
+//!         .recolor_part("swatch", color);
+//! }
+//! assert_eq!(scene.node_count(), 7);
+//! ```
+
+use super::{
+    BlendMode, ClipMask, Material, NodeBuilder, NodeEffect, NodeId, RenderTarget, Renderable,
+    SceneGraph, ShaderMaterial,
+};
+use crate::animation::{deform::VertexDeformer, procedural::ProceduralModifier};
+use crate::core::{Color, Transform};
+use std::ops::Range;
+use std::sync::Arc;
+
+/// A node of a prefab template
+#[derive(Clone)]
+struct PrefabNode {
+    name: String,
+    transform: Transform,
+    /// Index of the parent in the template (`None` for the root)
+    parent: Option<usize>,
+    visible: bool,
+    opacity: f32,
+    renderable: Option<Renderable>,
+    glyph_colors: Vec<(Range<usize>, Color)>,
+    material: Material,
+    shader: Option<ShaderMaterial>,
+    blend: BlendMode,
+    effects: Vec<NodeEffect>,
+    clip: Option<ClipMask>,
+    modifiers: Vec<ProceduralModifier>,
+    deformers: Vec<Arc<dyn VertexDeformer>>,
+    tags: Vec<String>,
+    render_target: Option<RenderTarget>,
+}
+
+/// A reusable subtree that can be instantiated many times
+///
+/// Animations are not part of the template; add them to each instance.
+#[derive(Clone)]
+pub struct Prefab {
+    name: String,
+    /// Template nodes in pre-order, the root first
+    nodes: Arc<[PrefabNode]>,
+}
+
+impl Prefab {
+    /// Build a prefab by adding nodes under `root` in a scratch scene
+    pub fn new(name: impl Into<String>, build: impl FnOnce(&mut SceneGraph, NodeId)) -> Self {
+        let name = name.into();
+        let mut scratch = SceneGraph::new();
+        let root = scratch.create_node(name.clone());
+        build(&mut scratch, root);
+        Self::capture(&scratch, root, name)
+    }
+
+    /// Capture the subtree rooted at `root` of an existing scene
+    pub fn from_subtree(scene: &SceneGraph, root: NodeId) -> Option<Self> {
+        let name = scene.get_node(root)?.name.clone();
+        Some(Self::capture(scene, root, name))
+    }
+
+    fn capture(scene: &SceneGraph, root: NodeId, name: String) -> Self {
+        let ids = scene.subtree(root);
+        let nodes = ids
+            .iter()
+            .filter_map(|&id| scene.get_node(id))
+            .map(|node| PrefabNode {
+                name: node.name.clone(),
+                transform: node._local_transform,
+                parent: node
+                    .parent
+                    .filter(|_| node.id != root)
+                    .and_then(|parent| ids.iter().position(|&id| id == parent)),
+                visible: node.visible,
+                opacity: node.opacity,
+                renderable: node.renderable.clone(),
+                glyph_colors: node.glyph_colors.clone(),
+                material: node.material,
+                shader: node.shader.clone(),
+                blend: node.blend,
+                effects: node.effects.clone(),
+                clip: node.clip.clone(),
+                modifiers: node.modifiers.clone(),
+                deformers: node.deformers.clone(),
+                tags: node.tags.clone(),
+                render_target: node.render_target.clone(),
+            })
+            .collect();
+        Self { name, nodes }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Number of nodes each instance adds to a scene
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+impl SceneGraph {
+    /// Add a copy of `prefab` whose root is called `name`, returning a
+    /// builder on the root to place, parent and recolor the instance
+    pub fn instantiate(&mut self, prefab: &Prefab, name: impl Into<String>) -> NodeBuilder<'_> {
+        let mut name = Some(name.into());
+        let mut ids: Vec<NodeId> = Vec::with_capacity(prefab.nodes.len());
+        for template in prefab.nodes.iter() {
+            let node_name = name.take().unwrap_or_else(|| template.name.clone());
+            let id = self.create_node_with_transform(node_name, template.transform);
+            if let Some(node) = self.get_node_mut(id) {
+                node.visible = template.visible;
+                node.opacity = template.opacity;
+                node.renderable = template.renderable.clone();
+                node.glyph_colors = template.glyph_colors.clone();
+                node.material = template.material;
+                node.shader = template.shader.clone();
+                node.blend = template.blend;
+                node.effects = template.effects.clone();
+                node.clip = template.clip.clone();
+                node.modifiers = template.modifiers.clone();
+                node.deformers = template.deformers.clone();
+                node.tags = template.tags.clone();
+                node.render_target = template.render_target.clone();
+            }
+            if let Some(parent) = template.parent {
+                self.parent(id, ids[parent]).ok();
+            }
+            ids.push(id);
+        }
+        NodeBuilder::new(self, ids[0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Color, Vector3};
+
+    fn labeled_dot() -> Prefab {
+        Prefab::new("dot", |scene, root| {
+            let body = scene
+                .add_circle("body", 0.1, Color::WHITE)
+                .parent_to(root)
+                .build();
+            scene
+                .add_text("label", "p", 24.0, Color::WHITE)
+                .at(0.2, 0.0, 0.0)
+                .parent_to(body);
+        })
+    }
+
+    #[test]
+    fn test_instances_get_independent_nodes() {
+        let prefab = labeled_dot();
+        assert_eq!(prefab.node_count(), 3);
+
+        let mut scene = SceneGraph::new();
+        let axes = scene.create_node("axes".to_string());
+        let a = scene
+            .instantiate(&prefab, "a")
+            .parent_to(axes)
+            .at(0.5, 0.0, 0.0)
+            .recolor(Color::RED)
+            .build();
+        let b = scene
+            .instantiate(&prefab, "b")
+            .recolor_part("label", Color::BLUE)
+            .build();
+        assert_eq!(scene.node_count(), 7);
+
+        let a_nodes = scene.subtree(a);
+        let b_nodes = scene.subtree(b);
+        assert_eq!(a_nodes.len(), 3);
+        assert!(a_nodes.iter().all(|id| !b_nodes.contains(id)));
+        assert_eq!(scene.get_node(a).unwrap().parent, Some(axes));
+        assert_eq!(scene.get_node(b).unwrap().parent, None);
+
+        let color = |id: NodeId| {
+            *scene
+                .get_node(id)
+                .unwrap()
+                .renderable
+                .clone()
+                .unwrap()
+                .color_mut()
+        };
+        assert_eq!(color(a_nodes[1]), Color::RED);
+        assert_eq!(color(a_nodes[2]), Color::RED);
+        assert_eq!(color(b_nodes[1]), Color::WHITE);
+        assert_eq!(color(b_nodes[2]), Color::BLUE);
+
+        // The label keeps its offset from the instance root
+        scene.update_transforms();
+        let label = scene.get_node(a_nodes[2]).unwrap();
+        assert_eq!(label.name, "label");
+        assert!((label.world_transform.position - Vector3::new(0.7, 0.0, 0.0)).length() < 1e-5);
+    }
+
+    #[test]
+    fn test_prefab_from_subtree() {
+        let mut scene = SceneGraph::new();
+        let group = scene.create_node("group".to_string());
+        scene.add_square("cell", 0.2, Color::GREEN).parent_to(group);
+        scene.add_circle("other", 0.1, None);
+
+        let prefab = Prefab::from_subtree(&scene, group).unwrap();
+        assert_eq!(prefab.name(), "group");
+        assert_eq!(prefab.node_count(), 2);
+        assert!(Prefab::from_subtree(&scene, NodeId::new(99)).is_none());
+    }
+}
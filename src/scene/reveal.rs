@@ -0,0 +1,236 @@
+//! Progressive reveal of a node's geometry
+//!
+//! A node's `progress` (0..1, animated by [`effects::create`]) says how much
+//! of its shape is drawn. The cut is made when the shape is tessellated, so
+//! it follows the real geometry rather than fading or scaling the node:
+//! circles sweep an arc from their rightmost point, rectangles and polygons
+//! fill in edge by edge from their first vertex, and lines, arrows and
+//! polylines grow from their start. Text, images and SVGs can't be cut and
+//! fade in with their progress instead.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::core::*;
+//! use diomanim::scene::Renderable;
+//!
+//! let line = Renderable::Line {
+//!     start: Vector3::zero(),
+//!     end: Vector3::new(2.0, 0.0, 0.0),
+//!     color: Color::WHITE,
+//!     thickness: 2.0,
+//! };
+//! let Some(Renderable::Line { end, .. }) = line.partial(0.25) else {
+//!     unreachable!()
+//! };
+//! assert_eq!(end, Vector3::new(0.5, 0.0, 0.0));
+//! ```
+//!
+//! [`effects::create`]: crate::animation::effects::create
+
+use super::{Renderable, SceneNode};
+use crate::core::Vector3;
+use std::borrow::Cow;
+
+/// Arc segments of a full circle, as [`ShapeRenderer::draw_circle`] uses
+///
+/// [`ShapeRenderer::draw_circle`]: crate::render::ShapeRenderer::draw_circle
+const CIRCLE_SEGMENTS: usize = 32;
+
+impl Renderable {
+    /// The part of the shape drawn at `progress` (0..1), or `None` for
+    /// renderables that can't be cut (text, images and SVGs)
+    pub fn partial(&self, progress: f32) -> Option<Renderable> {
+        let progress = progress.clamp(0.0, 1.0);
+        if progress >= 1.0 {
+            return Some(self.clone());
+        }
+        let partial = match self {
+            Renderable::Circle { radius, color } => {
+                // A pie slice fanned from the center
+                let sweep = progress * std::f32::consts::TAU;
+                let segments = ((CIRCLE_SEGMENTS as f32 * progress).ceil() as usize).max(1);
+                let arc = (0..=segments).map(|i| {
+                    let angle = sweep * i as f32 / segments as f32;
+                    Vector3::new(radius * angle.cos(), radius * angle.sin(), 0.0)
+                });
+                Renderable::Polygon {
+                    vertices: std::iter::once(Vector3::zero()).chain(arc).collect(),
+                    color: *color,
+                }
+            }
+            Renderable::Rectangle {
+                width,
+                height,
+                color,
+            } => {
+                let (x, y) = (width / 2.0, height / 2.0);
+                let corners = [
+                    Vector3::new(-x, -y, 0.0),
+                    Vector3::new(x, -y, 0.0),
+                    Vector3::new(x, y, 0.0),
+                    Vector3::new(-x, y, 0.0),
+                ];
+                Renderable::Polygon {
+                    vertices: traced(&corners, true, progress),
+                    color: *color,
+                }
+            }
+            Renderable::Polygon { vertices, color } => Renderable::Polygon {
+                vertices: traced(vertices, true, progress),
+                color: *color,
+            },
+            Renderable::Line {
+                start,
+                end,
+                color,
+                thickness,
+            } => Renderable::Line {
+                start: *start,
+                end: start.lerp(end, progress),
+                color: *color,
+                thickness: *thickness,
+            },
+            Renderable::Arrow {
+                start,
+                end,
+                color,
+                thickness,
+            } => Renderable::Arrow {
+                start: *start,
+                end: start.lerp(end, progress),
+                color: *color,
+                thickness: *thickness,
+            },
+            Renderable::Polyline {
+                points,
+                color,
+                thickness,
+                fade,
+            } => Renderable::Polyline {
+                points: traced(points, false, progress),
+                color: *color,
+                thickness: *thickness,
+                fade: *fade,
+            },
+            Renderable::Text { .. }
+            | Renderable::Math { .. }
+            | Renderable::RichText { .. }
+            | Renderable::TextOnPath { .. }
+            | Renderable::Image { .. }
+            | Renderable::Svg { .. } => return None,
+        };
+        Some(partial)
+    }
+}
+
+impl SceneNode {
+    /// The node's renderable as drawn at its progress, with the opacity to
+    /// draw it at (faded by the progress for renderables that can't be cut)
+    pub fn drawn_renderable(&self) -> Option<(Cow<'_, Renderable>, f32)> {
+        let renderable = self.renderable.as_ref()?;
+        if self.progress >= 1.0 {
+            return Some((Cow::Borrowed(renderable), self.opacity));
+        }
+        Some(match renderable.partial(self.progress) {
+            Some(partial) => (Cow::Owned(partial), self.opacity),
+            None => (Cow::Borrowed(renderable), self.opacity * self.progress),
+        })
+    }
+}
+
+/// The vertices of the path through `points` (closed back to the first when
+/// `closed`) up to `progress` of its length, ending with the point reached
+fn traced(points: &[Vector3], closed: bool, progress: f32) -> Vec<Vector3> {
+    let Some(&first) = points.first() else {
+        return Vec::new();
+    };
+    let close = closed.then_some(first);
+    let edges: Vec<(Vector3, Vector3)> = points
+        .iter()
+        .copied()
+        .zip(points.iter().copied().skip(1).chain(close))
+        .collect();
+    let total: f32 = edges.iter().map(|(a, b)| a.distance(b)).sum();
+
+    let mut remaining = total * progress;
+    let mut vertices = vec![first];
+    for (a, b) in edges {
+        let length = a.distance(&b);
+        if remaining < length {
+            if remaining > 0.0 {
+                vertices.push(a.lerp(&b, remaining / length));
+            }
+            break;
+        }
+        remaining -= length;
+        vertices.push(b);
+    }
+    vertices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Color;
+
+    #[test]
+    fn test_partial_shapes_follow_the_outline() {
+        // Half a circle is a slice ending at the leftmost point
+        let circle = Renderable::Circle {
+            radius: 1.0,
+            color: Color::RED,
+        };
+        let Some(Renderable::Polygon { vertices, .. }) = circle.partial(0.5) else {
+            panic!("circles are cut into slices");
+        };
+        assert_eq!(vertices[0], Vector3::zero());
+        assert_eq!(vertices.len(), 2 + CIRCLE_SEGMENTS / 2);
+        assert!((*vertices.last().unwrap() - Vector3::new(-1.0, 0.0, 0.0)).length() < 1e-5);
+
+        // Five eighths of a square's perimeter: two edges and half the third
+        let square = Renderable::Rectangle {
+            width: 2.0,
+            height: 2.0,
+            color: Color::RED,
+        };
+        let Some(Renderable::Polygon { vertices, .. }) = square.partial(0.625) else {
+            panic!("rectangles are cut into polygons");
+        };
+        assert_eq!(
+            vertices,
+            [
+                Vector3::new(-1.0, -1.0, 0.0),
+                Vector3::new(1.0, -1.0, 0.0),
+                Vector3::new(1.0, 1.0, 0.0),
+                Vector3::new(0.0, 1.0, 0.0),
+            ]
+        );
+
+        let polyline = Renderable::Polyline {
+            points: vec![Vector3::zero(), Vector3::new(1.0, 0.0, 0.0)],
+            color: Color::RED,
+            thickness: 1.0,
+            fade: 0.0,
+        };
+        let Some(Renderable::Polyline { points, .. }) = polyline.partial(0.0) else {
+            panic!("polylines are cut");
+        };
+        assert_eq!(points, [Vector3::zero()]);
+        assert_eq!(square.partial(1.0).as_ref(), Some(&square));
+    }
+
+    #[test]
+    fn test_uncuttable_renderables_fade() {
+        let mut node = SceneNode::new(crate::scene::NodeId::new(1), "label".to_string());
+        node.renderable = Some(Renderable::Text {
+            content: "x".to_string(),
+            font_size: 24.0,
+            color: Color::WHITE,
+        });
+        node.progress = 0.25;
+        let (renderable, opacity) = node.drawn_renderable().unwrap();
+        assert!(matches!(renderable, Cow::Borrowed(_)));
+        assert_eq!(opacity, 0.25);
+    }
+}
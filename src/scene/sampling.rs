@@ -0,0 +1,242 @@
+//! Shape sampling
+//!
+//! Points spread over a shape, for effects that treat it as a set of dots:
+//! morphing one outline into another, emitting particles from a shape, or
+//! turning a shape into dust. [`ShapeSampling::Outline`] spaces points
+//! evenly by distance along the outline; [`ShapeSampling::Interior`]
+//! spreads them evenly over the area, using a low-discrepancy sequence so
+//! even a few dozen points cover the shape without clumps or gaps.
+//!
+//! Circles, rectangles, polygons, images and SVGs (their bounds) have an
+//! area; lines, arrows and polylines only an outline, which interior
+//! sampling falls back to. Text has no outline and gives no points.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::core::*;
+//! use diomanim::scene::{SceneGraph, ShapeSampling};
+//!
+//! let mut scene = SceneGraph::new();
+//! let dot = scene.add_circle("dot", 0.5, Color::RED).at(1.0, 0.0, 0.0).build();
+//! scene.update_transforms();
+//!
+//! let rim = scene.sample_node(dot, 12, ShapeSampling::Outline);
+//! assert!((rim[0] - Vector3::new(1.5, 0.0, 0.0)).length() < 1e-4);
+//! let dust = scene.sample_node(dot, 200, ShapeSampling::Interior { seed: 1 });
+//! assert!(dust.iter().all(|p| (*p - Vector3::new(1.0, 0.0, 0.0)).length() < 0.501));
+//! ```
+
+use super::interaction::polygon_contains;
+use super::{NodeId, Renderable, SceneGraph};
+use crate::animation::procedural::unit_hash;
+use crate::core::{Path, Vector3};
+
+/// Candidate points tried per requested interior point before giving up on
+/// a shape with (almost) no area
+const MAX_TRIES_PER_POINT: usize = 64;
+
+/// Where on a shape points are taken from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShapeSampling {
+    /// Evenly spaced along the outline, starting where the outline starts
+    Outline,
+    /// Evenly spread over the area; `seed` picks one of many equally even
+    /// arrangements
+    Interior { seed: u32 },
+}
+
+impl Renderable {
+    /// The shape's outline in its local coordinates, closed for shapes with
+    /// an area (`None` for text)
+    pub fn outline(&self) -> Option<Path> {
+        let rectangle = |width: f32, height: f32| {
+            let (x, y) = (width / 2.0, height / 2.0);
+            Path::polygon(&[
+                Vector3::new(-x, -y, 0.0),
+                Vector3::new(x, -y, 0.0),
+                Vector3::new(x, y, 0.0),
+                Vector3::new(-x, y, 0.0),
+            ])
+        };
+        match self {
+            Renderable::Circle { radius, .. } => Some(Path::circle(Vector3::zero(), *radius)),
+            Renderable::Rectangle { width, height, .. }
+            | Renderable::Image { width, height, .. }
+            | Renderable::Svg { width, height, .. } => Some(rectangle(*width, *height)),
+            Renderable::Line { start, end, .. } | Renderable::Arrow { start, end, .. } => {
+                Some(Path::polyline(&[*start, *end]))
+            }
+            Renderable::Polygon { vertices, .. } => Some(Path::polygon(vertices)),
+            Renderable::Polyline { points, .. } => Some(Path::polyline(points)),
+            Renderable::Text { .. }
+            | Renderable::Math { .. }
+            | Renderable::RichText { .. }
+            | Renderable::TextOnPath { .. } => None,
+        }
+    }
+
+    /// `count` points on the shape in its local coordinates (none for text)
+    pub fn sample_points(&self, count: usize, sampling: ShapeSampling) -> Vec<Vector3> {
+        let Some(outline) = self.outline() else {
+            return Vec::new();
+        };
+        match sampling {
+            ShapeSampling::Outline => outline_points(&outline, count),
+            ShapeSampling::Interior { seed } => interior_points(&outline, count, seed),
+        }
+    }
+}
+
+impl SceneGraph {
+    /// `count` points on the node's shape in scene coordinates, as of the
+    /// last transform update (none for text and nodes without a shape)
+    pub fn sample_node(&self, id: NodeId, count: usize, sampling: ShapeSampling) -> Vec<Vector3> {
+        let Some(node) = self.nodes.get(&id) else {
+            return Vec::new();
+        };
+        let Some(renderable) = &node.renderable else {
+            return Vec::new();
+        };
+        renderable
+            .sample_points(count, sampling)
+            .into_iter()
+            .map(|point| node.world_transform.transform_point(point))
+            .collect()
+    }
+}
+
+/// `count` points evenly spaced by distance along `path`; closed paths
+/// don't repeat their start at the end, open paths include both ends
+pub fn outline_points(path: &Path, count: usize) -> Vec<Vector3> {
+    let sampler = path.sampler();
+    let steps = if path.closed {
+        count
+    } else {
+        count.saturating_sub(1)
+    };
+    let steps = steps.max(1) as f32;
+    (0..count)
+        .map(|i| sampler.point_at(i as f32 / steps * sampler.length()))
+        .collect()
+}
+
+/// `count` points evenly spread inside the closed `path` (even-odd rule),
+/// or along it if it is open or has no area
+pub fn interior_points(path: &Path, count: usize, seed: u32) -> Vec<Vector3> {
+    if !path.closed {
+        return outline_points(path, count);
+    }
+    let polygon = path.flatten();
+    let (min, max) = polygon.iter().fold(
+        (
+            Vector3::new(f32::MAX, f32::MAX, 0.0),
+            Vector3::new(f32::MIN, f32::MIN, 0.0),
+        ),
+        |(min, max), p| {
+            (
+                Vector3::new(min.x.min(p.x), min.y.min(p.y), 0.0),
+                Vector3::new(max.x.max(p.x), max.y.max(p.y), 0.0),
+            )
+        },
+    );
+
+    // Halton points over the bounds, shifted by the seed, keeping those inside
+    let shift = (unit_hash(seed, 0), unit_hash(seed, 1));
+    let mut points = Vec::with_capacity(count);
+    for index in 1..=count * MAX_TRIES_PER_POINT {
+        if points.len() == count {
+            return points;
+        }
+        let u = (halton(index, 2) + shift.0).fract();
+        let v = (halton(index, 3) + shift.1).fract();
+        let point = Vector3::new(
+            min.x + u * (max.x - min.x),
+            min.y + v * (max.y - min.y),
+            0.0,
+        );
+        if polygon_contains(&polygon, point) {
+            points.push(point);
+        }
+    }
+    if points.len() < count {
+        return outline_points(path, count);
+    }
+    points
+}
+
+/// Element `index` of the Halton sequence in `base`, in `[0, 1)`
+fn halton(mut index: usize, base: usize) -> f32 {
+    let (mut value, mut scale) = (0.0, 1.0);
+    while index > 0 {
+        scale /= base as f32;
+        value += (index % base) as f32 * scale;
+        index /= base;
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Color;
+
+    #[test]
+    fn test_outline_points_are_evenly_spaced() {
+        let square = Renderable::Rectangle {
+            width: 2.0,
+            height: 2.0,
+            color: Color::RED,
+        };
+        let points = square.sample_points(8, ShapeSampling::Outline);
+        assert_eq!(points.len(), 8);
+        // Corners and edge midpoints, without the start repeated
+        assert!((points[0] - Vector3::new(-1.0, -1.0, 0.0)).length() < 1e-5);
+        assert!((points[1] - Vector3::new(0.0, -1.0, 0.0)).length() < 1e-5);
+        assert!((points[7] - Vector3::new(-1.0, 0.0, 0.0)).length() < 1e-5);
+
+        let line = Renderable::Line {
+            start: Vector3::zero(),
+            end: Vector3::new(3.0, 0.0, 0.0),
+            color: Color::RED,
+            thickness: 1.0,
+        };
+        let points = line.sample_points(4, ShapeSampling::Interior { seed: 0 });
+        assert_eq!(points[3], Vector3::new(3.0, 0.0, 0.0));
+        assert!((points[1].x - 1.0).abs() < 1e-5);
+
+        let text = Renderable::Text {
+            content: "Hi".to_string(),
+            font_size: 12.0,
+            color: Color::RED,
+        };
+        assert!(text.sample_points(4, ShapeSampling::Outline).is_empty());
+    }
+
+    #[test]
+    fn test_interior_points_cover_the_area_evenly() {
+        // An L shape: the left column and the bottom row of a 2x2 grid
+        let l_shape = Renderable::Polygon {
+            vertices: [
+                (0.0, 0.0),
+                (2.0, 0.0),
+                (2.0, 1.0),
+                (1.0, 1.0),
+                (1.0, 2.0),
+                (0.0, 2.0),
+            ]
+            .map(|(x, y)| Vector3::new(x, y, 0.0))
+            .to_vec(),
+            color: Color::RED,
+        };
+        let points = l_shape.sample_points(300, ShapeSampling::Interior { seed: 5 });
+        assert_eq!(points.len(), 300);
+        assert!(points.iter().all(|p| !(p.x > 1.0 && p.y > 1.0)));
+        // Each of the three unit cells gets about a third
+        let in_corner = points.iter().filter(|p| p.x < 1.0 && p.y < 1.0).count();
+        assert!((85..=115).contains(&in_corner), "{in_corner} in one cell");
+
+        let other_seed = l_shape.sample_points(300, ShapeSampling::Interior { seed: 6 });
+        assert_ne!(points, other_seed);
+    }
+}
@@ -0,0 +1,158 @@
+//! Custom Shader Materials
+//!
+//! A [`ShaderMaterial`] replaces the flat fill of a node's shape with a WGSL
+//! fragment snippet, for per-object effects like animated gradients, noise
+//! fills and scan lines. The snippet defines
+//!
+//! ```wgsl
+//! fn shade(in: ShadeInput) -> vec4<f32>
+//! ```
+//!
+//! where `ShadeInput` holds the shape's `color` (with the node opacity in
+//! its alpha), the fragment's `local` position in the shape's own
+//! coordinates, the material's `time` in seconds and its four `params`
+//! vectors. The renderer compiles each material name once, so materials
+//! with different snippets need different names.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::scene::*;
+//! use diomanim::core::*;
+//!
+//! let mut scene = SceneGraph::new();
+//! scene.add_circle("sun", 0.4, Color::YELLOW)
+//!     .shader(ShaderMaterial::gradient(Color::YELLOW, Color::RED, 0.5));
+//!
+//! // A hand-written snippet: stripes whose width comes from params[0].x
+//! let stripes = ShaderMaterial::new(
+//!     "stripes",
+//!     "fn shade(in: ShadeInput) -> vec4<f32> {
+//!         let on = step(0.5, fract(in.local.x / in.params[0].x));
+//!         return vec4<f32>(in.color.rgb * on, in.color.a);
+//!     }",
+//! )
+//! .with_param(0, [0.1, 0.0, 0.0, 0.0]);
+//! scene.add_square("flag", 0.5, Color::BLUE).shader(stripes);
+//! ```
+
+use crate::core::Color;
+use std::sync::Arc;
+
+/// Number of `vec4<f32>` parameters passed to a material's snippet
+pub const SHADER_PARAMS: usize = 4;
+
+const GRADIENT: &str = "fn shade(in: ShadeInput) -> vec4<f32> {
+    let t = 0.5 + 0.5 * sin((in.local.x - in.time * in.params[2].x) * 3.14159265);
+    let color = mix(in.params[0], in.params[1], t);
+    return vec4<f32>(color.rgb, color.a * in.color.a);
+}";
+
+const SCAN_LINES: &str = "fn shade(in: ShadeInput) -> vec4<f32> {
+    let phase = fract((in.local.y + in.time * in.params[0].y) / in.params[0].x);
+    let shade = mix(1.0 - in.params[0].z, 1.0, step(0.5, phase));
+    return vec4<f32>(in.color.rgb * shade, in.color.a);
+}";
+
+const NOISE: &str = "fn hash(cell: vec2<f32>) -> f32 {
+    return fract(sin(dot(cell, vec2<f32>(127.1, 311.7))) * 43758.5453);
+}
+
+fn shade(in: ShadeInput) -> vec4<f32> {
+    let p = in.local.xy * in.params[0].x + vec2<f32>(in.time * in.params[0].y, 0.0);
+    let cell = floor(p);
+    let f = fract(p);
+    let u = f * f * (3.0 - 2.0 * f);
+    let value = mix(
+        mix(hash(cell), hash(cell + vec2<f32>(1.0, 0.0)), u.x),
+        mix(hash(cell + vec2<f32>(0.0, 1.0)), hash(cell + vec2<f32>(1.0, 1.0)), u.x),
+        u.y,
+    );
+    return vec4<f32>(in.color.rgb * mix(1.0 - in.params[0].z, 1.0, value), in.color.a);
+}";
+
+/// A custom fragment shader and its uniforms, drawn in place of a node's
+/// flat color
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShaderMaterial {
+    /// Name the snippet is compiled under
+    pub name: &'static str,
+    /// WGSL source defining `fn shade(in: ShadeInput) -> vec4<f32>`
+    pub fragment: Arc<str>,
+    /// Values the snippet reads as `in.params`
+    pub params: [[f32; 4]; SHADER_PARAMS],
+    /// Seconds the material has been running, advanced with the node's
+    /// animations
+    pub time: f32,
+}
+
+impl ShaderMaterial {
+    pub fn new(name: &'static str, fragment: impl Into<Arc<str>>) -> Self {
+        Self {
+            name,
+            fragment: fragment.into(),
+            params: [[0.0; 4]; SHADER_PARAMS],
+            time: 0.0,
+        }
+    }
+
+    /// Set `in.params[index]`; out-of-range indices are ignored
+    pub fn with_param(mut self, index: usize, value: [f32; 4]) -> Self {
+        if let Some(param) = self.params.get_mut(index) {
+            *param = value;
+        }
+        self
+    }
+
+    /// Colors waving from `from` to `to` across the shape, scrolling
+    /// `speed` units per second along x
+    pub fn gradient(from: Color, to: Color, speed: f32) -> Self {
+        Self::new("gradient", GRADIENT)
+            .with_param(0, from.to_f32_array())
+            .with_param(1, to.to_f32_array())
+            .with_param(2, [speed, 0.0, 0.0, 0.0])
+    }
+
+    /// Horizontal lines `spacing` units apart darkened by `strength`
+    /// (0..1), scrolling `speed` units per second
+    pub fn scan_lines(spacing: f32, strength: f32, speed: f32) -> Self {
+        Self::new("scan_lines", SCAN_LINES).with_param(
+            0,
+            [
+                spacing.max(f32::EPSILON),
+                speed,
+                strength.clamp(0.0, 1.0),
+                0.0,
+            ],
+        )
+    }
+
+    /// Smooth value noise with `scale` cells per unit, darkening the color
+    /// by up to `strength` (0..1) and drifting `speed` cells per second
+    pub fn noise(scale: f32, strength: f32, speed: f32) -> Self {
+        Self::new("noise", NOISE).with_param(0, [scale, speed, strength.clamp(0.0, 1.0), 0.0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shader_material_params() {
+        let material = ShaderMaterial::gradient(Color::RED, Color::BLUE, 2.0);
+        assert_eq!(material.name, "gradient");
+        assert_eq!(material.params[0], Color::RED.to_f32_array());
+        assert_eq!(material.params[2][0], 2.0);
+
+        let material = ShaderMaterial::new(
+            "custom",
+            "fn shade(in: ShadeInput) -> vec4<f32> { return in.color; }",
+        )
+        .with_param(3, [1.0; 4])
+        .with_param(SHADER_PARAMS, [2.0; 4]);
+        assert_eq!(material.params[3], [1.0; 4]);
+        assert_eq!(material.params[0], [0.0; 4]);
+        assert!(material.fragment.contains("fn shade"));
+    }
+}
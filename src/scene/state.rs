@@ -0,0 +1,462 @@
+//! Target states and morphing
+//!
+//! A [`NodeState`] is a snapshot of how a node looks: its transform,
+//! opacity and renderable. [`SceneNode::generate_target`] copies the node
+//! into its `target`, which is edited in place and then animated into by
+//! [`effects::move_to_target`], so a scene says what things should end up
+//! like rather than which property goes where:
+//!
+//! - transforms blend position, scale and rotation (slerped)
+//! - shapes of the same kind blend their sizes, endpoints and colors
+//! - different shapes morph outline into outline; text can't be morphed
+//!   and switches halfway
+//!
+//! [`SceneNode::save_state`] keeps a snapshot the same way, and
+//! [`effects::restore`] animates back to it, undoing whatever emphasis or
+//! moves happened in between.
+//!
+//! Transitions start from wherever the node is when their animation
+//! starts. [`SceneGraph::replacement_transform`] builds the target from
+//! another node and hands over to it at the end (Manim's
+//! ReplacementTransform).
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::animation::{effects, property::AnimationInstance};
+//! use diomanim::core::*;
+//! use diomanim::scene::*;
+//!
+//! let mut scene = SceneGraph::new();
+//! let dot = scene.add_circle("dot", 0.2, Color::RED).build();
+//! let node = scene.get_node_mut(dot).unwrap();
+//! node.generate_target()
+//!     .shift(Vector3::new(1.0, 0.0, 0.0))
+//!     .scale(2.0)
+//!     .set_color(Color::BLUE);
+//! node.add_animation(AnimationInstance::new(
+//!     effects::move_to_target(1.0),
+//!     TimeValue::new(0.0),
+//! ));
+//!
+//! scene.update_animations(TimeValue::new(1.0));
+//! let node = scene.get_node(dot).unwrap();
+//! assert_eq!(node._local_transform.position, Vector3::new(1.0, 0.0, 0.0));
+//! assert_eq!(node.renderable.as_ref().unwrap().color(), Color::BLUE);
+//! ```
+//!
+//! [`effects::move_to_target`]: crate::animation::effects::move_to_target
+//! [`effects::restore`]: crate::animation::effects::restore
+
+use super::sampling::outline_points;
+use super::{NodeId, Renderable, SceneGraph, SceneNode};
+use crate::animation::effects;
+use crate::animation::property::{
+    AnimationClip, AnimationInstance, AnimationTrack, InterpolationType, Keyframe,
+};
+use crate::core::{transform::Quaternion, Color, TimeValue, Transform, Vector3};
+
+/// Points along each outline when morphing one shape into another
+const MORPH_POINTS: usize = 96;
+
+/// How a node looks: where it is, how opaque, and what it draws
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeState {
+    pub transform: Transform,
+    pub opacity: f32,
+    pub renderable: Option<Renderable>,
+}
+
+impl NodeState {
+    /// The current state of `node`
+    pub fn of(node: &SceneNode) -> Self {
+        Self {
+            transform: node._local_transform,
+            opacity: node.opacity,
+            renderable: node.renderable.clone(),
+        }
+    }
+
+    /// Move by `offset`
+    pub fn shift(&mut self, offset: Vector3) -> &mut Self {
+        self.transform.position = self.transform.position + offset;
+        self
+    }
+
+    /// Move to `position`
+    pub fn move_to(&mut self, position: Vector3) -> &mut Self {
+        self.transform.position = position;
+        self
+    }
+
+    /// Multiply the scale by `factor`
+    pub fn scale(&mut self, factor: f32) -> &mut Self {
+        self.transform.scale = self.transform.scale * factor;
+        self
+    }
+
+    /// Turn by `angle` radians about the z axis
+    pub fn rotate_z(&mut self, angle: f32) -> &mut Self {
+        self.transform.rotation = Quaternion::from_rotation_z(angle) * self.transform.rotation;
+        self
+    }
+
+    pub fn set_opacity(&mut self, opacity: f32) -> &mut Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn set_color(&mut self, color: Color) -> &mut Self {
+        if let Some(renderable) = &mut self.renderable {
+            *renderable.color_mut() = color;
+        }
+        self
+    }
+
+    pub fn set_renderable(&mut self, renderable: Renderable) -> &mut Self {
+        self.renderable = Some(renderable);
+        self
+    }
+
+    /// The state `t` (0..1) of the way from this one to `other`
+    pub fn interpolate(&self, other: &NodeState, t: f32) -> NodeState {
+        let renderable = match (&self.renderable, &other.renderable) {
+            (Some(from), Some(to)) => Some(from.interpolate(to, t)),
+            (from, to) => {
+                let nearer = if t < 1.0 { from } else { to };
+                nearer.clone()
+            }
+        };
+        NodeState {
+            transform: self.transform.lerp(&other.transform, t),
+            opacity: self.opacity + (other.opacity - self.opacity) * t,
+            renderable,
+        }
+    }
+
+    /// Make `node` look like this state
+    pub fn apply_to(&self, node: &mut SceneNode) {
+        node._local_transform = self.transform;
+        node.opacity = self.opacity.clamp(0.0, 1.0);
+        node.renderable = self.renderable.clone();
+    }
+}
+
+impl Renderable {
+    /// The shape `t` (0..1) of the way to `other`
+    ///
+    /// Shapes of the same kind blend their parameters; other shapes with an
+    /// outline morph into each other as polygons (or polylines, if either
+    /// is open). Anything else switches to `other` halfway.
+    pub fn interpolate(&self, other: &Renderable, t: f32) -> Renderable {
+        if t <= 0.0 {
+            return self.clone();
+        }
+        if t >= 1.0 {
+            return other.clone();
+        }
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        let color = self.color().lerp(&other.color(), t);
+        match (self, other) {
+            (Renderable::Circle { radius: a, .. }, Renderable::Circle { radius: b, .. }) => {
+                return Renderable::Circle {
+                    radius: lerp(*a, *b),
+                    color,
+                };
+            }
+            (
+                Renderable::Rectangle {
+                    width: w0,
+                    height: h0,
+                    ..
+                },
+                Renderable::Rectangle {
+                    width: w1,
+                    height: h1,
+                    ..
+                },
+            ) => {
+                return Renderable::Rectangle {
+                    width: lerp(*w0, *w1),
+                    height: lerp(*h0, *h1),
+                    color,
+                };
+            }
+            (
+                Renderable::Line {
+                    start: s0,
+                    end: e0,
+                    thickness: k0,
+                    ..
+                },
+                Renderable::Line {
+                    start: s1,
+                    end: e1,
+                    thickness: k1,
+                    ..
+                },
+            ) => {
+                return Renderable::Line {
+                    start: s0.lerp(s1, t),
+                    end: e0.lerp(e1, t),
+                    color,
+                    thickness: lerp(*k0, *k1),
+                };
+            }
+            (
+                Renderable::Arrow {
+                    start: s0,
+                    end: e0,
+                    thickness: k0,
+                    ..
+                },
+                Renderable::Arrow {
+                    start: s1,
+                    end: e1,
+                    thickness: k1,
+                    ..
+                },
+            ) => {
+                return Renderable::Arrow {
+                    start: s0.lerp(s1, t),
+                    end: e0.lerp(e1, t),
+                    color,
+                    thickness: lerp(*k0, *k1),
+                };
+            }
+            (Renderable::Polygon { vertices: a, .. }, Renderable::Polygon { vertices: b, .. })
+                if a.len() == b.len() =>
+            {
+                return Renderable::Polygon {
+                    vertices: a.iter().zip(b).map(|(a, b)| a.lerp(b, t)).collect(),
+                    color,
+                };
+            }
+            _ => {}
+        }
+
+        let (Some(from), Some(to)) = (self.outline(), other.outline()) else {
+            let nearer = if t < 0.5 { self } else { other };
+            return nearer.clone();
+        };
+        let start = outline_points(&from, MORPH_POINTS);
+        let mut end = outline_points(&to, MORPH_POINTS);
+        let closed = from.closed && to.closed;
+        if closed {
+            align_loop(&start, &mut end);
+        }
+        let points = start.iter().zip(&end).map(|(a, b)| a.lerp(b, t)).collect();
+        if closed {
+            Renderable::Polygon {
+                vertices: points,
+                color,
+            }
+        } else {
+            Renderable::Polyline {
+                points,
+                color,
+                thickness: lerp(
+                    self.thickness().unwrap_or(other.thickness().unwrap_or(0.0)),
+                    other.thickness().unwrap_or(self.thickness().unwrap_or(0.0)),
+                ),
+                fade: 0.0,
+            }
+        }
+    }
+
+    /// Stroke thickness of lines, arrows and polylines
+    fn thickness(&self) -> Option<f32> {
+        match self {
+            Renderable::Line { thickness, .. }
+            | Renderable::Arrow { thickness, .. }
+            | Renderable::Polyline { thickness, .. } => Some(*thickness),
+            _ => None,
+        }
+    }
+}
+
+/// Rotate the closed loop `end` to start at the point that keeps the
+/// morph from `start` shortest, so shapes don't twist as they change
+fn align_loop(start: &[Vector3], end: &mut [Vector3]) {
+    let count = end.len();
+    let cost = |shift: usize| -> f32 {
+        start
+            .iter()
+            .enumerate()
+            .map(|(i, point)| {
+                let d = *point - end[(i + shift) % count];
+                d.dot(&d)
+            })
+            .sum()
+    };
+    if let Some(best) = (0..count).min_by(|a, b| cost(*a).total_cmp(&cost(*b))) {
+        end.rotate_left(best);
+    }
+}
+
+impl SceneNode {
+    /// Copy the node's current state into its `target` and return the copy
+    /// to edit; [`effects::move_to_target`] then animates the node into it
+    ///
+    /// [`effects::move_to_target`]: crate::animation::effects::move_to_target
+    pub fn generate_target(&mut self) -> &mut NodeState {
+        let state = NodeState::of(self);
+        self.target.insert(state)
+    }
+
+    /// Remember the node's current state for
+    /// [`effects::restore`](crate::animation::effects::restore)
+    pub fn save_state(&mut self) {
+        self.saved_state = Some(NodeState::of(self));
+    }
+
+    /// Move `progress` (0..1) of the way from where the node was when the
+    /// transition started to `to`
+    pub(super) fn step_transition(&mut self, to: &NodeState, progress: f32) {
+        if progress >= 1.0 {
+            self.transition_start = None;
+            to.apply_to(self);
+            return;
+        }
+        let start = match self.transition_start.take() {
+            Some(start) => start,
+            None => NodeState::of(self),
+        };
+        start.interpolate(to, progress).apply_to(self);
+        self.transition_start = Some(start);
+    }
+}
+
+impl SceneGraph {
+    /// Morph `source` into `target` from `start_time`, then hide `source`
+    /// and show `target` in its place (Manim's ReplacementTransform)
+    ///
+    /// `target` is hidden until the handover. Both nodes should share a
+    /// parent, as the morph ends at `target`'s local transform.
+    pub fn replacement_transform(
+        &mut self,
+        source: NodeId,
+        target: NodeId,
+        start_time: f32,
+        duration: f32,
+    ) {
+        let Some(end) = self.get_node(target).map(NodeState::of) else {
+            return;
+        };
+        let start = TimeValue::new(start_time);
+        if let Some(node) = self.get_node_mut(source) {
+            node.target = Some(end);
+            let mut clip = effects::move_to_target(duration);
+            clip.add_track(visibility_switch(true, duration));
+            node.add_animation(AnimationInstance::new(clip, start));
+        }
+        if let Some(node) = self.get_node_mut(target) {
+            let mut clip = AnimationClip::new("ReplacementTransform".to_string());
+            clip.add_track(visibility_switch(false, duration));
+            clip.loop_animation = false;
+            node.add_animation(AnimationInstance::new(clip, start).with_fill_before(true));
+        }
+    }
+}
+
+/// A `visible` track flipping away from `from` at `time`
+fn visibility_switch(from: bool, time: f32) -> AnimationTrack<Vector3> {
+    let value = |visible: bool| Vector3::new(if visible { 1.0 } else { 0.0 }, 0.0, 0.0);
+    let mut track = AnimationTrack::new("visible".to_string());
+    track.add_keyframe(
+        Keyframe::new(TimeValue::new(0.0), value(from)).with_interpolation(InterpolationType::Step),
+    );
+    track.add_keyframe(Keyframe::new(TimeValue::new(time), value(!from)));
+    track
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_morph_between_shapes() {
+        let circle = Renderable::Circle {
+            radius: 1.0,
+            color: Color::RED,
+        };
+        let bigger = Renderable::Circle {
+            radius: 3.0,
+            color: Color::BLUE,
+        };
+        let Renderable::Circle { radius, color } = circle.interpolate(&bigger, 0.5) else {
+            panic!("circles blend into circles");
+        };
+        assert_eq!(radius, 2.0);
+        assert_eq!(color, Color::RED.lerp(&Color::BLUE, 0.5));
+
+        // A circle into a square passes through a polygon in between
+        let square = Renderable::Rectangle {
+            width: 2.0,
+            height: 2.0,
+            color: Color::RED,
+        };
+        let Renderable::Polygon { vertices, .. } = circle.interpolate(&square, 0.5) else {
+            panic!("different shapes morph as polygons");
+        };
+        assert_eq!(vertices.len(), MORPH_POINTS);
+        // Each point lies between the circle and the square around it
+        assert!(vertices
+            .iter()
+            .all(|p| p.length() >= 0.9 && p.length() <= 2f32.sqrt() + 1e-3));
+        assert_eq!(circle.interpolate(&square, 1.0), square);
+    }
+
+    #[test]
+    fn test_restore_saved_state() {
+        let mut scene = SceneGraph::new();
+        let dot = scene
+            .add_circle("dot", 0.2, Color::RED)
+            .at(1.0, 0.0, 0.0)
+            .build();
+        let node = scene.get_node_mut(dot).unwrap();
+        node.save_state();
+        let saved = NodeState::of(node);
+        node.opacity = 0.5;
+        node._local_transform.position = Vector3::new(3.0, 0.0, 0.0);
+        *node.renderable.as_mut().unwrap().color_mut() = Color::YELLOW;
+        node.add_animation(AnimationInstance::new(
+            effects::restore(2.0),
+            TimeValue::new(0.0),
+        ));
+
+        scene.update_animations(TimeValue::new(1.0));
+        let node = scene.get_node(dot).unwrap();
+        assert_eq!(node._local_transform.position, Vector3::new(2.0, 0.0, 0.0));
+        assert_eq!(node.opacity, 0.75);
+
+        scene.update_animations(TimeValue::new(1.0));
+        assert_eq!(NodeState::of(scene.get_node(dot).unwrap()), saved);
+    }
+
+    #[test]
+    fn test_replacement_transform_hands_over() {
+        let mut scene = SceneGraph::new();
+        let dot = scene.add_circle("dot", 0.2, Color::RED).build();
+        let block = scene
+            .add_rectangle("block", 0.5, 0.5, Color::BLUE)
+            .at(1.0, 0.0, 0.0)
+            .build();
+        scene.replacement_transform(dot, block, 0.0, 1.0);
+
+        scene.update_animations(TimeValue::new(0.5));
+        let node = scene.get_node(dot).unwrap();
+        assert!(node.visible);
+        assert_eq!(node._local_transform.position, Vector3::new(0.5, 0.0, 0.0));
+        assert!(!scene.get_node(block).unwrap().visible);
+
+        scene.update_animations(TimeValue::new(0.5));
+        let node = scene.get_node(dot).unwrap();
+        assert!(!node.visible);
+        assert_eq!(
+            NodeState::of(node),
+            NodeState::of(scene.get_node(block).unwrap())
+        );
+        assert!(scene.get_node(block).unwrap().visible);
+    }
+}
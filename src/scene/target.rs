@@ -0,0 +1,157 @@
+//! Render Targets
+//!
+//! A node with a [`RenderTarget`] draws its subtree into a texture of its
+//! own instead of the frame, through a camera of its own. An
+//! [`add_image`](super::SceneGraph::add_image) node showing the target's
+//! [`handle`](RenderTarget::handle) then displays that texture anywhere in
+//! the scene, for picture-in-picture insets, magnifier lenses and portals.
+//!
+//! The target's content is laid out in the target node's local space, and
+//! its camera shows the square from `center - 1 / zoom` to
+//! `center + 1 / zoom` of it. An image `2 / zoom` units on each side shows
+//! the content at the size it would have in the scene.
+//!
+//! A target with [`TargetSource::Scene`] draws the rest of the scene
+//! instead, with its camera in scene coordinates, for magnifiers that show
+//! a zoomed region of the frame (see
+//! [`Magnifier`](crate::mobjects::Magnifier)). Its own subtree is drawn in
+//! the frame as usual, and its camera can follow a node or pan with the
+//! `target_center` animation track.
+//!
+//! Targets are rendered innermost first (scene captures last), before the
+//! frame. An image showing a target inside what that target draws (a
+//! recursive view) shows the previous frame's texture.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::scene::*;
+//! use diomanim::core::*;
+//!
+//! let mut scene = SceneGraph::new();
+//! let inset = scene
+//!     .add_render_target("inset", 256, 256)
+//!     .target_camera(Vector3::zero(), 2.0)
+//!     .build();
+//! scene.add_circle("planet", 0.3, Color::BLUE).parent_to(inset);
+//!
+//! scene
+//!     .add_image("view", RenderTarget::handle_for("inset"), 0.5, 0.5)
+//!     .at(0.6, 0.6, 0.0);
+//! ```
+
+use super::NodeId;
+use crate::assets::AssetHandle;
+use crate::core::{Color, Matrix4, Transform, Vector3};
+
+/// What a render target draws into its texture
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TargetSource {
+    /// The target node's subtree, which is left out of the frame
+    #[default]
+    Subtree,
+    /// Everything the frame shows except the target node's subtree
+    Scene,
+}
+
+/// Texture a node's subtree is rendered into
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderTarget {
+    /// Handle image nodes use to display the texture
+    pub handle: AssetHandle,
+    /// Texture size in pixels
+    pub width: u32,
+    pub height: u32,
+    /// Point of the target's local space at the middle of the texture
+    pub center: Vector3,
+    /// Magnification; 1.0 shows the local square from -1 to 1
+    pub zoom: f32,
+    /// Color the texture is cleared to every frame
+    pub background: Color,
+    pub source: TargetSource,
+    /// Node whose world position becomes `center` whenever transforms are
+    /// updated, for scene captures tracking a moving point
+    pub follow: Option<NodeId>,
+}
+
+impl RenderTarget {
+    /// A transparent `width` x `height` target; `name` identifies its
+    /// texture, so different targets need different names
+    pub fn new(name: &str, width: u32, height: u32) -> Self {
+        Self {
+            handle: Self::handle_for(name),
+            width: width.max(1),
+            height: height.max(1),
+            center: Vector3::zero(),
+            zoom: 1.0,
+            background: Color::rgba(0.0, 0.0, 0.0, 0.0),
+            source: TargetSource::Subtree,
+            follow: None,
+        }
+    }
+
+    /// The texture handle of the target called `name`
+    pub fn handle_for(name: &str) -> AssetHandle {
+        AssetHandle::for_source(&format!("render-target:{name}"))
+    }
+
+    pub fn with_camera(mut self, center: Vector3, zoom: f32) -> Self {
+        self.center = center;
+        self.zoom = zoom;
+        self
+    }
+
+    pub fn with_background(mut self, background: Color) -> Self {
+        self.background = background;
+        self
+    }
+
+    pub fn with_source(mut self, source: TargetSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// View-projection of the target's camera, for a target node placed at
+    /// `world`
+    pub fn view_projection(&self, world: &Transform) -> Matrix4 {
+        let zoom = self.zoom.max(f32::EPSILON);
+        let camera = glam::Mat4::from_scale(glam::Vec3::new(zoom, zoom, 1.0))
+            * glam::Mat4::from_translation(-glam::Vec3::from(self.center));
+        match self.source {
+            TargetSource::Subtree => {
+                let local_to_world: glam::Mat4 = world.matrix().into();
+                (camera * local_to_world.inverse()).into()
+            }
+            TargetSource::Scene => camera.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_camera() {
+        let target =
+            RenderTarget::new("inset", 128, 64).with_camera(Vector3::new(0.5, 0.0, 0.0), 2.0);
+        assert_eq!(target.handle, RenderTarget::handle_for("inset"));
+        assert_ne!(target.handle, RenderTarget::handle_for("other"));
+
+        // Content is laid out relative to the target node
+        let mut world = Transform::new();
+        world.position = Vector3::new(-0.3, 0.2, 0.0);
+        let view_proj = target.view_projection(&world);
+
+        let local = Vector3::new(0.5, 0.25, 0.0);
+        let clip = view_proj.transform_point(world.matrix().transform_point(local));
+        assert!((clip - Vector3::new(0.0, 0.5, 0.0)).length() < 1e-5);
+
+        // Scene captures look at scene coordinates wherever the node is
+        let view_proj = target
+            .with_source(TargetSource::Scene)
+            .view_projection(&world);
+        let clip = view_proj.transform_point(Vector3::new(0.5, 0.25, 0.0));
+        assert!((clip - Vector3::new(0.0, 0.5, 0.0)).length() < 1e-5);
+    }
+}
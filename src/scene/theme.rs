@@ -0,0 +1,161 @@
+//! Scene Themes
+//!
+//! A [`Theme`] holds the styling defaults for a scene: background, the
+//! color of text and strokes, stroke width, font and a color palette. The
+//! builder methods on [`SceneGraph`](super::SceneGraph) take `None` for a
+//! color, stroke width or font size to use the scene's theme:
+//!
+//! ```rust
+//! use diomanim::core::*;
+//! use diomanim::pipeline::RenderConfig;
+//! use diomanim::scene::{SceneGraph, Theme};
+//!
+//! let mut scene = SceneGraph::new();
+//! scene.set_theme(Theme::light());
+//! scene.add_circle("dot", 0.5, None);
+//! scene.add_line("axis", Vector3::zero(), Vector3::new(3.0, 0.0, 0.0), None, None);
+//! scene.add_text("label", "x", None, Color::RED);
+//!
+//! let config = RenderConfig::new(1280, 720, 30, 2.0).with_theme(scene.theme());
+//! assert_eq!(config.background, Theme::light().background);
+//! ```
+
+use crate::core::Color;
+use crate::text::SystemFonts;
+use serde::{Deserialize, Serialize};
+
+/// Styling defaults for a scene
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Theme {
+    pub background: Color,
+    /// Color of text, lines and arrows
+    pub foreground: Color,
+    /// Fill color of shapes
+    pub fill: Color,
+    /// Thickness of lines and arrows
+    pub stroke_width: f32,
+    pub font_size: f32,
+    /// Font file for text (the system sans-serif font if `None`)
+    pub font: Option<String>,
+    /// Accent colors for telling objects apart, see [`Theme::color`]
+    pub palette: Vec<Color>,
+}
+
+impl Theme {
+    /// Light text on a near-black background, with 3Blue1Brown's accents
+    pub fn dark() -> Self {
+        Self {
+            background: Color::from_hex("0e0e0e"),
+            foreground: Color::WHITE,
+            fill: Color::BLUE_C,
+            stroke_width: 2.0,
+            font_size: 48.0,
+            font: None,
+            palette: vec![
+                Color::BLUE_C,
+                Color::RED_C,
+                Color::GREEN_C,
+                Color::YELLOW_C,
+                Color::PURPLE_C,
+                Color::TEAL_C,
+                Color::GOLD_C,
+            ],
+        }
+    }
+
+    /// Dark text on an off-white background, with deeper accents that
+    /// hold their contrast on paper
+    pub fn light() -> Self {
+        Self {
+            background: Color::from_hex("f5f5f0"),
+            foreground: Color::from_hex("1c1c1c"),
+            fill: Color::BLUE_E,
+            palette: vec![
+                Color::BLUE_E,
+                Color::RED_E,
+                Color::GREEN_E,
+                Color::GOLD_E,
+                Color::PURPLE_E,
+                Color::TEAL_E,
+                Color::MAROON_E,
+            ],
+            ..Self::dark()
+        }
+    }
+
+    /// The `index`th palette color, cycling through the palette (the fill
+    /// color if the palette is empty)
+    pub fn color(&self, index: usize) -> Color {
+        if self.palette.is_empty() {
+            self.fill
+        } else {
+            self.palette[index % self.palette.len()]
+        }
+    }
+
+    /// Path of the font file to render text with
+    pub fn font_path(&self) -> &str {
+        self.font.as_deref().unwrap_or(SystemFonts::sans_serif())
+    }
+
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.background = color;
+        self
+    }
+
+    pub fn with_palette(mut self, palette: Vec<Color>) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    pub fn with_font(mut self, font_path: impl Into<String>) -> Self {
+        self.font = Some(font_path.into());
+        self
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Vector3;
+    use crate::scene::{Renderable, SceneGraph};
+
+    #[test]
+    fn test_builders_fall_back_to_theme() {
+        let theme = Theme::light();
+        assert_eq!(theme.color(1), theme.color(1 + theme.palette.len()));
+
+        let mut scene = SceneGraph::new();
+        scene.set_theme(theme.clone());
+        let dot = scene.add_circle("dot", 0.5, None).id();
+        let red = scene.add_circle("red", 0.5, Color::RED).id();
+        let line = scene
+            .add_line(
+                "line",
+                Vector3::zero(),
+                Vector3::new(1.0, 0.0, 0.0),
+                None,
+                5.0,
+            )
+            .id();
+
+        let renderable = |id| scene.get_node(id).unwrap().renderable.clone();
+        assert!(
+            matches!(renderable(dot), Some(Renderable::Circle { color, .. }) if color == theme.fill)
+        );
+        assert!(
+            matches!(renderable(red), Some(Renderable::Circle { color, .. }) if color == Color::RED)
+        );
+        assert!(matches!(
+            renderable(line),
+            Some(Renderable::Line { color, thickness, .. })
+                if color == theme.foreground && thickness == 5.0
+        ));
+    }
+}
@@ -0,0 +1,196 @@
+//! Always-Redraw Updaters
+//!
+//! An [`Updater`] rebuilds a node's renderable from the state of other
+//! nodes every time the scene's transforms are updated, so derived shapes
+//! stay attached to what they depend on: a line that always connects two
+//! moving dots, or an angle arc that always measures between two lines.
+//! Updaters read world transforms after animations and constraints have
+//! been applied, and return shapes in their own node's local space.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use diomanim::scene::*;
+//! use diomanim::core::*;
+//!
+//! let mut scene = SceneGraph::new();
+//! let a = scene
+//!     .add_circle("a", 0.05, Color::RED)
+//!     .at(-0.5, 0.0, 0.0)
+//!     .move_to(0.0, Vector3::new(-0.5, 0.5, 0.0), 1.0)
+//!     .build();
+//! let b = scene.add_circle("b", 0.05, Color::BLUE).at(0.5, 0.0, 0.0).build();
+//! scene.add_always_redraw("link", Updater::line_between(a, b, Color::WHITE, 2.0));
+//!
+//! // Any other derived shape: a circle whose radius is the distance a-b
+//! scene.add_always_redraw(
+//!     "span",
+//!     Updater::new(move |scene, _| {
+//!         let a = scene.get_node(a)?.world_transform.position;
+//!         let b = scene.get_node(b)?.world_transform.position;
+//!         Some(Renderable::Circle { radius: a.distance(&b), color: Color::GRAY })
+//!     }),
+//! );
+//! ```
+
+use super::{NodeId, Renderable, SceneGraph, SceneNode};
+use crate::core::{Color, Vector3};
+use std::sync::Arc;
+
+/// Points on the arc drawn by [`Updater::angle_between`]
+const ANGLE_ARC_POINTS: usize = 32;
+
+type Rebuild = dyn Fn(&SceneGraph, &SceneNode) -> Option<Renderable> + Send + Sync;
+
+/// Closure computing a node's renderable from the rest of the scene
+///
+/// It receives the scene and the node being redrawn; returning `None`
+/// keeps the previous renderable (e.g. while a dependency is missing).
+#[derive(Clone)]
+pub struct Updater(Arc<Rebuild>);
+
+impl std::fmt::Debug for Updater {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Updater")
+    }
+}
+
+impl Updater {
+    pub fn new(
+        rebuild: impl Fn(&SceneGraph, &SceneNode) -> Option<Renderable> + Send + Sync + 'static,
+    ) -> Self {
+        Self(Arc::new(rebuild))
+    }
+
+    /// The renderable `node` should have now
+    pub fn evaluate(&self, scene: &SceneGraph, node: &SceneNode) -> Option<Renderable> {
+        (self.0)(scene, node)
+    }
+
+    /// A line from the center of `from` to the center of `to`
+    pub fn line_between(from: NodeId, to: NodeId, color: Color, thickness: f32) -> Self {
+        Self::new(move |scene, node| {
+            let start = local_position(scene, node, from)?;
+            let end = local_position(scene, node, to)?;
+            Some(Renderable::Line {
+                start,
+                end,
+                color,
+                thickness,
+            })
+        })
+    }
+
+    /// An arc of `radius` marking the angle between two lines, centered on
+    /// the start of `first` and swept the short way from `first` to
+    /// `second`
+    pub fn angle_between(
+        first: NodeId,
+        second: NodeId,
+        radius: f32,
+        color: Color,
+        thickness: f32,
+    ) -> Self {
+        Self::new(move |scene, node| {
+            let (vertex, first_end) = world_line(scene, first)?;
+            let (second_start, second_end) = world_line(scene, second)?;
+            let from = (first_end.y - vertex.y).atan2(first_end.x - vertex.x);
+            let to = (second_end.y - second_start.y).atan2(second_end.x - second_start.x);
+            let sweep = angle_difference(from, to);
+
+            let to_local = node.world_transform.inverse();
+            let points = (0..ANGLE_ARC_POINTS)
+                .map(|i| {
+                    let angle = from + sweep * i as f32 / (ANGLE_ARC_POINTS - 1) as f32;
+                    let point = vertex + Vector3::new(angle.cos(), angle.sin(), 0.0) * radius;
+                    to_local.transform_point(point)
+                })
+                .collect();
+            Some(Renderable::Polyline {
+                points,
+                color,
+                thickness,
+                fade: 0.0,
+            })
+        })
+    }
+}
+
+/// Signed angle from `from` to `to`, in -PI..=PI
+fn angle_difference(from: f32, to: f32) -> f32 {
+    let tau = std::f32::consts::TAU;
+    let sweep = (to - from).rem_euclid(tau);
+    if sweep > std::f32::consts::PI {
+        sweep - tau
+    } else {
+        sweep
+    }
+}
+
+/// Position of node `id` in the local space of `node`
+fn local_position(scene: &SceneGraph, node: &SceneNode, id: NodeId) -> Option<Vector3> {
+    let position = scene.get_node(id)?.world_transform.position;
+    Some(node.world_transform.inverse().transform_point(position))
+}
+
+/// World-space endpoints of a line or arrow node
+fn world_line(scene: &SceneGraph, id: NodeId) -> Option<(Vector3, Vector3)> {
+    let node = scene.get_node(id)?;
+    let renderable = node.renderable.as_ref()?;
+    let (start, end, _, _) = renderable.as_line().or_else(|| renderable.as_arrow())?;
+    Some((
+        node.world_transform.transform_point(*start),
+        node.world_transform.transform_point(*end),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    #[test]
+    fn test_angle_difference_takes_short_way() {
+        assert!((angle_difference(0.0, FRAC_PI_2) - FRAC_PI_2).abs() < 1e-6);
+        assert!((angle_difference(FRAC_PI_2, 0.0) + FRAC_PI_2).abs() < 1e-6);
+        assert!((angle_difference(0.9 * PI, -0.9 * PI) - 0.2 * PI).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_updaters_follow_moving_nodes() {
+        let mut scene = SceneGraph::new();
+        let a = scene
+            .add_circle("a", 0.05, None)
+            .move_to(0.0, Vector3::new(0.0, 1.0, 0.0), 1.0)
+            .build();
+        let b = scene.add_circle("b", 0.05, None).at(1.0, 0.0, 0.0).build();
+        let link = scene
+            .add_always_redraw("link", Updater::line_between(a, b, Color::WHITE, 2.0))
+            .build();
+        let x_axis = scene
+            .add_line("x", Vector3::zero(), Vector3::new(1.0, 0.0, 0.0), None, 2.0)
+            .build();
+        let diagonal = scene
+            .add_line("d", Vector3::zero(), Vector3::new(1.0, 1.0, 0.0), None, 2.0)
+            .build();
+        let angle = scene
+            .add_always_redraw(
+                "angle",
+                Updater::angle_between(x_axis, diagonal, 0.2, Color::YELLOW, 2.0),
+            )
+            .build();
+
+        scene.update_animations(crate::core::TimeValue::new(1.0));
+        let line = scene.get_node(link).unwrap().renderable.clone().unwrap();
+        let (start, end, _, _) = line.as_line().unwrap();
+        assert!((*start - Vector3::new(0.0, 1.0, 0.0)).length() < 1e-5);
+        assert!((*end - Vector3::new(1.0, 0.0, 0.0)).length() < 1e-5);
+
+        let arc = scene.get_node(angle).unwrap().renderable.clone().unwrap();
+        let (points, _, _, _) = arc.as_polyline().unwrap();
+        assert_eq!(points.len(), ANGLE_ARC_POINTS);
+        assert!((points[0] - Vector3::new(0.2, 0.0, 0.0)).length() < 1e-5);
+        let last = points[ANGLE_ARC_POINTS - 1];
+        assert!((last.x - last.y).abs() < 1e-5);
+    }
+}
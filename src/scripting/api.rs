@@ -0,0 +1,401 @@
+//! Rhai bindings for scenes, nodes, colors and effects
+//!
+//! Scripts see a `scene` variable and build on it with the same method names
+//! as the Rust builder API. Node methods return the node so they chain.
+
+use crate::core::{Color, Vector3};
+use crate::scene::{NodeBuilder, NodeEffect, NodeId, PostEffect, SceneGraph, Theme};
+use rhai::{Array, Engine, EvalAltResult, FLOAT, INT};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// What a script builds: the scene and how long its animations run
+pub(crate) struct ScriptState {
+    pub(crate) scene: SceneGraph,
+    /// Set by the script, otherwise the end of the last animation
+    pub(crate) duration: Option<f32>,
+    pub(crate) animation_end: f32,
+}
+
+/// The `scene` value scripts build on
+#[derive(Clone)]
+pub(crate) struct ScriptScene(pub(crate) Rc<RefCell<ScriptState>>);
+
+/// A node handle returned by the `scene` methods
+#[derive(Clone)]
+pub(crate) struct ScriptNode {
+    state: Rc<RefCell<ScriptState>>,
+    id: NodeId,
+}
+
+impl ScriptScene {
+    pub(crate) fn new() -> Self {
+        Self(Rc::new(RefCell::new(ScriptState {
+            scene: SceneGraph::new(),
+            duration: None,
+            animation_end: 0.0,
+        })))
+    }
+
+    /// Add a node with a builder method and hand it to the script
+    fn add(&mut self, add: impl FnOnce(&mut SceneGraph) -> NodeId) -> ScriptNode {
+        let id = add(&mut self.0.borrow_mut().scene);
+        ScriptNode {
+            state: self.0.clone(),
+            id,
+        }
+    }
+
+    fn add_post_effect(&mut self, effect: PostEffect) {
+        self.0.borrow_mut().scene.add_post_effect(effect);
+    }
+}
+
+impl ScriptNode {
+    /// Apply a builder method to this node
+    fn with(&mut self, apply: impl FnOnce(NodeBuilder) -> NodeBuilder) -> Self {
+        apply(NodeBuilder::new(
+            &mut self.state.borrow_mut().scene,
+            self.id,
+        ));
+        self.clone()
+    }
+
+    /// Apply a builder animation ending at `start + duration`
+    fn animate(
+        &mut self,
+        start: f32,
+        duration: f32,
+        apply: impl FnOnce(NodeBuilder) -> NodeBuilder,
+    ) -> Self {
+        let mut state = self.state.borrow_mut();
+        state.animation_end = state.animation_end.max(start + duration);
+        apply(NodeBuilder::new(&mut state.scene, self.id));
+        self.clone()
+    }
+}
+
+fn count(value: INT, what: &str) -> ScriptResult<usize> {
+    usize::try_from(value).map_err(|_| format!("{what} must not be negative, got {value}").into())
+}
+
+fn named_color(name: &str) -> ScriptResult<Color> {
+    Color::from_name(name).ok_or_else(|| format!("Unknown color '{name}'").into())
+}
+
+fn named_theme(name: &str) -> ScriptResult<Theme> {
+    match name {
+        "dark" => Ok(Theme::dark()),
+        "light" => Ok(Theme::light()),
+        _ => Err(format!("Unknown theme '{name}' (expected \"dark\" or \"light\")").into()),
+    }
+}
+
+fn points(vertices: &Array) -> ScriptResult<Vec<Vector3>> {
+    vertices
+        .iter()
+        .map(|v| {
+            v.clone()
+                .try_cast::<Vector3>()
+                .ok_or_else(|| format!("Expected vec3 in polygon, got {}", v.type_name()).into())
+        })
+        .collect()
+}
+
+/// Register the scene API on `engine`
+pub(crate) fn register(engine: &mut Engine) {
+    register_values(engine);
+    register_scene(engine);
+    register_node(engine);
+}
+
+fn register_values(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<Vector3>("Vec3")
+        .register_fn("vec3", Vector3::new)
+        .register_fn("vec2", |x: FLOAT, y: FLOAT| Vector3::new(x, y, 0.0))
+        .register_get("x", |v: &mut Vector3| v.x)
+        .register_get("y", |v: &mut Vector3| v.y)
+        .register_get("z", |v: &mut Vector3| v.z)
+        .register_fn("+", |a: Vector3, b: Vector3| a + b)
+        .register_fn("-", |a: Vector3, b: Vector3| a - b)
+        .register_fn("*", |a: Vector3, s: FLOAT| a * s)
+        .register_fn("*", |s: FLOAT, a: Vector3| a * s)
+        .register_fn("to_string", |v: &mut Vector3| {
+            format!("vec3({}, {}, {})", v.x, v.y, v.z)
+        });
+
+    engine
+        .register_type_with_name::<Color>("Color")
+        .register_fn("rgb", Color::new)
+        .register_fn("rgba", Color::rgba)
+        .register_fn("hex", |hex: &str| Color::from_hex(hex))
+        .register_fn("color", named_color)
+        .register_fn("with_alpha", |c: &mut Color, alpha: FLOAT| {
+            Color::rgba(c.r, c.g, c.b, alpha)
+        })
+        .register_fn("to_string", |c: &mut Color| {
+            format!("rgba({}, {}, {}, {})", c.r, c.g, c.b, c.a)
+        });
+}
+
+fn register_scene(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<ScriptScene>("Scene")
+        .register_fn(
+            "circle",
+            |s: &mut ScriptScene, name: &str, radius: FLOAT| {
+                s.add(|g| g.add_circle(name, radius, None).id())
+            },
+        )
+        .register_fn(
+            "circle",
+            |s: &mut ScriptScene, name: &str, radius: FLOAT, color: Color| {
+                s.add(|g| g.add_circle(name, radius, color).id())
+            },
+        )
+        .register_fn(
+            "rectangle",
+            |s: &mut ScriptScene, name: &str, width: FLOAT, height: FLOAT| {
+                s.add(|g| g.add_rectangle(name, width, height, None).id())
+            },
+        )
+        .register_fn(
+            "rectangle",
+            |s: &mut ScriptScene, name: &str, width: FLOAT, height: FLOAT, color: Color| {
+                s.add(|g| g.add_rectangle(name, width, height, color).id())
+            },
+        )
+        .register_fn("square", |s: &mut ScriptScene, name: &str, side: FLOAT| {
+            s.add(|g| g.add_square(name, side, None).id())
+        })
+        .register_fn(
+            "square",
+            |s: &mut ScriptScene, name: &str, side: FLOAT, color: Color| {
+                s.add(|g| g.add_square(name, side, color).id())
+            },
+        )
+        .register_fn(
+            "line",
+            |s: &mut ScriptScene, name: &str, start: Vector3, end: Vector3| {
+                s.add(|g| g.add_line(name, start, end, None, None).id())
+            },
+        )
+        .register_fn(
+            "line",
+            |s: &mut ScriptScene, name: &str, start: Vector3, end: Vector3, color: Color| {
+                s.add(|g| g.add_line(name, start, end, color, None).id())
+            },
+        )
+        .register_fn(
+            "arrow",
+            |s: &mut ScriptScene, name: &str, start: Vector3, end: Vector3| {
+                s.add(|g| g.add_arrow(name, start, end, None, None).id())
+            },
+        )
+        .register_fn(
+            "arrow",
+            |s: &mut ScriptScene, name: &str, start: Vector3, end: Vector3, color: Color| {
+                s.add(|g| g.add_arrow(name, start, end, color, None).id())
+            },
+        )
+        .register_fn(
+            "polygon",
+            |s: &mut ScriptScene, name: &str, vertices: Array| -> ScriptResult<ScriptNode> {
+                let vertices = points(&vertices)?;
+                Ok(s.add(|g| g.add_polygon(name, vertices, None).id()))
+            },
+        )
+        .register_fn(
+            "polygon",
+            |s: &mut ScriptScene,
+             name: &str,
+             vertices: Array,
+             color: Color|
+             -> ScriptResult<ScriptNode> {
+                let vertices = points(&vertices)?;
+                Ok(s.add(|g| g.add_polygon(name, vertices, color).id()))
+            },
+        )
+        .register_fn(
+            "regular_polygon",
+            |s: &mut ScriptScene,
+             name: &str,
+             sides: INT,
+             radius: FLOAT|
+             -> ScriptResult<ScriptNode> {
+                let sides = count(sides, "sides")?;
+                Ok(s.add(|g| g.add_regular_polygon(name, sides, radius, None).id()))
+            },
+        )
+        .register_fn(
+            "regular_polygon",
+            |s: &mut ScriptScene,
+             name: &str,
+             sides: INT,
+             radius: FLOAT,
+             color: Color|
+             -> ScriptResult<ScriptNode> {
+                let sides = count(sides, "sides")?;
+                Ok(s.add(|g| g.add_regular_polygon(name, sides, radius, color).id()))
+            },
+        )
+        .register_fn(
+            "star",
+            |s: &mut ScriptScene,
+             name: &str,
+             points: INT,
+             outer: FLOAT,
+             inner: FLOAT|
+             -> ScriptResult<ScriptNode> {
+                let points = count(points, "points")?;
+                Ok(s.add(|g| g.add_star(name, points, outer, inner, None).id()))
+            },
+        )
+        .register_fn("text", |s: &mut ScriptScene, name: &str, content: &str| {
+            s.add(|g| g.add_text(name, content, None, None).id())
+        })
+        .register_fn(
+            "text",
+            |s: &mut ScriptScene, name: &str, content: &str, font_size: FLOAT| {
+                s.add(|g| g.add_text(name, content, font_size, None).id())
+            },
+        )
+        .register_fn(
+            "text",
+            |s: &mut ScriptScene, name: &str, content: &str, font_size: FLOAT, color: Color| {
+                s.add(|g| g.add_text(name, content, font_size, color).id())
+            },
+        )
+        .register_fn(
+            "theme",
+            |s: &mut ScriptScene, name: &str| -> ScriptResult<()> {
+                s.0.borrow_mut().scene.set_theme(named_theme(name)?);
+                Ok(())
+            },
+        )
+        .register_fn(
+            "bloom",
+            |s: &mut ScriptScene, threshold: FLOAT, intensity: FLOAT| {
+                s.add_post_effect(PostEffect::bloom(threshold, intensity));
+            },
+        )
+        .register_fn("vignette", |s: &mut ScriptScene, intensity: FLOAT| {
+            s.add_post_effect(PostEffect::vignette(intensity));
+        })
+        .register_fn("blur", |s: &mut ScriptScene, radius: FLOAT| {
+            s.add_post_effect(PostEffect::blur(radius));
+        })
+        .register_get_set(
+            "background",
+            |s: &mut ScriptScene| s.0.borrow().scene.theme().background,
+            |s: &mut ScriptScene, color: Color| {
+                let mut state = s.0.borrow_mut();
+                let theme = state.scene.theme().clone().with_background(color);
+                state.scene.set_theme(theme);
+            },
+        )
+        .register_get_set(
+            "duration",
+            |s: &mut ScriptScene| {
+                let state = s.0.borrow();
+                state.duration.unwrap_or(state.animation_end)
+            },
+            |s: &mut ScriptScene, duration: FLOAT| s.0.borrow_mut().duration = Some(duration),
+        );
+}
+
+fn register_node(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<ScriptNode>("Node")
+        .register_fn("at", |n: &mut ScriptNode, x: FLOAT, y: FLOAT, z: FLOAT| {
+            n.with(|b| b.at(x, y, z))
+        })
+        .register_fn("at", |n: &mut ScriptNode, position: Vector3| {
+            n.with(|b| b.at_vec(position))
+        })
+        .register_fn("scale", |n: &mut ScriptNode, scale: FLOAT| {
+            n.with(|b| b.scale(scale))
+        })
+        .register_fn(
+            "scale",
+            |n: &mut ScriptNode, x: FLOAT, y: FLOAT, z: FLOAT| n.with(|b| b.scale_xyz(x, y, z)),
+        )
+        .register_fn("rotate_z", |n: &mut ScriptNode, degrees: FLOAT| {
+            n.with(|b| b.rotate_z_degrees(degrees))
+        })
+        .register_fn("opacity", |n: &mut ScriptNode, opacity: FLOAT| {
+            n.with(|b| b.opacity(opacity))
+        })
+        .register_fn("visible", |n: &mut ScriptNode, visible: bool| {
+            n.with(|b| b.visible(visible))
+        })
+        .register_fn("glow", |n: &mut ScriptNode, color: Color, radius: FLOAT| {
+            n.with(|b| b.effect(NodeEffect::glow(color, radius)))
+        })
+        .register_fn(
+            "shadow",
+            |n: &mut ScriptNode, offset_x: FLOAT, offset_y: FLOAT, softness: FLOAT| {
+                n.with(|b| b.shadow(offset_x, offset_y, softness))
+            },
+        )
+        .register_fn("parent_to", |n: &mut ScriptNode, parent: ScriptNode| {
+            n.with(|b| b.parent_to(parent.id))
+        })
+        .register_fn(
+            "fade_in",
+            |n: &mut ScriptNode, start: FLOAT, duration: FLOAT| {
+                n.animate(start, duration, |b| b.fade_in(start, duration))
+            },
+        )
+        .register_fn(
+            "fade_out",
+            |n: &mut ScriptNode, start: FLOAT, duration: FLOAT| {
+                n.animate(start, duration, |b| b.fade_out(start, duration))
+            },
+        )
+        .register_fn(
+            "create",
+            |n: &mut ScriptNode, start: FLOAT, duration: FLOAT| {
+                n.animate(start, duration, |b| b.create(start, duration))
+            },
+        )
+        .register_fn(
+            "uncreate",
+            |n: &mut ScriptNode, start: FLOAT, duration: FLOAT| {
+                n.animate(start, duration, |b| b.uncreate(start, duration))
+            },
+        )
+        .register_fn(
+            "grow",
+            |n: &mut ScriptNode, start: FLOAT, duration: FLOAT| {
+                n.animate(start, duration, |b| b.grow(start, duration))
+            },
+        )
+        .register_fn(
+            "shrink",
+            |n: &mut ScriptNode, start: FLOAT, duration: FLOAT| {
+                n.animate(start, duration, |b| b.shrink(start, duration))
+            },
+        )
+        .register_fn(
+            "move_to",
+            |n: &mut ScriptNode, start: FLOAT, target: Vector3, duration: FLOAT| {
+                n.animate(start, duration, |b| b.move_to(start, target, duration))
+            },
+        )
+        .register_fn(
+            "shift",
+            |n: &mut ScriptNode, start: FLOAT, offset: Vector3, duration: FLOAT| {
+                n.animate(start, duration, |b| b.shift(start, offset, duration))
+            },
+        )
+        .register_fn(
+            "spin",
+            |n: &mut ScriptNode, start: FLOAT, rotations: FLOAT, duration: FLOAT| {
+                n.animate(start, duration, |b| b.spin(start, rotations, duration))
+            },
+        );
+}
@@ -0,0 +1,193 @@
+//! Scene Scripting
+//!
+//! Author scenes as [Rhai](https://rhai.rs) scripts instead of Rust, and
+//! reload them while the preview is open (requires the `scripting` feature).
+//! A script builds on the `scene` variable with the builder's method names:
+//!
+//! ```rust
+//! use diomanim::scripting::SceneScript;
+//!
+//! let script = SceneScript::new(r#"
+//!     scene.theme("light");
+//!     let dot = scene.circle("dot", 0.5, color("BLUE"));
+//!     dot.at(-2.0, 0.0, 0.0).fade_in(0.0, 0.5).move_to(0.5, vec2(2.0, 0.0), 1.5);
+//!     scene.text("label", "Hello", 36.0).at(0.0, 1.5, 0.0).create(0.0, 1.0);
+//!     scene.vignette(0.4);
+//! "#)?;
+//!
+//! let built = script.run()?;
+//! assert_eq!(built.scene.node_count(), 2);
+//! assert_eq!(built.duration, 2.0);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Numbers passed as sizes, times and coordinates are floats, so write
+//! `1.0` rather than `1`. The script's duration is the end of its last
+//! animation unless it sets `scene.duration`.
+//!
+//! [`run_script_preview`](crate::preview::run_script_preview) opens a
+//! preview that re-runs the script whenever its file is saved.
+
+mod api;
+
+use crate::scene::SceneGraph;
+use rhai::{Engine, Scope, AST};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Duration of a script that neither animates nor sets `scene.duration`
+const STILL_DURATION: f32 = 1.0;
+
+/// A scene built by running a script
+pub struct ScriptedScene {
+    pub scene: SceneGraph,
+    /// Seconds until the last animation ends (or `scene.duration`)
+    pub duration: f32,
+}
+
+/// A compiled scene script, optionally backed by a file it reloads from
+pub struct SceneScript {
+    engine: Engine,
+    ast: AST,
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+}
+
+impl SceneScript {
+    /// Compile a script from source
+    pub fn new(source: &str) -> Result<Self, Box<dyn Error>> {
+        let engine = Self::engine();
+        let ast = engine.compile(source)?;
+        Ok(Self {
+            engine,
+            ast,
+            path: None,
+            modified: None,
+        })
+    }
+
+    /// Compile the script at `path`; [`reload_if_changed`](Self::reload_if_changed)
+    /// picks up later edits
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let path = path.as_ref();
+        let modified = std::fs::metadata(path)?.modified().ok();
+        let mut script = Self::new(&std::fs::read_to_string(path)?)?;
+        script.path = Some(path.to_path_buf());
+        script.modified = modified;
+        Ok(script)
+    }
+
+    fn engine() -> Engine {
+        let mut engine = Engine::new();
+        api::register(&mut engine);
+        engine
+    }
+
+    /// File the script was loaded from
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Run the script to build a fresh scene
+    pub fn run(&self) -> Result<ScriptedScene, Box<dyn Error>> {
+        let scene = api::ScriptScene::new();
+        let mut scope = Scope::new();
+        scope.push("scene", scene.clone());
+        self.engine.run_ast_with_scope(&mut scope, &self.ast)?;
+        drop(scope);
+
+        let state = std::rc::Rc::try_unwrap(scene.0)
+            .map_err(|_| "script kept a reference to the scene")?
+            .into_inner();
+        let duration = state.duration.unwrap_or(if state.animation_end > 0.0 {
+            state.animation_end
+        } else {
+            STILL_DURATION
+        });
+        Ok(ScriptedScene {
+            scene: state.scene,
+            duration,
+        })
+    }
+
+    /// Recompile the script if its file changed since it was last read.
+    ///
+    /// Returns whether it was reloaded. On a compile error the previous
+    /// version is kept, and the broken one isn't retried until the file
+    /// changes again.
+    pub fn reload_if_changed(&mut self) -> Result<bool, Box<dyn Error>> {
+        let Some(path) = &self.path else {
+            return Ok(false);
+        };
+        let modified = std::fs::metadata(path)?.modified().ok();
+        if modified == self.modified {
+            return Ok(false);
+        }
+        self.modified = modified;
+        self.ast = self.engine.compile(std::fs::read_to_string(path)?)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Color;
+    use crate::scene::Renderable;
+
+    #[test]
+    fn test_script_builds_scene_and_reloads() {
+        let built = SceneScript::new(
+            r#"
+            scene.background = hex("202020");
+            let parent = scene.square("box", 1.0);
+            scene.polygon("tri", [vec2(0.0, 0.0), vec2(1.0, 0.0), vec2(0.0, 1.0)], color("RED"))
+                .parent_to(parent)
+                .spin(1.0, 2.0, 3.0);
+            scene.duration = 2.5;
+            "#,
+        )
+        .unwrap()
+        .run()
+        .unwrap();
+        assert_eq!(built.scene.node_count(), 2);
+        assert_eq!(built.duration, 2.5);
+        assert_eq!(built.scene.theme().background, Color::from_hex("202020"));
+        let tri = built
+            .scene
+            .visible_renderable_nodes()
+            .into_iter()
+            .find(|node| node.name == "tri")
+            .unwrap();
+        assert!(tri.parent.is_some());
+        assert!(matches!(
+            &tri.renderable,
+            Some(Renderable::Polygon { vertices, color }) if vertices.len() == 3 && *color == Color::RED_C
+        ));
+
+        assert!(SceneScript::new(r#"scene.regular_polygon("p", -3, 1.0);"#)
+            .unwrap()
+            .run()
+            .is_err());
+        assert!(SceneScript::new("scene.circle(").is_err());
+
+        let path =
+            std::env::temp_dir().join(format!("diomanim_script_{}.rhai", std::process::id()));
+        std::fs::write(&path, r#"scene.circle("a", 1.0).fade_in(0.0, 2.0);"#).unwrap();
+        let mut script = SceneScript::load(&path).unwrap();
+        assert_eq!(script.run().unwrap().duration, 2.0);
+        assert!(!script.reload_if_changed().unwrap());
+
+        // Force a different timestamp in case the filesystem's is coarse
+        std::fs::write(&path, r#"scene.circle("a", 1.0); scene.circle("b", 1.0);"#).unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + std::time::Duration::from_secs(5))
+            .unwrap();
+        assert!(script.reload_if_changed().unwrap());
+        let reloaded = script.run().unwrap();
+        assert_eq!(reloaded.scene.node_count(), 2);
+        assert_eq!(reloaded.duration, STILL_DURATION);
+        std::fs::remove_file(&path).ok();
+    }
+}
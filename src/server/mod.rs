@@ -0,0 +1,411 @@
+//! # Render Server
+//!
+//! A long-running process that renders scenes for other programs (a web
+//! app, a render farm queue) over a WebSocket speaking JSON-RPC 2.0
+//! (requires the `server` feature):
+//!
+//! ```rust,no_run
+//! use diomanim::server::RenderServer;
+//!
+//! let server = RenderServer::bind("127.0.0.1:9460", "renders")?;
+//! server.run()?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Scenes are sent as [scene scripts](crate::scripting). A `render` call
+//! queues a job and answers with its id, then the job reports back through
+//! notifications:
+//!
+//! ```text
+//! → {"jsonrpc": "2.0", "id": 1, "method": "render", "params": {
+//!       "script": "scene.circle(\"dot\", 0.5, color(\"BLUE\")).fade_in(0.0, 1.0);",
+//!       "width": 640, "height": 360, "video": "dot.mp4"}}
+//! ← {"jsonrpc": "2.0", "id": 1, "result": {"job": 1}}
+//! ← {"jsonrpc": "2.0", "method": "render.stage", "params": {"job": 1, "stage": "rendering"}}
+//! ← {"jsonrpc": "2.0", "method": "render.progress", "params": {"job": 1, "frame": 1, "total": 30}}
+//!   ...
+//! ← {"jsonrpc": "2.0", "method": "render.stage", "params": {"job": 1, "stage": "encoding"}}
+//!   ...
+//! ← {"jsonrpc": "2.0", "method": "render.finished", "params": {"job": 1,
+//!       "frames_dir": "job_1", "video": "dot.mp4", "frames_rendered": 30, "frames_cached": 0}}
+//! ```
+//!
+//! A job that fails sends `render.failed` with a `message` instead. The
+//! other methods are `ping` and `status`, which counts the jobs queued or
+//! rendering.
+//!
+//! Output paths are relative to the server's output directory and can't
+//! leave it. Jobs from every connection share one GPU renderer and run one
+//! at a time, in the order they were queued.
+
+pub mod protocol;
+
+use crate::export::{export_video_ffmpeg_with_progress, VideoExportSettings};
+use crate::pipeline::{render_frames_with_progress, ProgressSink, RenderConfig, RenderStage};
+use crate::render::ShapeRenderer;
+use crate::scripting::SceneScript;
+use protocol::{
+    error_response, notification, response, RenderParams, Request, RpcError, INVALID_PARAMS,
+    METHOD_NOT_FOUND, SERVER_ERROR,
+};
+use serde_json::{json, Value};
+use std::error::Error;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::time::Duration;
+use tungstenite::Message;
+
+/// How long a connection waits for a client message before forwarding its
+/// jobs' notifications
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Pixel size glyphs are rasterized at
+const TEXT_ATLAS_SIZE: f32 = 48.0;
+
+/// Renders scene scripts sent by WebSocket clients
+pub struct RenderServer {
+    listener: TcpListener,
+    output_dir: PathBuf,
+}
+
+impl RenderServer {
+    /// Listen on `addr`, writing renders under `output_dir`
+    pub fn bind(
+        addr: impl ToSocketAddrs,
+        output_dir: impl Into<PathBuf>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let output_dir = output_dir.into();
+        std::fs::create_dir_all(&output_dir)?;
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            output_dir,
+        })
+    }
+
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serve connections until the listener fails
+    pub fn run(self) -> Result<(), Box<dyn Error>> {
+        let (queue, jobs) = RenderQueue::new();
+        let pending = Arc::clone(&queue.pending);
+        let output_dir = self.output_dir.clone();
+        std::thread::spawn(move || render_worker(&jobs, &output_dir, &pending));
+
+        for stream in self.listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    println!("Render server failed to accept a connection: {e}");
+                    continue;
+                }
+            };
+            let session = Session::new(queue.clone());
+            std::thread::spawn(move || {
+                if let Err(e) = serve_connection(stream, &session) {
+                    println!("Render server connection closed: {e}");
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+struct Job {
+    id: u64,
+    params: RenderParams,
+    /// Notifications for the connection that queued the job
+    events: Sender<Value>,
+}
+
+/// Hands jobs to the render worker
+#[derive(Clone)]
+struct RenderQueue {
+    jobs: Sender<Job>,
+    next_id: Arc<AtomicU64>,
+    /// Jobs queued or rendering
+    pending: Arc<AtomicUsize>,
+}
+
+impl RenderQueue {
+    fn new() -> (Self, Receiver<Job>) {
+        let (jobs, receiver) = mpsc::channel();
+        let queue = Self {
+            jobs,
+            next_id: Arc::new(AtomicU64::new(1)),
+            pending: Arc::new(AtomicUsize::new(0)),
+        };
+        (queue, receiver)
+    }
+
+    fn submit(&self, params: RenderParams, events: Sender<Value>) -> Result<u64, RpcError> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        self.jobs
+            .send(Job { id, params, events })
+            .map_err(|_| RpcError::new(SERVER_ERROR, "render worker stopped"))?;
+        Ok(id)
+    }
+}
+
+/// Render queued jobs one at a time, creating the renderer for the first
+fn render_worker(jobs: &Receiver<Job>, output_dir: &Path, pending: &AtomicUsize) {
+    let mut renderer = None;
+    for job in jobs {
+        let message = match render_job(&mut renderer, &job, output_dir) {
+            Ok(result) => notification("render.finished", result),
+            Err(e) => notification(
+                "render.failed",
+                json!({ "job": job.id, "message": e.to_string() }),
+            ),
+        };
+        pending.fetch_sub(1, Ordering::SeqCst);
+        // The client may have disconnected
+        let _ = job.events.send(message);
+    }
+}
+
+fn render_job(
+    renderer: &mut Option<ShapeRenderer>,
+    job: &Job,
+    output_dir: &Path,
+) -> Result<Value, Box<dyn Error>> {
+    let params = &job.params;
+    let built = SceneScript::new(&params.script)?.run()?;
+    let mut scene = built.scene;
+
+    let frames_dir = params
+        .frames_dir
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(format!("job_{}", job.id)));
+    let config = RenderConfig::new(
+        params.width,
+        params.height,
+        params.fps,
+        params.duration.unwrap_or(built.duration),
+    )
+    .with_theme(scene.theme())
+    .with_frames_dir(output_dir.join(&frames_dir));
+
+    let renderer = match renderer {
+        Some(renderer) => renderer,
+        None => renderer.insert(create_renderer(params.width, params.height)?),
+    };
+    let mut progress = JobProgress { job };
+    let stats = render_frames_with_progress(renderer, &mut scene, &config, &mut progress)?;
+
+    if let Some(video) = &params.video {
+        let settings = VideoExportSettings::new(
+            params.width,
+            params.height,
+            params.fps,
+            output_dir.join(video).to_string_lossy().into_owned(),
+            config.frame_pattern(),
+        );
+        export_video_ffmpeg_with_progress(&settings, &mut progress)?;
+    }
+
+    Ok(json!({
+        "job": job.id,
+        "frames_dir": frames_dir,
+        "video": params.video,
+        "frames_rendered": stats.frames_rendered,
+        "frames_cached": stats.frames_cached,
+    }))
+}
+
+/// Sends a job's progress to its client
+struct JobProgress<'a> {
+    job: &'a Job,
+}
+
+impl ProgressSink for JobProgress<'_> {
+    fn on_frame(&mut self, frame: u32, total: u32) {
+        let progress = json!({ "job": self.job.id, "frame": frame, "total": total });
+        let _ = self
+            .job
+            .events
+            .send(notification("render.progress", progress));
+    }
+
+    fn on_stage(&mut self, stage: RenderStage) {
+        let stage = json!({ "job": self.job.id, "stage": stage.to_string() });
+        let _ = self.job.events.send(notification("render.stage", stage));
+    }
+}
+
+fn create_renderer(width: u32, height: u32) -> Result<ShapeRenderer, Box<dyn Error>> {
+    let mut renderer = pollster::block_on(ShapeRenderer::new(width, height))?;
+    // Scenes without text still render when no font is installed
+    if let Err(e) = renderer.init_text_rendering(TEXT_ATLAS_SIZE) {
+        eprintln!("Render server: text rendering unavailable: {e}");
+    }
+    renderer.init_lighting();
+    Ok(renderer)
+}
+
+/// One client's connection: answers its calls and collects notifications
+/// from the jobs it queued
+struct Session {
+    queue: RenderQueue,
+    event_sender: Sender<Value>,
+    events: Receiver<Value>,
+}
+
+impl Session {
+    fn new(queue: RenderQueue) -> Self {
+        let (event_sender, events) = mpsc::channel();
+        Self {
+            queue,
+            event_sender,
+            events,
+        }
+    }
+
+    /// Handle a message from the client, returning the response (none for
+    /// notifications)
+    fn handle(&self, text: &str) -> Option<Value> {
+        let request = match Request::parse(text) {
+            Ok(request) => request,
+            Err(error) => return Some(error_response(Value::Null, &error)),
+        };
+        let result = self.call(&request.method, request.params);
+        let id = request.id?;
+        Some(match result {
+            Ok(result) => response(id, result),
+            Err(error) => error_response(id, &error),
+        })
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "ping" => Ok(json!("pong")),
+            "status" => Ok(json!({ "pending": self.queue.pending.load(Ordering::SeqCst) })),
+            "render" => {
+                let params: RenderParams = serde_json::from_value(params)
+                    .map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))?;
+                params
+                    .validate()
+                    .map_err(|e| RpcError::new(INVALID_PARAMS, e))?;
+                // Report syntax errors now rather than when the job runs
+                SceneScript::new(&params.script)
+                    .map_err(|e| RpcError::new(INVALID_PARAMS, format!("script: {e}")))?;
+                let job = self.queue.submit(params, self.event_sender.clone())?;
+                Ok(json!({ "job": job }))
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method `{method}`"),
+            )),
+        }
+    }
+}
+
+fn serve_connection(stream: TcpStream, session: &Session) -> Result<(), Box<dyn Error>> {
+    let mut socket = tungstenite::accept(stream)?;
+    // Reads time out so job notifications go out while the client is quiet
+    socket.get_ref().set_read_timeout(Some(POLL_INTERVAL))?;
+    loop {
+        while let Ok(event) = session.events.try_recv() {
+            socket.send(Message::text(event.to_string()))?;
+        }
+        match socket.read() {
+            Ok(Message::Text(text)) => {
+                if let Some(reply) = session.handle(&text) {
+                    socket.send(Message::text(reply.to_string()))?;
+                }
+            }
+            // Pings and closes are answered by tungstenite
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(e))
+                if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol::{INVALID_REQUEST, PARSE_ERROR};
+
+    fn call(session: &Session, request: &Value) -> Value {
+        session.handle(&request.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_session_answers_calls() {
+        let (queue, _jobs) = RenderQueue::new();
+        let session = Session::new(queue);
+
+        let pong = call(
+            &session,
+            &json!({"jsonrpc": "2.0", "id": 1, "method": "ping"}),
+        );
+        assert_eq!(pong, json!({"jsonrpc": "2.0", "id": 1, "result": "pong"}));
+
+        let unknown = call(
+            &session,
+            &json!({"jsonrpc": "2.0", "id": "a", "method": "nap"}),
+        );
+        assert_eq!(unknown["id"], "a");
+        assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+
+        assert_eq!(
+            session.handle("not json").unwrap()["error"]["code"],
+            PARSE_ERROR
+        );
+        assert_eq!(
+            call(&session, &json!({"id": 2, "method": "ping"}))["error"]["code"],
+            INVALID_REQUEST
+        );
+        // Notifications get no response
+        assert!(session
+            .handle(r#"{"jsonrpc": "2.0", "method": "ping"}"#)
+            .is_none());
+    }
+
+    #[test]
+    fn test_render_queues_jobs() {
+        let (queue, jobs) = RenderQueue::new();
+        let session = Session::new(queue);
+        let render = |params: Value| {
+            call(
+                &session,
+                &json!({"jsonrpc": "2.0", "id": 1, "method": "render", "params": params}),
+            )
+        };
+
+        let queued = render(json!({"script": "scene.theme(\"light\");", "width": 64}));
+        assert_eq!(queued["result"]["job"], 1);
+        let queued = render(json!({"script": "", "frames_dir": "shots/b"}));
+        assert_eq!(queued["result"]["job"], 2);
+        let status = call(
+            &session,
+            &json!({"jsonrpc": "2.0", "id": 3, "method": "status"}),
+        );
+        assert_eq!(status["result"]["pending"], 2);
+
+        let first = jobs.try_recv().unwrap();
+        assert_eq!((first.id, first.params.width), (1, 64));
+        first.events.send(json!("done")).unwrap();
+        assert_eq!(session.events.try_recv().unwrap(), json!("done"));
+        assert_eq!(jobs.try_recv().unwrap().id, 2);
+
+        for rejected in [
+            json!({"script": "let = ;"}),
+            json!({"script": "", "video": "../escape.mp4"}),
+            json!({"script": "", "height": 0}),
+            json!({"width": 64}),
+        ] {
+            assert_eq!(render(rejected)["error"]["code"], INVALID_PARAMS);
+        }
+        assert!(jobs.try_recv().is_err());
+    }
+}
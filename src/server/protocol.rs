@@ -0,0 +1,207 @@
+//! JSON-RPC 2.0 messages exchanged with render server clients
+
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::path::{Component, Path, PathBuf};
+
+/// The message isn't valid JSON
+pub const PARSE_ERROR: i64 = -32700;
+/// The message is JSON but not a JSON-RPC request
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+/// The server can't take requests (its render worker stopped)
+pub const SERVER_ERROR: i64 = -32000;
+
+/// An error returned to the client in place of a result
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// A call from a client; without an `id` it is a notification and gets no
+/// response
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    pub jsonrpc: String,
+    #[serde(default)]
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+impl Request {
+    pub fn parse(text: &str) -> Result<Self, RpcError> {
+        let value: Value =
+            serde_json::from_str(text).map_err(|e| RpcError::new(PARSE_ERROR, e.to_string()))?;
+        let request: Self = serde_json::from_value(value)
+            .map_err(|e| RpcError::new(INVALID_REQUEST, e.to_string()))?;
+        if request.jsonrpc != "2.0" {
+            return Err(RpcError::new(INVALID_REQUEST, "`jsonrpc` must be \"2.0\""));
+        }
+        Ok(request)
+    }
+}
+
+/// Parameters of the `render` method
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RenderParams {
+    /// Scene script source (see [`crate::scripting`])
+    pub script: String,
+    #[serde(default = "default_width")]
+    pub width: u32,
+    #[serde(default = "default_height")]
+    pub height: u32,
+    #[serde(default = "default_fps")]
+    pub fps: u32,
+    /// Seconds to render, instead of the script's duration
+    #[serde(default)]
+    pub duration: Option<f32>,
+    /// Frame directory, relative to the server's output directory
+    /// (default `job_<id>`)
+    #[serde(default)]
+    pub frames_dir: Option<PathBuf>,
+    /// Video to encode the frames into with ffmpeg, relative to the server's
+    /// output directory
+    #[serde(default)]
+    pub video: Option<PathBuf>,
+}
+
+fn default_width() -> u32 {
+    1920
+}
+
+fn default_height() -> u32 {
+    1080
+}
+
+fn default_fps() -> u32 {
+    30
+}
+
+impl RenderParams {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 || self.fps == 0 {
+            return Err("width, height and fps must be positive".to_string());
+        }
+        if let Some(duration) = self.duration {
+            if !duration.is_finite() || duration < 0.0 {
+                return Err(format!("invalid duration {duration}"));
+            }
+        }
+        for path in self.frames_dir.iter().chain(&self.video) {
+            if !is_contained(path) {
+                return Err(format!(
+                    "`{}` must be a relative path inside the output directory",
+                    path.display()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether `path` is relative and never steps up a directory
+fn is_contained(path: &Path) -> bool {
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+pub fn response(id: Value, result: Value) -> Value {
+    message([("id", id), ("result", result)])
+}
+
+/// Error response; `id` is null when the request couldn't be read
+pub fn error_response(id: Value, error: &RpcError) -> Value {
+    let error = json!({ "code": error.code, "message": error.message });
+    message([("id", id), ("error", error)])
+}
+
+/// A server-to-client message that expects no reply
+pub fn notification(method: &str, params: Value) -> Value {
+    message([("method", method.into()), ("params", params)])
+}
+
+fn message(fields: [(&str, Value); 2]) -> Value {
+    let mut message = Map::new();
+    message.insert("jsonrpc".to_string(), "2.0".into());
+    for (key, value) in fields {
+        message.insert(key.to_string(), value);
+    }
+    Value::Object(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_requests() {
+        let request = Request::parse(r#"{"jsonrpc": "2.0", "id": 7, "method": "ping"}"#).unwrap();
+        assert_eq!(request.id, Some(json!(7)));
+        assert_eq!(request.method, "ping");
+        assert!(request.params.is_null());
+
+        let notification = Request::parse(r#"{"jsonrpc": "2.0", "method": "ping"}"#).unwrap();
+        assert!(notification.id.is_none());
+
+        assert_eq!(Request::parse("{").unwrap_err().code, PARSE_ERROR);
+        assert_eq!(
+            Request::parse(r#"{"id": 1, "method": "ping"}"#)
+                .unwrap_err()
+                .code,
+            INVALID_REQUEST
+        );
+        assert_eq!(
+            Request::parse(r#"{"jsonrpc": "1.0", "id": 1, "method": "ping"}"#)
+                .unwrap_err()
+                .code,
+            INVALID_REQUEST
+        );
+    }
+
+    #[test]
+    fn test_render_params() {
+        let params: RenderParams = serde_json::from_value(json!({
+            "script": "scene.theme(\"light\");",
+            "width": 640,
+            "video": "out/intro.mp4",
+        }))
+        .unwrap();
+        assert_eq!((params.width, params.height, params.fps), (640, 1080, 30));
+        assert!(params.validate().is_ok());
+
+        let outside = |path: &str| RenderParams {
+            frames_dir: Some(PathBuf::from(path)),
+            ..params.clone()
+        };
+        assert!(outside("/tmp/frames").validate().is_err());
+        assert!(outside("../frames").validate().is_err());
+        assert!(outside("a/../../frames").validate().is_err());
+        assert!(outside("").validate().is_err());
+        assert!(outside("./jobs/a").validate().is_ok());
+
+        let still = RenderParams { fps: 0, ..params };
+        assert!(still.validate().is_err());
+
+        assert!(serde_json::from_value::<RenderParams>(json!({
+            "script": "",
+            "resolution": [640, 360],
+        }))
+        .is_err());
+    }
+}
@@ -0,0 +1,375 @@
+//! # Visual Regression Testing
+//!
+//! Renders scenes to pixel buffers and compares them against golden PNGs, so
+//! renderer changes that alter output are caught by `cargo test`.
+//!
+//! - **Snapshot**: an RGBA8 image, rendered from a scene or loaded from a PNG
+//! - **compare**: pixel and SSIM (structural similarity) differences between two snapshots
+//! - **Tolerance**: how different a render may be from its golden and still pass
+//! - **GoldenSet**: a directory of golden PNGs that snapshots are checked against
+//!
+//! Missing goldens are written on first use. Set `DIOMANIM_UPDATE_GOLDENS=1`
+//! to overwrite goldens after an intended output change; on a failed check
+//! the render is saved next to the golden as `<name>.actual.png`.
+//!
+//! ```rust,no_run
+//! use diomanim::core::Color;
+//! use diomanim::pipeline::RenderConfig;
+//! use diomanim::render::ShapeRenderer;
+//! use diomanim::scene::SceneGraph;
+//! use diomanim::testing::{GoldenSet, Snapshot, Tolerance};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut renderer = ShapeRenderer::new(128, 128).await?;
+//! let mut scene = SceneGraph::new();
+//! scene.add_circle("dot", 0.5, Color::RED);
+//! scene.update_transforms();
+//!
+//! let config = RenderConfig::new(128, 128, 30, 0.0);
+//! let snapshot = Snapshot::render(&mut renderer, &scene, &config)?;
+//! GoldenSet::new("tests/golden").check("red_dot", &snapshot, Tolerance::default())?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::pipeline::{render_frame, save_png, RenderConfig};
+use crate::render::ShapeRenderer;
+use crate::scene::SceneGraph;
+use std::path::{Path, PathBuf};
+
+/// Environment variable that makes [`GoldenSet::check`] overwrite goldens
+pub const UPDATE_GOLDENS_ENV: &str = "DIOMANIM_UPDATE_GOLDENS";
+
+/// An RGBA8 image with tightly packed rows
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Snapshot {
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Self {
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Render the current state of `scene` the way [`crate::pipeline::render_frames`] would
+    pub fn render(
+        renderer: &mut ShapeRenderer,
+        scene: &SceneGraph,
+        config: &RenderConfig,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let pixels = render_frame(renderer, scene, config)?;
+        Ok(Self::new(config.width, config.height, pixels))
+    }
+
+    /// Load an 8-bit RGB or RGBA PNG
+    pub fn load_png(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = std::fs::File::open(path)?;
+        let mut decoder = png::Decoder::new(std::io::BufReader::new(file));
+        decoder.set_transformations(png::Transformations::EXPAND);
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size().ok_or("PNG too large")?];
+        let info = reader.next_frame(&mut buffer)?;
+        buffer.truncate(info.buffer_size());
+
+        let pixels = match (info.color_type, info.bit_depth) {
+            (png::ColorType::Rgba, png::BitDepth::Eight) => buffer,
+            (png::ColorType::Rgb, png::BitDepth::Eight) => buffer
+                .chunks_exact(3)
+                .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+                .collect(),
+            (color, depth) => {
+                return Err(format!("unsupported PNG format {color:?} {depth:?}").into())
+            }
+        };
+        Ok(Self::new(info.width, info.height, pixels))
+    }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        save_png(path, self.width, self.height, &self.pixels)
+    }
+
+    /// Rec. 601 luma of every pixel, 0-255
+    fn luma(&self) -> Vec<f64> {
+        self.pixels
+            .chunks_exact(4)
+            .map(|p| 0.299 * f64::from(p[0]) + 0.587 * f64::from(p[1]) + 0.114 * f64::from(p[2]))
+            .collect()
+    }
+}
+
+/// How two snapshots differ
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageDiff {
+    /// Mean structural similarity of the luma, 1.0 for identical images
+    pub ssim: f64,
+    /// Pixels with any channel differing by more than the threshold
+    pub mismatched_pixels: usize,
+    pub total_pixels: usize,
+    /// Largest difference of any channel of any pixel
+    pub max_channel_diff: u8,
+}
+
+impl ImageDiff {
+    /// Fraction of pixels that differ beyond the channel threshold
+    pub fn mismatch_ratio(&self) -> f64 {
+        if self.total_pixels == 0 {
+            0.0
+        } else {
+            self.mismatched_pixels as f64 / self.total_pixels as f64
+        }
+    }
+
+    pub fn passes(&self, tolerance: Tolerance) -> bool {
+        self.ssim >= tolerance.min_ssim && self.mismatch_ratio() <= tolerance.max_mismatch_ratio
+    }
+}
+
+/// Allowed difference between a render and its golden
+///
+/// Small per-pixel differences are expected between GPUs and drivers
+/// (rasterization rules, blending precision), so exact matches are not required.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    /// Lowest acceptable [`ImageDiff::ssim`]
+    pub min_ssim: f64,
+    /// Channel difference (0-255) below which a pixel still counts as matching
+    pub channel_threshold: u8,
+    /// Highest acceptable [`ImageDiff::mismatch_ratio`]
+    pub max_mismatch_ratio: f64,
+}
+
+impl Tolerance {
+    /// Byte-identical output only
+    pub fn exact() -> Self {
+        Self {
+            min_ssim: 1.0,
+            channel_threshold: 0,
+            max_mismatch_ratio: 0.0,
+        }
+    }
+
+    /// For output that depends on the machine beyond the GPU, such as text
+    /// drawn with a system font
+    pub fn loose() -> Self {
+        Self {
+            min_ssim: 0.9,
+            channel_threshold: 32,
+            max_mismatch_ratio: 0.05,
+        }
+    }
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            min_ssim: 0.98,
+            channel_threshold: 8,
+            max_mismatch_ratio: 0.01,
+        }
+    }
+}
+
+/// Side length of the windows SSIM is computed over
+const SSIM_WINDOW: usize = 8;
+
+/// Compare two snapshots of the same size
+pub fn compare(
+    actual: &Snapshot,
+    expected: &Snapshot,
+    channel_threshold: u8,
+) -> Result<ImageDiff, String> {
+    if (actual.width, actual.height) != (expected.width, expected.height) {
+        return Err(format!(
+            "size mismatch: {}x{} vs {}x{}",
+            actual.width, actual.height, expected.width, expected.height
+        ));
+    }
+
+    let mut mismatched_pixels = 0;
+    let mut max_channel_diff = 0;
+    for (a, b) in actual
+        .pixels
+        .chunks_exact(4)
+        .zip(expected.pixels.chunks_exact(4))
+    {
+        let diff = a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| a.abs_diff(*b))
+            .max()
+            .unwrap_or(0);
+        max_channel_diff = max_channel_diff.max(diff);
+        if diff > channel_threshold {
+            mismatched_pixels += 1;
+        }
+    }
+
+    Ok(ImageDiff {
+        ssim: ssim(actual, expected),
+        mismatched_pixels,
+        total_pixels: actual.pixels.len() / 4,
+        max_channel_diff,
+    })
+}
+
+/// Mean SSIM of the luma over 8x8 windows (stride 4) of two equally sized snapshots
+fn ssim(a: &Snapshot, b: &Snapshot) -> f64 {
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+    let (width, height) = (a.width as usize, a.height as usize);
+    let (luma_a, luma_b) = (a.luma(), b.luma());
+    let window = SSIM_WINDOW.min(width).min(height);
+    if window == 0 {
+        return 1.0;
+    }
+    let stride = (window / 2).max(1);
+
+    let mut total = 0.0;
+    let mut windows = 0;
+    for y in (0..=height - window).step_by(stride) {
+        for x in (0..=width - window).step_by(stride) {
+            let samples =
+                (y..y + window).flat_map(|row| (x..x + window).map(move |col| row * width + col));
+            let n = (window * window) as f64;
+            let (mut sum_a, mut sum_b) = (0.0, 0.0);
+            for i in samples.clone() {
+                sum_a += luma_a[i];
+                sum_b += luma_b[i];
+            }
+            let (mean_a, mean_b) = (sum_a / n, sum_b / n);
+            let (mut var_a, mut var_b, mut covariance) = (0.0, 0.0, 0.0);
+            for i in samples {
+                let (da, db) = (luma_a[i] - mean_a, luma_b[i] - mean_b);
+                var_a += da * da;
+                var_b += db * db;
+                covariance += da * db;
+            }
+            let (var_a, var_b, covariance) = (var_a / n, var_b / n, covariance / n);
+
+            total += ((2.0 * mean_a * mean_b + C1) * (2.0 * covariance + C2))
+                / ((mean_a * mean_a + mean_b * mean_b + C1) * (var_a + var_b + C2));
+            windows += 1;
+        }
+    }
+    total / f64::from(windows)
+}
+
+/// A directory of golden PNGs
+#[derive(Debug, Clone)]
+pub struct GoldenSet {
+    dir: PathBuf,
+}
+
+impl GoldenSet {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn golden_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.png"))
+    }
+
+    /// Where a failing render of `name` is written
+    pub fn actual_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.actual.png"))
+    }
+
+    /// Compare `snapshot` with golden `name`
+    ///
+    /// Writes the golden instead when it does not exist yet or when
+    /// [`UPDATE_GOLDENS_ENV`] is set. On failure the render is saved to
+    /// [`Self::actual_path`] and the error describes the difference.
+    pub fn check(
+        &self,
+        name: &str,
+        snapshot: &Snapshot,
+        tolerance: Tolerance,
+    ) -> Result<ImageDiff, Box<dyn std::error::Error>> {
+        let golden_path = self.golden_path(name);
+        let update = std::env::var_os(UPDATE_GOLDENS_ENV).is_some_and(|value| value != "0");
+        if update || !golden_path.exists() {
+            std::fs::create_dir_all(&self.dir)?;
+            snapshot.save_png(&golden_path)?;
+            return compare(snapshot, snapshot, tolerance.channel_threshold).map_err(Into::into);
+        }
+
+        let golden = Snapshot::load_png(&golden_path)?;
+        let diff = compare(snapshot, &golden, tolerance.channel_threshold);
+        match diff {
+            Ok(diff) if diff.passes(tolerance) => {
+                // A stale failure image would be misleading
+                let _ = std::fs::remove_file(self.actual_path(name));
+                Ok(diff)
+            }
+            result => {
+                snapshot.save_png(self.actual_path(name))?;
+                let reason = match result {
+                    Ok(diff) => format!(
+                        "SSIM {:.4} (min {:.4}), {:.2}% pixels differ (max {:.2}%), max channel diff {}",
+                        diff.ssim,
+                        tolerance.min_ssim,
+                        diff.mismatch_ratio() * 100.0,
+                        tolerance.max_mismatch_ratio * 100.0,
+                        diff.max_channel_diff
+                    ),
+                    Err(message) => message,
+                };
+                Err(format!(
+                    "`{name}` does not match {}: {reason}; render saved to {}",
+                    golden_path.display(),
+                    self.actual_path(name).display()
+                )
+                .into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkerboard(size: u32, offset: u32) -> Snapshot {
+        let mut pixels = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                let value = if ((x + offset) / 4 + y / 4).is_multiple_of(2) {
+                    255
+                } else {
+                    0
+                };
+                pixels.extend_from_slice(&[value, value, value, 255]);
+            }
+        }
+        Snapshot::new(size, size, pixels)
+    }
+
+    #[test]
+    fn test_compare_detects_changes() {
+        let image = checkerboard(32, 0);
+        let same = compare(&image, &image, 0).unwrap();
+        assert!((same.ssim - 1.0).abs() < 1e-9);
+        assert_eq!(same.mismatched_pixels, 0);
+        assert!(same.passes(Tolerance::exact()));
+
+        // One slightly off pixel passes the default tolerance
+        let mut nudged = image.clone();
+        nudged.pixels[0] -= 3;
+        assert!(compare(&nudged, &image, 8)
+            .unwrap()
+            .passes(Tolerance::default()));
+
+        let shifted = compare(&checkerboard(32, 4), &image, 8).unwrap();
+        assert_eq!(shifted.mismatched_pixels, 32 * 32);
+        assert!(!shifted.passes(Tolerance::loose()));
+
+        assert!(compare(&checkerboard(16, 0), &image, 0).is_err());
+    }
+}
@@ -2,7 +2,10 @@
 //!
 //! Handles TrueType font loading and glyph metrics using ttf-parser.
 
-use std::sync::Arc;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Fallback chain set with [`SystemFonts::set_fallbacks`]
+static FALLBACKS: RwLock<Option<Vec<String>>> = RwLock::new(None);
 
 /// A loaded TrueType font
 pub struct Font {
@@ -111,6 +114,11 @@ impl SystemFonts {
         {
             "C:\\Windows\\Fonts\\arial.ttf"
         }
+        // Browsers have no font files; pages pass font data instead
+        #[cfg(target_arch = "wasm32")]
+        {
+            ""
+        }
     }
 
     /// Get path to a monospace font
@@ -127,6 +135,86 @@ impl SystemFonts {
         {
             "C:\\Windows\\Fonts\\consola.ttf"
         }
+        // Browsers have no font files; pages pass font data instead
+        #[cfg(target_arch = "wasm32")]
+        {
+            ""
+        }
+    }
+    /// Get path to a color emoji font (CBDT, sbix or COLR)
+    pub fn emoji() -> &'static str {
+        #[cfg(target_os = "macos")]
+        {
+            "/System/Library/Fonts/Apple Color Emoji.ttc"
+        }
+        #[cfg(target_os = "linux")]
+        {
+            "/usr/share/fonts/truetype/noto/NotoColorEmoji.ttf"
+        }
+        #[cfg(target_os = "windows")]
+        {
+            "C:\\Windows\\Fonts\\seguiemj.ttf"
+        }
+        // Browsers have no font files; pages pass font data instead
+        #[cfg(target_arch = "wasm32")]
+        {
+            ""
+        }
+    }
+
+    /// Fonts to try, in order, for characters the primary font lacks:
+    /// the list set with [`set_fallbacks`](Self::set_fallbacks), or else
+    /// the platform's math, CJK and emoji fonts
+    pub fn fallbacks() -> Vec<String> {
+        if let Some(fallbacks) = FALLBACKS
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+        {
+            return fallbacks.clone();
+        }
+
+        #[cfg(target_os = "macos")]
+        let defaults = [
+            "/System/Library/Fonts/Apple Symbols.ttf",
+            "/System/Library/Fonts/Supplemental/STIXTwoMath.otf",
+            "/System/Library/Fonts/PingFang.ttc",
+            "/System/Library/Fonts/Supplemental/Arial Unicode.ttf",
+        ];
+        #[cfg(target_os = "linux")]
+        let defaults = [
+            "/usr/share/fonts/truetype/dejavu/DejaVuMathTeXGyre.ttf",
+            "/usr/share/fonts/truetype/dejavu/DejaVuSerif.ttf",
+            "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+            "/usr/share/fonts/truetype/noto/NotoSansSymbols2-Regular.ttf",
+        ];
+        #[cfg(target_os = "windows")]
+        let defaults = [
+            "C:\\Windows\\Fonts\\cambria.ttc",
+            "C:\\Windows\\Fonts\\seguisym.ttf",
+            "C:\\Windows\\Fonts\\msyh.ttc",
+            "C:\\Windows\\Fonts\\malgun.ttf",
+        ];
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            defaults
+                .into_iter()
+                .chain([Self::emoji()])
+                .map(String::from)
+                .collect()
+        }
+        #[cfg(target_arch = "wasm32")]
+        Vec::new()
+    }
+
+    /// Replace the fallback chain used by atlases created afterwards
+    pub fn set_fallbacks<I, P>(paths: I)
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<String>,
+    {
+        *FALLBACKS.write().unwrap_or_else(PoisonError::into_inner) =
+            Some(paths.into_iter().map(Into::into).collect());
     }
 }
 
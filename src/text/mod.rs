@@ -6,8 +6,13 @@
 //! ## Features
 //! - TrueType font loading
 //! - Glyph rasterization with texture atlas
+//! - Shaping with rustybuzz: kerning, ligatures, right-to-left scripts
+//! - Per-glyph font fallback for math symbols, CJK and emoji
+//! - Color glyphs (COLR and CBDT/sbix emoji fonts)
 //! - Basic text rendering with color and size
 //! - Text positioning and alignment
+//! - Rich text: per-span color, weight and size, with inline math
+//! - Text laid out along a path
 //! - Future: LaTeX support
 //!
 //! ## Example
@@ -19,11 +24,17 @@
 //! ```
 
 pub mod font;
+pub mod on_path;
 pub mod rasterizer;
+pub mod rich;
+pub mod shaping;
 
 use crate::core::{Color, Vector3};
 pub use font::{Font, SystemFonts};
+pub use on_path::{PathGlyph, PathTextAlign, TextPath};
 pub use rasterizer::{GlyphAtlas, RasterizedGlyph};
+pub use rich::{RichText, SpanStyle, TextSpan};
+pub use shaping::{ShapedGlyph, ShapedText};
 
 /// Text mobject for rendering text in animations
 #[derive(Clone)]
@@ -0,0 +1,149 @@
+//! Text along a path
+//!
+//! Places each glyph of a shaped line of text on a [`Path`] by arc length and turns
+//! it to follow the path's direction, for circular labels and annotations
+//! that hug a curve:
+//!
+//! ```rust
+//! use diomanim::core::*;
+//! use diomanim::scene::SceneGraph;
+//! use diomanim::text::{PathTextAlign, TextPath};
+//!
+//! let rim = Path::arc(Vector3::zero(), 1.5, 0.0, std::f32::consts::PI);
+//! let mut scene = SceneGraph::new();
+//! scene.add_text_on_path(
+//!     "label",
+//!     "around the rim",
+//!     TextPath::new(rim).with_align(PathTextAlign::Center),
+//!     48.0,
+//!     Color::WHITE,
+//! );
+//! ```
+
+use crate::core::{Path, Vector3};
+
+/// Where the text sits along the path
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PathTextAlign {
+    /// Text starts at the beginning of the path
+    #[default]
+    Start,
+    /// Text is centered on the middle of the path
+    Center,
+    /// Text ends at the end of the path
+    End,
+}
+
+/// A path to lay text along
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextPath {
+    pub path: Path,
+    pub align: PathTextAlign,
+    /// Extra distance along the path to shift the text by
+    pub offset: f32,
+}
+
+/// One glyph placed on a path
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathGlyph {
+    /// Index of the glyph in the advances given to [`TextPath::layout`]
+    pub index: usize,
+    /// Point on the path under the middle of the glyph's baseline
+    pub position: Vector3,
+    /// Direction of the path there, in radians from the x axis
+    pub angle: f32,
+    pub advance: f32,
+}
+
+impl TextPath {
+    pub fn new(path: Path) -> Self {
+        Self {
+            path,
+            align: PathTextAlign::Start,
+            offset: 0.0,
+        }
+    }
+
+    pub fn with_align(mut self, align: PathTextAlign) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn with_offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Place glyphs with the given advances (in path units) one after
+    /// another. Glyphs past the ends of an open path are left out; closed
+    /// paths wrap around.
+    pub fn layout(&self, advances: &[f32]) -> Vec<PathGlyph> {
+        let sampler = self.path.sampler();
+        let length = sampler.length();
+        if length <= f32::EPSILON {
+            return Vec::new();
+        }
+
+        let width: f32 = advances.iter().sum();
+        let mut distance = self.offset
+            + match self.align {
+                PathTextAlign::Start => 0.0,
+                PathTextAlign::Center => (length - width) / 2.0,
+                PathTextAlign::End => length - width,
+            };
+
+        let mut glyphs = Vec::with_capacity(advances.len());
+        for (index, &advance) in advances.iter().enumerate() {
+            let mut middle = distance + advance / 2.0;
+            distance += advance;
+            if self.path.closed {
+                middle = middle.rem_euclid(length);
+            } else if !(0.0..=length).contains(&middle) {
+                continue;
+            }
+            let tangent = sampler.tangent_at(middle);
+            glyphs.push(PathGlyph {
+                index,
+                position: sampler.point_at(middle),
+                angle: tangent.y.atan2(tangent.x),
+                advance,
+            });
+        }
+        glyphs
+    }
+}
+
+impl From<Path> for TextPath {
+    fn from(path: Path) -> Self {
+        Self::new(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_text_path_layout() {
+        let line = Path::polyline(&[Vector3::zero(), Vector3::new(10.0, 0.0, 0.0)]);
+        let glyphs = TextPath::new(line.clone()).layout(&[1.0; 3]);
+        assert_eq!(glyphs[0].position, Vector3::new(0.5, 0.0, 0.0));
+        assert_eq!(glyphs[2].position, Vector3::new(2.5, 0.0, 0.0));
+        assert_eq!(glyphs[1].angle, 0.0);
+
+        let centered = TextPath::new(line.clone()).with_align(PathTextAlign::Center);
+        assert_eq!(centered.layout(&[1.0; 2])[0].position.x, 4.5);
+        // Glyphs that would hang off an open path are dropped
+        let shifted = TextPath::new(line).with_offset(8.8);
+        assert_eq!(shifted.layout(&[1.0; 3]).len(), 1);
+
+        // Going up the right side of a circle, glyphs turn to face up
+        let circle = Path::circle(Vector3::zero(), 1.0);
+        let glyphs = TextPath::new(circle).with_offset(-0.05).layout(&[0.05; 2]);
+        assert!((glyphs[0].angle - FRAC_PI_2).abs() < 0.05);
+        assert!((glyphs[0].position.x - 1.0).abs() < 0.01);
+        // The first glyph wrapped around to the end of the closed path
+        assert!(glyphs[0].position.y < 0.0 && glyphs[1].position.y > 0.0);
+    }
+}
@@ -1,9 +1,24 @@
 //! Glyph Rasterization and Texture Atlas
 //!
 //! Handles converting TrueType glyphs to GPU textures for rendering.
+//!
+//! Outline glyphs are stored as white with the glyph's coverage in alpha, so
+//! the text color tints them. Color glyphs (COLR layers, or CBDT/sbix
+//! embedded PNGs as used by emoji fonts) are stored in their own colors.
+//!
+//! Characters the primary font lacks are drawn from a chain of fallback
+//! fonts (see [`SystemFonts::fallbacks`](super::SystemFonts::fallbacks)),
+//! loaded the first time they're needed. Atlas entries are keyed by font
+//! index and glyph id, since glyph ids only mean something within one font.
 
-use ab_glyph::{Font as AbFont, FontRef, PxScale, ScaleFont};
+use super::shaping::{self, ShapedText};
+use ab_glyph::{
+    point, Font as AbFont, FontRef, GlyphId, Outline, OutlineCurve, OutlinedGlyph, Point, PxScale,
+    Rect, ScaleFont,
+};
 use std::collections::HashMap;
+use ttf_parser::colr::{ClipBox, CompositeMode, Paint, Painter};
+use ttf_parser::{RasterImageFormat, RgbaColor, Transform};
 
 /// A rasterized glyph with texture coordinates
 #[derive(Debug, Clone)]
@@ -20,20 +35,54 @@ pub struct RasterizedGlyph {
     pub advance: f32,
     /// UV coordinates in texture atlas (left, top, right, bottom)
     pub uv: (f32, f32, f32, f32),
-    /// Bitmap data (grayscale alpha)
+    /// Bitmap data (grayscale alpha, or RGBA for color glyphs)
     pub bitmap: Vec<u8>,
+    /// Drawn in its own colors instead of the text color
+    pub is_color: bool,
 }
 
-/// Texture atlas for caching rasterized glyphs
-pub struct GlyphAtlas {
-    /// Font data
-    font_data: Vec<u8>,
+/// A font loaded into the atlas
+struct LoadedFont {
     /// Parsed font
     font: FontRef<'static>,
+    /// The same font, for shaping and the color tables ab_glyph doesn't read
+    face: rustybuzz::Face<'static>,
+    /// Font data, borrowed by `font` and `face`
+    _data: Vec<u8>,
+}
+
+impl LoadedFont {
+    fn parse(data: Vec<u8>) -> Result<Self, Box<dyn std::error::Error>> {
+        let (font, face) = unsafe {
+            let data_ptr = data.as_ptr();
+            let data_slice = std::slice::from_raw_parts(data_ptr, data.len());
+            (
+                FontRef::try_from_slice(data_slice)?,
+                rustybuzz::Face::from_face(ttf_parser::Face::parse(data_slice, 0)?),
+            )
+        };
+        Ok(Self {
+            font,
+            face,
+            _data: data,
+        })
+    }
+
+    fn covers(&self, c: char) -> bool {
+        self.face.glyph_index(c).is_some()
+    }
+}
+
+/// Texture atlas for caching rasterized glyphs
+pub struct GlyphAtlas {
+    /// The primary font followed by the fallbacks loaded so far
+    fonts: Vec<LoadedFont>,
+    /// Fallback font files not loaded yet, in order
+    pending_fallbacks: Vec<String>,
     /// Font size
     font_size: f32,
-    /// Cache of rasterized glyphs (char -> glyph)
-    glyphs: HashMap<char, RasterizedGlyph>,
+    /// Cache of rasterized glyphs ((font index, glyph id) -> glyph)
+    glyphs: HashMap<(usize, u16), RasterizedGlyph>,
     /// Atlas texture width
     atlas_width: u32,
     /// Atlas texture height
@@ -52,11 +101,7 @@ impl GlyphAtlas {
     /// Create a new glyph atlas
     pub fn new(font_data: Vec<u8>, font_size: f32) -> Result<Self, Box<dyn std::error::Error>> {
         // Parse font
-        let font = unsafe {
-            let data_ptr = font_data.as_ptr();
-            let data_slice = std::slice::from_raw_parts(data_ptr, font_data.len());
-            FontRef::try_from_slice(data_slice)?
-        };
+        let font = LoadedFont::parse(font_data)?;
 
         // Create atlas (1024x1024 should be plenty for most use cases)
         let atlas_width = 1024;
@@ -64,8 +109,8 @@ impl GlyphAtlas {
         let atlas_data = vec![0u8; (atlas_width * atlas_height * 4) as usize]; // RGBA8
 
         Ok(Self {
-            font_data,
-            font,
+            fonts: vec![font],
+            pending_fallbacks: Vec::new(),
             font_size,
             glyphs: HashMap::new(),
             atlas_width,
@@ -77,11 +122,58 @@ impl GlyphAtlas {
         })
     }
 
-    /// Load from system font
+    /// Load from system font, with the system fallback chain
     pub fn from_system_font(font_size: f32) -> Result<Self, Box<dyn std::error::Error>> {
         let font_path = crate::text::font::SystemFonts::sans_serif();
         let font_data = std::fs::read(font_path)?;
-        Self::new(font_data, font_size)
+        Ok(Self::new(font_data, font_size)?
+            .with_fallback_paths(crate::text::font::SystemFonts::fallbacks()))
+    }
+
+    /// Queue font files to fall back to, in order, for characters the fonts
+    /// before them lack. Each is loaded when first needed; missing or
+    /// unreadable files are skipped.
+    pub fn with_fallback_paths<I, P>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<String>,
+    {
+        self.pending_fallbacks
+            .extend(paths.into_iter().map(Into::into));
+        self
+    }
+
+    /// Load a fallback font now, after any already loaded
+    pub fn add_fallback(&mut self, font_data: Vec<u8>) -> Result<(), Box<dyn std::error::Error>> {
+        self.fonts.push(LoadedFont::parse(font_data)?);
+        Ok(())
+    }
+
+    /// Number of fonts loaded, the primary font included
+    pub fn font_count(&self) -> usize {
+        self.fonts.len()
+    }
+
+    /// Index of the first font that has `c`, loading queued fallbacks until
+    /// one does. Falls back to the primary font (and its missing-glyph box).
+    pub fn font_for(&mut self, c: char) -> usize {
+        if let Some(index) = self.fonts.iter().position(|font| font.covers(c)) {
+            return index;
+        }
+        while !self.pending_fallbacks.is_empty() {
+            let path = self.pending_fallbacks.remove(0);
+            let Some(font) = std::fs::read(&path)
+                .ok()
+                .and_then(|data| LoadedFont::parse(data).ok())
+            else {
+                continue;
+            };
+            self.fonts.push(font);
+            if self.fonts[self.fonts.len() - 1].covers(c) {
+                return self.fonts.len() - 1;
+            }
+        }
+        0
     }
 
     /// Rasterize a character and add to atlas
@@ -89,38 +181,58 @@ impl GlyphAtlas {
         &mut self,
         c: char,
     ) -> Result<&RasterizedGlyph, Box<dyn std::error::Error>> {
+        let font = self.font_for(c);
+        self.rasterize_glyph(font, self.fonts[font].font.glyph_id(c).0)
+    }
+
+    /// Rasterize a glyph of the `font`th loaded font by its id (as produced
+    /// by shaping) and add it to the atlas
+    pub fn rasterize_glyph(
+        &mut self,
+        font: usize,
+        id: u16,
+    ) -> Result<&RasterizedGlyph, Box<dyn std::error::Error>> {
+        let key = (font, id);
         // Check if already cached
-        if self.glyphs.contains_key(&c) {
-            return Ok(&self.glyphs[&c]);
+        if self.glyphs.contains_key(&key) {
+            return Ok(&self.glyphs[&key]);
         }
+        let Some(loaded) = self.fonts.get(font) else {
+            return Err(format!("No font loaded at index {font}").into());
+        };
 
         // Get glyph
-        let glyph_id = self.font.glyph_id(c);
-        let scaled_font = self.font.as_scaled(PxScale::from(self.font_size));
+        let glyph_id = GlyphId(id);
+        let scaled_font = loaded.font.as_scaled(PxScale::from(self.font_size));
         let glyph = glyph_id
             .with_scale_and_position(PxScale::from(self.font_size), ab_glyph::point(0.0, 0.0));
 
         // Get glyph metrics
         let h_metrics = scaled_font.h_advance(glyph_id);
 
+        let outlined = scaled_font.outline_glyph(glyph);
+        if let Some(color) = self.color_bitmap(loaded, glyph_id) {
+            let uv = self.place(color.width, color.height, &color.pixels)?;
+            let rasterized = RasterizedGlyph {
+                width: color.width,
+                height: color.height,
+                bearing_x: color.left,
+                bearing_y: -color.top,
+                advance: h_metrics,
+                uv,
+                bitmap: color.pixels,
+                is_color: true,
+            };
+            self.glyphs.insert(key, rasterized);
+            return Ok(&self.glyphs[&key]);
+        }
+
         // Try to outline and rasterize
-        if let Some(outlined) = scaled_font.outline_glyph(glyph) {
+        if let Some(outlined) = outlined {
             let bounds = outlined.px_bounds();
             let width = bounds.width().ceil() as u32;
             let height = bounds.height().ceil() as u32;
 
-            // Check if we need a new row
-            if self.current_x + width > self.atlas_width {
-                self.current_x = 0;
-                self.current_y += self.row_height;
-                self.row_height = 0;
-            }
-
-            // Check if atlas is full
-            if self.current_y + height > self.atlas_height {
-                return Err("Glyph atlas is full".into());
-            }
-
             // Rasterize glyph
             let mut bitmap = vec![0u8; (width * height) as usize];
             outlined.draw(|x, y, v| {
@@ -130,32 +242,12 @@ impl GlyphAtlas {
                 }
             });
 
-            // Copy to atlas
-            for y in 0..height {
-                for x in 0..width {
-                    let atlas_x = self.current_x + x;
-                    let atlas_y = self.current_y + y;
-                    let atlas_idx = ((atlas_y * self.atlas_width + atlas_x) * 4) as usize;
-                    let bitmap_idx = (y * width + x) as usize;
-
-                    if atlas_idx + 3 < self.atlas_data.len() && bitmap_idx < bitmap.len() {
-                        let alpha = bitmap[bitmap_idx];
-                        // White color with alpha from glyph
-                        self.atlas_data[atlas_idx] = 255; // R
-                        self.atlas_data[atlas_idx + 1] = 255; // G
-                        self.atlas_data[atlas_idx + 2] = 255; // B
-                        self.atlas_data[atlas_idx + 3] = alpha; // A
-                    }
-                }
-            }
-
-            // Calculate UV coordinates
-            let uv = (
-                self.current_x as f32 / self.atlas_width as f32,
-                self.current_y as f32 / self.atlas_height as f32,
-                (self.current_x + width) as f32 / self.atlas_width as f32,
-                (self.current_y + height) as f32 / self.atlas_height as f32,
-            );
+            // White color with alpha from glyph
+            let rgba: Vec<u8> = bitmap
+                .iter()
+                .flat_map(|&alpha| [255, 255, 255, alpha])
+                .collect();
+            let uv = self.place(width, height, &rgba)?;
 
             // Create rasterized glyph
             let rasterized = RasterizedGlyph {
@@ -166,15 +258,12 @@ impl GlyphAtlas {
                 advance: h_metrics,
                 uv,
                 bitmap,
+                is_color: false,
             };
 
-            // Update atlas position
-            self.current_x += width;
-            self.row_height = self.row_height.max(height);
-
             // Cache and return
-            self.glyphs.insert(c, rasterized);
-            Ok(&self.glyphs[&c])
+            self.glyphs.insert(key, rasterized);
+            Ok(&self.glyphs[&key])
         } else {
             // Glyph has no outline (e.g., space), create empty glyph
             let rasterized = RasterizedGlyph {
@@ -185,11 +274,176 @@ impl GlyphAtlas {
                 advance: h_metrics,
                 uv: (0.0, 0.0, 0.0, 0.0),
                 bitmap: Vec::new(),
+                is_color: false,
             };
 
-            self.glyphs.insert(c, rasterized);
-            Ok(&self.glyphs[&c])
+            self.glyphs.insert(key, rasterized);
+            Ok(&self.glyphs[&key])
+        }
+    }
+
+    /// Copy an RGBA bitmap into the next free atlas slot and return its UVs
+    fn place(
+        &mut self,
+        width: u32,
+        height: u32,
+        rgba: &[u8],
+    ) -> Result<(f32, f32, f32, f32), Box<dyn std::error::Error>> {
+        // Check if we need a new row
+        if self.current_x + width > self.atlas_width {
+            self.current_x = 0;
+            self.current_y += self.row_height;
+            self.row_height = 0;
+        }
+
+        // Check if atlas is full
+        if self.current_y + height > self.atlas_height {
+            return Err("Glyph atlas is full".into());
         }
+
+        // Copy to atlas
+        for y in 0..height {
+            let row = (y * width * 4) as usize..((y + 1) * width * 4) as usize;
+            let atlas_idx =
+                (((self.current_y + y) * self.atlas_width + self.current_x) * 4) as usize;
+            if let (Some(src), Some(dst)) = (
+                rgba.get(row.clone()),
+                self.atlas_data.get_mut(atlas_idx..atlas_idx + row.len()),
+            ) {
+                dst.copy_from_slice(src);
+            }
+        }
+
+        // Calculate UV coordinates
+        let uv = (
+            self.current_x as f32 / self.atlas_width as f32,
+            self.current_y as f32 / self.atlas_height as f32,
+            (self.current_x + width) as f32 / self.atlas_width as f32,
+            (self.current_y + height) as f32 / self.atlas_height as f32,
+        );
+
+        // Update atlas position
+        self.current_x += width;
+        self.row_height = self.row_height.max(height);
+        Ok(uv)
+    }
+
+    /// Draw `glyph_id` in color if the font has color data for it
+    fn color_bitmap(&self, loaded: &LoadedFont, glyph_id: GlyphId) -> Option<ColorBitmap> {
+        let id = ttf_parser::GlyphId(glyph_id.0);
+        if loaded.face.is_color_glyph(id) {
+            return self.paint_color_layers(loaded, id);
+        }
+
+        // Bitmap fonts pick the strike closest to the requested size
+        let image = loaded
+            .face
+            .glyph_raster_image(id, self.font_size.round() as u16)?;
+        let (width, height, pixels) = match image.format {
+            RasterImageFormat::PNG => decode_png(image.data).ok()?,
+            RasterImageFormat::BitmapPremulBgra32 => {
+                let pixels = image
+                    .data
+                    .chunks_exact(4)
+                    .flat_map(|bgra| {
+                        let unpremultiply = |c: u8| {
+                            if bgra[3] == 0 {
+                                0
+                            } else {
+                                (u32::from(c) * 255 / u32::from(bgra[3])).min(255) as u8
+                            }
+                        };
+                        [
+                            unpremultiply(bgra[2]),
+                            unpremultiply(bgra[1]),
+                            unpremultiply(bgra[0]),
+                            bgra[3],
+                        ]
+                    })
+                    .collect();
+                (u32::from(image.width), u32::from(image.height), pixels)
+            }
+            _ => return None,
+        };
+
+        let scale = self.font_size / f32::from(image.pixels_per_em.max(1));
+        let scaled_width = ((width as f32 * scale).round() as u32).max(1);
+        let scaled_height = ((height as f32 * scale).round() as u32).max(1);
+        Some(ColorBitmap {
+            width: scaled_width,
+            height: scaled_height,
+            left: f32::from(image.x) * scale,
+            // `y` is the bottom of the image, measured up from the baseline
+            top: -(f32::from(image.y) + height as f32) * scale,
+            pixels: resample_rgba(&pixels, (width, height), (scaled_width, scaled_height)),
+        })
+    }
+
+    /// Composite the COLR layers of `id` into one bitmap
+    fn paint_color_layers(
+        &self,
+        loaded: &LoadedFont,
+        id: ttf_parser::GlyphId,
+    ) -> Option<ColorBitmap> {
+        let mut collector = LayerCollector::default();
+        // Layers in the foreground color are left white for the text color to tint
+        let foreground = RgbaColor::new(255, 255, 255, 255);
+        loaded
+            .face
+            .paint_color_glyph(id, 0, foreground, &mut collector)?;
+
+        let scaled_font = loaded.font.as_scaled(PxScale::from(self.font_size));
+        let layers: Vec<(OutlinedGlyph, RgbaColor)> = collector
+            .layers
+            .into_iter()
+            .filter_map(|layer| {
+                let outline = transform_outline(
+                    loaded.font.outline(GlyphId(layer.glyph.0))?,
+                    &layer.transform,
+                );
+                let glyph = GlyphId(layer.glyph.0)
+                    .with_scale_and_position(PxScale::from(self.font_size), point(0.0, 0.0));
+                Some((
+                    OutlinedGlyph::new(glyph, outline, scaled_font.scale_factor()),
+                    layer.color,
+                ))
+            })
+            .collect();
+
+        let bounds = layers
+            .iter()
+            .map(|(outlined, _)| outlined.px_bounds())
+            .reduce(|a, b| Rect {
+                min: point(a.min.x.min(b.min.x), a.min.y.min(b.min.y)),
+                max: point(a.max.x.max(b.max.x), a.max.y.max(b.max.y)),
+            })?;
+        let width = bounds.width().ceil() as u32;
+        let height = bounds.height().ceil() as u32;
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let mut pixels = vec![0u8; (width * height * 4) as usize];
+        for (outlined, color) in &layers {
+            let layer_bounds = outlined.px_bounds();
+            let offset_x = (layer_bounds.min.x - bounds.min.x) as u32;
+            let offset_y = (layer_bounds.min.y - bounds.min.y) as u32;
+            outlined.draw(|x, y, coverage| {
+                let (x, y) = (x + offset_x, y + offset_y);
+                if x < width && y < height {
+                    let idx = ((y * width + x) * 4) as usize;
+                    blend_over(&mut pixels[idx..idx + 4], *color, coverage);
+                }
+            });
+        }
+
+        Some(ColorBitmap {
+            width,
+            height,
+            left: bounds.min.x,
+            top: bounds.min.y,
+            pixels,
+        })
     }
 
     /// Rasterize all characters in a string
@@ -205,23 +459,302 @@ impl GlyphAtlas {
         &self.atlas_data
     }
 
+    /// Pixel size glyphs are rasterized at
+    pub fn font_size(&self) -> f32 {
+        self.font_size
+    }
+
     /// Get atlas dimensions
     pub fn atlas_dimensions(&self) -> (u32, u32) {
         (self.atlas_width, self.atlas_height)
     }
 
-    /// Get a cached glyph
+    /// Get a cached glyph, from the first loaded font that has `c`
     pub fn get_glyph(&self, c: char) -> Option<&RasterizedGlyph> {
-        self.glyphs.get(&c)
+        let font = self.fonts.iter().position(|font| font.covers(c))?;
+        self.glyph(font, self.fonts[font].font.glyph_id(c).0)
     }
 
-    /// Measure the width of a string
-    pub fn measure_text(&mut self, text: &str) -> Result<f32, Box<dyn std::error::Error>> {
-        let mut width = 0.0;
+    /// Get a cached glyph by font index and its id in that font
+    pub fn glyph(&self, font: usize, id: u16) -> Option<&RasterizedGlyph> {
+        self.glyphs.get(&(font, id))
+    }
+
+    /// Shape `text` at the atlas's font size and rasterize the glyphs it
+    /// uses, taking characters the primary font lacks from the fallbacks
+    pub fn shape(&mut self, text: &str) -> Result<ShapedText, Box<dyn std::error::Error>> {
         for c in text.chars() {
-            let glyph = self.rasterize_char(c)?;
-            width += glyph.advance;
+            self.font_for(c);
+        }
+        let faces: Vec<&rustybuzz::Face> = self.fonts.iter().map(|font| &font.face).collect();
+        let shaped = shaping::shape(&faces, text, self.font_size);
+        for glyph in &shaped.glyphs {
+            self.rasterize_glyph(glyph.font, glyph.glyph_id)?;
+        }
+        Ok(shaped)
+    }
+
+    /// Measure the width of a string, with kerning
+    pub fn measure_text(&mut self, text: &str) -> Result<f32, Box<dyn std::error::Error>> {
+        Ok(self.shape(text)?.width)
+    }
+}
+
+/// A glyph drawn in its own colors
+struct ColorBitmap {
+    width: u32,
+    height: u32,
+    /// Offset of the left edge from the pen position, in pixels
+    left: f32,
+    /// Offset of the top edge from the baseline, in pixels (negative is up)
+    top: f32,
+    /// RGBA8 with straight alpha
+    pixels: Vec<u8>,
+}
+
+/// One filled shape of a COLR glyph
+struct ColorLayer {
+    glyph: ttf_parser::GlyphId,
+    /// Font-unit transform applied to the glyph's outline
+    transform: Transform,
+    color: RgbaColor,
+}
+
+/// Flattens a COLR paint graph into filled layers. Gradients are drawn in
+/// the average of their stop colors, and clip boxes and blend modes are
+/// ignored; at text sizes that reads the same for emoji.
+#[derive(Default)]
+struct LayerCollector {
+    outline: Option<ttf_parser::GlyphId>,
+    /// Glyph clips in effect; box clips push `None`
+    clips: Vec<Option<ttf_parser::GlyphId>>,
+    transforms: Vec<Transform>,
+    layers: Vec<ColorLayer>,
+}
+
+impl<'a> Painter<'a> for LayerCollector {
+    fn outline_glyph(&mut self, glyph_id: ttf_parser::GlyphId) {
+        self.outline = Some(glyph_id);
+    }
+
+    fn paint(&mut self, paint: Paint<'a>) {
+        // The innermost glyph clip is the shape being filled
+        let Some(glyph) = self
+            .clips
+            .iter()
+            .rev()
+            .find_map(|clip| *clip)
+            .or(self.outline)
+        else {
+            return;
+        };
+        let color = match paint {
+            Paint::Solid(color) => color,
+            Paint::LinearGradient(gradient) => average_color(gradient.stops(0, &[])),
+            Paint::RadialGradient(gradient) => average_color(gradient.stops(0, &[])),
+            Paint::SweepGradient(gradient) => average_color(gradient.stops(0, &[])),
+        };
+        self.layers.push(ColorLayer {
+            glyph,
+            transform: self.transforms.last().copied().unwrap_or_default(),
+            color,
+        });
+    }
+
+    fn push_clip(&mut self) {
+        self.clips.push(self.outline);
+    }
+
+    fn push_clip_box(&mut self, _clipbox: ClipBox) {
+        self.clips.push(None);
+    }
+
+    fn pop_clip(&mut self) {
+        self.clips.pop();
+    }
+
+    fn push_layer(&mut self, _mode: CompositeMode) {}
+
+    fn pop_layer(&mut self) {}
+
+    fn push_transform(&mut self, transform: Transform) {
+        let current = self.transforms.last().copied().unwrap_or_default();
+        self.transforms.push(Transform::combine(current, transform));
+    }
+
+    fn pop_transform(&mut self) {
+        self.transforms.pop();
+    }
+}
+
+fn average_color(stops: impl Iterator<Item = ttf_parser::colr::ColorStop>) -> RgbaColor {
+    let (mut sum, mut count) = ([0u32; 4], 0u32);
+    for stop in stops {
+        let c = stop.color;
+        for (total, channel) in sum.iter_mut().zip([c.red, c.green, c.blue, c.alpha]) {
+            *total += u32::from(channel);
+        }
+        count += 1;
+    }
+    let [r, g, b, a] = sum.map(|total| (total / count.max(1)) as u8);
+    RgbaColor::new(r, g, b, a)
+}
+
+/// Apply a font-unit transform to every point of an outline
+fn transform_outline(outline: Outline, transform: &Transform) -> Outline {
+    if transform.is_default() {
+        return outline;
+    }
+    let map = |p: Point| {
+        point(
+            transform.a * p.x + transform.c * p.y + transform.e,
+            transform.b * p.x + transform.d * p.y + transform.f,
+        )
+    };
+    let curves: Vec<OutlineCurve> = outline
+        .curves
+        .into_iter()
+        .map(|curve| match curve {
+            OutlineCurve::Line(a, b) => OutlineCurve::Line(map(a), map(b)),
+            OutlineCurve::Quad(a, b, c) => OutlineCurve::Quad(map(a), map(b), map(c)),
+            OutlineCurve::Cubic(a, b, c, d) => OutlineCurve::Cubic(map(a), map(b), map(c), map(d)),
+        })
+        .collect();
+
+    let mut bounds = Rect {
+        min: point(f32::MAX, f32::MAX),
+        max: point(f32::MIN, f32::MIN),
+    };
+    for curve in &curves {
+        let points: &[Point] = match curve {
+            OutlineCurve::Line(a, b) => &[*a, *b],
+            OutlineCurve::Quad(a, b, c) => &[*a, *b, *c],
+            OutlineCurve::Cubic(a, b, c, d) => &[*a, *b, *c, *d],
+        };
+        for p in points {
+            bounds.min = point(bounds.min.x.min(p.x), bounds.min.y.min(p.y));
+            bounds.max = point(bounds.max.x.max(p.x), bounds.max.y.max(p.y));
+        }
+    }
+    Outline { bounds, curves }
+}
+
+/// Source-over blend of `color` at `coverage` onto a straight-alpha pixel
+fn blend_over(pixel: &mut [u8], color: RgbaColor, coverage: f32) {
+    let src_a = f32::from(color.alpha) / 255.0 * coverage.clamp(0.0, 1.0);
+    let dst_a = f32::from(pixel[3]) / 255.0;
+    let out_a = src_a + dst_a * (1.0 - src_a);
+    if out_a <= 0.0 {
+        return;
+    }
+    for (channel, src) in pixel.iter_mut().zip([color.red, color.green, color.blue]) {
+        let mixed = (f32::from(src) * src_a + f32::from(*channel) * dst_a * (1.0 - src_a)) / out_a;
+        *channel = mixed.round() as u8;
+    }
+    pixel[3] = (out_a * 255.0).round() as u8;
+}
+
+/// Box-filter an RGBA image to a new size, averaging in premultiplied alpha
+/// so transparent pixels don't darken edges
+fn resample_rgba(pixels: &[u8], from: (u32, u32), to: (u32, u32)) -> Vec<u8> {
+    if from == to {
+        return pixels.to_vec();
+    }
+    let (sx, sy) = (from.0 as f32 / to.0 as f32, from.1 as f32 / to.1 as f32);
+    let mut out = Vec::with_capacity((to.0 * to.1 * 4) as usize);
+    for y in 0..to.1 {
+        let y0 = (y as f32 * sy) as u32;
+        let y1 = (((y + 1) as f32 * sy).ceil() as u32).clamp(y0 + 1, from.1);
+        for x in 0..to.0 {
+            let x0 = (x as f32 * sx) as u32;
+            let x1 = (((x + 1) as f32 * sx).ceil() as u32).clamp(x0 + 1, from.0);
+            let mut sum = [0.0f32; 4];
+            for py in y0..y1 {
+                for px in x0..x1 {
+                    let idx = ((py * from.0 + px) * 4) as usize;
+                    let alpha = f32::from(pixels[idx + 3]);
+                    for channel in 0..3 {
+                        sum[channel] += f32::from(pixels[idx + channel]) * alpha;
+                    }
+                    sum[3] += alpha;
+                }
+            }
+            let count = ((y1 - y0) * (x1 - x0)) as f32;
+            for channel in 0..3 {
+                out.push(if sum[3] > 0.0 {
+                    (sum[channel] / sum[3]).round() as u8
+                } else {
+                    0
+                });
+            }
+            out.push((sum[3] / count).round() as u8);
         }
-        Ok(width)
+    }
+    out
+}
+
+/// Decode a PNG glyph image to RGBA8
+fn decode_png(data: &[u8]) -> Result<(u32, u32, Vec<u8>), Box<dyn std::error::Error>> {
+    let mut decoder = png::Decoder::new(std::io::Cursor::new(data));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size().ok_or("PNG too large")?];
+    let info = reader.next_frame(&mut buffer)?;
+    buffer.truncate(info.buffer_size());
+
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => buffer,
+        png::ColorType::Rgb => buffer
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buffer
+            .chunks_exact(2)
+            .flat_map(|ga| [ga[0], ga[0], ga[0], ga[1]])
+            .collect(),
+        png::ColorType::Grayscale => buffer.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        png::ColorType::Indexed => return Err("unexpanded indexed PNG".into()),
+    };
+    Ok((info.width, info.height, pixels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_bitmap_helpers() {
+        // A 2x2 image: opaque red on the left, transparent on the right
+        let image = [255, 0, 0, 255, 0, 0, 0, 0, 255, 0, 0, 255, 0, 0, 0, 0];
+        let halved = resample_rgba(&image, (2, 2), (1, 1));
+        // Transparent pixels don't pull the color towards black
+        assert_eq!(halved, [255, 0, 0, 128]);
+
+        let mut pixel = [0, 0, 255, 255];
+        blend_over(&mut pixel, RgbaColor::new(255, 0, 0, 255), 0.5);
+        assert_eq!(pixel, [128, 0, 128, 255]);
+
+        let mut png_data = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut png_data, 2, 2);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(&image).unwrap();
+        }
+        assert_eq!(decode_png(&png_data).unwrap(), (2, 2, image.to_vec()));
+
+        let square = Transform::new_scale(2.0, 1.0);
+        let outline = Outline {
+            bounds: Rect {
+                min: point(0.0, 0.0),
+                max: point(1.0, 1.0),
+            },
+            curves: vec![OutlineCurve::Line(point(0.0, 0.0), point(1.0, 1.0))],
+        };
+        assert_eq!(
+            transform_outline(outline, &square).bounds.max,
+            point(2.0, 1.0)
+        );
     }
 }
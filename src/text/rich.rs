@@ -0,0 +1,337 @@
+//! Rich text
+//!
+//! A [`RichText`] is a line of text made of styled spans, so single words
+//! can be colored, bold or resized, with inline math laid out by the math
+//! engine. Build it from markup:
+//!
+//! ```rust
+//! use diomanim::text::RichText;
+//!
+//! let label = RichText::parse("Energy is [b]conserved[/b]: $E = mc^2$ [color=#ff0]always[/color]").unwrap();
+//! assert_eq!(label.plain_text(), "Energy is conserved: E = mc^2 always");
+//! ```
+//!
+//! or span by span:
+//!
+//! ```rust
+//! use diomanim::core::Color;
+//! use diomanim::text::RichText;
+//!
+//! let label = RichText::new()
+//!     .text("area ")
+//!     .colored("grows", Color::YELLOW)
+//!     .text(" as ")
+//!     .math("\\pi r^2");
+//! ```
+//!
+//! Markup tags are `[color=#rgb]` / `[color=#rrggbb]`, `[b]` and
+//! `[size=1.5]` (relative to the base size), each closed by `[/tag]` and
+//! freely nested. `$...$` is inline math; `\[`, `\$` and `\\` escape.
+
+use crate::core::{Color, Vector3};
+use crate::math::{expression::parse_latex, layout::MathLayout};
+
+/// Horizontal offset of the second pass of synthetic bold, as a fraction of
+/// the font size
+pub const BOLD_OFFSET: f32 = 0.04;
+
+/// How a span differs from the text's base style
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpanStyle {
+    /// Overrides the base color
+    pub color: Option<Color>,
+    pub bold: bool,
+    /// Multiplies the base font size
+    pub scale: f32,
+}
+
+impl Default for SpanStyle {
+    fn default() -> Self {
+        Self {
+            color: None,
+            bold: false,
+            scale: 1.0,
+        }
+    }
+}
+
+/// A run of text in one style
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextSpan {
+    /// Plain text, or LaTeX source when `math` is set
+    pub text: String,
+    pub style: SpanStyle,
+    /// Lay the span out as inline math
+    pub math: bool,
+}
+
+/// A glyph run placed by [`RichText::layout`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacedRun {
+    pub text: String,
+    /// Baseline origin, in pixels at the layout's font size (y grows down)
+    pub x: f32,
+    pub y: f32,
+    /// Size relative to the layout's font size
+    pub scale: f32,
+    pub color: Option<Color>,
+    pub bold: bool,
+}
+
+/// A line of styled spans
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RichText {
+    pub spans: Vec<TextSpan>,
+}
+
+impl RichText {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a span with `style`
+    pub fn span(mut self, text: impl Into<String>, style: SpanStyle) -> Self {
+        self.spans.push(TextSpan {
+            text: text.into(),
+            style,
+            math: false,
+        });
+        self
+    }
+
+    /// Append text in the base style
+    pub fn text(self, text: impl Into<String>) -> Self {
+        self.span(text, SpanStyle::default())
+    }
+
+    pub fn colored(self, text: impl Into<String>, color: Color) -> Self {
+        self.span(
+            text,
+            SpanStyle {
+                color: Some(color),
+                ..SpanStyle::default()
+            },
+        )
+    }
+
+    pub fn bold(self, text: impl Into<String>) -> Self {
+        self.span(
+            text,
+            SpanStyle {
+                bold: true,
+                ..SpanStyle::default()
+            },
+        )
+    }
+
+    /// Append text at `scale` times the base size
+    pub fn sized(self, text: impl Into<String>, scale: f32) -> Self {
+        self.span(
+            text,
+            SpanStyle {
+                scale,
+                ..SpanStyle::default()
+            },
+        )
+    }
+
+    /// Append inline math from LaTeX source
+    pub fn math(mut self, latex: impl Into<String>) -> Self {
+        self.spans.push(TextSpan {
+            text: latex.into(),
+            style: SpanStyle::default(),
+            math: true,
+        });
+        self
+    }
+
+    /// Parse span markup (see the module docs)
+    pub fn parse(markup: &str) -> Result<Self, String> {
+        let mut rich = Self::new();
+        // Open tags with the style in effect inside them
+        let mut stack: Vec<(String, SpanStyle)> = Vec::new();
+        let mut current = String::new();
+        let mut in_math = false;
+        let mut chars = markup.chars().peekable();
+
+        let style_of = |stack: &[(String, SpanStyle)]| {
+            stack.last().map_or_else(SpanStyle::default, |(_, s)| *s)
+        };
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' if !in_math => match chars.next() {
+                    Some(escaped) => current.push(escaped),
+                    None => current.push('\\'),
+                },
+                '$' => {
+                    let style = style_of(&stack);
+                    if !current.is_empty() {
+                        rich.spans.push(TextSpan {
+                            text: std::mem::take(&mut current),
+                            style,
+                            math: in_math,
+                        });
+                    }
+                    in_math = !in_math;
+                }
+                '[' if !in_math => {
+                    let mut tag = String::new();
+                    loop {
+                        match chars.next() {
+                            Some(']') => break,
+                            Some(c) => tag.push(c),
+                            None => return Err(format!("unterminated tag '[{tag}'")),
+                        }
+                    }
+
+                    let style = style_of(&stack);
+                    if !current.is_empty() {
+                        rich = rich.span(std::mem::take(&mut current), style);
+                    }
+
+                    if let Some(name) = tag.strip_prefix('/') {
+                        match stack.pop() {
+                            Some((open, _)) if open == name => {}
+                            Some((open, _)) => {
+                                return Err(format!("[/{name}] closes [{open}]"));
+                            }
+                            None => return Err(format!("[/{name}] was never opened")),
+                        }
+                    } else {
+                        let (name, value) = match tag.split_once('=') {
+                            Some((name, value)) => (name, Some(value)),
+                            None => (tag.as_str(), None),
+                        };
+                        let mut style = style;
+                        match (name, value) {
+                            ("b", None) => style.bold = true,
+                            ("color", Some(value)) => style.color = Some(parse_color(value)?),
+                            ("size", Some(value)) => {
+                                style.scale *= value
+                                    .parse::<f32>()
+                                    .ok()
+                                    .filter(|s| *s > 0.0)
+                                    .ok_or_else(|| format!("invalid size '{value}'"))?;
+                            }
+                            _ => return Err(format!("unknown tag '[{tag}]'")),
+                        }
+                        stack.push((name.to_string(), style));
+                    }
+                }
+                c => current.push(c),
+            }
+        }
+
+        if in_math {
+            return Err("unterminated '$'".to_string());
+        }
+        if let Some((open, _)) = stack.last() {
+            return Err(format!("[{open}] is never closed"));
+        }
+        if !current.is_empty() {
+            rich = rich.text(current);
+        }
+        Ok(rich)
+    }
+
+    /// The text without styling, with math as its LaTeX source
+    pub fn plain_text(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    /// Place the spans along a baseline at `font_size` pixels, measuring
+    /// text with `measure` (pixels at `font_size`)
+    pub fn layout(&self, font_size: f32, mut measure: impl FnMut(&str) -> f32) -> Vec<PlacedRun> {
+        let mut runs = Vec::new();
+        let mut cursor = 0.0;
+
+        for span in &self.spans {
+            let style = span.style;
+            let bold_extra = if style.bold {
+                BOLD_OFFSET * font_size * style.scale
+            } else {
+                0.0
+            };
+
+            if span.math {
+                let layout =
+                    MathLayout::layout_node(&parse_latex(&span.text), font_size * style.scale);
+                for (position, text, size) in layout.flatten() {
+                    let Vector3 { x, y, .. } = position;
+                    runs.push(PlacedRun {
+                        text,
+                        x: cursor + x,
+                        y,
+                        scale: size / font_size,
+                        color: style.color,
+                        bold: style.bold,
+                    });
+                }
+                cursor += layout.width + bold_extra;
+            } else {
+                runs.push(PlacedRun {
+                    text: span.text.clone(),
+                    x: cursor,
+                    y: 0.0,
+                    scale: style.scale,
+                    color: style.color,
+                    bold: style.bold,
+                });
+                cursor += measure(&span.text) * style.scale + bold_extra;
+            }
+        }
+        runs
+    }
+}
+
+/// `#rgb` or `#rrggbb`
+fn parse_color(value: &str) -> Result<Color, String> {
+    let hex = value.trim_start_matches('#');
+    let valid = hex.chars().all(|c| c.is_ascii_hexdigit());
+    match hex.len() {
+        6 if valid => Ok(Color::from_hex(hex)),
+        3 if valid => {
+            let doubled: String = hex.chars().flat_map(|c| [c, c]).collect();
+            Ok(Color::from_hex(&doubled))
+        }
+        _ => Err(format!("invalid color '{value}'")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_layout() {
+        let rich =
+            RichText::parse("a [color=#ff0][b]bold[/b] [size=2]big[/size][/color] \\$1 $x^2$")
+                .unwrap();
+        let texts: Vec<_> = rich.spans.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, ["a ", "bold", " ", "big", " $1 ", "x^2"]);
+        let yellow = Some(Color::from_hex("ffff00"));
+        assert_eq!(rich.spans[1].style.color, yellow);
+        assert!(rich.spans[1].style.bold);
+        assert!(!rich.spans[2].style.bold);
+        assert_eq!(rich.spans[3].style.scale, 2.0);
+        assert_eq!(rich.spans[3].style.color, yellow);
+        assert!(rich.spans[5].math);
+
+        assert!(RichText::parse("[b]open").is_err());
+        assert!(RichText::parse("[b]x[/color]").is_err());
+        assert!(RichText::parse("[color=red]x[/color]").is_err());
+        assert!(RichText::parse("$x").is_err());
+
+        // Every character advances 10px at scale 1
+        let runs = RichText::new()
+            .text("ab")
+            .sized("cd", 2.0)
+            .math("\\frac{1}{2}")
+            .layout(100.0, |text| 10.0 * text.chars().count() as f32);
+        assert_eq!(runs[1].x, 20.0);
+        // The numerator sits above the baseline at a smaller size
+        let numerator = runs.iter().find(|run| run.text == "1").unwrap();
+        assert!(numerator.x >= 60.0 && numerator.y < 0.0 && numerator.scale < 1.0);
+    }
+}
@@ -0,0 +1,162 @@
+//! Text shaping
+//!
+//! Turns a string into positioned glyphs with rustybuzz, so kerning pairs,
+//! ligatures and contextual forms (Arabic joining, Indic reordering) come
+//! out as the font intends. Lines mixing left-to-right and right-to-left
+//! text are split into runs by the Unicode bidi algorithm and shaped run by
+//! run, in visual order. Within a run, characters are shaped with the first
+//! font of a fallback chain that has them.
+
+use rustybuzz::{Direction, UnicodeBuffer};
+use unicode_bidi::BidiInfo;
+
+/// A glyph placed by the shaper
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapedGlyph {
+    /// Index of the font in the chain the glyph comes from
+    pub font: usize,
+    /// Glyph id in that font (ligatures have no single character)
+    pub glyph_id: u16,
+    /// Byte offset in the text of the first character the glyph came from
+    pub cluster: usize,
+    /// Where the glyph's origin goes, in pixels at the shaped size (y grows
+    /// down, like glyph bitmaps)
+    pub x: f32,
+    pub y: f32,
+    /// How far the pen moves after this glyph, in pixels
+    pub advance: f32,
+}
+
+/// A shaped line of text
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ShapedText {
+    /// Glyphs in visual (left-to-right drawing) order
+    pub glyphs: Vec<ShapedGlyph>,
+    /// Total advance, in pixels
+    pub width: f32,
+}
+
+/// Shape `text` at `font_size` pixels per em with the first of `faces`,
+/// falling back to the later ones for characters it lacks
+pub fn shape(faces: &[&rustybuzz::Face], text: &str, font_size: f32) -> ShapedText {
+    let mut shaped = ShapedText::default();
+    if faces.is_empty() {
+        return shaped;
+    }
+
+    let bidi = BidiInfo::new(text, None);
+    for paragraph in &bidi.paragraphs {
+        let (levels, runs) = bidi.visual_runs(paragraph, paragraph.range.clone());
+        for run in runs {
+            let direction = if levels[run.start].is_rtl() {
+                Direction::RightToLeft
+            } else {
+                Direction::LeftToRight
+            };
+            let mut pieces = font_runs(faces, &text[run.clone()]);
+            // Right-to-left runs are drawn from their end
+            if direction == Direction::RightToLeft {
+                pieces.reverse();
+            }
+
+            for (font, range) in pieces {
+                let face = faces[font];
+                let scale = font_size / face.units_per_em() as f32;
+                let start = run.start + range.start;
+                let mut buffer = UnicodeBuffer::new();
+                buffer.push_str(&text[start..run.start + range.end]);
+                buffer.set_direction(direction);
+
+                let output = rustybuzz::shape(face, &[], buffer);
+                for (info, position) in output.glyph_infos().iter().zip(output.glyph_positions()) {
+                    let advance = position.x_advance as f32 * scale;
+                    shaped.glyphs.push(ShapedGlyph {
+                        font,
+                        glyph_id: info.glyph_id as u16,
+                        cluster: start + info.cluster as usize,
+                        x: shaped.width + position.x_offset as f32 * scale,
+                        // Font units point up, glyph bitmaps down
+                        y: -(position.y_offset as f32) * scale,
+                        advance,
+                    });
+                    shaped.width += advance;
+                }
+            }
+        }
+    }
+    shaped
+}
+
+/// Split `text` into byte ranges that each use one font: the first of
+/// `faces` that has the character. Whitespace and characters no font has
+/// stay with the font before them, so they don't break up a run.
+fn font_runs(faces: &[&rustybuzz::Face], text: &str) -> Vec<(usize, std::ops::Range<usize>)> {
+    let mut runs: Vec<(usize, std::ops::Range<usize>)> = Vec::new();
+    for (offset, c) in text.char_indices() {
+        let end = offset + c.len_utf8();
+        let covering = faces.iter().position(|face| face.glyph_index(c).is_some());
+        match (runs.last_mut(), covering) {
+            (Some((current, range)), _)
+                if c.is_whitespace() && faces[*current].glyph_index(c).is_some() =>
+            {
+                range.end = end;
+            }
+            (Some((_, range)), None) => range.end = end,
+            (Some((current, range)), Some(font)) if *current == font => range.end = end,
+            (_, font) => runs.push((font.unwrap_or(0), offset..end)),
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::SystemFonts;
+
+    #[test]
+    fn test_shaping_kerns_and_orders_runs() {
+        // Shaping needs a real font; skip where the system font is missing
+        let Ok(data) = std::fs::read(SystemFonts::sans_serif()) else {
+            return;
+        };
+        let face = rustybuzz::Face::from_slice(&data, 0).unwrap();
+        let faces = [&face];
+
+        let unkerned: f32 = ["A", "V"]
+            .iter()
+            .map(|s| shape(&faces, s, 100.0).width)
+            .sum();
+        let pair = shape(&faces, "AV", 100.0);
+        assert_eq!(pair.glyphs.len(), 2);
+        assert!(pair.width < unkerned);
+        assert!((pair.glyphs[1].x - pair.glyphs[0].advance).abs() < 1e-3);
+
+        // The Hebrew run is drawn right to left: its last letter comes first
+        let mixed = shape(&faces, "ab \u{05D0}\u{05D1}", 100.0);
+        let clusters: Vec<usize> = mixed.glyphs.iter().map(|glyph| glyph.cluster).collect();
+        assert_eq!(clusters, [0, 1, 2, 5, 3]);
+    }
+
+    #[test]
+    fn test_shaping_falls_back_per_glyph() {
+        let (Ok(mono), Ok(sans)) = (
+            std::fs::read(SystemFonts::monospace()),
+            std::fs::read(SystemFonts::sans_serif()),
+        ) else {
+            return;
+        };
+        let mono = rustybuzz::Face::from_slice(&mono, 0).unwrap();
+        let sans = rustybuzz::Face::from_slice(&sans, 0).unwrap();
+        if mono.glyph_index('\u{222E}').is_some() {
+            return;
+        }
+
+        // The contour integral sign comes from the second font, and the
+        // space after it stays in the same run
+        let shaped = shape(&[&mono, &sans], "x \u{222E} y", 100.0);
+        let fonts: Vec<usize> = shaped.glyphs.iter().map(|glyph| glyph.font).collect();
+        assert_eq!(fonts, [0, 0, 1, 1, 0]);
+        assert!(shaped.glyphs.iter().all(|glyph| glyph.glyph_id != 0));
+    }
+}
@@ -0,0 +1,625 @@
+//! Golden-image tests: every renderable and effect is rendered and compared
+//! against `tests/golden/<name>.png`.
+//!
+//! Run with `DIOMANIM_UPDATE_GOLDENS=1 cargo test --test visual_regression`
+//! after an intended change to renderer output, and review the new PNGs.
+
+use diomanim::assets::AssetServer;
+use diomanim::core::{Color, TimeValue, Vector3};
+use diomanim::mobjects::{Magnifier, TracedPath};
+use diomanim::pipeline::{
+    render_frame, render_loop, save_png, DownsampleFilter, LoopMismatch, LoopSeam, NoProgress,
+    RenderConfig, Watermark, WatermarkAnchor,
+};
+use diomanim::render::{RendererDescriptor, ShapeRenderer};
+use diomanim::scene::{
+    BlendMode, ClipMask, Light, PostEffect, RenderTarget, Renderable, SceneGraph, ShaderMaterial,
+};
+use diomanim::testing::{GoldenSet, Snapshot, Tolerance};
+
+const SIZE: u32 = 96;
+
+/// Renderer for the tests, or `None` (test skipped) when no adapter exists at all
+fn renderer() -> Option<ShapeRenderer> {
+    match pollster::block_on(ShapeRenderer::with_descriptor(&RendererDescriptor::new(
+        SIZE, SIZE,
+    ))) {
+        Ok(renderer) => Some(renderer),
+        Err(err) => {
+            eprintln!("skipping visual regression tests: {err}");
+            None
+        }
+    }
+}
+
+fn goldens() -> GoldenSet {
+    GoldenSet::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden"))
+}
+
+/// Render each scene and check it against its golden, reporting all failures at once
+fn check_all(
+    renderer: &mut ShapeRenderer,
+    cases: Vec<(&str, SceneGraph, Tolerance)>,
+) -> Result<(), String> {
+    let config = RenderConfig::new(SIZE, SIZE, 30, 0.0).with_background(Color::WHITE);
+    let failures: Vec<String> = cases
+        .into_iter()
+        .filter_map(|(name, mut scene, tolerance)| {
+            scene.update_transforms();
+            Snapshot::render(renderer, &scene, &config)
+                .and_then(|snapshot| goldens().check(name, &snapshot, tolerance))
+                .err()
+                .map(|err| err.to_string())
+        })
+        .collect();
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures.join("\n"))
+    }
+}
+
+fn scene_with(build: impl FnOnce(&mut SceneGraph)) -> SceneGraph {
+    let mut scene = SceneGraph::new();
+    build(&mut scene);
+    scene
+}
+
+#[test]
+fn renderables_match_goldens() -> Result<(), String> {
+    let Some(mut renderer) = renderer() else {
+        return Ok(());
+    };
+
+    let mut cases = vec![
+        (
+            "circle",
+            scene_with(|scene| {
+                scene.add_circle("circle", 0.6, Color::RED);
+            }),
+            Tolerance::default(),
+        ),
+        (
+            "rectangle",
+            scene_with(|scene| {
+                scene
+                    .add_rectangle("rectangle", 1.2, 0.6, Color::BLUE)
+                    .rotate_z_degrees(30.0);
+            }),
+            Tolerance::default(),
+        ),
+        (
+            "line",
+            scene_with(|scene| {
+                scene.add_line(
+                    "line",
+                    Vector3::new(-0.7, -0.5, 0.0),
+                    Vector3::new(0.7, 0.5, 0.0),
+                    Color::GREEN,
+                    8.0,
+                );
+            }),
+            Tolerance::default(),
+        ),
+        (
+            "arrow",
+            scene_with(|scene| {
+                scene.add_arrow(
+                    "arrow",
+                    Vector3::new(-0.7, 0.0, 0.0),
+                    Vector3::new(0.7, 0.0, 0.0),
+                    Color::BLACK,
+                    6.0,
+                );
+            }),
+            Tolerance::default(),
+        ),
+        (
+            "polygon",
+            scene_with(|scene| {
+                scene.add_star("star", 5, 0.7, 0.3, Color::YELLOW);
+            }),
+            Tolerance::default(),
+        ),
+        (
+            "lit_circle",
+            scene_with(|scene| {
+                scene
+                    .add_circle("circle", 0.6, Color::WHITE)
+                    .shading(0.8, 0.4);
+                scene.add_light(Light::point(
+                    Vector3::new(0.5, 0.5, 1.0),
+                    Color::new(1.0, 0.8, 0.6),
+                    1.0,
+                ));
+            }),
+            Tolerance::default(),
+        ),
+    ];
+
+    // Glyphs come from a system font, so they can only be compared loosely
+    renderer.init_lighting();
+    if renderer.init_text_rendering(32.0).is_ok() {
+        cases.push((
+            "text",
+            scene_with(|scene| {
+                scene.add_text("text", "Hi", 32.0, Color::BLACK);
+            }),
+            Tolerance::loose(),
+        ));
+        cases.push((
+            "math",
+            scene_with(|scene| {
+                let id = scene.create_node("math".to_string());
+                scene
+                    .get_node_mut(id)
+                    .unwrap()
+                    .set_renderable(Renderable::Math {
+                        latex: "x^2".to_string(),
+                        font_size: 32.0,
+                        color: Color::BLACK,
+                    });
+            }),
+            Tolerance::loose(),
+        ));
+    }
+
+    check_all(&mut renderer, cases)
+}
+
+#[test]
+fn effects_match_goldens() -> Result<(), String> {
+    let Some(mut renderer) = renderer() else {
+        return Ok(());
+    };
+
+    let dots = |scene: &mut SceneGraph| {
+        scene.add_circle("left", 0.3, Color::RED).at(-0.4, 0.0, 0.0);
+        scene
+            .add_circle("right", 0.3, Color::BLUE)
+            .at(0.4, 0.0, 0.0);
+    };
+    let cases = vec![
+        (
+            "bloom",
+            scene_with(|scene| {
+                dots(scene);
+                scene.add_post_effect(PostEffect::bloom(0.5, 1.0));
+            }),
+            Tolerance::default(),
+        ),
+        (
+            "vignette",
+            scene_with(|scene| {
+                dots(scene);
+                scene.add_post_effect(PostEffect::vignette(0.8));
+            }),
+            Tolerance::default(),
+        ),
+        (
+            "blur",
+            scene_with(|scene| {
+                dots(scene);
+                scene.add_post_effect(PostEffect::blur(6.0));
+            }),
+            Tolerance::default(),
+        ),
+        (
+            "shadow",
+            scene_with(|scene| {
+                scene
+                    .add_square("card", 0.8, Color::WHITE)
+                    .shadow(0.1, -0.1, 4.0);
+            }),
+            Tolerance::default(),
+        ),
+        (
+            "glow",
+            scene_with(|scene| {
+                scene
+                    .add_circle("sun", 0.4, Color::YELLOW)
+                    .glow(Color::new(1.0, 0.5, 0.0), 8.0);
+            }),
+            Tolerance::default(),
+        ),
+        (
+            "clip",
+            scene_with(|scene| {
+                let window = scene
+                    .add_square("window", 1.0, Color::GREEN)
+                    .clip(ClipMask::Circle { radius: 0.5 })
+                    .build();
+                scene
+                    .add_rectangle("bar", 2.0, 0.3, Color::BLUE)
+                    .parent_to(window);
+            }),
+            Tolerance::default(),
+        ),
+        (
+            "blend_modes",
+            scene_with(|scene| {
+                scene.add_square("backdrop", 2.0, Color::new(0.5, 0.5, 0.5));
+                for (i, mode) in [
+                    BlendMode::Normal,
+                    BlendMode::Additive,
+                    BlendMode::Multiply,
+                    BlendMode::Screen,
+                ]
+                .into_iter()
+                .enumerate()
+                {
+                    let x = if i % 2 == 0 { -0.45 } else { 0.45 };
+                    let y = if i < 2 { 0.45 } else { -0.45 };
+                    scene
+                        .add_circle(format!("{mode:?}"), 0.35, Color::new(1.0, 0.6, 0.0))
+                        .at(x, y, 0.0)
+                        .opacity(0.8)
+                        .blend(mode);
+                }
+            }),
+            Tolerance::default(),
+        ),
+        (
+            "shader_gradient",
+            scene_with(|scene| {
+                scene
+                    .add_square("panel", 1.6, Color::WHITE)
+                    .shader(ShaderMaterial::gradient(Color::BLUE, Color::RED, 0.5));
+            }),
+            Tolerance::default(),
+        ),
+        (
+            "shader_scan_lines",
+            scene_with(|scene| {
+                scene
+                    .add_circle("screen", 0.8, Color::GREEN)
+                    .shader(ShaderMaterial::scan_lines(0.2, 0.6, 0.0));
+            }),
+            Tolerance::default(),
+        ),
+        (
+            "shader_noise",
+            scene_with(|scene| {
+                scene
+                    .add_square("cloud", 1.6, Color::new(0.3, 0.5, 0.9))
+                    .shader(ShaderMaterial::noise(6.0, 0.8, 0.0));
+            }),
+            Tolerance::default(),
+        ),
+        (
+            "render_target",
+            scene_with(|scene| {
+                // The target's circle only shows up in the magnified inset
+                let inset = scene
+                    .add_render_target("inset", 64, 64)
+                    .target_camera(Vector3::zero(), 2.0)
+                    .target_background(Color::new(0.8, 0.8, 0.8))
+                    .at(-0.5, 0.0, 0.0)
+                    .build();
+                scene
+                    .add_circle("planet", 0.3, Color::RED)
+                    .at(0.2, 0.0, 0.0)
+                    .parent_to(inset);
+                scene
+                    .add_square("frame", 0.4, Color::BLUE)
+                    .at(-0.5, -0.5, 0.0);
+                scene
+                    .add_image("view", RenderTarget::handle_for("inset"), 0.8, 0.8)
+                    .at(0.4, 0.4, 0.0);
+            }),
+            Tolerance::default(),
+        ),
+        (
+            "traced_path",
+            scene_with(|scene| {
+                // A dot that has swung along an arc, leaving a fading trail
+                let dot = scene.add_circle("dot", 0.06, Color::BLUE).build();
+                scene.add_traced_path(
+                    "trail",
+                    TracedPath::new(dot).with_tail(0.8),
+                    Color::BLUE,
+                    6.0,
+                );
+                for frame in 0..=30 {
+                    let angle = frame as f32 / 30.0 * std::f32::consts::PI;
+                    if let Some(node) = scene.get_node_mut(dot) {
+                        node._local_transform.position =
+                            Vector3::new(-0.7 * angle.cos(), 0.7 * angle.sin() - 0.3, 0.0);
+                    }
+                    scene.update_transforms();
+                    scene.update_animations(TimeValue::new(1.0 / 30.0));
+                }
+            }),
+            Tolerance::default(),
+        ),
+        (
+            "magnifier",
+            scene_with(|scene| {
+                // Small dots around the focus appear four times larger
+                scene
+                    .add_circle("dot", 0.04, Color::RED)
+                    .at(-0.45, -0.4, 0.0);
+                scene
+                    .add_square("tick", 0.05, Color::BLUE)
+                    .at(-0.35, -0.4, 0.0);
+                Magnifier::new(0.35, 4.0)
+                    .with_border(Color::BLACK, 0.03)
+                    .with_background(Color::new(0.9, 0.9, 0.6))
+                    .add_to_scene(
+                        scene,
+                        "lens",
+                        Vector3::new(-0.4, -0.4, 0.0),
+                        Vector3::new(0.4, 0.4, 0.0),
+                    );
+            }),
+            Tolerance::default(),
+        ),
+    ];
+
+    check_all(&mut renderer, cases)
+}
+
+#[test]
+fn assets_match_goldens() -> Result<(), Box<dyn std::error::Error>> {
+    let Some(mut renderer) = renderer() else {
+        return Ok(());
+    };
+
+    // A 2x2 checker, stretched with linear filtering
+    let dir = std::env::temp_dir().join(format!("diomanim_golden_{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let checker = dir.join("checker.png");
+    #[rustfmt::skip]
+    save_png(&checker, 2, 2, &[
+        255, 0, 0, 255,   0, 0, 255, 255,
+        0, 0, 255, 255,   255, 0, 0, 255,
+    ])?;
+
+    let assets = AssetServer::new();
+    let image = assets.load(&checker)?;
+    let svg = assets.load_bytes(
+        "shapes.svg",
+        br##"<svg viewBox="0 0 40 20">
+               <rect width="20" height="20" fill="#00aa00"/>
+               <circle cx="30" cy="10" r="8" fill="#ff8800"/>
+             </svg>"##
+            .to_vec(),
+    )?;
+    std::fs::remove_dir_all(&dir).ok();
+    renderer.set_asset_server(assets);
+
+    let cases = vec![
+        (
+            "image",
+            scene_with(|scene| {
+                // Two nodes share one texture upload
+                scene.add_image("left", image, 0.8, 0.8).at(-0.45, 0.0, 0.0);
+                scene.add_image("right", image, 0.8, 0.8).at(0.45, 0.0, 0.0);
+            }),
+            Tolerance::default(),
+        ),
+        (
+            "svg",
+            scene_with(|scene| {
+                scene.add_svg("shapes", svg, 1.6, 0.8);
+            }),
+            Tolerance::default(),
+        ),
+    ];
+    Ok(check_all(&mut renderer, cases)?)
+}
+
+#[test]
+fn large_scene_matches_golden() -> Result<(), String> {
+    let Some(mut renderer) = renderer() else {
+        return Ok(());
+    };
+
+    // More draws than the renderer's initial transform slots
+    const SIDE: usize = 48;
+    let scene = scene_with(|scene| {
+        let cell = 2.0 / SIDE as f32;
+        for row in 0..SIDE {
+            for column in 0..SIDE {
+                let shade = (row + column) as f32 / (2 * SIDE) as f32;
+                scene
+                    .add_square(
+                        format!("cell{row}_{column}"),
+                        cell * 0.8,
+                        Color::new(shade, 0.2, 1.0 - shade),
+                    )
+                    .at(
+                        (column as f32 + 0.5) * cell - 1.0,
+                        (row as f32 + 0.5) * cell - 1.0,
+                        0.0,
+                    );
+            }
+        }
+    });
+    check_all(
+        &mut renderer,
+        vec![("large_scene", scene, Tolerance::default())],
+    )?;
+    assert!(renderer.transform_capacity() as usize >= SIDE * SIDE);
+    Ok(())
+}
+
+#[test]
+fn tiled_render_matches_golden() -> Result<(), Box<dyn std::error::Error>> {
+    let Some(mut renderer) = renderer() else {
+        return Ok(());
+    };
+
+    // Tiles that don't divide the frame evenly, stitched back together
+    let mut scene = scene_with(|scene| {
+        scene.add_circle("circle", 0.6, Color::RED);
+    });
+    scene.update_transforms();
+    let config = RenderConfig::new(SIZE, SIZE, 30, 0.0)
+        .with_background(Color::WHITE)
+        .with_tile_size(40);
+    let snapshot = Snapshot::render(&mut renderer, &scene, &config)?;
+    goldens().check("circle", &snapshot, Tolerance::default())?;
+    Ok(())
+}
+
+#[test]
+fn supersampled_render_matches_golden() -> Result<(), Box<dyn std::error::Error>> {
+    let Some(mut renderer) = renderer() else {
+        return Ok(());
+    };
+
+    // Only the anti-aliased edge may differ from the MSAA golden
+    let mut scene = scene_with(|scene| {
+        scene.add_circle("circle", 0.6, Color::RED);
+    });
+    scene.update_transforms();
+    for filter in [DownsampleFilter::Box, DownsampleFilter::Tent] {
+        let config = RenderConfig::new(SIZE, SIZE, 30, 0.0)
+            .with_background(Color::WHITE)
+            .with_supersampling(2, filter);
+        let snapshot = Snapshot::render(&mut renderer, &scene, &config)?;
+        goldens().check("circle", &snapshot, Tolerance::loose())?;
+    }
+    Ok(())
+}
+
+#[test]
+fn loop_seam_is_validated() -> Result<(), Box<dyn std::error::Error>> {
+    let Some(mut renderer) = renderer() else {
+        return Ok(());
+    };
+
+    let frames_dir = std::env::temp_dir().join(format!("diomanim_loop_{}", std::process::id()));
+    let config = RenderConfig::new(SIZE, SIZE, 10, 1.0)
+        .with_background(Color::WHITE)
+        .with_frames_dir(&frames_dir);
+    let seam = LoopSeam::Validate(Tolerance::default());
+
+    // A still scene loops; one that moves away doesn't
+    let mut still = scene_with(|scene| {
+        scene.add_circle("circle", 0.3, Color::RED);
+    });
+    render_loop(&mut renderer, &mut still, &config, seam, &mut NoProgress)?;
+    let mut moving = scene_with(|scene| {
+        scene
+            .add_circle("circle", 0.3, Color::RED)
+            .move_to(0.0, Vector3::new(0.6, 0.0, 0.0), 1.0);
+    });
+    let error = render_loop(&mut renderer, &mut moving, &config, seam, &mut NoProgress)
+        .expect_err("a moving scene doesn't loop");
+    assert!(error.downcast_ref::<LoopMismatch>().is_some());
+
+    std::fs::remove_dir_all(&frames_dir)?;
+    Ok(())
+}
+
+/// Bounds of the dark pixels of an empty, aspect-framed frame `width`
+/// pixels wide with `watermark`, as (left, top, right, bottom)
+fn dark_box(
+    renderer: &mut ShapeRenderer,
+    watermark: Watermark,
+    width: u32,
+) -> Result<(u32, u32, u32, u32), Box<dyn std::error::Error>> {
+    let config = RenderConfig::new(width, SIZE, 30, 0.0)
+        .with_background(Color::WHITE)
+        .with_aspect_framing()
+        .with_watermark(watermark);
+    dark_bounds(&render_frame(renderer, &SceneGraph::new(), &config)?, width)
+}
+
+/// Bounding box of the dark pixels in an RGBA frame `width` pixels wide
+fn dark_bounds(
+    pixels: &[u8],
+    width: u32,
+) -> Result<(u32, u32, u32, u32), Box<dyn std::error::Error>> {
+    let dark: Vec<(u32, u32)> = (0..pixels.len() as u32 / 4)
+        .filter(|&i| pixels[i as usize * 4] < 128)
+        .map(|i| (i % width, i / width))
+        .collect();
+    let left = dark.iter().map(|p| p.0).min().ok_or("nothing dark drawn")?;
+    let top = dark.iter().map(|p| p.1).min().unwrap_or(0);
+    let right = dark.iter().map(|p| p.0).max().unwrap_or(0);
+    let bottom = dark.iter().map(|p| p.1).max().unwrap_or(0);
+    Ok((left, top, right, bottom))
+}
+
+#[test]
+fn watermark_is_pinned_to_its_corner() -> Result<(), Box<dyn std::error::Error>> {
+    let Some(mut renderer) = renderer() else {
+        return Ok(());
+    };
+
+    let dir = std::env::temp_dir().join(format!("diomanim_watermark_{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let logo = dir.join("logo.png");
+    save_png(&logo, 1, 1, &[0, 0, 255, 255])?;
+    let assets = AssetServer::new();
+    let image = assets.load(&logo)?;
+    std::fs::remove_dir_all(&dir).ok();
+    renderer.set_asset_server(assets);
+
+    // A quarter-frame logo 8 pixels from the top left
+    let logo = Watermark::image(image, 0.5, 0.5)
+        .with_anchor(WatermarkAnchor::TopLeft)
+        .with_margin(8);
+    assert_eq!(dark_box(&mut renderer, logo.clone(), SIZE)?, (8, 8, 31, 31));
+    // Sized against the frame, whatever the scene's framing
+    assert_eq!(dark_box(&mut renderer, logo, 2 * SIZE)?, (8, 8, 55, 31));
+
+    if renderer.init_text_rendering(32.0).is_ok() {
+        let text = Watermark::text("DRAFT", 8.0, Color::BLACK).with_margin(4);
+        let (left, top, right, bottom) = dark_box(&mut renderer, text, SIZE)?;
+        assert!(left > 4 && top > 4, "text escaped its corner");
+        assert!(
+            (SIZE - 8..SIZE - 4).contains(&right),
+            "right edge at {right}"
+        );
+        assert!(
+            (SIZE - 8..SIZE - 4).contains(&bottom),
+            "bottom edge at {bottom}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn aspect_framing_keeps_circles_round() -> Result<(), Box<dyn std::error::Error>> {
+    let Some(mut renderer) = renderer() else {
+        return Ok(());
+    };
+
+    let mut scene = SceneGraph::new();
+    scene.add_circle("dot", 0.5, Color::BLACK);
+    scene.update_transforms();
+    let config = RenderConfig::new(2 * SIZE, SIZE, 30, 0.0)
+        .with_background(Color::WHITE)
+        .with_aspect_framing();
+    let (left, top, right, bottom) =
+        dark_bounds(&render_frame(&mut renderer, &scene, &config)?, 2 * SIZE)?;
+    // Half the frame's height across, both ways, in the middle
+    let (width, height) = (right + 1 - left, bottom + 1 - top);
+    assert!(width.abs_diff(SIZE / 2) <= 1, "{width} pixels wide");
+    assert!(height.abs_diff(SIZE / 2) <= 1, "{height} pixels tall");
+    assert!((left + right + 1).abs_diff(2 * SIZE) <= 1);
+    Ok(())
+}
+
+#[test]
+fn create_sweeps_circles_along_their_arc() -> Result<(), Box<dyn std::error::Error>> {
+    let Some(mut renderer) = renderer() else {
+        return Ok(());
+    };
+
+    // Halfway through Create a circle is its upper half
+    let mut scene = SceneGraph::new();
+    scene.add_circle("dot", 0.5, Color::BLACK).create(0.0, 2.0);
+    scene.update_animations(TimeValue::new(1.0));
+    let config = RenderConfig::new(SIZE, SIZE, 30, 0.0).with_background(Color::WHITE);
+    let (left, top, right, bottom) =
+        dark_bounds(&render_frame(&mut renderer, &scene, &config)?, SIZE)?;
+    assert!((right + 1 - left).abs_diff(SIZE / 2) <= 1);
+    assert!(top.abs_diff(SIZE / 4) <= 1, "top at {top}");
+    assert!(bottom.abs_diff(SIZE / 2) <= 1, "bottom at {bottom}");
+    Ok(())
+}